@@ -0,0 +1,220 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reading and writing tar and zip archives.
+//!
+//! The `tar` module reads and writes ustar archives, using GNU extensions
+//! for names which don't fit in a ustar header and reading POSIX extended
+//! headers. Tar archives are read and written as streams, so they can be
+//! compressed by wrapping the reader or writer in one of the gzip adaptors in
+//! `flate`.
+//!
+//! The `zip` module reads and writes zip files whose entries are stored or
+//! compressed with deflate. Reading a zip file requires seeking, since its
+//! directory is at the end.
+//!
+//! In both formats the entries of an archive are `Reader`s, and metadata for
+//! new entries can be taken from `io::fs`. Paths in archives always use `/`
+//! to separate their components.
+//!
+//! # Example
+//!
+//! ```rust
+//! extern crate archive;
+//! extern crate flate;
+//!
+//! use std::io::File;
+//! use archive::tar;
+//! use flate::{GzEncoder, Default};
+//!
+//! fn package(src: &Path, dst: &Path) -> std::io::IoResult<()> {
+//!     let gz = GzEncoder::new(try!(File::create(dst)), Default);
+//!     let mut builder = tar::Builder::new(gz);
+//!     try!(builder.append_dir_all(&Path::new("rust-nightly"), src));
+//!     let gz = try!(builder.finish());
+//!     try!(gz.finish());
+//!     Ok(())
+//! }
+//! # fn main() {}
+//! ```
+
+#![crate_id = "archive#0.11-pre"]
+#![crate_type = "rlib"]
+#![crate_type = "dylib"]
+#![license = "MIT/ASL2"]
+#![doc(html_logo_url = "http://www.rust-lang.org/logos/rust-logo-128x128-blk-v2.png",
+       html_favicon_url = "http://www.rust-lang.org/favicon.ico",
+       html_root_url = "http://static.rust-lang.org/doc/master")]
+#![deny(deprecated_owned_vector)]
+
+extern crate flate;
+extern crate time;
+
+use std::io::{IoResult, IoError, InvalidInput, TypeDirectory, TypeSymlink, UserDir};
+use std::io::fs;
+
+pub mod tar;
+pub mod zip;
+
+fn invalid(desc: &'static str) -> IoError {
+    IoError { kind: InvalidInput, desc: desc, detail: None }
+}
+
+/// The name of `path` in an archive, with `/` separating its components.
+fn archive_name(path: &Path) -> Vec<u8> {
+    let mut name = Vec::from_slice(path.as_vec());
+    if cfg!(windows) {
+        for b in name.mut_iter() {
+            if *b == '\\' as u8 {
+                *b = '/' as u8;
+            }
+        }
+    }
+    name
+}
+
+/// Where the entry of an archive named `name` should be extracted to under
+/// `dst`, or `None` if the name is absolute or refers to a parent directory,
+/// and so could be used to write outside of `dst`.
+fn extract_path(dst: &Path, name: &[u8]) -> Option<Path> {
+    if name.starts_with(bytes!("/")) {
+        return None;
+    }
+    let mut path = dst.clone();
+    for component in name.split(|&b| b == '/' as u8) {
+        match component {
+            b if b.is_empty() || b == bytes!(".") => {}
+            b if b == bytes!("..") => return None,
+            b if cfg!(windows) && b.iter().any(|&c| c == '\\' as u8 || c == ':' as u8) => {
+                return None
+            }
+            b => path.push(b),
+        }
+    }
+    Some(path)
+}
+
+/// Whether a symlink named `name` in an archive, pointing to `target`, stays
+/// within the directory the archive is extracted to. Only the names are
+/// looked at: the target is followed from the directory containing the link.
+fn link_in_bounds(name: &[u8], target: &[u8]) -> bool {
+    if target.starts_with(bytes!("/")) {
+        return false;
+    }
+    let mut depth = -1i;
+    for component in name.split(|&b| b == '/' as u8) {
+        if !component.is_empty() && component != bytes!(".") {
+            depth += 1;
+        }
+    }
+    for component in target.split(|&b| b == '/' as u8) {
+        match component {
+            b if b.is_empty() || b == bytes!(".") => {}
+            b if b == bytes!("..") => {
+                depth -= 1;
+                if depth < 0 {
+                    return false;
+                }
+            }
+            b if cfg!(windows) && b.iter().any(|&c| c == '\\' as u8 || c == ':' as u8) => {
+                return false
+            }
+            _ => depth += 1,
+        }
+    }
+    true
+}
+
+/// Make sure the directories leading from `dst` to `path` exist, creating them
+/// if `create` is set, and that none of them is a symlink, which could lead
+/// somewhere outside of `dst`.
+fn check_parents(dst: &Path, path: &Path, create: bool) -> IoResult<()> {
+    let mut parents = Vec::new();
+    let mut dir = path.dir_path();
+    while dir != *dst {
+        let up = dir.dir_path();
+        if up == dir {
+            break;
+        }
+        parents.push(dir);
+        dir = up;
+    }
+    for dir in parents.iter().rev() {
+        match fs::lstat(dir) {
+            Ok(ref stat) if stat.kind == TypeSymlink => {
+                return Err(invalid("archive entry path goes through a symlink"))
+            }
+            Ok(..) => {}
+            Err(..) if create => try!(fs::mkdir(dir, UserDir)),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// Get `path`, somewhere under `dst`, ready for an entry to be extracted to
+/// it: create its parent directories and remove whatever is already there
+/// unless it is a directory, so that writing to `path` can't follow a symlink.
+fn prepare_path(dst: &Path, path: &Path) -> IoResult<()> {
+    if path == dst {
+        return Ok(());
+    }
+    try!(check_parents(dst, path, true));
+    match fs::lstat(path) {
+        Ok(ref stat) if stat.kind != TypeDirectory => fs::unlink(path),
+        _ => Ok(()),
+    }
+}
+
+/// All the files and directories under `dir`, relative to it, sorted so that
+/// archives of the same tree are the same and directories come before their
+/// contents.
+fn walk_sorted(dir: &Path) -> IoResult<Vec<Path>> {
+    let mut entries = try!(fs::readdir(dir));
+    entries.sort_by(|a, b| a.as_vec().cmp(&b.as_vec()));
+    let mut paths = Vec::new();
+    for path in entries.move_iter() {
+        let stat = try!(fs::lstat(&path));
+        let relative = path.path_relative_from(dir).unwrap();
+        if stat.kind == TypeDirectory {
+            let contents = try!(walk_sorted(&path));
+            paths.push(relative.clone());
+            paths.extend(contents.move_iter().map(|p| relative.join(p)));
+        } else {
+            paths.push(relative);
+        }
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{extract_path, link_in_bounds};
+
+    #[test]
+    fn test_extract_path() {
+        let dst = Path::new("dst");
+        assert!(extract_path(&dst, bytes!("a/b.txt")) == Some(Path::new("dst/a/b.txt")));
+        assert!(extract_path(&dst, bytes!("./a//b/")) == Some(Path::new("dst/a/b")));
+        assert!(extract_path(&dst, bytes!("/etc/passwd")).is_none());
+        assert!(extract_path(&dst, bytes!("a/../../b")).is_none());
+        assert!(extract_path(&dst, bytes!("..")).is_none());
+    }
+
+    #[test]
+    fn test_link_in_bounds() {
+        assert!(link_in_bounds(bytes!("a"), bytes!("b")));
+        assert!(link_in_bounds(bytes!("a/b/c"), bytes!("../d/./e")));
+        assert!(link_in_bounds(bytes!("a/b"), bytes!("../b/..")));
+        assert!(!link_in_bounds(bytes!("a"), bytes!("/etc")));
+        assert!(!link_in_bounds(bytes!("a"), bytes!("..")));
+        assert!(!link_in_bounds(bytes!("./a/b"), bytes!("c/../../..")));
+    }
+}
@@ -0,0 +1,999 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+
+Reading and writing tar archives
+
+A tar archive is a sequence of entries, each a 512 byte header followed by the
+entry's data padded to a multiple of 512 bytes, ending with two blocks of
+zeros.
+
+Archives are written in the ustar format. A path too long for a ustar header
+is written as a GNU long name entry, which GNU tar, bsdtar and this module all
+read. When reading, GNU long names and the `path`, `linkpath`, `size` and
+`mtime` records of POSIX extended headers are understood too.
+
+# Example
+
+```rust
+use std::io::{MemReader, MemWriter};
+use archive::tar::{Archive, Builder, Header, Regular};
+
+let mut header = Header::new(Path::new("hello.txt"), Regular);
+header.size = 12;
+let mut builder = Builder::new(MemWriter::new());
+builder.append(&header, &mut MemReader::new(Vec::from_slice(bytes!("hello world\n")))).unwrap();
+let tarball = builder.finish().unwrap().unwrap();
+
+let mut archive = Archive::new(MemReader::new(tarball));
+let mut entry = archive.next_entry().unwrap().unwrap();
+assert!(entry.header().path == Path::new("hello.txt"));
+assert_eq!(entry.read_to_end().unwrap().as_slice(), bytes!("hello world\n"));
+```
+
+*/
+
+use std::cmp;
+use std::io;
+use std::io::{IoResult, EndOfFile, FilePermission, FileStat};
+use std::io::{TypeFile, TypeDirectory, TypeSymlink, TypeNamedPipe, TypeBlockSpecial};
+use std::io::{TypeUnknown, UserDir, AllPermissions};
+use std::io::fs;
+use std::io::fs::File;
+use std::str;
+
+use {invalid, archive_name, extract_path, walk_sorted};
+use {link_in_bounds, check_parents, prepare_path};
+
+static BLOCK_SIZE: u64 = 512;
+
+// The name GNU tar gives its long name and long link entries
+static LONG_LINK: &'static [u8] = bytes!("././@LongLink");
+
+/// The kind of file an entry of a tar archive is.
+#[deriving(Clone, Eq, Show)]
+pub enum EntryType {
+    /// A regular file
+    Regular,
+    /// A hard link to an earlier entry, named by the header's `link`
+    HardLink,
+    /// A symbolic link to the header's `link`
+    Symlink,
+    /// A character device
+    CharDevice,
+    /// A block device
+    BlockDevice,
+    /// A directory
+    Directory,
+    /// A named pipe
+    Fifo,
+    /// Some other kind of entry, by its type flag
+    Other(u8),
+}
+
+impl EntryType {
+    fn from_flag(flag: u8) -> EntryType {
+        match flag as char {
+            '0' | '\0' | '7' => Regular,
+            '1' => HardLink,
+            '2' => Symlink,
+            '3' => CharDevice,
+            '4' => BlockDevice,
+            '5' => Directory,
+            '6' => Fifo,
+            _ => Other(flag),
+        }
+    }
+
+    fn flag(&self) -> u8 {
+        match *self {
+            Regular => '0' as u8,
+            HardLink => '1' as u8,
+            Symlink => '2' as u8,
+            CharDevice => '3' as u8,
+            BlockDevice => '4' as u8,
+            Directory => '5' as u8,
+            Fifo => '6' as u8,
+            Other(flag) => flag,
+        }
+    }
+}
+
+/// The metadata of an entry of a tar archive.
+#[deriving(Clone)]
+pub struct Header {
+    /// The path of the entry in the archive
+    pub path: Path,
+    /// The kind of file the entry is
+    pub kind: EntryType,
+    /// The permission bits of the file
+    pub perm: FilePermission,
+    /// The user id of the file's owner
+    pub uid: u64,
+    /// The group id of the file's owner
+    pub gid: u64,
+    /// The size of the entry's data, which is 0 unless it's a regular file
+    pub size: u64,
+    /// When the file was last modified, in seconds since the Unix epoch
+    pub mtime: u64,
+    /// The target of a link
+    pub link: Option<Path>,
+}
+
+impl Header {
+    /// Create a header for an empty entry owned by root, modified at the
+    /// epoch, with the usual permissions of a file or directory.
+    pub fn new(path: Path, kind: EntryType) -> Header {
+        Header {
+            path: path,
+            perm: if kind == Directory { 0o755 } else { 0o644 },
+            kind: kind,
+            uid: 0,
+            gid: 0,
+            size: 0,
+            mtime: 0,
+            link: None,
+        }
+    }
+
+    /// Create a header for the file described by `stat`, to be stored in the
+    /// archive as `path`. The `link` of a symlink isn't filled in.
+    pub fn from_stat(path: Path, stat: &FileStat) -> Header {
+        let kind = match stat.kind {
+            TypeFile | TypeUnknown => Regular,
+            TypeDirectory => Directory,
+            TypeSymlink => Symlink,
+            TypeNamedPipe => Fifo,
+            TypeBlockSpecial => BlockDevice,
+        };
+        Header {
+            path: path,
+            kind: kind,
+            perm: stat.perm & AllPermissions,
+            uid: stat.unstable.uid,
+            gid: stat.unstable.gid,
+            size: if kind == Regular { stat.size } else { 0 },
+            mtime: stat.modified / 1000,
+            link: None,
+        }
+    }
+}
+
+// The bytes of a NUL padded field.
+fn field<'a>(bytes: &'a [u8]) -> &'a [u8] {
+    match bytes.iter().position(|&b| b == 0) {
+        Some(i) => bytes.slice_to(i),
+        None => bytes,
+    }
+}
+
+// Parse a number, which is either octal digits optionally surrounded by
+// spaces and NULs, or GNU's base-256 encoding for numbers too large for that,
+// flagged by the high bit of the first byte.
+fn parse_number(bytes: &[u8]) -> IoResult<u64> {
+    if bytes.len() > 0 && bytes[0] & 0x80 != 0 {
+        let mut n = (bytes[0] & 0x7f) as u64;
+        for &b in bytes.slice_from(1).iter() {
+            n = (n << 8) | b as u64;
+        }
+        return Ok(n);
+    }
+    let mut n = 0;
+    let digits = bytes.iter().skip_while(|&&b| b == ' ' as u8 || b == 0)
+                             .take_while(|&&b| b != ' ' as u8 && b != 0);
+    for &b in digits {
+        if b < '0' as u8 || b > '7' as u8 {
+            return Err(invalid("invalid number in tar header"));
+        }
+        n = (n << 3) | (b - '0' as u8) as u64;
+    }
+    Ok(n)
+}
+
+// Write `n` as octal digits followed by a NUL, or in base-256 if it doesn't
+// fit.
+fn write_number(field: &mut [u8], mut n: u64) {
+    let digits = field.len() - 1;
+    if n < 1 << (3 * digits) {
+        for i in range(0, digits).rev() {
+            field[i] = '0' as u8 + (n & 7) as u8;
+            n >>= 3;
+        }
+        field[digits] = 0;
+    } else {
+        for i in range(1, field.len()).rev() {
+            field[i] = n as u8;
+            n >>= 8;
+        }
+        field[0] = 0x80;
+    }
+}
+
+fn checksum(block: &[u8]) -> (u64, u64) {
+    // The checksum field is summed as if it were spaces. Some old tars summed
+    // the bytes as signed chars.
+    let mut unsigned = 0u64;
+    let mut signed = 0i64;
+    for (i, &b) in block.iter().enumerate() {
+        let b = if i >= 148 && i < 156 { ' ' as u8 } else { b };
+        unsigned += b as u64;
+        signed += b as i8 as i64;
+    }
+    (unsigned, signed as u64)
+}
+
+// The number of padding bytes after `size` bytes of data.
+fn padding(size: u64) -> u64 {
+    (BLOCK_SIZE - size % BLOCK_SIZE) % BLOCK_SIZE
+}
+
+/// A `Reader` of the entries of a tar archive.
+pub struct Archive<R> {
+    inner: R,
+    // What's left of the current entry's data, and the padding after it
+    remaining: u64,
+    padding: u64,
+    done: bool,
+}
+
+/// An entry of a tar archive, which is a `Reader` of its data.
+pub struct Entry<'a, R> {
+    archive: &'a mut Archive<R>,
+    header: Header,
+}
+
+// The fields of extended headers which override the ustar header
+struct Extensions {
+    path: Option<Vec<u8>>,
+    link: Option<Vec<u8>>,
+    size: Option<u64>,
+    mtime: Option<u64>,
+}
+
+impl<R: Reader> Archive<R> {
+    /// Create an archive reading from `r`.
+    pub fn new(r: R) -> Archive<R> {
+        Archive { inner: r, remaining: 0, padding: 0, done: false }
+    }
+
+    /// Return the underlying reader.
+    pub fn unwrap(self) -> R {
+        self.inner
+    }
+
+    // Read exactly `buf.len()` bytes, or return `Ok(false)` if the archive
+    // ends first without anything having been read.
+    fn read_exactly(&mut self, buf: &mut [u8]) -> IoResult<bool> {
+        let mut pos = 0;
+        while pos < buf.len() {
+            match self.inner.read(buf.mut_slice_from(pos)) {
+                Ok(n) => pos += n,
+                Err(ref e) if e.kind == EndOfFile && pos == 0 => return Ok(false),
+                Err(ref e) if e.kind == EndOfFile => {
+                    return Err(invalid("truncated tar archive"))
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(true)
+    }
+
+    fn skip(&mut self, mut n: u64) -> IoResult<()> {
+        let mut buf = [0u8, ..4096];
+        while n > 0 {
+            let len = cmp::min(n, buf.len() as u64) as uint;
+            if !try!(self.read_exactly(buf.mut_slice_to(len))) {
+                return Err(invalid("truncated tar archive"));
+            }
+            n -= len as u64;
+        }
+        Ok(())
+    }
+
+    // Read the data of an extended header entry.
+    fn read_data(&mut self, size: u64) -> IoResult<Vec<u8>> {
+        if size > 1 << 20 {
+            return Err(invalid("tar extended header is too long"));
+        }
+        let mut data = Vec::from_elem(size as uint, 0u8);
+        if !try!(self.read_exactly(data.as_mut_slice())) && size > 0 {
+            return Err(invalid("truncated tar archive"));
+        }
+        try!(self.skip(padding(size)));
+        Ok(data)
+    }
+
+    /// Read the header of the next entry, returning `None` at the end of the
+    /// archive. Whatever wasn't read of the previous entry is skipped.
+    pub fn next_entry<'a>(&'a mut self) -> IoResult<Option<Entry<'a, R>>> {
+        let skip = self.remaining + self.padding;
+        self.remaining = 0;
+        self.padding = 0;
+        try!(self.skip(skip));
+
+        let mut ext = Extensions { path: None, link: None, size: None, mtime: None };
+        let mut block = [0u8, ..512];
+        loop {
+            if self.done || !try!(self.read_exactly(block)) {
+                self.done = true;
+                return Ok(None);
+            }
+            if block.iter().all(|&b| b == 0) {
+                self.done = true;
+                return Ok(None);
+            }
+
+            let (unsigned, signed) = checksum(block);
+            let expected = try!(parse_number(block.slice(148, 156)));
+            if expected != unsigned && expected != signed {
+                return Err(invalid("tar header checksum mismatch"));
+            }
+            let size = try!(parse_number(block.slice(124, 136)));
+            match block[156] as char {
+                'L' => {
+                    let data = try!(self.read_data(size));
+                    ext.path = Some(Vec::from_slice(field(data.as_slice())));
+                }
+                'K' => {
+                    let data = try!(self.read_data(size));
+                    ext.link = Some(Vec::from_slice(field(data.as_slice())));
+                }
+                'x' => {
+                    let data = try!(self.read_data(size));
+                    try!(parse_pax(data.as_slice(), &mut ext));
+                }
+                'g' => {
+                    // global extended headers apply to the archive, not the
+                    // next entry
+                    try!(self.skip(size + padding(size)));
+                }
+                _ => break,
+            }
+        }
+
+        let header = try!(parse_header(block, ext));
+        self.remaining = header.size;
+        self.padding = padding(header.size);
+        Ok(Some(Entry { archive: self, header: header }))
+    }
+
+    /// Extract the rest of the archive into the directory `dst`, setting the
+    /// permissions and modification times of the files created.
+    ///
+    /// Entries whose paths are absolute, contain `..` or lead through a
+    /// symlink are refused, so an archive can't write outside of `dst`.
+    /// Symlinks pointing outside of `dst`, devices and pipes are skipped.
+    pub fn unpack(&mut self, dst: &Path) -> IoResult<()> {
+        // Directories are given their permissions last, in case they don't
+        // allow writing their contents
+        let mut dirs = Vec::new();
+        loop {
+            let mut entry = match try!(self.next_entry()) {
+                Some(entry) => entry,
+                None => break,
+            };
+            let header = entry.header.clone();
+            let name = archive_name(&header.path);
+            let path = match extract_path(dst, name.as_slice()) {
+                Some(path) => path,
+                None => return Err(invalid("tar entry path is outside the destination")),
+            };
+            match header.kind {
+                Symlink => match header.link {
+                    Some(ref link) if !link_in_bounds(name.as_slice(),
+                                                      archive_name(link).as_slice()) => continue,
+                    _ => {}
+                },
+                Directory | Regular | HardLink => {}
+                _ => continue,
+            }
+            try!(prepare_path(dst, &path));
+            match header.kind {
+                Directory => {
+                    if !path.is_dir() {
+                        try!(fs::mkdir(&path, UserDir));
+                    }
+                    dirs.push((path, header));
+                    continue;
+                }
+                Regular => {
+                    let mut file = try!(File::create(&path));
+                    try!(io::util::copy(&mut entry, &mut file));
+                }
+                Symlink | HardLink => {
+                    let link = match header.link {
+                        Some(ref link) => link,
+                        None => return Err(invalid("tar link entry has no target")),
+                    };
+                    if header.kind == Symlink {
+                        try!(fs::symlink(link, &path));
+                    } else {
+                        match extract_path(dst, archive_name(link).as_slice()) {
+                            Some(target) => {
+                                try!(check_parents(dst, &target, false));
+                                try!(fs::link(&target, &path));
+                            }
+                            None => {
+                                return Err(invalid("tar link target is outside the destination"))
+                            }
+                        }
+                    }
+                    continue;
+                }
+                _ => continue,
+            }
+            try!(fs::chmod(&path, header.perm & AllPermissions));
+            try!(fs::change_file_times(&path, header.mtime * 1000, header.mtime * 1000));
+        }
+        for &(ref path, ref header) in dirs.iter().rev() {
+            try!(fs::chmod(path, header.perm & AllPermissions));
+            try!(fs::change_file_times(path, header.mtime * 1000, header.mtime * 1000));
+        }
+        Ok(())
+    }
+}
+
+// Apply the records of a POSIX extended header, each of the form
+// "<length> <key>=<value>\n".
+fn parse_pax(mut data: &[u8], ext: &mut Extensions) -> IoResult<()> {
+    while !data.is_empty() {
+        let space = data.iter().position(|&b| b == ' ' as u8);
+        let len = space.and_then(|i| str::from_utf8(data.slice_to(i)))
+                       .and_then(|s| from_str::<uint>(s));
+        let (space, len) = match (space, len) {
+            (Some(space), Some(len)) if len > space + 1 && len <= data.len() => (space, len),
+            _ => return Err(invalid("invalid tar extended header")),
+        };
+        let record = data.slice(space + 1, len - 1);
+        data = data.slice_from(len);
+
+        let eq = match record.iter().position(|&b| b == '=' as u8) {
+            Some(eq) => eq,
+            None => return Err(invalid("invalid tar extended header")),
+        };
+        let (key, value) = (record.slice_to(eq), record.slice_from(eq + 1));
+        let number = || {
+            // fractional seconds are ignored
+            let digits = value.iter().position(|&b| b == '.' as u8).unwrap_or(value.len());
+            str::from_utf8(value.slice_to(digits)).and_then(|s| from_str::<u64>(s))
+        };
+        match key {
+            k if k == bytes!("path") => ext.path = Some(Vec::from_slice(value)),
+            k if k == bytes!("linkpath") => ext.link = Some(Vec::from_slice(value)),
+            k if k == bytes!("size") => ext.size = number(),
+            k if k == bytes!("mtime") => ext.mtime = number(),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn parse_header(block: &[u8], ext: Extensions) -> IoResult<Header> {
+    let kind = EntryType::from_flag(block[156]);
+    let path = match ext.path {
+        Some(path) => path,
+        None => {
+            let name = field(block.slice(0, 100));
+            let prefix = field(block.slice(345, 500));
+            // only POSIX ustar headers have a prefix; GNU ones use the space
+            // for other things
+            if block.slice(257, 263) == bytes!("ustar\0") && !prefix.is_empty() {
+                let mut path = Vec::from_slice(prefix);
+                path.push('/' as u8);
+                path.push_all(name);
+                path
+            } else {
+                Vec::from_slice(name)
+            }
+        }
+    };
+    if path.is_empty() {
+        return Err(invalid("tar entry has an empty path"));
+    }
+    let link = match kind {
+        HardLink | Symlink => Some(match ext.link {
+            Some(link) => Path::new(link),
+            None => Path::new(field(block.slice(157, 257))),
+        }),
+        _ => None,
+    };
+    let size = match ext.size {
+        Some(size) => size,
+        None => try!(parse_number(block.slice(124, 136))),
+    };
+    let mtime = match ext.mtime {
+        Some(mtime) => mtime,
+        None => try!(parse_number(block.slice(136, 148))),
+    };
+    Ok(Header {
+        path: Path::new(path),
+        kind: kind,
+        perm: (try!(parse_number(block.slice(100, 108))) & 0o7777) as FilePermission,
+        uid: try!(parse_number(block.slice(108, 116))),
+        gid: try!(parse_number(block.slice(116, 124))),
+        // only regular files have data, whatever the size says
+        size: match kind { HardLink | Symlink | Directory => 0, _ => size },
+        mtime: mtime,
+        link: link,
+    })
+}
+
+impl<'a, R: Reader> Entry<'a, R> {
+    /// Return the entry's header.
+    pub fn header<'b>(&'b self) -> &'b Header {
+        &self.header
+    }
+}
+
+impl<'a, R: Reader> Reader for Entry<'a, R> {
+    fn read(&mut self, buf: &mut [u8]) -> IoResult<uint> {
+        let archive = &mut *self.archive;
+        if archive.remaining == 0 {
+            return Err(io::standard_error(EndOfFile));
+        }
+        let len = cmp::min(buf.len() as u64, archive.remaining) as uint;
+        match archive.inner.read(buf.mut_slice_to(len)) {
+            Ok(n) => {
+                archive.remaining -= n as u64;
+                Ok(n)
+            }
+            Err(ref e) if e.kind == EndOfFile => Err(invalid("truncated tar archive")),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// A `Writer` of a tar archive.
+///
+/// `finish` must be called once all the entries have been added, to end the
+/// archive.
+pub struct Builder<W> {
+    inner: W,
+}
+
+impl<W: Writer> Builder<W> {
+    /// Create a builder writing an archive to `w`.
+    pub fn new(w: W) -> Builder<W> {
+        Builder { inner: w }
+    }
+
+    // Write a GNU long name or link entry holding `name`.
+    fn write_long_name(&mut self, flag: char, name: &[u8]) -> IoResult<()> {
+        let mut header = Header::new(Path::new(LONG_LINK), Regular);
+        header.size = name.len() as u64 + 1;
+        let mut block = [0u8, ..512];
+        encode(&mut block, &header, LONG_LINK, bytes!(""), bytes!(""));
+        block[156] = flag as u8;
+        set_checksum(&mut block);
+        try!(self.inner.write(block));
+        try!(self.inner.write(name));
+        try!(self.inner.write([0]));
+        self.write_padding(header.size)
+    }
+
+    fn write_padding(&mut self, size: u64) -> IoResult<()> {
+        static ZEROS: [u8, ..512] = [0u8, ..512];
+        self.inner.write(ZEROS.slice_to(padding(size) as uint))
+    }
+
+    /// Add an entry to the archive, whose data, if it's a regular file, is
+    /// the first `header.size` bytes read from `data`.
+    pub fn append(&mut self, header: &Header, data: &mut Reader) -> IoResult<()> {
+        let mut path = archive_name(&header.path);
+        if header.kind == Directory {
+            path.push('/' as u8);
+        }
+        let link = header.link.as_ref().map_or(Vec::new(), |link| archive_name(link));
+
+        let (prefix, name) = match split_path(path.as_slice()) {
+            Some(split) => split,
+            None => {
+                try!(self.write_long_name('L', path.as_slice()));
+                (bytes!(""), path.slice_to(100))
+            }
+        };
+        if link.len() > 100 {
+            try!(self.write_long_name('K', link.as_slice()));
+        }
+
+        let size = if header.kind == Regular { header.size } else { 0 };
+        let mut block = [0u8, ..512];
+        encode(&mut block, header, name, prefix, link.slice_to(cmp::min(link.len(), 100)));
+        write_number(block.mut_slice(124, 136), size);
+        set_checksum(&mut block);
+        try!(self.inner.write(block));
+
+        let mut buf = [0u8, ..4096];
+        let mut remaining = size;
+        while remaining > 0 {
+            let len = cmp::min(remaining, buf.len() as u64) as uint;
+            let n = match data.read(buf.mut_slice_to(len)) {
+                Ok(n) => n,
+                Err(ref e) if e.kind == EndOfFile => {
+                    return Err(invalid("tar entry data is shorter than its size"))
+                }
+                Err(e) => return Err(e),
+            };
+            try!(self.inner.write(buf.slice_to(n)));
+            remaining -= n as u64;
+        }
+        self.write_padding(size)
+    }
+
+    /// Add the file, directory or symlink at `src` to the archive as `path`.
+    /// A directory's contents aren't added.
+    pub fn append_path(&mut self, path: &Path, src: &Path) -> IoResult<()> {
+        let stat = try!(fs::lstat(src));
+        let mut header = Header::from_stat(path.clone(), &stat);
+        match header.kind {
+            Regular => {
+                let mut file = try!(File::open(src));
+                self.append(&header, &mut file)
+            }
+            Symlink => {
+                header.link = Some(try!(fs::readlink(src)));
+                self.append(&header, &mut io::util::NullReader)
+            }
+            _ => self.append(&header, &mut io::util::NullReader),
+        }
+    }
+
+    /// Add the directory `src` and everything in it to the archive, as
+    /// `path`. Symlinks are added as links rather than followed.
+    pub fn append_dir_all(&mut self, path: &Path, src: &Path) -> IoResult<()> {
+        try!(self.append_path(path, src));
+        for relative in try!(walk_sorted(src)).iter() {
+            try!(self.append_path(&path.join(relative), &src.join(relative)));
+        }
+        Ok(())
+    }
+
+    /// End the archive, returning the underlying writer.
+    pub fn finish(mut self) -> IoResult<W> {
+        try!(self.inner.write([0u8, ..1024]));
+        Ok(self.inner)
+    }
+}
+
+// Split a path into the prefix and name fields of a ustar header, if it
+// fits. A path longer than the name field must be split at a `/`.
+fn split_path<'a>(path: &'a [u8]) -> Option<(&'a [u8], &'a [u8])> {
+    if path.len() <= 100 {
+        return Some((bytes!(""), path));
+    }
+    // the first `/` leaving at most 100 bytes for the name
+    let start = path.len() - 101;
+    match path.slice_from(start).iter().position(|&b| b == '/' as u8) {
+        Some(i) if start + i <= 155 && start + i + 1 < path.len() => {
+            Some((path.slice_to(start + i), path.slice_from(start + i + 1)))
+        }
+        _ => None,
+    }
+}
+
+// Fill in the fields of a ustar header block other than the size and
+// checksum.
+fn encode(block: &mut [u8], header: &Header, name: &[u8], prefix: &[u8], link: &[u8]) {
+    block.mut_slice(0, name.len()).copy_from(name);
+    write_number(block.mut_slice(100, 108), (header.perm & 0o7777) as u64);
+    write_number(block.mut_slice(108, 116), header.uid);
+    write_number(block.mut_slice(116, 124), header.gid);
+    write_number(block.mut_slice(136, 148), header.mtime);
+    block[156] = header.kind.flag();
+    block.mut_slice(157, 157 + link.len()).copy_from(link);
+    block.mut_slice(257, 265).copy_from(bytes!("ustar\x0000"));
+    write_number(block.mut_slice(329, 337), 0);
+    write_number(block.mut_slice(337, 345), 0);
+    block.mut_slice(345, 345 + prefix.len()).copy_from(prefix);
+}
+
+fn set_checksum(block: &mut [u8]) {
+    let (sum, _) = checksum(block);
+    write_number(block.mut_slice(148, 155), sum);
+    block[155] = ' ' as u8;
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{MemReader, MemWriter, TempDir, InvalidInput, TypeFile, TypeDirectory};
+    use std::io::fs;
+    use std::io::fs::File;
+    use std::io::util::NullReader;
+    use std::str;
+    use super::{Archive, Builder, Header, Regular, Directory, Symlink, HardLink};
+    use super::{parse_number, write_number};
+
+    fn entries(tarball: Vec<u8>) -> Vec<(Header, Vec<u8>)> {
+        let mut archive = Archive::new(MemReader::new(tarball));
+        let mut entries = Vec::new();
+        loop {
+            match archive.next_entry().unwrap() {
+                Some(mut entry) => {
+                    let data = entry.read_to_end().unwrap();
+                    entries.push((entry.header().clone(), data));
+                }
+                None => return entries,
+            }
+        }
+    }
+
+    #[test]
+    fn test_numbers() {
+        let mut field = [0u8, ..12];
+        write_number(field, 0o644);
+        assert_eq!(field.as_slice(), bytes!("00000000644\0"));
+        assert_eq!(parse_number(field).unwrap(), 0o644);
+        assert_eq!(parse_number(bytes!("   644 \0")).unwrap(), 0o644);
+        assert_eq!(parse_number(bytes!("\0\0\0\0")).unwrap(), 0);
+        assert!(parse_number(bytes!("0000009\0")).is_err());
+
+        // larger than 11 octal digits
+        let big = 10 << 30;
+        write_number(field, big);
+        assert_eq!(field[0], 0x80);
+        assert_eq!(parse_number(field).unwrap(), big);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let long_dir = "d".repeat(120);
+        let long_name = format!("{}/{}", long_dir, "f".repeat(60));
+        let longer_name = "x".repeat(300);
+
+        let mut builder = Builder::new(MemWriter::new());
+        let mut header = Header::new(Path::new("dir"), Directory);
+        header.mtime = 1234567890;
+        builder.append(&header, &mut NullReader).unwrap();
+
+        let mut header = Header::new(Path::new("dir/hello.txt"), Regular);
+        header.size = 12;
+        header.perm = 0o600;
+        header.uid = 1000;
+        header.mtime = 1234567890;
+        // the data may be longer than the size
+        let data = Vec::from_slice(bytes!("hello world\nignored"));
+        builder.append(&header, &mut MemReader::new(data)).unwrap();
+
+        let mut header = Header::new(Path::new(long_name.as_slice()), Regular);
+        header.size = 1000;
+        builder.append(&header, &mut MemReader::new(Vec::from_elem(1000, 7u8))).unwrap();
+
+        let mut header = Header::new(Path::new(longer_name.as_slice()), Symlink);
+        header.link = Some(Path::new(long_name.as_slice()));
+        builder.append(&header, &mut NullReader).unwrap();
+
+        let mut header = Header::new(Path::new("hard"), HardLink);
+        header.link = Some(Path::new("dir/hello.txt"));
+        builder.append(&header, &mut NullReader).unwrap();
+
+        let tarball = builder.finish().unwrap().unwrap();
+        assert_eq!(tarball.len() % 512, 0);
+        let entries = entries(tarball);
+        assert_eq!(entries.len(), 5);
+
+        let (ref dir, _) = *entries.get(0);
+        assert!(dir.path == Path::new("dir"));
+        assert_eq!(dir.kind, Directory);
+        assert_eq!(dir.perm, 0o755);
+        assert_eq!(dir.mtime, 1234567890);
+
+        let (ref hello, ref data) = *entries.get(1);
+        assert!(hello.path == Path::new("dir/hello.txt"));
+        assert_eq!(hello.kind, Regular);
+        assert_eq!(hello.perm, 0o600);
+        assert_eq!(hello.uid, 1000);
+        assert_eq!(hello.size, 12);
+        assert_eq!(data.as_slice(), bytes!("hello world\n"));
+
+        let (ref long, ref data) = *entries.get(2);
+        assert_eq!(long.path.as_str(), Some(long_name.as_slice()));
+        assert!(*data == Vec::from_elem(1000, 7u8));
+
+        let (ref link, _) = *entries.get(3);
+        assert_eq!(link.path.as_str(), Some(longer_name.as_slice()));
+        assert_eq!(link.kind, Symlink);
+        assert_eq!(link.link.as_ref().unwrap().as_str(), Some(long_name.as_slice()));
+
+        let (ref hard, _) = *entries.get(4);
+        assert_eq!(hard.kind, HardLink);
+        assert_eq!(hard.link.as_ref().unwrap().as_str(), Some("dir/hello.txt"));
+    }
+
+    #[test]
+    fn test_skip_unread() {
+        let mut builder = Builder::new(MemWriter::new());
+        for (i, name) in ["a", "b", "c"].iter().enumerate() {
+            let mut header = Header::new(Path::new(*name), Regular);
+            header.size = 700 * i as u64;
+            let data = Vec::from_elem(700 * i, i as u8);
+            builder.append(&header, &mut MemReader::new(data)).unwrap();
+        }
+        let mut archive = Archive::new(MemReader::new(builder.finish().unwrap().unwrap()));
+        assert!(archive.next_entry().unwrap().unwrap().header().path == Path::new("a"));
+        {
+            let mut b = archive.next_entry().unwrap().unwrap();
+            assert_eq!(b.read_exact(10).unwrap(), Vec::from_elem(10, 1u8));
+        }
+        let mut c = archive.next_entry().unwrap().unwrap();
+        assert!(c.read_to_end().unwrap() == Vec::from_elem(1400, 2u8));
+    }
+
+    #[test]
+    fn test_pax() {
+        let records = bytes!("31 path=some/long/pax/name.txt\n20 mtime=1234567890\n");
+        let mut pax = Header::new(Path::new("PaxHeaders/x"), Regular);
+        pax.size = records.len() as u64;
+        let mut builder = Builder::new(MemWriter::new());
+        builder.append(&pax, &mut MemReader::new(Vec::from_slice(records))).unwrap();
+        let mut header = Header::new(Path::new("short"), Regular);
+        header.size = 3;
+        builder.append(&header, &mut MemReader::new(Vec::from_slice(bytes!("abc")))).unwrap();
+        let mut tarball = builder.finish().unwrap().unwrap();
+
+        // turn the first entry into an extended header
+        *tarball.get_mut(156) = 'x' as u8;
+        let mut block = Vec::from_slice(tarball.slice_to(512));
+        super::set_checksum(block.as_mut_slice());
+        tarball.mut_slice_to(512).copy_from(block.as_slice());
+
+        let entries = entries(tarball);
+        assert_eq!(entries.len(), 1);
+        let (ref header, ref data) = *entries.get(0);
+        assert_eq!(header.path.as_str(), Some("some/long/pax/name.txt"));
+        assert_eq!(header.mtime, 1234567890);
+        assert_eq!(data.as_slice(), bytes!("abc"));
+    }
+
+    #[test]
+    fn test_corrupt() {
+        let mut builder = Builder::new(MemWriter::new());
+        let mut header = Header::new(Path::new("a"), Regular);
+        header.size = 600;
+        builder.append(&header, &mut MemReader::new(Vec::from_elem(600, 1u8))).unwrap();
+        let tarball = builder.finish().unwrap().unwrap();
+
+        let mut bad = tarball.clone();
+        *bad.get_mut(0) = 'b' as u8;
+        let mut archive = Archive::new(MemReader::new(bad));
+        let err = archive.next_entry().err().unwrap();
+        assert_eq!(err.kind, InvalidInput);
+        assert_eq!(err.desc, "tar header checksum mismatch");
+
+        let truncated = Vec::from_slice(tarball.slice_to(700));
+        let mut archive = Archive::new(MemReader::new(truncated));
+        let mut entry = archive.next_entry().unwrap().unwrap();
+        assert_eq!(entry.read_to_end().unwrap_err().desc, "truncated tar archive");
+
+        // the data is shorter than the header says
+        let mut builder = Builder::new(MemWriter::new());
+        let err = builder.append(&header, &mut MemReader::new(vec!(1, 2, 3))).unwrap_err();
+        assert_eq!(err.desc, "tar entry data is shorter than its size");
+    }
+
+    #[test]
+    fn test_unpack() {
+        let src = TempDir::new("tar-src").unwrap();
+        let dst = TempDir::new("tar-dst").unwrap();
+        let srcdir = src.path();
+        fs::mkdir(&srcdir.join("sub"), 0o755).unwrap();
+        File::create(&srcdir.join("sub/file.txt")).write(bytes!("contents")).unwrap();
+        fs::chmod(&srcdir.join("sub/file.txt"), 0o640).unwrap();
+        fs::change_file_times(&srcdir.join("sub/file.txt"), 1000000000000, 1000000000000)
+            .unwrap();
+        fs::symlink(&Path::new("file.txt"), &srcdir.join("sub/link")).unwrap();
+        File::create(&srcdir.join("top")).write(bytes!("top")).unwrap();
+
+        let mut builder = Builder::new(MemWriter::new());
+        builder.append_dir_all(&Path::new("pkg"), srcdir).unwrap();
+        let tarball = builder.finish().unwrap().unwrap();
+
+        let names: Vec<~str> = entries(tarball.clone()).iter().map(|&(ref h, _)| {
+            h.path.as_str().unwrap().to_owned()
+        }).collect();
+        assert_eq!(names, vec!("pkg".to_owned(), "pkg/sub".to_owned(),
+                               "pkg/sub/file.txt".to_owned(), "pkg/sub/link".to_owned(),
+                               "pkg/top".to_owned()));
+
+        Archive::new(MemReader::new(tarball)).unpack(dst.path()).unwrap();
+        let file = dst.path().join("pkg/sub/file.txt");
+        assert_eq!(File::open(&file).read_to_end().unwrap().as_slice(), bytes!("contents"));
+        let stat = fs::stat(&file).unwrap();
+        assert_eq!(stat.perm, 0o640);
+        assert_eq!(stat.modified, 1000000000000);
+        let link = fs::readlink(&dst.path().join("pkg/sub/link")).unwrap();
+        assert!(link == Path::new("file.txt"));
+        let top = File::open(&dst.path().join("pkg/top")).read_to_end().unwrap();
+        assert_eq!(str::from_utf8(top.as_slice()), Some("top"));
+    }
+
+    #[test]
+    fn test_unpack_outside() {
+        let dst = TempDir::new("tar-dst").unwrap();
+        let mut builder = Builder::new(MemWriter::new());
+        let header = Header::new(Path::new("../evil"), Regular);
+        builder.append(&header, &mut NullReader).unwrap();
+        let tarball = builder.finish().unwrap().unwrap();
+        let err = Archive::new(MemReader::new(tarball)).unpack(dst.path()).unwrap_err();
+        assert_eq!(err.desc, "tar entry path is outside the destination");
+        assert!(!dst.path().dir_path().join("evil").exists());
+    }
+
+    fn link(name: &str, target: &Path) -> Header {
+        let mut header = Header::new(Path::new(name), Symlink);
+        header.link = Some(target.clone());
+        header
+    }
+
+    fn file(name: &str, data: &[u8]) -> Header {
+        let mut header = Header::new(Path::new(name), Regular);
+        header.size = data.len() as u64;
+        header
+    }
+
+    #[test]
+    fn test_unpack_symlink_outside() {
+        let dst = TempDir::new("tar-dst").unwrap();
+        let outside = TempDir::new("tar-outside").unwrap();
+        let mut builder = Builder::new(MemWriter::new());
+        builder.append(&link("abs", outside.path()), &mut NullReader).unwrap();
+        builder.append(&file("abs/evil", bytes!("evil")),
+                       &mut MemReader::new(Vec::from_slice(bytes!("evil")))).unwrap();
+        builder.append(&link("sub/rel", &Path::new("../..")), &mut NullReader).unwrap();
+        builder.append(&file("sub/rel/evil", bytes!("evil")),
+                       &mut MemReader::new(Vec::from_slice(bytes!("evil")))).unwrap();
+        let tarball = builder.finish().unwrap().unwrap();
+
+        // the links are skipped, so the files go into real directories
+        Archive::new(MemReader::new(tarball)).unpack(dst.path()).unwrap();
+        assert!(!outside.path().join("evil").exists());
+        assert!(!dst.path().dir_path().join("evil").exists());
+        for name in ["abs", "sub/rel"].iter() {
+            let dir = dst.path().join(*name);
+            assert_eq!(fs::lstat(&dir).unwrap().kind, TypeDirectory);
+            let data = File::open(&dir.join("evil")).read_to_end().unwrap();
+            assert_eq!(data.as_slice(), bytes!("evil"));
+        }
+    }
+
+    #[test]
+    fn test_unpack_through_symlink() {
+        let dst = TempDir::new("tar-dst").unwrap();
+        let outside = TempDir::new("tar-outside").unwrap();
+        fs::symlink(outside.path(), &dst.path().join("existing")).unwrap();
+        File::create(&dst.path().join("target")).write(bytes!("target")).unwrap();
+
+        // a link already in `dst` isn't followed
+        let mut builder = Builder::new(MemWriter::new());
+        builder.append(&file("existing/evil", bytes!("evil")),
+                       &mut MemReader::new(Vec::from_slice(bytes!("evil")))).unwrap();
+        let tarball = builder.finish().unwrap().unwrap();
+        let err = Archive::new(MemReader::new(tarball)).unpack(dst.path()).unwrap_err();
+        assert_eq!(err.desc, "archive entry path goes through a symlink");
+        assert!(!outside.path().join("evil").exists());
+
+        // neither is one the archive makes, and a file replaces a link rather
+        // than writing to its target
+        let mut builder = Builder::new(MemWriter::new());
+        builder.append(&link("inside", &Path::new("target")), &mut NullReader).unwrap();
+        builder.append(&file("inside", bytes!("evil")),
+                       &mut MemReader::new(Vec::from_slice(bytes!("evil")))).unwrap();
+        builder.append(&link("dir", &Path::new(".")), &mut NullReader).unwrap();
+        builder.append(&file("dir/evil", bytes!("evil")),
+                       &mut MemReader::new(Vec::from_slice(bytes!("evil")))).unwrap();
+        let tarball = builder.finish().unwrap().unwrap();
+        let err = Archive::new(MemReader::new(tarball)).unpack(dst.path()).unwrap_err();
+        assert_eq!(err.desc, "archive entry path goes through a symlink");
+        let target = File::open(&dst.path().join("target")).read_to_end().unwrap();
+        assert_eq!(target.as_slice(), bytes!("target"));
+        assert_eq!(fs::lstat(&dst.path().join("inside")).unwrap().kind, TypeFile);
+        assert!(!dst.path().join("evil").exists());
+    }
+}
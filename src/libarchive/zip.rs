@@ -0,0 +1,827 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+
+Reading and writing zip files
+
+A zip file is a sequence of entries, each a local header followed by the
+entry's data, ending with a central directory listing the entries and where
+they are. Entries are read by looking them up in the central directory, so a
+`ZipArchive` needs a reader which can seek.
+
+Only entries which are stored or compressed with deflate can be read, and
+archives and entries larger than 4GB, which need the zip64 extensions, aren't
+supported. Modification times are stored in MS-DOS format, to a resolution of
+two seconds, and are taken to be in UTC.
+
+# Example
+
+```rust
+use std::io::{MemReader, MemWriter};
+use archive::zip::{ZipArchive, ZipWriter, Header};
+
+let mut writer = ZipWriter::new(MemWriter::new());
+let data = Vec::from_slice(bytes!("hello world\n"));
+writer.append(&Header::new(Path::new("hello.txt")), &mut MemReader::new(data)).unwrap();
+let zipfile = writer.finish().unwrap().unwrap();
+
+let mut archive = ZipArchive::new(MemReader::new(zipfile)).unwrap();
+let i = archive.find(&Path::new("hello.txt")).unwrap();
+let mut entry = archive.open(i).unwrap();
+assert_eq!(entry.read_to_end().unwrap().as_slice(), bytes!("hello world\n"));
+```
+
+*/
+
+use std::cmp;
+use std::io;
+use std::io::{IoResult, IoError, EndOfFile, FilePermission, UserDir, AllPermissions};
+use std::io::{MemReader, Seek, SeekSet, SeekCur, SeekEnd, RefReader};
+use std::io::{TypeDirectory, TypeFile};
+use std::io::fs;
+use std::io::fs::File;
+use std::io::util::LimitReader;
+use std::str;
+use flate;
+use flate::{DeflateDecoder, DeflateEncoder};
+use flate::checksum::Crc32;
+use time;
+use time::{Tm, Timespec};
+
+use {invalid, archive_name, extract_path, prepare_path, walk_sorted};
+
+static LOCAL_HEADER_SIG: u32 = 0x04034b50;
+static DATA_DESCRIPTOR_SIG: u32 = 0x08074b50;
+static CENTRAL_HEADER_SIG: u32 = 0x02014b50;
+static END_SIG: u32 = 0x06054b50;
+
+// The size of the end of central directory record, without its comment
+static END_SIZE: u64 = 22;
+
+// General purpose flags: the sizes and checksum follow the data, and the
+// name is UTF-8
+static FLAG_DATA_DESCRIPTOR: u16 = 1 << 3;
+static FLAG_UTF8: u16 = 1 << 11;
+
+// Made by a Unix system, to version 2.0 of the specification
+static VERSION_MADE_BY: u16 = 3 << 8 | 20;
+static VERSION_NEEDED: u16 = 20;
+
+static S_IFREG: u32 = 0o100000;
+static S_IFDIR: u32 = 0o040000;
+// The MS-DOS attribute flagging a directory
+static DOS_DIRECTORY: u32 = 0x10;
+
+/// How the data of an entry of a zip file is compressed.
+#[deriving(Clone, Eq, Show)]
+pub enum CompressionMethod {
+    /// Not compressed
+    Stored,
+    /// Compressed with deflate
+    Deflated,
+    /// Some method this library can't decompress, by its number
+    Unsupported(u16),
+}
+
+impl CompressionMethod {
+    fn from_u16(n: u16) -> CompressionMethod {
+        match n {
+            0 => Stored,
+            8 => Deflated,
+            n => Unsupported(n),
+        }
+    }
+
+    fn to_u16(&self) -> u16 {
+        match *self {
+            Stored => 0,
+            Deflated => 8,
+            Unsupported(n) => n,
+        }
+    }
+}
+
+/// The metadata of an entry of a zip file.
+#[deriving(Clone)]
+pub struct Header {
+    /// The path of the entry in the archive
+    pub path: Path,
+    /// Whether the entry is a directory
+    pub is_dir: bool,
+    /// How the entry's data is compressed
+    pub method: CompressionMethod,
+    /// The permission bits of the file
+    pub perm: FilePermission,
+    /// When the file was last modified, in seconds since the Unix epoch
+    pub mtime: u64,
+    /// The size of the entry's data, which is filled in when it's written
+    pub size: u64,
+    /// The size of the entry's data once compressed, which is filled in when
+    /// it's written
+    pub compressed_size: u64,
+    /// The CRC-32 of the entry's data, which is filled in when it's written
+    pub crc32: u32,
+    // Where the entry's local header is in the archive
+    offset: u64,
+}
+
+impl Header {
+    /// Create a header for a file compressed with deflate, modified at the
+    /// start of 1980, which is as early as zip files can record.
+    pub fn new(path: Path) -> Header {
+        Header {
+            path: path,
+            is_dir: false,
+            method: Deflated,
+            perm: 0o644,
+            mtime: 315532800,
+            size: 0,
+            compressed_size: 0,
+            crc32: 0,
+            offset: 0,
+        }
+    }
+
+    /// Create a header for a directory.
+    pub fn new_dir(path: Path) -> Header {
+        Header {
+            is_dir: true,
+            method: Stored,
+            perm: 0o755,
+            .. Header::new(path)
+        }
+    }
+
+    fn external_attributes(&self) -> u32 {
+        let perm = (self.perm & AllPermissions) as u32;
+        if self.is_dir {
+            (S_IFDIR | perm) << 16 | DOS_DIRECTORY
+        } else {
+            (S_IFREG | perm) << 16
+        }
+    }
+}
+
+// Convert a time to MS-DOS's date and time formats.
+fn dos_date_time(mtime: u64) -> (u16, u16) {
+    let tm = time::at_utc(Timespec::new(mtime as i64, 0));
+    let year = tm.tm_year + 1900;
+    if year < 1980 {
+        return ((1 << 5) | 1, 0);
+    }
+    let year = cmp::min(year, 2107);
+    let date = ((year - 1980) << 9) | ((tm.tm_mon + 1) << 5) | tm.tm_mday;
+    let time = (tm.tm_hour << 11) | (tm.tm_min << 5) | (tm.tm_sec / 2);
+    (date as u16, time as u16)
+}
+
+// Convert an MS-DOS date and time to seconds since the epoch.
+fn unix_time(date: u16, time: u16) -> u64 {
+    let tm = Tm {
+        tm_sec: ((time & 0x1f) * 2) as i32,
+        tm_min: ((time >> 5) & 0x3f) as i32,
+        tm_hour: (time >> 11) as i32,
+        tm_mday: (date & 0x1f) as i32,
+        tm_mon: ((date >> 5) & 0xf) as i32 - 1,
+        tm_year: (date >> 9) as i32 + 80,
+        tm_wday: 0,
+        tm_yday: 0,
+        tm_isdst: 0,
+        tm_gmtoff: 0,
+        tm_zone: "UTC".to_owned(),
+        tm_nsec: 0,
+    };
+    tm.to_timespec().sec as u64
+}
+
+fn too_large() -> IoError {
+    invalid("zip64 archives are not supported")
+}
+
+/// A zip file read from a `Reader` which can seek.
+pub struct ZipArchive<R> {
+    inner: R,
+    headers: Vec<Header>,
+}
+
+// Parse one entry of the central directory.
+fn read_central_header(r: &mut MemReader) -> IoResult<Header> {
+    if try!(r.read_le_u32()) != CENTRAL_HEADER_SIG {
+        return Err(invalid("invalid zip central directory"));
+    }
+    let made_by = try!(r.read_le_u16());
+    let _needed = try!(r.read_le_u16());
+    let _flags = try!(r.read_le_u16());
+    let method = CompressionMethod::from_u16(try!(r.read_le_u16()));
+    let time = try!(r.read_le_u16());
+    let date = try!(r.read_le_u16());
+    let crc32 = try!(r.read_le_u32());
+    let compressed_size = try!(r.read_le_u32());
+    let size = try!(r.read_le_u32());
+    let name_len = try!(r.read_le_u16()) as uint;
+    let extra_len = try!(r.read_le_u16()) as uint;
+    let comment_len = try!(r.read_le_u16()) as uint;
+    let _disk = try!(r.read_le_u16());
+    let _internal_attributes = try!(r.read_le_u16());
+    let external_attributes = try!(r.read_le_u32());
+    let offset = try!(r.read_le_u32());
+    let name = try!(r.read_exact(name_len));
+    try!(r.read_exact(extra_len + comment_len));
+
+    if compressed_size == 0xffffffff || size == 0xffffffff || offset == 0xffffffff {
+        return Err(too_large());
+    }
+    // Names are UTF-8 if flagged as such, and code page 437 otherwise, which
+    // is the same for ASCII names. Either way the bytes are kept as they are.
+    let is_dir = name.as_slice().ends_with(bytes!("/")) ||
+                 external_attributes & DOS_DIRECTORY != 0;
+    let perm = if made_by >> 8 == 3 && external_attributes >> 16 != 0 {
+        (external_attributes >> 16) as FilePermission & AllPermissions
+    } else if is_dir {
+        0o755
+    } else {
+        0o644
+    };
+    Ok(Header {
+        path: Path::new(name),
+        is_dir: is_dir,
+        method: method,
+        perm: perm,
+        mtime: unix_time(date, time),
+        size: size as u64,
+        compressed_size: compressed_size as u64,
+        crc32: crc32,
+        offset: offset as u64,
+    })
+}
+
+impl<R: Reader + Seek> ZipArchive<R> {
+    /// Read the directory of the zip file read from `r`.
+    pub fn new(mut r: R) -> IoResult<ZipArchive<R>> {
+        // The end of central directory record is followed by a comment of up
+        // to 65535 bytes, so search backwards for its signature.
+        try!(r.seek(0, SeekEnd));
+        let len = try!(r.tell());
+        let tail_len = cmp::min(len, END_SIZE + 0xffff);
+        try!(r.seek((len - tail_len) as i64, SeekSet));
+        let tail = try!(r.read_exact(tail_len as uint));
+        let sig = [0x50u8, 0x4b, 0x05, 0x06];
+        let end = match range(0, tail.len()).rev().find(|&i| {
+            tail.len() - i >= END_SIZE as uint && tail.slice(i, i + 4) == sig.as_slice()
+        }) {
+            Some(end) => end,
+            None => return Err(invalid("not a zip file")),
+        };
+
+        let mut end = MemReader::new(Vec::from_slice(tail.slice_from(end)));
+        try!(end.read_le_u32());
+        let disk = try!(end.read_le_u16());
+        let cd_disk = try!(end.read_le_u16());
+        let _disk_entries = try!(end.read_le_u16());
+        let entries = try!(end.read_le_u16());
+        let cd_size = try!(end.read_le_u32());
+        let cd_offset = try!(end.read_le_u32());
+        if disk != 0 || cd_disk != 0 {
+            return Err(invalid("multi-disk zip files are not supported"));
+        }
+        if entries == 0xffff || cd_offset == 0xffffffff {
+            return Err(too_large());
+        }
+
+        try!(r.seek(cd_offset as i64, SeekSet));
+        let mut cd = MemReader::new(try!(r.read_exact(cd_size as uint)));
+        let mut headers = Vec::with_capacity(entries as uint);
+        for _ in range(0, entries) {
+            headers.push(try!(read_central_header(&mut cd)));
+        }
+        Ok(ZipArchive { inner: r, headers: headers })
+    }
+
+    /// Return the number of entries in the archive.
+    pub fn len(&self) -> uint {
+        self.headers.len()
+    }
+
+    /// Return the headers of the entries in the archive, in order.
+    pub fn headers<'a>(&'a self) -> &'a [Header] {
+        self.headers.as_slice()
+    }
+
+    /// Return the index of the entry with the given path.
+    pub fn find(&self, path: &Path) -> Option<uint> {
+        self.headers.iter().position(|h| h.path == *path)
+    }
+
+    /// Open the `i`th entry of the archive for reading.
+    ///
+    /// # Failure
+    ///
+    /// Fails if `i` is out of bounds.
+    pub fn open<'a>(&'a mut self, i: uint) -> IoResult<Entry<'a, R>> {
+        let header = self.headers.get(i).clone();
+        match header.method {
+            Unsupported(_) => {
+                return Err(IoError {
+                    detail: Some(format!("compression method {}", header.method.to_u16())),
+                    .. invalid("unsupported zip compression method")
+                })
+            }
+            _ => {}
+        }
+
+        // The name and extra field in the local header may differ from those
+        // in the central directory, so only their lengths are used.
+        try!(self.inner.seek(header.offset as i64, SeekSet));
+        if try!(self.inner.read_le_u32()) != LOCAL_HEADER_SIG {
+            return Err(invalid("invalid zip local header"));
+        }
+        try!(self.inner.seek(22, SeekCur));
+        let name_len = try!(self.inner.read_le_u16());
+        let extra_len = try!(self.inner.read_le_u16());
+        try!(self.inner.seek(name_len as i64 + extra_len as i64, SeekCur));
+
+        let limited = LimitReader::new(self.inner.by_ref(), header.compressed_size as uint);
+        let data = match header.method {
+            Deflated => DeflatedData(DeflateDecoder::new(limited)),
+            _ => StoredData(limited),
+        };
+        Ok(Entry { data: data, header: header, crc: Crc32::new(), size: 0 })
+    }
+
+    /// Extract all the entries of the archive into the directory `dst`,
+    /// setting the permissions and modification times of the files created.
+    ///
+    /// Entries whose paths are absolute, contain `..` or lead through a
+    /// symlink are refused, so an archive can't write outside of `dst`.
+    pub fn extract(&mut self, dst: &Path) -> IoResult<()> {
+        let mut dirs = Vec::new();
+        for i in range(0, self.len()) {
+            let mut entry = try!(self.open(i));
+            let header = entry.header.clone();
+            let path = match extract_path(dst, archive_name(&header.path).as_slice()) {
+                Some(path) => path,
+                None => return Err(invalid("zip entry path is outside the destination")),
+            };
+            try!(prepare_path(dst, &path));
+            if header.is_dir {
+                if !path.is_dir() {
+                    try!(fs::mkdir(&path, UserDir));
+                }
+                dirs.push((path, header));
+                continue;
+            }
+            let mut file = try!(File::create(&path));
+            try!(io::util::copy(&mut entry, &mut file));
+            try!(fs::chmod(&path, header.perm));
+            try!(fs::change_file_times(&path, header.mtime * 1000, header.mtime * 1000));
+        }
+        // directories last, in case they don't allow writing their contents
+        for &(ref path, ref header) in dirs.iter().rev() {
+            try!(fs::chmod(path, header.perm));
+            try!(fs::change_file_times(path, header.mtime * 1000, header.mtime * 1000));
+        }
+        Ok(())
+    }
+
+    /// Return the underlying reader.
+    pub fn unwrap(self) -> R {
+        self.inner
+    }
+}
+
+enum EntryData<'a, R> {
+    StoredData(LimitReader<RefReader<'a, R>>),
+    DeflatedData(DeflateDecoder<LimitReader<RefReader<'a, R>>>),
+}
+
+/// An entry of a zip file, which is a `Reader` of its decompressed data. The
+/// data's size and checksum are checked once it has all been read.
+pub struct Entry<'a, R> {
+    data: EntryData<'a, R>,
+    header: Header,
+    crc: Crc32,
+    size: u64,
+}
+
+impl<'a, R: Reader> Entry<'a, R> {
+    /// Return the entry's header.
+    pub fn header<'b>(&'b self) -> &'b Header {
+        &self.header
+    }
+}
+
+impl<'a, R: Reader> Reader for Entry<'a, R> {
+    fn read(&mut self, buf: &mut [u8]) -> IoResult<uint> {
+        let result = match self.data {
+            StoredData(ref mut r) => r.read(buf),
+            DeflatedData(ref mut r) => r.read(buf),
+        };
+        match result {
+            Ok(n) => {
+                self.crc.update(buf.slice_to(n));
+                self.size += n as u64;
+                Ok(n)
+            }
+            Err(ref e) if e.kind == EndOfFile => {
+                if self.size != self.header.size {
+                    Err(invalid("zip entry length mismatch"))
+                } else if self.crc.sum() != self.header.crc32 {
+                    Err(invalid("zip entry checksum mismatch"))
+                } else {
+                    Err(io::standard_error(EndOfFile))
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+// Counts the bytes written through it, to know where the entries are.
+struct CountingWriter<W> {
+    inner: W,
+    count: u64,
+}
+
+impl<W: Writer> Writer for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> IoResult<()> {
+        try!(self.inner.write(buf));
+        self.count += buf.len() as u64;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> IoResult<()> {
+        self.inner.flush()
+    }
+}
+
+/// A `Writer` of a zip file. It doesn't need to seek, since the sizes and
+/// checksum of each entry are written after its data.
+///
+/// `finish` must be called once all the entries have been added, to write the
+/// central directory.
+pub struct ZipWriter<W> {
+    inner: CountingWriter<W>,
+    headers: Vec<Header>,
+}
+
+impl<W: Writer> ZipWriter<W> {
+    /// Create a writer of a zip file to `w`.
+    pub fn new(w: W) -> ZipWriter<W> {
+        ZipWriter { inner: CountingWriter { inner: w, count: 0 }, headers: Vec::new() }
+    }
+
+    /// Add an entry to the archive, whose data is everything read from
+    /// `data`. The sizes and checksum of the header are ignored and computed
+    /// from the data.
+    pub fn append(&mut self, header: &Header, data: &mut Reader) -> IoResult<()> {
+        let mut header = header.clone();
+        match header.method {
+            Unsupported(_) => return Err(invalid("unsupported zip compression method")),
+            _ => {}
+        }
+        if self.inner.count > 0xfffffffe || self.headers.len() >= 0xffff {
+            return Err(too_large());
+        }
+        header.offset = self.inner.count;
+
+        let mut name = archive_name(&header.path);
+        if header.is_dir {
+            name.push('/' as u8);
+            header.method = Stored;
+        }
+        let flags = if str::is_utf8(name.as_slice()) {
+            FLAG_DATA_DESCRIPTOR | FLAG_UTF8
+        } else {
+            FLAG_DATA_DESCRIPTOR
+        };
+        let (date, time) = dos_date_time(header.mtime);
+        {
+            let w = &mut self.inner;
+            try!(w.write_le_u32(LOCAL_HEADER_SIG));
+            try!(w.write_le_u16(VERSION_NEEDED));
+            try!(w.write_le_u16(flags));
+            try!(w.write_le_u16(header.method.to_u16()));
+            try!(w.write_le_u16(time));
+            try!(w.write_le_u16(date));
+            // the checksum and sizes are in the data descriptor
+            try!(w.write_le_u32(0));
+            try!(w.write_le_u32(0));
+            try!(w.write_le_u32(0));
+            try!(w.write_le_u16(name.len() as u16));
+            try!(w.write_le_u16(0));
+            try!(w.write(name.as_slice()));
+        }
+
+        let start = self.inner.count;
+        let mut crc = Crc32::new();
+        let mut size = 0u64;
+        if !header.is_dir {
+            match header.method {
+                Deflated => {
+                    let mut encoder = DeflateEncoder::new(self.inner.by_ref(), flate::Default);
+                    try!(copy_checked(data, &mut encoder, &mut crc, &mut size));
+                    try!(encoder.finish());
+                }
+                _ => try!(copy_checked(data, &mut self.inner, &mut crc, &mut size)),
+            }
+        }
+        header.size = size;
+        header.compressed_size = self.inner.count - start;
+        header.crc32 = crc.sum();
+        if header.size > 0xfffffffe || header.compressed_size > 0xfffffffe {
+            return Err(too_large());
+        }
+
+        try!(self.inner.write_le_u32(DATA_DESCRIPTOR_SIG));
+        try!(self.inner.write_le_u32(header.crc32));
+        try!(self.inner.write_le_u32(header.compressed_size as u32));
+        try!(self.inner.write_le_u32(header.size as u32));
+        self.headers.push(header);
+        Ok(())
+    }
+
+    /// Add the file or directory at `src` to the archive as `path`, with its
+    /// permissions and modification time. A directory's contents aren't
+    /// added.
+    pub fn append_path(&mut self, path: &Path, src: &Path) -> IoResult<()> {
+        let stat = try!(fs::stat(src));
+        let mut header = match stat.kind {
+            TypeDirectory => Header::new_dir(path.clone()),
+            TypeFile => Header::new(path.clone()),
+            _ => return Err(invalid("only files and directories can be added to zip files")),
+        };
+        header.perm = stat.perm;
+        header.mtime = stat.modified / 1000;
+        if header.is_dir {
+            self.append(&header, &mut io::util::NullReader)
+        } else {
+            let mut file = try!(File::open(src));
+            self.append(&header, &mut file)
+        }
+    }
+
+    /// Add the directory `src` and everything in it to the archive, as
+    /// `path`. Symlinks are followed.
+    pub fn append_dir_all(&mut self, path: &Path, src: &Path) -> IoResult<()> {
+        try!(self.append_path(path, src));
+        for relative in try!(walk_sorted(src)).iter() {
+            try!(self.append_path(&path.join(relative), &src.join(relative)));
+        }
+        Ok(())
+    }
+
+    /// Write the central directory, returning the underlying writer.
+    pub fn finish(mut self) -> IoResult<W> {
+        let start = self.inner.count;
+        if start > 0xfffffffe {
+            return Err(too_large());
+        }
+        for header in self.headers.iter() {
+            let w = &mut self.inner;
+            let mut name = archive_name(&header.path);
+            if header.is_dir {
+                name.push('/' as u8);
+            }
+            let flags = if str::is_utf8(name.as_slice()) {
+                FLAG_DATA_DESCRIPTOR | FLAG_UTF8
+            } else {
+                FLAG_DATA_DESCRIPTOR
+            };
+            let (date, time) = dos_date_time(header.mtime);
+            try!(w.write_le_u32(CENTRAL_HEADER_SIG));
+            try!(w.write_le_u16(VERSION_MADE_BY));
+            try!(w.write_le_u16(VERSION_NEEDED));
+            try!(w.write_le_u16(flags));
+            try!(w.write_le_u16(header.method.to_u16()));
+            try!(w.write_le_u16(time));
+            try!(w.write_le_u16(date));
+            try!(w.write_le_u32(header.crc32));
+            try!(w.write_le_u32(header.compressed_size as u32));
+            try!(w.write_le_u32(header.size as u32));
+            try!(w.write_le_u16(name.len() as u16));
+            // extra field, comment, disk number and internal attributes
+            try!(w.write_le_u16(0));
+            try!(w.write_le_u16(0));
+            try!(w.write_le_u16(0));
+            try!(w.write_le_u16(0));
+            try!(w.write_le_u32(header.external_attributes()));
+            try!(w.write_le_u32(header.offset as u32));
+            try!(w.write(name.as_slice()));
+        }
+        let size = self.inner.count - start;
+        let entries = self.headers.len() as u16;
+        {
+            let w = &mut self.inner;
+            try!(w.write_le_u32(END_SIG));
+            // this disk and the disk the central directory starts on
+            try!(w.write_le_u16(0));
+            try!(w.write_le_u16(0));
+            try!(w.write_le_u16(entries));
+            try!(w.write_le_u16(entries));
+            try!(w.write_le_u32(size as u32));
+            try!(w.write_le_u32(start as u32));
+            // comment length
+            try!(w.write_le_u16(0));
+        }
+        Ok(self.inner.inner)
+    }
+}
+
+// Copy everything from `r` to `w`, adding it to `crc` and `size`.
+fn copy_checked<W: Writer>(r: &mut Reader, w: &mut W, crc: &mut Crc32,
+                           size: &mut u64) -> IoResult<()> {
+    let mut buf = [0u8, ..8192];
+    loop {
+        let n = match r.read(buf) {
+            Ok(n) => n,
+            Err(ref e) if e.kind == EndOfFile => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        crc.update(buf.slice_to(n));
+        *size += n as u64;
+        try!(w.write(buf.slice_to(n)));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{MemReader, MemWriter, TempDir, InvalidInput};
+    use std::io::fs;
+    use std::io::fs::File;
+    use std::io::util::NullReader;
+    use super::{ZipArchive, ZipWriter, Header, Stored, Deflated};
+    use super::{dos_date_time, unix_time};
+
+    // Written by Python's zipfile: "hello.txt" stored, the directory "dir/"
+    // and "dir/data.txt" deflated, from a Unix system
+    static PYTHON_ZIP: &'static [u8] = &[
+        0x50, 0x4b, 0x03, 0x04, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0xc0, 0x63,
+        0xa1, 0x44, 0x2d, 0x3b, 0x08, 0xaf, 0x0c, 0x00, 0x00, 0x00, 0x0c, 0x00,
+        0x00, 0x00, 0x09, 0x00, 0x00, 0x00, 0x68, 0x65, 0x6c, 0x6c, 0x6f, 0x2e,
+        0x74, 0x78, 0x74, 0x68, 0x65, 0x6c, 0x6c, 0x6f, 0x20, 0x77, 0x6f, 0x72,
+        0x6c, 0x64, 0x0a, 0x50, 0x4b, 0x03, 0x04, 0x14, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0xc0, 0x63, 0xa1, 0x44, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x64, 0x69, 0x72,
+        0x2f, 0x50, 0x4b, 0x03, 0x04, 0x14, 0x00, 0x00, 0x00, 0x08, 0x00, 0xc1,
+        0x63, 0xa1, 0x44, 0x1c, 0xcb, 0xe9, 0x77, 0x09, 0x00, 0x00, 0x00, 0x3d,
+        0x00, 0x00, 0x00, 0x0c, 0x00, 0x00, 0x00, 0x64, 0x69, 0x72, 0x2f, 0x64,
+        0x61, 0x74, 0x61, 0x2e, 0x74, 0x78, 0x74, 0x4b, 0x4c, 0x4a, 0x4e, 0x24,
+        0x17, 0x71, 0x01, 0x00, 0x50, 0x4b, 0x01, 0x02, 0x14, 0x03, 0x14, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0xc0, 0x63, 0xa1, 0x44, 0x2d, 0x3b, 0x08, 0xaf,
+        0x0c, 0x00, 0x00, 0x00, 0x0c, 0x00, 0x00, 0x00, 0x09, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xa4, 0x81, 0x00, 0x00,
+        0x00, 0x00, 0x68, 0x65, 0x6c, 0x6c, 0x6f, 0x2e, 0x74, 0x78, 0x74, 0x50,
+        0x4b, 0x01, 0x02, 0x14, 0x03, 0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0xc0,
+        0x63, 0xa1, 0x44, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x10, 0x00, 0xed, 0x41, 0x33, 0x00, 0x00, 0x00, 0x64, 0x69, 0x72,
+        0x2f, 0x50, 0x4b, 0x01, 0x02, 0x14, 0x03, 0x14, 0x00, 0x00, 0x00, 0x08,
+        0x00, 0xc1, 0x63, 0xa1, 0x44, 0x1c, 0xcb, 0xe9, 0x77, 0x09, 0x00, 0x00,
+        0x00, 0x3d, 0x00, 0x00, 0x00, 0x0c, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x81, 0x55, 0x00, 0x00, 0x00, 0x64,
+        0x69, 0x72, 0x2f, 0x64, 0x61, 0x74, 0x61, 0x2e, 0x74, 0x78, 0x74, 0x50,
+        0x4b, 0x05, 0x06, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x03, 0x00, 0xa3,
+        0x00, 0x00, 0x00, 0x88, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    // 2014-05-01 12:30:00 UTC
+    static MTIME: u64 = 1398947400;
+
+    fn python_zip() -> ZipArchive<MemReader> {
+        ZipArchive::new(MemReader::new(Vec::from_slice(PYTHON_ZIP))).unwrap()
+    }
+
+    #[test]
+    fn test_dos_time() {
+        assert_eq!(dos_date_time(MTIME), (0x44a1, 0x63c0));
+        assert_eq!(unix_time(0x44a1, 0x63c0), MTIME);
+        assert_eq!(unix_time(0x44a1, 0x63c1), MTIME + 2);
+        assert_eq!(dos_date_time(0), (0x21, 0));
+        assert_eq!(unix_time(0x21, 0), 315532800);
+    }
+
+    #[test]
+    fn test_read_python() {
+        let mut archive = python_zip();
+        assert_eq!(archive.len(), 3);
+        {
+            let headers = archive.headers();
+            assert!(headers[0].path == Path::new("hello.txt"));
+            assert_eq!(headers[0].method, Stored);
+            assert_eq!(headers[0].perm, 0o644);
+            assert_eq!(headers[0].mtime, MTIME);
+            assert!(headers[1].is_dir);
+            assert_eq!(headers[1].perm, 0o755);
+            assert_eq!(headers[2].method, Deflated);
+            assert_eq!(headers[2].perm, 0o600);
+            assert_eq!(headers[2].size, 61);
+            assert_eq!(headers[2].mtime, MTIME + 2);
+        }
+
+        let i = archive.find(&Path::new("dir/data.txt")).unwrap();
+        let data = archive.open(i).unwrap().read_to_end().unwrap();
+        assert_eq!(data.as_slice(), bytes!("abcabcabcabcabcabcabcabcabcabcabcabcabcabcabcabcabcabcabcabc\n"));
+        let data = archive.open(0).unwrap().read_to_end().unwrap();
+        assert_eq!(data.as_slice(), bytes!("hello world\n"));
+        assert!(archive.find(&Path::new("missing")).is_none());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut big = Vec::new();
+        for i in range(0u, 10000) {
+            big.push_all(format!("{}\n", i % 123).as_bytes());
+        }
+
+        let mut writer = ZipWriter::new(MemWriter::new());
+        let mut header = Header::new(Path::new("a/big.txt"));
+        header.perm = 0o600;
+        header.mtime = MTIME;
+        writer.append(&header, &mut MemReader::new(big.clone())).unwrap();
+        let mut header = Header::new(Path::new("stored"));
+        header.method = Stored;
+        writer.append(&header, &mut MemReader::new(vec!(1, 2, 3))).unwrap();
+        writer.append(&Header::new_dir(Path::new("a/empty")), &mut NullReader).unwrap();
+        writer.append(&Header::new(Path::new("nothing")), &mut NullReader).unwrap();
+        let zipfile = writer.finish().unwrap().unwrap();
+
+        let mut archive = ZipArchive::new(MemReader::new(zipfile)).unwrap();
+        assert_eq!(archive.len(), 4);
+        {
+            let headers = archive.headers();
+            assert!(headers[0].path == Path::new("a/big.txt"));
+            assert_eq!(headers[0].perm, 0o600);
+            assert_eq!(headers[0].mtime, MTIME);
+            assert_eq!(headers[0].size, big.len() as u64);
+            assert!(headers[0].compressed_size < headers[0].size / 4);
+            assert_eq!(headers[1].compressed_size, 3);
+            assert!(headers[2].path == Path::new("a/empty"));
+            assert!(headers[2].is_dir);
+        }
+        assert!(archive.open(0).unwrap().read_to_end().unwrap() == big);
+        assert_eq!(archive.open(1).unwrap().read_to_end().unwrap(), vec!(1, 2, 3));
+        assert_eq!(archive.open(3).unwrap().read_to_end().unwrap(), vec!());
+    }
+
+    #[test]
+    fn test_corrupt() {
+        assert_eq!(ZipArchive::new(MemReader::new(vec!(1, 2, 3))).err().unwrap().desc,
+                   "not a zip file");
+
+        // a bit flipped in the stored data of "hello.txt"
+        let mut data = Vec::from_slice(PYTHON_ZIP);
+        *data.get_mut(40) ^= 1;
+        let mut archive = ZipArchive::new(MemReader::new(data)).unwrap();
+        let err = archive.open(0).unwrap().read_to_end().unwrap_err();
+        assert_eq!(err.kind, InvalidInput);
+        assert_eq!(err.desc, "zip entry checksum mismatch");
+
+        // the end of central directory record cut off
+        let truncated = Vec::from_slice(PYTHON_ZIP.slice_to(PYTHON_ZIP.len() - 4));
+        assert!(ZipArchive::new(MemReader::new(truncated)).is_err());
+    }
+
+    #[test]
+    fn test_extract() {
+        let src = TempDir::new("zip-src").unwrap();
+        let dst = TempDir::new("zip-dst").unwrap();
+        fs::mkdir(&src.path().join("sub"), 0o755).unwrap();
+        File::create(&src.path().join("sub/file.txt")).write(bytes!("contents")).unwrap();
+        fs::chmod(&src.path().join("sub/file.txt"), 0o640).unwrap();
+        fs::change_file_times(&src.path().join("sub/file.txt"), MTIME * 1000, MTIME * 1000)
+            .unwrap();
+
+        let mut writer = ZipWriter::new(MemWriter::new());
+        writer.append_dir_all(&Path::new("pkg"), src.path()).unwrap();
+        let zipfile = writer.finish().unwrap().unwrap();
+
+        let mut archive = ZipArchive::new(MemReader::new(zipfile)).unwrap();
+        {
+            let names: Vec<Option<&str>> = archive.headers().iter().map(|h| {
+                h.path.as_str()
+            }).collect();
+            assert_eq!(names, vec!(Some("pkg"), Some("pkg/sub"), Some("pkg/sub/file.txt")));
+        }
+        archive.extract(dst.path()).unwrap();
+
+        let file = dst.path().join("pkg/sub/file.txt");
+        assert_eq!(File::open(&file).read_to_end().unwrap().as_slice(), bytes!("contents"));
+        let stat = fs::stat(&file).unwrap();
+        assert_eq!(stat.perm, 0o640);
+        assert_eq!(stat.modified, MTIME * 1000);
+
+        let mut writer = ZipWriter::new(MemWriter::new());
+        writer.append(&Header::new(Path::new("/abs")), &mut NullReader).unwrap();
+        let zipfile = writer.finish().unwrap().unwrap();
+        let mut archive = ZipArchive::new(MemReader::new(zipfile)).unwrap();
+        assert!(archive.extract(dst.path()).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// Copyright 2013-2014 The Rust Project Developers. See the COPYRIGHT
 // file at the top-level directory of this distribution and at
 // http://rust-lang.org/COPYRIGHT.
 //
@@ -7,900 +7,1543 @@
 // <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
-//
-// btree.rs
-//
-
-//! Starting implementation of a btree for rust.
-//! Structure inspired by github user davidhalperin's gist.
 
-///A B-tree contains a root node (which contains a vector of elements),
-///a length (the height of the tree), and lower and upper bounds on the
-///number of elements that a given node can contain.
+//! An ordered map and set based on a B-Tree.
+//!
+//! A B-Tree stores many keys in each node, so a lookup visits far fewer
+//! allocations than in a binary tree such as `TreeMap`. The keys of a node are
+//! kept in a contiguous buffer separate from the values, so searching a node
+//! is a linear scan over a few cache lines.
 
+use std::cast;
+use std::cmp::Ordering;
 use std::fmt;
 use std::fmt::Show;
-
-#[allow(missing_doc)]
-pub struct BTree<K, V> {
+use std::iter::Peekable;
+use std::kinds::marker;
+use std::mem::replace;
+use std::vec;
+
+// Every node other than the root holds between `B - 1` and `2 * B - 1` keys,
+// and every internal node has exactly one more edge than it has keys.
+static B: uint = 6;
+static MIN_LEN: uint = B - 1;
+static CAPACITY: uint = 2 * B - 1;
+
+/// An ordered map implemented as a B-Tree
+///
+/// # Example
+///
+/// ```rust
+/// use collections::BTreeMap;
+///
+/// let mut map = BTreeMap::new();
+/// map.insert(3, "c");
+/// map.insert(1, "a");
+/// map.insert(2, "b");
+///
+/// assert_eq!(map.find(&2), Some(&"b"));
+/// let keys: Vec<int> = map.iter().map(|(&k, _)| k).collect();
+/// assert_eq!(keys, vec!(1, 2, 3));
+/// ```
+#[deriving(Clone)]
+pub struct BTreeMap<K, V> {
     root: Node<K, V>,
-    len: uint,
-    lower_bound: uint,
-    upper_bound: uint
+    length: uint,
 }
 
-impl<K: TotalOrd, V> BTree<K, V> {
+#[deriving(Clone)]
+struct Node<K, V> {
+    keys: Vec<K>,
+    vals: Vec<V>,
+    // Empty for leaves
+    edges: Vec<~Node<K, V>>,
+}
 
-    ///Returns new BTree with root node (leaf) and user-supplied lower bound
-    ///The lower bound applies to every node except the root node.
-    pub fn new(k: K, v: V, lb: uint) -> BTree<K, V> {
-        BTree {
-            root: Node::new_leaf(vec!(LeafElt::new(k, v))),
-            len: 1,
-            lower_bound: lb,
-            upper_bound: 2 * lb
+impl<K: Eq + TotalOrd, V: Eq> Eq for BTreeMap<K, V> {
+    fn eq(&self, other: &BTreeMap<K, V>) -> bool {
+        self.len() == other.len() &&
+            self.iter().zip(other.iter()).all(|(a, b)| a == b)
+    }
+}
+
+// Lexicographical comparison
+impl<K: Ord + TotalOrd, V: Ord> Ord for BTreeMap<K, V> {
+    fn lt(&self, other: &BTreeMap<K, V>) -> bool {
+        for ((key_a, value_a), (key_b, value_b)) in self.iter().zip(other.iter()) {
+            if *key_a < *key_b { return true; }
+            if *key_a > *key_b { return false; }
+            if *value_a < *value_b { return true; }
+            if *value_a > *value_b { return false; }
         }
+
+        self.len() < other.len()
     }
+}
 
-    ///Helper function for clone: returns new BTree with supplied root node,
-    ///length, and lower bound.  For use when the length is known already.
-    fn new_with_node_len(n: Node<K, V>,
-                         length: uint,
-                         lb: uint) -> BTree<K, V> {
-        BTree {
-            root: n,
-            len: length,
-            lower_bound: lb,
-            upper_bound: 2 * lb
+impl<K: TotalOrd + Show, V: Show> Show for BTreeMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f.buf, r"\{"));
+
+        for (i, (k, v)) in self.iter().enumerate() {
+            if i != 0 { try!(write!(f.buf, ", ")); }
+            try!(write!(f.buf, "{}: {}", *k, *v));
         }
+
+        write!(f.buf, r"\}")
     }
 }
 
-//We would probably want to remove the dependence on the Clone trait in the future.
-//It is here as a crutch to ensure values can be passed around through the tree's nodes
-//especially during insertions and deletions.
-impl<K: Clone + TotalOrd, V: Clone> BTree<K, V> {
-    ///Returns the value of a given key, which may not exist in the tree.
-    ///Calls the root node's get method.
-    pub fn get(self, k: K) -> Option<V> {
-        return self.root.get(k);
+impl<K: TotalOrd, V> Container for BTreeMap<K, V> {
+    fn len(&self) -> uint { self.length }
+}
+
+impl<K: TotalOrd, V> Mutable for BTreeMap<K, V> {
+    fn clear(&mut self) {
+        self.root = Node::new();
+        self.length = 0;
     }
+}
 
-    ///An insert method that uses the clone() feature for support.
-    pub fn insert(mut self, k: K, v: V) -> BTree<K, V> {
-        let (a, b) = self.root.clone().insert(k, v, self.upper_bound.clone());
-        if b {
-            match a.clone() {
-                LeafNode(leaf) => {
-                    self.root = Node::new_leaf(leaf.clone().elts);
-                }
-                BranchNode(branch) => {
-                    self.root = Node::new_branch(branch.clone().elts,
-                                                 branch.clone().rightmost_child);
+impl<K: TotalOrd, V> Map<K, V> for BTreeMap<K, V> {
+    fn find<'a>(&'a self, key: &K) -> Option<&'a V> {
+        let mut node = &self.root;
+        loop {
+            match node.search(key) {
+                Ok(i) => return Some(node.vals.get(i)),
+                Err(i) => {
+                    if node.is_leaf() { return None }
+                    node = &**node.edges.get(i);
                 }
             }
         }
-        self
     }
 }
 
-impl<K: Clone + TotalOrd, V: Clone> Clone for BTree<K, V> {
-    ///Implements the Clone trait for the BTree.
-    ///Uses a helper function/constructor to produce a new BTree.
-    fn clone(&self) -> BTree<K, V> {
-        BTree::new_with_node_len(self.root.clone(), self.len, self.lower_bound)
+impl<K: TotalOrd, V> MutableMap<K, V> for BTreeMap<K, V> {
+    fn find_mut<'a>(&'a mut self, key: &K) -> Option<&'a mut V> {
+        self.root.find_mut(key)
+    }
+
+    fn swap(&mut self, key: K, value: V) -> Option<V> {
+        match self.root.insert(key, value) {
+            Replaced(old) => return Some(old),
+            Fit => {}
+            Split(key, value, right) => {
+                // The root overflowed, so the tree grows by one level
+                let left = replace(&mut self.root, Node::new());
+                self.root.keys.push(key);
+                self.root.vals.push(value);
+                self.root.edges.push(~left);
+                self.root.edges.push(~right);
+            }
+        }
+        self.length += 1;
+        None
     }
-}
 
-impl<K: TotalOrd, V: TotalEq> Eq for BTree<K, V> {
-    fn eq(&self, other: &BTree<K, V>) -> bool {
-        self.root.cmp(&other.root) == Equal
+    fn pop(&mut self, key: &K) -> Option<V> {
+        let ret = self.root.remove(key);
+        if ret.is_some() {
+            self.length -= 1;
+            // The root lost its last key by merging its only two children, so
+            // the tree shrinks by one level
+            if self.root.len() == 0 && !self.root.is_leaf() {
+                let child = self.root.edges.pop().unwrap();
+                self.root = *child;
+            }
+        }
+        ret
     }
 }
 
-impl<K: TotalOrd, V: TotalEq> TotalEq for BTree<K, V> {}
-
-impl<K: TotalOrd, V: TotalEq> Ord for BTree<K, V> {
-    fn lt(&self, other: &BTree<K, V>) -> bool {
-        self.cmp(other) == Less
+impl<K: TotalOrd, V> BTreeMap<K, V> {
+    /// Create an empty BTreeMap
+    pub fn new() -> BTreeMap<K, V> {
+        BTreeMap { root: Node::new(), length: 0 }
     }
-}
 
-impl<K: TotalOrd, V: TotalEq> TotalOrd for BTree<K, V> {
-    ///Returns an ordering based on the root nodes of each BTree.
-    fn cmp(&self, other: &BTree<K, V>) -> Ordering {
-        self.root.cmp(&other.root)
+    /// Get a lazy iterator over the key-value pairs in the map, in ascending
+    /// order of keys.
+    pub fn iter<'a>(&'a self) -> Entries<'a, K, V> {
+        Entries::new(Cursor::new(&self.root, true), self.length)
     }
-}
 
-impl<K: fmt::Show + TotalOrd, V: fmt::Show> fmt::Show for BTree<K, V> {
-    ///Returns a string representation of the BTree
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.root.fmt(f)
+    /// Get a lazy iterator over the key-value pairs in the map, in descending
+    /// order of keys.
+    pub fn rev_iter<'a>(&'a self) -> Entries<'a, K, V> {
+        Entries::new(Cursor::new(&self.root, false), self.length)
     }
-}
 
+    /// Get a lazy iterator over the key-value pairs in the map, in ascending
+    /// order of keys, with the values being mutable.
+    pub fn mut_iter<'a>(&'a mut self) -> MutEntries<'a, K, V> {
+        MutEntries { iter: Entries::new(Cursor::new(&self.root, true), self.length) }
+    }
 
-//Node types
-//A node is either a LeafNode or a BranchNode, which contain either a Leaf or a Branch.
-//Branches contain BranchElts, which contain a left child (another node) and a key-value
-//pair.  Branches also contain the rightmost child of the elements in the array.
-//Leaves contain LeafElts, which do not have children.
-enum Node<K, V> {
-    LeafNode(Leaf<K, V>),
-    BranchNode(Branch<K, V>)
-}
+    /// Get a lazy iterator over the key-value pairs in the map, in descending
+    /// order of keys, with the values being mutable.
+    pub fn mut_rev_iter<'a>(&'a mut self) -> MutEntries<'a, K, V> {
+        MutEntries { iter: Entries::new(Cursor::new(&self.root, false), self.length) }
+    }
 
+    /// Get a lazy iterator that consumes the map, in ascending order of keys.
+    pub fn move_iter(self) -> MoveEntries<K, V> {
+        let BTreeMap { root, length } = self;
+        let mut iter = MoveEntries { stack: Vec::new(), remaining: length };
+        iter.descend(root);
+        iter
+    }
 
-//Node functions/methods
-impl<K: TotalOrd, V> Node<K, V> {
-    ///Creates a new leaf node given a vector of elements.
-    fn new_leaf(vec: Vec<LeafElt<K, V>>) -> Node<K,V> {
-        LeafNode(Leaf::new(vec))
+    /// Return a lazy iterator starting at the first key-value pair whose key
+    /// is not less than `k`.
+    pub fn lower_bound<'a>(&'a self, k: &K) -> Entries<'a, K, V> {
+        Entries::bounded(Cursor::seek(&self.root, k, true), None, self.length)
     }
 
-    ///Creates a new branch node given a vector of an elements and a pointer to a rightmost child.
-    fn new_branch(vec: Vec<BranchElt<K, V>>, right: ~Node<K, V>) -> Node<K, V> {
-        BranchNode(Branch::new(vec, right))
+    /// Return a lazy iterator starting at the first key-value pair whose key
+    /// is greater than `k`.
+    pub fn upper_bound<'a>(&'a self, k: &K) -> Entries<'a, K, V> {
+        Entries::bounded(Cursor::seek(&self.root, k, false), None, self.length)
     }
 
-    ///Determines whether the given Node contains a Branch or a Leaf.
-    ///Used in testing.
-    fn is_leaf(&self) -> bool {
-        match self {
-            &LeafNode(..) => true,
-            &BranchNode(..) => false
+    /// Return a lazy iterator over the key-value pairs whose keys lie in the
+    /// half-open interval `[min, max)`, in ascending order.
+    pub fn range<'a>(&'a self, min: &K, max: &K) -> Entries<'a, K, V> {
+        if min.cmp(max) != Less {
+            return Entries::bounded(Cursor::empty(), None, 0)
         }
+        let end = Cursor::seek(&self.root, max, true).next();
+        Entries::bounded(Cursor::seek(&self.root, min, true), end, self.length)
     }
 
-    ///A binary search function for Nodes.
-    ///Calls either the Branch's or the Leaf's bsearch function.
-    fn bsearch_node(&self, k: K) -> Option<uint> {
-         match self {
-             &LeafNode(ref leaf) => leaf.bsearch_leaf(k),
-             &BranchNode(ref branch) => branch.bsearch_branch(k)
-         }
-     }
-}
+    /// Return a lazy iterator over the key-value pairs whose keys lie in the
+    /// half-open interval `[min, max)`, in ascending order, with the values
+    /// being mutable.
+    pub fn mut_range<'a>(&'a mut self, min: &K, max: &K) -> MutEntries<'a, K, V> {
+        MutEntries { iter: self.range(min, max) }
+    }
 
-impl<K: Clone + TotalOrd, V: Clone> Node<K, V> {
-    ///Returns the corresponding value to the provided key.
-    ///get() is called in different ways on a branch or a leaf.
-    fn get(&self, k: K) -> Option<V> {
-        match *self {
-            LeafNode(ref leaf) => return leaf.get(k),
-            BranchNode(ref branch) => return branch.get(k)
-        }
+    /// Return the key-value pair with the smallest key, if any.
+    pub fn first<'a>(&'a self) -> Option<(&'a K, &'a V)> {
+        self.iter().next()
     }
 
-    ///Matches on the Node, then performs and returns the appropriate insert method.
-    fn insert(self, k: K, v: V, ub: uint) -> (Node<K, V>, bool) {
-        match self {
-            LeafNode(leaf) => leaf.insert(k, v, ub),
-            BranchNode(branch) => branch.insert(k, v, ub)
-        }
+    /// Return the key-value pair with the largest key, if any.
+    pub fn last<'a>(&'a self) -> Option<(&'a K, &'a V)> {
+        self.rev_iter().next()
     }
 }
 
-impl<K: Clone + TotalOrd, V: Clone> Clone for Node<K, V> {
-    ///Returns a new node based on whether or not it is a branch or a leaf.
-    fn clone(&self) -> Node<K, V> {
-        match *self {
-            LeafNode(ref leaf) => {
-                Node::new_leaf(leaf.elts.clone())
-            }
-            BranchNode(ref branch) => {
-                Node::new_branch(branch.elts.clone(),
-                                 branch.rightmost_child.clone())
+enum InsertResult<K, V> {
+    Replaced(V),
+    Fit,
+    // The node overflowed and was split around the key and value, which must
+    // be inserted into the parent along with the new right sibling
+    Split(K, V, Node<K, V>),
+}
+
+impl<K, V> Node<K, V> {
+    fn new() -> Node<K, V> {
+        Node { keys: Vec::new(), vals: Vec::new(), edges: Vec::new() }
+    }
+
+    #[inline]
+    fn len(&self) -> uint { self.keys.len() }
+
+    #[inline]
+    fn is_leaf(&self) -> bool { self.edges.is_empty() }
+}
+
+impl<K: TotalOrd, V> Node<K, V> {
+    /// Returns `Ok` of the index of `key` in this node, or `Err` of the index
+    /// of the edge below which it would be found.
+    #[inline]
+    fn search(&self, key: &K) -> Result<uint, uint> {
+        for (i, k) in self.keys.iter().enumerate() {
+            match key.cmp(k) {
+                Greater => {}
+                Equal => return Ok(i),
+                Less => return Err(i),
             }
         }
+        Err(self.keys.len())
     }
-}
 
-impl<K: TotalOrd, V: TotalEq> Eq for Node<K, V> {
-    fn eq(&self, other: &Node<K, V>) -> bool {
-        match *self{
-            BranchNode(ref branch) => {
-                if other.is_leaf() {
-                    return false;
-                }
-                match *other {
-                    BranchNode(ref branch2) => branch.cmp(branch2) == Equal,
-                    LeafNode(..) => false
-                }
+    fn find_mut<'a>(&'a mut self, key: &K) -> Option<&'a mut V> {
+        match self.search(key) {
+            Ok(i) => Some(self.vals.get_mut(i)),
+            Err(i) => {
+                if self.is_leaf() { return None }
+                self.edges.get_mut(i).find_mut(key)
             }
-            LeafNode(ref leaf) => {
-                match *other {
-                    LeafNode(ref leaf2) => leaf.cmp(leaf2) == Equal,
-                    BranchNode(..) => false
+        }
+    }
+
+    fn insert(&mut self, key: K, value: V) -> InsertResult<K, V> {
+        match self.search(&key) {
+            Ok(i) => return Replaced(replace(self.vals.get_mut(i), value)),
+            Err(i) => {
+                if self.is_leaf() {
+                    self.keys.insert(i, key);
+                    self.vals.insert(i, value);
+                } else {
+                    match self.edges.get_mut(i).insert(key, value) {
+                        Split(key, value, right) => {
+                            self.keys.insert(i, key);
+                            self.vals.insert(i, value);
+                            self.edges.insert(i + 1, ~right);
+                        }
+                        result => return result,
+                    }
                 }
             }
         }
+        if self.len() > CAPACITY { self.split() } else { Fit }
     }
-}
-
-impl<K: TotalOrd, V: TotalEq> TotalEq for Node<K, V> {}
 
-impl<K: TotalOrd, V: TotalEq> Ord for Node<K, V> {
-    fn lt(&self, other: &Node<K, V>) -> bool {
-        self.cmp(other) == Less
+    /// Splits a node which has overflowed by one key, keeping the first `B`
+    /// keys and moving the last `B - 1` into a new right sibling.
+    fn split(&mut self) -> InsertResult<K, V> {
+        let keys = split_off(&mut self.keys, B + 1);
+        let vals = split_off(&mut self.vals, B + 1);
+        let edges = if self.is_leaf() {
+            Vec::new()
+        } else {
+            split_off(&mut self.edges, B + 1)
+        };
+        let key = self.keys.pop().unwrap();
+        let value = self.vals.pop().unwrap();
+        Split(key, value, Node { keys: keys, vals: vals, edges: edges })
     }
-}
 
-impl<K: TotalOrd, V: TotalEq> TotalOrd for Node<K, V> {
-    ///Implementation of TotalOrd for Nodes.
-    fn cmp(&self, other: &Node<K, V>) -> Ordering {
-        match *self {
-            LeafNode(ref leaf) => {
-                match *other {
-                    LeafNode(ref leaf2) => leaf.cmp(leaf2),
-                    BranchNode(_) => Less
+    fn remove(&mut self, key: &K) -> Option<V> {
+        match self.search(key) {
+            Ok(i) => {
+                if self.is_leaf() {
+                    self.keys.remove(i);
+                    return self.vals.remove(i)
                 }
+                // Replace the entry with its predecessor, which is the
+                // largest entry of the subtree to its left
+                let (k, v) = self.edges.get_mut(i).pop_max();
+                replace(self.keys.get_mut(i), k);
+                let ret = replace(self.vals.get_mut(i), v);
+                self.fix_child(i);
+                Some(ret)
             }
-            BranchNode(ref branch) => {
-                match *other {
-                    BranchNode(ref branch2) => branch.cmp(branch2),
-                    LeafNode(_) => Greater
-                }
+            Err(i) => {
+                if self.is_leaf() { return None }
+                let ret = self.edges.get_mut(i).remove(key);
+                if ret.is_some() { self.fix_child(i) }
+                ret
             }
         }
     }
-}
 
-impl<K: fmt::Show + TotalOrd, V: fmt::Show> fmt::Show for Node<K, V> {
-    ///Returns a string representation of a Node.
-    ///Will iterate over the Node and show "Key: x, value: y, child: () // "
-    ///for all elements in the Node. "Child" only exists if the Node contains
-    ///a branch.
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            LeafNode(ref leaf) => leaf.fmt(f),
-            BranchNode(ref branch) => branch.fmt(f),
+    /// Removes the largest entry of the subtree rooted at this node, which
+    /// must not be empty.
+    fn pop_max(&mut self) -> (K, V) {
+        if self.is_leaf() {
+            return (self.keys.pop().unwrap(), self.vals.pop().unwrap())
         }
+        let last = self.edges.len() - 1;
+        let ret = self.edges.get_mut(last).pop_max();
+        self.fix_child(last);
+        ret
     }
-}
 
+    /// Restores the minimum occupancy of `edges[i]` after a removal below it,
+    /// either by rotating an entry in from a sibling or by merging with one.
+    fn fix_child(&mut self, i: uint) {
+        if self.edges.get(i).len() >= MIN_LEN { return }
+        if i > 0 && self.edges.get(i - 1).len() > MIN_LEN {
+            self.steal_left(i);
+        } else if i + 1 < self.edges.len() && self.edges.get(i + 1).len() > MIN_LEN {
+            self.steal_right(i);
+        } else if i > 0 {
+            self.merge(i - 1);
+        } else {
+            self.merge(i);
+        }
+    }
+
+    fn steal_left(&mut self, i: uint) {
+        let (k, v, edge) = {
+            let left = self.edges.get_mut(i - 1);
+            (left.keys.pop().unwrap(), left.vals.pop().unwrap(), left.edges.pop())
+        };
+        let k = replace(self.keys.get_mut(i - 1), k);
+        let v = replace(self.vals.get_mut(i - 1), v);
+        let child = self.edges.get_mut(i);
+        child.keys.unshift(k);
+        child.vals.unshift(v);
+        match edge {
+            Some(edge) => child.edges.unshift(edge),
+            None => {}
+        }
+    }
 
-//A leaf is a vector with elements that contain no children.  A leaf also
-//does not contain a rightmost child.
-struct Leaf<K, V> {
-    elts: Vec<LeafElt<K, V>>
+    fn steal_right(&mut self, i: uint) {
+        let (k, v, edge) = {
+            let right = self.edges.get_mut(i + 1);
+            (right.keys.shift().unwrap(), right.vals.shift().unwrap(), right.edges.shift())
+        };
+        let k = replace(self.keys.get_mut(i), k);
+        let v = replace(self.vals.get_mut(i), v);
+        let child = self.edges.get_mut(i);
+        child.keys.push(k);
+        child.vals.push(v);
+        match edge {
+            Some(edge) => child.edges.push(edge),
+            None => {}
+        }
+    }
+
+    /// Merges `keys[i]` and `edges[i + 1]` into `edges[i]`.
+    fn merge(&mut self, i: uint) {
+        let k = self.keys.remove(i).unwrap();
+        let v = self.vals.remove(i).unwrap();
+        let ~Node { keys, vals, edges } = self.edges.remove(i + 1).unwrap();
+        let left = self.edges.get_mut(i);
+        left.keys.push(k);
+        left.vals.push(v);
+        left.keys.push_all_move(keys);
+        left.vals.push_all_move(vals);
+        left.edges.push_all_move(edges);
+    }
 }
 
-//Vector of values with children, plus a rightmost child (greater than all)
-struct Branch<K, V> {
-    elts: Vec<BranchElt<K,V>>,
-    rightmost_child: ~Node<K, V>
+/// Moves the elements of `v` from index `at` onwards into a new vector.
+fn split_off<T>(v: &mut Vec<T>, at: uint) -> Vec<T> {
+    let mut tail = Vec::with_capacity(CAPACITY + 1);
+    while v.len() > at {
+        tail.push(v.pop().unwrap());
+    }
+    tail.reverse();
+    tail
 }
 
+/// A position within a tree, used to implement the borrowed iterators.
+///
+/// The stack holds the path from the root to the current node, each node
+/// paired with the index of the next key to yield from it when moving
+/// forward, or one past that index when moving backward.
+struct Cursor<K, V> {
+    stack: Vec<(*Node<K, V>, uint)>,
+    forward: bool,
+}
 
-impl<K: TotalOrd, V> Leaf<K, V> {
-    ///Creates a new Leaf from a vector of LeafElts.
-    fn new(vec: Vec<LeafElt<K, V>>) -> Leaf<K, V> {
-        Leaf {
-            elts: vec
+impl<K: TotalOrd, V> Cursor<K, V> {
+    /// Creates a forward cursor positioned at the first key not less than
+    /// (if `inclusive`) or greater than `key`.
+    fn seek(root: &Node<K, V>, key: &K, inclusive: bool) -> Cursor<K, V> {
+        let mut cursor = Cursor::empty();
+        let mut node = root;
+        loop {
+            let i = match node.search(key) {
+                Ok(i) if inclusive => {
+                    cursor.stack.push((node as *Node<K, V>, i));
+                    return cursor
+                }
+                Ok(i) => i + 1,
+                Err(i) => i,
+            };
+            cursor.stack.push((node as *Node<K, V>, i));
+            if node.is_leaf() { return cursor }
+            node = &**node.edges.get(i);
         }
     }
+}
+
+impl<K, V> Cursor<K, V> {
+    fn new(root: &Node<K, V>, forward: bool) -> Cursor<K, V> {
+        let mut cursor = Cursor { stack: Vec::new(), forward: forward };
+        cursor.descend(root);
+        cursor
+    }
 
-    ///Searches a leaf for a spot for a new element using a binary search.
-    ///Returns None if the element is already in the vector.
-    fn bsearch_leaf(&self, k: K) -> Option<uint> {
-        let mut high: uint = self.elts.len();
-        let mut low: uint = 0;
-        let mut midpoint: uint = (high - low) / 2 ;
-        if midpoint == high {
-            midpoint = 0;
+    fn empty() -> Cursor<K, V> {
+        Cursor { stack: Vec::new(), forward: true }
+    }
+
+    /// Pushes `node` and the path from it to its first key (or last, when
+    /// moving backward).
+    fn descend(&mut self, mut node: &Node<K, V>) {
+        loop {
+            let i = if self.forward { 0 } else { node.len() };
+            self.stack.push((node as *Node<K, V>, i));
+            if node.is_leaf() { return }
+            node = &**node.edges.get(i);
         }
+    }
+
+    /// Advances the cursor, returning the node and index of the entry it
+    /// moved over.
+    fn next(&mut self) -> Option<(*Node<K, V>, uint)> {
         loop {
-            let order = self.elts.get(midpoint).key.cmp(&k);
-            match order {
-                Equal => {
-                    return None;
-                }
-                Greater => {
-                    if midpoint > 0 {
-                        if self.elts.get(midpoint - 1).key.cmp(&k) == Less {
-                            return Some(midpoint);
-                        }
-                        else {
-                            let tmp = midpoint;
-                            midpoint = midpoint / 2;
-                            high = tmp;
-                            continue;
-                        }
-                    }
-                    else {
-                        return Some(0);
-                    }
-                }
-                Less => {
-                    if midpoint + 1 < self.elts.len() {
-                        if self.elts.get(midpoint + 1).key.cmp(&k) == Greater {
-                            return Some(midpoint);
-                        }
-                        else {
-                            let tmp = midpoint;
-                            midpoint = (high + low) / 2;
-                            low = tmp;
-                        }
-                    }
-                    else {
-                        return Some(self.elts.len());
-                    }
-                }
+            let (ptr, i) = match self.stack.last() {
+                Some(&top) => top,
+                None => return None,
+            };
+            let node = unsafe { &*ptr };
+            let (key, next) = if self.forward {
+                if i == node.len() { self.stack.pop(); continue }
+                (i, i + 1)
+            } else {
+                if i == 0 { self.stack.pop(); continue }
+                (i - 1, i - 1)
+            };
+            *self.stack.mut_last().unwrap() = (ptr, next);
+            // The entries between this key and the next one in the
+            // direction of travel live below the edge between them
+            if !node.is_leaf() {
+                self.descend(&**node.edges.get(if self.forward { key + 1 } else { key }));
             }
+            return Some((ptr, key))
         }
     }
 }
 
+/// Lazy iterator over the key-value pairs of a map
+pub struct Entries<'a, K, V> {
+    cursor: Cursor<K, V>,
+    // The entry at which iteration stops, for ranges
+    end: Option<(*Node<K, V>, uint)>,
+    remaining_min: uint,
+    remaining_max: uint,
+    marker: marker::ContravariantLifetime<'a>,
+}
 
-impl<K: Clone + TotalOrd, V: Clone> Leaf<K, V> {
-    ///Returns the corresponding value to the supplied key.
-    fn get(&self, k: K) -> Option<V> {
-        for s in self.elts.iter() {
-            let order = s.key.cmp(&k);
-            match order {
-                Equal => return Some(s.value.clone()),
-                _ => {}
-            }
+impl<'a, K, V> Entries<'a, K, V> {
+    fn new(cursor: Cursor<K, V>, len: uint) -> Entries<'a, K, V> {
+        Entries {
+            cursor: cursor,
+            end: None,
+            remaining_min: len,
+            remaining_max: len,
+            marker: marker::ContravariantLifetime::<'a>,
         }
-        return None;
     }
 
-    ///Uses clone() to facilitate inserting new elements into a tree.
-    fn insert(mut self, k: K, v: V, ub: uint) -> (Node<K, V>, bool) {
-        let to_insert = LeafElt::new(k, v);
-        let index: Option<uint> = self.bsearch_leaf(to_insert.clone().key);
-        //Check index to see whether we actually inserted the element into the vector.
-        match index {
-            //If the index is None, the new element already exists in the vector.
-            None => {
-                return (Node::new_leaf(self.clone().elts), false);
-            }
-            //If there is an index, insert at that index.
-            _ => {
-                if index.unwrap() >= self.elts.len() {
-                    self.elts.push(to_insert.clone());
-                }
-                else {
-                    self.elts.insert(index.unwrap(), to_insert.clone());
-                }
+    fn bounded(cursor: Cursor<K, V>, end: Option<(*Node<K, V>, uint)>,
+               len: uint) -> Entries<'a, K, V> {
+        let mut iter = Entries::new(cursor, len);
+        iter.end = end;
+        iter.remaining_min = 0;
+        iter
+    }
+
+    fn next_entry(&mut self) -> Option<(*Node<K, V>, uint)> {
+        if self.remaining_max == 0 { return None }
+        let entry = match self.cursor.next() {
+            None => return None,
+            Some(entry) => entry,
+        };
+        match self.end {
+            Some(end) if end == entry => {
+                self.remaining_max = 0;
+                self.remaining_min = 0;
+                return None
             }
+            _ => {}
         }
-        //If we have overfilled the vector (by making its size greater than the
-        //upper bound), we return a new Branch with one element and two children.
-        if self.elts.len() > ub {
-            let midpoint_opt = self.elts.remove(ub / 2);
-            let midpoint = midpoint_opt.unwrap();
-            let (left_leaf, right_leaf) = self.elts.partition(|le|
-                                                              le.key.cmp(&midpoint.key.clone())
-                                                              == Less);
-            let branch_return = Node::new_branch(vec!(BranchElt::new(midpoint.key.clone(),
-                                                                  midpoint.value.clone(),
-                                                             ~Node::new_leaf(left_leaf))),
-                                            ~Node::new_leaf(right_leaf));
-            return (branch_return, true);
+        self.remaining_max -= 1;
+        if self.remaining_min > 0 {
+            self.remaining_min -= 1;
         }
-        (Node::new_leaf(self.elts.clone()), true)
+        Some(entry)
     }
 }
 
-impl<K: Clone + TotalOrd, V: Clone> Clone for Leaf<K, V> {
-    ///Returns a new Leaf with the same elts.
-    fn clone(&self) -> Leaf<K, V> {
-        Leaf::new(self.elts.clone())
+impl<'a, K, V> Iterator<(&'a K, &'a V)> for Entries<'a, K, V> {
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        self.next_entry().map(|(ptr, i)| {
+            let node: &'a Node<K, V> = unsafe { &*ptr };
+            (node.keys.get(i), node.vals.get(i))
+        })
     }
-}
 
-impl<K: TotalOrd, V: TotalEq> Eq for Leaf<K, V> {
-    fn eq(&self, other: &Leaf<K, V>) -> bool {
-        self.elts == other.elts
+    #[inline]
+    fn size_hint(&self) -> (uint, Option<uint>) {
+        (self.remaining_min, Some(self.remaining_max))
     }
 }
 
-impl<K: TotalOrd, V: TotalEq> TotalEq for Leaf<K, V> {}
-
-impl<K: TotalOrd, V: TotalEq> Ord for Leaf<K, V> {
-    fn lt(&self, other: &Leaf<K, V>) -> bool {
-        self.cmp(other) == Less
-    }
+/// Lazy iterator over the key-value pairs of a map, with the values being
+/// mutable
+pub struct MutEntries<'a, K, V> {
+    iter: Entries<'a, K, V>,
 }
 
-impl<K: TotalOrd, V: TotalEq> TotalOrd for Leaf<K, V> {
-    ///Returns an ordering based on the first element of each Leaf.
-    fn cmp(&self, other: &Leaf<K, V>) -> Ordering {
-        if self.elts.len() > other.elts.len() {
-            return Greater;
-        }
-        if self.elts.len() < other.elts.len() {
-            return Less;
-        }
-        self.elts.get(0).cmp(other.elts.get(0))
+impl<'a, K, V> Iterator<(&'a K, &'a mut V)> for MutEntries<'a, K, V> {
+    fn next(&mut self) -> Option<(&'a K, &'a mut V)> {
+        self.iter.next_entry().map(|(ptr, i)| {
+            // The map is mutably borrowed for the lifetime of the iterator,
+            // and each value is handed out at most once.
+            let node: &'a mut Node<K, V> = unsafe { cast::transmute(ptr) };
+            (node.keys.get(i), node.vals.get_mut(i))
+        })
     }
-}
 
-
-impl<K: fmt::Show + TotalOrd, V: fmt::Show> fmt::Show for Leaf<K, V> {
-    ///Returns a string representation of a Leaf.
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for (i, s) in self.elts.iter().enumerate() {
-            if i != 0 { try!(write!(f.buf, " // ")) }
-            try!(write!(f.buf, "{}", *s))
-        }
-        Ok(())
+    #[inline]
+    fn size_hint(&self) -> (uint, Option<uint>) {
+        self.iter.size_hint()
     }
 }
 
+/// Lazy iterator over the key-value pairs of a map that consumes the map
+pub struct MoveEntries<K, V> {
+    stack: Vec<MoveNode<K, V>>,
+    remaining: uint,
+}
 
-impl<K: TotalOrd, V> Branch<K, V> {
-    ///Creates a new Branch from a vector of BranchElts and a rightmost child (a node).
-    fn new(vec: Vec<BranchElt<K, V>>, right: ~Node<K, V>) -> Branch<K, V> {
-        Branch {
-            elts: vec,
-            rightmost_child: right
-        }
-    }
+struct MoveNode<K, V> {
+    keys: vec::MoveItems<K>,
+    vals: vec::MoveItems<V>,
+    edges: vec::MoveItems<~Node<K, V>>,
+}
 
-    fn bsearch_branch(&self, k: K) -> Option<uint> {
-        let mut midpoint: uint = self.elts.len() / 2;
-        let mut high: uint = self.elts.len();
-        let mut low: uint = 0u;
-        if midpoint == high {
-            midpoint = 0u;
-        }
+impl<K, V> MoveEntries<K, V> {
+    fn descend(&mut self, node: Node<K, V>) {
+        let mut node = node;
         loop {
-            let order = self.elts.get(midpoint).key.cmp(&k);
-            match order {
-                Equal => {
-                    return None;
-                }
-                Greater => {
-                    if midpoint > 0 {
-                        if self.elts.get(midpoint - 1).key.cmp(&k) == Less {
-                            return Some(midpoint);
-                        }
-                        else {
-                            let tmp = midpoint;
-                            midpoint = (midpoint - low) / 2;
-                            high = tmp;
-                            continue;
-                        }
-                    }
-                    else {
-                        return Some(0);
-                    }
-                }
-                Less => {
-                    if midpoint + 1 < self.elts.len() {
-                        if self.elts.get(midpoint + 1).key.cmp(&k) == Greater {
-                            return Some(midpoint);
-                        }
-                        else {
-                            let tmp = midpoint;
-                            midpoint = (high - midpoint) / 2;
-                            low = tmp;
-                        }
-                    }
-                    else {
-                        return Some(self.elts.len());
-                    }
-                }
+            let Node { keys, vals, edges } = node;
+            let mut frame = MoveNode {
+                keys: keys.move_iter(),
+                vals: vals.move_iter(),
+                edges: edges.move_iter(),
+            };
+            let child = frame.edges.next();
+            self.stack.push(frame);
+            match child {
+                Some(child) => node = *child,
+                None => return,
             }
         }
     }
 }
 
-impl<K: Clone + TotalOrd, V: Clone> Branch<K, V> {
-    ///Returns the corresponding value to the supplied key.
-    ///If the key is not there, find the child that might hold it.
-    fn get(&self, k: K) -> Option<V> {
-        for s in self.elts.iter() {
-            let order = s.key.cmp(&k);
-            match order {
-                Less => return s.left.get(k),
-                Equal => return Some(s.value.clone()),
-                _ => {}
-            }
-        }
-        self.rightmost_child.get(k)
-    }
-
-    ///An insert method that uses .clone() for support.
-    fn insert(mut self, k: K, v: V, ub: uint) -> (Node<K, V>, bool) {
-        let mut new_branch = Node::new_branch(self.clone().elts, self.clone().rightmost_child);
-        let mut outcome = false;
-        let index: Option<uint> = new_branch.bsearch_node(k.clone());
-        //First, find which path down the tree will lead to the appropriate leaf
-        //for the key-value pair.
-        match index.clone() {
-            None => {
-                return (Node::new_branch(self.clone().elts,
-                                         self.clone().rightmost_child),
-                        outcome);
-            }
-            _ => {
-                if index.unwrap() == self.elts.len() {
-                    let new_outcome = self.clone().rightmost_child.insert(k.clone(),
-                                                                       v.clone(),
-                                                                       ub.clone());
-                    new_branch = new_outcome.clone().val0();
-                    outcome = new_outcome.val1();
-                }
-                else {
-                    let new_outcome = self.elts.get(index.unwrap()).left.clone().insert(k.clone(),
-                                                                                 v.clone(),
-                                                                                 ub.clone());
-                    new_branch = new_outcome.clone().val0();
-                    outcome = new_outcome.val1();
-                }
-                //Check to see whether a branch or a leaf was returned from the
-                //tree traversal.
-                match new_branch.clone() {
-                    //If we have a leaf, we do not need to resize the tree,
-                    //so we can return false.
-                    LeafNode(..) => {
-                        if index.unwrap() == self.elts.len() {
-                            self.rightmost_child = ~new_branch.clone();
-                        }
-                        else {
-                            self.elts.get_mut(index.unwrap()).left = ~new_branch.clone();
-                        }
-                        return (Node::new_branch(self.clone().elts,
-                                                 self.clone().rightmost_child),
-                                true);
-                    }
-                    //If we have a branch, we might need to refactor the tree.
-                    BranchNode(..) => {}
-                }
-            }
-        }
-        //If we inserted something into the tree, do the following:
-        if outcome {
-            match new_branch.clone() {
-                //If we have a new leaf node, integrate it into the current branch
-                //and return it, saying we have inserted a new element.
-                LeafNode(..) => {
-                    if index.unwrap() == self.elts.len() {
-                        self.rightmost_child = ~new_branch;
-                    }
-                    else {
-                        self.elts.get_mut(index.unwrap()).left = ~new_branch;
-                    }
-                    return (Node::new_branch(self.clone().elts,
-                                             self.clone().rightmost_child),
-                            true);
-                }
-                //If we have a new branch node, attempt to insert it into the tree
-                //as with the key-value pair, then check to see if the node is overfull.
-                BranchNode(branch) => {
-                    let new_elt = branch.elts.get(0).clone();
-                    let new_elt_index = self.bsearch_branch(new_elt.clone().key);
-                    match new_elt_index {
-                        None => {
-                            return (Node::new_branch(self.clone().elts,
-                                                     self.clone().rightmost_child),
-                                    false);
-                            }
-                        _ => {
-                            self.elts.insert(new_elt_index.unwrap(), new_elt);
-                            if new_elt_index.unwrap() + 1 >= self.elts.len() {
-                                self.rightmost_child = branch.clone().rightmost_child;
-                            }
-                            else {
-                                self.elts.get_mut(new_elt_index.unwrap() + 1).left =
-                                    branch.clone().rightmost_child;
-                            }
-                        }
+impl<K, V> Iterator<(K, V)> for MoveEntries<K, V> {
+    fn next(&mut self) -> Option<(K, V)> {
+        loop {
+            let (entry, child) = match self.stack.mut_last() {
+                None => return None,
+                Some(top) => match top.keys.next() {
+                    Some(k) => (Some((k, top.vals.next().unwrap())), top.edges.next()),
+                    None => (None, None),
+                },
+            };
+            match entry {
+                Some(entry) => {
+                    match child {
+                        Some(child) => self.descend(*child),
+                        None => {}
                     }
+                    self.remaining -= 1;
+                    return Some(entry)
                 }
-            }
-            //If the current node is overfilled, create a new branch with one element
-            //and two children.
-            if self.elts.len() > ub {
-                let midpoint = self.elts.remove(ub / 2).unwrap();
-                let (new_left, new_right) = self.clone().elts.partition(|le|
-                                                                midpoint.key.cmp(&le.key)
-                                                                        == Greater);
-                new_branch = Node::new_branch(
-                    vec!(BranchElt::new(midpoint.clone().key,
-                                     midpoint.clone().value,
-                                     ~Node::new_branch(new_left,
-                                                       midpoint.clone().left))),
-                    ~Node::new_branch(new_right, self.clone().rightmost_child));
-                return (new_branch, true);
+                None => { self.stack.pop(); }
             }
         }
-        (Node::new_branch(self.elts.clone(), self.rightmost_child.clone()), outcome)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (uint, Option<uint>) {
+        (self.remaining, Some(self.remaining))
     }
 }
 
-impl<K: Clone + TotalOrd, V: Clone> Clone for Branch<K, V> {
-    ///Returns a new branch using the clone methods of the Branch's internal variables.
-    fn clone(&self) -> Branch<K, V> {
-        Branch::new(self.elts.clone(), self.rightmost_child.clone())
+impl<K: TotalOrd, V> FromIterator<(K, V)> for BTreeMap<K, V> {
+    fn from_iter<T: Iterator<(K, V)>>(iter: T) -> BTreeMap<K, V> {
+        let mut map = BTreeMap::new();
+        map.extend(iter);
+        map
     }
 }
 
-impl<K: TotalOrd, V: TotalEq> Eq for Branch<K, V> {
-    fn eq(&self, other: &Branch<K, V>) -> bool {
-        self.elts == other.elts
+impl<K: TotalOrd, V> Extendable<(K, V)> for BTreeMap<K, V> {
+    #[inline]
+    fn extend<T: Iterator<(K, V)>>(&mut self, mut iter: T) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
     }
 }
 
-impl<K: TotalOrd, V: TotalEq> TotalEq for Branch<K, V> {}
+/// An implementation of the `Set` trait on top of `BTreeMap`. The only
+/// requirement is that the type of the elements contained ascribes to the
+/// `TotalOrd` trait.
+#[deriving(Clone)]
+pub struct BTreeSet<T> {
+    map: BTreeMap<T, ()>
+}
 
-impl<K: TotalOrd, V: TotalEq> Ord for Branch<K, V> {
-    fn lt(&self, other: &Branch<K, V>) -> bool {
-        self.cmp(other) == Less
-    }
+impl<T: Eq + TotalOrd> Eq for BTreeSet<T> {
+    #[inline]
+    fn eq(&self, other: &BTreeSet<T>) -> bool { self.map == other.map }
 }
 
-impl<K: TotalOrd, V: TotalEq> TotalOrd for Branch<K, V> {
-    ///Compares the first elements of two branches to determine an ordering
-    fn cmp(&self, other: &Branch<K, V>) -> Ordering {
-        if self.elts.len() > other.elts.len() {
-            return Greater;
-        }
-        if self.elts.len() < other.elts.len() {
-            return Less;
-        }
-        self.elts.get(0).cmp(other.elts.get(0))
-    }
+impl<T: Ord + TotalOrd> Ord for BTreeSet<T> {
+    #[inline]
+    fn lt(&self, other: &BTreeSet<T>) -> bool { self.map < other.map }
 }
 
-impl<K: fmt::Show + TotalOrd, V: fmt::Show> fmt::Show for Branch<K, V> {
-    ///Returns a string representation of a Branch.
+impl<T: TotalOrd + Show> Show for BTreeSet<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for (i, s) in self.elts.iter().enumerate() {
-            if i != 0 { try!(write!(f.buf, " // ")) }
-            try!(write!(f.buf, "{}", *s))
+        try!(write!(f.buf, r"\{"));
+
+        for (i, x) in self.iter().enumerate() {
+            if i != 0 { try!(write!(f.buf, ", ")); }
+            try!(write!(f.buf, "{}", *x));
         }
-        write!(f.buf, " // rightmost child: ({}) ", *self.rightmost_child)
+
+        write!(f.buf, r"\}")
     }
 }
 
-//A LeafElt containts no left child, but a key-value pair.
-struct LeafElt<K, V> {
-    key: K,
-    value: V
+impl<T: TotalOrd> Container for BTreeSet<T> {
+    #[inline]
+    fn len(&self) -> uint { self.map.len() }
 }
 
-//A BranchElt has a left child in insertition to a key-value pair.
-struct BranchElt<K, V> {
-    left: ~Node<K, V>,
-    key: K,
-    value: V
+impl<T: TotalOrd> Mutable for BTreeSet<T> {
+    #[inline]
+    fn clear(&mut self) { self.map.clear() }
 }
 
-impl<K: TotalOrd, V> LeafElt<K, V> {
-    ///Creates a new LeafElt from a supplied key-value pair.
-    fn new(k: K, v: V) -> LeafElt<K, V> {
-        LeafElt {
-            key: k,
-            value: v
+impl<T: TotalOrd> Set<T> for BTreeSet<T> {
+    #[inline]
+    fn contains(&self, value: &T) -> bool {
+        self.map.contains_key(value)
+    }
+
+    fn is_disjoint(&self, other: &BTreeSet<T>) -> bool {
+        self.intersection(other).next().is_none()
+    }
+
+    fn is_subset(&self, other: &BTreeSet<T>) -> bool {
+        let mut y = other.iter().peekable();
+        for a in self.iter() {
+            loop {
+                match y.peek().map(|b| a.cmp(*b)) {
+                    None | Some(Less) => return false,
+                    Some(Equal) => { y.next(); break }
+                    Some(Greater) => { y.next(); }
+                }
+            }
         }
+        true
     }
 }
 
-impl<K: Clone + TotalOrd, V: Clone> Clone for LeafElt<K, V> {
-    ///Returns a new LeafElt by cloning the key and value.
-    fn clone(&self) -> LeafElt<K, V> {
-        LeafElt::new(self.key.clone(), self.value.clone())
-    }
+impl<T: TotalOrd> MutableSet<T> for BTreeSet<T> {
+    #[inline]
+    fn insert(&mut self, value: T) -> bool { self.map.insert(value, ()) }
+
+    #[inline]
+    fn remove(&mut self, value: &T) -> bool { self.map.remove(value) }
 }
 
-impl<K: TotalOrd, V: TotalEq> Eq for LeafElt<K, V> {
-    fn eq(&self, other: &LeafElt<K, V>) -> bool {
-        self.key == other.key && self.value == other.value
+impl<T: TotalOrd> BTreeSet<T> {
+    /// Create an empty BTreeSet
+    #[inline]
+    pub fn new() -> BTreeSet<T> { BTreeSet { map: BTreeMap::new() } }
+
+    /// Get a lazy iterator over the values in the set, in ascending order.
+    #[inline]
+    pub fn iter<'a>(&'a self) -> SetItems<'a, T> {
+        SetItems { iter: self.map.iter() }
     }
-}
 
-impl<K: TotalOrd, V: TotalEq> TotalEq for LeafElt<K, V> {}
+    /// Get a lazy iterator over the values in the set, in descending order.
+    #[inline]
+    pub fn rev_iter<'a>(&'a self) -> SetItems<'a, T> {
+        SetItems { iter: self.map.rev_iter() }
+    }
 
-impl<K: TotalOrd, V: TotalEq> Ord for LeafElt<K, V> {
-    fn lt(&self, other: &LeafElt<K, V>) -> bool {
-        self.cmp(other) == Less
+    /// Get a lazy iterator that consumes the set, in ascending order.
+    #[inline]
+    pub fn move_iter(self) -> SetMoveItems<T> {
+        SetMoveItems { iter: self.map.move_iter() }
+    }
+
+    /// Get a lazy iterator starting at the first value not less than `v`.
+    #[inline]
+    pub fn lower_bound<'a>(&'a self, v: &T) -> SetItems<'a, T> {
+        SetItems { iter: self.map.lower_bound(v) }
     }
-}
 
-impl<K: TotalOrd, V: TotalEq> TotalOrd for LeafElt<K, V> {
-    ///Returns an ordering based on the keys of the LeafElts.
-    fn cmp(&self, other: &LeafElt<K, V>) -> Ordering {
-        self.key.cmp(&other.key)
+    /// Get a lazy iterator starting at the first value greater than `v`.
+    #[inline]
+    pub fn upper_bound<'a>(&'a self, v: &T) -> SetItems<'a, T> {
+        SetItems { iter: self.map.upper_bound(v) }
+    }
+
+    /// Get a lazy iterator over the values in the half-open interval
+    /// `[min, max)`, in ascending order.
+    #[inline]
+    pub fn range<'a>(&'a self, min: &T, max: &T) -> SetItems<'a, T> {
+        SetItems { iter: self.map.range(min, max) }
+    }
+
+    /// Visit the values (in-order) representing the difference
+    pub fn difference<'a>(&'a self, other: &'a BTreeSet<T>) -> DifferenceItems<'a, T> {
+        DifferenceItems { a: self.iter().peekable(), b: other.iter().peekable() }
+    }
+
+    /// Visit the values (in-order) representing the symmetric difference
+    pub fn symmetric_difference<'a>(&'a self, other: &'a BTreeSet<T>)
+        -> SymDifferenceItems<'a, T> {
+        SymDifferenceItems { a: self.iter().peekable(), b: other.iter().peekable() }
+    }
+
+    /// Visit the values (in-order) representing the intersection
+    pub fn intersection<'a>(&'a self, other: &'a BTreeSet<T>)
+        -> IntersectionItems<'a, T> {
+        IntersectionItems { a: self.iter().peekable(), b: other.iter().peekable() }
+    }
+
+    /// Visit the values (in-order) representing the union
+    pub fn union<'a>(&'a self, other: &'a BTreeSet<T>) -> UnionItems<'a, T> {
+        UnionItems { a: self.iter().peekable(), b: other.iter().peekable() }
     }
 }
 
-impl<K: fmt::Show + TotalOrd, V: fmt::Show> fmt::Show for LeafElt<K, V> {
-    ///Returns a string representation of a LeafElt.
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f.buf, "Key: {}, value: {};", self.key, self.value)
+impl<T: TotalOrd> FromIterator<T> for BTreeSet<T> {
+    fn from_iter<Iter: Iterator<T>>(iter: Iter) -> BTreeSet<T> {
+        let mut set = BTreeSet::new();
+        set.extend(iter);
+        set
     }
 }
 
-impl<K: TotalOrd, V> BranchElt<K, V> {
-    ///Creates a new BranchElt from a supplied key, value, and left child.
-    fn new(k: K, v: V, n: ~Node<K, V>) -> BranchElt<K, V> {
-        BranchElt {
-            left: n,
-            key: k,
-            value: v
+impl<T: TotalOrd> Extendable<T> for BTreeSet<T> {
+    #[inline]
+    fn extend<Iter: Iterator<T>>(&mut self, mut iter: Iter) {
+        for elem in iter {
+            self.insert(elem);
         }
     }
 }
 
+/// Lazy iterator over a set
+pub struct SetItems<'a, T> {
+    iter: Entries<'a, T, ()>
+}
+
+impl<'a, T> Iterator<&'a T> for SetItems<'a, T> {
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        self.iter.next().map(|(value, _)| value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (uint, Option<uint>) {
+        self.iter.size_hint()
+    }
+}
+
+/// Lazy iterator over a set that consumes the set
+pub struct SetMoveItems<T> {
+    iter: MoveEntries<T, ()>
+}
+
+impl<T> Iterator<T> for SetMoveItems<T> {
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        self.iter.next().map(|(value, _)| value)
+    }
 
-impl<K: Clone + TotalOrd, V: Clone> Clone for BranchElt<K, V> {
-    ///Returns a new BranchElt by cloning the key, value, and left child.
-    fn clone(&self) -> BranchElt<K, V> {
-        BranchElt::new(self.key.clone(),
-                       self.value.clone(),
-                       self.left.clone())
+    #[inline]
+    fn size_hint(&self) -> (uint, Option<uint>) {
+        self.iter.size_hint()
     }
 }
 
-impl<K: TotalOrd, V: TotalEq> Eq for BranchElt<K, V>{
-    fn eq(&self, other: &BranchElt<K, V>) -> bool {
-        self.key == other.key && self.value == other.value
+/// Lazy iterator producing elements in the set difference (in-order)
+pub struct DifferenceItems<'a, T> {
+    a: Peekable<&'a T, SetItems<'a, T>>,
+    b: Peekable<&'a T, SetItems<'a, T>>,
+}
+
+/// Lazy iterator producing elements in the set symmetric difference (in-order)
+pub struct SymDifferenceItems<'a, T> {
+    a: Peekable<&'a T, SetItems<'a, T>>,
+    b: Peekable<&'a T, SetItems<'a, T>>,
+}
+
+/// Lazy iterator producing elements in the set intersection (in-order)
+pub struct IntersectionItems<'a, T> {
+    a: Peekable<&'a T, SetItems<'a, T>>,
+    b: Peekable<&'a T, SetItems<'a, T>>,
+}
+
+/// Lazy iterator producing elements in the set union (in-order)
+pub struct UnionItems<'a, T> {
+    a: Peekable<&'a T, SetItems<'a, T>>,
+    b: Peekable<&'a T, SetItems<'a, T>>,
+}
+
+/// Compare `x` and `y`, but return `short` if x is None and `long` if y is None
+fn cmp_opt<T: TotalOrd>(x: Option<&T>, y: Option<&T>,
+                        short: Ordering, long: Ordering) -> Ordering {
+    match (x, y) {
+        (None    , _       ) => short,
+        (_       , None    ) => long,
+        (Some(x1), Some(y1)) => x1.cmp(y1),
     }
 }
 
-impl<K: TotalOrd, V: TotalEq> TotalEq for BranchElt<K, V>{}
+impl<'a, T: TotalOrd> Iterator<&'a T> for DifferenceItems<'a, T> {
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            match cmp_opt(self.a.peek(), self.b.peek(), Less, Less) {
+                Less    => return self.a.next(),
+                Equal   => { self.a.next(); self.b.next(); }
+                Greater => { self.b.next(); }
+            }
+        }
+    }
+}
 
-impl<K: TotalOrd, V: TotalEq> Ord for BranchElt<K, V> {
-    fn lt(&self, other: &BranchElt<K, V>) -> bool {
-        self.cmp(other) == Less
+impl<'a, T: TotalOrd> Iterator<&'a T> for SymDifferenceItems<'a, T> {
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            match cmp_opt(self.a.peek(), self.b.peek(), Greater, Less) {
+                Less    => return self.a.next(),
+                Equal   => { self.a.next(); self.b.next(); }
+                Greater => return self.b.next(),
+            }
+        }
     }
 }
 
-impl<K: TotalOrd, V: TotalEq> TotalOrd for BranchElt<K, V> {
-    ///Fulfills TotalOrd for BranchElts
-    fn cmp(&self, other: &BranchElt<K, V>) -> Ordering {
-        self.key.cmp(&other.key)
+impl<'a, T: TotalOrd> Iterator<&'a T> for IntersectionItems<'a, T> {
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            let o_cmp = match (self.a.peek(), self.b.peek()) {
+                (None    , _       ) => None,
+                (_       , None    ) => None,
+                (Some(a1), Some(b1)) => Some(a1.cmp(b1)),
+            };
+            match o_cmp {
+                None          => return None,
+                Some(Less)    => { self.a.next(); }
+                Some(Equal)   => { self.b.next(); return self.a.next() }
+                Some(Greater) => { self.b.next(); }
+            }
+        }
     }
 }
 
-impl<K: fmt::Show + TotalOrd, V: fmt::Show> fmt::Show for BranchElt<K, V> {
-    /// Returns string containing key, value, and child (which should recur to a
-    /// leaf) Consider changing in future to be more readable.
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f.buf, "Key: {}, value: {}, (child: {})",
-               self.key, self.value, *self.left)
+impl<'a, T: TotalOrd> Iterator<&'a T> for UnionItems<'a, T> {
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            match cmp_opt(self.a.peek(), self.b.peek(), Greater, Less) {
+                Less    => return self.a.next(),
+                Equal   => { self.b.next(); return self.a.next() }
+                Greater => return self.b.next(),
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod test_btree {
+    use super::{BTreeMap, Node, MIN_LEN, CAPACITY};
+
+    use rand::Rng;
+    use rand;
+
+    // Checks the structural invariants of the tree below `node`, returning
+    // its height.
+    fn check_node<K: TotalOrd, V>(node: &Node<K, V>, is_root: bool,
+                                  lo: Option<&K>, hi: Option<&K>) -> uint {
+        assert_eq!(node.keys.len(), node.vals.len());
+        assert!(node.len() <= CAPACITY);
+        if !is_root { assert!(node.len() >= MIN_LEN); }
+        for w in node.keys.as_slice().windows(2) {
+            assert!(w[0] < w[1]);
+        }
+        match (lo, node.keys.iter().next()) {
+            (Some(lo), Some(k)) => assert!(*lo < *k),
+            _ => {}
+        }
+        match (hi, node.keys.last()) {
+            (Some(hi), Some(k)) => assert!(*k < *hi),
+            _ => {}
+        }
+        if node.is_leaf() { return 1 }
+
+        assert_eq!(node.edges.len(), node.len() + 1);
+        let mut height = None;
+        for (i, edge) in node.edges.iter().enumerate() {
+            let lo = if i == 0 { lo } else { Some(node.keys.get(i - 1)) };
+            let hi = if i == node.len() { hi } else { Some(node.keys.get(i)) };
+            let h = check_node(&**edge, false, lo, hi);
+            match height {
+                None => height = Some(h),
+                Some(height) => assert_eq!(height, h),
+            }
+        }
+        height.unwrap() + 1
+    }
+
+    fn check<K: TotalOrd, V>(map: &BTreeMap<K, V>) {
+        check_node(&map.root, true, None, None);
+        assert_eq!(map.iter().len(), map.len());
+    }
+
+    #[test]
+    fn find_empty() {
+        let m: BTreeMap<int, int> = BTreeMap::new();
+        assert!(m.find(&5) == None);
+        assert!(m.iter().next().is_none());
+    }
+
+    #[test]
+    fn find_not_found() {
+        let mut m = BTreeMap::new();
+        assert!(m.insert(1, 2));
+        assert!(m.insert(5, 3));
+        assert!(m.insert(9, 3));
+        assert_eq!(m.find(&2), None);
+    }
+
+    #[test]
+    fn test_find_mut() {
+        let mut m = BTreeMap::new();
+        for i in range(0, 100) {
+            assert!(m.insert(i, i));
+        }
+        match m.find_mut(&57) {
+            None => fail!(), Some(x) => *x = 1000
+        }
+        assert_eq!(m.find(&57), Some(&1000));
+    }
+
+    #[test]
+    fn insert_replace() {
+        let mut m = BTreeMap::new();
+        assert!(m.insert(5, 2));
+        assert!(m.insert(2, 9));
+        assert!(!m.insert(2, 11));
+        assert_eq!(m.find(&2).unwrap(), &11);
+        assert_eq!(m.swap(5, 3), Some(2));
+        assert_eq!(m.len(), 2);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut m = BTreeMap::new();
+        m.clear();
+        for i in range(0, 50) {
+            assert!(m.insert(i, i));
+        }
+        m.clear();
+        assert!(m.find(&5).is_none());
+        assert!(m.is_empty());
+        check(&m);
+    }
+
+    #[test]
+    fn test_sequential() {
+        let mut m = BTreeMap::new();
+        for i in range(0, 1000) {
+            assert!(m.insert(i, i * 2));
+            check(&m);
+        }
+        for i in range(0, 1000) {
+            assert_eq!(m.find(&i), Some(&(i * 2)));
+        }
+        for i in range(0, 1000).rev() {
+            assert_eq!(m.pop(&i), Some(i * 2));
+            check(&m);
+        }
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn test_rand() {
+        let mut rng = rand::weak_rng();
+        let mut m = BTreeMap::new();
+        let mut keys = Vec::new();
+
+        for _ in range(0, 2000) {
+            let k = rng.gen_range(0u, 5000);
+            if m.insert(k, k + 1) { keys.push(k); }
+        }
+        check(&m);
+        assert_eq!(m.len(), keys.len());
+
+        rng.shuffle(keys.as_mut_slice());
+        for (n, k) in keys.iter().enumerate() {
+            assert_eq!(m.pop(k), Some(*k + 1));
+            assert!(m.find(k).is_none());
+            if n % 50 == 0 { check(&m); }
+        }
+        assert!(m.is_empty());
+        check(&m);
+    }
+
+    #[test]
+    fn test_iterators() {
+        let mut m = BTreeMap::new();
+        for i in range(0, 500).rev() {
+            m.insert(i, i);
+        }
 
-    use super::{BTree, Node, LeafElt};
+        let mut n = 0;
+        for (k, v) in m.iter() {
+            assert_eq!(*k, n);
+            assert_eq!(*v, n);
+            n += 1;
+        }
+        assert_eq!(n, 500);
+
+        for (k, v) in m.rev_iter() {
+            n -= 1;
+            assert_eq!(*k, n);
+            assert_eq!(*v, n);
+        }
+        assert_eq!(n, 0);
+
+        assert_eq!(m.iter().size_hint(), (500, Some(500)));
+        assert_eq!(m.first(), Some((&0, &0)));
+        assert_eq!(m.last(), Some((&499, &499)));
+    }
+
+    #[test]
+    fn test_mut_iter() {
+        let mut m = BTreeMap::new();
+        for i in range(0u, 300) {
+            m.insert(i, 100 * i);
+        }
+
+        for (i, (&k, v)) in m.mut_iter().enumerate() {
+            *v += k * 10 + i; // 000 + 00 + 0, 100 + 10 + 1, ...
+        }
+
+        for (&k, &v) in m.iter() {
+            assert_eq!(v, 111 * k);
+        }
+
+        for (&k, v) in m.mut_rev_iter() {
+            *v -= k;
+        }
+
+        for (&k, &v) in m.iter() {
+            assert_eq!(v, 110 * k);
+        }
+    }
+
+    #[test]
+    fn test_move_iter() {
+        let mut m = BTreeMap::new();
+        for i in range(0, 400).rev() {
+            m.insert(i, ~i);
+        }
+
+        let mut iter = m.move_iter();
+        assert_eq!(iter.size_hint(), (400, Some(400)));
+        for i in range(0, 400) {
+            assert_eq!(iter.next(), Some((i, ~i)));
+        }
+        assert_eq!(iter.next(), None);
+    }
 
-    //Tests the functionality of the insert methods (which are unfinished).
     #[test]
-    fn insert_test_one() {
-        let b = BTree::new(1, "abc".to_owned(), 2);
-        let is_insert = b.insert(2, "xyz".to_owned());
-        //println!("{}", is_insert.clone().to_str());
-        assert!(is_insert.root.is_leaf());
+    fn test_bounds() {
+        let mut m = BTreeMap::new();
+        for i in range(0, 300) {
+            m.insert(i * 2, i);
+        }
+
+        for i in range(-1, 605) {
+            let lower = m.lower_bound(&i).next().map(|(&k, _)| k);
+            let upper = m.upper_bound(&i).next().map(|(&k, _)| k);
+            let expected_lower = if i < 0 { 0 } else { (i + 1) / 2 * 2 };
+            let expected_upper = if i < 0 { 0 } else { i / 2 * 2 + 2 };
+            let check = |k| if k < 600 { Some(k) } else { None };
+            assert_eq!(lower, check(expected_lower));
+            assert_eq!(upper, check(expected_upper));
+        }
+
+        let rest: Vec<int> = m.lower_bound(&590).map(|(&k, _)| k).collect();
+        assert_eq!(rest, vec!(590, 592, 594, 596, 598));
     }
 
     #[test]
-    fn insert_test_two() {
-        let leaf_elt_1 = LeafElt::new(1, "aaa".to_owned());
-        let leaf_elt_2 = LeafElt::new(2, "bbb".to_owned());
-        let leaf_elt_3 = LeafElt::new(3, "ccc".to_owned());
-        let n = Node::new_leaf(vec!(leaf_elt_1, leaf_elt_2, leaf_elt_3));
-        let b = BTree::new_with_node_len(n, 3, 2);
-        //println!("{}", b.clone().insert(4, "ddd".to_owned()).to_str());
-        assert!(b.insert(4, "ddd".to_owned()).root.is_leaf());
+    fn test_range() {
+        let mut m = BTreeMap::new();
+        for i in range(0, 200) {
+            m.insert(i * 3, i);
+        }
+
+        for &(min, max) in [(0, 600), (1, 2), (5, 5), (7, 6), (-10, 10),
+                             (298, 423), (590, 1000), (599, 600)].iter() {
+            let got: Vec<int> = m.range(&min, &max).map(|(&k, _)| k).collect();
+            let expected: Vec<int> = range(min, max).filter(|k| *k % 3 == 0 &&
+                                                             *k >= 0 &&
+                                                             *k < 600).collect();
+            assert_eq!(got, expected);
+        }
+
+        for (_, v) in m.mut_range(&30, &60) {
+            *v = -1;
+        }
+        assert_eq!(m.iter().filter(|&(_, v)| *v == -1).len(), 10);
     }
 
     #[test]
-    fn insert_test_three() {
-        let leaf_elt_1 = LeafElt::new(1, "aaa".to_owned());
-        let leaf_elt_2 = LeafElt::new(2, "bbb".to_owned());
-        let leaf_elt_3 = LeafElt::new(3, "ccc".to_owned());
-        let leaf_elt_4 = LeafElt::new(4, "ddd".to_owned());
-        let n = Node::new_leaf(vec!(leaf_elt_1, leaf_elt_2, leaf_elt_3, leaf_elt_4));
-        let b = BTree::new_with_node_len(n, 3, 2);
-        //println!("{}", b.clone().insert(5, "eee".to_owned()).to_str());
-        assert!(!b.insert(5, "eee".to_owned()).root.is_leaf());
+    fn test_eq_ord() {
+        let mut a = BTreeMap::new();
+        let mut b = BTreeMap::new();
+
+        assert!(a == b);
+        assert!(a.insert(0, 5));
+        assert!(a != b);
+        assert!(b.insert(0, 4));
+        assert!(a != b && b < a);
+        assert!(a.insert(5, 19));
+        assert!(b.insert(0, 5) == false);
+        assert!(b < a);
+        assert!(b.insert(5, 19));
+        assert!(a == b);
     }
 
     #[test]
-    fn insert_test_four() {
-        let leaf_elt_1 = LeafElt::new(1, "aaa".to_owned());
-        let leaf_elt_2 = LeafElt::new(2, "bbb".to_owned());
-        let leaf_elt_3 = LeafElt::new(3, "ccc".to_owned());
-        let leaf_elt_4 = LeafElt::new(4, "ddd".to_owned());
-        let n = Node::new_leaf(vec!(leaf_elt_1, leaf_elt_2, leaf_elt_3, leaf_elt_4));
-        let mut b = BTree::new_with_node_len(n, 3, 2);
-        b = b.clone().insert(5, "eee".to_owned());
-        b = b.clone().insert(6, "fff".to_owned());
-        b = b.clone().insert(7, "ggg".to_owned());
-        b = b.clone().insert(8, "hhh".to_owned());
-        b = b.clone().insert(0, "omg".to_owned());
-        //println!("{}", b.clone().to_str());
-        assert!(!b.root.is_leaf());
+    fn test_show() {
+        let mut map = BTreeMap::new();
+        let empty: BTreeMap<int, int> = BTreeMap::new();
+
+        map.insert(1, 2);
+        map.insert(3, 4);
+
+        assert_eq!(format!("{}", map), "{1: 2, 3: 4}".to_owned());
+        assert_eq!(format!("{}", empty), "{}".to_owned());
+    }
+
+    #[test]
+    fn test_from_iter() {
+        let xs = [(1, 1), (2, 2), (3, 3), (4, 4), (5, 5), (6, 6)];
+
+        let map: BTreeMap<int, int> = xs.iter().map(|&x| x).collect();
+
+        for &(k, v) in xs.iter() {
+            assert_eq!(map.find(&k), Some(&v));
+        }
     }
 
     #[test]
-    fn bsearch_test_one() {
-        let b = BTree::new(1, "abc".to_owned(), 2);
-        assert_eq!(Some(1), b.root.bsearch_node(2));
+    fn test_clone() {
+        let mut m = BTreeMap::new();
+        for i in range(0, 100) {
+            m.insert(i, i);
+        }
+        let mut m2 = m.clone();
+        m2.pop(&50);
+        assert_eq!(m.len(), 100);
+        assert_eq!(m2.len(), 99);
+        assert_eq!(m.find(&50), Some(&50));
+        check(&m2);
+    }
+}
+
+#[cfg(test)]
+mod bench {
+    extern crate test;
+    use self::test::Bencher;
+    use super::BTreeMap;
+    use treemap::TreeMap;
+    use deque::bench::{insert_rand_n, insert_seq_n, find_rand_n, find_seq_n};
+
+    // The TreeMap benchmarks of the same names live in treemap.rs; the
+    // iteration benchmarks below compare the two maps side by side.
+
+    // Insert rand
+    #[bench]
+    pub fn insert_rand_100(b: &mut Bencher) {
+        let mut m : BTreeMap<uint,uint> = BTreeMap::new();
+        insert_rand_n(100, &mut m, b);
+    }
+
+    #[bench]
+    pub fn insert_rand_10_000(b: &mut Bencher) {
+        let mut m : BTreeMap<uint,uint> = BTreeMap::new();
+        insert_rand_n(10_000, &mut m, b);
+    }
+
+    // Insert seq
+    #[bench]
+    pub fn insert_seq_100(b: &mut Bencher) {
+        let mut m : BTreeMap<uint,uint> = BTreeMap::new();
+        insert_seq_n(100, &mut m, b);
+    }
+
+    #[bench]
+    pub fn insert_seq_10_000(b: &mut Bencher) {
+        let mut m : BTreeMap<uint,uint> = BTreeMap::new();
+        insert_seq_n(10_000, &mut m, b);
+    }
+
+    // Find rand
+    #[bench]
+    pub fn find_rand_100(b: &mut Bencher) {
+        let mut m : BTreeMap<uint,uint> = BTreeMap::new();
+        find_rand_n(100, &mut m, b);
+    }
+
+    #[bench]
+    pub fn find_rand_10_000(b: &mut Bencher) {
+        let mut m : BTreeMap<uint,uint> = BTreeMap::new();
+        find_rand_n(10_000, &mut m, b);
+    }
+
+    // Find seq
+    #[bench]
+    pub fn find_seq_100(b: &mut Bencher) {
+        let mut m : BTreeMap<uint,uint> = BTreeMap::new();
+        find_seq_n(100, &mut m, b);
+    }
+
+    #[bench]
+    pub fn find_seq_10_000(b: &mut Bencher) {
+        let mut m : BTreeMap<uint,uint> = BTreeMap::new();
+        find_seq_n(10_000, &mut m, b);
+    }
+
+    // Iteration
+    #[bench]
+    pub fn iter_10_000(b: &mut Bencher) {
+        let m: BTreeMap<uint, uint> = range(0u, 10_000).map(|i| (i, i)).collect();
+        b.iter(|| {
+            let mut sum = 0;
+            for (_, &v) in m.iter() { sum += v; }
+            sum
+        })
+    }
+
+    #[bench]
+    pub fn treemap_iter_10_000(b: &mut Bencher) {
+        let m: TreeMap<uint, uint> = range(0u, 10_000).map(|i| (i, i)).collect();
+        b.iter(|| {
+            let mut sum = 0;
+            for (_, &v) in m.iter() { sum += v; }
+            sum
+        })
     }
 
+    // Range queries
+    #[bench]
+    pub fn range_10_000(b: &mut Bencher) {
+        let m: BTreeMap<uint, uint> = range(0u, 10_000).map(|i| (i, i)).collect();
+        let mut lo = 0;
+        b.iter(|| {
+            lo = (lo + 1237) % 9_900;
+            m.range(&lo, &(lo + 100)).len()
+        })
+    }
+
+    #[bench]
+    pub fn treemap_range_10_000(b: &mut Bencher) {
+        let m: TreeMap<uint, uint> = range(0u, 10_000).map(|i| (i, i)).collect();
+        let mut lo = 0;
+        b.iter(|| {
+            lo = (lo + 1237) % 9_900;
+            m.lower_bound(&lo).take_while(|&(&k, _)| k < lo + 100).len()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_set {
+    use super::BTreeSet;
+
     #[test]
-    fn bsearch_test_two() {
-        let b = BTree::new(1, "abc".to_owned(), 2);
-        assert_eq!(Some(0), b.root.bsearch_node(0));
+    fn test_clear() {
+        let mut s = BTreeSet::new();
+        s.clear();
+        assert!(s.insert(5));
+        assert!(s.insert(12));
+        assert!(s.insert(19));
+        s.clear();
+        assert!(!s.contains(&5));
+        assert!(!s.contains(&12));
+        assert!(!s.contains(&19));
+        assert!(s.is_empty());
     }
 
     #[test]
-    fn bsearch_test_three() {
-        let leaf_elt_1 = LeafElt::new(1, "aaa".to_owned());
-        let leaf_elt_2 = LeafElt::new(2, "bbb".to_owned());
-        let leaf_elt_3 = LeafElt::new(4, "ccc".to_owned());
-        let leaf_elt_4 = LeafElt::new(5, "ddd".to_owned());
-        let n = Node::new_leaf(vec!(leaf_elt_1, leaf_elt_2, leaf_elt_3, leaf_elt_4));
-        let b = BTree::new_with_node_len(n, 3, 2);
-        assert_eq!(Some(2), b.root.bsearch_node(3));
+    fn test_disjoint() {
+        let mut xs = BTreeSet::new();
+        let mut ys = BTreeSet::new();
+        assert!(xs.is_disjoint(&ys));
+        assert!(ys.is_disjoint(&xs));
+        assert!(xs.insert(5));
+        assert!(ys.insert(11));
+        assert!(xs.is_disjoint(&ys));
+        assert!(ys.is_disjoint(&xs));
+        assert!(xs.insert(7));
+        assert!(xs.insert(19));
+        assert!(xs.insert(4));
+        assert!(ys.insert(2));
+        assert!(ys.insert(-11));
+        assert!(xs.is_disjoint(&ys));
+        assert!(ys.is_disjoint(&xs));
+        assert!(ys.insert(7));
+        assert!(!xs.is_disjoint(&ys));
+        assert!(!ys.is_disjoint(&xs));
     }
 
     #[test]
-    fn bsearch_test_four() {
-        let leaf_elt_1 = LeafElt::new(1, "aaa".to_owned());
-        let leaf_elt_2 = LeafElt::new(2, "bbb".to_owned());
-        let leaf_elt_3 = LeafElt::new(4, "ccc".to_owned());
-        let leaf_elt_4 = LeafElt::new(5, "ddd".to_owned());
-        let n = Node::new_leaf(vec!(leaf_elt_1, leaf_elt_2, leaf_elt_3, leaf_elt_4));
-        let b = BTree::new_with_node_len(n, 3, 2);
-        assert_eq!(Some(4), b.root.bsearch_node(800));
+    fn test_subset_and_superset() {
+        let mut a = BTreeSet::new();
+        assert!(a.insert(0));
+        assert!(a.insert(5));
+        assert!(a.insert(11));
+        assert!(a.insert(7));
+
+        let mut b = BTreeSet::new();
+        assert!(b.insert(0));
+        assert!(b.insert(7));
+        assert!(b.insert(19));
+        assert!(b.insert(250));
+        assert!(b.insert(11));
+        assert!(b.insert(200));
+
+        assert!(!a.is_subset(&b));
+        assert!(!a.is_superset(&b));
+        assert!(!b.is_subset(&a));
+        assert!(!b.is_superset(&a));
+
+        assert!(b.insert(5));
+
+        assert!(a.is_subset(&b));
+        assert!(!a.is_superset(&b));
+        assert!(!b.is_subset(&a));
+        assert!(b.is_superset(&a));
     }
 
-    //Tests the functionality of the get method.
     #[test]
-    fn get_test() {
-        let b = BTree::new(1, "abc".to_owned(), 2);
-        let val = b.get(1);
-        assert_eq!(val, Some("abc".to_owned()));
+    fn test_iterator() {
+        let mut m = BTreeSet::new();
+
+        assert!(m.insert(3));
+        assert!(m.insert(0));
+        assert!(m.insert(4));
+        assert!(m.insert(2));
+        assert!(m.insert(1));
+
+        let v: Vec<int> = m.iter().map(|&x| x).collect();
+        assert_eq!(v, vec!(0, 1, 2, 3, 4));
+        let v: Vec<int> = m.rev_iter().map(|&x| x).collect();
+        assert_eq!(v, vec!(4, 3, 2, 1, 0));
+        let v: Vec<int> = m.range(&1, &4).map(|&x| x).collect();
+        assert_eq!(v, vec!(1, 2, 3));
+        let v: Vec<int> = m.move_iter().collect();
+        assert_eq!(v, vec!(0, 1, 2, 3, 4));
+    }
+
+    fn check(a: &[int], b: &[int], expected: &[int],
+             f: |&BTreeSet<int>, &BTreeSet<int>, f: |&int| -> bool| -> bool) {
+        let mut set_a = BTreeSet::new();
+        let mut set_b = BTreeSet::new();
+
+        for x in a.iter() { assert!(set_a.insert(*x)) }
+        for y in b.iter() { assert!(set_b.insert(*y)) }
+
+        let mut i = 0;
+        f(&set_a, &set_b, |x| {
+            assert_eq!(*x, expected[i]);
+            i += 1;
+            true
+        });
+        assert_eq!(i, expected.len());
     }
 
-    //Tests the BTree's clone() method.
     #[test]
-    fn btree_clone_test() {
-        let b = BTree::new(1, "abc".to_owned(), 2);
-        let b2 = b.clone();
-        assert!(b.root == b2.root)
+    fn test_intersection() {
+        fn check_intersection(a: &[int], b: &[int], expected: &[int]) {
+            check(a, b, expected, |x, y, f| x.intersection(y).advance(f))
+        }
+
+        check_intersection([], [], []);
+        check_intersection([1, 2, 3], [], []);
+        check_intersection([], [1, 2, 3], []);
+        check_intersection([2], [1, 2, 3], [2]);
+        check_intersection([1, 2, 3], [2], [2]);
+        check_intersection([11, 1, 3, 77, 103, 5, -5],
+                           [2, 11, 77, -9, -42, 5, 3],
+                           [3, 5, 11, 77]);
     }
 
-    //Tests the BTree's cmp() method when one node is "less than" another.
     #[test]
-    fn btree_cmp_test_less() {
-        let b = BTree::new(1, "abc".to_owned(), 2);
-        let b2 = BTree::new(2, "bcd".to_owned(), 2);
-        assert!(&b.cmp(&b2) == &Less)
+    fn test_difference() {
+        fn check_difference(a: &[int], b: &[int], expected: &[int]) {
+            check(a, b, expected, |x, y, f| x.difference(y).advance(f))
+        }
+
+        check_difference([], [], []);
+        check_difference([1, 12], [], [1, 12]);
+        check_difference([], [1, 2, 3, 9], []);
+        check_difference([1, 3, 5, 9, 11],
+                         [3, 9],
+                         [1, 5, 11]);
+        check_difference([-5, 11, 22, 33, 40, 42],
+                         [-12, -5, 14, 23, 34, 38, 39, 50],
+                         [11, 22, 33, 40, 42]);
     }
 
-    //Tests the BTree's cmp() method when two nodes are equal.
     #[test]
-    fn btree_cmp_test_eq() {
-        let b = BTree::new(1, "abc".to_owned(), 2);
-        let b2 = BTree::new(1, "bcd".to_owned(), 2);
-        assert!(&b.cmp(&b2) == &Equal)
+    fn test_symmetric_difference() {
+        fn check_symmetric_difference(a: &[int], b: &[int],
+                                      expected: &[int]) {
+            check(a, b, expected, |x, y, f| x.symmetric_difference(y).advance(f))
+        }
+
+        check_symmetric_difference([], [], []);
+        check_symmetric_difference([1, 2, 3], [2], [1, 3]);
+        check_symmetric_difference([2], [1, 2, 3], [1, 3]);
+        check_symmetric_difference([1, 3, 5, 9, 11],
+                                   [-2, 3, 9, 14, 22],
+                                   [-2, 1, 5, 11, 14, 22]);
     }
 
-    //Tests the BTree's cmp() method when one node is "greater than" another.
     #[test]
-    fn btree_cmp_test_greater() {
-        let b = BTree::new(1, "abc".to_owned(), 2);
-        let b2 = BTree::new(2, "bcd".to_owned(), 2);
-        assert!(&b2.cmp(&b) == &Greater)
+    fn test_union() {
+        fn check_union(a: &[int], b: &[int],
+                                      expected: &[int]) {
+            check(a, b, expected, |x, y, f| x.union(y).advance(f))
+        }
+
+        check_union([], [], []);
+        check_union([1, 2, 3], [2], [1, 2, 3]);
+        check_union([2], [1, 2, 3], [1, 2, 3]);
+        check_union([1, 3, 5, 9, 11, 16, 19, 24],
+                    [-2, 1, 5, 9, 13, 19],
+                    [-2, 1, 3, 5, 9, 11, 13, 16, 19, 24]);
     }
 
-    //Tests the BTree's to_str() method.
     #[test]
-    fn btree_tostr_test() {
-        let b = BTree::new(1, "abc".to_owned(), 2);
-        assert_eq!(b.to_str(), "Key: 1, value: abc;".to_owned())
+    fn test_large() {
+        let evens: BTreeSet<uint> = range(0u, 2000).map(|i| i * 2).collect();
+        let threes: BTreeSet<uint> = range(0u, 1500).map(|i| i * 3).collect();
+        let both: Vec<uint> = evens.intersection(&threes).map(|&x| x).collect();
+        let expected: Vec<uint> = range(0u, 667).map(|i| i * 6).collect();
+        assert_eq!(both, expected);
     }
 
+    #[test]
+    fn test_show() {
+        let mut set: BTreeSet<int> = BTreeSet::new();
+        let empty: BTreeSet<int> = BTreeSet::new();
+
+        set.insert(1);
+        set.insert(2);
+
+        assert_eq!(format!("{}", set), "{1, 2}".to_owned());
+        assert_eq!(format!("{}", empty), "{}".to_owned());
+    }
 }
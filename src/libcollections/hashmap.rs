@@ -16,7 +16,7 @@ use std::cmp::{Eq, TotalEq, Equiv, max};
 use std::default::Default;
 use std::fmt;
 use std::fmt::Show;
-use std::hash::{Hash, Hasher, sip};
+use std::hash::{Hash, Hasher, SeedableHasher, sip};
 use std::iter;
 use std::iter::{Iterator, FromIterator, Extendable};
 use std::iter::{FilterMap, Chain, Repeat, Zip};
@@ -1041,6 +1041,54 @@ impl<K: Hash + TotalEq, V> HashMap<K, V, sip::SipHasher> {
     }
 }
 
+/// A seeding policy which keys a hasher with fresh random values drawn from
+/// the task-local RNG, as `HashMap::new` does for `SipHasher`.
+///
+/// Wrapping a faster hasher in `Randomized` keeps the per-map key that makes
+/// collisions hard to precompute, and since it implements `Default` it also
+/// works with `FromIterator` and `Default` for `HashMap`:
+///
+/// ```rust
+/// use collections::HashMap;
+/// use collections::hashmap::Randomized;
+/// use std::default::Default;
+/// use std::hash::fnv::FnvHasher;
+///
+/// let mut map: HashMap<uint, uint, Randomized<FnvHasher>> = Default::default();
+/// map.insert(1, 2);
+/// ```
+#[deriving(Clone)]
+pub struct Randomized<H> {
+    hasher: H,
+}
+
+impl<H: SeedableHasher> Randomized<H> {
+    /// Key a new hasher from the task-local RNG.
+    pub fn new() -> Randomized<H> {
+        Randomized::with_rng(&mut rand::task_rng())
+    }
+
+    /// Key a new hasher from the provided RNG.
+    pub fn with_rng<R: Rng>(rng: &mut R) -> Randomized<H> {
+        let k0 = rng.gen();
+        let k1 = rng.gen();
+        Randomized { hasher: SeedableHasher::from_keys(k0, k1) }
+    }
+}
+
+impl<S, H: Hasher<S>> Hasher<S> for Randomized<H> {
+    #[inline]
+    fn hash<T: Hash<S>>(&self, value: &T) -> u64 {
+        self.hasher.hash(value)
+    }
+}
+
+impl<H: SeedableHasher> Default for Randomized<H> {
+    fn default() -> Randomized<H> {
+        Randomized::new()
+    }
+}
+
 impl<K: TotalEq + Hash<S>, V, S, H: Hasher<S>> HashMap<K, V, H> {
     pub fn with_hasher(hasher: H) -> HashMap<K, V, H> {
         HashMap::with_capacity_and_hasher(INITIAL_CAPACITY, hasher)
@@ -1286,6 +1334,115 @@ impl<K: TotalEq + Hash<S>, V, S, H: Hasher<S>> HashMap<K, V, H> {
         }
     }
 
+    /// Gets the given key's corresponding entry in the map for in-place
+    /// manipulation. The key is hashed exactly once, whether the entry ends
+    /// up being read, updated, inserted or removed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use collections::HashMap;
+    /// use collections::hashmap::{Occupied, Vacant};
+    ///
+    /// let mut counts = HashMap::new();
+    /// for word in "a b a c a".words() {
+    ///     match counts.entry(word) {
+    ///         Occupied(mut entry) => *entry.get_mut() += 1,
+    ///         Vacant(entry) => { entry.insert(1); }
+    ///     }
+    /// }
+    /// assert_eq!(*counts.get(&"a"), 3);
+    /// ```
+    pub fn entry<'a>(&'a mut self, key: K) -> Entry<'a, K, V, H> {
+        let hash = self.make_hash(&key);
+        match self.search_hashed(&hash, &key) {
+            Some(idx) => Occupied(OccupiedEntry { map: self, index: idx }),
+            None      => Vacant(VacantEntry { map: self, hash: hash, key: key }),
+        }
+    }
+
+    /// Retains only the key-value pairs for which `f` returns true,
+    /// removing all the others in place. Each pair is visited exactly once.
+    pub fn retain(&mut self, f: |&K, &mut V| -> bool) {
+        if self.table.size() == 0 {
+            return
+        }
+
+        // Start walking just after an empty bucket. Removals backwards-shift
+        // the rest of their cluster, and since no cluster spans that bucket,
+        // nothing we've already visited can be shifted in front of us.
+        let cap = self.table.capacity();
+        let start = {
+            let mut i = 0;
+            loop {
+                match self.table.peek(i) {
+                    table::Empty(_) => break,
+                    table::Full(_)  => i += 1,
+                }
+            }
+            i
+        };
+
+        let mut probe = self.probe_next(start);
+        let mut remaining = cap - 1;
+        while remaining > 0 {
+            let idx = match self.table.peek(probe) {
+                table::Empty(_)  => {
+                    probe = self.probe_next(probe);
+                    remaining -= 1;
+                    continue;
+                }
+                table::Full(idx) => idx
+            };
+
+            let keep = {
+                let (k, v) = self.table.read_mut(&idx);
+                f(k, v)
+            };
+
+            if keep {
+                probe = self.probe_next(probe);
+                remaining -= 1;
+            } else {
+                // The next element of the cluster (if any) now lives in this
+                // bucket, so look at it again without advancing.
+                self.pop_internal(idx);
+            }
+        }
+    }
+
+    /// Removes every key-value pair from the map, returning them in
+    /// arbitrary order through a by-value iterator. The map keeps its
+    /// current capacity.
+    pub fn drain(&mut self) -> MoveEntries<K, V> {
+        let cap = self.table.capacity();
+        let old_table = replace(&mut self.table, table::RawTable::new(cap));
+        old_table.move_iter().map(|(_, k, v)| (k, v))
+    }
+
+    /// Return the number of elements the map can hold before it next has
+    /// to grow.
+    pub fn capacity(&self) -> uint {
+        self.grow_at
+    }
+
+    /// Shrinks the underlying table to the smallest power-of-two capacity
+    /// that holds the current elements without immediately growing again.
+    /// This also lowers the minimum capacity set by `reserve`.
+    pub fn shrink_to_fit(&mut self) {
+        let size = self.table.size();
+        let mut cap = num::next_power_of_two(max(INITIAL_CAPACITY, size));
+        while grow_at(cap, self.load_factor) <= size {
+            cap <<= 1;
+        }
+
+        self.minimum_capacity = cap;
+
+        if cap < self.table.capacity() {
+            self.resize(cap);
+        }
+    }
+
     /// Retrieves a value for the given key, failing if the key is not present.
     pub fn get<'a>(&'a self, k: &K) -> &'a V {
         match self.find(k) {
@@ -1432,6 +1589,82 @@ impl<K: TotalEq + Hash<S>, V, S, H: Hasher<S> + Default> Extendable<(K, V)> for
     }
 }
 
+/// A view into a single location in a `HashMap`, returned by `entry`.
+pub enum Entry<'a, K, V, H> {
+    /// The key is present in the map.
+    Occupied(OccupiedEntry<'a, K, V, H>),
+    /// The key is absent from the map.
+    Vacant(VacantEntry<'a, K, V, H>),
+}
+
+/// An entry for a key which is present in a `HashMap`.
+pub struct OccupiedEntry<'a, K, V, H> {
+    map: &'a mut HashMap<K, V, H>,
+    index: table::FullIndex,
+}
+
+/// An entry for a key which is absent from a `HashMap`.
+pub struct VacantEntry<'a, K, V, H> {
+    map: &'a mut HashMap<K, V, H>,
+    hash: table::SafeHash,
+    key: K,
+}
+
+impl<'a, K: TotalEq + Hash<S>, V, S, H: Hasher<S>> OccupiedEntry<'a, K, V, H> {
+    /// The key stored in the map for this entry.
+    pub fn key<'b>(&'b self) -> &'b K {
+        let (k, _) = self.map.table.read(&self.index);
+        k
+    }
+
+    /// The value stored in the map for this entry.
+    pub fn get<'b>(&'b self) -> &'b V {
+        let (_, v) = self.map.table.read(&self.index);
+        v
+    }
+
+    /// A mutable reference to the value, for modifying it in place.
+    pub fn get_mut<'b>(&'b mut self) -> &'b mut V {
+        let (_, v) = self.map.table.read_mut(&self.index);
+        v
+    }
+
+    /// Converts the entry into a mutable reference to the value which lives
+    /// as long as the borrow of the map.
+    pub fn into_mut(self) -> &'a mut V {
+        let OccupiedEntry { map, index } = self;
+        let (_, v) = map.table.read_mut(&index);
+        v
+    }
+
+    /// Replaces the entry's value, returning the old one.
+    pub fn insert(&mut self, value: V) -> V {
+        replace(self.get_mut(), value)
+    }
+
+    /// Removes the entry from the map, returning its value.
+    pub fn remove(self) -> V {
+        let OccupiedEntry { map, index } = self;
+        let value = map.pop_internal(index).unwrap();
+        let size = map.table.size();
+        map.make_some_room(size);
+        value
+    }
+}
+
+impl<'a, K: TotalEq + Hash<S>, V, S, H: Hasher<S>> VacantEntry<'a, K, V, H> {
+    /// The key that would be used when inserting through this entry.
+    pub fn key<'b>(&'b self) -> &'b K {
+        &self.key
+    }
+
+    /// Inserts `value` under the entry's key, returning a reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        let VacantEntry { map, hash, key } = self;
+        map.manual_insert_hashed(hash, key, value)
+    }
+}
+
 /// HashSet iterator
 pub type SetItems<'a, K> =
     iter::Map<'static, (&'a K, &'a ()), &'a K, Entries<'a, K, ()>>;
@@ -1614,7 +1847,7 @@ pub type SetAlgebraItems<'a, T, H> =
 
 #[cfg(test)]
 mod test_map {
-    use super::HashMap;
+    use super::{HashMap, Occupied, Vacant};
     use std::cmp::Equiv;
     use std::hash::Hash;
     use std::iter::{Iterator,range_inclusive,range_step_inclusive};
@@ -2020,6 +2253,164 @@ mod test_map {
             assert_eq!(map.find(&k), Some(&v));
         }
     }
+
+    #[test]
+    fn test_alternate_hashers() {
+        use super::Randomized;
+        use std::default::Default;
+        use std::hash::fnv::FnvHasher;
+        use std::hash::xxhash::XxHasher;
+
+        let mut fnv = HashMap::with_hasher(FnvHasher::new());
+        let mut xx = HashMap::with_hasher(XxHasher::new());
+        for i in range(0, 1000) {
+            fnv.insert(i, i * 2);
+            xx.insert(i, i * 2);
+        }
+        for i in range(0, 1000) {
+            assert_eq!(fnv.find(&i), Some(&(i * 2)));
+            assert_eq!(xx.find(&i), Some(&(i * 2)));
+        }
+
+        let mut random: HashMap<~str, int, Randomized<XxHasher>> = Default::default();
+        random.insert("foo".to_owned(), 1);
+        assert_eq!(random.find_equiv(&("foo")), Some(&1));
+
+        let collected: HashMap<int, int, Randomized<FnvHasher>> =
+            range(0, 10).map(|i| (i, i)).collect();
+        assert_eq!(collected.len(), 10);
+    }
+
+    #[test]
+    fn test_entry() {
+        let mut m = HashMap::new();
+        for &k in [1, 2, 1, 3, 1, 2].iter() {
+            match m.entry(k) {
+                Occupied(mut e) => *e.get_mut() += 1,
+                Vacant(e) => { e.insert(1); }
+            }
+        }
+        assert_eq!(m.len(), 3);
+        assert_eq!(*m.get(&1), 3);
+        assert_eq!(*m.get(&2), 2);
+        assert_eq!(*m.get(&3), 1);
+
+        match m.entry(2) {
+            Occupied(mut e) => {
+                assert_eq!(*e.key(), 2);
+                assert_eq!(e.insert(10), 2);
+                assert_eq!(*e.get(), 10);
+            }
+            Vacant(_) => fail!()
+        }
+        assert_eq!(*m.get(&2), 10);
+
+        match m.entry(1) {
+            Occupied(e) => assert_eq!(e.remove(), 3),
+            Vacant(_) => fail!()
+        }
+        assert!(!m.contains_key(&1));
+
+        let v = match m.entry(4) {
+            Occupied(_) => fail!(),
+            Vacant(e) => {
+                assert_eq!(*e.key(), 4);
+                e.insert(40)
+            }
+        };
+        *v += 2;
+        assert_eq!(*m.get(&4), 42);
+
+        let v = match m.entry(4) {
+            Occupied(e) => e.into_mut(),
+            Vacant(_) => fail!()
+        };
+        *v = 0;
+        assert_eq!(*m.get(&4), 0);
+    }
+
+    #[test]
+    fn test_entry_many() {
+        let mut m = HashMap::new();
+        for i in range(0, 1000) {
+            match m.entry(i % 100) {
+                Occupied(mut e) => { e.get_mut().push(i); }
+                Vacant(e) => { e.insert(vec!(i)); }
+            }
+        }
+        assert_eq!(m.len(), 100);
+        for (k, v) in m.iter() {
+            assert_eq!(v.len(), 10);
+            assert!(v.iter().all(|x| x % 100 == *k));
+        }
+
+        for i in range(0, 100) {
+            match m.entry(i) {
+                Occupied(e) => { e.remove(); }
+                Vacant(_) => fail!()
+            }
+        }
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut m: HashMap<int, int> = range(0, 1000).map(|x| (x, x * 10)).collect();
+        let mut visited = 0;
+        m.retain(|&k, v| {
+            visited += 1;
+            *v += 1;
+            k % 3 == 0
+        });
+        assert_eq!(visited, 1000);
+        assert_eq!(m.len(), 334);
+        for i in range(0, 1000) {
+            if i % 3 == 0 {
+                assert_eq!(m.find(&i), Some(&(i * 10 + 1)));
+            } else {
+                assert!(m.find(&i).is_none());
+            }
+        }
+
+        m.retain(|_, _| false);
+        assert!(m.is_empty());
+        m.retain(|_, _| fail!());
+    }
+
+    #[test]
+    fn test_drain() {
+        let mut m: HashMap<int, int> = range(0, 100).map(|x| (x, x)).collect();
+        let cap = m.capacity();
+        let mut drained: Vec<(int, int)> = m.drain().collect();
+        drained.sort();
+        assert_eq!(drained, range(0, 100).map(|x| (x, x)).collect());
+        assert!(m.is_empty());
+        assert_eq!(m.capacity(), cap);
+
+        m.insert(1, 1);
+        assert_eq!(m.find(&1), Some(&1));
+    }
+
+    #[test]
+    fn test_shrink_to_fit() {
+        let mut m = HashMap::new();
+        m.reserve(1024);
+        for i in range(0, 100) {
+            m.insert(i, i);
+        }
+        assert!(m.capacity() >= 900);
+
+        m.shrink_to_fit();
+        assert!(m.capacity() > 100);
+        assert!(m.capacity() < 256);
+        for i in range(0, 100) {
+            assert_eq!(m.find(&i), Some(&i));
+        }
+
+        m.clear();
+        m.shrink_to_fit();
+        assert_eq!(m.table.capacity(), super::INITIAL_CAPACITY);
+    }
 }
 
 #[cfg(test)]
@@ -2337,6 +2728,69 @@ mod bench {
         });
     }
 
+    // Compare the hashers available to `HashMap` on short integer and string
+    // keys.
+    macro_rules! bench_hasher(
+        ($insert_int:ident, $find_int:ident, $find_str:ident, $hasher:expr) => (
+            #[bench]
+            fn $insert_int(b: &mut Bencher) {
+                use super::HashMap;
+
+                let mut m = HashMap::with_hasher($hasher);
+
+                for i in range_inclusive(1u64, 1000) {
+                    m.insert(i, i);
+                }
+
+                let mut k = 1001u64;
+
+                b.iter(|| {
+                    m.insert(k, k);
+                    k += 1;
+                });
+            }
+
+            #[bench]
+            fn $find_int(b: &mut Bencher) {
+                use super::HashMap;
+
+                let mut m = HashMap::with_hasher($hasher);
+
+                for i in range_inclusive(1u64, 1000) {
+                    m.insert(i, i);
+                }
+
+                b.iter(|| {
+                    m.contains_key(&412);
+                });
+            }
+
+            #[bench]
+            fn $find_str(b: &mut Bencher) {
+                use super::HashMap;
+
+                let mut m = HashMap::with_hasher($hasher);
+
+                for i in range_inclusive(1, 1000) {
+                    m.insert(format!("key{}", i), i);
+                }
+
+                let key = "key412".to_owned();
+
+                b.iter(|| {
+                    m.contains_key(&key);
+                });
+            }
+        )
+    )
+
+    bench_hasher!(sip_insert_int, sip_find_int, sip_find_str,
+                  ::std::hash::sip::SipHasher::new())
+    bench_hasher!(fnv_insert_int, fnv_find_int, fnv_find_str,
+                  ::std::hash::fnv::FnvHasher::new())
+    bench_hasher!(xx_insert_int, xx_find_int, xx_find_str,
+                  ::std::hash::xxhash::XxHasher::new())
+
     #[bench]
     fn find_pop_insert(b: &mut Bencher) {
         use super::HashMap;
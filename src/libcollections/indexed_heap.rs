@@ -0,0 +1,395 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! An addressable priority queue implemented with a 4-ary heap
+//!
+//! Pushing an item onto an `IndexedHeap` returns a `Handle` which stays
+//! valid until that item leaves the heap. The handle can be used to look the
+//! item up, to change it (moving it up or down the heap as its priority
+//! rises or falls, i.e. increase-key and decrease-key) or to remove it,
+//! all in O(log n). This lets algorithms like Dijkstra's update queued
+//! entries instead of pushing duplicates and skipping stale ones.
+//!
+//! Handles carry a generation count, so a handle to an item which has since
+//! been popped or removed is recognised as stale even if its slot has been
+//! reused for a newer item.
+
+#![allow(missing_doc)]
+
+use std::cmp::min;
+use std::mem::swap;
+use std::slice;
+use std::uint;
+
+static ARITY: uint = 4;
+
+// Marks a slot which doesn't currently hold an item.
+static FREE: uint = uint::MAX;
+
+/// Identifies an item pushed onto an `IndexedHeap`.
+#[deriving(Clone, Eq, TotalEq, Hash, Show)]
+pub struct Handle {
+    slot: uint,
+    generation: uint,
+}
+
+struct Entry<T> {
+    value: T,
+    slot: uint,
+}
+
+struct Slot {
+    // Position of the item in `heap`, or FREE.
+    pos: uint,
+    generation: uint,
+}
+
+/// An addressable priority queue. By default the greatest item is on top,
+/// like `PriorityQueue`; `new_min` creates a heap with the least item on top.
+pub struct IndexedHeap<T> {
+    heap: Vec<Entry<T>>,
+    slots: Vec<Slot>,
+    free: Vec<uint>,
+    min: bool,
+}
+
+impl<T: Ord> Container for IndexedHeap<T> {
+    /// Returns the length of the heap
+    fn len(&self) -> uint { self.heap.len() }
+}
+
+impl<T: Ord> Mutable for IndexedHeap<T> {
+    /// Drop all items from the heap, invalidating all handles
+    fn clear(&mut self) {
+        while !self.heap.is_empty() {
+            self.pop_entry(0);
+        }
+    }
+}
+
+impl<T: Ord> IndexedHeap<T> {
+    /// Create an empty IndexedHeap with the greatest item on top
+    pub fn new() -> IndexedHeap<T> {
+        IndexedHeap { heap: Vec::new(), slots: Vec::new(), free: Vec::new(), min: false }
+    }
+
+    /// Create an empty IndexedHeap with the least item on top
+    pub fn new_min() -> IndexedHeap<T> {
+        IndexedHeap { heap: Vec::new(), slots: Vec::new(), free: Vec::new(), min: true }
+    }
+
+    /// An iterator visiting all items in arbitrary order
+    pub fn iter<'a>(&'a self) -> Items<'a, T> {
+        Items { iter: self.heap.iter() }
+    }
+
+    /// Returns the top item of the heap, or None if it is empty
+    pub fn top<'a>(&'a self) -> Option<&'a T> {
+        if self.heap.is_empty() { None } else { Some(&self.heap.get(0).value) }
+    }
+
+    /// Returns the handle of the top item of the heap, or None if it is empty
+    pub fn top_handle(&self) -> Option<Handle> {
+        if self.heap.is_empty() { None } else { Some(self.handle_for(self.heap.get(0).slot)) }
+    }
+
+    /// Push an item onto the heap, returning a handle to it
+    pub fn push(&mut self, value: T) -> Handle {
+        let slot = match self.free.pop() {
+            Some(slot) => slot,
+            None => {
+                self.slots.push(Slot { pos: FREE, generation: 0 });
+                self.slots.len() - 1
+            }
+        };
+
+        let pos = self.heap.len();
+        self.heap.push(Entry { value: value, slot: slot });
+        self.slots.get_mut(slot).pos = pos;
+        self.sift_up(pos);
+        self.handle_for(slot)
+    }
+
+    /// Pop the top item from the heap, or None if it is empty
+    pub fn pop(&mut self) -> Option<T> {
+        if self.heap.is_empty() { None } else { Some(self.pop_entry(0)) }
+    }
+
+    /// Returns true if `handle` refers to an item still in the heap
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.position(handle).is_some()
+    }
+
+    /// Returns the item for `handle`, or None if it has left the heap
+    pub fn get<'a>(&'a self, handle: Handle) -> Option<&'a T> {
+        self.position(handle).map(|pos| &self.heap.get(pos).value)
+    }
+
+    /// Replace the item for `handle` with `value`, moving it up or down the
+    /// heap as needed. Returns the old item, or None (dropping `value`) if
+    /// the handle is stale.
+    pub fn update(&mut self, handle: Handle, value: T) -> Option<T> {
+        let mut value = value;
+        match self.modify(handle, |v| swap(v, &mut value)) {
+            Some(()) => Some(value),
+            None => None,
+        }
+    }
+
+    /// Modify the item for `handle` in place, then move it up or down the
+    /// heap as needed. Returns None if the handle is stale.
+    pub fn modify(&mut self, handle: Handle, f: |&mut T|) -> Option<()> {
+        let pos = match self.position(handle) {
+            Some(pos) => pos,
+            None => return None,
+        };
+
+        f(&mut self.heap.get_mut(pos).value);
+
+        let pos = self.sift_up(pos);
+        self.sift_down(pos);
+        Some(())
+    }
+
+    /// Remove the item for `handle` from the heap, returning it, or None if
+    /// the handle is stale.
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        self.position(handle).map(|pos| self.pop_entry(pos))
+    }
+
+    /// Consume the IndexedHeap and return the items in arbitrary order
+    pub fn into_vec(self) -> Vec<T> {
+        let IndexedHeap { heap, .. } = self;
+        heap.move_iter().map(|e| e.value).collect()
+    }
+
+    /// Consume the IndexedHeap and return a vector in sorted (ascending)
+    /// order
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        let mut heap = self;
+        let mut v = Vec::with_capacity(heap.len());
+        loop {
+            match heap.pop() {
+                Some(x) => v.push(x),
+                None => break,
+            }
+        }
+        if !heap.min {
+            v.reverse();
+        }
+        v
+    }
+
+    fn handle_for(&self, slot: uint) -> Handle {
+        Handle { slot: slot, generation: self.slots.get(slot).generation }
+    }
+
+    fn position(&self, handle: Handle) -> Option<uint> {
+        if handle.slot >= self.slots.len() {
+            return None;
+        }
+        let slot = self.slots.get(handle.slot);
+        if slot.generation != handle.generation || slot.pos == FREE {
+            None
+        } else {
+            Some(slot.pos)
+        }
+    }
+
+    // Remove the entry at `pos`, releasing its slot.
+    fn pop_entry(&mut self, pos: uint) -> T {
+        let last = self.heap.len() - 1;
+        self.swap(pos, last);
+        let entry = self.heap.pop().unwrap();
+
+        {
+            let slot = self.slots.get_mut(entry.slot);
+            slot.pos = FREE;
+            slot.generation += 1;
+        }
+        self.free.push(entry.slot);
+
+        if pos < self.heap.len() {
+            let pos = self.sift_up(pos);
+            self.sift_down(pos);
+        }
+        entry.value
+    }
+
+    // Whether the entry at `a` belongs above the entry at `b`.
+    #[inline]
+    fn above(&self, a: uint, b: uint) -> bool {
+        let (a, b) = (&self.heap.get(a).value, &self.heap.get(b).value);
+        if self.min { *a < *b } else { *a > *b }
+    }
+
+    fn swap(&mut self, a: uint, b: uint) {
+        if a == b { return }
+        self.heap.as_mut_slice().swap(a, b);
+        let (slot_a, slot_b) = (self.heap.get(a).slot, self.heap.get(b).slot);
+        self.slots.get_mut(slot_a).pos = a;
+        self.slots.get_mut(slot_b).pos = b;
+    }
+
+    // Returns the entry's new position.
+    fn sift_up(&mut self, mut pos: uint) -> uint {
+        while pos > 0 {
+            let parent = (pos - 1) / ARITY;
+            if !self.above(pos, parent) { break }
+            self.swap(pos, parent);
+            pos = parent;
+        }
+        pos
+    }
+
+    fn sift_down(&mut self, mut pos: uint) {
+        let len = self.heap.len();
+        loop {
+            let first = pos * ARITY + 1;
+            if first >= len { break }
+
+            let mut best = first;
+            for child in range(first + 1, min(first + ARITY, len)) {
+                if self.above(child, best) {
+                    best = child;
+                }
+            }
+
+            if !self.above(best, pos) { break }
+            self.swap(pos, best);
+            pos = best;
+        }
+    }
+}
+
+/// IndexedHeap iterator
+pub struct Items<'a, T> {
+    iter: slice::Items<'a, Entry<T>>,
+}
+
+impl<'a, T> Iterator<&'a T> for Items<'a, T> {
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> { self.iter.next().map(|e| &e.value) }
+
+    #[inline]
+    fn size_hint(&self) -> (uint, Option<uint>) { self.iter.size_hint() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IndexedHeap;
+    use rand;
+    use rand::Rng;
+
+    #[test]
+    fn test_push_pop() {
+        let mut heap = IndexedHeap::new();
+        for &x in [5, 1, 8, 3, 9, 2, 7].iter() {
+            heap.push(x);
+        }
+        assert_eq!(heap.len(), 7);
+        assert_eq!(heap.top(), Some(&9));
+        let popped: Vec<int> = range(0, 7).map(|_| heap.pop().unwrap()).collect();
+        assert_eq!(popped, vec!(9, 8, 7, 5, 3, 2, 1));
+        assert!(heap.pop().is_none());
+        assert!(heap.top().is_none());
+    }
+
+    #[test]
+    fn test_min() {
+        let mut heap = IndexedHeap::new_min();
+        for &x in [5, 1, 8, 3].iter() {
+            heap.push(x);
+        }
+        assert_eq!(heap.into_sorted_vec(), vec!(1, 3, 5, 8));
+    }
+
+    #[test]
+    fn test_handles() {
+        let mut heap = IndexedHeap::new_min();
+        let a = heap.push(10);
+        let b = heap.push(20);
+        let c = heap.push(30);
+
+        // decrease-key
+        assert_eq!(heap.update(c, 5), Some(30));
+        assert_eq!(heap.top_handle(), Some(c));
+        // increase-key
+        assert_eq!(heap.update(c, 25), Some(5));
+        assert_eq!(heap.top_handle(), Some(a));
+
+        assert_eq!(heap.get(b), Some(&20));
+        assert_eq!(heap.remove(b), Some(20));
+        assert!(!heap.contains(b));
+        assert_eq!(heap.get(b), None);
+        assert_eq!(heap.remove(b), None);
+        assert_eq!(heap.update(b, 1), None);
+
+        // The freed slot is reused, but the old handle stays stale.
+        let d = heap.push(1);
+        assert!(d != b);
+        assert!(!heap.contains(b));
+        assert_eq!(heap.get(d), Some(&1));
+
+        assert!(heap.modify(a, |v| *v = 0).is_some());
+        assert_eq!(heap.pop(), Some(0));
+        assert!(!heap.contains(a));
+        assert_eq!(heap.into_sorted_vec(), vec!(1, 25));
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng = rand::weak_rng();
+        let mut heap = IndexedHeap::new();
+        let mut live = Vec::new();
+
+        for _ in range(0, 2000) {
+            match rng.gen_range(0u, 4) {
+                0 | 1 => {
+                    let x = rng.gen_range(0, 1000);
+                    live.push((heap.push(x), x));
+                }
+                2 if !live.is_empty() => {
+                    let i = rng.gen_range(0, live.len());
+                    let (h, _) = *live.get(i);
+                    let x = rng.gen_range(0, 1000);
+                    heap.update(h, x);
+                    *live.get_mut(i) = (h, x);
+                }
+                _ if !live.is_empty() => {
+                    let i = rng.gen_range(0, live.len());
+                    let (h, x) = live.swap_remove(i).unwrap();
+                    assert_eq!(heap.remove(h), Some(x));
+                }
+                _ => {}
+            }
+
+            assert_eq!(heap.len(), live.len());
+            let max = live.iter().map(|&(_, x)| x).max();
+            assert_eq!(heap.top().map(|&x| x), max);
+        }
+
+        let mut expected: Vec<int> = live.iter().map(|&(_, x)| x).collect();
+        expected.sort();
+        assert_eq!(heap.into_sorted_vec(), expected);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut heap = IndexedHeap::new();
+        let a = heap.push(1);
+        heap.clear();
+        assert!(heap.is_empty());
+        assert!(!heap.contains(a));
+        let b = heap.push(2);
+        assert_eq!(heap.iter().map(|&x| x).collect::<Vec<int>>(), vec!(2));
+        assert!(heap.contains(b));
+    }
+}
@@ -30,14 +30,18 @@ extern crate rand;
 #[cfg(test)] #[phase(syntax, link)] extern crate log;
 
 pub use bitv::Bitv;
-pub use btree::BTree;
+pub use btree::{BTreeMap, BTreeSet};
 pub use deque::Deque;
 pub use dlist::DList;
 pub use enum_set::EnumSet;
 pub use hashmap::{HashMap, HashSet};
+pub use indexed_heap::IndexedHeap;
 pub use lru_cache::LruCache;
-pub use priority_queue::PriorityQueue;
+pub use persistent::{PersistentVec, PersistentMap, PersistentSet};
+pub use priority_queue::{PriorityQueue, MinPriorityQueue};
+pub use radix::{RadixMap, RadixSet};
 pub use ringbuf::RingBuf;
+pub use roaring::RoaringBitmap;
 pub use smallintmap::SmallIntMap;
 pub use treemap::{TreeMap, TreeSet};
 pub use trie::{TrieMap, TrieSet};
@@ -48,9 +52,13 @@ pub mod deque;
 pub mod dlist;
 pub mod enum_set;
 pub mod hashmap;
+pub mod indexed_heap;
 pub mod lru_cache;
+pub mod persistent;
 pub mod priority_queue;
+pub mod radix;
 pub mod ringbuf;
+pub mod roaring;
 pub mod smallintmap;
 pub mod treemap;
 pub mod trie;
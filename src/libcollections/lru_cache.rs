@@ -36,9 +36,42 @@
 //! cache.change_capacity(1);
 //! assert!(cache.get(&2).is_none());
 //! ```
+//!
+//! Beyond that, a cache can be given:
+//!
+//! * A `Weigher`, which makes the capacity a bound on the total weight of
+//!   the entries (for example their size in bytes) rather than their number.
+//! * A `Clock`, which allows entries to be put with a time-to-live after
+//!   which they expire and are no longer returned.
+//! * An `EvictionListener`, which is handed every pair the cache drops on
+//!   its own, whether because of the capacity or because it expired.
+//!
+//! These have to be `Send` and `Share`, so that a cache using them can still
+//! be sent to and shared between tasks.
+//!
+//! Hits, misses, evictions and expirations are counted in `CacheStats`.
+//!
+//! ```rust
+//! use collections::lru_cache::{LruCache, Weigher};
+//!
+//! struct ByLength;
+//!
+//! impl Weigher<int, ~str> for ByLength {
+//!     fn weigh(&self, _: &int, v: &~str) -> uint { v.len() }
+//! }
+//!
+//! let mut cache = LruCache::with_weigher(10, ~ByLength);
+//! cache.put(1, "hello".to_owned());
+//! cache.put(2, "world".to_owned());
+//! assert_eq!(cache.weight(), 10);
+//! cache.put(3, "!".to_owned());
+//! assert!(cache.peek(&1).is_none());
+//! assert_eq!(cache.stats().evictions, 1);
+//! ```
 
 use std::cast;
 use std::container::Container;
+use std::default::Default;
 use std::hash::Hash;
 use std::fmt;
 use std::ptr;
@@ -50,16 +83,104 @@ struct KeyRef<K> { k: *K }
 struct LruEntry<K, V> {
     key: Option<K>,
     value: Option<V>,
+    weight: uint,
+    expires: Option<u64>,
     next: *mut LruEntry<K, V>,
     prev: *mut LruEntry<K, V>,
 }
 
+/// A source of monotonically increasing time, used to expire entries. The
+/// unit is up to the implementation, but must match the time-to-live values
+/// given to `put_with_ttl`.
+///
+/// # Example
+///
+/// ```rust
+/// extern crate collections;
+/// extern crate time;
+///
+/// use collections::lru_cache::{Clock, LruCache};
+///
+/// struct PreciseTime;
+///
+/// impl Clock for PreciseTime {
+///     fn now(&self) -> u64 { time::precise_time_ns() }
+/// }
+///
+/// fn main() {
+///     let mut cache = LruCache::new(100);
+///     cache.set_clock(~PreciseTime);
+///     // Keep the entry for one second.
+///     cache.put_with_ttl(1, 10, 1000000000);
+///     assert_eq!(cache.get(&1), Some(&10));
+/// }
+/// ```
+pub trait Clock {
+    /// Return the current time.
+    fn now(&self) -> u64;
+}
+
+/// Computes the weight of a key-value pair, in whatever unit the capacity of
+/// the cache is expressed in.
+pub trait Weigher<K, V> {
+    /// Return the weight of the pair. This is called once when the pair is
+    /// put into the cache, so it must not depend on anything else that can
+    /// change while the pair is cached.
+    fn weigh(&self, key: &K, value: &V) -> uint;
+}
+
+/// Why a key-value pair was dropped from the cache.
+#[deriving(Clone, Eq, Show)]
+pub enum EvictionCause {
+    /// The pair was the least recently used one when the cache went over
+    /// its capacity.
+    Capacity,
+    /// The time-to-live of the pair ran out.
+    Expired,
+}
+
+/// Receives the key-value pairs the cache drops on its own. Pairs which are
+/// removed with `pop` or `clear`, or replaced with `put`, are not reported.
+pub trait EvictionListener<K, V> {
+    /// Called with each evicted pair, after it has been removed.
+    fn on_evict(&mut self, key: K, value: V, cause: EvictionCause);
+}
+
+/// Counters of what happened to a cache's lookups and entries.
+#[deriving(Clone, Eq, Show, Default)]
+pub struct CacheStats {
+    /// The number of `get`s which found a live entry.
+    pub hits: u64,
+    /// The number of `get`s which found nothing, or an expired entry.
+    pub misses: u64,
+    /// The number of entries evicted to stay within the capacity.
+    pub evictions: u64,
+    /// The number of entries dropped because they expired.
+    pub expirations: u64,
+}
+
+impl Add<CacheStats, CacheStats> for CacheStats {
+    fn add(&self, other: &CacheStats) -> CacheStats {
+        CacheStats {
+            hits: self.hits + other.hits,
+            misses: self.misses + other.misses,
+            evictions: self.evictions + other.evictions,
+            expirations: self.expirations + other.expirations,
+        }
+    }
+}
+
 /// An LRU Cache.
 pub struct LruCache<K, V> {
     map: HashMap<KeyRef<K>, ~LruEntry<K, V>>,
     max_size: uint,
+    weight: uint,
     head: *mut LruEntry<K, V>,
     tail: *mut LruEntry<K, V>,
+    weigher: Option<~Weigher<K, V>:Send+Share>,
+    clock: Option<~Clock:Send+Share>,
+    listener: Option<~EvictionListener<K, V>:Send+Share>,
+    stats: CacheStats,
 }
 
 impl<S, K: Hash<S>> Hash<S> for KeyRef<K> {
@@ -81,15 +202,19 @@ impl<K, V> LruEntry<K, V> {
         LruEntry {
             key: None,
             value: None,
+            weight: 0,
+            expires: None,
             next: ptr::mut_null(),
             prev: ptr::mut_null(),
         }
     }
 
-    fn with_key_value(k: K, v: V) -> LruEntry<K, V> {
+    fn with_key_value(k: K, v: V, weight: uint, expires: Option<u64>) -> LruEntry<K, V> {
         LruEntry {
             key: Some(k),
             value: Some(v),
+            weight: weight,
+            expires: expires,
             next: ptr::mut_null(),
             prev: ptr::mut_null(),
         }
@@ -99,11 +224,28 @@ impl<K, V> LruEntry<K, V> {
 impl<K: Hash + TotalEq, V> LruCache<K, V> {
     /// Create an LRU Cache that holds at most `capacity` items.
     pub fn new(capacity: uint) -> LruCache<K, V> {
+        LruCache::new_internal(capacity, None)
+    }
+
+    /// Create an LRU Cache whose entries are weighed by `weigher`, and which
+    /// holds entries with a total weight of at most `capacity`.
+    pub fn with_weigher(capacity: uint, weigher: ~Weigher<K, V>:Send+Share)
+                        -> LruCache<K, V> {
+        LruCache::new_internal(capacity, Some(weigher))
+    }
+
+    fn new_internal(capacity: uint, weigher: Option<~Weigher<K, V>:Send+Share>)
+                    -> LruCache<K, V> {
         let cache = LruCache {
             map: HashMap::new(),
             max_size: capacity,
+            weight: 0,
             head: unsafe{ cast::transmute(~LruEntry::<K, V>::new()) },
             tail: unsafe{ cast::transmute(~LruEntry::<K, V>::new()) },
+            weigher: weigher,
+            clock: None,
+            listener: None,
+            stats: Default::default(),
         };
         unsafe {
             (*cache.head).next = cache.tail;
@@ -112,95 +254,230 @@ impl<K: Hash + TotalEq, V> LruCache<K, V> {
         return cache;
     }
 
+    /// Set the clock used to expire entries put with `put_with_ttl`.
+    pub fn set_clock(&mut self, clock: ~Clock:Send+Share) {
+        self.clock = Some(clock);
+    }
+
+    /// Set the listener which is told about evicted and expired entries.
+    pub fn set_eviction_listener(&mut self,
+                                 listener: ~EvictionListener<K, V>:Send+Share) {
+        self.listener = Some(listener);
+    }
+
     /// Put a key-value pair into cache.
+    ///
+    /// A pair weighing more than the capacity of the cache is evicted straight
+    /// away, along with any value it replaces, and the other entries are left
+    /// alone.
     pub fn put(&mut self, k: K, v: V) {
-        let mut key_existed = false;
-        let (node_ptr, node_opt) = match self.map.find_mut(&KeyRef{k: &k}) {
+        self.put_internal(k, v, None);
+    }
+
+    /// Put a key-value pair into cache, which expires once `ttl` has passed
+    /// on the cache's clock.
+    ///
+    /// # Failure
+    ///
+    /// Fails if no clock has been set with `set_clock`.
+    pub fn put_with_ttl(&mut self, k: K, v: V, ttl: u64) {
+        let expires = match self.clock {
+            Some(ref clock) => clock.now() + ttl,
+            None => fail!("LruCache::put_with_ttl: no clock has been set"),
+        };
+        self.put_internal(k, v, Some(expires));
+    }
+
+    fn put_internal(&mut self, k: K, v: V, expires: Option<u64>) {
+        let weight = match self.weigher {
+            Some(ref weigher) => weigher.weigh(&k, &v),
+            None => 1,
+        };
+        if weight > self.max_size {
+            // Evicting everything else wouldn't make room for it anyway
+            let node_ptr = match self.map.find_mut(&KeyRef{k: &k}) {
+                Some(node) => Some(&mut **node as *mut LruEntry<K, V>),
+                None => None,
+            };
+            match node_ptr {
+                Some(node_ptr) => { self.remove_node(node_ptr); }
+                None => {}
+            }
+            self.dropped(k, v, Capacity);
+            return;
+        }
+        let existing = match self.map.find_mut(&KeyRef{k: &k}) {
             Some(node) => {
-                key_existed = true;
                 node.value = Some(v);
+                node.expires = expires;
+                self.weight = self.weight - node.weight + weight;
+                node.weight = weight;
                 let node_ptr: *mut LruEntry<K, V> = &mut **node;
-                (node_ptr, None)
+                Some(node_ptr)
+            }
+            None => None,
+        };
+        match existing {
+            Some(node_ptr) => {
+                self.detach(node_ptr);
+                self.attach(node_ptr);
             }
             None => {
-                let mut node = ~LruEntry::with_key_value(k, v);
+                let mut node = ~LruEntry::with_key_value(k, v, weight, expires);
                 let node_ptr: *mut LruEntry<K, V> = &mut *node;
-                (node_ptr, Some(node))
-            }
-        };
-        if key_existed {
-            self.detach(node_ptr);
-            self.attach(node_ptr);
-        } else {
-            let keyref = unsafe { (*node_ptr).key.as_ref().unwrap() };
-            self.map.swap(KeyRef{k: keyref}, node_opt.unwrap());
-            self.attach(node_ptr);
-            if self.len() > self.capacity() {
-                self.remove_lru();
+                let keyref = unsafe { (*node_ptr).key.as_ref().unwrap() };
+                self.map.swap(KeyRef{k: keyref}, node);
+                self.attach(node_ptr);
+                self.weight += weight;
             }
         }
+        self.shrink();
     }
 
     /// Return a value corresponding to the key in the cache.
     pub fn get<'a>(&'a mut self, k: &K) -> Option<&'a V> {
-        let (value, node_ptr_opt) = match self.map.find_mut(&KeyRef{k: k}) {
-            None => (None, None),
-            Some(node) => {
-                let node_ptr: *mut LruEntry<K, V> = &mut **node;
-                unsafe {
-                    match (*node_ptr).value {
-                        None => (None, None),
-                        Some(ref value) => (Some(value), Some(node_ptr))
-                    }
-                }
+        let node_ptr: *mut LruEntry<K, V> = match self.map.find_mut(&KeyRef{k: k}) {
+            Some(node) => &mut **node,
+            None => {
+                self.stats.misses += 1;
+                return None;
             }
         };
-        match node_ptr_opt {
-            None => (),
-            Some(node_ptr) => {
-                self.detach(node_ptr);
-                self.attach(node_ptr);
+        if self.is_expired(unsafe { (*node_ptr).expires }) {
+            self.stats.misses += 1;
+            self.evict(node_ptr, Expired);
+            return None;
+        }
+        self.stats.hits += 1;
+        self.detach(node_ptr);
+        self.attach(node_ptr);
+        unsafe { (*node_ptr).value.as_ref() }
+    }
+
+    /// Return a value corresponding to the key in the cache, without
+    /// marking it as used or counting the lookup in the statistics.
+    pub fn peek<'a>(&'a self, k: &K) -> Option<&'a V> {
+        match self.map.find(&KeyRef{k: k}) {
+            Some(node) => {
+                if self.is_expired(node.expires) {
+                    None
+                } else {
+                    node.value.as_ref()
+                }
             }
+            None => None,
         }
-        return value;
     }
 
     /// Remove and return a value corresponding to the key from the cache.
     pub fn pop(&mut self, k: &K) -> Option<V> {
-        match self.map.pop(&KeyRef{k: k}) {
-            None => None,
-            Some(lru_entry) => lru_entry.value
+        let node_ptr: *mut LruEntry<K, V> = match self.map.find_mut(&KeyRef{k: k}) {
+            Some(node) => &mut **node,
+            None => return None,
+        };
+        if self.is_expired(unsafe { (*node_ptr).expires }) {
+            self.evict(node_ptr, Expired);
+            return None;
+        }
+        let (_, v) = self.remove_node(node_ptr);
+        Some(v)
+    }
+
+    /// Remove all expired key-value pairs from the cache. Expired pairs are
+    /// otherwise only removed when they are looked up, and until then they
+    /// count towards the length and weight of the cache.
+    pub fn purge_expired(&mut self) {
+        let mut cur = unsafe { (*self.tail).prev };
+        while cur != self.head {
+            let prev = unsafe { (*cur).prev };
+            if self.is_expired(unsafe { (*cur).expires }) {
+                self.evict(cur, Expired);
+            }
+            cur = prev;
         }
     }
 
-    /// Return the maximum number of key-value pairs the cache can hold.
+    /// Return the maximum number of key-value pairs the cache can hold, or
+    /// their maximum total weight if the cache has a `Weigher`.
     pub fn capacity(&self) -> uint {
         self.max_size
     }
 
+    /// Return the total weight of the key-value pairs in the cache. Without
+    /// a `Weigher` this is the same as the number of pairs.
+    pub fn weight(&self) -> uint {
+        self.weight
+    }
+
     /// Change the number of key-value pairs the cache can hold. Remove
     /// least-recently-used key-value pairs if necessary.
     pub fn change_capacity(&mut self, capacity: uint) {
-        for _ in range(capacity, self.len()) {
+        self.max_size = capacity;
+        self.shrink();
+    }
+
+    /// Return the statistics gathered since the cache was created, or since
+    /// they were last reset.
+    pub fn stats(&self) -> CacheStats {
+        self.stats.clone()
+    }
+
+    /// Reset all the statistics to zero.
+    pub fn reset_stats(&mut self) {
+        self.stats = Default::default();
+    }
+
+    #[inline]
+    fn shrink(&mut self) {
+        while self.weight > self.max_size && self.len() > 0 {
             self.remove_lru();
         }
-        self.max_size = capacity;
     }
 
     #[inline]
     fn remove_lru(&mut self) {
         if self.len() > 0 {
             let lru = unsafe { (*self.tail).prev };
-            self.detach(lru);
-            unsafe {
-                match (*lru).key {
-                    None => (),
-                    Some(ref k) => { self.map.pop(&KeyRef{k: k}); }
-                }
-            }
+            self.evict(lru, Capacity);
+        }
+    }
+
+    fn is_expired(&self, expires: Option<u64>) -> bool {
+        match expires {
+            Some(expires) => match self.clock {
+                Some(ref clock) => clock.now() >= expires,
+                None => false,
+            },
+            None => false,
         }
     }
 
+    fn evict(&mut self, node: *mut LruEntry<K, V>, cause: EvictionCause) {
+        let (k, v) = self.remove_node(node);
+        self.dropped(k, v, cause);
+    }
+
+    fn dropped(&mut self, k: K, v: V, cause: EvictionCause) {
+        match cause {
+            Capacity => self.stats.evictions += 1,
+            Expired => self.stats.expirations += 1,
+        }
+        match self.listener {
+            Some(ref mut listener) => listener.on_evict(k, v, cause),
+            None => {}
+        }
+    }
+
+    fn remove_node(&mut self, node: *mut LruEntry<K, V>) -> (K, V) {
+        self.detach(node);
+        let mut entry = unsafe {
+            let k = (*node).key.as_ref().unwrap();
+            self.map.pop(&KeyRef{k: k}).unwrap()
+        };
+        self.weight -= entry.weight;
+        (entry.key.take_unwrap(), entry.value.take_unwrap())
+    }
+
     #[inline]
     fn detach(&mut self, node: *mut LruEntry<K, V>) {
         unsafe {
@@ -260,6 +537,11 @@ impl<K: Hash + TotalEq, V> Mutable for LruCache<K, V> {
     /// Clear the cache of all key-value pairs.
     fn clear(&mut self) {
         self.map.clear();
+        self.weight = 0;
+        unsafe {
+            (*self.head).next = self.tail;
+            (*self.tail).prev = self.head;
+        }
     }
 }
 
@@ -275,7 +557,51 @@ impl<K, V> Drop for LruCache<K, V> {
 
 #[cfg(test)]
 mod tests {
-    use super::LruCache;
+    use super::{LruCache, Clock, Weigher, EvictionListener, EvictionCause,
+                Capacity, Expired};
+    use std::default::Default;
+    use std::sync::atomics::{AtomicUint, SeqCst, INIT_ATOMIC_UINT};
+    use std::unstable::sync::Exclusive;
+
+    static mut NOW: AtomicUint = INIT_ATOMIC_UINT;
+
+    struct TestClock;
+
+    impl Clock for TestClock {
+        fn now(&self) -> u64 { unsafe { NOW.load(SeqCst) as u64 } }
+    }
+
+    fn advance(by: uint) {
+        unsafe { NOW.fetch_add(by, SeqCst); }
+    }
+
+    struct ByValue;
+
+    impl Weigher<int, uint> for ByValue {
+        fn weigh(&self, _: &int, v: &uint) -> uint { *v }
+    }
+
+    // A `Sender` isn't `Share` on its own
+    struct SendEvictions(Exclusive<Sender<(int, uint, EvictionCause)>>);
+
+    impl EvictionListener<int, uint> for SendEvictions {
+        fn on_evict(&mut self, k: int, v: uint, cause: EvictionCause) {
+            let SendEvictions(ref tx) = *self;
+            unsafe { tx.with(|tx| tx.send((k, v, cause))) }
+        }
+    }
+
+    fn send_evictions(tx: Sender<(int, uint, EvictionCause)>) -> ~SendEvictions {
+        ~SendEvictions(Exclusive::new(tx))
+    }
+
+    fn assert_send<T: Send>() {}
+
+    #[test]
+    fn test_send() {
+        // Fails to compile rather than at runtime
+        assert_send::<LruCache<int, int>>();
+    }
 
     fn assert_opt_eq<V: Eq>(opt: Option<&V>, v: V) {
         assert!(opt.is_some());
@@ -364,4 +690,123 @@ mod tests {
         assert!(cache.get(&2).is_none());
         assert_eq!(cache.to_str(), "{}".to_owned());
     }
+
+    #[test]
+    fn test_pop_unlinks() {
+        let mut cache: LruCache<int, int> = LruCache::new(2);
+        cache.put(1, 10);
+        cache.put(2, 20);
+        cache.pop(&2);
+        cache.put(3, 30);
+        cache.put(4, 40);
+        assert_eq!(cache.to_str(), "{4: 40, 3: 30}".to_owned());
+        cache.clear();
+        cache.put(5, 50);
+        assert_eq!(cache.to_str(), "{5: 50}".to_owned());
+    }
+
+    #[test]
+    fn test_peek() {
+        let mut cache: LruCache<int, int> = LruCache::new(2);
+        cache.put(1, 10);
+        cache.put(2, 20);
+        assert_opt_eq(cache.peek(&1), 10);
+        assert!(cache.peek(&3).is_none());
+        cache.put(3, 30);
+        assert!(cache.peek(&1).is_none());
+        assert_eq!(cache.stats().hits, 0);
+        assert_eq!(cache.stats().misses, 0);
+    }
+
+    #[test]
+    fn test_weigher() {
+        let mut cache = LruCache::with_weigher(10, ~ByValue);
+        let (tx, rx) = channel();
+        cache.set_eviction_listener(send_evictions(tx));
+
+        cache.put(1, 4u);
+        cache.put(2, 4u);
+        cache.put(3, 1u);
+        assert_eq!(cache.weight(), 9);
+        assert!(rx.try_recv().is_err());
+
+        cache.put(4, 3u);
+        assert_eq!(rx.recv(), (1, 4, Capacity));
+        assert_eq!(cache.weight(), 8);
+
+        // Growing an existing entry can evict others too
+        cache.put(3, 6u);
+        assert_eq!(rx.recv(), (2, 4, Capacity));
+        assert_eq!(cache.weight(), 9);
+
+        // An entry heavier than the whole cache can't be kept, but doesn't
+        // push out the others either
+        cache.put(5, 11u);
+        assert_eq!(rx.recv(), (5, 11, Capacity));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.weight(), 9);
+        assert_eq!(cache.stats().evictions, 3);
+
+        // The value it replaces is gone too
+        cache.put(4, 11u);
+        assert_eq!(rx.recv(), (4, 11, Capacity));
+        assert!(cache.peek(&4).is_none());
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.weight(), 6);
+
+        cache.put(6, 2u);
+        cache.put(7, 2u);
+        cache.change_capacity(3);
+        assert!(cache.peek(&3).is_none());
+        assert!(cache.peek(&6).is_none());
+        assert_eq!(cache.weight(), 2);
+    }
+
+    #[test]
+    fn test_ttl() {
+        let mut cache = LruCache::new(10);
+        cache.set_clock(~TestClock);
+        let (tx, rx) = channel();
+        cache.set_eviction_listener(send_evictions(tx));
+
+        cache.put_with_ttl(1, 10u, 5);
+        cache.put_with_ttl(2, 20u, 10);
+        cache.put(3, 30u);
+        cache.put_with_ttl(4, 40u, 5);
+        assert_opt_eq(cache.get(&1), 10);
+
+        advance(5);
+        assert!(cache.peek(&1).is_none());
+        assert!(cache.get(&1).is_none());
+        assert_eq!(rx.recv(), (1, 10, Expired));
+        assert_opt_eq(cache.get(&2), 20);
+        assert_eq!(cache.len(), 3);
+
+        cache.purge_expired();
+        assert_eq!(rx.recv(), (4, 40, Expired));
+        assert_eq!(cache.len(), 2);
+
+        // Putting again resets the expiry
+        cache.put_with_ttl(2, 21u, 10);
+        advance(5);
+        assert_opt_eq(cache.get(&2), 21);
+        assert!(cache.pop(&2).is_some());
+
+        advance(100);
+        assert_opt_eq(cache.get(&3), 30);
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 4);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.expirations, 2);
+        cache.reset_stats();
+        assert_eq!(cache.stats(), Default::default());
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_ttl_without_clock() {
+        let mut cache = LruCache::new(1);
+        cache.put_with_ttl(1, 1, 1);
+    }
 }
@@ -0,0 +1,1068 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Persistent (immutable) collections with structural sharing.
+//!
+//! Every "modifying" operation on these types leaves the receiver untouched
+//! and returns a new version, which shares all the nodes it didn't have to
+//! change with the old one through `Rc`. Updates copy only the path from the
+//! root to the affected element, so they cost O(log n) time and space, and
+//! cloning a collection is O(1). This makes them a good fit for keeping a
+//! history of states (undo/redo) or handing out snapshots to readers within
+//! a task while a writer carries on.
+//!
+//! * `PersistentVec` is a 32-way trie with a detached tail, in the style of
+//!   Clojure's vectors: indexing, `set`, `push` and `pop` touch at most
+//!   log32(n) nodes.
+//! * `PersistentMap` and `PersistentSet` are ordered, built on a
+//!   path-copying AVL tree.
+//!
+//! Since the nodes are shared with `Rc`, versions can't be sent to other
+//! tasks, and snapshot isolation between tasks is out of scope for these
+//! types. Sharing the nodes with `Arc` instead would need versions of them
+//! outside of this crate, as `sync` depends on `collections`.
+
+use std::cmp::max;
+use std::default::Default;
+use std::fmt;
+use std::fmt::Show;
+use std::iter;
+use std::rc::Rc;
+
+static BITS: uint = 5;
+static WIDTH: uint = 1 << BITS;
+static MASK: uint = WIDTH - 1;
+
+enum VecNode<T> {
+    Branch(Vec<Rc<VecNode<T>>>),
+    Leaf(Vec<T>),
+}
+
+impl<T> VecNode<T> {
+    fn children<'a>(&'a self) -> &'a [Rc<VecNode<T>>] {
+        match *self {
+            Branch(ref children) => children.as_slice(),
+            Leaf(_) => fail!("PersistentVec: expected a branch node"),
+        }
+    }
+
+    fn values<'a>(&'a self) -> &'a [T] {
+        match *self {
+            Leaf(ref values) => values.as_slice(),
+            Branch(_) => fail!("PersistentVec: expected a leaf node"),
+        }
+    }
+}
+
+/// A persistent vector, supporting O(log n) indexing, update, push and pop
+/// at the end, with O(1) clones.
+pub struct PersistentVec<T> {
+    length: uint,
+    // The number of bits to shift an index by to find its slot in the root.
+    shift: uint,
+    root: Rc<VecNode<T>>,
+    // The last 1 to 32 elements live outside the trie, so that most pushes
+    // and pops only copy this one node.
+    tail: Rc<VecNode<T>>,
+}
+
+impl<T> PersistentVec<T> {
+    /// Create an empty PersistentVec.
+    pub fn new() -> PersistentVec<T> {
+        PersistentVec {
+            length: 0,
+            shift: BITS,
+            root: Rc::new(Branch(Vec::new())),
+            tail: Rc::new(Leaf(Vec::new())),
+        }
+    }
+
+    /// Return a reference to the element at the given index. Fails if the
+    /// index is out of bounds.
+    pub fn get<'a>(&'a self, index: uint) -> &'a T {
+        if index >= self.length {
+            fail!("PersistentVec::get: index {} out of bounds", index);
+        }
+        &self.leaf_for(index)[index & MASK]
+    }
+
+    /// Return a reference to the last element, or `None` if the vector is
+    /// empty.
+    pub fn last<'a>(&'a self) -> Option<&'a T> {
+        if self.length == 0 {
+            None
+        } else {
+            Some(self.get(self.length - 1))
+        }
+    }
+
+    /// Return an iterator over the elements in order.
+    pub fn iter<'a>(&'a self) -> Items<'a, T> {
+        Items { vec: self, index: 0, end: self.length, leaf: &[] }
+    }
+
+    // The index of the first element held in the tail.
+    fn tail_offset(&self) -> uint {
+        if self.length < WIDTH {
+            0
+        } else {
+            ((self.length - 1) >> BITS) << BITS
+        }
+    }
+
+    // The elements of the leaf holding `index`.
+    fn leaf_for<'a>(&'a self, index: uint) -> &'a [T] {
+        if index >= self.tail_offset() {
+            return self.tail.values();
+        }
+
+        let mut node: &'a VecNode<T> = &*self.root;
+        let mut level = self.shift;
+        while level > 0 {
+            node = &*node.children()[(index >> level) & MASK];
+            level -= BITS;
+        }
+        node.values()
+    }
+
+    // Like `leaf_for`, but returns the shared node itself. `index` must lie
+    // in the trie rather than the tail.
+    fn leaf_node_for(&self, index: uint) -> Rc<VecNode<T>> {
+        let mut node = self.root.clone();
+        let mut level = self.shift;
+        while level > 0 {
+            let next = node.children()[(index >> level) & MASK].clone();
+            node = next;
+            level -= BITS;
+        }
+        node
+    }
+
+    // Copy the path to the rightmost leaf of `parent`, hanging the full
+    // `tail` off the end of it.
+    fn push_tail(&self, level: uint, parent: &VecNode<T>,
+                 tail: Rc<VecNode<T>>) -> VecNode<T> {
+        let subidx = ((self.length - 1) >> level) & MASK;
+        let mut children = Vec::from_slice(parent.children());
+
+        let child = if level == BITS {
+            tail
+        } else if subidx < children.len() {
+            Rc::new(self.push_tail(level - BITS, &**children.get(subidx), tail))
+        } else {
+            new_path(level - BITS, tail)
+        };
+
+        if subidx < children.len() {
+            *children.get_mut(subidx) = child;
+        } else {
+            children.push(child);
+        }
+        Branch(children)
+    }
+
+    // Copy the path to the rightmost leaf of `node` without that leaf,
+    // returning `None` if nothing is left.
+    fn pop_tail(&self, level: uint, node: &VecNode<T>) -> Option<Rc<VecNode<T>>> {
+        let subidx = ((self.length - 2) >> level) & MASK;
+        if level > BITS {
+            let child = self.pop_tail(level - BITS, &*node.children()[subidx]);
+            if child.is_none() && subidx == 0 {
+                return None;
+            }
+            let mut children = Vec::from_slice(node.children().slice_to(subidx));
+            match child {
+                Some(child) => children.push(child),
+                None => {}
+            }
+            Some(Rc::new(Branch(children)))
+        } else if subidx == 0 {
+            None
+        } else {
+            Some(Rc::new(Branch(Vec::from_slice(node.children().slice_to(subidx)))))
+        }
+    }
+}
+
+// Build a chain of single-child branches `level` bits high above `node`.
+fn new_path<T>(level: uint, node: Rc<VecNode<T>>) -> Rc<VecNode<T>> {
+    if level == 0 {
+        node
+    } else {
+        Rc::new(Branch(vec!(new_path(level - BITS, node))))
+    }
+}
+
+// Copy the path to `index`, replacing the element there with `value`.
+fn assoc<T: Clone>(level: uint, node: &VecNode<T>, index: uint, value: T) -> VecNode<T> {
+    if level == 0 {
+        let mut values = Vec::from_slice(node.values());
+        *values.get_mut(index & MASK) = value;
+        Leaf(values)
+    } else {
+        let subidx = (index >> level) & MASK;
+        let mut children = Vec::from_slice(node.children());
+        let child = Rc::new(assoc(level - BITS, &**children.get(subidx), index, value));
+        *children.get_mut(subidx) = child;
+        Branch(children)
+    }
+}
+
+impl<T: Clone> PersistentVec<T> {
+    /// Return a new vector with `value` appended.
+    pub fn push(&self, value: T) -> PersistentVec<T> {
+        let tail_len = self.length - self.tail_offset();
+        if tail_len < WIDTH {
+            let mut tail = Vec::with_capacity(tail_len + 1);
+            tail.push_all(self.tail.values());
+            tail.push(value);
+            return PersistentVec {
+                length: self.length + 1,
+                shift: self.shift,
+                root: self.root.clone(),
+                tail: Rc::new(Leaf(tail)),
+            };
+        }
+
+        // The tail is full: move it into the trie, growing a new root level
+        // if the current one has no room left.
+        let full_tail = self.tail.clone();
+        let (root, shift) = if (self.length >> BITS) > (1 << self.shift) {
+            let root = Branch(vec!(self.root.clone(), new_path(self.shift, full_tail)));
+            (Rc::new(root), self.shift + BITS)
+        } else {
+            (Rc::new(self.push_tail(self.shift, &*self.root, full_tail)), self.shift)
+        };
+
+        PersistentVec {
+            length: self.length + 1,
+            shift: shift,
+            root: root,
+            tail: Rc::new(Leaf(vec!(value))),
+        }
+    }
+
+    /// Return a new vector without its last element, or `None` if the
+    /// vector is empty.
+    pub fn pop(&self) -> Option<PersistentVec<T>> {
+        if self.length == 0 {
+            return None;
+        } else if self.length == 1 {
+            return Some(PersistentVec::new());
+        }
+
+        let tail_len = self.length - self.tail_offset();
+        if tail_len > 1 {
+            let values = self.tail.values();
+            let tail = Vec::from_slice(values.slice_to(values.len() - 1));
+            return Some(PersistentVec {
+                length: self.length - 1,
+                shift: self.shift,
+                root: self.root.clone(),
+                tail: Rc::new(Leaf(tail)),
+            });
+        }
+
+        // The tail is about to be empty, so the trie's last leaf becomes
+        // the new tail.
+        let tail = self.leaf_node_for(self.length - 2);
+        let mut root = match self.pop_tail(self.shift, &*self.root) {
+            Some(root) => root,
+            None => Rc::new(Branch(Vec::new())),
+        };
+        let mut shift = self.shift;
+
+        // Drop a root level that only has a single child left.
+        if shift > BITS && root.children().len() == 1 {
+            let child = root.children()[0].clone();
+            root = child;
+            shift -= BITS;
+        }
+
+        Some(PersistentVec {
+            length: self.length - 1,
+            shift: shift,
+            root: root,
+            tail: tail,
+        })
+    }
+
+    /// Return a new vector with the element at `index` replaced by `value`.
+    /// Fails if the index is out of bounds.
+    pub fn set(&self, index: uint, value: T) -> PersistentVec<T> {
+        if index >= self.length {
+            fail!("PersistentVec::set: index {} out of bounds", index);
+        }
+
+        if index >= self.tail_offset() {
+            let mut tail = Vec::from_slice(self.tail.values());
+            *tail.get_mut(index & MASK) = value;
+            PersistentVec {
+                length: self.length,
+                shift: self.shift,
+                root: self.root.clone(),
+                tail: Rc::new(Leaf(tail)),
+            }
+        } else {
+            PersistentVec {
+                length: self.length,
+                shift: self.shift,
+                root: Rc::new(assoc(self.shift, &*self.root, index, value)),
+                tail: self.tail.clone(),
+            }
+        }
+    }
+}
+
+impl<T> Container for PersistentVec<T> {
+    /// Return the number of elements in the vector
+    fn len(&self) -> uint { self.length }
+}
+
+impl<T> Mutable for PersistentVec<T> {
+    /// Make this version of the vector empty. Other versions are unaffected.
+    fn clear(&mut self) {
+        *self = PersistentVec::new();
+    }
+}
+
+impl<T> Clone for PersistentVec<T> {
+    /// Return a new version sharing all of this one's structure.
+    fn clone(&self) -> PersistentVec<T> {
+        PersistentVec {
+            length: self.length,
+            shift: self.shift,
+            root: self.root.clone(),
+            tail: self.tail.clone(),
+        }
+    }
+}
+
+impl<T: Eq> Eq for PersistentVec<T> {
+    fn eq(&self, other: &PersistentVec<T>) -> bool {
+        self.len() == other.len() &&
+            self.iter().zip(other.iter()).all(|(a, b)| *a == *b)
+    }
+}
+
+impl<T: Show> Show for PersistentVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f.buf, "["));
+
+        for (i, x) in self.iter().enumerate() {
+            if i != 0 { try!(write!(f.buf, ", ")); }
+            try!(write!(f.buf, "{}", *x));
+        }
+
+        write!(f.buf, "]")
+    }
+}
+
+impl<T> Default for PersistentVec<T> {
+    fn default() -> PersistentVec<T> { PersistentVec::new() }
+}
+
+impl<T: Clone> FromIterator<T> for PersistentVec<T> {
+    fn from_iter<I: Iterator<T>>(mut iter: I) -> PersistentVec<T> {
+        let mut vec = PersistentVec::new();
+        for x in iter {
+            vec = vec.push(x);
+        }
+        vec
+    }
+}
+
+/// PersistentVec iterator
+pub struct Items<'a, T> {
+    vec: &'a PersistentVec<T>,
+    index: uint,
+    end: uint,
+    // The leaf holding `index`, so we only walk the trie once per leaf.
+    leaf: &'a [T],
+}
+
+impl<'a, T> Iterator<&'a T> for Items<'a, T> {
+    fn next(&mut self) -> Option<&'a T> {
+        if self.index >= self.end {
+            return None;
+        }
+        if self.index & MASK == 0 || self.leaf.is_empty() {
+            self.leaf = self.vec.leaf_for(self.index);
+        }
+        let elt = &self.leaf[self.index & MASK];
+        self.index += 1;
+        Some(elt)
+    }
+
+    fn size_hint(&self) -> (uint, Option<uint>) {
+        let len = self.end - self.index;
+        (len, Some(len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator<&'a T> for Items<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.index >= self.end {
+            return None;
+        }
+        self.end -= 1;
+        Some(self.vec.get(self.end))
+    }
+}
+
+impl<'a, T> ExactSize<&'a T> for Items<'a, T> {}
+
+struct MapNode<K, V> {
+    key: K,
+    value: V,
+    left: Link<K, V>,
+    right: Link<K, V>,
+    height: uint,
+}
+
+type Link<K, V> = Option<Rc<MapNode<K, V>>>;
+
+fn height<K, V>(link: &Link<K, V>) -> uint {
+    match *link {
+        Some(ref node) => node.height,
+        None => 0,
+    }
+}
+
+fn make_node<K, V>(key: K, value: V, left: Link<K, V>, right: Link<K, V>) -> Rc<MapNode<K, V>> {
+    let height = 1 + max(height(&left), height(&right));
+    Rc::new(MapNode { key: key, value: value, left: left, right: right, height: height })
+}
+
+// Build a node from its parts, rotating once or twice if the two sides'
+// heights differ by two (they never differ by more after a single insert or
+// remove).
+fn balance<K: Clone, V: Clone>(key: K, value: V, left: Link<K, V>,
+                               right: Link<K, V>) -> Rc<MapNode<K, V>> {
+    let (hl, hr) = (height(&left), height(&right));
+    if hl > hr + 1 {
+        let l = left.unwrap();
+        if height(&l.left) >= height(&l.right) {
+            let new_right = make_node(key, value, l.right.clone(), right);
+            make_node(l.key.clone(), l.value.clone(), l.left.clone(), Some(new_right))
+        } else {
+            let lr = l.right.get_ref();
+            let new_left = make_node(l.key.clone(), l.value.clone(),
+                                     l.left.clone(), lr.left.clone());
+            let new_right = make_node(key, value, lr.right.clone(), right);
+            make_node(lr.key.clone(), lr.value.clone(), Some(new_left), Some(new_right))
+        }
+    } else if hr > hl + 1 {
+        let r = right.unwrap();
+        if height(&r.right) >= height(&r.left) {
+            let new_left = make_node(key, value, left, r.left.clone());
+            make_node(r.key.clone(), r.value.clone(), Some(new_left), r.right.clone())
+        } else {
+            let rl = r.left.get_ref();
+            let new_left = make_node(key, value, left, rl.left.clone());
+            let new_right = make_node(r.key.clone(), r.value.clone(),
+                                      rl.right.clone(), r.right.clone());
+            make_node(rl.key.clone(), rl.value.clone(), Some(new_left), Some(new_right))
+        }
+    } else {
+        make_node(key, value, left, right)
+    }
+}
+
+// Returns the new subtree and whether the key was newly added.
+fn insert<K: TotalOrd + Clone, V: Clone>(link: &Link<K, V>, key: K,
+                                         value: V) -> (Rc<MapNode<K, V>>, bool) {
+    let node = match *link {
+        None => return (make_node(key, value, None, None), true),
+        Some(ref node) => node,
+    };
+
+    match key.cmp(&node.key) {
+        Less => {
+            let (left, added) = insert(&node.left, key, value);
+            (balance(node.key.clone(), node.value.clone(), Some(left), node.right.clone()), added)
+        }
+        Greater => {
+            let (right, added) = insert(&node.right, key, value);
+            (balance(node.key.clone(), node.value.clone(), node.left.clone(), Some(right)), added)
+        }
+        Equal => (make_node(key, value, node.left.clone(), node.right.clone()), false),
+    }
+}
+
+// Returns the new subtree, or `None` if the key wasn't present (in which case
+// the old subtree can be shared as is).
+fn remove<K: TotalOrd + Clone, V: Clone>(link: &Link<K, V>, key: &K) -> Option<Link<K, V>> {
+    let node = match *link {
+        None => return None,
+        Some(ref node) => node,
+    };
+
+    match key.cmp(&node.key) {
+        Less => remove(&node.left, key).map(|left| {
+            Some(balance(node.key.clone(), node.value.clone(), left, node.right.clone()))
+        }),
+        Greater => remove(&node.right, key).map(|right| {
+            Some(balance(node.key.clone(), node.value.clone(), node.left.clone(), right))
+        }),
+        Equal => Some(match (&node.left, &node.right) {
+            (&None, right) => right.clone(),
+            (left, &None) => left.clone(),
+            (left, &Some(ref right)) => {
+                let (key, value, right) = remove_min(right);
+                Some(balance(key, value, left.clone(), right))
+            }
+        }),
+    }
+}
+
+// Detach the smallest entry of a subtree, returning it and what's left.
+fn remove_min<K: Clone, V: Clone>(node: &Rc<MapNode<K, V>>) -> (K, V, Link<K, V>) {
+    match node.left {
+        None => (node.key.clone(), node.value.clone(), node.right.clone()),
+        Some(ref left) => {
+            let (key, value, left) = remove_min(left);
+            (key, value, Some(balance(node.key.clone(), node.value.clone(),
+                                      left, node.right.clone())))
+        }
+    }
+}
+
+/// A persistent ordered map, supporting O(log n) lookup, insertion and
+/// removal, with O(1) clones.
+pub struct PersistentMap<K, V> {
+    root: Link<K, V>,
+    length: uint,
+}
+
+impl<K: TotalOrd, V> PersistentMap<K, V> {
+    /// Create an empty PersistentMap.
+    pub fn new() -> PersistentMap<K, V> {
+        PersistentMap { root: None, length: 0 }
+    }
+
+    /// Return an iterator over the key-value pairs in ascending key order.
+    pub fn iter<'a>(&'a self) -> Entries<'a, K, V> {
+        Entries {
+            stack: Vec::new(),
+            node: self.root.as_ref().map(|node| &**node),
+            remaining: self.length,
+        }
+    }
+
+    /// Return an iterator over the keys in ascending order.
+    pub fn keys<'a>(&'a self) -> Keys<'a, K, V> {
+        self.iter().map(|(k, _v)| k)
+    }
+
+    /// Return an iterator over the values in ascending order of their keys.
+    pub fn values<'a>(&'a self) -> Values<'a, K, V> {
+        self.iter().map(|(_k, v)| v)
+    }
+}
+
+impl<K: TotalOrd + Clone, V: Clone> PersistentMap<K, V> {
+    /// Return a new map with `key` set to `value`, replacing any existing
+    /// value for it.
+    pub fn insert(&self, key: K, value: V) -> PersistentMap<K, V> {
+        let (root, added) = insert(&self.root, key, value);
+        PersistentMap {
+            root: Some(root),
+            length: if added { self.length + 1 } else { self.length },
+        }
+    }
+
+    /// Return a new map without `key`. If the key isn't present, the result
+    /// shares the whole of this map.
+    pub fn remove(&self, key: &K) -> PersistentMap<K, V> {
+        match remove(&self.root, key) {
+            Some(root) => PersistentMap { root: root, length: self.length - 1 },
+            None => self.clone(),
+        }
+    }
+}
+
+impl<K: TotalOrd, V> Container for PersistentMap<K, V> {
+    /// Return the number of elements in the map
+    fn len(&self) -> uint { self.length }
+}
+
+impl<K: TotalOrd, V> Mutable for PersistentMap<K, V> {
+    /// Make this version of the map empty. Other versions are unaffected.
+    fn clear(&mut self) {
+        self.root = None;
+        self.length = 0;
+    }
+}
+
+impl<K: TotalOrd, V> Map<K, V> for PersistentMap<K, V> {
+    fn find<'a>(&'a self, key: &K) -> Option<&'a V> {
+        let mut current = &self.root;
+        loop {
+            match *current {
+                Some(ref node) => {
+                    match key.cmp(&node.key) {
+                        Less => current = &node.left,
+                        Greater => current = &node.right,
+                        Equal => return Some(&node.value),
+                    }
+                }
+                None => return None,
+            }
+        }
+    }
+}
+
+impl<K, V> Clone for PersistentMap<K, V> {
+    /// Return a new version sharing all of this one's structure.
+    fn clone(&self) -> PersistentMap<K, V> {
+        PersistentMap { root: self.root.clone(), length: self.length }
+    }
+}
+
+impl<K: TotalOrd, V: Eq> Eq for PersistentMap<K, V> {
+    fn eq(&self, other: &PersistentMap<K, V>) -> bool {
+        self.len() == other.len() &&
+            self.iter().zip(other.iter()).all(|((k1, v1), (k2, v2))| {
+                k1.cmp(k2) == Equal && *v1 == *v2
+            })
+    }
+}
+
+impl<K: TotalOrd + Show, V: Show> Show for PersistentMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f.buf, r"\{"));
+
+        for (i, (k, v)) in self.iter().enumerate() {
+            if i != 0 { try!(write!(f.buf, ", ")); }
+            try!(write!(f.buf, "{}: {}", *k, *v));
+        }
+
+        write!(f.buf, r"\}")
+    }
+}
+
+impl<K: TotalOrd, V> Default for PersistentMap<K, V> {
+    fn default() -> PersistentMap<K, V> { PersistentMap::new() }
+}
+
+impl<K: TotalOrd + Clone, V: Clone> FromIterator<(K, V)> for PersistentMap<K, V> {
+    fn from_iter<T: Iterator<(K, V)>>(mut iter: T) -> PersistentMap<K, V> {
+        let mut map = PersistentMap::new();
+        for (k, v) in iter {
+            map = map.insert(k, v);
+        }
+        map
+    }
+}
+
+/// PersistentMap iterator
+pub struct Entries<'a, K, V> {
+    stack: Vec<&'a MapNode<K, V>>,
+    node: Option<&'a MapNode<K, V>>,
+    remaining: uint,
+}
+
+impl<'a, K, V> Iterator<(&'a K, &'a V)> for Entries<'a, K, V> {
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        loop {
+            match self.node {
+                Some(node) => {
+                    self.stack.push(node);
+                    self.node = node.left.as_ref().map(|left| &**left);
+                }
+                None => match self.stack.pop() {
+                    Some(node) => {
+                        self.node = node.right.as_ref().map(|right| &**right);
+                        self.remaining -= 1;
+                        return Some((&node.key, &node.value));
+                    }
+                    None => return None,
+                }
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (uint, Option<uint>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+/// PersistentMap keys iterator
+pub type Keys<'a, K, V> =
+    iter::Map<'static, (&'a K, &'a V), &'a K, Entries<'a, K, V>>;
+
+/// PersistentMap values iterator
+pub type Values<'a, K, V> =
+    iter::Map<'static, (&'a K, &'a V), &'a V, Entries<'a, K, V>>;
+
+/// A persistent ordered set, implemented as a `PersistentMap` with unit
+/// values.
+pub struct PersistentSet<T> {
+    map: PersistentMap<T, ()>,
+}
+
+impl<T: TotalOrd> PersistentSet<T> {
+    /// Create an empty PersistentSet.
+    pub fn new() -> PersistentSet<T> {
+        PersistentSet { map: PersistentMap::new() }
+    }
+
+    /// Return an iterator over the values in ascending order.
+    pub fn iter<'a>(&'a self) -> SetItems<'a, T> {
+        self.map.keys()
+    }
+}
+
+impl<T: TotalOrd + Clone> PersistentSet<T> {
+    /// Return a new set which also contains `value`.
+    pub fn insert(&self, value: T) -> PersistentSet<T> {
+        PersistentSet { map: self.map.insert(value, ()) }
+    }
+
+    /// Return a new set without `value`.
+    pub fn remove(&self, value: &T) -> PersistentSet<T> {
+        PersistentSet { map: self.map.remove(value) }
+    }
+}
+
+impl<T: TotalOrd> Container for PersistentSet<T> {
+    /// Return the number of elements in the set
+    fn len(&self) -> uint { self.map.len() }
+}
+
+impl<T: TotalOrd> Mutable for PersistentSet<T> {
+    /// Make this version of the set empty. Other versions are unaffected.
+    fn clear(&mut self) { self.map.clear() }
+}
+
+impl<T: TotalOrd> Set<T> for PersistentSet<T> {
+    fn contains(&self, value: &T) -> bool {
+        self.map.contains_key(value)
+    }
+
+    fn is_disjoint(&self, other: &PersistentSet<T>) -> bool {
+        self.iter().all(|v| !other.contains(v))
+    }
+
+    fn is_subset(&self, other: &PersistentSet<T>) -> bool {
+        self.len() <= other.len() && self.iter().all(|v| other.contains(v))
+    }
+}
+
+impl<T> Clone for PersistentSet<T> {
+    /// Return a new version sharing all of this one's structure.
+    fn clone(&self) -> PersistentSet<T> {
+        PersistentSet { map: self.map.clone() }
+    }
+}
+
+impl<T: TotalOrd> Eq for PersistentSet<T> {
+    fn eq(&self, other: &PersistentSet<T>) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<T: TotalOrd + Show> Show for PersistentSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f.buf, r"\{"));
+
+        for (i, x) in self.iter().enumerate() {
+            if i != 0 { try!(write!(f.buf, ", ")); }
+            try!(write!(f.buf, "{}", *x));
+        }
+
+        write!(f.buf, r"\}")
+    }
+}
+
+impl<T: TotalOrd> Default for PersistentSet<T> {
+    fn default() -> PersistentSet<T> { PersistentSet::new() }
+}
+
+impl<T: TotalOrd + Clone> FromIterator<T> for PersistentSet<T> {
+    fn from_iter<I: Iterator<T>>(mut iter: I) -> PersistentSet<T> {
+        let mut set = PersistentSet::new();
+        for x in iter {
+            set = set.insert(x);
+        }
+        set
+    }
+}
+
+/// PersistentSet iterator
+pub type SetItems<'a, T> = Keys<'a, T, ()>;
+
+#[cfg(test)]
+mod test_vec {
+    use super::PersistentVec;
+
+    #[test]
+    fn test_push_get() {
+        let mut v = PersistentVec::new();
+        for i in range(0u, 5000) {
+            v = v.push(i);
+            assert_eq!(v.len(), i + 1);
+            assert_eq!(*v.get(i), i);
+        }
+        for i in range(0u, 5000) {
+            assert_eq!(*v.get(i), i);
+        }
+        assert_eq!(v.last(), Some(&4999));
+    }
+
+    #[test]
+    fn test_deep() {
+        // Enough elements for a trie three levels deep.
+        let v: PersistentVec<uint> = range(0u, 40000).collect();
+        assert_eq!(v.len(), 40000);
+        assert!(v.iter().enumerate().all(|(i, &x)| i == x));
+
+        let mut w = v.clone();
+        for i in range(0u, 40000).rev() {
+            assert_eq!(w.last(), Some(&i));
+            w = w.pop().unwrap();
+        }
+        assert!(w.is_empty());
+        assert!(w.pop().is_none());
+        assert_eq!(v.len(), 40000);
+    }
+
+    #[test]
+    fn test_persistence() {
+        let v1: PersistentVec<int> = range(0, 100).collect();
+        let v2 = v1.set(10, -10).set(99, -99);
+        let v3 = v2.push(100);
+        let v4 = v1.pop().unwrap();
+
+        assert_eq!(*v1.get(10), 10);
+        assert_eq!(*v1.get(99), 99);
+        assert_eq!(*v2.get(10), -10);
+        assert_eq!(*v2.get(99), -99);
+        assert_eq!(v2.len(), 100);
+        assert_eq!(v3.len(), 101);
+        assert_eq!(*v3.get(100), 100);
+        assert_eq!(v4.len(), 99);
+        assert_eq!(v1.len(), 100);
+        assert!(v1 != v2);
+        assert!(v1 == v1.clone());
+    }
+
+    #[test]
+    fn test_push_pop_boundaries() {
+        // Cross the tail/trie and trie-level boundaries in both directions.
+        let mut versions = vec!(PersistentVec::new());
+        for i in range(0u, 1100) {
+            let next = versions.last().unwrap().push(i);
+            versions.push(next);
+        }
+        for (n, v) in versions.iter().enumerate() {
+            assert_eq!(v.len(), n);
+            assert!(v.iter().enumerate().all(|(i, &x)| i == x));
+            match v.pop() {
+                Some(p) => assert!(p == *versions.get(n - 1)),
+                None => assert_eq!(n, 0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_iter() {
+        let v: PersistentVec<uint> = range(0u, 100).collect();
+        assert_eq!(v.iter().size_hint(), (100, Some(100)));
+        let back: Vec<uint> = v.iter().rev().map(|&x| x).collect();
+        assert_eq!(back, range(0u, 100).rev().collect());
+        assert_eq!(PersistentVec::<uint>::new().iter().next(), None);
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_get_out_of_bounds() {
+        let v = PersistentVec::new().push(1);
+        v.get(1);
+    }
+
+    #[test]
+    fn test_show() {
+        let v: PersistentVec<int> = range(1, 4).collect();
+        assert_eq!(format!("{}", v), "[1, 2, 3]".to_owned());
+    }
+}
+
+#[cfg(test)]
+mod test_map {
+    use super::{PersistentMap, Link, height};
+    use std::cmp::max;
+    use rand::Rng;
+    use rand;
+
+    fn check<K: TotalOrd, V>(link: &Link<K, V>) -> uint {
+        match *link {
+            None => 0,
+            Some(ref node) => {
+                let hl = check(&node.left);
+                let hr = check(&node.right);
+                assert!(hl <= hr + 1 && hr <= hl + 1);
+                assert_eq!(node.height, 1 + max(hl, hr));
+                assert_eq!(node.height, height(link));
+                hl + hr + 1
+            }
+        }
+    }
+
+    fn check_map<K: TotalOrd, V>(map: &PersistentMap<K, V>) {
+        assert_eq!(check(&map.root), map.len());
+        let keys: Vec<&K> = map.keys().collect();
+        for w in keys.as_slice().windows(2) {
+            assert!(*w[0] < *w[1]);
+        }
+    }
+
+    #[test]
+    fn test_insert_find() {
+        let mut m = PersistentMap::new();
+        for i in range(0, 1000) {
+            m = m.insert(i, i * 2);
+        }
+        check_map(&m);
+        assert_eq!(m.len(), 1000);
+        for i in range(0, 1000) {
+            assert_eq!(m.find(&i), Some(&(i * 2)));
+        }
+        assert_eq!(m.find(&1000), None);
+
+        let m2 = m.insert(5, -5);
+        assert_eq!(m2.len(), 1000);
+        assert_eq!(m2.find(&5), Some(&-5));
+        assert_eq!(m.find(&5), Some(&10));
+    }
+
+    #[test]
+    fn test_remove() {
+        let m: PersistentMap<int, int> = range(0, 500).map(|i| (i, i)).collect();
+        let mut n = m.clone();
+        for i in range(0, 500) {
+            if i % 3 != 0 {
+                n = n.remove(&i);
+                check_map(&n);
+            }
+        }
+        assert_eq!(n.len(), 167);
+        assert!(n.iter().all(|(k, _)| k % 3 == 0));
+        assert_eq!(m.len(), 500);
+        check_map(&m);
+
+        let same = n.remove(&1);
+        assert!(same == n);
+    }
+
+    #[test]
+    fn test_random() {
+        let mut rng = rand::weak_rng();
+        let mut m = PersistentMap::new();
+        let mut versions = Vec::new();
+        for _ in range(0, 2000) {
+            let k = rng.gen_range(0u, 200);
+            m = if rng.gen() { m.insert(k, k) } else { m.remove(&k) };
+            versions.push(m.clone());
+        }
+        for v in versions.iter() {
+            check_map(v);
+        }
+    }
+
+    #[test]
+    fn test_iter() {
+        let m: PersistentMap<int, ~str> =
+            range(0, 10).rev().map(|i| (i, i.to_str())).collect();
+        let mut expected = 0;
+        for (k, v) in m.iter() {
+            assert_eq!(*k, expected);
+            assert_eq!(*v, expected.to_str());
+            expected += 1;
+        }
+        assert_eq!(expected, 10);
+        assert_eq!(m.iter().size_hint(), (10, Some(10)));
+        assert_eq!(m.values().len(), 10);
+    }
+
+    #[test]
+    fn test_show() {
+        let m = PersistentMap::new().insert(2, 'b').insert(1, 'a');
+        assert_eq!(format!("{}", m), "{1: a, 2: b}".to_owned());
+    }
+}
+
+#[cfg(test)]
+mod test_set {
+    use super::PersistentSet;
+
+    #[test]
+    fn test_set() {
+        let a: PersistentSet<int> = range(0, 10).collect();
+        let b = a.remove(&3).insert(20);
+
+        assert!(a.contains(&3) && !a.contains(&20));
+        assert!(!b.contains(&3) && b.contains(&20));
+        assert_eq!(a.len(), 10);
+        assert_eq!(b.len(), 10);
+
+        let evens: PersistentSet<int> = range(0, 10).filter(|x| x % 2 == 0).collect();
+        assert!(evens.is_subset(&a));
+        assert!(a.is_superset(&evens));
+        assert!(!evens.is_subset(&b));
+
+        let odds: PersistentSet<int> = range(0, 10).filter(|x| x % 2 == 1).collect();
+        assert!(odds.is_disjoint(&evens));
+        assert!(!odds.is_disjoint(&a));
+
+        let v: Vec<int> = b.iter().map(|&x| x).collect();
+        assert_eq!(v, vec!(0, 1, 2, 4, 5, 6, 7, 8, 9, 20));
+    }
+}
+
+#[cfg(test)]
+mod bench {
+    extern crate test;
+    use self::test::Bencher;
+    use super::{PersistentVec, PersistentMap};
+
+    #[bench]
+    fn vec_push(b: &mut Bencher) {
+        let v: PersistentVec<uint> = range(0u, 1000).collect();
+        b.iter(|| {
+            v.push(1000)
+        })
+    }
+
+    #[bench]
+    fn vec_set(b: &mut Bencher) {
+        let v: PersistentVec<uint> = range(0u, 1000).collect();
+        b.iter(|| {
+            v.set(500, 0)
+        })
+    }
+
+    #[bench]
+    fn map_insert(b: &mut Bencher) {
+        let m: PersistentMap<uint, uint> = range(0u, 1000).map(|i| (i * 2, i)).collect();
+        b.iter(|| {
+            m.insert(501, 0)
+        })
+    }
+
+    #[bench]
+    fn map_find(b: &mut Bencher) {
+        let m: PersistentMap<uint, uint> = range(0u, 1000).map(|i| (i, i)).collect();
+        b.iter(|| {
+            m.find(&412)
+        })
+    }
+}
@@ -99,11 +99,11 @@ impl<T:Ord> PriorityQueue<T> {
     }
 
     /// Consume the PriorityQueue and return the underlying vector
-    pub fn to_vec(self) -> Vec<T> { let PriorityQueue{data: v} = self; v }
+    pub fn into_vec(self) -> Vec<T> { let PriorityQueue{data: v} = self; v }
 
     /// Consume the PriorityQueue and return a vector in sorted
     /// (ascending) order
-    pub fn to_sorted_vec(self) -> Vec<T> {
+    pub fn into_sorted_vec(self) -> Vec<T> {
         let mut q = self;
         let mut end = q.len();
         while end > 1 {
@@ -111,7 +111,32 @@ impl<T:Ord> PriorityQueue<T> {
             q.data.as_mut_slice().swap(0, end);
             q.siftdown_range(0, end)
         }
-        q.to_vec()
+        q.into_vec()
+    }
+
+    /// Consume the PriorityQueue and return the underlying vector
+    #[deprecated = "renamed to `into_vec`"]
+    pub fn to_vec(self) -> Vec<T> { self.into_vec() }
+
+    /// Consume the PriorityQueue and return a vector in sorted
+    /// (ascending) order
+    #[deprecated = "renamed to `into_sorted_vec`"]
+    pub fn to_sorted_vec(self) -> Vec<T> { self.into_sorted_vec() }
+
+    /// Move all the items of `other` into this queue
+    pub fn merge(&mut self, other: PriorityQueue<T>) {
+        let other = other.into_vec();
+
+        // Pushing costs O(log n) per item while rebuilding the heap costs
+        // O(n) overall, so only push when `other` is comparatively small.
+        if other.len() * 4 < self.len() {
+            for item in other.move_iter() {
+                self.push(item);
+            }
+        } else {
+            self.data.push_all_move(other);
+            self.rebuild();
+        }
     }
 
     /// Create an empty PriorityQueue
@@ -125,12 +150,17 @@ impl<T:Ord> PriorityQueue<T> {
     /// Create a PriorityQueue from a vector (heapify)
     pub fn from_vec(xs: Vec<T>) -> PriorityQueue<T> {
         let mut q = PriorityQueue{data: xs,};
-        let mut n = q.len() / 2;
+        q.rebuild();
+        q
+    }
+
+    // Restore the heap property over the whole vector.
+    fn rebuild(&mut self) {
+        let mut n = self.len() / 2;
         while n > 0 {
             n -= 1;
-            q.siftdown(n)
+            self.siftdown(n)
         }
-        q
     }
 
     // The implementations of siftup and siftdown use unsafe blocks in
@@ -218,9 +248,158 @@ impl<T: Ord> Extendable<T> for PriorityQueue<T> {
     }
 }
 
+// Orders a value the opposite way to `T`, so that `MinPriorityQueue` can
+// reuse the max-heap.
+#[deriving(Clone, Eq)]
+struct Reversed<T>(T);
+
+impl<T: Ord> Ord for Reversed<T> {
+    #[inline]
+    fn lt(&self, other: &Reversed<T>) -> bool {
+        let (&Reversed(ref a), &Reversed(ref b)) = (self, other);
+        *b < *a
+    }
+}
+
+#[inline]
+fn unwrap_ref<'a, T>(r: &'a Reversed<T>) -> &'a T {
+    let &Reversed(ref x) = r;
+    x
+}
+
+#[inline]
+fn unwrap<T>(r: Reversed<T>) -> T {
+    let Reversed(x) = r;
+    x
+}
+
+/// A priority queue which pops its least item first, implemented with a
+/// binary heap. This saves wrapping items in a type with reversed ordering
+/// in order to use `PriorityQueue` as a min-heap.
+#[deriving(Clone)]
+pub struct MinPriorityQueue<T> {
+    heap: PriorityQueue<Reversed<T>>,
+}
+
+impl<T: Ord> Container for MinPriorityQueue<T> {
+    /// Returns the length of the queue
+    fn len(&self) -> uint { self.heap.len() }
+}
+
+impl<T: Ord> Mutable for MinPriorityQueue<T> {
+    /// Drop all items from the queue
+    fn clear(&mut self) { self.heap.clear() }
+}
+
+impl<T: Ord> MinPriorityQueue<T> {
+    /// Create an empty MinPriorityQueue
+    pub fn new() -> MinPriorityQueue<T> {
+        MinPriorityQueue { heap: PriorityQueue::new() }
+    }
+
+    /// Create an empty MinPriorityQueue with capacity `capacity`
+    pub fn with_capacity(capacity: uint) -> MinPriorityQueue<T> {
+        MinPriorityQueue { heap: PriorityQueue::with_capacity(capacity) }
+    }
+
+    /// Create a MinPriorityQueue from a vector (heapify)
+    pub fn from_vec(xs: Vec<T>) -> MinPriorityQueue<T> {
+        MinPriorityQueue {
+            heap: PriorityQueue::from_vec(xs.move_iter().map(Reversed).collect())
+        }
+    }
+
+    /// An iterator visiting all values in underlying vector, in
+    /// arbitrary order.
+    pub fn iter<'a>(&'a self) -> MinItems<'a, T> {
+        MinItems { iter: self.heap.data.iter() }
+    }
+
+    /// Returns the least item in the queue - fails if empty
+    pub fn top<'a>(&'a self) -> &'a T { unwrap_ref(self.heap.top()) }
+
+    /// Returns the least item in the queue - None if empty
+    pub fn maybe_top<'a>(&'a self) -> Option<&'a T> {
+        self.heap.maybe_top().map(unwrap_ref)
+    }
+
+    /// Returns the number of elements the queue can hold without reallocating
+    pub fn capacity(&self) -> uint { self.heap.capacity() }
+
+    /// Reserve capacity for at least n elements in the MinPriorityQueue.
+    /// Do nothing if the capacity is already sufficient.
+    pub fn reserve(&mut self, n: uint) { self.heap.reserve(n) }
+
+    /// Pop the least item from the queue - fails if empty
+    pub fn pop(&mut self) -> T { unwrap(self.heap.pop()) }
+
+    /// Pop the least item from the queue - None if empty
+    pub fn maybe_pop(&mut self) -> Option<T> {
+        self.heap.maybe_pop().map(unwrap)
+    }
+
+    /// Push an item onto the queue
+    pub fn push(&mut self, item: T) { self.heap.push(Reversed(item)) }
+
+    /// Optimized version of a push followed by a pop
+    pub fn push_pop(&mut self, item: T) -> T {
+        unwrap(self.heap.push_pop(Reversed(item)))
+    }
+
+    /// Optimized version of a pop followed by a push - fails if empty
+    pub fn replace(&mut self, item: T) -> T {
+        unwrap(self.heap.replace(Reversed(item)))
+    }
+
+    /// Move all the items of `other` into this queue
+    pub fn merge(&mut self, other: MinPriorityQueue<T>) {
+        self.heap.merge(other.heap)
+    }
+
+    /// Consume the MinPriorityQueue and return the underlying vector
+    pub fn into_vec(self) -> Vec<T> {
+        self.heap.into_vec().move_iter().map(unwrap).collect()
+    }
+
+    /// Consume the MinPriorityQueue and return a vector in sorted
+    /// (ascending) order
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        let mut v: Vec<T> = self.heap.into_sorted_vec().move_iter().map(unwrap).collect();
+        v.reverse();
+        v
+    }
+}
+
+/// MinPriorityQueue iterator
+pub struct MinItems<'a, T> {
+    iter: slice::Items<'a, Reversed<T>>,
+}
+
+impl<'a, T> Iterator<&'a T> for MinItems<'a, T> {
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> { self.iter.next().map(unwrap_ref) }
+
+    #[inline]
+    fn size_hint(&self) -> (uint, Option<uint>) { self.iter.size_hint() }
+}
+
+impl<T: Ord> FromIterator<T> for MinPriorityQueue<T> {
+    fn from_iter<Iter: Iterator<T>>(iter: Iter) -> MinPriorityQueue<T> {
+        let mut q = MinPriorityQueue::new();
+        q.extend(iter);
+        q
+    }
+}
+
+impl<T: Ord> Extendable<T> for MinPriorityQueue<T> {
+    fn extend<Iter: Iterator<T>>(&mut self, iter: Iter) {
+        self.heap.extend(iter.map(Reversed))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use priority_queue::PriorityQueue;
+    use priority_queue::{PriorityQueue, MinPriorityQueue};
 
     #[test]
     fn test_iterator() {
@@ -318,6 +497,7 @@ mod tests {
         assert_eq!(heap.len(), 5);
     }
 
+    #[allow(deprecated)]
     fn check_to_vec(mut data: Vec<int>) {
         let heap = PriorityQueue::from_vec(data.clone());
         let mut v = heap.clone().to_vec();
@@ -345,6 +525,26 @@ mod tests {
         check_to_vec(vec!(5, 4, 3, 2, 1, 5, 4, 3, 2, 1, 5, 4, 3, 2, 1));
     }
 
+    fn check_into_vec(mut data: Vec<int>) {
+        let heap = PriorityQueue::from_vec(data.clone());
+        let mut v = heap.clone().into_vec();
+        v.sort();
+        data.sort();
+
+        assert_eq!(v, data);
+        assert_eq!(heap.into_sorted_vec(), data);
+    }
+
+    #[test]
+    fn test_into_vec() {
+        check_into_vec(vec!());
+        check_into_vec(vec!(5));
+        check_into_vec(vec!(3, 2));
+        check_into_vec(vec!(5, 1, 2));
+        check_into_vec(vec!(2, 4, 6, 2, 1, 8, 10, 3, 5, 7, 0, 9, 1));
+        check_into_vec(vec!(10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0));
+    }
+
     #[test]
     #[should_fail]
     fn test_empty_pop() {
@@ -388,4 +588,57 @@ mod tests {
             assert_eq!(q.pop(), x);
         }
     }
+
+    #[test]
+    fn test_merge() {
+        let mut a = PriorityQueue::from_vec(vec!(1, 5, 3));
+        a.merge(PriorityQueue::from_vec(vec!(4, 2, 6, 0)));
+        assert_eq!(a.len(), 7);
+        assert_eq!(a.clone().into_sorted_vec(), vec!(0, 1, 2, 3, 4, 5, 6));
+
+        // Small enough to be pushed one at a time.
+        a.merge(PriorityQueue::from_vec(vec!(10)));
+        assert_eq!(a.pop(), 10);
+        a.merge(PriorityQueue::new());
+        assert_eq!(a.len(), 7);
+    }
+
+    #[test]
+    fn test_min_top_and_pop() {
+        let data = vec!(2u, 4, 6, 2, 1, 8, 10, 3, 5, 7, 0, 9, 1);
+        let mut sorted = data.clone();
+        sorted.sort();
+        sorted.reverse();
+        let mut heap = MinPriorityQueue::from_vec(data);
+        while !heap.is_empty() {
+            assert_eq!(heap.top(), sorted.last().unwrap());
+            assert_eq!(heap.pop(), sorted.pop().unwrap());
+        }
+        assert!(heap.maybe_pop().is_none());
+        assert!(heap.maybe_top().is_none());
+    }
+
+    #[test]
+    fn test_min_push_pop_replace() {
+        let mut heap = MinPriorityQueue::from_vec(vec!(5, 5, 2, 1, 3));
+        assert_eq!(heap.push_pop(0), 0);
+        assert_eq!(heap.push_pop(4), 1);
+        assert_eq!(heap.replace(6), 2);
+        assert_eq!(heap.len(), 5);
+        assert_eq!(heap.into_sorted_vec(), vec!(3, 4, 5, 5, 6));
+    }
+
+    #[test]
+    fn test_min_collect_merge() {
+        let mut a: MinPriorityQueue<int> = vec!(9, 3, 7).move_iter().collect();
+        let b: MinPriorityQueue<int> = vec!(8, 1).move_iter().collect();
+        a.merge(b);
+        let mut all: Vec<int> = a.iter().map(|&x| x).collect();
+        all.sort();
+        assert_eq!(all, vec!(1, 3, 7, 8, 9));
+        assert_eq!(a.pop(), 1);
+        let mut v = a.into_vec();
+        v.sort();
+        assert_eq!(v, vec!(3, 7, 8, 9));
+    }
 }
@@ -0,0 +1,812 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Ordered containers keyed by byte strings, implemented as radix trees
+//! (`RadixMap` and `RadixSet` types)
+//!
+//! Each edge of the tree is labelled with a run of bytes, and chains of
+//! nodes with a single child are merged into one edge, so a lookup touches
+//! one node per branching point rather than one per byte. Keys can be given
+//! as `&[u8]` or `&str`; they come back out of iterators as `Vec<u8>`.
+//!
+//! Besides exact lookups, the tree answers the queries that make it useful
+//! for routing tables and autocompletion: the longest stored key that is a
+//! prefix of a given key, iteration over all keys that start with a given
+//! prefix, and iteration in lexicographic order from a lower or upper bound.
+
+use std::iter;
+use std::mem::replace;
+
+/// Types which can be used as keys of a `RadixMap`.
+pub trait RadixKey {
+    /// The bytes of the key.
+    fn as_key<'a>(&'a self) -> &'a [u8];
+}
+
+impl<'b> RadixKey for &'b [u8] {
+    #[inline]
+    fn as_key<'a>(&'a self) -> &'a [u8] { *self }
+}
+
+impl<'b> RadixKey for &'b str {
+    #[inline]
+    fn as_key<'a>(&'a self) -> &'a [u8] { self.as_bytes() }
+}
+
+struct Node<T> {
+    // The label of the edge leading to this node. Only the root's is empty.
+    prefix: Vec<u8>,
+    value: Option<T>,
+    // Sorted by the first byte of their prefix, which is unique.
+    children: Vec<~Node<T>>,
+}
+
+impl<T> Node<T> {
+    fn new(prefix: &[u8], value: Option<T>) -> Node<T> {
+        Node { prefix: Vec::from_slice(prefix), value: value, children: Vec::new() }
+    }
+
+    // Ok(index) of the child starting with `byte`, or Err(index) where such
+    // a child would be inserted.
+    fn find_child(&self, byte: u8) -> Result<uint, uint> {
+        let (mut lo, mut hi) = (0, self.children.len());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            let b = *self.children.get(mid).prefix.get(0);
+            if b == byte {
+                return Ok(mid);
+            } else if b < byte {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        Err(lo)
+    }
+}
+
+// The length of the longest common prefix of `a` and `b`.
+fn common_prefix(a: &[u8], b: &[u8]) -> uint {
+    let mut i = 0;
+    while i < a.len() && i < b.len() && a[i] == b[i] {
+        i += 1;
+    }
+    i
+}
+
+fn insert<T>(node: &mut Node<T>, key: &[u8], value: T) -> Option<T> {
+    if key.is_empty() {
+        return replace(&mut node.value, Some(value));
+    }
+
+    let i = match node.find_child(key[0]) {
+        Err(i) => {
+            node.children.insert(i, ~Node::new(key, Some(value)));
+            return None;
+        }
+        Ok(i) => i,
+    };
+
+    let common = common_prefix(node.children.get(i).prefix.as_slice(), key);
+    if common == node.children.get(i).prefix.len() {
+        return insert(&mut **node.children.get_mut(i), key.slice_from(common), value);
+    }
+
+    // The key diverges partway along the child's edge: split the edge with
+    // a new node holding the shared part.
+    let mut child = node.children.remove(i).unwrap();
+    let mut mid = ~Node::new(key.slice_to(common), None);
+    child.prefix = Vec::from_slice(child.prefix.slice_from(common));
+
+    if common == key.len() {
+        mid.value = Some(value);
+        mid.children.push(child);
+    } else {
+        let leaf = ~Node::new(key.slice_from(common), Some(value));
+        if *leaf.prefix.get(0) < *child.prefix.get(0) {
+            mid.children.push(leaf);
+            mid.children.push(child);
+        } else {
+            mid.children.push(child);
+            mid.children.push(leaf);
+        }
+    }
+
+    node.children.insert(i, mid);
+    None
+}
+
+fn remove<T>(node: &mut Node<T>, key: &[u8]) -> Option<T> {
+    if key.is_empty() {
+        return node.value.take();
+    }
+
+    let i = match node.find_child(key[0]) {
+        Ok(i) => i,
+        Err(_) => return None,
+    };
+
+    let ret = {
+        let child = &mut **node.children.get_mut(i);
+        if !key.starts_with(child.prefix.as_slice()) {
+            return None;
+        }
+        let len = child.prefix.len();
+        remove(child, key.slice_from(len))
+    };
+
+    if ret.is_some() {
+        // Keep the tree compressed: drop a child that's become empty, and
+        // merge one that's left with a single child into that child.
+        let (has_value, nchildren) = {
+            let child = node.children.get(i);
+            (child.value.is_some(), child.children.len())
+        };
+        if !has_value && nchildren == 0 {
+            node.children.remove(i);
+        } else if !has_value && nchildren == 1 {
+            let mut child = node.children.remove(i).unwrap();
+            let mut grandchild = child.children.pop().unwrap();
+            child.prefix.push_all(grandchild.prefix.as_slice());
+            grandchild.prefix = child.prefix;
+            node.children.insert(i, grandchild);
+        }
+    }
+
+    ret
+}
+
+fn find_mut<'a, T>(node: &'a mut Node<T>, key: &[u8]) -> Option<&'a mut T> {
+    if key.is_empty() {
+        return node.value.as_mut();
+    }
+
+    match node.find_child(key[0]) {
+        Ok(i) => {
+            let child = &mut **node.children.get_mut(i);
+            if key.starts_with(child.prefix.as_slice()) {
+                let len = child.prefix.len();
+                find_mut(child, key.slice_from(len))
+            } else {
+                None
+            }
+        }
+        Err(_) => None,
+    }
+}
+
+/// An ordered map keyed by byte strings, implemented as a radix tree.
+pub struct RadixMap<T> {
+    root: Node<T>,
+    length: uint,
+}
+
+impl<T> Container for RadixMap<T> {
+    /// Return the number of elements in the map
+    #[inline]
+    fn len(&self) -> uint { self.length }
+}
+
+impl<T> Mutable for RadixMap<T> {
+    /// Clear the map, removing all values.
+    #[inline]
+    fn clear(&mut self) {
+        self.root = Node::new([], None);
+        self.length = 0;
+    }
+}
+
+impl<T> RadixMap<T> {
+    /// Create an empty RadixMap
+    #[inline]
+    pub fn new() -> RadixMap<T> {
+        RadixMap { root: Node::new([], None), length: 0 }
+    }
+
+    /// Return a reference to the value corresponding to the key
+    pub fn find<'a, K: RadixKey>(&'a self, key: K) -> Option<&'a T> {
+        let mut node = &self.root;
+        let mut rest = key.as_key();
+        loop {
+            if rest.is_empty() {
+                return node.value.as_ref();
+            }
+            match node.find_child(rest[0]) {
+                Ok(i) => {
+                    let child = &**node.children.get(i);
+                    if !rest.starts_with(child.prefix.as_slice()) {
+                        return None;
+                    }
+                    rest = rest.slice_from(child.prefix.len());
+                    node = child;
+                }
+                Err(_) => return None,
+            }
+        }
+    }
+
+    /// Return true if the map contains a value for the specified key
+    #[inline]
+    pub fn contains_key<K: RadixKey>(&self, key: K) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Return a mutable reference to the value corresponding to the key
+    #[inline]
+    pub fn find_mut<'a, K: RadixKey>(&'a mut self, key: K) -> Option<&'a mut T> {
+        find_mut(&mut self.root, key.as_key())
+    }
+
+    /// Insert a key-value pair into the map. An existing value for a
+    /// key is replaced by the new value. Return true if the key did
+    /// not already exist in the map.
+    #[inline]
+    pub fn insert<K: RadixKey>(&mut self, key: K, value: T) -> bool {
+        self.swap(key, value).is_none()
+    }
+
+    /// Remove a key-value pair from the map. Return true if the key
+    /// was present in the map, otherwise false.
+    #[inline]
+    pub fn remove<K: RadixKey>(&mut self, key: K) -> bool {
+        self.pop(key).is_some()
+    }
+
+    /// Insert a key-value pair from the map. If the key already had a value
+    /// present in the map, that value is returned. Otherwise None is returned.
+    pub fn swap<K: RadixKey>(&mut self, key: K, value: T) -> Option<T> {
+        let ret = insert(&mut self.root, key.as_key(), value);
+        if ret.is_none() { self.length += 1 }
+        ret
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key
+    /// was previously in the map.
+    pub fn pop<K: RadixKey>(&mut self, key: K) -> Option<T> {
+        let ret = remove(&mut self.root, key.as_key());
+        if ret.is_some() { self.length -= 1 }
+        ret
+    }
+
+    /// Find the longest key in the map which is a prefix of `key`, returning
+    /// its length along with its value.
+    pub fn longest_prefix<'a, K: RadixKey>(&'a self, key: K) -> Option<(uint, &'a T)> {
+        let key = key.as_key();
+        let mut node = &self.root;
+        let mut depth = 0;
+        let mut best = node.value.as_ref().map(|v| (0u, v));
+
+        while depth < key.len() {
+            let rest = key.slice_from(depth);
+            match node.find_child(rest[0]) {
+                Ok(i) => {
+                    let child = &**node.children.get(i);
+                    if !rest.starts_with(child.prefix.as_slice()) {
+                        break;
+                    }
+                    depth += child.prefix.len();
+                    node = child;
+                    match node.value {
+                        Some(ref v) => best = Some((depth, v)),
+                        None => {}
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+
+        best
+    }
+
+    /// Get an iterator over the key-value pairs in the map, in lexicographic
+    /// order of the keys
+    pub fn iter<'a>(&'a self) -> Entries<'a, T> {
+        Entries {
+            stack: vec!(Frame { node: &self.root, yielded: false, next_child: 0 }),
+            key: Vec::new(),
+        }
+    }
+
+    /// Get an iterator over the keys in the map, in lexicographic order
+    pub fn keys<'a>(&'a self) -> Keys<'a, T> {
+        self.iter().map(|(k, _v)| k)
+    }
+
+    /// Get an iterator over the values in the map, in lexicographic order of
+    /// their keys
+    pub fn values<'a>(&'a self) -> Values<'a, T> {
+        self.iter().map(|(_k, v)| v)
+    }
+
+    /// Get an iterator over the key-value pairs whose keys start with
+    /// `prefix`, in lexicographic order of the keys
+    pub fn prefix_iter<'a, K: RadixKey>(&'a self, prefix: K) -> Entries<'a, T> {
+        let mut node = &self.root;
+        let mut rest = prefix.as_key();
+        let mut key = Vec::new();
+
+        loop {
+            if rest.is_empty() {
+                return Entries {
+                    stack: vec!(Frame { node: node, yielded: false, next_child: 0 }),
+                    key: key,
+                };
+            }
+
+            let child = match node.find_child(rest[0]) {
+                Ok(i) => &**node.children.get(i),
+                Err(_) => return Entries { stack: Vec::new(), key: Vec::new() },
+            };
+            let common = common_prefix(child.prefix.as_slice(), rest);
+            if common == rest.len() {
+                // The prefix ends at or partway along this edge, so every
+                // key below the child matches.
+                key.push_all(child.prefix.as_slice());
+                return Entries {
+                    stack: vec!(Frame { node: child, yielded: false, next_child: 0 }),
+                    key: key,
+                };
+            } else if common < child.prefix.len() {
+                return Entries { stack: Vec::new(), key: Vec::new() };
+            }
+
+            key.push_all(child.prefix.as_slice());
+            rest = rest.slice_from(common);
+            node = child;
+        }
+    }
+
+    // If `upper` is true then returns upper_bound else returns lower_bound.
+    fn bound<'a>(&'a self, key: &[u8], upper: bool) -> Entries<'a, T> {
+        let mut iter = Entries { stack: Vec::new(), key: Vec::new() };
+        let mut node = &self.root;
+        let mut rest = key;
+
+        loop {
+            if rest.is_empty() {
+                // This node's own key equals `key`; everything below it is
+                // greater.
+                iter.stack.push(Frame { node: node, yielded: upper, next_child: 0 });
+                return iter;
+            }
+
+            // This node's key is a proper prefix of `key`, so it's less.
+            let (i, descend) = match node.find_child(rest[0]) {
+                Err(i) => (i, false),
+                Ok(i) => {
+                    let p = node.children.get(i).prefix.as_slice();
+                    let common = common_prefix(p, rest);
+                    if common == p.len() {
+                        (i, true)
+                    } else if common == rest.len() || p[common] > rest[common] {
+                        // The whole subtree sorts after `key`.
+                        (i, false)
+                    } else {
+                        // The whole subtree sorts before `key`.
+                        (i + 1, false)
+                    }
+                }
+            };
+
+            if !descend {
+                iter.stack.push(Frame { node: node, yielded: true, next_child: i });
+                return iter;
+            }
+
+            iter.stack.push(Frame { node: node, yielded: true, next_child: i + 1 });
+            let child = &**node.children.get(i);
+            iter.key.push_all(child.prefix.as_slice());
+            rest = rest.slice_from(child.prefix.len());
+            node = child;
+        }
+    }
+
+    /// Get an iterator pointing to the first key-value pair whose key is not less than `key`.
+    /// If all keys in the map are less than `key` an empty iterator is returned.
+    pub fn lower_bound<'a, K: RadixKey>(&'a self, key: K) -> Entries<'a, T> {
+        self.bound(key.as_key(), false)
+    }
+
+    /// Get an iterator pointing to the first key-value pair whose key is greater than `key`.
+    /// If all keys in the map are not greater than `key` an empty iterator is returned.
+    pub fn upper_bound<'a, K: RadixKey>(&'a self, key: K) -> Entries<'a, T> {
+        self.bound(key.as_key(), true)
+    }
+}
+
+impl<K: RadixKey, T> FromIterator<(K, T)> for RadixMap<T> {
+    fn from_iter<Iter: Iterator<(K, T)>>(iter: Iter) -> RadixMap<T> {
+        let mut map = RadixMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: RadixKey, T> Extendable<(K, T)> for RadixMap<T> {
+    fn extend<Iter: Iterator<(K, T)>>(&mut self, mut iter: Iter) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+struct Frame<'a, T> {
+    node: &'a Node<T>,
+    // Whether this node's own value has been dealt with.
+    yielded: bool,
+    // The next child to descend into.
+    next_child: uint,
+}
+
+/// Forward iterator over a map
+pub struct Entries<'a, T> {
+    stack: Vec<Frame<'a, T>>,
+    // The concatenated prefixes of the nodes on the stack.
+    key: Vec<u8>,
+}
+
+impl<'a, T> Iterator<(Vec<u8>, &'a T)> for Entries<'a, T> {
+    fn next(&mut self) -> Option<(Vec<u8>, &'a T)> {
+        loop {
+            let (node, yielded, next_child) = match self.stack.last() {
+                Some(frame) => (frame.node, frame.yielded, frame.next_child),
+                None => return None,
+            };
+
+            if !yielded {
+                self.stack.mut_last().unwrap().yielded = true;
+                match node.value {
+                    Some(ref value) => return Some((self.key.clone(), value)),
+                    None => {}
+                }
+            } else if next_child < node.children.len() {
+                self.stack.mut_last().unwrap().next_child += 1;
+                let child = &**node.children.get(next_child);
+                self.key.push_all(child.prefix.as_slice());
+                self.stack.push(Frame { node: child, yielded: false, next_child: 0 });
+            } else {
+                self.stack.pop();
+                let len = self.key.len() - node.prefix.len();
+                self.key.truncate(len);
+            }
+        }
+    }
+}
+
+/// RadixMap keys iterator
+pub type Keys<'a, T> =
+    iter::Map<'static, (Vec<u8>, &'a T), Vec<u8>, Entries<'a, T>>;
+
+/// RadixMap values iterator
+pub type Values<'a, T> =
+    iter::Map<'static, (Vec<u8>, &'a T), &'a T, Entries<'a, T>>;
+
+/// An ordered set of byte strings, implemented as a radix tree.
+pub struct RadixSet {
+    map: RadixMap<()>,
+}
+
+impl Container for RadixSet {
+    /// Return the number of elements in the set
+    #[inline]
+    fn len(&self) -> uint { self.map.len() }
+}
+
+impl Mutable for RadixSet {
+    /// Clear the set, removing all values.
+    #[inline]
+    fn clear(&mut self) { self.map.clear() }
+}
+
+impl RadixSet {
+    /// Create an empty RadixSet
+    #[inline]
+    pub fn new() -> RadixSet {
+        RadixSet { map: RadixMap::new() }
+    }
+
+    /// Return true if the set contains a value
+    #[inline]
+    pub fn contains<K: RadixKey>(&self, value: K) -> bool {
+        self.map.contains_key(value)
+    }
+
+    /// Add a value to the set. Return true if the value was not already
+    /// present in the set.
+    #[inline]
+    pub fn insert<K: RadixKey>(&mut self, value: K) -> bool {
+        self.map.insert(value, ())
+    }
+
+    /// Remove a value from the set. Return true if the value was
+    /// present in the set.
+    #[inline]
+    pub fn remove<K: RadixKey>(&mut self, value: K) -> bool {
+        self.map.remove(value)
+    }
+
+    /// Return the length of the longest value in the set which is a prefix
+    /// of `value`
+    #[inline]
+    pub fn longest_prefix<K: RadixKey>(&self, value: K) -> Option<uint> {
+        self.map.longest_prefix(value).map(|(len, _)| len)
+    }
+
+    /// Get an iterator over the values in the set, in lexicographic order
+    #[inline]
+    pub fn iter<'a>(&'a self) -> SetItems<'a> {
+        SetItems { iter: self.map.iter() }
+    }
+
+    /// Get an iterator over the values in the set which start with `prefix`
+    #[inline]
+    pub fn prefix_iter<'a, K: RadixKey>(&'a self, prefix: K) -> SetItems<'a> {
+        SetItems { iter: self.map.prefix_iter(prefix) }
+    }
+
+    /// Get an iterator pointing to the first value that is not less than `val`.
+    /// If all values in the set are less than `val` an empty iterator is returned.
+    pub fn lower_bound<'a, K: RadixKey>(&'a self, val: K) -> SetItems<'a> {
+        SetItems { iter: self.map.lower_bound(val) }
+    }
+
+    /// Get an iterator pointing to the first value that key is greater than `val`.
+    /// If all values in the set are not greater than `val` an empty iterator is returned.
+    pub fn upper_bound<'a, K: RadixKey>(&'a self, val: K) -> SetItems<'a> {
+        SetItems { iter: self.map.upper_bound(val) }
+    }
+}
+
+impl<K: RadixKey> FromIterator<K> for RadixSet {
+    fn from_iter<Iter: Iterator<K>>(iter: Iter) -> RadixSet {
+        let mut set = RadixSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<K: RadixKey> Extendable<K> for RadixSet {
+    fn extend<Iter: Iterator<K>>(&mut self, mut iter: Iter) {
+        for elem in iter {
+            self.insert(elem);
+        }
+    }
+}
+
+/// Forward iterator over a set
+pub struct SetItems<'a> {
+    iter: Entries<'a, ()>,
+}
+
+impl<'a> Iterator<Vec<u8>> for SetItems<'a> {
+    fn next(&mut self) -> Option<Vec<u8>> {
+        self.iter.next().map(|(key, _)| key)
+    }
+}
+
+#[cfg(test)]
+mod test_map {
+    use super::{RadixMap, Node};
+    use rand;
+    use rand::Rng;
+    use std::str;
+
+    fn check_node<T>(node: &Node<T>, is_root: bool) -> uint {
+        if !is_root {
+            assert!(!node.prefix.is_empty());
+            // No empty nodes, and no uncompressed chains.
+            assert!(node.value.is_some() || node.children.len() >= 2);
+        }
+        for w in node.children.as_slice().windows(2) {
+            assert!(*w[0].prefix.get(0) < *w[1].prefix.get(0));
+        }
+        let below = node.children.iter().fold(0, |n, c| n + check_node(&**c, false));
+        below + if node.value.is_some() { 1 } else { 0 }
+    }
+
+    fn check<T>(map: &RadixMap<T>) {
+        assert_eq!(check_node(&map.root, true), map.len());
+    }
+
+    fn keys<T>(iter: super::Entries<T>) -> Vec<~str> {
+        iter.map(|(k, _)| str::from_utf8(k.as_slice()).unwrap().to_owned()).collect()
+    }
+
+    #[test]
+    fn test_insert_find() {
+        let mut m = RadixMap::new();
+        assert!(m.insert("romane", 1));
+        assert!(m.insert("romanus", 2));
+        assert!(m.insert("romulus", 3));
+        assert!(m.insert("rubens", 4));
+        assert!(m.insert("ruber", 5));
+        assert!(m.insert("rubicon", 6));
+        assert!(m.insert("rubicundus", 7));
+        assert!(m.insert("rom", 8));
+        assert!(m.insert("", 9));
+        assert!(!m.insert("ruber", 50));
+        check(&m);
+
+        assert_eq!(m.len(), 9);
+        assert_eq!(m.find("romane"), Some(&1));
+        assert_eq!(m.find("ruber"), Some(&50));
+        assert_eq!(m.find("rom"), Some(&8));
+        assert_eq!(m.find(""), Some(&9));
+        assert_eq!(m.find("ro"), None);
+        assert_eq!(m.find("romanes"), None);
+        assert_eq!(m.find("x"), None);
+        assert_eq!(m.find("rubicundus".as_bytes()), Some(&7));
+
+        *m.find_mut("rom").unwrap() = 80;
+        assert_eq!(m.find("rom"), Some(&80));
+        assert!(m.find_mut("roman").is_none());
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut m = RadixMap::new();
+        for &k in ["test", "team", "toast", "te", "t"].iter() {
+            m.insert(k, k.len());
+        }
+        check(&m);
+
+        assert_eq!(m.pop("te"), Some(2));
+        check(&m);
+        assert_eq!(m.pop("te"), None);
+        assert_eq!(m.pop("tes"), None);
+        assert!(m.remove("test"));
+        check(&m);
+        assert_eq!(m.find("team"), Some(&4));
+        assert!(m.remove("t"));
+        check(&m);
+        assert_eq!(keys(m.iter()), vec!("team".to_owned(), "toast".to_owned()));
+        assert!(m.remove("team"));
+        assert!(m.remove("toast"));
+        check(&m);
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn test_iter_order() {
+        let words = ["b", "abc", "a", "ab", "abd", "ba", "", "c", "abcd"];
+        let m: RadixMap<uint> = words.iter().enumerate().map(|(i, &w)| (w, i)).collect();
+        let mut sorted: Vec<~str> = words.iter().map(|w| w.to_owned()).collect();
+        sorted.sort();
+        assert_eq!(keys(m.iter()), sorted);
+        assert_eq!(m.values().len(), words.len());
+    }
+
+    #[test]
+    fn test_longest_prefix() {
+        let mut routes = RadixMap::new();
+        routes.insert("/", "root");
+        routes.insert("/api", "api");
+        routes.insert("/api/v1/", "v1");
+        routes.insert("/static/", "static");
+
+        assert_eq!(routes.longest_prefix("/api/v1/users"), Some((8, &"v1")));
+        assert_eq!(routes.longest_prefix("/api/v2"), Some((4, &"api")));
+        assert_eq!(routes.longest_prefix("/apx"), Some((1, &"root")));
+        assert_eq!(routes.longest_prefix("/static/"), Some((8, &"static")));
+        assert_eq!(routes.longest_prefix("nope"), None);
+
+        // Byte-oriented keys, such as IP prefixes.
+        let mut table = RadixMap::new();
+        table.insert(&[10u8], 1);
+        table.insert(&[10u8, 1], 2);
+        table.insert(&[192u8, 168], 3);
+        assert_eq!(table.longest_prefix(&[10u8, 1, 2, 3]), Some((2, &2)));
+        assert_eq!(table.longest_prefix(&[10u8, 2, 2, 3]), Some((1, &1)));
+        assert_eq!(table.longest_prefix(&[192u8, 169, 0, 1]), None);
+    }
+
+    #[test]
+    fn test_prefix_iter() {
+        let mut m = RadixMap::new();
+        for &w in ["car", "cart", "carbon", "care", "cat", "dog", "ca"].iter() {
+            m.insert(w, ());
+        }
+
+        let car: Vec<~str> = vec!("car", "carbon", "care", "cart").move_iter()
+                                                                  .map(|s| s.to_owned()).collect();
+        assert_eq!(keys(m.prefix_iter("car")), car);
+        assert_eq!(keys(m.prefix_iter("carb")), vec!("carbon".to_owned()));
+        assert_eq!(keys(m.prefix_iter("c")).len(), 6);
+        assert_eq!(keys(m.prefix_iter("")).len(), 7);
+        assert_eq!(keys(m.prefix_iter("cab")).len(), 0);
+        assert_eq!(keys(m.prefix_iter("carbons")).len(), 0);
+        assert_eq!(keys(m.prefix_iter("x")).len(), 0);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let mut rng = rand::weak_rng();
+        let mut m = RadixMap::new();
+        let mut all = Vec::new();
+        for _ in range(0, 300) {
+            let len = rng.gen_range(0u, 5);
+            let k: ~str = range(0, len).map(|_| rng.choose(['a', 'b', 'c'])).collect();
+            if m.insert(k.as_slice(), ()) {
+                all.push(k);
+            }
+        }
+        check(&m);
+        all.sort();
+        assert_eq!(keys(m.iter()), all);
+
+        for _ in range(0, 200) {
+            let len = rng.gen_range(0u, 6);
+            let probe: ~str = range(0, len).map(|_| rng.choose(['a', 'b', 'c', 'd'])).collect();
+
+            let lower: Vec<~str> = all.iter().filter(|k| k.as_slice() >= probe.as_slice())
+                                      .map(|k| k.clone()).collect();
+            let upper: Vec<~str> = all.iter().filter(|k| k.as_slice() > probe.as_slice())
+                                      .map(|k| k.clone()).collect();
+            assert_eq!(keys(m.lower_bound(probe.as_slice())), lower);
+            assert_eq!(keys(m.upper_bound(probe.as_slice())), upper);
+        }
+    }
+
+    #[test]
+    fn test_random_remove() {
+        let mut rng = rand::weak_rng();
+        let mut m = RadixMap::new();
+        let mut present = Vec::new();
+        for _ in range(0, 1000) {
+            let len = rng.gen_range(0u, 4);
+            let k: Vec<u8> = range(0, len).map(|_| rng.gen_range(0u8, 3)).collect();
+            if rng.gen() {
+                if m.insert(k.as_slice(), k.len()) { present.push(k) }
+            } else {
+                let was = present.iter().position(|p| *p == k);
+                assert_eq!(m.remove(k.as_slice()), was.is_some());
+                match was {
+                    Some(i) => { present.swap_remove(i); }
+                    None => {}
+                }
+            }
+            assert_eq!(m.len(), present.len());
+        }
+        check(&m);
+        for k in present.iter() {
+            assert_eq!(m.find(k.as_slice()), Some(&k.len()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_set {
+    use super::RadixSet;
+
+    #[test]
+    fn test_set() {
+        let mut set: RadixSet = vec!("apple", "app", "banana").move_iter().collect();
+        assert_eq!(set.len(), 3);
+        assert!(set.contains("app"));
+        assert!(!set.contains("ap"));
+        assert!(!set.insert("app"));
+        assert!(set.insert("ap"));
+
+        let all: Vec<Vec<u8>> = set.iter().collect();
+        assert_eq!(all, vec!(Vec::from_slice("ap".as_bytes()),
+                             Vec::from_slice("app".as_bytes()),
+                             Vec::from_slice("apple".as_bytes()),
+                             Vec::from_slice("banana".as_bytes())));
+
+        assert_eq!(set.prefix_iter("app").len(), 2);
+        assert_eq!(set.lower_bound("apq").next(), Some(Vec::from_slice("banana".as_bytes())));
+        assert_eq!(set.upper_bound("banana").next(), None);
+        assert_eq!(set.longest_prefix("apples"), Some(5));
+
+        assert!(set.remove("apple"));
+        assert!(!set.remove("apple"));
+        assert_eq!(set.len(), 3);
+    }
+}
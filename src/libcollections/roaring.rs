@@ -0,0 +1,751 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A compressed set of `u32`s (`RoaringBitmap` type)
+//!
+//! The value space is split into chunks of 2^16 values sharing the same high
+//! 16 bits. Only non-empty chunks are stored, and each one picks the cheaper
+//! of two representations: a sorted array of the low 16 bits while it holds
+//! at most 4096 values, or a dense 8KiB bitmap once it holds more. Large
+//! sparse sets therefore cost about two bytes per value, dense regions cost
+//! one bit per value, and set operations work a chunk at a time, touching
+//! only the chunks both operands have in common.
+//!
+//! Besides the usual set operations, `rank` and `select` answer "how many
+//! values are no greater than `x`" and "which value is the `n`th smallest"
+//! without walking every value.
+
+use std::fmt;
+use std::num::Bitwise;
+use std::slice;
+
+// A chunk switches from an array to a bitmap when it grows beyond this many
+// values, which is the point where the array would take more space.
+static ARRAY_MAX: uint = 4096;
+static BITMAP_WORDS: uint = 1024;
+
+#[deriving(Clone, Eq)]
+enum Store {
+    // Sorted low bits of the values.
+    Array(Vec<u16>),
+    // 1024 words of bits, and the number of bits set.
+    Bitmap(Vec<u64>, uint),
+}
+
+#[deriving(Clone, Eq)]
+struct Chunk {
+    key: u16,
+    store: Store,
+}
+
+// Ok(index) of `value` in the sorted `values`, or Err(index) where it would
+// be inserted.
+fn search<T: TotalOrd>(values: &[T], value: &T) -> Result<uint, uint> {
+    let (mut lo, mut hi) = (0, values.len());
+    while lo < hi {
+        let mid = (lo + hi) / 2;
+        match values[mid].cmp(value) {
+            Equal => return Ok(mid),
+            Less => lo = mid + 1,
+            Greater => hi = mid,
+        }
+    }
+    Err(lo)
+}
+
+fn popcount(words: &[u64]) -> uint {
+    words.iter().fold(0, |n, w| n + w.count_ones() as uint)
+}
+
+impl Store {
+    // Build a store from sorted values, picking the right representation.
+    fn from_array(values: Vec<u16>) -> Store {
+        if values.len() <= ARRAY_MAX {
+            Array(values)
+        } else {
+            let mut words = Vec::from_elem(BITMAP_WORDS, 0u64);
+            for &v in values.iter() {
+                *words.get_mut(v as uint / 64) |= 1 << (v as uint % 64);
+            }
+            Bitmap(words, values.len())
+        }
+    }
+
+    // Build a store from bitmap words, picking the right representation.
+    fn from_words(words: Vec<u64>) -> Store {
+        let len = popcount(words.as_slice());
+        if len > ARRAY_MAX {
+            Bitmap(words, len)
+        } else {
+            let mut values = Vec::with_capacity(len);
+            for (i, &w) in words.iter().enumerate() {
+                let mut w = w;
+                while w != 0 {
+                    values.push((i * 64 + w.trailing_zeros() as uint) as u16);
+                    w &= w - 1;
+                }
+            }
+            Array(values)
+        }
+    }
+
+    fn to_words(&self) -> Vec<u64> {
+        match *self {
+            Array(ref values) => {
+                let mut words = Vec::from_elem(BITMAP_WORDS, 0u64);
+                for &v in values.iter() {
+                    *words.get_mut(v as uint / 64) |= 1 << (v as uint % 64);
+                }
+                words
+            }
+            Bitmap(ref words, _) => words.clone(),
+        }
+    }
+
+    fn len(&self) -> uint {
+        match *self {
+            Array(ref values) => values.len(),
+            Bitmap(_, len) => len,
+        }
+    }
+
+    fn contains(&self, value: u16) -> bool {
+        match *self {
+            Array(ref values) => search(values.as_slice(), &value).is_ok(),
+            Bitmap(ref words, _) => {
+                *words.get(value as uint / 64) & (1 << (value as uint % 64)) != 0
+            }
+        }
+    }
+
+    fn insert(&mut self, value: u16) -> bool {
+        let grown = match *self {
+            Array(ref mut values) => {
+                match search(values.as_slice(), &value) {
+                    Ok(_) => return false,
+                    Err(i) => values.insert(i, value),
+                }
+                values.len() > ARRAY_MAX
+            }
+            Bitmap(ref mut words, ref mut len) => {
+                let word = words.get_mut(value as uint / 64);
+                let bit = 1 << (value as uint % 64);
+                if *word & bit != 0 {
+                    return false;
+                }
+                *word |= bit;
+                *len += 1;
+                false
+            }
+        };
+        if grown {
+            *self = Bitmap(self.to_words(), ARRAY_MAX + 1);
+        }
+        true
+    }
+
+    fn remove(&mut self, value: u16) -> bool {
+        let shrunk = match *self {
+            Array(ref mut values) => {
+                match search(values.as_slice(), &value) {
+                    Ok(i) => { values.remove(i); }
+                    Err(_) => return false,
+                }
+                false
+            }
+            Bitmap(ref mut words, ref mut len) => {
+                let word = words.get_mut(value as uint / 64);
+                let bit = 1 << (value as uint % 64);
+                if *word & bit == 0 {
+                    return false;
+                }
+                *word &= !bit;
+                *len -= 1;
+                *len <= ARRAY_MAX
+            }
+        };
+        if shrunk {
+            *self = Store::from_words(self.to_words());
+        }
+        true
+    }
+
+    // The number of values no greater than `value`.
+    fn rank(&self, value: u16) -> uint {
+        match *self {
+            Array(ref values) => {
+                match search(values.as_slice(), &value) {
+                    Ok(i) => i + 1,
+                    Err(i) => i,
+                }
+            }
+            Bitmap(ref words, _) => {
+                let i = value as uint / 64;
+                let below = popcount(words.slice_to(i));
+                let bit = value as uint % 64;
+                let mask = if bit == 63 { !0 } else { (1 << (bit + 1)) - 1 };
+                below + (*words.get(i) & mask).count_ones() as uint
+            }
+        }
+    }
+
+    // The `n`th smallest value, where `n` is less than the length.
+    fn select(&self, n: uint) -> u16 {
+        match *self {
+            Array(ref values) => *values.get(n),
+            Bitmap(ref words, _) => {
+                let mut n = n;
+                for (i, &w) in words.iter().enumerate() {
+                    let ones = w.count_ones() as uint;
+                    if n < ones {
+                        let mut w = w;
+                        for _ in range(0, n) {
+                            w &= w - 1;
+                        }
+                        return (i * 64 + w.trailing_zeros() as uint) as u16;
+                    }
+                    n -= ones;
+                }
+                unreachable!()
+            }
+        }
+    }
+
+    fn min(&self) -> u16 { self.select(0) }
+
+    fn max(&self) -> u16 { self.select(self.len() - 1) }
+
+    fn iter<'a>(&'a self) -> StoreItems<'a> {
+        StoreItems { store: self, pos: 0 }
+    }
+
+    fn is_subset(&self, other: &Store) -> bool {
+        if self.len() > other.len() {
+            return false;
+        }
+        match (self, other) {
+            (&Bitmap(ref a, _), &Bitmap(ref b, _)) => {
+                a.iter().zip(b.iter()).all(|(&a, &b)| a & !b == 0)
+            }
+            _ => self.iter().all(|v| other.contains(v)),
+        }
+    }
+
+    fn is_disjoint(&self, other: &Store) -> bool {
+        match (self, other) {
+            (&Bitmap(ref a, _), &Bitmap(ref b, _)) => {
+                a.iter().zip(b.iter()).all(|(&a, &b)| a & b == 0)
+            }
+            (&Array(_), &Bitmap(..)) => self.iter().all(|v| !other.contains(v)),
+            _ => other.iter().all(|v| !self.contains(v)),
+        }
+    }
+
+    // Combine two stores. `op` is applied word by word when either side is
+    // a bitmap, and `keep` decides which values survive a merge of two
+    // arrays given whether they appear in the left and right sides.
+    fn combine(&self, other: &Store, op: |u64, u64| -> u64,
+               keep: |bool, bool| -> bool) -> Store {
+        match (self, other) {
+            (&Array(ref a), &Array(ref b)) => {
+                let mut out = Vec::new();
+                let (mut i, mut j) = (0, 0);
+                while i < a.len() || j < b.len() {
+                    let (x, in_a, in_b) = if j == b.len() ||
+                                             (i < a.len() && *a.get(i) < *b.get(j)) {
+                        i += 1;
+                        (*a.get(i - 1), true, false)
+                    } else if i == a.len() || *b.get(j) < *a.get(i) {
+                        j += 1;
+                        (*b.get(j - 1), false, true)
+                    } else {
+                        i += 1;
+                        j += 1;
+                        (*a.get(i - 1), true, true)
+                    };
+                    if keep(in_a, in_b) {
+                        out.push(x);
+                    }
+                }
+                Store::from_array(out)
+            }
+            _ => {
+                let mut words = self.to_words();
+                let theirs = other.to_words();
+                for (w, &t) in words.mut_iter().zip(theirs.iter()) {
+                    *w = op(*w, t);
+                }
+                Store::from_words(words)
+            }
+        }
+    }
+}
+
+struct StoreItems<'a> {
+    store: &'a Store,
+    // An index into the array, or the next bit to look at in the bitmap.
+    pos: uint,
+}
+
+impl<'a> Iterator<u16> for StoreItems<'a> {
+    fn next(&mut self) -> Option<u16> {
+        match *self.store {
+            Array(ref values) => {
+                if self.pos < values.len() {
+                    self.pos += 1;
+                    Some(*values.get(self.pos - 1))
+                } else {
+                    None
+                }
+            }
+            Bitmap(ref words, _) => {
+                while self.pos < BITMAP_WORDS * 64 {
+                    let w = *words.get(self.pos / 64) >> (self.pos % 64);
+                    if w == 0 {
+                        // Skip the rest of this word.
+                        self.pos = (self.pos / 64 + 1) * 64;
+                    } else {
+                        let value = self.pos + w.trailing_zeros() as uint;
+                        self.pos = value + 1;
+                        return Some(value as u16);
+                    }
+                }
+                None
+            }
+        }
+    }
+}
+
+fn split(value: u32) -> (u16, u16) {
+    ((value >> 16) as u16, value as u16)
+}
+
+fn join(key: u16, low: u16) -> u32 {
+    (key as u32 << 16) | low as u32
+}
+
+/// A compressed bitmap holding a set of `u32` values.
+#[deriving(Clone, Eq)]
+pub struct RoaringBitmap {
+    // Non-empty chunks, sorted by key.
+    chunks: Vec<Chunk>,
+}
+
+impl RoaringBitmap {
+    /// Create an empty RoaringBitmap
+    #[inline]
+    pub fn new() -> RoaringBitmap {
+        RoaringBitmap { chunks: Vec::new() }
+    }
+
+    fn find(&self, key: u16) -> Result<uint, uint> {
+        let (mut lo, mut hi) = (0, self.chunks.len());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            match self.chunks.get(mid).key.cmp(&key) {
+                Equal => return Ok(mid),
+                Less => lo = mid + 1,
+                Greater => hi = mid,
+            }
+        }
+        Err(lo)
+    }
+
+    /// Return the number of values no greater than `value`
+    pub fn rank(&self, value: u32) -> uint {
+        let (key, low) = split(value);
+        let (i, within) = match self.find(key) {
+            Ok(i) => (i, self.chunks.get(i).store.rank(low)),
+            Err(i) => (i, 0),
+        };
+        self.chunks.slice_to(i).iter().fold(within, |n, c| n + c.store.len())
+    }
+
+    /// Return the `n`th smallest value in the set, counting from zero, or
+    /// `None` if the set holds no more than `n` values
+    pub fn select(&self, n: uint) -> Option<u32> {
+        let mut n = n;
+        for chunk in self.chunks.iter() {
+            let len = chunk.store.len();
+            if n < len {
+                return Some(join(chunk.key, chunk.store.select(n)));
+            }
+            n -= len;
+        }
+        None
+    }
+
+    /// Return the smallest value in the set, or `None` if it is empty
+    pub fn min(&self) -> Option<u32> {
+        self.chunks.iter().next().map(|c| join(c.key, c.store.min()))
+    }
+
+    /// Return the largest value in the set, or `None` if it is empty
+    pub fn max(&self) -> Option<u32> {
+        self.chunks.last().map(|c| join(c.key, c.store.max()))
+    }
+
+    /// Get an iterator over the values in the set, in ascending order
+    pub fn iter<'a>(&'a self) -> Items<'a> {
+        Items { chunks: self.chunks.iter(), current: None }
+    }
+
+    // Merge the chunks of two bitmaps. Chunks with a key in only one side
+    // are kept according to `left` and `right`; chunks with a key in both
+    // are combined with `op` and `keep` as in `Store::combine`.
+    fn merge(&self, other: &RoaringBitmap, left: bool, right: bool,
+             op: |u64, u64| -> u64, keep: |bool, bool| -> bool) -> RoaringBitmap {
+        let mut chunks = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        let (a, b) = (self.chunks.as_slice(), other.chunks.as_slice());
+        while i < a.len() || j < b.len() {
+            if j == b.len() || (i < a.len() && a[i].key < b[j].key) {
+                if left { chunks.push(a[i].clone()) }
+                i += 1;
+            } else if i == a.len() || b[j].key < a[i].key {
+                if right { chunks.push(b[j].clone()) }
+                j += 1;
+            } else {
+                let store = a[i].store.combine(&b[j].store, |x, y| op(x, y),
+                                               |x, y| keep(x, y));
+                if store.len() > 0 {
+                    chunks.push(Chunk { key: a[i].key, store: store });
+                }
+                i += 1;
+                j += 1;
+            }
+        }
+        RoaringBitmap { chunks: chunks }
+    }
+
+    /// Return the values in `self` or `other`
+    pub fn union(&self, other: &RoaringBitmap) -> RoaringBitmap {
+        self.merge(other, true, true, |a, b| a | b, |a, b| a || b)
+    }
+
+    /// Return the values in both `self` and `other`
+    pub fn intersection(&self, other: &RoaringBitmap) -> RoaringBitmap {
+        self.merge(other, false, false, |a, b| a & b, |a, b| a && b)
+    }
+
+    /// Return the values in `self` but not in `other`
+    pub fn difference(&self, other: &RoaringBitmap) -> RoaringBitmap {
+        self.merge(other, true, false, |a, b| a & !b, |a, b| a && !b)
+    }
+
+    /// Return the values in exactly one of `self` and `other`
+    pub fn symmetric_difference(&self, other: &RoaringBitmap) -> RoaringBitmap {
+        self.merge(other, true, true, |a, b| a ^ b, |a, b| a != b)
+    }
+
+    /// Add all the values in `other` to `self`
+    pub fn union_with(&mut self, other: &RoaringBitmap) {
+        *self = self.union(other);
+    }
+
+    /// Remove the values from `self` which are not in `other`
+    pub fn intersect_with(&mut self, other: &RoaringBitmap) {
+        *self = self.intersection(other);
+    }
+
+    /// Remove the values in `other` from `self`
+    pub fn difference_with(&mut self, other: &RoaringBitmap) {
+        *self = self.difference(other);
+    }
+
+    /// Keep the values in exactly one of `self` and `other`
+    pub fn symmetric_difference_with(&mut self, other: &RoaringBitmap) {
+        *self = self.symmetric_difference(other);
+    }
+}
+
+impl Container for RoaringBitmap {
+    /// Return the number of values in the set
+    fn len(&self) -> uint {
+        self.chunks.iter().fold(0, |n, c| n + c.store.len())
+    }
+
+    /// Return true if the set contains no values
+    #[inline]
+    fn is_empty(&self) -> bool { self.chunks.is_empty() }
+}
+
+impl Mutable for RoaringBitmap {
+    /// Clear the set, removing all values.
+    #[inline]
+    fn clear(&mut self) { self.chunks.clear() }
+}
+
+impl Set<u32> for RoaringBitmap {
+    fn contains(&self, value: &u32) -> bool {
+        let (key, low) = split(*value);
+        match self.find(key) {
+            Ok(i) => self.chunks.get(i).store.contains(low),
+            Err(_) => false,
+        }
+    }
+
+    fn is_disjoint(&self, other: &RoaringBitmap) -> bool {
+        self.chunks.iter().all(|c| {
+            match other.find(c.key) {
+                Ok(i) => c.store.is_disjoint(&other.chunks.get(i).store),
+                Err(_) => true,
+            }
+        })
+    }
+
+    fn is_subset(&self, other: &RoaringBitmap) -> bool {
+        self.chunks.iter().all(|c| {
+            match other.find(c.key) {
+                Ok(i) => c.store.is_subset(&other.chunks.get(i).store),
+                Err(_) => false,
+            }
+        })
+    }
+}
+
+impl MutableSet<u32> for RoaringBitmap {
+    fn insert(&mut self, value: u32) -> bool {
+        let (key, low) = split(value);
+        match self.find(key) {
+            Ok(i) => self.chunks.get_mut(i).store.insert(low),
+            Err(i) => {
+                self.chunks.insert(i, Chunk { key: key, store: Array(vec!(low)) });
+                true
+            }
+        }
+    }
+
+    fn remove(&mut self, value: &u32) -> bool {
+        let (key, low) = split(*value);
+        match self.find(key) {
+            Ok(i) => {
+                let removed = self.chunks.get_mut(i).store.remove(low);
+                if self.chunks.get(i).store.len() == 0 {
+                    self.chunks.remove(i);
+                }
+                removed
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+impl FromIterator<u32> for RoaringBitmap {
+    fn from_iter<Iter: Iterator<u32>>(iter: Iter) -> RoaringBitmap {
+        let mut set = RoaringBitmap::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extendable<u32> for RoaringBitmap {
+    fn extend<Iter: Iterator<u32>>(&mut self, mut iter: Iter) {
+        for elem in iter {
+            self.insert(elem);
+        }
+    }
+}
+
+impl fmt::Show for RoaringBitmap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f.buf, r"\{"));
+
+        for (i, x) in self.iter().enumerate() {
+            if i != 0 { try!(write!(f.buf, ", ")); }
+            try!(write!(f.buf, "{}", x));
+        }
+
+        write!(f.buf, r"\}")
+    }
+}
+
+/// Forward iterator over a RoaringBitmap
+pub struct Items<'a> {
+    chunks: slice::Items<'a, Chunk>,
+    current: Option<(u16, StoreItems<'a>)>,
+}
+
+impl<'a> Iterator<u32> for Items<'a> {
+    fn next(&mut self) -> Option<u32> {
+        loop {
+            match self.current {
+                Some((key, ref mut values)) => match values.next() {
+                    Some(low) => return Some(join(key, low)),
+                    None => {}
+                },
+                None => {}
+            }
+            match self.chunks.next() {
+                Some(chunk) => self.current = Some((chunk.key, chunk.store.iter())),
+                None => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{RoaringBitmap, Array, Bitmap, ARRAY_MAX};
+    use rand;
+    use rand::Rng;
+    use std::iter::range_step;
+    use treemap::TreeSet;
+
+    fn check(set: &RoaringBitmap) {
+        for w in set.chunks.as_slice().windows(2) {
+            assert!(w[0].key < w[1].key);
+        }
+        for c in set.chunks.iter() {
+            match c.store {
+                Array(ref values) => {
+                    assert!(values.len() > 0 && values.len() <= ARRAY_MAX);
+                    for w in values.as_slice().windows(2) {
+                        assert!(w[0] < w[1]);
+                    }
+                }
+                Bitmap(ref words, len) => {
+                    assert!(len > ARRAY_MAX);
+                    assert_eq!(super::popcount(words.as_slice()), len);
+                }
+            }
+        }
+    }
+
+    fn random_set(rng: &mut rand::XorShiftRng, n: uint, bound: u32) -> RoaringBitmap {
+        range(0, n).map(|_| rng.gen_range(0, bound)).collect()
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut set = RoaringBitmap::new();
+        assert!(set.is_empty());
+        assert!(set.insert(5));
+        assert!(set.insert(100000));
+        assert!(set.insert(0xffffffff));
+        assert!(!set.insert(5));
+        assert_eq!(set.len(), 3);
+        assert!(set.contains(&5));
+        assert!(set.contains(&0xffffffff));
+        assert!(!set.contains(&6));
+        assert!(!set.contains(&(5 + (1 << 16))));
+
+        assert!(set.remove(&100000));
+        assert!(!set.remove(&100000));
+        assert_eq!(set.chunks.len(), 2);
+        assert_eq!(set.iter().collect::<Vec<u32>>(), vec!(5, 0xffffffff));
+        assert_eq!(set.min(), Some(5));
+        assert_eq!(set.max(), Some(0xffffffff));
+
+        set.clear();
+        assert!(set.is_empty());
+        assert_eq!(set.min(), None);
+    }
+
+    #[test]
+    fn test_array_bitmap_conversion() {
+        let mut set = RoaringBitmap::new();
+        for i in range(0u32, ARRAY_MAX as u32) {
+            set.insert(i * 2);
+        }
+        check(&set);
+        assert!(match set.chunks.get(0).store { Array(_) => true, _ => false });
+
+        set.insert(1);
+        check(&set);
+        assert!(match set.chunks.get(0).store { Bitmap(..) => true, _ => false });
+        assert_eq!(set.len(), ARRAY_MAX + 1);
+
+        set.remove(&0);
+        check(&set);
+        assert!(match set.chunks.get(0).store { Array(_) => true, _ => false });
+        assert_eq!(set.iter().take(3).collect::<Vec<u32>>(), vec!(1, 2, 4));
+    }
+
+    #[test]
+    fn test_rank_select() {
+        let set: RoaringBitmap = range_step(0u32, 300000, 7).collect();
+        check(&set);
+        assert_eq!(set.rank(0), 1);
+        assert_eq!(set.rank(6), 1);
+        assert_eq!(set.rank(7), 2);
+        assert_eq!(set.rank(299999), set.len());
+        for &n in [0u, 1, 100, 9361, 9362, set.len() - 1].iter() {
+            let v = set.select(n).unwrap();
+            assert_eq!(v, n as u32 * 7);
+            assert_eq!(set.rank(v), n + 1);
+        }
+        assert_eq!(set.select(set.len()), None);
+    }
+
+    #[test]
+    fn test_set_ops() {
+        let mut rng = rand::weak_rng();
+        // Mix sparse chunks with ones dense enough to become bitmaps.
+        let a = random_set(&mut rng, 20000, 150000);
+        let b = random_set(&mut rng, 3000, 150000);
+        let ta: TreeSet<u32> = a.iter().collect();
+        let tb: TreeSet<u32> = b.iter().collect();
+
+        let expect = |set: RoaringBitmap, f: |&u32| -> bool| {
+            check(&set);
+            let mut want = Vec::new();
+            for x in ta.union(&tb) {
+                if f(x) { want.push(*x) }
+            }
+            assert_eq!(set.iter().collect::<Vec<u32>>(), want);
+        };
+
+        expect(a.union(&b), |_| true);
+        expect(a.intersection(&b), |x| ta.contains(x) && tb.contains(x));
+        expect(a.difference(&b), |x| ta.contains(x) && !tb.contains(x));
+        expect(a.symmetric_difference(&b), |x| ta.contains(x) != tb.contains(x));
+
+        let mut c = a.clone();
+        c.union_with(&b);
+        assert!(a.is_subset(&c));
+        assert!(c.is_superset(&b));
+        assert!(!c.is_subset(&b));
+        c.difference_with(&b);
+        assert!(c.is_disjoint(&b));
+        assert!(!a.is_disjoint(&a));
+    }
+
+    #[test]
+    fn test_show() {
+        let set: RoaringBitmap = vec!(3u32, 1, 70000).move_iter().collect();
+        assert_eq!(format!("{}", set), "{1, 3, 70000}".to_owned());
+    }
+}
+
+#[cfg(test)]
+mod bench {
+    extern crate test;
+    use self::test::Bencher;
+    use super::RoaringBitmap;
+    use rand::{weak_rng, Rng};
+
+    #[bench]
+    fn bench_insert_sparse(b: &mut Bencher) {
+        let mut rng = weak_rng();
+        let mut set = RoaringBitmap::new();
+        b.iter(|| {
+            set.insert(rng.gen());
+        })
+    }
+
+    #[bench]
+    fn bench_intersection(b: &mut Bencher) {
+        let mut rng = weak_rng();
+        let x: RoaringBitmap = range(0, 100000).map(|_| rng.gen_range(0u32, 1 << 22)).collect();
+        let y: RoaringBitmap = range(0, 100000).map(|_| rng.gen_range(0u32, 1 << 22)).collect();
+        b.iter(|| x.intersection(&y))
+    }
+}
@@ -0,0 +1,240 @@
+// Copyright 2012-2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+An implementation of the HMAC message authentication code (RFC 2104).
+
+`Hmac` wraps any `Digest` and is itself a `Digest`, whose result is the authentication code of
+everything that has been input since it was created or last reset.
+
+# Example
+
+```rust
+use crypto::Digest;
+use crypto::hmac::Hmac;
+use crypto::sha2::Sha256;
+
+let mut mac = Hmac::new(Sha256::new(), bytes!("key"));
+mac.input_str("The quick brown fox jumps over the lazy dog");
+let code = mac.result_bytes();
+
+let mut check = Hmac::new(Sha256::new(), bytes!("key"));
+check.input_str("The quick brown fox jumps over the lazy dog");
+assert!(check.verify(code.as_slice()));
+```
+*/
+
+use std::io::{Writer, IoResult};
+
+use Digest;
+
+/// The HMAC construction over the digest `D`.
+pub struct Hmac<D> {
+    digest: D,
+    i_key: Vec<u8>,
+    o_key: Vec<u8>,
+    finished: bool,
+}
+
+// Pad or hash the key out to the digest's block size, as described in section 2 of RFC 2104.
+fn expand_key<D: Digest>(digest: &mut D, key: &[u8]) -> Vec<u8> {
+    let bs = digest.block_size();
+    let mut expanded_key = Vec::from_elem(bs, 0u8);
+    if key.len() <= bs {
+        for (dst, &src) in expanded_key.mut_iter().zip(key.iter()) {
+            *dst = src;
+        }
+    } else {
+        let output_size = (digest.output_bits() + 7) / 8;
+        digest.input(key);
+        digest.result(expanded_key.as_mut_slice().mut_slice_to(output_size));
+        digest.reset();
+    }
+    expanded_key
+}
+
+fn derive_key(key: &[u8], mask: u8) -> Vec<u8> {
+    key.iter().map(|&b| b ^ mask).collect()
+}
+
+impl<D: Digest> Hmac<D> {
+    /// Create a new Hmac instance authenticating with `key`. The digest should be freshly
+    /// constructed; it is used for both the inner and outer hashes.
+    pub fn new(mut digest: D, key: &[u8]) -> Hmac<D> {
+        let expanded_key = expand_key(&mut digest, key);
+        let i_key = derive_key(expanded_key.as_slice(), 0x36);
+        let o_key = derive_key(expanded_key.as_slice(), 0x5c);
+        digest.input(i_key.as_slice());
+        Hmac {
+            digest: digest,
+            i_key: i_key,
+            o_key: o_key,
+            finished: false,
+        }
+    }
+
+    /// Compute the authentication code of the input so far and compare it with `code`. The
+    /// comparison takes the same time wherever the codes differ, so it doesn't leak how much of a
+    /// forged code was correct.
+    pub fn verify(&mut self, code: &[u8]) -> bool {
+        let mac = self.result_bytes();
+        if mac.len() != code.len() {
+            return false;
+        }
+        let mut diff = 0u8;
+        for (&a, &b) in mac.iter().zip(code.iter()) {
+            diff |= a ^ b;
+        }
+        diff == 0
+    }
+}
+
+impl<D: Digest> Digest for Hmac<D> {
+    fn input(&mut self, data: &[u8]) {
+        assert!(!self.finished);
+        self.digest.input(data);
+    }
+
+    fn result(&mut self, out: &mut [u8]) {
+        if !self.finished {
+            let inner = self.digest.result_bytes();
+            self.digest.reset();
+            self.digest.input(self.o_key.as_slice());
+            self.digest.input(inner.as_slice());
+            self.finished = true;
+        }
+        self.digest.result(out);
+    }
+
+    fn reset(&mut self) {
+        self.digest.reset();
+        self.digest.input(self.i_key.as_slice());
+        self.finished = false;
+    }
+
+    fn output_bits(&self) -> uint { self.digest.output_bits() }
+
+    fn block_size(&self) -> uint { self.digest.block_size() }
+}
+
+impl<D: Digest> Writer for Hmac<D> {
+    fn write(&mut self, buf: &[u8]) -> IoResult<()> {
+        self.input(buf);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Hmac;
+    use Digest;
+    use md5::Md5;
+    use sha1::Sha1;
+    use sha2::{Sha256, Sha512};
+
+    struct Test {
+        key: Vec<u8>,
+        data: Vec<u8>,
+    }
+
+    // Test cases 1, 2 and 6 from RFC 4231, which RFC 2202 shares for SHA-1. The last uses a key
+    // longer than the block size of all the digests, which must be hashed first.
+    fn tests() -> Vec<Test> {
+        vec!(
+            Test {
+                key: Vec::from_elem(20, 0x0bu8),
+                data: Vec::from_slice(bytes!("Hi There")),
+            },
+            Test {
+                key: Vec::from_slice(bytes!("Jefe")),
+                data: Vec::from_slice(bytes!("what do ya want for nothing?")),
+            },
+            Test {
+                key: Vec::from_elem(131, 0xaau8),
+                data: Vec::from_slice(bytes!("Test Using Larger Than Block-Size Key - Hash Key \
+                                              First")),
+            })
+    }
+
+    fn test_hmac<D: Digest>(new: fn() -> D, expected: &[&str]) {
+        for (t, &output_str) in tests().iter().zip(expected.iter()) {
+            let mut mac = Hmac::new(new(), t.key.as_slice());
+            mac.input(t.data.as_slice());
+            assert_eq!(mac.result_str().as_slice(), output_str);
+
+            // Once reset, the same key can authenticate another message
+            mac.reset();
+            for b in t.data.iter() {
+                mac.input([*b]);
+            }
+            assert_eq!(mac.result_str().as_slice(), output_str);
+        }
+    }
+
+    fn new_md5() -> Md5 { Md5::new() }
+    fn new_sha1() -> Sha1 { Sha1::new() }
+    fn new_sha256() -> Sha256 { Sha256::new() }
+    fn new_sha512() -> Sha512 { Sha512::new() }
+
+    #[test]
+    fn test_hmac_md5() {
+        test_hmac(new_md5, [
+            "5ccec34ea9656392457fa1ac27f08fbc",
+            "750c783e6ab0b503eaa86e310a5db738",
+            "bfecaf4efff90a3a668f3922fec3762d",
+        ]);
+    }
+
+    #[test]
+    fn test_hmac_sha1() {
+        test_hmac(new_sha1, [
+            "b617318655057264e28bc0b6fb378c8ef146be00",
+            "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79",
+            "90d0dace1c1bdc957339307803160335bde6df2b",
+        ]);
+    }
+
+    #[test]
+    fn test_hmac_sha256() {
+        test_hmac(new_sha256, [
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+        ]);
+    }
+
+    #[test]
+    fn test_hmac_sha512() {
+        test_hmac(new_sha512, [
+            "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cde\
+             daa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854",
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554\
+             9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737",
+            "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f352\
+             6b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598",
+        ]);
+    }
+
+    #[test]
+    fn test_verify() {
+        let mut mac = Hmac::new(Sha1::new(), bytes!("Jefe"));
+        write!(&mut mac, "what do ya want {} nothing?", "for").unwrap();
+        let code = mac.result_bytes();
+
+        mac.reset();
+        mac.input_str("what do ya want for nothing?");
+        assert!(mac.verify(code.as_slice()));
+
+        let mut forged = code.clone();
+        *forged.get_mut(19) ^= 1;
+        assert!(!mac.verify(forged.as_slice()));
+        assert!(!mac.verify(code.slice_to(19)));
+    }
+}
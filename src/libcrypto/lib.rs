@@ -0,0 +1,123 @@
+// Copyright 2012-2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+
+Cryptographic hash functions
+
+This crate provides the MD5, SHA-1 and SHA-2 message digests, and HMAC
+message authentication codes built on any of them. Every algorithm implements
+the common `Digest` trait, so code that only needs "some hash" can be generic
+over it, and every digest is also a `Writer`, so anything that can be written
+out can be hashed without buffering it first.
+
+MD5 and SHA-1 are provided for compatibility with existing formats (such as
+name-based UUIDs); new code that needs collision resistance should use one of
+the SHA-2 functions.
+
+# Example
+
+```rust
+use crypto::Digest;
+use crypto::sha2::Sha256;
+
+let mut hasher = Sha256::new();
+hasher.input_str("hello, ");
+write!(&mut hasher, "{}", "world").unwrap();
+assert_eq!(hasher.result_str(),
+           "09ca7e4eaa6e8ae9c7d261167129184883644d07dfba7cbfbc4c8a2e08360d5b".to_owned());
+```
+
+*/
+
+#![crate_id = "crypto#0.11-pre"]
+#![crate_type = "rlib"]
+#![crate_type = "dylib"]
+#![license = "MIT/ASL2"]
+#![doc(html_logo_url = "http://www.rust-lang.org/logos/rust-logo-128x128-blk-v2.png",
+       html_favicon_url = "http://www.rust-lang.org/favicon.ico",
+       html_root_url = "http://static.rust-lang.org/doc/master")]
+#![feature(macro_rules)]
+#![deny(deprecated_owned_vector)]
+
+extern crate serialize;
+
+use serialize::hex::ToHex;
+
+/// The Digest trait specifies an interface common to digest functions, such as SHA-1 and the SHA-2
+/// family of digest functions.
+pub trait Digest {
+    /// Provide message data.
+    ///
+    /// # Arguments
+    ///
+    /// * input - A vector of message data
+    fn input(&mut self, input: &[u8]);
+
+    /// Retrieve the digest result. This method may be called multiple times.
+    ///
+    /// # Arguments
+    ///
+    /// * out - the vector to hold the result. Must be large enough to contain output_bits().
+    fn result(&mut self, out: &mut [u8]);
+
+    /// Reset the digest. This method must be called after result() and before supplying more
+    /// data.
+    fn reset(&mut self);
+
+    /// Get the output size in bits.
+    fn output_bits(&self) -> uint;
+
+    /// Get the size in bytes of the blocks the digest processes its input in.
+    fn block_size(&self) -> uint;
+
+    /// Convenience function that feeds a string into a digest.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` The string to feed into the digest
+    fn input_str(&mut self, input: &str) {
+        self.input(input.as_bytes());
+    }
+
+    /// Convenience function that retrieves the result of a digest as a
+    /// newly allocated vec of bytes.
+    fn result_bytes(&mut self) -> Vec<u8> {
+        let mut buf = Vec::from_elem((self.output_bits()+7)/8, 0u8);
+        self.result(buf.as_mut_slice());
+        buf
+    }
+
+    /// Convenience function that retrieves the result of a digest as a
+    /// ~str in hexadecimal format.
+    fn result_str(&mut self) -> ~str {
+        self.result_bytes().as_slice().to_hex()
+    }
+}
+
+// Writing to a digest feeds the written bytes to it as input, which never
+// fails.
+macro_rules! impl_writer(
+    ($name:ident) => (
+        impl ::std::io::Writer for $name {
+            fn write(&mut self, buf: &[u8]) -> ::std::io::IoResult<()> {
+                ::Digest::input(self, buf);
+                Ok(())
+            }
+        }
+    )
+)
+
+mod cryptoutil;
+
+pub mod hmac;
+pub mod md5;
+pub mod sha1;
+pub mod sha2;
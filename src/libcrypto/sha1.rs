@@ -0,0 +1,194 @@
+// Copyright 2012-2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+An implementation of the SHA-1 cryptographic hash algorithm (FIPS 180-4).
+
+Practical collision attacks against SHA-1 are known. It is provided for formats that require it,
+such as version 5 UUIDs; new designs should use one of the SHA-2 functions instead.
+
+# Example
+
+```rust
+use crypto::Digest;
+use crypto::sha1::Sha1;
+
+let mut hasher = Sha1::new();
+hasher.input_str("hello world");
+assert_eq!(hasher.result_str(), "2aae6c35c94fcfb415dbe95f408b9ce91ee846ed".to_owned());
+```
+*/
+
+use Digest;
+use cryptoutil::{write_u32_be, read_u32v_be, add_bytes_to_bits, FixedBuffer, FixedBuffer64,
+                 StandardPadding};
+
+static K0: u32 = 0x5A827999u32;
+static K1: u32 = 0x6ED9EBA1u32;
+static K2: u32 = 0x8F1BBCDCu32;
+static K3: u32 = 0xCA62C1D6u32;
+
+static H: [u32, ..5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+/// The SHA-1 hash algorithm
+pub struct Sha1 {
+    h: [u32, ..5],
+    length_bits: u64,
+    buffer: FixedBuffer64,
+    finished: bool,
+}
+
+fn circular_shift(bits: u32, word: u32) -> u32 {
+    return word << bits | word >> (32u32 - bits);
+}
+
+fn process_block(h: &mut [u32, ..5], block: &[u8]) {
+    let mut w = [0u32, ..80];
+    read_u32v_be(w.mut_slice(0, 16), block);
+    for t in range(16u, 80) {
+        w[t] = circular_shift(1, w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]);
+    }
+
+    let mut a = h[0];
+    let mut b = h[1];
+    let mut c = h[2];
+    let mut d = h[3];
+    let mut e = h[4];
+
+    for t in range(0u, 80) {
+        let (f, k) = match t / 20 {
+            0 => ((b & c) | (!b & d), K0),
+            1 => (b ^ c ^ d, K1),
+            2 => ((b & c) | (b & d) | (c & d), K2),
+            _ => (b ^ c ^ d, K3),
+        };
+        let temp = circular_shift(5, a) + f + e + w[t] + k;
+        e = d;
+        d = c;
+        c = circular_shift(30, b);
+        b = a;
+        a = temp;
+    }
+
+    h[0] += a;
+    h[1] += b;
+    h[2] += c;
+    h[3] += d;
+    h[4] += e;
+}
+
+impl Sha1 {
+    /// Construct a new instance of a SHA-1 digest.
+    pub fn new() -> Sha1 {
+        Sha1 {
+            h: H,
+            length_bits: 0,
+            buffer: FixedBuffer64::new(),
+            finished: false,
+        }
+    }
+}
+
+impl Digest for Sha1 {
+    fn input(&mut self, input: &[u8]) {
+        assert!(!self.finished);
+        // Assumes that input.len() can be converted to u64 without overflow
+        self.length_bits = add_bytes_to_bits(self.length_bits, input.len() as u64);
+        let h = &mut self.h;
+        self.buffer.input(input, |d: &[u8]| { process_block(h, d); });
+    }
+
+    fn result(&mut self, out: &mut [u8]) {
+        if !self.finished {
+            let h = &mut self.h;
+            self.buffer.standard_padding(8, |d: &[u8]| { process_block(h, d); });
+            write_u32_be(self.buffer.next(4), (self.length_bits >> 32) as u32);
+            write_u32_be(self.buffer.next(4), self.length_bits as u32);
+            process_block(h, self.buffer.full_buffer());
+            self.finished = true;
+        }
+
+        write_u32_be(out.mut_slice(0, 4), self.h[0]);
+        write_u32_be(out.mut_slice(4, 8), self.h[1]);
+        write_u32_be(out.mut_slice(8, 12), self.h[2]);
+        write_u32_be(out.mut_slice(12, 16), self.h[3]);
+        write_u32_be(out.mut_slice(16, 20), self.h[4]);
+    }
+
+    fn reset(&mut self) {
+        self.h = H;
+        self.length_bits = 0;
+        self.buffer.reset();
+        self.finished = false;
+    }
+
+    fn output_bits(&self) -> uint { 160 }
+
+    fn block_size(&self) -> uint { 64 }
+}
+
+impl_writer!(Sha1)
+
+#[cfg(test)]
+mod tests {
+    use super::Sha1;
+    use Digest;
+    use cryptoutil::test::{Test, test_hash, test_digest_1million_random};
+
+    #[test]
+    fn test_sha1() {
+        // Examples from FIPS 180-1 and wikipedia
+        let tests = [
+            Test {
+                input: "",
+                output_str: "da39a3ee5e6b4b0d3255bfef95601890afd80709"
+            },
+            Test {
+                input: "abc",
+                output_str: "a9993e364706816aba3e25717850c26c9cd0d89d"
+            },
+            Test {
+                input: "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                output_str: "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+            },
+            Test {
+                input: "abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnop\
+                        jklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
+                output_str: "a49b2446a02c645bf419f995b67091253a04a259"
+            },
+            Test {
+                input: "The quick brown fox jumps over the lazy dog",
+                output_str: "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12"
+            },
+            Test {
+                input: "The quick brown fox jumps over the lazy cog",
+                output_str: "de9f2c7fd25e1b3afad3e85a0bd17d9b100db4b3"
+            },
+        ];
+
+        test_hash(&mut Sha1::new(), tests.as_slice());
+    }
+
+    #[test]
+    fn test_writer() {
+        let mut sh = Sha1::new();
+        write!(&mut sh, "The quick brown {} jumps over the lazy {}", "fox", "dog").unwrap();
+        assert_eq!(sh.result_str().as_slice(), "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12");
+    }
+
+    #[test]
+    fn test_1million_random_sha1() {
+        let mut sh = Sha1::new();
+        test_digest_1million_random(
+            &mut sh,
+            64,
+            "34aa973cd4c4daa4f61eeb2bdbad27316534016f");
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+
+The checksums used by the zlib and gzip formats
+
+Both checksums can be computed over a whole buffer at once, with `crc32` and
+`adler32`, or updated a piece at a time, with `Crc32` and `Adler32`.
+
+*/
+
+use libc::{c_ulong, size_t};
+
+use rustrt;
+
+/// A running CRC-32, as used by gzip and zip files.
+#[deriving(Clone)]
+pub struct Crc32 {
+    sum: u32,
+}
+
+impl Crc32 {
+    /// Create a CRC-32 of no bytes.
+    pub fn new() -> Crc32 {
+        Crc32 { sum: 0 }
+    }
+
+    /// Add `bytes` to the data being summed.
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.sum = unsafe {
+            rustrt::mz_crc32(self.sum as c_ulong, bytes.as_ptr(), bytes.len() as size_t) as u32
+        };
+    }
+
+    /// Return the CRC-32 of the bytes given so far.
+    pub fn sum(&self) -> u32 {
+        self.sum
+    }
+
+    /// Start again from no bytes.
+    pub fn reset(&mut self) {
+        self.sum = 0;
+    }
+}
+
+/// A running Adler-32, as used by zlib streams.
+#[deriving(Clone)]
+pub struct Adler32 {
+    sum: u32,
+}
+
+impl Adler32 {
+    /// Create an Adler-32 of no bytes.
+    pub fn new() -> Adler32 {
+        Adler32 { sum: 1 }
+    }
+
+    /// Add `bytes` to the data being summed.
+    pub fn update(&mut self, bytes: &[u8]) {
+        self.sum = unsafe {
+            rustrt::mz_adler32(self.sum as c_ulong, bytes.as_ptr(), bytes.len() as size_t) as u32
+        };
+    }
+
+    /// Return the Adler-32 of the bytes given so far.
+    pub fn sum(&self) -> u32 {
+        self.sum
+    }
+
+    /// Start again from no bytes.
+    pub fn reset(&mut self) {
+        self.sum = 1;
+    }
+}
+
+/// Compute the CRC-32 of `bytes`.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(bytes);
+    crc.sum()
+}
+
+/// Compute the Adler-32 of `bytes`.
+pub fn adler32(bytes: &[u8]) -> u32 {
+    let mut adler = Adler32::new();
+    adler.update(bytes);
+    adler.sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Crc32, Adler32, crc32, adler32};
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32([]), 0);
+        assert_eq!(crc32(bytes!("123456789")), 0xcbf43926);
+
+        let mut crc = Crc32::new();
+        crc.update(bytes!("1234"));
+        crc.update(bytes!(""));
+        crc.update(bytes!("56789"));
+        assert_eq!(crc.sum(), 0xcbf43926);
+        crc.reset();
+        crc.update(bytes!("hello world\n"));
+        assert_eq!(crc.sum(), 0xaf083b2d);
+    }
+
+    #[test]
+    fn test_adler32() {
+        assert_eq!(adler32([]), 1);
+        assert_eq!(adler32(bytes!("Wikipedia")), 0x11e60398);
+
+        let mut adler = Adler32::new();
+        adler.update(bytes!("Wiki"));
+        adler.update(bytes!("pedia"));
+        assert_eq!(adler.sum(), 0x11e60398);
+        adler.reset();
+        assert_eq!(adler.sum(), 1);
+    }
+}
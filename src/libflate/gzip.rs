@@ -0,0 +1,439 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Streaming encoder and decoder for gzip files (RFC 1952).
+
+use std::io::{IoResult, IoError, EndOfFile, InvalidInput, standard_error};
+
+use {Compression, Fast, Best, Level, compression_flags};
+use checksum::Crc32;
+use stream::{Encoder, Inflater};
+
+static FHCRC: u8 = 1 << 1;
+static FEXTRA: u8 = 1 << 2;
+static FNAME: u8 = 1 << 3;
+static FCOMMENT: u8 = 1 << 4;
+static FRESERVED: u8 = 0xe0;
+
+/// The metadata at the start of a gzip file.
+#[deriving(Clone, Eq, Show)]
+pub struct GzHeader {
+    /// The name of the original file, without a directory, in ISO 8859-1
+    pub filename: Option<Vec<u8>>,
+    /// A comment in ISO 8859-1, whose lines end with `\n`
+    pub comment: Option<Vec<u8>>,
+    /// Application specific data, at most 65535 bytes
+    pub extra: Option<Vec<u8>>,
+    /// When the original file was modified, in seconds since the Unix epoch,
+    /// or 0 if that isn't known
+    pub mtime: u32,
+    /// The operating system the file was written on, or 255 if that isn't
+    /// known
+    pub os: u8,
+}
+
+impl GzHeader {
+    /// Create a header without a file name, comment, extra data or
+    /// modification time.
+    pub fn new() -> GzHeader {
+        GzHeader {
+            filename: None,
+            comment: None,
+            extra: None,
+            mtime: 0,
+            os: 255,
+        }
+    }
+}
+
+fn invalid(desc: &'static str) -> IoError {
+    IoError { kind: InvalidInput, desc: desc, detail: None }
+}
+
+// Encode `header` for a file compressed at `level`.
+fn header_bytes(header: &GzHeader, level: Compression) -> Vec<u8> {
+    let mut flags = 0;
+    let mut rest = Vec::new();
+    match header.extra {
+        Some(ref extra) => {
+            if extra.len() > 0xffff {
+                fail!("gzip extra field is longer than 65535 bytes");
+            }
+            flags |= FEXTRA;
+            rest.push(extra.len() as u8);
+            rest.push((extra.len() >> 8) as u8);
+            rest.push_all(extra.as_slice());
+        }
+        None => {}
+    }
+    for &(field, flag) in [(&header.filename, FNAME), (&header.comment, FCOMMENT)].iter() {
+        match *field {
+            Some(ref s) => {
+                if s.as_slice().contains(&0) {
+                    fail!("gzip file name or comment contains a NUL byte");
+                }
+                flags |= flag;
+                rest.push_all(s.as_slice());
+                rest.push(0);
+            }
+            None => {}
+        }
+    }
+
+    // Extra flags: 2 for the best compression, 4 for the fastest
+    let xfl = match level {
+        Best => 2,
+        Level(n) if n >= 9 => 2,
+        Fast => 4,
+        Level(n) if n < 2 => 4,
+        _ => 0,
+    };
+    let mtime = header.mtime;
+    let mut bytes = vec!(0x1f, 0x8b, 8, flags,
+                         mtime as u8, (mtime >> 8) as u8, (mtime >> 16) as u8, (mtime >> 24) as u8,
+                         xfl, header.os);
+    bytes.push_all_move(rest);
+    bytes
+}
+
+/// A `Writer` which compresses what is written to it into a gzip file.
+///
+/// `finish` must be called once all the data has been written, to write the
+/// end of the file.
+pub struct GzEncoder<W> {
+    inner: Encoder<W>,
+    // The header, until it has been written
+    header: Option<Vec<u8>>,
+    crc: Crc32,
+    size: u32,
+}
+
+impl<W: Writer> GzEncoder<W> {
+    /// Create an encoder writing a gzip file with an empty header to `w`.
+    pub fn new(w: W, level: Compression) -> GzEncoder<W> {
+        GzEncoder::with_header(w, level, GzHeader::new())
+    }
+
+    /// Create an encoder writing a gzip file with the given header to `w`.
+    ///
+    /// # Failure
+    ///
+    /// Fails if the extra field is too long, or the file name or comment
+    /// contains a NUL byte.
+    pub fn with_header(w: W, level: Compression, header: GzHeader) -> GzEncoder<W> {
+        GzEncoder {
+            header: Some(header_bytes(&header, level)),
+            inner: Encoder::new(w, compression_flags(level)),
+            crc: Crc32::new(),
+            size: 0,
+        }
+    }
+
+    fn write_header(&mut self) -> IoResult<()> {
+        match self.header.take() {
+            Some(header) => self.inner.inner.write(header.as_slice()),
+            None => Ok(()),
+        }
+    }
+
+    /// End the compressed data and write the file's checksum and length,
+    /// returning the underlying writer.
+    pub fn finish(mut self) -> IoResult<W> {
+        try!(self.write_header());
+        let crc = self.crc.sum();
+        let size = self.size;
+        let mut w = try!(self.inner.finish());
+        try!(w.write_le_u32(crc));
+        try!(w.write_le_u32(size));
+        Ok(w)
+    }
+}
+
+impl<W: Writer> Writer for GzEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> IoResult<()> {
+        try!(self.write_header());
+        self.crc.update(buf);
+        // the length is stored modulo 2^32
+        self.size += buf.len() as u32;
+        self.inner.write(buf)
+    }
+
+    /// Write out everything compressed so far, so that it can be decoded
+    /// without waiting for the rest of the file. Flushing often makes the
+    /// compression worse.
+    fn flush(&mut self) -> IoResult<()> {
+        try!(self.write_header());
+        self.inner.flush()
+    }
+}
+
+/// A `Reader` which decompresses a gzip file read from another `Reader`,
+/// checking its checksum and length.
+///
+/// A gzip file may consist of several compressed members one after another,
+/// which are decompressed as if they were one.
+pub struct GzDecoder<R> {
+    inner: Inflater<R>,
+    header: GzHeader,
+    crc: Crc32,
+    size: u32,
+    done: bool,
+}
+
+// Read a byte, failing at the end of the input.
+fn read_byte<R: Reader>(r: &mut Inflater<R>) -> IoResult<u8> {
+    match r.read_byte() {
+        Err(ref e) if e.kind == EndOfFile => Err(invalid("truncated gzip file")),
+        result => result,
+    }
+}
+
+fn read_le_u32<R: Reader>(r: &mut Inflater<R>) -> IoResult<u32> {
+    let mut n = 0;
+    for i in range(0u, 4) {
+        n |= (try!(read_byte(r)) as u32) << (8 * i);
+    }
+    Ok(n)
+}
+
+// Read a byte of the header, adding it to the header's checksum.
+fn next<R: Reader>(r: &mut Inflater<R>, crc: &mut Crc32) -> IoResult<u8> {
+    let b = try!(read_byte(r));
+    crc.update([b]);
+    Ok(b)
+}
+
+// Read a NUL terminated string of the header.
+fn read_string<R: Reader>(r: &mut Inflater<R>, crc: &mut Crc32) -> IoResult<Vec<u8>> {
+    let mut s = Vec::new();
+    loop {
+        match try!(next(r, crc)) {
+            0 => return Ok(s),
+            b => s.push(b),
+        }
+    }
+}
+
+fn read_header<R: Reader>(r: &mut Inflater<R>) -> IoResult<GzHeader> {
+    let mut crc = Crc32::new();
+    let mut fixed = [0u8, ..10];
+    for b in fixed.mut_iter() {
+        *b = try!(next(r, &mut crc));
+    }
+    if fixed[0] != 0x1f || fixed[1] != 0x8b {
+        return Err(invalid("not a gzip file"));
+    }
+    if fixed[2] != 8 {
+        return Err(invalid("unsupported gzip compression method"));
+    }
+    let flags = fixed[3];
+    if flags & FRESERVED != 0 {
+        return Err(invalid("unsupported gzip header flags"));
+    }
+
+    let mut header = GzHeader::new();
+    header.mtime = fixed[4] as u32 | fixed[5] as u32 << 8 |
+                   fixed[6] as u32 << 16 | fixed[7] as u32 << 24;
+    header.os = fixed[9];
+    if flags & FEXTRA != 0 {
+        let len = try!(next(r, &mut crc)) as uint | try!(next(r, &mut crc)) as uint << 8;
+        let mut extra = Vec::with_capacity(len);
+        for _ in range(0, len) {
+            extra.push(try!(next(r, &mut crc)));
+        }
+        header.extra = Some(extra);
+    }
+    if flags & FNAME != 0 {
+        header.filename = Some(try!(read_string(r, &mut crc)));
+    }
+    if flags & FCOMMENT != 0 {
+        header.comment = Some(try!(read_string(r, &mut crc)));
+    }
+    if flags & FHCRC != 0 {
+        let expected = crc.sum() & 0xffff;
+        let found = try!(read_byte(r)) as u32 | try!(read_byte(r)) as u32 << 8;
+        if found != expected {
+            return Err(invalid("gzip header checksum mismatch"));
+        }
+    }
+    Ok(header)
+}
+
+impl<R: Reader> GzDecoder<R> {
+    /// Create a decoder reading a gzip file from `r`, reading its header.
+    pub fn new(r: R) -> IoResult<GzDecoder<R>> {
+        let mut inner = Inflater::new(r, 0);
+        let header = try!(read_header(&mut inner));
+        Ok(GzDecoder {
+            inner: inner,
+            header: header,
+            crc: Crc32::new(),
+            size: 0,
+            done: false,
+        })
+    }
+
+    /// Return the header of the file, or of its first member if it has
+    /// several.
+    pub fn header<'a>(&'a self) -> &'a GzHeader {
+        &self.header
+    }
+
+    /// Return the underlying reader.
+    pub fn unwrap(self) -> R {
+        self.inner.unwrap()
+    }
+
+    // Check the trailer of the member just decompressed, and start on the
+    // next one if there is one.
+    fn next_member(&mut self) -> IoResult<()> {
+        let crc = try!(read_le_u32(&mut self.inner));
+        let size = try!(read_le_u32(&mut self.inner));
+        if crc != self.crc.sum() {
+            return Err(invalid("gzip checksum mismatch"));
+        }
+        if size != self.size {
+            return Err(invalid("gzip length mismatch"));
+        }
+        if try!(self.inner.is_eof()) {
+            self.done = true;
+            return Ok(());
+        }
+        try!(read_header(&mut self.inner));
+        self.inner.reset();
+        self.crc.reset();
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl<R: Reader> Reader for GzDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> IoResult<uint> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        while !self.done {
+            let n = try!(self.inner.inflate(buf));
+            if n > 0 {
+                self.crc.update(buf.slice_to(n));
+                self.size += n as u32;
+                return Ok(n);
+            }
+            try!(self.next_member());
+        }
+        Err(standard_error(EndOfFile))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{MemReader, MemWriter, InvalidInput};
+    use super::{GzEncoder, GzDecoder, GzHeader};
+    use {Default, Best};
+
+    // `gzip.compress(b"hello world\n")` in Python, with the file name
+    // "hello.txt" and modification time 1234567890
+    static HELLO: &'static [u8] = &[
+        0x1f, 0x8b, 0x08, 0x08, 0xd2, 0x02, 0x96, 0x49, 0x02, 0xff, 0x68, 0x65,
+        0x6c, 0x6c, 0x6f, 0x2e, 0x74, 0x78, 0x74, 0x00, 0xcb, 0x48, 0xcd, 0xc9,
+        0xc9, 0x57, 0x28, 0xcf, 0x2f, 0xca, 0x49, 0xe1, 0x02, 0x00, 0x2d, 0x3b,
+        0x08, 0xaf, 0x0c, 0x00, 0x00, 0x00,
+    ];
+
+    fn hello_header() -> GzHeader {
+        let mut header = GzHeader::new();
+        header.filename = Some(Vec::from_slice(bytes!("hello.txt")));
+        header.mtime = 1234567890;
+        header
+    }
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(MemWriter::new(), Default);
+        encoder.write(data).unwrap();
+        encoder.finish().unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_decode_golden() {
+        let mut decoder = GzDecoder::new(MemReader::new(Vec::from_slice(HELLO))).unwrap();
+        assert_eq!(*decoder.header(), hello_header());
+        assert_eq!(decoder.read_to_end().unwrap().as_slice(), bytes!("hello world\n"));
+    }
+
+    #[test]
+    fn test_encode_header() {
+        let mut encoder = GzEncoder::with_header(MemWriter::new(), Best, hello_header());
+        encoder.write(bytes!("hello ")).unwrap();
+        encoder.flush().unwrap();
+        encoder.write(bytes!("world\n")).unwrap();
+        let compressed = encoder.finish().unwrap().unwrap();
+        assert_eq!(compressed.slice_to(20), HELLO.slice_to(20));
+        assert_eq!(compressed.slice_from(compressed.len() - 8), HELLO.slice_from(HELLO.len() - 8));
+
+        let mut header = hello_header();
+        header.comment = Some(Vec::from_slice(bytes!("a comment")));
+        header.extra = Some(vec!(1, 2, 3));
+        header.os = 3;
+        let mut encoder = GzEncoder::with_header(MemWriter::new(), Default, header.clone());
+        encoder.write(bytes!("hello world\n")).unwrap();
+        let compressed = encoder.finish().unwrap().unwrap();
+        let mut decoder = GzDecoder::new(MemReader::new(compressed)).unwrap();
+        assert_eq!(*decoder.header(), header);
+        assert_eq!(decoder.read_to_end().unwrap().as_slice(), bytes!("hello world\n"));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut data = Vec::new();
+        for i in range(0u, 10000) {
+            data.push_all(format!("{} bottles of beer\n", i % 99).as_bytes());
+        }
+        let compressed = gzip(data.as_slice());
+        assert!(compressed.len() < data.len() / 10);
+        let mut decoder = GzDecoder::new(MemReader::new(compressed)).unwrap();
+        assert!(decoder.read_to_end().unwrap() == data);
+
+        let mut decoder = GzDecoder::new(MemReader::new(gzip([]))).unwrap();
+        assert_eq!(decoder.read_to_end().unwrap(), vec!());
+    }
+
+    #[test]
+    fn test_multiple_members() {
+        let mut compressed = gzip(bytes!("hello "));
+        compressed.push_all(gzip([]).as_slice());
+        compressed.push_all(HELLO);
+        let mut decoder = GzDecoder::new(MemReader::new(compressed)).unwrap();
+        assert_eq!(*decoder.header(), GzHeader::new());
+        assert_eq!(decoder.read_to_end().unwrap().as_slice(), bytes!("hello hello world\n"));
+    }
+
+    #[test]
+    fn test_corrupt() {
+        let mut bad_crc = Vec::from_slice(HELLO);
+        *bad_crc.get_mut(HELLO.len() - 8) ^= 1;
+        let mut decoder = GzDecoder::new(MemReader::new(bad_crc)).unwrap();
+        let err = decoder.read_to_end().unwrap_err();
+        assert_eq!(err.kind, InvalidInput);
+        assert_eq!(err.desc, "gzip checksum mismatch");
+
+        let mut bad_size = Vec::from_slice(HELLO);
+        *bad_size.get_mut(HELLO.len() - 4) = 13;
+        let mut decoder = GzDecoder::new(MemReader::new(bad_size)).unwrap();
+        assert_eq!(decoder.read_to_end().unwrap_err().desc, "gzip length mismatch");
+
+        let truncated = Vec::from_slice(HELLO.slice_to(HELLO.len() - 3));
+        let mut decoder = GzDecoder::new(MemReader::new(truncated)).unwrap();
+        assert_eq!(decoder.read_to_end().unwrap_err().desc, "truncated gzip file");
+
+        let err = GzDecoder::new(MemReader::new(Vec::from_slice(bytes!("hello")))).err().unwrap();
+        assert_eq!(err.desc, "not a gzip file");
+        assert!(GzDecoder::new(MemReader::new(Vec::from_slice(HELLO.slice_to(15)))).is_err());
+    }
+}
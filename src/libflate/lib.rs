@@ -12,6 +12,29 @@
 
 Simple compression
 
+The `deflate_bytes` and `inflate_bytes` functions compress and decompress
+whole buffers in memory. For data that doesn't fit in memory, or arrives a
+piece at a time, the encoders in this crate are `Writer`s which compress what
+is written to them into another `Writer`, and the decoders are `Reader`s which
+decompress what they read from another `Reader`. They come in three flavours:
+raw deflate streams (RFC 1951), zlib streams (RFC 1950) and gzip files (RFC
+1952).
+
+# Example
+
+```rust
+use flate::{GzEncoder, GzDecoder, Default};
+use std::io::{MemReader, MemWriter};
+
+let mut encoder = GzEncoder::new(MemWriter::new(), Default);
+encoder.write(bytes!("hello, ")).unwrap();
+encoder.write(bytes!("world")).unwrap();
+let compressed = encoder.finish().unwrap().unwrap();
+
+let mut decoder = GzDecoder::new(MemReader::new(compressed)).unwrap();
+assert_eq!(decoder.read_to_end().unwrap().as_slice(), bytes!("hello, world"));
+```
+
 */
 
 #![crate_id = "flate#0.11-pre"]
@@ -29,13 +52,49 @@ Simple compression
 extern crate libc;
 
 use std::c_vec::CVec;
+use std::cmp;
 use libc::{c_void, size_t, c_int};
 
+pub use stream::{DeflateEncoder, DeflateDecoder, ZlibEncoder, ZlibDecoder};
+pub use gzip::{GzEncoder, GzDecoder, GzHeader};
+
+pub mod checksum;
+mod gzip;
+mod stream;
 
 pub mod rustrt {
-    use libc::{c_void, size_t, c_int};
+    use libc::{c_void, size_t, c_int, c_ulong};
     #[link(name = "miniz", kind = "static")]
     extern {
+        pub fn tdefl_compressor_alloc() -> *mut c_void;
+        pub fn tdefl_compressor_free(d: *mut c_void);
+        pub fn tdefl_init(d: *mut c_void,
+                          put_buf_func: *c_void,
+                          put_buf_user: *mut c_void,
+                          flags: c_int)
+                          -> c_int;
+        pub fn tdefl_compress(d: *mut c_void,
+                              in_buf: *c_void,
+                              in_buf_size: *mut size_t,
+                              out_buf: *mut c_void,
+                              out_buf_size: *mut size_t,
+                              flush: c_int)
+                              -> c_int;
+
+        pub fn tinfl_decompressor_alloc() -> *mut c_void;
+        pub fn tinfl_decompressor_free(r: *mut c_void);
+        pub fn tinfl_decompress(r: *mut c_void,
+                                in_buf_next: *u8,
+                                in_buf_size: *mut size_t,
+                                out_buf_start: *mut u8,
+                                out_buf_next: *mut u8,
+                                out_buf_size: *mut size_t,
+                                flags: u32)
+                                -> c_int;
+
+        pub fn mz_crc32(crc: c_ulong, ptr: *u8, buf_len: size_t) -> c_ulong;
+        pub fn mz_adler32(adler: c_ulong, ptr: *u8, buf_len: size_t) -> c_ulong;
+
         pub fn tdefl_compress_mem_to_heap(psrc_buf: *c_void,
                                           src_buf_len: size_t,
                                           pout_len: *mut size_t,
@@ -53,6 +112,44 @@ pub mod rustrt {
 static LZ_NORM : c_int = 0x80;  // LZ with 128 probes, "normal"
 static TINFL_FLAG_PARSE_ZLIB_HEADER : c_int = 0x1; // parse zlib header and adler32 checksum
 static TDEFL_WRITE_ZLIB_HEADER : c_int = 0x01000; // write zlib header and adler32 checksum
+static TDEFL_GREEDY_PARSING_FLAG : c_int = 0x04000; // faster, but compresses less
+static TDEFL_FORCE_ALL_RAW_BLOCKS : c_int = 0x80000; // store the data without compressing it
+
+/// How hard an encoder works to make its output small, at the expense of
+/// speed.
+#[deriving(Clone, Eq, Show)]
+pub enum Compression {
+    /// Compress quickly, same as `Level(1)`
+    Fast,
+    /// The default, same as `Level(6)`
+    Default,
+    /// Compress as well as possible, same as `Level(9)`
+    Best,
+    /// A level from 0, which stores the data without compressing it, to 10,
+    /// which is slower than 9 for a little gain. Levels above 10 are treated
+    /// as 10.
+    Level(uint),
+}
+
+// The compressor flags for a compression level, as chosen by zlib's
+// levels: the number of probes per dictionary search and the parsing.
+fn compression_flags(level: Compression) -> c_int {
+    static NUM_PROBES: [c_int, ..11] = [0, 1, 6, 32, 16, 32, 128, 256, 512, 768, 1500];
+    let level = match level {
+        Fast => 1,
+        Default => 6,
+        Best => 9,
+        Level(n) => cmp::min(n, 10),
+    };
+    let mut flags = NUM_PROBES[level];
+    if level <= 3 {
+        flags |= TDEFL_GREEDY_PARSING_FLAG;
+    }
+    if level == 0 {
+        flags |= TDEFL_FORCE_ALL_RAW_BLOCKS;
+    }
+    flags
+}
 
 fn deflate_bytes_internal(bytes: &[u8], flags: c_int) -> Option<CVec<u8>> {
     unsafe {
@@ -0,0 +1,581 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Streaming deflate and zlib encoders and decoders.
+
+use std::cmp;
+use std::io::{IoResult, IoError, EndOfFile, InvalidInput, OtherIoError, standard_error};
+use std::ptr;
+use std::slice::bytes::copy_memory;
+use libc::{c_void, c_int, size_t};
+
+use {rustrt, Compression, compression_flags};
+use {TDEFL_WRITE_ZLIB_HEADER, TINFL_FLAG_PARSE_ZLIB_HEADER};
+
+static BUF_SIZE: uint = 32 * 1024;
+// The size of the window the decompressor writes into, which must hold the
+// largest distance a deflate stream can refer back.
+static DICT_SIZE: uint = 32768;
+
+static TDEFL_STATUS_DONE: c_int = 1;
+static TDEFL_NO_FLUSH: c_int = 0;
+static TDEFL_SYNC_FLUSH: c_int = 2;
+static TDEFL_FINISH: c_int = 4;
+
+static TINFL_FLAG_HAS_MORE_INPUT: u32 = 2;
+static TINFL_STATUS_ADLER32_MISMATCH: c_int = -2;
+static TINFL_STATUS_DONE: c_int = 0;
+static TINFL_STATUS_NEEDS_MORE_INPUT: c_int = 1;
+static TINFL_STATUS_HAS_MORE_OUTPUT: c_int = 2;
+
+// Owns a compressor allocated by miniz.
+struct RawDeflater {
+    raw: *mut c_void,
+}
+
+impl RawDeflater {
+    fn new(flags: c_int) -> RawDeflater {
+        unsafe {
+            let raw = rustrt::tdefl_compressor_alloc();
+            if raw.is_null() {
+                fail!("failed to allocate a deflate compressor");
+            }
+            rustrt::tdefl_init(raw, ptr::null(), ptr::mut_null(), flags);
+            RawDeflater { raw: raw }
+        }
+    }
+}
+
+impl Drop for RawDeflater {
+    fn drop(&mut self) {
+        unsafe { rustrt::tdefl_compressor_free(self.raw) }
+    }
+}
+
+// Owns a decompressor allocated by miniz.
+struct RawInflater {
+    raw: *mut c_void,
+}
+
+impl RawInflater {
+    fn new() -> RawInflater {
+        let raw = unsafe { rustrt::tinfl_decompressor_alloc() };
+        if raw.is_null() {
+            fail!("failed to allocate a deflate decompressor");
+        }
+        RawInflater { raw: raw }
+    }
+}
+
+impl Drop for RawInflater {
+    fn drop(&mut self) {
+        unsafe { rustrt::tinfl_decompressor_free(self.raw) }
+    }
+}
+
+/// Compresses what is written to it into `inner`; shared by all the encoders.
+pub struct Encoder<W> {
+    pub inner: W,
+    raw: RawDeflater,
+    buf: Vec<u8>,
+}
+
+impl<W: Writer> Encoder<W> {
+    pub fn new(w: W, flags: c_int) -> Encoder<W> {
+        Encoder {
+            inner: w,
+            raw: RawDeflater::new(flags),
+            buf: Vec::from_elem(BUF_SIZE, 0u8),
+        }
+    }
+
+    fn compress(&mut self, mut input: &[u8], flush: c_int) -> IoResult<()> {
+        loop {
+            let mut in_size = input.len() as size_t;
+            let mut out_size = self.buf.len() as size_t;
+            let status = unsafe {
+                rustrt::tdefl_compress(self.raw.raw,
+                                       input.as_ptr() as *c_void,
+                                       &mut in_size,
+                                       self.buf.as_mut_ptr() as *mut c_void,
+                                       &mut out_size,
+                                       flush)
+            };
+            if status < 0 {
+                return Err(IoError {
+                    kind: OtherIoError,
+                    desc: "deflate compression failed",
+                    detail: None,
+                });
+            }
+            input = input.slice_from(in_size as uint);
+            try!(self.inner.write(self.buf.slice_to(out_size as uint)));
+
+            // When flushing, a full output buffer means there may be more
+            if status == TDEFL_STATUS_DONE ||
+               (input.is_empty() && flush != TDEFL_FINISH &&
+                (out_size as uint) < self.buf.len()) {
+                return Ok(());
+            }
+        }
+    }
+
+    pub fn write(&mut self, buf: &[u8]) -> IoResult<()> {
+        self.compress(buf, TDEFL_NO_FLUSH)
+    }
+
+    pub fn flush(&mut self) -> IoResult<()> {
+        try!(self.compress([], TDEFL_SYNC_FLUSH));
+        self.inner.flush()
+    }
+
+    pub fn finish(mut self) -> IoResult<W> {
+        try!(self.compress([], TDEFL_FINISH));
+        Ok(self.inner)
+    }
+}
+
+/// Decompresses what is read from `inner`; shared by all the decoders.
+///
+/// Input is read from `inner` in blocks, so it will usually have been read
+/// past the end of the compressed stream. The bytes following the stream can
+/// be read with `read_byte`.
+pub struct Inflater<R> {
+    inner: R,
+    raw: RawInflater,
+    flags: u32,
+    // Input read from `inner`, of which `input[pos..cap]` is unused
+    input: Vec<u8>,
+    pos: uint,
+    cap: uint,
+    eof: bool,
+    // The window the decompressor writes into; `dict[out_pos..out_pos+out_len]`
+    // has yet to be returned, and `dict_pos` is where the next output goes.
+    dict: Vec<u8>,
+    dict_pos: uint,
+    out_pos: uint,
+    out_len: uint,
+    done: bool,
+}
+
+impl<R: Reader> Inflater<R> {
+    pub fn new(r: R, flags: u32) -> Inflater<R> {
+        Inflater {
+            inner: r,
+            raw: RawInflater::new(),
+            flags: flags,
+            input: Vec::from_elem(BUF_SIZE, 0u8),
+            pos: 0,
+            cap: 0,
+            eof: false,
+            dict: Vec::from_elem(DICT_SIZE, 0u8),
+            dict_pos: 0,
+            out_pos: 0,
+            out_len: 0,
+            done: false,
+        }
+    }
+
+    fn fill_buf(&mut self) -> IoResult<()> {
+        self.pos = 0;
+        self.cap = 0;
+        match self.inner.read(self.input.as_mut_slice()) {
+            Ok(n) => self.cap = n,
+            Err(ref e) if e.kind == EndOfFile => self.eof = true,
+            Err(e) => return Err(e),
+        }
+        Ok(())
+    }
+
+    /// Read the next byte of input which the decompressor hasn't consumed.
+    pub fn read_byte(&mut self) -> IoResult<u8> {
+        while self.pos == self.cap {
+            if self.eof {
+                return Err(standard_error(EndOfFile));
+            }
+            try!(self.fill_buf());
+        }
+        let b = *self.input.get(self.pos);
+        self.pos += 1;
+        Ok(b)
+    }
+
+    /// Return whether all of the input has been consumed.
+    pub fn is_eof(&mut self) -> IoResult<bool> {
+        while self.pos == self.cap {
+            if self.eof {
+                return Ok(true);
+            }
+            try!(self.fill_buf());
+        }
+        Ok(false)
+    }
+
+    /// Return whether the end of the compressed stream has been reached and
+    /// all of its output returned.
+    pub fn is_done(&self) -> bool {
+        self.done && self.out_len == 0
+    }
+
+    /// Start decompressing a new stream from the rest of the input.
+    pub fn reset(&mut self) {
+        self.raw = RawInflater::new();
+        self.dict_pos = 0;
+        self.out_pos = 0;
+        self.out_len = 0;
+        self.done = false;
+    }
+
+    /// Decompress into `buf`, returning how many bytes were read, or 0 at the
+    /// end of the stream.
+    pub fn inflate(&mut self, buf: &mut [u8]) -> IoResult<uint> {
+        loop {
+            if self.out_len > 0 {
+                let n = cmp::min(buf.len(), self.out_len);
+                copy_memory(buf, self.dict.slice(self.out_pos, self.out_pos + n));
+                self.out_pos += n;
+                self.out_len -= n;
+                return Ok(n);
+            }
+            if self.done {
+                return Ok(0);
+            }
+            if self.pos == self.cap && !self.eof {
+                try!(self.fill_buf());
+            }
+
+            // Without this flag miniz pads the input with zeros once it runs
+            // out, which decode as valid data forever instead of failing, so
+            // the end of a truncated stream is detected below.
+            let flags = self.flags | TINFL_FLAG_HAS_MORE_INPUT;
+            let mut in_size = (self.cap - self.pos) as size_t;
+            let mut out_size = (DICT_SIZE - self.dict_pos) as size_t;
+            let status = unsafe {
+                let dict = self.dict.as_mut_ptr();
+                rustrt::tinfl_decompress(self.raw.raw,
+                                         self.input.as_ptr().offset(self.pos as int),
+                                         &mut in_size,
+                                         dict,
+                                         dict.offset(self.dict_pos as int),
+                                         &mut out_size,
+                                         flags)
+            };
+            self.pos += in_size as uint;
+            self.out_pos = self.dict_pos;
+            self.out_len = out_size as uint;
+            self.dict_pos = (self.dict_pos + self.out_len) & (DICT_SIZE - 1);
+
+            match status {
+                TINFL_STATUS_DONE => self.done = true,
+                TINFL_STATUS_NEEDS_MORE_INPUT
+                        if self.eof && self.pos == self.cap && self.out_len == 0 => {
+                    return Err(IoError {
+                        kind: InvalidInput,
+                        desc: "invalid or truncated deflate stream",
+                        detail: None,
+                    })
+                }
+                TINFL_STATUS_NEEDS_MORE_INPUT | TINFL_STATUS_HAS_MORE_OUTPUT => {}
+                TINFL_STATUS_ADLER32_MISMATCH => {
+                    return Err(IoError {
+                        kind: InvalidInput,
+                        desc: "zlib checksum mismatch",
+                        detail: None,
+                    })
+                }
+                _ => {
+                    return Err(IoError {
+                        kind: InvalidInput,
+                        desc: "invalid or truncated deflate stream",
+                        detail: None,
+                    })
+                }
+            }
+        }
+    }
+
+    pub fn unwrap(self) -> R {
+        self.inner
+    }
+}
+
+// Read from an inflater with the semantics of `Reader::read`.
+fn read<R: Reader>(inflater: &mut Inflater<R>, buf: &mut [u8]) -> IoResult<uint> {
+    if buf.is_empty() {
+        return Ok(0);
+    }
+    match try!(inflater.inflate(buf)) {
+        0 => Err(standard_error(EndOfFile)),
+        n => Ok(n),
+    }
+}
+
+/// A `Writer` which compresses what is written to it into a raw deflate
+/// stream.
+///
+/// `finish` must be called once all the data has been written, to end the
+/// stream.
+pub struct DeflateEncoder<W> {
+    inner: Encoder<W>,
+}
+
+impl<W: Writer> DeflateEncoder<W> {
+    /// Create an encoder writing the compressed stream to `w`.
+    pub fn new(w: W, level: Compression) -> DeflateEncoder<W> {
+        DeflateEncoder { inner: Encoder::new(w, compression_flags(level)) }
+    }
+
+    /// End the compressed stream, returning the underlying writer.
+    pub fn finish(self) -> IoResult<W> {
+        self.inner.finish()
+    }
+}
+
+impl<W: Writer> Writer for DeflateEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> IoResult<()> {
+        self.inner.write(buf)
+    }
+
+    /// Write out everything compressed so far, so that it can be decoded
+    /// without waiting for the rest of the stream. Flushing often makes the
+    /// compression worse.
+    fn flush(&mut self) -> IoResult<()> {
+        self.inner.flush()
+    }
+}
+
+/// A `Writer` which compresses what is written to it into a zlib stream.
+///
+/// `finish` must be called once all the data has been written, to end the
+/// stream.
+pub struct ZlibEncoder<W> {
+    inner: Encoder<W>,
+}
+
+impl<W: Writer> ZlibEncoder<W> {
+    /// Create an encoder writing the compressed stream to `w`.
+    pub fn new(w: W, level: Compression) -> ZlibEncoder<W> {
+        let flags = compression_flags(level) | TDEFL_WRITE_ZLIB_HEADER;
+        ZlibEncoder { inner: Encoder::new(w, flags) }
+    }
+
+    /// End the compressed stream, returning the underlying writer.
+    pub fn finish(self) -> IoResult<W> {
+        self.inner.finish()
+    }
+}
+
+impl<W: Writer> Writer for ZlibEncoder<W> {
+    fn write(&mut self, buf: &[u8]) -> IoResult<()> {
+        self.inner.write(buf)
+    }
+
+    /// Write out everything compressed so far, so that it can be decoded
+    /// without waiting for the rest of the stream. Flushing often makes the
+    /// compression worse.
+    fn flush(&mut self) -> IoResult<()> {
+        self.inner.flush()
+    }
+}
+
+/// A `Reader` which decompresses a raw deflate stream read from another
+/// `Reader`.
+pub struct DeflateDecoder<R> {
+    inner: Inflater<R>,
+}
+
+impl<R: Reader> DeflateDecoder<R> {
+    /// Create a decoder reading the compressed stream from `r`.
+    pub fn new(r: R) -> DeflateDecoder<R> {
+        DeflateDecoder { inner: Inflater::new(r, 0) }
+    }
+
+    /// Return the underlying reader. Input is read in blocks, so some of
+    /// what follows the compressed stream may have been read from it.
+    pub fn unwrap(self) -> R {
+        self.inner.unwrap()
+    }
+}
+
+impl<R: Reader> Reader for DeflateDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> IoResult<uint> {
+        read(&mut self.inner, buf)
+    }
+}
+
+/// A `Reader` which decompresses a zlib stream read from another `Reader`,
+/// checking its checksum.
+pub struct ZlibDecoder<R> {
+    inner: Inflater<R>,
+}
+
+impl<R: Reader> ZlibDecoder<R> {
+    /// Create a decoder reading the compressed stream from `r`.
+    pub fn new(r: R) -> ZlibDecoder<R> {
+        ZlibDecoder { inner: Inflater::new(r, TINFL_FLAG_PARSE_ZLIB_HEADER as u32) }
+    }
+
+    /// Return the underlying reader. Input is read in blocks, so some of
+    /// what follows the compressed stream may have been read from it.
+    pub fn unwrap(self) -> R {
+        self.inner.unwrap()
+    }
+}
+
+impl<R: Reader> Reader for ZlibDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> IoResult<uint> {
+        read(&mut self.inner, buf)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{MemReader, MemWriter, IoResult, InvalidInput};
+    use super::{DeflateEncoder, DeflateDecoder, ZlibEncoder, ZlibDecoder};
+    use {Fast, Default, Best, Level, Compression};
+    use {deflate_bytes, deflate_bytes_zlib, inflate_bytes, inflate_bytes_zlib};
+
+    // Some compressible data larger than the window and the buffers.
+    fn sample() -> Vec<u8> {
+        let mut data = Vec::new();
+        for i in range(0u, 20000) {
+            data.push_all(format!("line {} of the log\n", i * 7 % 977).as_bytes());
+        }
+        data
+    }
+
+    // Write `data` in uneven pieces, flushing now and then.
+    fn write_chunks<W: Writer>(w: &mut W, data: &[u8]) {
+        let mut rest = data;
+        let mut i = 0u;
+        while !rest.is_empty() {
+            let n = ::std::cmp::min(rest.len(), 1 + i * 997 % 5000);
+            w.write(rest.slice_to(n)).unwrap();
+            if i % 10 == 0 {
+                w.flush().unwrap();
+            }
+            rest = rest.slice_from(n);
+            i += 1;
+        }
+    }
+
+    // Read everything with small, uneven reads.
+    fn read_chunks<R: Reader>(r: &mut R) -> IoResult<Vec<u8>> {
+        let mut out = Vec::new();
+        let mut buf = [0u8, ..777];
+        let mut i = 0u;
+        loop {
+            let n = 1 + i * 31 % buf.len();
+            match r.read(buf.mut_slice_to(n)) {
+                Ok(n) => out.push_all(buf.slice_to(n)),
+                Err(ref e) if e.kind == ::std::io::EndOfFile => return Ok(out),
+                Err(e) => return Err(e),
+            }
+            i += 1;
+        }
+    }
+
+    fn deflate(data: &[u8], level: Compression) -> Vec<u8> {
+        let mut encoder = DeflateEncoder::new(MemWriter::new(), level);
+        write_chunks(&mut encoder, data);
+        encoder.finish().unwrap().unwrap()
+    }
+
+    fn zlib(data: &[u8], level: Compression) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(MemWriter::new(), level);
+        write_chunks(&mut encoder, data);
+        encoder.finish().unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_deflate_round_trip() {
+        let data = sample();
+        for &level in [Level(0), Fast, Default, Best, Level(10)].iter() {
+            let compressed = deflate(data.as_slice(), level);
+            let mut decoder = DeflateDecoder::new(MemReader::new(compressed));
+            assert!(read_chunks(&mut decoder).unwrap() == data);
+        }
+    }
+
+    #[test]
+    fn test_zlib_round_trip() {
+        let data = sample();
+        let compressed = zlib(data.as_slice(), Default);
+        let mut decoder = ZlibDecoder::new(MemReader::new(compressed));
+        assert!(read_chunks(&mut decoder).unwrap() == data);
+    }
+
+    #[test]
+    fn test_empty() {
+        let compressed = deflate([], Default);
+        let mut decoder = DeflateDecoder::new(MemReader::new(compressed));
+        assert_eq!(decoder.read_to_end().unwrap(), vec!());
+        let compressed = zlib([], Default);
+        let mut decoder = ZlibDecoder::new(MemReader::new(compressed));
+        assert_eq!(decoder.read_to_end().unwrap(), vec!());
+    }
+
+    #[test]
+    fn test_one_shot_interop() {
+        let data = sample();
+
+        let compressed = deflate(data.as_slice(), Default);
+        let inflated = inflate_bytes(compressed.as_slice()).expect("inflation failed");
+        assert!(inflated.as_slice() == data.as_slice());
+        let compressed = deflate_bytes(data.as_slice()).expect("deflation failed");
+        let mut decoder = DeflateDecoder::new(MemReader::new(Vec::from_slice(compressed.as_slice())));
+        assert!(decoder.read_to_end().unwrap() == data);
+
+        let compressed = zlib(data.as_slice(), Best);
+        let inflated = inflate_bytes_zlib(compressed.as_slice()).expect("inflation failed");
+        assert!(inflated.as_slice() == data.as_slice());
+        let compressed = deflate_bytes_zlib(data.as_slice()).expect("deflation failed");
+        let mut decoder = ZlibDecoder::new(MemReader::new(Vec::from_slice(compressed.as_slice())));
+        assert!(decoder.read_to_end().unwrap() == data);
+    }
+
+    #[test]
+    fn test_levels() {
+        let data = sample();
+        let stored = deflate(data.as_slice(), Level(0)).len();
+        let fast = deflate(data.as_slice(), Fast).len();
+        let best = deflate(data.as_slice(), Best).len();
+        assert!(stored > data.len());
+        assert!(fast < stored);
+        assert!(best <= fast);
+    }
+
+    #[test]
+    fn test_corrupt() {
+        let data = sample();
+        let mut compressed = zlib(data.as_slice(), Default);
+
+        // truncated
+        let truncated = Vec::from_slice(compressed.slice_to(compressed.len() / 2));
+        let mut decoder = ZlibDecoder::new(MemReader::new(truncated));
+        assert_eq!(decoder.read_to_end().unwrap_err().kind, InvalidInput);
+        let raw = deflate(data.as_slice(), Default);
+        let truncated = Vec::from_slice(raw.slice_to(raw.len() - 1));
+        let mut decoder = DeflateDecoder::new(MemReader::new(truncated));
+        assert_eq!(decoder.read_to_end().unwrap_err().kind, InvalidInput);
+
+        // bad checksum
+        let last = compressed.len() - 1;
+        *compressed.get_mut(last) ^= 1;
+        let mut decoder = ZlibDecoder::new(MemReader::new(compressed));
+        let err = decoder.read_to_end().unwrap_err();
+        assert_eq!(err.kind, InvalidInput);
+        assert_eq!(err.desc, "zlib checksum mismatch");
+
+        // not compressed at all
+        let mut decoder = ZlibDecoder::new(MemReader::new(data));
+        assert_eq!(decoder.read_to_end().unwrap_err().kind, InvalidInput);
+    }
+}
@@ -0,0 +1,245 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Generation of shell completion scripts from the options of a `Command`.
+
+use std::strbuf::StrBuf;
+
+use {Command, OptGroup, Yes, No, Maybe, Multi};
+
+/// All commands in the tree rooted at `cmd`, parents first, along with their
+/// paths of space separated names.
+fn collect<'a>(cmd: &'a Command, path: ~str, out: &mut Vec<(~str, &'a Command)>) {
+    out.push((path.clone(), cmd));
+    for sub in cmd.subcommands.iter() {
+        collect(sub, format!("{} {}", path, sub.name), out);
+    }
+}
+
+/// The words naming an option on the command line, e.g. `-h` and `--help`.
+fn option_words(opt: &OptGroup) -> Vec<~str> {
+    let mut words = Vec::new();
+    if !opt.short_name.is_empty() {
+        words.push(format!("-{}", opt.short_name));
+    }
+    if !opt.long_name.is_empty() {
+        words.push(format!("--{}", opt.long_name));
+    }
+    words
+}
+
+/// Whether `nm` is the short or long name of `opt`.
+fn is_named(opt: &OptGroup, nm: &str) -> bool {
+    opt.short_name.as_slice() == nm || opt.long_name.as_slice() == nm
+}
+
+/// The words of every option sharing an exclusive group with `opt`.
+fn excluded_words(cmd: &Command, opt: &OptGroup) -> Vec<~str> {
+    let mut words = Vec::new();
+    for group in cmd.exclusive.iter() {
+        if !group.iter().any(|nm| is_named(opt, *nm)) {
+            continue;
+        }
+        for nm in group.iter().filter(|nm| !is_named(opt, **nm)) {
+            match cmd.opts.iter().find(|o| is_named(*o, *nm)) {
+                Some(other) => words.push_all_move(option_words(other)),
+                None => {}
+            }
+        }
+    }
+    words
+}
+
+/// Turn `s` into something usable as part of a shell function name.
+fn ident(s: &str) -> ~str {
+    s.chars().map(|c| if c.is_alphanumeric() { c } else { '_' }).collect()
+}
+
+/// Escape `s` for use inside a single quoted shell string.
+fn quote(s: &str) -> ~str {
+    s.replace("'", "'\\''")
+}
+
+/// Generate a bash completion script for `cmd`.
+pub fn bash(cmd: &Command) -> ~str {
+    let mut cmds = Vec::new();
+    collect(cmd, cmd.name.clone(), &mut cmds);
+    let func = format!("_{}", ident(cmd.name));
+
+    let mut s = StrBuf::new();
+    s.push_str(func);
+    s.push_str("() {\n");
+    s.push_str("    local cur prev cmd opts argopts i\n");
+    s.push_str("    COMPREPLY=()\n");
+    s.push_str("    cur=\"${COMP_WORDS[COMP_CWORD]}\"\n");
+    s.push_str("    prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n");
+    s.push_str(format!("    cmd=\"{}\"\n", cmd.name));
+
+    // Find the innermost subcommand given so far
+    if cmds.len() > 1 {
+        s.push_str("    for ((i = 1; i < COMP_CWORD; i++)); do\n");
+        s.push_str("        case \"${cmd} ${COMP_WORDS[i]}\" in\n");
+        for &(ref path, _) in cmds.iter().skip(1) {
+            s.push_str(format!("            \"{0}\") cmd=\"{0}\" ;;\n", *path));
+        }
+        s.push_str("        esac\n");
+        s.push_str("    done\n");
+    }
+
+    s.push_str("    case \"${cmd}\" in\n");
+    for &(ref path, c) in cmds.iter() {
+        let mut opts = Vec::new();
+        let mut argopts = Vec::new();
+        for opt in c.opts.iter() {
+            if opt.hasarg == Yes {
+                argopts.push_all(option_words(opt).as_slice());
+            }
+            opts.push_all_move(option_words(opt));
+        }
+        for sub in c.subcommands.iter() {
+            opts.push(sub.name.clone());
+        }
+        s.push_str(format!("        \"{}\")\n", *path));
+        s.push_str(format!("            opts=\"{}\"\n", opts.connect(" ")));
+        s.push_str(format!("            argopts=\"{}\"\n", argopts.connect(" ")));
+        s.push_str("            ;;\n");
+    }
+    s.push_str("    esac\n");
+
+    // Complete file names for the argument of an option
+    s.push_str("    if [[ -n \"${argopts}\" && \" ${argopts} \" == *\" ${prev} \"* ]]; then\n");
+    s.push_str("        COMPREPLY=( $(compgen -f -- \"${cur}\") )\n");
+    s.push_str("    else\n");
+    s.push_str("        COMPREPLY=( $(compgen -W \"${opts}\" -- \"${cur}\") )\n");
+    s.push_str("    fi\n");
+    s.push_str("}\n");
+    s.push_str(format!("complete -o default -F {} {}\n", func, cmd.name));
+    s.into_owned()
+}
+
+/// The `_arguments` spec of an option, e.g.
+/// `'(-o --output)'{-o+,--output=}'[write output to FILE]:FILE:_files'`.
+fn zsh_option(cmd: &Command, opt: &OptGroup) -> ~str {
+    let mut s = StrBuf::new();
+
+    // Options which can't follow this one
+    let mut excluded = excluded_words(cmd, opt);
+    if opt.occur != Multi {
+        excluded.push_all(option_words(opt).as_slice());
+    }
+    s.push_char('\'');
+    if !excluded.is_empty() {
+        s.push_char('(');
+        s.push_str(excluded.connect(" "));
+        s.push_char(')');
+    }
+    if opt.occur == Multi {
+        s.push_char('*');
+    }
+    s.push_char('\'');
+
+    let (short_suffix, long_suffix) = match opt.hasarg {
+        Yes => ("+", "="),
+        Maybe => ("-", "=-"),
+        No => ("", ""),
+    };
+    let mut names = Vec::new();
+    if !opt.short_name.is_empty() {
+        names.push(format!("-{}{}", opt.short_name, short_suffix));
+    }
+    if !opt.long_name.is_empty() {
+        names.push(format!("--{}{}", opt.long_name, long_suffix));
+    }
+    if names.len() > 1 {
+        s.push_char('{');
+        s.push_str(names.connect(","));
+        s.push_char('}');
+    } else {
+        s.push_str(names.connect(""));
+    }
+
+    let desc = quote(opt.desc).replace("[", "\\[").replace("]", "\\]");
+    s.push_str(format!("'[{}]", desc));
+    if opt.hasarg != No {
+        let hint = if opt.hint.is_empty() { "arg".to_owned() } else { opt.hint.clone() };
+        let colons = if opt.hasarg == Maybe { "::" } else { ":" };
+        s.push_str(format!("{}{}:_files", colons, quote(hint).replace(":", "\\:")));
+    }
+    s.push_char('\'');
+    s.into_owned()
+}
+
+/// Append the zsh completion function for `cmd`, named `func`, and those of
+/// its subcommands.
+fn zsh_function(cmd: &Command, func: &str, s: &mut StrBuf) {
+    let mut specs: Vec<~str> = cmd.opts.iter().map(|opt| zsh_option(cmd, opt)).collect();
+    if cmd.subcommands.is_empty() {
+        specs.push("'*: :_files'".to_owned());
+    } else {
+        specs.push("': :->command'".to_owned());
+        specs.push("'*:: :->args'".to_owned());
+    }
+
+    s.push_str(func);
+    s.push_str("() {\n");
+    if !cmd.subcommands.is_empty() {
+        s.push_str("    local curcontext=\"$curcontext\" state line\n");
+        s.push_str("    typeset -A opt_args\n\n");
+        s.push_str("    _arguments -s -C \\\n");
+    } else {
+        s.push_str("    _arguments -s \\\n");
+    }
+    for (i, spec) in specs.iter().enumerate() {
+        s.push_str("        ");
+        s.push_str(spec.as_slice());
+        s.push_str(if i + 1 < specs.len() { " \\\n" } else { "\n" });
+    }
+
+    if !cmd.subcommands.is_empty() {
+        s.push_str("\n    case $state in\n");
+        s.push_str("        command)\n");
+        s.push_str("            local -a commands\n");
+        s.push_str("            commands=(\n");
+        for sub in cmd.subcommands.iter() {
+            s.push_str(format!("                '{}:{}'\n",
+                               quote(sub.name).replace(":", "\\:"), quote(sub.desc)));
+        }
+        s.push_str("            )\n");
+        s.push_str("            _describe -t commands 'command' commands\n");
+        s.push_str("            ;;\n");
+        s.push_str("        args)\n");
+        s.push_str("            case $line[1] in\n");
+        for sub in cmd.subcommands.iter() {
+            s.push_str(format!("                {}) {}_{} ;;\n",
+                               sub.name, func, ident(sub.name)));
+        }
+        s.push_str("            esac\n");
+        s.push_str("            ;;\n");
+        s.push_str("    esac\n");
+    }
+    s.push_str("}\n");
+
+    for sub in cmd.subcommands.iter() {
+        s.push_char('\n');
+        zsh_function(sub, format!("{}_{}", func, ident(sub.name)).as_slice(), s);
+    }
+}
+
+/// Generate a zsh completion script for `cmd`.
+pub fn zsh(cmd: &Command) -> ~str {
+    let func = format!("_{}", ident(cmd.name));
+    let mut s = StrBuf::new();
+    s.push_str("#compdef ");
+    s.push_str(cmd.name);
+    s.push_str("\n\n");
+    zsh_function(cmd, func.as_slice(), &mut s);
+    s.push_str(format!("\n{} \"$@\"\n", func));
+    s.into_owned()
+}
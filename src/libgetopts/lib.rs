@@ -23,6 +23,12 @@
 //! argument following either a space or an equals sign. Single-character
 //! options don't require the space.
 //!
+//! Options can fall back to an environment variable with `with_env`, and
+//! `opt_get` converts arguments to any `FromStr` type. Programs with
+//! subcommands, or options which can't be used together, can describe
+//! themselves with a `Command`, which can also generate bash and zsh
+//! completion scripts.
+//!
 //! # Example
 //!
 //! The following example shows simple command line parsing for an application
@@ -90,10 +96,14 @@
 #[cfg(test)] #[phase(syntax, link)] extern crate log;
 
 use std::cmp::Eq;
+use std::from_str::FromStr;
+use std::os;
 use std::result::{Err, Ok};
 use std::result;
 use std::strbuf::StrBuf;
 
+mod completion;
+
 /// Name of an option. Either a string or a single char.
 #[deriving(Clone, Eq)]
 pub enum Name {
@@ -155,7 +165,9 @@ pub struct OptGroup {
     /// Whether it has an argument
     pub hasarg: HasArg,
     /// How often it can occur
-    pub occur: Occur
+    pub occur: Occur,
+    /// Environment variable which supplies the option when it isn't given
+    pub env: Option<~str>,
 }
 
 /// Describes wether an option is given at all or has a value.
@@ -175,6 +187,8 @@ pub struct Matches {
     vals: Vec<Vec<Optval> > ,
     /// Free string fragments
     pub free: Vec<~str>,
+    /// Name and matches of the subcommand, if any
+    sub: Option<(~str, ~Matches)>,
 }
 
 /// The type returned when the command line does not conform to the
@@ -192,6 +206,14 @@ pub enum Fail_ {
     OptionDuplicated(~str),
     /// There's an argument being passed to a non-argument option.
     UnexpectedArgument(~str),
+    /// The argument of an option couldn't be converted to the requested
+    /// type. Contains the name of the option and the argument.
+    InvalidArgument(~str, ~str),
+    /// Two mutually exclusive options are both present.
+    OptionConflict(~str, ~str),
+    /// The command has subcommands, and the first free argument isn't one
+    /// of them.
+    UnrecognizedCommand(~str),
 }
 
 /// The type of failure that occurred.
@@ -203,6 +225,9 @@ pub enum FailType {
     OptionMissing_,
     OptionDuplicated_,
     UnexpectedArgument_,
+    InvalidArgument_,
+    OptionConflict_,
+    UnrecognizedCommand_,
 }
 
 /// The result of parsing a command line with a set of options.
@@ -226,6 +251,17 @@ impl Name {
 }
 
 impl OptGroup {
+    /// Use the value of the environment variable `var` for this option when
+    /// it isn't given on the command line.
+    ///
+    /// For an option which takes an argument, the value is the argument,
+    /// and an empty value is ignored. A flag is given if the variable is
+    /// set to anything other than the empty string or `0`.
+    pub fn with_env(mut self, var: &str) -> OptGroup {
+        self.env = Some(var.to_owned());
+        self
+    }
+
     /// Translate OptGroup into Opt.
     /// (Both short and long names correspond to different Opts).
     pub fn long_to_short(&self) -> Opt {
@@ -361,12 +397,60 @@ impl Matches {
         }
     }
 
+    /// Returns the argument supplied to a matching option converted to a
+    /// `T`, or `None` if there is no argument.
+    ///
+    /// Returns `Err(InvalidArgument(..))` if the argument can't be
+    /// converted, which can be reported like any other failure.
+    pub fn opt_get<T: FromStr>(&self, nm: &str) -> result::Result<Option<T>, Fail_> {
+        match self.opt_str(nm) {
+            Some(s) => match from_str(s) {
+                Some(v) => Ok(Some(v)),
+                None => Err(InvalidArgument(nm.to_owned(), s))
+            },
+            None => Ok(None)
+        }
+    }
+
+    /// Like `opt_get`, but returns `def` if there is no argument.
+    pub fn opt_get_default<T: FromStr>(&self, nm: &str, def: T) -> result::Result<T, Fail_> {
+        self.opt_get(nm).map(|v| v.unwrap_or(def))
+    }
+
+    /// Returns the arguments provided to all matches of the given option,
+    /// converted to `T`s.
+    pub fn opt_gets<T: FromStr>(&self, nm: &str) -> result::Result<Vec<T>, Fail_> {
+        let mut acc = Vec::new();
+        for s in self.opt_strs(nm).move_iter() {
+            match from_str(s) {
+                Some(v) => acc.push(v),
+                None => return Err(InvalidArgument(nm.to_owned(), s))
+            }
+        }
+        Ok(acc)
+    }
+
+    /// Returns the name of the subcommand and its matches, if the command
+    /// was parsed by a `Command` with subcommands and one was given.
+    pub fn subcommand<'a>(&'a self) -> Option<(&'a str, &'a Matches)> {
+        match self.sub {
+            Some((ref name, ref m)) => Some((name.as_slice(), &**m)),
+            None => None
+        }
+    }
 }
 
 fn is_arg(arg: &str) -> bool {
     arg.len() > 1 && arg[0] == '-' as u8
 }
 
+fn opt_id(opts: &[Opt], nm: &str) -> uint {
+    match find_opt(opts, Name::from_str(nm)) {
+        Some(id) => id,
+        None => fail!("No option '{}' defined", nm)
+    }
+}
+
 fn find_opt(opts: &[Opt], nm: Name) -> Option<uint> {
     // Search main options.
     let pos = opts.iter().position(|opt| opt.name == nm);
@@ -394,7 +478,8 @@ pub fn reqopt(short_name: &str, long_name: &str, desc: &str, hint: &str) -> OptG
         hint: hint.to_owned(),
         desc: desc.to_owned(),
         hasarg: Yes,
-        occur: Req
+        occur: Req,
+        env: None
     }
 }
 
@@ -408,7 +493,8 @@ pub fn optopt(short_name: &str, long_name: &str, desc: &str, hint: &str) -> OptG
         hint: hint.to_owned(),
         desc: desc.to_owned(),
         hasarg: Yes,
-        occur: Optional
+        occur: Optional,
+        env: None
     }
 }
 
@@ -422,7 +508,8 @@ pub fn optflag(short_name: &str, long_name: &str, desc: &str) -> OptGroup {
         hint: "".to_owned(),
         desc: desc.to_owned(),
         hasarg: No,
-        occur: Optional
+        occur: Optional,
+        env: None
     }
 }
 
@@ -437,7 +524,8 @@ pub fn optflagmulti(short_name: &str, long_name: &str, desc: &str) -> OptGroup {
         hint: "".to_owned(),
         desc: desc.to_owned(),
         hasarg: No,
-        occur: Multi
+        occur: Multi,
+        env: None
     }
 }
 
@@ -451,7 +539,8 @@ pub fn optflagopt(short_name: &str, long_name: &str, desc: &str, hint: &str) ->
         hint: hint.to_owned(),
         desc: desc.to_owned(),
         hasarg: Maybe,
-        occur: Optional
+        occur: Optional,
+        env: None
     }
 }
 
@@ -466,7 +555,8 @@ pub fn optmulti(short_name: &str, long_name: &str, desc: &str, hint: &str) -> Op
         hint: hint.to_owned(),
         desc: desc.to_owned(),
         hasarg: Yes,
-        occur: Multi
+        occur: Multi,
+        env: None
     }
 }
 
@@ -485,7 +575,8 @@ pub fn opt(short_name: &str,
         hint: hint.to_owned(),
         desc: desc.to_owned(),
         hasarg: hasarg,
-        occur: occur
+        occur: occur,
+        env: None
     }
 }
 
@@ -508,6 +599,15 @@ impl Fail_ {
             UnexpectedArgument(ref nm) => {
                 format!("Option '{}' does not take an argument.", *nm)
             }
+            InvalidArgument(ref nm, ref arg) => {
+                format!("Invalid argument '{}' to option '{}'.", *arg, *nm)
+            }
+            OptionConflict(ref a, ref b) => {
+                format!("Options '{}' and '{}' cannot be used together.", *a, *b)
+            }
+            UnrecognizedCommand(ref nm) => {
+                format!("Unrecognized command: '{}'.", *nm)
+            }
         }
     }
 }
@@ -518,6 +618,15 @@ impl Fail_ {
 /// `opt_str`, etc. to interrogate results.  Returns `Err(Fail_)` on failure.
 /// Use `to_err_msg` to get an error message.
 pub fn getopts(args: &[~str], optgrps: &[OptGroup]) -> Result {
+    getopts_inner(args, optgrps, [], false).map(|(m, _)| m)
+}
+
+/// Parse `args` as `getopts` does, also checking that no two options of
+/// any of the `exclusive` groups are given. If `stop_at_free` is true,
+/// stops at the first free argument and returns its index.
+fn getopts_inner(args: &[~str], optgrps: &[OptGroup], exclusive: &[Vec<~str>],
+                 stop_at_free: bool)
+                 -> result::Result<(Matches, Option<uint>), Fail_> {
     let opts: Vec<Opt> = optgrps.iter().map(|x| x.long_to_short()).collect();
     let n_opts = opts.len();
 
@@ -525,12 +634,17 @@ pub fn getopts(args: &[~str], optgrps: &[OptGroup]) -> Result {
 
     let mut vals = Vec::from_fn(n_opts, f);
     let mut free: Vec<~str> = Vec::new();
+    let mut stop = None;
     let l = args.len();
     let mut i = 0;
     while i < l {
         let cur = args[i].clone();
         let curlen = cur.len();
         if !is_arg(cur) {
+            if stop_at_free {
+                stop = Some(i);
+                break;
+            }
             free.push(cur);
         } else if cur == "--".to_owned() {
             let mut j = i + 1;
@@ -629,6 +743,45 @@ pub fn getopts(args: &[~str], optgrps: &[OptGroup]) -> Result {
         }
         i += 1;
     }
+
+    for group in exclusive.iter() {
+        let mut given = group.iter().filter(|nm| {
+            !vals.get(opt_id(opts.as_slice(), **nm)).is_empty()
+        });
+        match (given.next(), given.next()) {
+            (Some(a), Some(b)) => return Err(OptionConflict(a.clone(), b.clone())),
+            _ => {}
+        }
+    }
+
+    // Fall back to the environment for options which weren't given, unless
+    // an option they exclude was.
+    for (id, grp) in optgrps.iter().enumerate() {
+        let var = match grp.env {
+            Some(ref var) if vals.get(id).is_empty() => var,
+            _ => continue
+        };
+        let excluded = exclusive.iter().any(|group| {
+            let ids: Vec<uint> = group.iter().map(|nm| opt_id(opts.as_slice(), *nm)).collect();
+            ids.contains(&id) && ids.iter().any(|&j| j != id && !vals.get(j).is_empty())
+        });
+        if excluded {
+            continue;
+        }
+        match os::getenv(*var) {
+            Some(val) => match grp.hasarg {
+                No => if !val.is_empty() && val.as_slice() != "0" {
+                    vals.get_mut(id).push(Given);
+                },
+                Maybe if val.is_empty() => vals.get_mut(id).push(Given),
+                _ => if !val.is_empty() {
+                    vals.get_mut(id).push(Val(val));
+                }
+            },
+            None => {}
+        }
+    }
+
     i = 0u;
     while i < n_opts {
         let n = vals.get(i).len();
@@ -645,11 +798,12 @@ pub fn getopts(args: &[~str], optgrps: &[OptGroup]) -> Result {
         }
         i += 1;
     }
-    Ok(Matches {
+    Ok((Matches {
         opts: opts,
         vals: vals,
-        free: free
-    })
+        free: free,
+        sub: None
+    }, stop))
 }
 
 /// Derive a usage message from a set of long options.
@@ -663,7 +817,12 @@ pub fn usage(brief: &str, opts: &[OptGroup]) -> ~str {
                      hint: hint,
                      desc: desc,
                      hasarg: hasarg,
+                     env: env,
                      ..} = (*optref).clone();
+        let desc = match env {
+            Some(var) => format!("{} [env: {}]", desc, var),
+            None => desc
+        };
 
         let mut row = StrBuf::from_owned_str(" ".repeat(4));
 
@@ -780,6 +939,153 @@ pub fn short_usage(program_name: &str, opts: &[OptGroup]) -> ~str {
     line.into_owned()
 }
 
+/// A command with its own set of options, and possibly subcommands with
+/// options of their own, as in `cargo build --release`.
+///
+/// The options of a command must come before the name of its subcommand,
+/// and everything after the name is parsed by the subcommand.
+///
+/// # Example
+///
+/// ~~~{.rust}
+/// use getopts::{Command, optflag, optopt};
+///
+/// let cmd = Command::new("prog", "An example program")
+///     .opt(optflag("v", "verbose", "print more output"))
+///     .opt(optflag("q", "quiet", "print less output"))
+///     .exclusive(["verbose", "quiet"])
+///     .subcommand(Command::new("build", "Build the project")
+///         .opt(optopt("j", "jobs", "number of parallel jobs", "N")
+///              .with_env("PROG_JOBS")));
+///
+/// let args = ["-v".to_owned(), "build".to_owned(), "-j4".to_owned()];
+/// let matches = cmd.parse(args).unwrap();
+/// assert!(matches.opt_present("verbose"));
+/// match matches.subcommand() {
+///     Some(("build", m)) => assert_eq!(m.opt_get::<uint>("j"), Ok(Some(4))),
+///     _ => fail!()
+/// }
+/// ~~~
+#[deriving(Clone)]
+pub struct Command {
+    /// Name of the command, as given on the command line
+    pub name: ~str,
+    /// Description, shown in the usage of the parent command
+    pub desc: ~str,
+    /// Options of the command
+    pub opts: Vec<OptGroup>,
+    /// Subcommands, of which at most one may be given
+    pub subcommands: Vec<Command>,
+    /// Groups of options of which at most one may be given
+    pub exclusive: Vec<Vec<~str>>,
+}
+
+impl Command {
+    /// Create a command with no options or subcommands.
+    pub fn new(name: &str, desc: &str) -> Command {
+        Command {
+            name: name.to_owned(),
+            desc: desc.to_owned(),
+            opts: Vec::new(),
+            subcommands: Vec::new(),
+            exclusive: Vec::new(),
+        }
+    }
+
+    /// Add an option to the command.
+    pub fn opt(mut self, opt: OptGroup) -> Command {
+        self.opts.push(opt);
+        self
+    }
+
+    /// Add a subcommand.
+    pub fn subcommand(mut self, cmd: Command) -> Command {
+        self.subcommands.push(cmd);
+        self
+    }
+
+    /// Make the options named in `names`, short or long, mutually
+    /// exclusive. Giving more than one of them is an `OptionConflict`, and
+    /// an option isn't taken from the environment if another one of them
+    /// was given.
+    ///
+    /// # Failure
+    ///
+    /// Parsing fails if any of the names isn't an option of the command.
+    pub fn exclusive(mut self, names: &[&str]) -> Command {
+        self.exclusive.push(names.iter().map(|s| s.to_owned()).collect());
+        self
+    }
+
+    /// Parse command line arguments according to the options of the
+    /// command and its subcommands.
+    ///
+    /// If the command has subcommands, the first free argument must name
+    /// one of them, otherwise `Err(UnrecognizedCommand(..))` is returned.
+    /// The matches of the subcommand are available through
+    /// `Matches::subcommand`.
+    pub fn parse(&self, args: &[~str]) -> Result {
+        let stop_at_free = !self.subcommands.is_empty();
+        let (mut matches, stop) = try!(getopts_inner(args,
+                                                     self.opts.as_slice(),
+                                                     self.exclusive.as_slice(),
+                                                     stop_at_free));
+        match stop {
+            Some(i) => {
+                let name = &args[i];
+                let cmd = match self.subcommands.iter().find(|c| c.name == *name) {
+                    Some(cmd) => cmd,
+                    None => return Err(UnrecognizedCommand(name.clone()))
+                };
+                let sub = try!(cmd.parse(args.slice_from(i + 1)));
+                matches.sub = Some((name.clone(), ~sub));
+            }
+            None => {}
+        }
+        Ok(matches)
+    }
+
+    /// Derive a usage message for the command, listing its options as
+    /// `usage` does, followed by its subcommands.
+    pub fn usage(&self, brief: &str) -> ~str {
+        let mut s = StrBuf::from_owned_str(usage(brief, self.opts.as_slice()));
+        if !self.subcommands.is_empty() {
+            s.push_str("\nCommands:\n");
+            for cmd in self.subcommands.iter() {
+                let mut row = StrBuf::from_owned_str(" ".repeat(4));
+                row.push_str(cmd.name);
+                let rowlen = row.as_slice().char_len();
+                if rowlen < 24 {
+                    for _ in range(0, 24 - rowlen) {
+                        row.push_char(' ');
+                    }
+                } else {
+                    row.push_char('\n');
+                    row.push_str(" ".repeat(24));
+                }
+                row.push_str(cmd.desc);
+                s.push_str(row.as_slice());
+                s.push_char('\n');
+            }
+        }
+        s.into_owned()
+    }
+
+    /// Generate a bash completion script for the command, which completes
+    /// its options and subcommands. Load it with `source`, or install it in
+    /// the system's `bash_completion.d` directory.
+    pub fn bash_completion(&self) -> ~str {
+        completion::bash(self)
+    }
+
+    /// Generate a zsh completion script for the command, for installing as
+    /// `_name` in a directory on `$fpath`. Options are described by their
+    /// descriptions and arguments are completed as file names.
+    pub fn zsh_completion(&self) -> ~str {
+        completion::zsh(self)
+    }
+}
+
 
 /// Splits a string into substrings with possibly internal whitespace,
 /// each of them at most `lim` bytes long. The substrings have leading and trailing
@@ -904,6 +1210,7 @@ fn test_split_within() {
 mod tests {
     use super::*;
 
+    use std::os;
     use std::result::{Err, Ok};
     use std::result;
 
@@ -913,7 +1220,10 @@ mod tests {
           UnrecognizedOption(_) => assert!(ft == UnrecognizedOption_),
           OptionMissing(_) => assert!(ft == OptionMissing_),
           OptionDuplicated(_) => assert!(ft == OptionDuplicated_),
-          UnexpectedArgument(_) => assert!(ft == UnexpectedArgument_)
+          UnexpectedArgument(_) => assert!(ft == UnexpectedArgument_),
+          InvalidArgument(..) => assert!(ft == InvalidArgument_),
+          OptionConflict(..) => assert!(ft == OptionConflict_),
+          UnrecognizedCommand(_) => assert!(ft == UnrecognizedCommand_)
         }
     }
 
@@ -1529,4 +1839,173 @@ Options:
         debug!("generated: <<{}>>", generated_usage);
         assert_eq!(generated_usage, expected);
     }
+
+    #[test]
+    fn test_opt_get() {
+        let args = vec!("-n".to_owned(), "42".to_owned(), "--ratio=x".to_owned(),
+                        "-l".to_owned(), "1".to_owned(), "-l".to_owned(), "2".to_owned());
+        let opts = vec!(optopt("n", "number", "a number", "N"),
+                        optopt("r", "ratio", "a ratio", "R"),
+                        optopt("m", "missing", "not given", "M"),
+                        optmulti("l", "", "a list", "L"));
+        let m = getopts(args.as_slice(), opts.as_slice()).unwrap();
+        assert_eq!(m.opt_get::<uint>("n"), Ok(Some(42)));
+        assert_eq!(m.opt_get::<uint>("missing"), Ok(None));
+        assert_eq!(m.opt_get_default::<uint>("m", 7), Ok(7));
+        assert_eq!(m.opt_gets::<int>("l"), Ok(vec!(1, 2)));
+        match m.opt_get::<f64>("ratio") {
+            Err(f) => {
+                assert_eq!(f.clone(), InvalidArgument("ratio".to_owned(), "x".to_owned()));
+                assert_eq!(f.to_err_msg(), "Invalid argument 'x' to option 'ratio'.".to_owned());
+            }
+            Ok(_) => fail!()
+        }
+    }
+
+    #[test]
+    fn test_env_fallback() {
+        os::setenv("GETOPTS_TEST_ENV_OUT", "out.txt");
+        os::setenv("GETOPTS_TEST_ENV_VERBOSE", "1");
+        os::setenv("GETOPTS_TEST_ENV_QUIET", "0");
+        os::setenv("GETOPTS_TEST_ENV_EMPTY", "");
+        let opts = vec!(reqopt("o", "output", "output file", "FILE")
+                            .with_env("GETOPTS_TEST_ENV_OUT"),
+                        optflag("v", "verbose", "verbose")
+                            .with_env("GETOPTS_TEST_ENV_VERBOSE"),
+                        optflag("q", "quiet", "quiet")
+                            .with_env("GETOPTS_TEST_ENV_QUIET"),
+                        optopt("e", "empty", "empty", "E")
+                            .with_env("GETOPTS_TEST_ENV_EMPTY"),
+                        optopt("u", "unset", "unset", "U")
+                            .with_env("GETOPTS_TEST_ENV_UNSET"));
+
+        let m = getopts([], opts.as_slice()).unwrap();
+        assert_eq!(m.opt_str("output"), Some("out.txt".to_owned()));
+        assert!(m.opt_present("verbose"));
+        assert!(!m.opt_present("quiet"));
+        assert!(!m.opt_present("empty"));
+        assert!(!m.opt_present("unset"));
+
+        // the command line takes precedence
+        let m = getopts(["-ofoo".to_owned()], opts.as_slice()).unwrap();
+        assert_eq!(m.opt_strs("o"), vec!("foo".to_owned()));
+    }
+
+    #[test]
+    fn test_usage_env() {
+        let optgroups = vec!(optopt("j", "jobs", "Number of jobs", "N").with_env("JOBS"));
+        let expected =
+~"Usage: fruits
+
+Options:
+    -j --jobs N         Number of jobs [env: JOBS]
+";
+        assert_eq!(usage("Usage: fruits", optgroups.as_slice()), expected);
+    }
+
+    fn test_command() -> Command {
+        Command::new("prog", "A test program")
+            .opt(optflag("v", "verbose", "Print more"))
+            .opt(optflag("q", "quiet", "Print less").with_env("GETOPTS_TEST_CMD_QUIET"))
+            .exclusive(["verbose", "q"])
+            .subcommand(Command::new("build", "Build things")
+                .opt(optopt("o", "output", "Write output to [FILE]", "FILE"))
+                .subcommand(Command::new("docs", "Build the docs")))
+            .subcommand(Command::new("test", "Run the tests")
+                .opt(optflagmulti("n", "", "Don't run")))
+    }
+
+    #[test]
+    fn test_subcommands() {
+        let cmd = test_command();
+        let args = vec!("-v".to_owned(), "build".to_owned(), "-o".to_owned(),
+                        "out".to_owned(), "docs".to_owned(), "x".to_owned());
+        let m = cmd.parse(args.as_slice()).unwrap();
+        assert!(m.opt_present("v"));
+        assert!(m.free.is_empty());
+        let (name, build) = m.subcommand().unwrap();
+        assert_eq!(name, "build");
+        assert_eq!(build.opt_str("output"), Some("out".to_owned()));
+        let (name, docs) = build.subcommand().unwrap();
+        assert_eq!(name, "docs");
+        assert_eq!(docs.free, vec!("x".to_owned()));
+        assert!(docs.subcommand().is_none());
+
+        // options of the parent aren't options of the subcommand
+        match cmd.parse(["test".to_owned(), "-v".to_owned()]) {
+            Err(f) => check_fail_type(f, UnrecognizedOption_),
+            Ok(_) => fail!()
+        }
+        match cmd.parse(["-v".to_owned(), "frobnicate".to_owned()]) {
+            Err(f) => assert_eq!(f, UnrecognizedCommand("frobnicate".to_owned())),
+            Ok(_) => fail!()
+        }
+
+        // no subcommand is fine, and "--" ends the options
+        let m = cmd.parse(["--".to_owned(), "build".to_owned()]).unwrap();
+        assert!(m.subcommand().is_none());
+        assert_eq!(m.free, vec!("build".to_owned()));
+    }
+
+    #[test]
+    fn test_exclusive() {
+        let cmd = test_command();
+        match cmd.parse(["-v".to_owned(), "-q".to_owned()]) {
+            Err(f) => {
+                assert_eq!(f.clone(), OptionConflict("verbose".to_owned(), "q".to_owned()));
+                assert_eq!(f.to_err_msg(),
+                           "Options 'verbose' and 'q' cannot be used together.".to_owned());
+            }
+            Ok(_) => fail!()
+        }
+
+        // an option given on the command line overrides an exclusive one
+        // from the environment
+        os::setenv("GETOPTS_TEST_CMD_QUIET", "1");
+        let m = cmd.parse([]).unwrap();
+        assert!(m.opt_present("q"));
+        let m = cmd.parse(["-v".to_owned()]).unwrap();
+        assert!(m.opt_present("v"));
+        assert!(!m.opt_present("q"));
+    }
+
+    #[test]
+    fn test_command_usage() {
+        let expected =
+~"Usage: prog [options] <command>
+
+Options:
+    -v --verbose        Print more
+    -q --quiet          Print less [env: GETOPTS_TEST_CMD_QUIET]
+
+Commands:
+    build               Build things
+    test                Run the tests
+";
+        assert_eq!(test_command().usage("Usage: prog [options] <command>"), expected);
+    }
+
+    #[test]
+    fn test_bash_completion() {
+        let script = test_command().bash_completion();
+        assert!(script.starts_with("_prog() {\n"));
+        assert!(script.contains("            \"prog build docs\") cmd=\"prog build docs\" ;;\n"));
+        assert!(script.contains("        \"prog\")\n            opts=\"-v --verbose -q --quiet build test\"\n            argopts=\"\"\n"));
+        assert!(script.contains("        \"prog build\")\n            opts=\"-o --output docs\"\n            argopts=\"-o --output\"\n"));
+        assert!(script.ends_with("complete -o default -F _prog prog\n"));
+    }
+
+    #[test]
+    fn test_zsh_completion() {
+        let script = test_command().zsh_completion();
+        assert!(script.starts_with("#compdef prog\n\n_prog() {\n"));
+        assert!(script.contains("        '(-q --quiet -v --verbose)'{-v,--verbose}'[Print more]' \\\n"));
+        assert!(script.contains("        '(-v --verbose -q --quiet)'{-q,--quiet}'[Print less]' \\\n"));
+        assert!(script.contains("                'build:Build things'\n"));
+        assert!(script.contains("                build) _prog_build ;;\n"));
+        assert!(script.contains("        '(-o --output)'{-o+,--output=}'[Write output to \\[FILE\\]]:FILE:_files' \\\n"));
+        assert!(script.contains("_prog_build_docs() {\n    _arguments -s \\\n        '*: :_files'\n}\n"));
+        assert!(script.contains("        '*'-n'[Don'\\''t run]' \\\n"));
+        assert!(script.ends_with("\n_prog \"$@\"\n"));
+    }
 }
@@ -0,0 +1,395 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Matching paths against the rules of `.gitignore` files.
+
+use std::io::{File, IoResult, TypeDirectory};
+use std::io::fs;
+use std::os;
+use std::strbuf::StrBuf;
+
+use {Pattern, MatchOptions};
+
+// One line of a rule file
+#[deriving(Clone)]
+struct Rule {
+    pattern: Pattern,
+    // `!pattern`, which re-includes what earlier rules excluded
+    negated: bool,
+    // `pattern/`, which only matches directories
+    dir_only: bool,
+    // The pattern contains a `/`, so it matches paths relative to the
+    // directory of the rule file rather than file names
+    anchored: bool,
+}
+
+static RULE_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false
+};
+
+// Parse a line of a rule file, returning `None` for blank lines and comments.
+fn parse_rule(line: &str) -> Option<Rule> {
+    let mut line = line;
+    if line.starts_with("#") {
+        return None;
+    }
+
+    // Trailing spaces are ignored unless escaped
+    while line.ends_with(" ") && !line.ends_with("\\ ") {
+        line = line.slice_to(line.len() - 1);
+    }
+
+    let negated = line.starts_with("!");
+    if negated {
+        line = line.slice_from(1);
+    }
+    let dir_only = line.ends_with("/");
+    if dir_only {
+        line = line.slice_to(line.len() - 1);
+    }
+    let anchored = line.contains_char('/');
+    if line.starts_with("/") {
+        line = line.slice_from(1);
+    }
+    if line.is_empty() {
+        return None;
+    }
+
+    // A backslash makes the next character literal
+    let mut pattern = StrBuf::new();
+    let mut escaped = false;
+    for c in line.chars() {
+        if escaped {
+            pattern.push_str(Pattern::escape(c.to_str()));
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else {
+            pattern.push_char(c);
+        }
+    }
+
+    Some(Rule {
+        pattern: Pattern::new(pattern.as_slice()),
+        negated: negated,
+        dir_only: dir_only,
+        anchored: anchored,
+    })
+}
+
+/**
+ * The rules of a tree of `.gitignore` files, for deciding which paths under
+ * a root directory are ignored, the way git does.
+ *
+ * Each directory may have its own rule file, with one pattern per line.
+ * Blank lines and lines starting with `#` are skipped, and a backslash makes
+ * the next character literal. The rules of a file apply to the paths below
+ * its directory:
+ *
+ * - A pattern containing a `/`, other than at its end, matches paths
+ *   relative to the directory. Other patterns match file names at any depth.
+ * - `*`, `?` and `[...]` don't match a `/`, while `**` matches any number of
+ *   directories, as described for `Pattern::new`.
+ * - A pattern ending with `/` only matches directories.
+ * - A pattern starting with `!` re-includes paths that earlier rules
+ *   excluded.
+ *
+ * When several rules match a path the last one wins, and the rules of a
+ * directory come after those of its parents. A path inside an ignored
+ * directory is ignored too, whatever the rules say about the path itself.
+ *
+ * # Example
+ *
+ * ```rust
+ * use glob::Gitignore;
+ *
+ * let mut ignore = Gitignore::new(Path::new("/project"));
+ * ignore.add_rules(&Path::new("/project"), "target/\n*.o\n!keep.o\n");
+ * ignore.add_rules(&Path::new("/project/src"), "/generated.rs\n");
+ *
+ * assert!(ignore.is_ignored(&Path::new("/project/target"), true));
+ * assert!(ignore.is_ignored(&Path::new("/project/target/lib.rs"), false));
+ * assert!(ignore.is_ignored(&Path::new("src/main.o"), false));
+ * assert!(!ignore.is_ignored(&Path::new("src/keep.o"), false));
+ * assert!(ignore.is_ignored(&Path::new("src/generated.rs"), false));
+ * assert!(!ignore.is_ignored(&Path::new("src/foo/generated.rs"), false));
+ * ```
+ */
+#[deriving(Clone)]
+pub struct Gitignore {
+    root: Path,
+    // The rules of each directory, which is given as its components relative
+    // to `root`, ordered from the shallowest directory to the deepest
+    dirs: Vec<(Vec<~str>, Vec<Rule>)>,
+}
+
+impl Gitignore {
+    /**
+     * Create a matcher for the paths under `root`, with no rules. A relative
+     * `root` is taken relative to the current working directory.
+     */
+    pub fn new(root: Path) -> Gitignore {
+        Gitignore {
+            root: os::make_absolute(&root),
+            dirs: Vec::new(),
+        }
+    }
+
+    /**
+     * Create a matcher for the paths under `root`, reading the `.gitignore`
+     * file of every directory in the tree which isn't itself ignored.
+     */
+    pub fn load(root: Path) -> IoResult<Gitignore> {
+        Gitignore::load_with(root, ".gitignore")
+    }
+
+    /**
+     * Like `load`, but reads rule files named `file_name` instead of
+     * `.gitignore`. `.git` directories and symbolic links are not searched.
+     */
+    pub fn load_with(root: Path, file_name: &str) -> IoResult<Gitignore> {
+        let mut ignore = Gitignore::new(root);
+        let mut todo = vec!(ignore.root.clone());
+        loop {
+            let dir = match todo.pop() {
+                Some(dir) => dir,
+                None => return Ok(ignore)
+            };
+            let file = dir.join(file_name);
+            if file.exists() {
+                try!(ignore.add_file(&file));
+            }
+            for entry in try!(fs::readdir(&dir)).move_iter() {
+                if entry.filename_str() == Some(".git") {
+                    continue;
+                }
+                match fs::lstat(&entry) {
+                    Ok(ref stat) if stat.kind == TypeDirectory => {
+                        if !ignore.is_ignored(&entry, true) {
+                            todo.push(entry);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /**
+     * Add rules, in the format of a `.gitignore` file, for the directory
+     * `dir`. They take precedence over the rules already added for the same
+     * directory.
+     *
+     * # Failure
+     *
+     * Fails if `dir` is not under the root.
+     */
+    pub fn add_rules(&mut self, dir: &Path, rules: &str) {
+        let dir = match self.components(dir) {
+            Some(dir) => dir,
+            None => fail!("`{}` is not under `{}`", dir.display(), self.root.display())
+        };
+        let rules: Vec<Rule> = rules.lines_any().filter_map(parse_rule).collect();
+        match self.dirs.iter().position(|&(ref d, _)| *d == dir) {
+            Some(i) => match *self.dirs.get_mut(i) {
+                (_, ref mut existing) => existing.push_all_move(rules)
+            },
+            None => {
+                self.dirs.push((dir, rules));
+                self.dirs.sort_by(|&(ref a, _), &(ref b, _)| a.len().cmp(&b.len()));
+            }
+        }
+    }
+
+    /**
+     * Read the rule file `path`, whose rules apply to the directory
+     * containing it.
+     */
+    pub fn add_file(&mut self, path: &Path) -> IoResult<()> {
+        let rules = try!(File::open(path).read_to_str());
+        self.add_rules(&path.dir_path(), rules);
+        Ok(())
+    }
+
+    /**
+     * Return if `path` is ignored. `path` can be absolute, or relative to the
+     * root, and paths outside the root are never ignored. `is_dir` says
+     * whether `path` is a directory, which saves querying the filesystem.
+     */
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let components = match self.components(path) {
+            Some(components) => components,
+            None => return false
+        };
+        if components.is_empty() {
+            return false;
+        }
+        for i in range(1, components.len()) {
+            if self.matches(components.slice_to(i), true) == Some(true) {
+                return true;
+            }
+        }
+        self.matches(components.as_slice(), is_dir) == Some(true)
+    }
+
+    // Whether the last rule matching the path with the given components
+    // ignores it, if any rule matches.
+    fn matches(&self, components: &[~str], is_dir: bool) -> Option<bool> {
+        let name = components.last().unwrap().as_slice();
+        let mut result = None;
+        for &(ref dir, ref rules) in self.dirs.iter() {
+            if dir.len() >= components.len() ||
+               components.slice_to(dir.len()) != dir.as_slice() {
+                continue;
+            }
+            let relative = components.slice_from(dir.len()).connect("/");
+            for rule in rules.iter() {
+                if rule.dir_only && !is_dir {
+                    continue;
+                }
+                let target = if rule.anchored { relative.as_slice() } else { name };
+                if rule.pattern.matches_with(target, RULE_OPTIONS) {
+                    result = Some(!rule.negated);
+                }
+            }
+        }
+        result
+    }
+
+    // The components of `path` relative to the root, or `None` if it isn't
+    // under the root or isn't valid UTF-8.
+    fn components(&self, path: &Path) -> Option<Vec<~str>> {
+        let relative = if path.is_absolute() {
+            match path.path_relative_from(&self.root) {
+                Some(relative) => relative,
+                None => return None
+            }
+        } else {
+            path.clone()
+        };
+        let mut components = Vec::new();
+        // FIXME (#9639): This needs to handle non-utf8 paths
+        for component in relative.str_components() {
+            match component {
+                Some(".") => {}
+                Some("..") | None => return None,
+                Some(component) => components.push(component.to_owned())
+            }
+        }
+        Some(components)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Gitignore;
+    use std::io::{fs, File, TempDir, UserRWX};
+
+    fn test_ignore() -> Gitignore {
+        let mut ignore = Gitignore::new(Path::new("/root"));
+        ignore.add_rules(&Path::new("/root"), "\
+# build products
+*.o
+/target/
+!important.o
+docs/*.html
+\\#notes
+trailing   \n\
+escaped\\ \n\
+**/gen/**/*.rs
+");
+        ignore.add_rules(&Path::new("sub"), "!*.o\nlocal\n");
+        ignore
+    }
+
+    #[test]
+    fn test_gitignore_rules() {
+        let ignore = test_ignore();
+        let ignored = |s: &str, is_dir| ignore.is_ignored(&Path::new(s), is_dir);
+
+        assert!(ignored("a.o", false));
+        assert!(ignored("deep/down/a.o", false));
+        assert!(!ignored("important.o", false));
+        assert!(!ignored("a.c", false));
+
+        // anchored to the root, and only directories
+        assert!(ignored("target", true));
+        assert!(!ignored("target", false));
+        assert!(!ignored("sub/target", true));
+        assert!(ignored("target/debug/lib.rs", false));
+
+        assert!(ignored("docs/index.html", false));
+        assert!(!ignored("docs/api/index.html", false));
+        assert!(!ignored("other/docs/index.html", false));
+
+        assert!(ignored("#notes", false));
+        assert!(ignored("trailing", false));
+        assert!(ignored("escaped ", false));
+        assert!(!ignored("escaped", false));
+
+        assert!(ignored("gen/a.rs", false));
+        assert!(ignored("x/gen/y/z/a.rs", false));
+        assert!(!ignored("x/gen.rs", false));
+
+        // rules of subdirectories take precedence
+        assert!(!ignored("sub/a.o", false));
+        assert!(ignored("sub/local", false));
+        assert!(ignored("sub/x/local", true));
+        assert!(!ignored("local", false));
+
+        // absolute paths, and paths outside the root
+        assert!(ignored("/root/x.o", false));
+        assert!(!ignored("/other/x.o", false));
+        assert!(!ignored("../x.o", false));
+        assert!(!ignored("/root", true));
+    }
+
+    #[test]
+    fn test_gitignore_recursive_contents() {
+        let mut ignore = Gitignore::new(Path::new("/root"));
+        ignore.add_rules(&Path::new("/root"), "build/**\n");
+        assert!(ignore.is_ignored(&Path::new("build/a.o"), false));
+        assert!(ignore.is_ignored(&Path::new("build/debug/deps/a.o"), false));
+        assert!(ignore.is_ignored(&Path::new("build/debug"), true));
+        assert!(!ignore.is_ignored(&Path::new("builds/a.o"), false));
+        assert!(!ignore.is_ignored(&Path::new("sub/build/a.o"), false));
+    }
+
+    #[test]
+    fn test_gitignore_no_reinclude() {
+        let mut ignore = Gitignore::new(Path::new("/root"));
+        ignore.add_rules(&Path::new("/root"), "build\n!build/keep\n");
+        assert!(ignore.is_ignored(&Path::new("build/keep"), false));
+        ignore.add_rules(&Path::new("/root"), "!build\n");
+        assert!(!ignore.is_ignored(&Path::new("build/keep"), false));
+    }
+
+    #[test]
+    fn test_gitignore_load() {
+        let dir = TempDir::new("test_gitignore_load").unwrap();
+        let root = dir.path();
+        fs::mkdir(&root.join("src"), UserRWX).unwrap();
+        fs::mkdir(&root.join("out"), UserRWX).unwrap();
+        File::create(&root.join(".gitignore")).write_str("out/\n*.log\n").unwrap();
+        File::create(&root.join("src/.gitignore")).write_str("!keep.log\n").unwrap();
+        // never read, as its directory is ignored
+        File::create(&root.join("out/.gitignore")).write_str("!*\n").unwrap();
+
+        let ignore = Gitignore::load(root.clone()).unwrap();
+        assert!(ignore.is_ignored(&root.join("out"), true));
+        assert!(ignore.is_ignored(&root.join("out/x"), false));
+        assert!(ignore.is_ignored(&root.join("a.log"), false));
+        assert!(ignore.is_ignored(&root.join("src/a.log"), false));
+        assert!(!ignore.is_ignored(&root.join("src/keep.log"), false));
+        assert!(!ignore.is_ignored(&root.join("src/lib.rs"), false));
+    }
+}
@@ -21,6 +21,10 @@
  * For consistency across platforms, and for Windows support, this module
  * is implemented entirely in Rust rather than deferring to the libc
  * `glob`/`fnmatch` functions.
+ *
+ * A `PatternSet` matches paths against a whole list of patterns, some of
+ * which may be negated, and `Gitignore` decides which paths are ignored by
+ * a tree of `.gitignore` files.
  */
 
 #![crate_id = "glob#0.11-pre"]
@@ -33,12 +37,20 @@
 
 #![deny(deprecated_owned_vector)]
 
+extern crate collections;
+
 use std::cell::Cell;
 use std::{cmp, os, path};
 use std::io::fs;
 use std::path::is_sep;
 use std::strbuf::StrBuf;
 
+pub use gitignore::Gitignore;
+pub use set::PatternSet;
+
+mod gitignore;
+mod set;
+
 /**
  * An iterator that yields Paths from the filesystem that match a particular
  * pattern - see the `glob` function for more details.
@@ -159,6 +171,23 @@ impl Iterator<Path> for Paths {
             }
             let ref pattern = *self.dir_patterns.get(idx);
 
+            if pattern.is_recursive() {
+                // `**` matches every entry, except hidden ones if asked to
+                if self.options.require_literal_leading_dot &&
+                   path.filename_str().map_or(true, |s| s.starts_with(".")) {
+                    continue;
+                }
+                if path.is_dir() {
+                    fill_todo(&mut self.todo, self.dir_patterns.as_slice(),
+                              idx, &path, self.options);
+                }
+                if idx == self.dir_patterns.len() - 1 &&
+                   (!self.require_dir || path.is_dir()) {
+                    return Some(path);
+                }
+                continue;
+            }
+
             if pattern.matches_with(match path.filename_str() {
                 // this ugly match needs to go here to avoid a borrowck error
                 None => {
@@ -208,8 +237,10 @@ enum PatternToken {
     Char(char),
     AnyChar,
     AnySequence,
+    AnyRecursiveSequence,
     AnyWithin(Vec<CharSpecifier> ),
-    AnyExcept(Vec<CharSpecifier> )
+    AnyExcept(Vec<CharSpecifier> ),
+    AnyOf(Vec<Vec<PatternToken> >)
 }
 
 #[deriving(Clone, Eq, TotalEq, Ord, TotalOrd, Hash)]
@@ -245,6 +276,17 @@ impl Pattern {
      *
      * When a `[` does not have a closing `]` before the end of the string then
      * the `[` will be treated literally.
+     *
+     * A `**` which makes up a whole path component matches any number of
+     * directories, including none, regardless of `require_literal_separator`:
+     * `src/**/*.rs` matches `src/lib.rs` and `src/a/b/mod.rs`, and `src/**`
+     * matches everything under `src`. Anywhere else `**` is the same as `*`.
+     *
+     * `{a,b,c}` matches any one of the comma separated alternatives, which
+     * are patterns themselves and may contain further braces. A `{` without
+     * a matching `}`, or whose alternatives are not separated by commas, is
+     * treated literally. Note that `glob` matches each path component
+     * separately, so there the alternatives may not contain separators.
      */
     pub fn new(pattern: &str) -> Pattern {
 
@@ -259,11 +301,39 @@ impl Pattern {
                     i += 1;
                 }
                 '*' => {
-                    // *, **, ***, ****, ... are all equivalent
+                    // *, **, ***, ****, ... are all equivalent, unless they
+                    // make up a whole path component
+                    let start = i;
                     while i < chars.len() && *chars.get(i) == '*' {
                         i += 1;
                     }
-                    tokens.push(AnySequence);
+                    let whole_component = (start == 0 || is_sep(*chars.get(start - 1))) &&
+                                          (i == chars.len() || is_sep(*chars.get(i)));
+                    if i - start > 1 && whole_component {
+                        // the separator is part of the match, so that `a/**/b`
+                        // also matches `a/b`
+                        if i < chars.len() {
+                            i += 1;
+                        }
+                        tokens.push(AnyRecursiveSequence);
+                    } else {
+                        tokens.push(AnySequence);
+                    }
+                }
+                '{' => {
+                    match parse_alternatives(chars.slice_from(i)) {
+                        Some((alternatives, len)) => {
+                            let alternatives = alternatives.iter().map(|s| {
+                                Pattern::new(*s).tokens
+                            }).collect();
+                            tokens.push(AnyOf(alternatives));
+                            i += len;
+                        }
+                        None => {
+                            tokens.push(Char('{'));
+                            i += 1;
+                        }
+                    }
                 }
                 '[' => {
 
@@ -315,7 +385,7 @@ impl Pattern {
         for c in s.chars() {
             match c {
                 // note that ! does not need escaping because it is only special inside brackets
-                '?' | '*' | '[' | ']' => {
+                '?' | '*' | '[' | ']' | '{' | '}' => {
                     escaped.push_char('[');
                     escaped.push_char(c);
                     escaped.push_char(']');
@@ -361,7 +431,7 @@ impl Pattern {
      * Return if the given `str` matches this `Pattern` using the specified match options.
      */
     pub fn matches_with(&self, str: &str, options: MatchOptions) -> bool {
-        self.matches_from(None, str, 0, options) == Match
+        matches_from(self.tokens.as_slice(), None, str, options) == Match
     }
 
     /**
@@ -375,85 +445,8 @@ impl Pattern {
         })
     }
 
-    fn matches_from(&self,
-                    prev_char: Option<char>,
-                    mut file: &str,
-                    i: uint,
-                    options: MatchOptions) -> MatchResult {
-
-        let prev_char = Cell::new(prev_char);
-
-        let require_literal = |c| {
-            (options.require_literal_separator && is_sep(c)) ||
-            (options.require_literal_leading_dot && c == '.'
-             && is_sep(prev_char.get().unwrap_or('/')))
-        };
-
-        for (ti, token) in self.tokens.slice_from(i).iter().enumerate() {
-            match *token {
-                AnySequence => {
-                    loop {
-                        match self.matches_from(prev_char.get(), file, i + ti + 1, options) {
-                            SubPatternDoesntMatch => (), // keep trying
-                            m => return m,
-                        }
-
-                        if file.is_empty() {
-                            return EntirePatternDoesntMatch;
-                        }
-
-                        let (some_c, next) = file.slice_shift_char();
-                        if require_literal(some_c.unwrap()) {
-                            return SubPatternDoesntMatch;
-                        }
-                        prev_char.set(some_c);
-                        file = next;
-                    }
-                }
-                _ => {
-                    if file.is_empty() {
-                        return EntirePatternDoesntMatch;
-                    }
-
-                    let (some_c, next) = file.slice_shift_char();
-                    let c = some_c.unwrap();
-                    let matches = match *token {
-                        AnyChar => {
-                            !require_literal(c)
-                        }
-                        AnyWithin(ref specifiers) => {
-                            !require_literal(c) &&
-                                in_char_specifiers(specifiers.as_slice(),
-                                                   c,
-                                                   options)
-                        }
-                        AnyExcept(ref specifiers) => {
-                            !require_literal(c) &&
-                                !in_char_specifiers(specifiers.as_slice(),
-                                                    c,
-                                                    options)
-                        }
-                        Char(c2) => {
-                            chars_eq(c, c2, options.case_sensitive)
-                        }
-                        AnySequence => {
-                            unreachable!()
-                        }
-                    };
-                    if !matches {
-                        return SubPatternDoesntMatch;
-                    }
-                    prev_char.set(some_c);
-                    file = next;
-                }
-            }
-        }
-
-        if file.is_empty() {
-            Match
-        } else {
-            SubPatternDoesntMatch
-        }
+    fn is_recursive(&self) -> bool {
+        self.tokens.len() == 1 && *self.tokens.get(0) == AnyRecursiveSequence
     }
 
 }
@@ -488,6 +481,20 @@ fn fill_todo(todo: &mut Vec<(Path, uint)>, patterns: &[Pattern], idx: uint, path
 
     let pattern = &patterns[idx];
 
+    if pattern.is_recursive() {
+        // `**` matches no directories at all, in which case the next pattern
+        // applies to the children of `path`, or some number of directories,
+        // in which case the iterator matches the children against `**` again.
+        if idx + 1 < patterns.len() {
+            fill_todo(todo, patterns, idx + 1, path, options);
+        }
+        match list_dir_sorted(path) {
+            Some(entries) => todo.extend(entries.move_iter().map(|x|(x, idx))),
+            None => {}
+        }
+        return;
+    }
+
     match pattern_as_str(pattern) {
         Some(s) => {
             // This pattern component doesn't have any metacharacters, so we
@@ -524,6 +531,165 @@ fn fill_todo(todo: &mut Vec<(Path, uint)>, patterns: &[Pattern], idx: uint, path
     }
 }
 
+fn matches_from(tokens: &[PatternToken],
+                prev_char: Option<char>,
+                mut file: &str,
+                options: MatchOptions) -> MatchResult {
+
+    let prev_char = Cell::new(prev_char);
+
+    let require_literal = |c| {
+        (options.require_literal_separator && is_sep(c)) ||
+        (options.require_literal_leading_dot && c == '.'
+         && is_sep(prev_char.get().unwrap_or('/')))
+    };
+
+    for (ti, token) in tokens.iter().enumerate() {
+        match *token {
+            AnySequence => {
+                loop {
+                    match matches_from(tokens.slice_from(ti + 1), prev_char.get(), file, options) {
+                        SubPatternDoesntMatch => (), // keep trying
+                        m => return m,
+                    }
+
+                    if file.is_empty() {
+                        return EntirePatternDoesntMatch;
+                    }
+
+                    let (some_c, next) = file.slice_shift_char();
+                    if require_literal(some_c.unwrap()) {
+                        return SubPatternDoesntMatch;
+                    }
+                    prev_char.set(some_c);
+                    file = next;
+                }
+            }
+            AnyRecursiveSequence => {
+                // A trailing `**` matches whatever is left, as long as none of
+                // its components is hidden when that matters.
+                if ti + 1 == tokens.len() {
+                    if options.require_literal_leading_dot {
+                        let mut prev = prev_char.get().unwrap_or('/');
+                        for c in file.chars() {
+                            if c == '.' && is_sep(prev) {
+                                return SubPatternDoesntMatch;
+                            }
+                            prev = c;
+                        }
+                    }
+                    return Match;
+                }
+
+                // Only try the rest of the pattern at the start of a path
+                // component, separators can be skipped over.
+                loop {
+                    if prev_char.get().map_or(true, is_sep) {
+                        if matches_from(tokens.slice_from(ti + 1), prev_char.get(),
+                                        file, options) == Match {
+                            return Match;
+                        }
+                    }
+
+                    if file.is_empty() {
+                        return SubPatternDoesntMatch;
+                    }
+
+                    let (some_c, next) = file.slice_shift_char();
+                    if options.require_literal_leading_dot && some_c == Some('.') &&
+                       is_sep(prev_char.get().unwrap_or('/')) {
+                        return SubPatternDoesntMatch;
+                    }
+                    prev_char.set(some_c);
+                    file = next;
+                }
+            }
+            AnyOf(ref alternatives) => {
+                for alternative in alternatives.iter() {
+                    let mut rest = alternative.clone();
+                    rest.push_all(tokens.slice_from(ti + 1));
+                    if matches_from(rest.as_slice(), prev_char.get(), file, options) == Match {
+                        return Match;
+                    }
+                }
+                return SubPatternDoesntMatch;
+            }
+            _ => {
+                if file.is_empty() {
+                    return EntirePatternDoesntMatch;
+                }
+
+                let (some_c, next) = file.slice_shift_char();
+                let c = some_c.unwrap();
+                let matches = match *token {
+                    AnyChar => {
+                        !require_literal(c)
+                    }
+                    AnyWithin(ref specifiers) => {
+                        !require_literal(c) &&
+                            in_char_specifiers(specifiers.as_slice(),
+                                               c,
+                                               options)
+                    }
+                    AnyExcept(ref specifiers) => {
+                        !require_literal(c) &&
+                            !in_char_specifiers(specifiers.as_slice(),
+                                                c,
+                                                options)
+                    }
+                    Char(c2) => {
+                        chars_eq(c, c2, options.case_sensitive)
+                    }
+                    AnySequence | AnyRecursiveSequence | AnyOf(..) => {
+                        unreachable!()
+                    }
+                };
+                if !matches {
+                    return SubPatternDoesntMatch;
+                }
+                prev_char.set(some_c);
+                file = next;
+            }
+        }
+    }
+
+    if file.is_empty() {
+        Match
+    } else {
+        SubPatternDoesntMatch
+    }
+}
+
+// Splits the alternatives of a brace expression at the start of `s`, returning
+// them along with the length of the whole expression, or `None` if the brace
+// isn't closed or there is only one alternative.
+fn parse_alternatives(s: &[char]) -> Option<(Vec<~str>, uint)> {
+    let mut alternatives = Vec::new();
+    let mut depth = 0;
+    let mut start = 1;
+    for (i, &c) in s.iter().enumerate() {
+        match c {
+            '{' => depth += 1,
+            ',' if depth == 1 => {
+                alternatives.push(s.slice(start, i).iter().map(|&c| c).collect());
+                start = i + 1;
+            }
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    if alternatives.is_empty() {
+                        return None;
+                    }
+                    alternatives.push(s.slice(start, i).iter().map(|&c| c).collect());
+                    return Some((alternatives, i + 1));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
 fn parse_char_specifiers(s: &[char]) -> Vec<CharSpecifier> {
     let mut cs = Vec::new();
     let mut i = 0;
@@ -650,6 +816,7 @@ impl MatchOptions {
 #[cfg(test)]
 mod test {
     use std::os;
+    use std::io::{fs, File, TempDir, UserRWX};
     use super::{glob, Pattern, MatchOptions};
 
     #[test]
@@ -679,6 +846,98 @@ mod test {
         assert!(Pattern::new("a*b[xyz]c*d").matches("abxcdbxcddd"));
     }
 
+    #[test]
+    fn test_recursive_wildcards() {
+        let pat = Pattern::new("src/**/*.rs");
+        assert!(pat.matches("src/lib.rs"));
+        assert!(pat.matches("src/a/lib.rs"));
+        assert!(pat.matches("src/a/b/c/lib.rs"));
+        assert!(!pat.matches("src.rs"));
+        assert!(!pat.matches("other/src/lib.rs"));
+
+        let pat = Pattern::new("**/test");
+        assert!(pat.matches("test"));
+        assert!(pat.matches("a/b/test"));
+        assert!(!pat.matches("a/btest"));
+
+        let pat = Pattern::new("a/**");
+        assert!(pat.matches("a/b"));
+        assert!(pat.matches("a/b/c"));
+        assert!(!pat.matches("a"));
+        assert!(!pat.matches("ab/c"));
+        assert!(!Pattern::new("a/**").matches_with("a/b/.c", MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: false,
+            require_literal_leading_dot: true
+        }));
+
+        // `**` crosses separators even when `*` may not
+        let options = MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: true,
+            require_literal_leading_dot: true
+        };
+        assert!(Pattern::new("**/*.rs").matches_with("a/b/lib.rs", options));
+        assert!(!Pattern::new("*/*.rs").matches_with("a/b/lib.rs", options));
+        assert!(!Pattern::new("**/*.rs").matches_with("a/.b/lib.rs", options));
+
+        // not a whole path component, so the same as `*`
+        assert!(Pattern::new("a**/b").matches("a/x/b"));
+        assert!(!Pattern::new("a**/b").matches_with("a/x/b", options));
+    }
+
+    #[test]
+    fn test_alternatives() {
+        let pat = Pattern::new("*.{rs,md}");
+        assert!(pat.matches("lib.rs"));
+        assert!(pat.matches("README.md"));
+        assert!(!pat.matches("lib.c"));
+
+        let pat = Pattern::new("{src,tests}/{*.rs,{a,b}?}");
+        assert!(pat.matches("src/lib.rs"));
+        assert!(pat.matches("tests/ax"));
+        assert!(pat.matches("tests/bx"));
+        assert!(!pat.matches("tests/cx"));
+        assert!(!pat.matches("bench/lib.rs"));
+
+        let pat = Pattern::new("a{,b}c");
+        assert!(pat.matches("ac"));
+        assert!(pat.matches("abc"));
+
+        // braces without alternatives are literal
+        assert!(Pattern::new("{a}").matches("{a}"));
+        assert!(Pattern::new("{a,b").matches("{a,b"));
+        assert!(Pattern::new("a}").matches("a}"));
+
+        assert_eq!(Pattern::escape("{a,b}"), "[{]a,b[}]".to_owned());
+    }
+
+    #[test]
+    fn test_glob_recursive() {
+        let dir = TempDir::new("test_glob_recursive").unwrap();
+        let root = dir.path();
+        fs::mkdir(&root.join("a"), UserRWX).unwrap();
+        fs::mkdir(&root.join("a/b"), UserRWX).unwrap();
+        for name in ["x.rs", "a/y.rs", "a/b/z.rs", "a/b/z.md"].iter() {
+            File::create(&root.join(*name)).unwrap();
+        }
+
+        let found = |pattern: &str| {
+            let mut paths = glob(root.join(pattern).as_str().unwrap()).map(|p| {
+                p.path_relative_from(root).unwrap().as_str().unwrap().to_owned()
+            }).collect::<Vec<~str>>();
+            paths.sort();
+            paths
+        };
+        assert_eq!(found("**/*.rs"),
+                   vec!("a/b/z.rs".to_owned(), "a/y.rs".to_owned(), "x.rs".to_owned()));
+        assert_eq!(found("a/**/z.*"), vec!("a/b/z.md".to_owned(), "a/b/z.rs".to_owned()));
+        assert_eq!(found("a/**"),
+                   vec!("a/b".to_owned(), "a/b/z.md".to_owned(), "a/b/z.rs".to_owned(),
+                        "a/y.rs".to_owned()));
+        assert_eq!(found("a/b/*.{md,txt}"), vec!("a/b/z.md".to_owned()));
+    }
+
     #[test]
     fn test_lots_of_files() {
         // this is a good test because it touches lots of differently named files
@@ -0,0 +1,257 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Matching a path against many patterns at once.
+
+use collections::HashMap;
+use std::path::is_sep;
+use std::strbuf::StrBuf;
+
+use {Pattern, MatchOptions, Char, AnySequence};
+
+/**
+ * A list of patterns compiled for matching each path against all of them, as
+ * in include or exclude lists.
+ *
+ * A pattern starting with `!` is negated. A path matches the set if the last
+ * pattern which matches it isn't negated, so `["*.rs", "!test.rs"]` matches
+ * all Rust source files except for `test.rs`. A pattern starting with a
+ * literal `!` can be written as `\!`.
+ *
+ * Patterns which match a single string, or are of the form `*.ext`, are
+ * looked up in hash tables rather than being tried one by one, so large sets
+ * of such patterns are cheap to match against.
+ *
+ * # Example
+ *
+ * ```rust
+ * use glob::PatternSet;
+ *
+ * let set = PatternSet::new(["*.o", "*.a", "!libfoo.a", "core"]);
+ * assert!(set.matches("main.o"));
+ * assert!(set.matches("core"));
+ * assert!(!set.matches("libfoo.a"));
+ * assert_eq!(set.matches_all("libfoo.a"), vec!(1, 2));
+ * ```
+ */
+#[deriving(Clone)]
+pub struct PatternSet {
+    patterns: Vec<Pattern>,
+    negated: Vec<bool>,
+    options: MatchOptions,
+    // Indexes of the patterns which only match one string, by that string
+    literals: HashMap<~str, Vec<uint>>,
+    // Indexes of the `*.ext` patterns, by what follows the last `.`
+    extensions: HashMap<~str, Vec<uint>>,
+    // Indexes of all other patterns
+    others: Vec<uint>,
+}
+
+// The string matched by a pattern without metacharacters.
+fn literal(pattern: &Pattern) -> Option<~str> {
+    let mut s = StrBuf::new();
+    for token in pattern.tokens.iter() {
+        match *token {
+            Char(c) if !is_sep(c) => s.push_char(c),
+            _ => return None
+        }
+    }
+    Some(s.into_owned())
+}
+
+// For a pattern made of `*` followed by literal characters including a `.`,
+// the characters after the last `.`.
+fn extension(pattern: &Pattern) -> Option<~str> {
+    let tokens = pattern.tokens.as_slice();
+    if tokens.len() < 2 || tokens[0] != AnySequence {
+        return None;
+    }
+    let mut suffix = StrBuf::new();
+    for token in tokens.slice_from(1).iter() {
+        match *token {
+            Char(c) if !is_sep(c) => suffix.push_char(c),
+            _ => return None
+        }
+    }
+    let suffix = suffix.as_slice();
+    suffix.rfind('.').map(|i| suffix.slice_from(i + 1).to_owned())
+}
+
+impl PatternSet {
+    /**
+     * Compile a set of patterns, which are matched using the default match
+     * options (i.e. `MatchOptions::new()`).
+     */
+    pub fn new(patterns: &[&str]) -> PatternSet {
+        PatternSet::new_with(patterns, MatchOptions::new())
+    }
+
+    /**
+     * Compile a set of patterns, which are matched using the specified match
+     * options.
+     */
+    pub fn new_with(patterns: &[&str], options: MatchOptions) -> PatternSet {
+        let mut set = PatternSet {
+            patterns: Vec::new(),
+            negated: Vec::new(),
+            options: options,
+            literals: HashMap::new(),
+            extensions: HashMap::new(),
+            others: Vec::new(),
+        };
+        for &pattern in patterns.iter() {
+            set.push(pattern);
+        }
+        set
+    }
+
+    /**
+     * Add a pattern to the end of the set, where it takes precedence over all
+     * the patterns before it.
+     */
+    pub fn push(&mut self, pattern: &str) {
+        let (pattern, negated) = if pattern.starts_with("!") {
+            (pattern.slice_from(1), true)
+        } else if pattern.starts_with("\\!") {
+            (pattern.slice_from(1), false)
+        } else {
+            (pattern, false)
+        };
+        let pattern = Pattern::new(pattern);
+        let idx = self.patterns.len();
+
+        // the lookup tables compare strings exactly
+        let keys = if self.options.case_sensitive {
+            (literal(&pattern), extension(&pattern))
+        } else {
+            (None, None)
+        };
+        match keys {
+            (Some(s), _) => self.literals.find_or_insert_with(s, |_| Vec::new()).push(idx),
+            (None, Some(ext)) => {
+                self.extensions.find_or_insert_with(ext, |_| Vec::new()).push(idx)
+            }
+            (None, None) => self.others.push(idx),
+        }
+
+        self.patterns.push(pattern);
+        self.negated.push(negated);
+    }
+
+    /// Return the number of patterns in the set.
+    pub fn len(&self) -> uint {
+        self.patterns.len()
+    }
+
+    /// Return true if the set contains no patterns.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    // The indexes of the patterns which might match `s`, in order.
+    fn candidates(&self, s: &str) -> Vec<uint> {
+        let mut candidates = self.others.clone();
+        match self.literals.find_equiv(&s) {
+            Some(idxs) => candidates.push_all(idxs.as_slice()),
+            None => {}
+        }
+        match s.rfind('.') {
+            Some(i) => match self.extensions.find_equiv(&s.slice_from(i + 1)) {
+                Some(idxs) => candidates.push_all(idxs.as_slice()),
+                None => {}
+            },
+            None => {}
+        }
+        candidates.sort();
+        candidates
+    }
+
+    /**
+     * Return the indexes of all the patterns which match `s`, in order,
+     * whether they are negated or not.
+     */
+    pub fn matches_all(&self, s: &str) -> Vec<uint> {
+        self.candidates(s).move_iter().filter(|&i| {
+            self.patterns.get(i).matches_with(s, self.options)
+        }).collect()
+    }
+
+    /**
+     * Return if the last pattern in the set which matches `s` isn't negated.
+     */
+    pub fn matches(&self, s: &str) -> bool {
+        for &i in self.candidates(s).iter().rev() {
+            if self.patterns.get(i).matches_with(s, self.options) {
+                return !*self.negated.get(i);
+            }
+        }
+        false
+    }
+
+    /**
+     * Return if the given `Path`, when converted to a `str`, matches the set.
+     */
+    pub fn matches_path(&self, path: &Path) -> bool {
+        // FIXME (#9639): This needs to handle non-utf8 paths
+        path.as_str().map_or(false, |s| self.matches(s))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PatternSet;
+    use MatchOptions;
+
+    #[test]
+    fn test_pattern_set() {
+        let set = PatternSet::new(["*.rs", "Makefile", "src/*/mod.rs", "*.tar.gz",
+                                   "!test.rs", "[ab]*.c", "\\!bang"]);
+        assert_eq!(set.len(), 7);
+        assert!(set.matches("lib.rs"));
+        assert!(set.matches("src/lib.rs"));
+        assert!(!set.matches("test.rs"));
+        assert!(set.matches("Makefile"));
+        assert!(!set.matches("makefile"));
+        assert!(set.matches("src/foo/mod.rs"));
+        assert!(set.matches("rust-0.11.tar.gz"));
+        assert!(!set.matches("rust-0.11.tar.bz2"));
+        assert!(set.matches("abc.c"));
+        assert!(!set.matches("cba.c"));
+        assert!(set.matches("!bang"));
+        assert!(!set.matches("bang"));
+
+        assert_eq!(set.matches_all("test.rs"), vec!(0, 4));
+        assert_eq!(set.matches_all("src/x/mod.rs"), vec!(0, 2));
+        assert_eq!(set.matches_all("none"), vec!());
+    }
+
+    #[test]
+    fn test_pattern_set_order() {
+        // later patterns take precedence
+        let set = PatternSet::new(["!*.rs", "*.rs"]);
+        assert!(set.matches("a.rs"));
+        let set = PatternSet::new(["*.rs", "!*.rs"]);
+        assert!(!set.matches("a.rs"));
+        assert!(!PatternSet::new([]).matches("a.rs"));
+    }
+
+    #[test]
+    fn test_pattern_set_options() {
+        let options = MatchOptions {
+            case_sensitive: false,
+            require_literal_separator: true,
+            require_literal_leading_dot: false
+        };
+        let set = PatternSet::new_with(["*.RS", "README"], options);
+        assert!(set.matches("lib.rs"));
+        assert!(set.matches("readme"));
+        assert!(!set.matches("src/lib.rs"));
+    }
+}
@@ -240,6 +240,7 @@ mod test {
         SchedPool::new(PoolConfig {
             threads: 1,
             event_loop_factory: basic::event_loop,
+            seed: None,
         })
     }
 
@@ -270,6 +271,7 @@ mod test {
         let mut pool = SchedPool::new(PoolConfig {
             threads: 2,
             event_loop_factory: basic::event_loop,
+            seed: None,
         });
 
         for _ in range(0, 20) {
@@ -0,0 +1,671 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A deterministic, single-threaded scheduler mode for testing concurrent code.
+//!
+//! Bugs in code using channels and locks are often dependent on the exact
+//! interleaving of tasks, which makes them hard to reproduce. This module
+//! provides an event loop and a pool configuration which run all tasks on one
+//! OS thread, drawing every scheduling decision from a pseudo-random generator
+//! seeded by the caller. Running the same program with the same seed will
+//! produce the same interleaving, so a failing seed can be replayed.
+//!
+//! Decisions are made at every scheduling point of the green runtime: when a
+//! task blocks on a channel or a lock, when a blocked task is woken up, and on
+//! every yield check. Additionally, `io::Timer` runs on virtual time: the clock
+//! only advances when no task is runnable, at which point it jumps straight to
+//! the next pending timer. Tests involving timeouts therefore run instantly and
+//! reproducibly. No other I/O is available in this mode.
+//!
+//! # Example
+//!
+//! ```rust
+//! use green::deterministic;
+//!
+//! for seed in range(0u64, 100) {
+//!     let result = deterministic::run(seed, proc() {
+//!         let (tx, rx) = channel();
+//!         spawn(proc() { tx.send(1); });
+//!         assert_eq!(rx.recv(), 1);
+//!     });
+//!     assert!(result.is_ok(), "failed with seed {}", seed);
+//! }
+//! ```
+
+use std::c_str::CString;
+use std::cast;
+use std::io;
+use std::io::IoResult;
+use std::io::net::addrinfo;
+use std::io::net::ip::SocketAddr;
+use std::io::process::ProcessConfig;
+use std::io::signal::Signum;
+use std::io::{FileMode, FileAccess, FileStat, FilePermission};
+use std::mem::replace;
+use std::rt::rtio::{EventLoop, IoFactory, RemoteCallback, PausableIdleCallback,
+                    Callback, CloseBehavior, RtioTimer, RtioTcpStream,
+                    RtioTcpListener, RtioUdpSocket, RtioUnixListener, RtioPipe,
+                    RtioFileStream, RtioProcess, RtioTTY, RtioSignal};
+use std::task::{TaskOpts, TaskResult};
+use std::unstable::sync::Exclusive;
+use libc;
+
+use {PoolConfig, SchedPool};
+
+/// Creates a new deterministic event loop.
+///
+/// This event loop runs its callbacks in the order in which they were
+/// registered and provides virtual time for timers. It is meant to be used as
+/// the `event_loop_factory` of a pool configured by `config`.
+pub fn event_loop() -> ~EventLoop:Send {
+    ~DeterministicLoop::new() as ~EventLoop:Send
+}
+
+/// Returns the configuration of a single-threaded deterministic pool which
+/// makes all of its scheduling decisions based on `seed`.
+pub fn config(seed: u64) -> PoolConfig {
+    PoolConfig {
+        threads: 1,
+        event_loop_factory: event_loop,
+        seed: Some(seed),
+    }
+}
+
+/// Runs `main` to completion in a deterministic pool seeded with `seed`.
+///
+/// This function returns once `main` and all the tasks it spawned have
+/// exited. The returned value is the result of `main`, so a failure can be
+/// reported along with the seed which triggered it.
+pub fn run(seed: u64, main: proc()) -> TaskResult {
+    let mut pool = SchedPool::new(config(seed));
+    let (tx, rx) = channel();
+    let mut opts = TaskOpts::new();
+    opts.notify_chan = Some(tx);
+    pool.spawn(opts, main);
+    let result = rx.recv();
+    pool.shutdown();
+    result
+}
+
+struct DeterministicLoop {
+    work: Vec<proc():Send>,             // pending work
+    idle: Option<*mut DeterministicPausable>, // only one is allowed
+    remotes: Vec<(uint, ~Callback:Send)>,
+    next_remote: uint,
+    messages: Exclusive<Vec<Message>>,
+    clock: VirtualClock,
+}
+
+enum Message { RunRemote(uint), RemoveRemote(uint) }
+
+/// The virtual time source of a deterministic event loop, in milliseconds.
+struct VirtualClock {
+    now: u64,
+    timers: Vec<PendingTimer>,
+    next_timer: uint,
+    next_seq: uint,
+}
+
+/// A timer which has been armed but hasn't fired yet.
+struct PendingTimer {
+    deadline: u64,
+    // Breaks ties between timers with the same deadline, in arming order.
+    seq: uint,
+    id: uint,
+    kind: TimerKind,
+}
+
+enum TimerKind {
+    Oneshot(Sender<()>),
+    Periodic(Sender<()>, u64),
+}
+
+impl DeterministicLoop {
+    fn new() -> DeterministicLoop {
+        DeterministicLoop {
+            work: vec![],
+            idle: None,
+            next_remote: 0,
+            remotes: vec![],
+            messages: Exclusive::new(vec![]),
+            clock: VirtualClock {
+                now: 0,
+                timers: vec![],
+                next_timer: 0,
+                next_seq: 0,
+            },
+        }
+    }
+
+    /// Process everything in the work queue (continually)
+    fn work(&mut self) {
+        while self.work.len() > 0 {
+            for work in replace(&mut self.work, vec![]).move_iter() {
+                work();
+            }
+        }
+    }
+
+    fn remote_work(&mut self) {
+        let messages = unsafe {
+            self.messages.with(|messages| {
+                if messages.len() > 0 {
+                    Some(replace(messages, vec![]))
+                } else {
+                    None
+                }
+            })
+        };
+        let messages = match messages {
+            Some(m) => m, None => return
+        };
+        for message in messages.iter() {
+            self.message(*message);
+        }
+    }
+
+    fn message(&mut self, message: Message) {
+        match message {
+            RunRemote(i) => {
+                match self.remotes.mut_iter().find(|& &(id, _)| id == i) {
+                    Some(&(_, ref mut f)) => f.call(),
+                    None => unreachable!()
+                }
+            }
+            RemoveRemote(i) => {
+                match self.remotes.iter().position(|&(id, _)| id == i) {
+                    Some(i) => { self.remotes.remove(i).unwrap(); }
+                    None => unreachable!()
+                }
+            }
+        }
+    }
+
+    /// Run the idle callback if one is registered
+    fn idle(&mut self) {
+        unsafe {
+            match self.idle {
+                Some(idle) => {
+                    if (*idle).active {
+                        (*idle).work.call();
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+
+    fn has_idle(&self) -> bool {
+        unsafe { self.idle.is_some() && (**self.idle.get_ref()).active }
+    }
+
+    fn has_messages(&self) -> bool {
+        unsafe { self.messages.with_imm(|messages| messages.len() > 0) }
+    }
+
+    /// Advances the virtual clock to the earliest pending timer and fires it.
+    fn fire_next_timer(&mut self) {
+        let next = {
+            let timers = &self.clock.timers;
+            range(0, timers.len()).min_by(|&i| {
+                let t = timers.get(i);
+                (t.deadline, t.seq)
+            })
+        };
+        let PendingTimer { deadline, id, kind, .. } = match next {
+            Some(i) => self.clock.timers.swap_remove(i).unwrap(),
+            None => return,
+        };
+        self.clock.now = deadline;
+        match kind {
+            Oneshot(tx) => { let _ = tx.send_opt(()); }
+            Periodic(tx, period) => {
+                // A periodic timer whose receiver is gone is simply dropped.
+                if tx.send_opt(()).is_ok() {
+                    self.clock.arm(id, deadline + period, Periodic(tx, period));
+                }
+            }
+        }
+    }
+}
+
+impl VirtualClock {
+    fn arm(&mut self, id: uint, deadline: u64, kind: TimerKind) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.timers.push(PendingTimer {
+            deadline: deadline,
+            seq: seq,
+            id: id,
+            kind: kind,
+        });
+    }
+
+    fn cancel(&mut self, id: uint) {
+        self.timers.retain(|t| t.id != id);
+    }
+}
+
+impl EventLoop for DeterministicLoop {
+    fn run(&mut self) {
+        while self.remotes.len() > 0 || self.work.len() > 0 || self.has_idle() {
+
+            self.work();
+            self.remote_work();
+
+            if self.has_idle() {
+                self.idle();
+                continue
+            }
+
+            // Nothing can run right now, so this is the moment at which time
+            // passes. Pending remote messages are handled first so that the
+            // order of events doesn't depend on how long they took to arrive.
+            if self.clock.timers.len() > 0 {
+                if !self.has_messages() {
+                    self.fire_next_timer();
+                }
+                continue
+            }
+
+            unsafe {
+                // We block here if we have no messages to process and we may
+                // receive a message at a later date
+                self.messages.hold_and_wait(|messages| {
+                    self.remotes.len() > 0 &&
+                        messages.len() == 0 &&
+                        self.work.len() == 0
+                })
+            }
+        }
+    }
+
+    fn callback(&mut self, f: proc():Send) {
+        self.work.push(f);
+    }
+
+    fn pausable_idle_callback(&mut self, cb: ~Callback:Send)
+        -> ~PausableIdleCallback:Send
+    {
+        let callback = ~DeterministicPausable::new(self, cb);
+        rtassert!(self.idle.is_none());
+        unsafe {
+            let cb_ptr: &*mut DeterministicPausable = cast::transmute(&callback);
+            self.idle = Some(*cb_ptr);
+        }
+        callback as ~PausableIdleCallback:Send
+    }
+
+    fn remote_callback(&mut self, f: ~Callback:Send) -> ~RemoteCallback:Send {
+        let id = self.next_remote;
+        self.next_remote += 1;
+        self.remotes.push((id, f));
+        ~DeterministicRemote::new(self.messages.clone(), id) as ~RemoteCallback:Send
+    }
+
+    fn io<'a>(&'a mut self) -> Option<&'a mut IoFactory> {
+        Some(self as &mut IoFactory)
+    }
+
+    fn has_active_io(&self) -> bool { false }
+}
+
+struct DeterministicRemote {
+    queue: Exclusive<Vec<Message>>,
+    id: uint,
+}
+
+impl DeterministicRemote {
+    fn new(queue: Exclusive<Vec<Message>>, id: uint) -> DeterministicRemote {
+        DeterministicRemote { queue: queue, id: id }
+    }
+}
+
+impl RemoteCallback for DeterministicRemote {
+    fn fire(&mut self) {
+        unsafe {
+            self.queue.hold_and_signal(|queue| {
+                queue.push(RunRemote(self.id));
+            })
+        }
+    }
+}
+
+impl Drop for DeterministicRemote {
+    fn drop(&mut self) {
+        unsafe {
+            self.queue.hold_and_signal(|queue| {
+                queue.push(RemoveRemote(self.id));
+            })
+        }
+    }
+}
+
+struct DeterministicPausable {
+    eloop: *mut DeterministicLoop,
+    work: ~Callback:Send,
+    active: bool,
+}
+
+impl DeterministicPausable {
+    fn new(eloop: &mut DeterministicLoop,
+           cb: ~Callback:Send) -> DeterministicPausable {
+        DeterministicPausable {
+            active: false,
+            work: cb,
+            eloop: eloop,
+        }
+    }
+}
+
+impl PausableIdleCallback for DeterministicPausable {
+    fn pause(&mut self) {
+        self.active = false;
+    }
+    fn resume(&mut self) {
+        self.active = true;
+    }
+}
+
+impl Drop for DeterministicPausable {
+    fn drop(&mut self) {
+        unsafe {
+            (*self.eloop).idle = None;
+        }
+    }
+}
+
+/// A timer running on the virtual clock of its event loop.
+///
+/// Arming the timer again cancels any previous request, like the timers of the
+/// other event loops do.
+struct VirtualTimer {
+    eloop: *mut DeterministicLoop,
+    id: uint,
+}
+
+impl VirtualTimer {
+    fn clock<'a>(&'a mut self) -> &'a mut VirtualClock {
+        unsafe { &mut (*self.eloop).clock }
+    }
+}
+
+impl RtioTimer for VirtualTimer {
+    fn sleep(&mut self, msecs: u64) {
+        self.oneshot(msecs).recv();
+    }
+
+    fn oneshot(&mut self, msecs: u64) -> Receiver<()> {
+        let (tx, rx) = channel();
+        let id = self.id;
+        let clock = self.clock();
+        clock.cancel(id);
+        let deadline = clock.now + msecs;
+        clock.arm(id, deadline, Oneshot(tx));
+        rx
+    }
+
+    fn period(&mut self, msecs: u64) -> Receiver<()> {
+        let (tx, rx) = channel();
+        let id = self.id;
+        let clock = self.clock();
+        clock.cancel(id);
+        let deadline = clock.now + msecs;
+        clock.arm(id, deadline, Periodic(tx, msecs));
+        rx
+    }
+}
+
+impl Drop for VirtualTimer {
+    fn drop(&mut self) {
+        let id = self.id;
+        self.clock().cancel(id);
+    }
+}
+
+fn unavailable<T>() -> IoResult<T> {
+    Err(io::standard_error(io::IoUnavailable))
+}
+
+// Only timers are supported, everything else would make the outcome of a run
+// depend on the outside world.
+impl IoFactory for DeterministicLoop {
+    fn tcp_connect(&mut self, _addr: SocketAddr,
+                   _timeout: Option<u64>) -> IoResult<~RtioTcpStream:Send> {
+        unavailable()
+    }
+    fn tcp_bind(&mut self, _addr: SocketAddr) -> IoResult<~RtioTcpListener:Send> {
+        unavailable()
+    }
+    fn udp_bind(&mut self, _addr: SocketAddr) -> IoResult<~RtioUdpSocket:Send> {
+        unavailable()
+    }
+    fn unix_bind(&mut self, _path: &CString)
+        -> IoResult<~RtioUnixListener:Send> {
+        unavailable()
+    }
+    fn unix_connect(&mut self, _path: &CString) -> IoResult<~RtioPipe:Send> {
+        unavailable()
+    }
+    fn get_host_addresses(&mut self, _host: Option<&str>,
+                          _servname: Option<&str>,
+                          _hint: Option<addrinfo::Hint>)
+        -> IoResult<~[addrinfo::Info]> {
+        unavailable()
+    }
+
+    fn fs_from_raw_fd(&mut self, _fd: libc::c_int, _close: CloseBehavior)
+        -> ~RtioFileStream:Send {
+        fail!("file descriptors are not available in deterministic mode")
+    }
+    fn fs_open(&mut self, _path: &CString, _fm: FileMode, _fa: FileAccess)
+        -> IoResult<~RtioFileStream:Send> {
+        unavailable()
+    }
+    fn fs_unlink(&mut self, _path: &CString) -> IoResult<()> { unavailable() }
+    fn fs_stat(&mut self, _path: &CString) -> IoResult<FileStat> {
+        unavailable()
+    }
+    fn fs_mkdir(&mut self, _path: &CString,
+                _mode: FilePermission) -> IoResult<()> {
+        unavailable()
+    }
+    fn fs_chmod(&mut self, _path: &CString,
+                _mode: FilePermission) -> IoResult<()> {
+        unavailable()
+    }
+    fn fs_rmdir(&mut self, _path: &CString) -> IoResult<()> { unavailable() }
+    fn fs_rename(&mut self, _path: &CString, _to: &CString) -> IoResult<()> {
+        unavailable()
+    }
+    fn fs_readdir(&mut self, _path: &CString, _flags: libc::c_int)
+        -> IoResult<Vec<Path>> {
+        unavailable()
+    }
+    fn fs_lstat(&mut self, _path: &CString) -> IoResult<FileStat> {
+        unavailable()
+    }
+    fn fs_chown(&mut self, _path: &CString, _uid: int, _gid: int)
+        -> IoResult<()> {
+        unavailable()
+    }
+    fn fs_readlink(&mut self, _path: &CString) -> IoResult<Path> {
+        unavailable()
+    }
+    fn fs_symlink(&mut self, _src: &CString, _dst: &CString) -> IoResult<()> {
+        unavailable()
+    }
+    fn fs_link(&mut self, _src: &CString, _dst: &CString) -> IoResult<()> {
+        unavailable()
+    }
+    fn fs_utime(&mut self, _src: &CString, _atime: u64, _mtime: u64)
+        -> IoResult<()> {
+        unavailable()
+    }
+
+    fn timer_init(&mut self) -> IoResult<~RtioTimer:Send> {
+        let id = self.clock.next_timer;
+        self.clock.next_timer += 1;
+        Ok(~VirtualTimer { eloop: self as *mut DeterministicLoop, id: id }
+               as ~RtioTimer:Send)
+    }
+    fn spawn(&mut self, _config: ProcessConfig)
+        -> IoResult<(~RtioProcess:Send, ~[Option<~RtioPipe:Send>])> {
+        unavailable()
+    }
+    fn kill(&mut self, _pid: libc::pid_t, _signal: int) -> IoResult<()> {
+        unavailable()
+    }
+    fn pipe_open(&mut self, _fd: libc::c_int) -> IoResult<~RtioPipe:Send> {
+        unavailable()
+    }
+    fn tty_open(&mut self, _fd: libc::c_int, _readable: bool)
+        -> IoResult<~RtioTTY:Send> {
+        unavailable()
+    }
+    fn signal(&mut self, _signal: Signum, _channel: Sender<Signum>)
+        -> IoResult<~RtioSignal:Send> {
+        unavailable()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::timer::Timer;
+    use std::task::TaskOpts;
+
+    use super::{run, config};
+    use SchedPool;
+
+    // Records the order in which a handful of tasks got to run.
+    fn trace(seed: u64) -> Vec<uint> {
+        let (tx, rx) = channel();
+        run(seed, proc() {
+            for i in range(0u, 5) {
+                let tx = tx.clone();
+                spawn(proc() {
+                    for _ in range(0, 3) {
+                        tx.send(i);
+                    }
+                });
+            }
+        }).unwrap();
+        rx.iter().collect()
+    }
+
+    #[test]
+    fn smoke() {
+        run(0, proc() {}).unwrap();
+    }
+
+    #[test]
+    fn failure_is_reported() {
+        assert!(run(0, proc() { fail!() }).is_err());
+    }
+
+    #[test]
+    fn same_seed_same_interleaving() {
+        for seed in range(0u64, 20) {
+            assert_eq!(trace(seed), trace(seed));
+        }
+    }
+
+    #[test]
+    fn seeds_explore_interleavings() {
+        let first = trace(0);
+        assert!(range(1u64, 50).any(|seed| trace(seed) != first));
+    }
+
+    #[test]
+    fn ping_pong() {
+        for seed in range(0u64, 20) {
+            run(seed, proc() {
+                let (tx1, rx1) = channel();
+                let (tx2, rx2) = channel();
+                spawn(proc() {
+                    for i in range(0, 10) {
+                        assert_eq!(rx1.recv(), i);
+                        tx2.send(i);
+                    }
+                });
+                for i in range(0, 10) {
+                    tx1.send(i);
+                    assert_eq!(rx2.recv(), i);
+                }
+            }).unwrap();
+        }
+    }
+
+    #[test]
+    fn virtual_time() {
+        run(0, proc() {
+            let mut timer = Timer::new().unwrap();
+            // An hour passes instantly
+            timer.sleep(60 * 60 * 1000);
+
+            let short = timer.oneshot(10);
+            let mut other = Timer::new().unwrap();
+            let long = other.oneshot(20);
+            assert_eq!(long.try_recv().is_err(), true);
+            short.recv();
+            long.recv();
+        }).unwrap();
+    }
+
+    #[test]
+    fn timers_fire_in_deadline_order() {
+        for seed in range(0u64, 10) {
+            let (tx, rx) = channel();
+            run(seed, proc() {
+                for &ms in [30u64, 10, 20].iter() {
+                    let tx = tx.clone();
+                    spawn(proc() {
+                        let mut timer = Timer::new().unwrap();
+                        timer.sleep(ms);
+                        tx.send(ms);
+                    });
+                }
+            }).unwrap();
+            assert_eq!(rx.iter().collect::<Vec<u64>>(), vec!(10, 20, 30));
+        }
+    }
+
+    #[test]
+    fn periodic_timer() {
+        run(0, proc() {
+            let mut timer = Timer::new().unwrap();
+            let rx = timer.periodic(5);
+            for _ in range(0, 10) {
+                rx.recv();
+            }
+        }).unwrap();
+    }
+
+    #[test]
+    fn rearming_cancels() {
+        run(0, proc() {
+            let mut timer = Timer::new().unwrap();
+            let first = timer.oneshot(5);
+            let second = timer.oneshot(10);
+            second.recv();
+            assert!(first.recv_opt().is_err());
+        }).unwrap();
+    }
+
+    #[test]
+    #[should_fail]
+    fn deterministic_pool_is_single_threaded() {
+        let mut cfg = config(0);
+        cfg.threads = 2;
+        let mut pool = SchedPool::new(cfg);
+        pool.spawn(TaskOpts::new(), proc() {});
+        pool.shutdown();
+    }
+}
@@ -237,6 +237,7 @@ mod message_queue;
 pub mod basic;
 pub mod context;
 pub mod coroutine;
+pub mod deterministic;
 pub mod sched;
 pub mod sleeper_list;
 pub mod stack;
@@ -341,6 +342,11 @@ pub struct PoolConfig {
     /// A factory function used to create new event loops. If this is not
     /// specified then the default event loop factory is used.
     pub event_loop_factory: fn() -> ~rtio::EventLoop:Send,
+    /// If specified, the pool is run in deterministic mode: every scheduling
+    /// decision is drawn from a pseudo-random generator seeded with this
+    /// value, so the same seed replays the same interleaving of tasks. This
+    /// requires `threads` to be 1. See the `deterministic` module.
+    pub seed: Option<u64>,
 }
 
 impl PoolConfig {
@@ -350,6 +356,7 @@ impl PoolConfig {
         PoolConfig {
             threads: rt::default_sched_threads(),
             event_loop_factory: basic::event_loop,
+            seed: None,
         }
     }
 }
@@ -368,6 +375,7 @@ pub struct SchedPool {
     factory: fn() -> ~rtio::EventLoop:Send,
     task_state: TaskState,
     tasks_done: Receiver<()>,
+    seed: Option<u64>,
 }
 
 /// This is an internal state shared among a pool of schedulers. This is used to
@@ -389,9 +397,12 @@ impl SchedPool {
 
         let PoolConfig {
             threads: nscheds,
-            event_loop_factory: factory
+            event_loop_factory: factory,
+            seed: seed
         } = config;
         assert!(nscheds > 0);
+        assert!(seed.is_none() || nscheds == 1,
+                "a deterministic pool must have exactly one scheduler");
 
         // The pool of schedulers that will be returned from this function
         let (p, state) = TaskState::new();
@@ -407,6 +418,7 @@ impl SchedPool {
             factory: factory,
             task_state: state,
             tasks_done: p,
+            seed: seed,
         };
 
         // Create a work queue for each scheduler, ntimes. Create an extra
@@ -433,6 +445,10 @@ impl SchedPool {
                                             pool.stealers.clone(),
                                             pool.sleepers.clone(),
                                             pool.task_state.clone());
+            match pool.seed {
+                Some(seed) => sched.make_deterministic(seed),
+                None => {}
+            }
             pool.handles.push(sched.make_handle());
             let sched = sched;
             pool.threads.push(Thread::start(proc() { sched.bootstrap(); }));
@@ -476,6 +492,8 @@ impl SchedPool {
     /// The scheduler spawned will participate in work stealing with all of the
     /// other schedulers currently in the scheduler pool.
     pub fn spawn_sched(&mut self) -> SchedHandle {
+        assert!(self.seed.is_none(),
+                "cannot spawn a scheduler into a deterministic pool");
         let (worker, stealer) = self.deque_pool.deque();
         self.stealers.push(stealer.clone());
 
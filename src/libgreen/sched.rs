@@ -91,6 +91,12 @@ pub struct Scheduler {
     /// A flag to tell the scheduler loop it needs to do some stealing
     /// in order to introduce randomness as part of a yield
     steal_for_yield: bool,
+    /// Whether this scheduler draws all of its scheduling decisions from
+    /// `rng` so that a run can be replayed from the same seed.
+    deterministic: bool,
+    /// The run queue used in deterministic mode. Unlike the work stealing
+    /// deque, tasks can be picked out of this queue at random.
+    run_queue: Vec<~GreenTask>,
 
     // n.b. currently destructors of an object are run in top-to-bottom in order
     //      of field declaration. Due to its nature, the pausable idle callback
@@ -165,6 +171,8 @@ impl Scheduler {
             idle_callback: None,
             yield_check_count: 0,
             steal_for_yield: false,
+            deterministic: false,
+            run_queue: Vec::new(),
             task_state: state,
         };
 
@@ -173,6 +181,19 @@ impl Scheduler {
         return sched;
     }
 
+    /// Switches this scheduler into deterministic mode.
+    ///
+    /// The scheduler's rng is reseeded from `seed`, and from then on every
+    /// choice of which runnable task to run next, whether a woken task
+    /// preempts its waker, and whether a yield check actually yields is drawn
+    /// from that rng. This must be called before the scheduler is bootstrapped,
+    /// and it is only meaningful for a pool with a single scheduler.
+    pub fn make_deterministic(&mut self, seed: u64) {
+        self.rng = seeded_sched_rng(seed);
+        self.yield_check_count = reset_yield_check(&mut self.rng);
+        self.deterministic = true;
+    }
+
     // FIXME: This may eventually need to be refactored so that
     // the scheduler itself doesn't have to call event_loop.run.
     // That will be important for embedding the runtime into external
@@ -461,6 +482,17 @@ impl Scheduler {
     // there, trying to steal from the remote work queues.
     fn find_work(&mut self) -> Option<~GreenTask> {
         rtdebug!("scheduler looking for work");
+        if self.deterministic {
+            // There is nobody to steal from, instead the next task is picked
+            // at random among all runnable tasks.
+            self.steal_for_yield = false;
+            let len = self.run_queue.len();
+            if len == 0 {
+                return self.work_queue.pop();
+            }
+            let index = self.rng.gen_range(0, len);
+            return self.run_queue.swap_remove(index);
+        }
         if !self.steal_for_yield {
             match self.work_queue.pop() {
                 Some(task) => {
@@ -580,7 +612,11 @@ impl Scheduler {
 
         // We push the task onto our local queue clone.
         assert!(!task.is_sched());
-        self.work_queue.push(task);
+        if self.deterministic {
+            self.run_queue.push(task);
+        } else {
+            self.work_queue.push(task);
+        }
         match self.idle_callback {
             Some(ref mut idle) => idle.resume(),
             None => {} // allow enqueuing before the scheduler starts
@@ -797,7 +833,13 @@ impl Scheduler {
         fail!("should never return!");
     }
 
-    pub fn run_task(~self, cur: ~GreenTask, next: ~GreenTask) {
+    pub fn run_task(mut ~self, cur: ~GreenTask, next: ~GreenTask) {
+        // In deterministic mode, whether the woken task runs before the
+        // current one is part of the interleaving being explored.
+        if self.deterministic && self.rng.gen() {
+            self.enqueue_task(next);
+            return cur.put_with_sched(self);
+        }
         let (sched, task) =
             self.process_task(cur, next, Scheduler::switch_task);
         task.put_with_sched(sched);
@@ -847,6 +889,17 @@ impl Scheduler {
             return cur.put_with_sched(self)
         }
 
+        // Deterministic schedulers treat every yield check as a possible
+        // preemption point so that interleavings are explored densely.
+        if self.deterministic {
+            if self.rng.gen() {
+                self.yield_now(cur);
+            } else {
+                cur.put_with_sched(self);
+            }
+            return
+        }
+
         // The number of times to do the yield check before yielding, chosen
         // arbitrarily.
         rtassert!(self.yield_check_count > 0);
@@ -1007,6 +1060,16 @@ fn new_sched_rng() -> XorShiftRng {
     SeedableRng::from_seed(seeds)
 }
 
+// Builds the rng of a deterministic scheduler. The two halves of the seed are
+// mixed with constants so that the xorshift state is never entirely zero.
+fn seeded_sched_rng(seed: u64) -> XorShiftRng {
+    use rand::SeedableRng;
+
+    let lo = seed as u32;
+    let hi = (seed >> 32) as u32;
+    SeedableRng::from_seed([lo, hi, lo ^ 0x9e3779b9, hi ^ 0x7f4a7c15])
+}
+
 #[cfg(test)]
 mod test {
     use rustuv;
@@ -1024,6 +1087,7 @@ mod test {
         SchedPool::new(PoolConfig {
             threads: 1,
             event_loop_factory: basic::event_loop,
+            seed: None,
         })
     }
 
@@ -1269,6 +1333,7 @@ mod test {
         let mut pool = SchedPool::new(PoolConfig {
             threads: 2,
             event_loop_factory: rustuv::event_loop,
+            seed: None,
         });
 
         // This is a regression test that when there are no schedulable tasks in
@@ -1420,6 +1485,7 @@ mod test {
         let mut pool = SchedPool::new(PoolConfig {
             threads: 2, // this must be > 1
             event_loop_factory: basic::event_loop,
+            seed: None,
         });
         pool.spawn(TaskOpts::new(), proc() {
             let (tx, rx) = channel();
@@ -494,6 +494,7 @@ mod tests {
         let mut pool = SchedPool::new(PoolConfig {
             threads: 1,
             event_loop_factory: ::rustuv::event_loop,
+            seed: None,
         });
         pool.spawn(opts, f);
         pool.shutdown();
@@ -235,6 +235,13 @@ impl Sub<BigUint, BigUint> for BigUint {
     }
 }
 
+// Below this many digits in the shorter operand, schoolbook multiplication
+// beats the divide-and-conquer algorithms.
+static KARATSUBA_THRESHOLD: uint = 32;
+// At and above this many digits in the shorter operand, Toom-3 beats
+// Karatsuba.
+static TOOM3_THRESHOLD: uint = 256;
+
 impl Mul<BigUint, BigUint> for BigUint {
     fn mul(&self, other: &BigUint) -> BigUint {
         if self.is_zero() || other.is_zero() { return Zero::zero(); }
@@ -243,6 +250,14 @@ impl Mul<BigUint, BigUint> for BigUint {
         if s_len == 1 { return mul_digit(other, self.data.as_slice()[0]);  }
         if o_len == 1 { return mul_digit(self,  other.data.as_slice()[0]); }
 
+        let min_len = cmp::min(s_len, o_len);
+        if min_len < KARATSUBA_THRESHOLD {
+            return mul_schoolbook(self.data.as_slice(), other.data.as_slice());
+        }
+        if min_len >= TOOM3_THRESHOLD {
+            return mul_toom3(self, other);
+        }
+
         // Using Karatsuba multiplication
         // (a1 * base + a0) * (b1 * base + b0)
         // = a1*b1 * base^2 +
@@ -283,6 +298,71 @@ impl Mul<BigUint, BigUint> for BigUint {
             return BigUint::new(prod);
         }
 
+        fn mul_schoolbook(a: &[BigDigit], b: &[BigDigit]) -> BigUint {
+            let mut prod = Vec::from_elem(a.len() + b.len(), ZERO_BIG_DIGIT);
+            for (i, &ai) in a.iter().enumerate() {
+                if ai == 0 { continue; }
+                let mut carry = 0;
+                for (j, &bj) in b.iter().enumerate() {
+                    // ai * bj + prod[i + j] + carry can't overflow a uint
+                    let (hi, lo) = BigDigit::from_uint(
+                        (ai as uint) * (bj as uint) + (*prod.get(i + j) as uint) + (carry as uint)
+                    );
+                    *prod.get_mut(i + j) = lo;
+                    carry = hi;
+                }
+                *prod.get_mut(i + b.len()) = carry;
+            }
+            return BigUint::new(prod);
+        }
+
+        // Toom-3 multiplication: split both operands into three pieces,
+        // evaluate the resulting polynomials at 0, 1, -1, -2 and infinity,
+        // multiply pointwise and interpolate. The interpolation steps follow
+        // Bodrato's sequence, and all the divisions in it are exact.
+        fn mul_toom3(a: &BigUint, b: &BigUint) -> BigUint {
+            let k = (cmp::max(a.data.len(), b.data.len()) + 2) / 3;
+            let split = |n: &BigUint| {
+                let (hi, lo) = cut_at(n, k);
+                let (hi, mid) = cut_at(&hi, k);
+                (BigInt::from_biguint(Plus, lo),
+                 BigInt::from_biguint(Plus, mid),
+                 BigInt::from_biguint(Plus, hi))
+            };
+            let (a0, a1, a2) = split(a);
+            let (b0, b1, b2) = split(b);
+
+            let two: BigInt = FromPrimitive::from_uint(2).unwrap();
+            let three: BigInt = FromPrimitive::from_uint(3).unwrap();
+
+            let p = a0 + a2;
+            let (pa1, pam1) = (p + a1, p - a1);
+            let pam2 = (pam1 + a2) * two - a0;
+            let q = b0 + b2;
+            let (qb1, qbm1) = (q + b1, q - b1);
+            let qbm2 = (qbm1 + b2) * two - b0;
+
+            let r0 = a0 * b0;
+            let r1 = pa1 * qb1;
+            let rm1 = pam1 * qbm1;
+            let rm2 = pam2 * qbm2;
+            let rinf = a2 * b2;
+
+            let mut c3 = (rm2 - r1) / three;
+            let mut c1 = (r1 - rm1) / two;
+            let mut c2 = rm1 - r0;
+            c3 = (c2 - c3) / two + rinf * two;
+            c2 = c2 + c1 - rinf;
+            c1 = c1 - c3;
+
+            let unsigned = |n: BigInt| n.to_biguint().unwrap();
+            return unsigned(r0)
+                + unsigned(c1).shl_unit(k)
+                + unsigned(c2).shl_unit(2 * k)
+                + unsigned(c3).shl_unit(3 * k)
+                + unsigned(rinf).shl_unit(4 * k);
+        }
+
         #[inline]
         fn cut_at(a: &BigUint, n: uint) -> (BigUint, BigUint) {
             let mid = cmp::min(a.data.len(), n);
@@ -385,6 +465,10 @@ impl Integer for BigUint {
             Greater => {} // Do nothing
         }
 
+        if other.data.len() == 1 {
+            return div_rem_digit(self, other.data.as_slice()[0]);
+        }
+
         let mut shift = 0;
         let mut n = *other.data.last().unwrap();
         while n < (1 << BigDigit::bits - 2) {
@@ -649,21 +733,42 @@ impl ToStrRadix for BigUint {
         }
         return fill_concat(convert_base(self, base).as_slice(), radix, max_len);
 
+        // Convert to digits in `base`, least significant first, by
+        // divide-and-conquer: split the number in two by dividing by
+        // base^(2^k), which needs far fewer operations on big numbers than
+        // peeling off one digit at a time.
         fn convert_base(n: &BigUint, base: uint) -> Vec<BigDigit> {
-            let divider    = FromPrimitive::from_uint(base).unwrap();
-            let mut result = Vec::new();
-            let mut m      = n.clone();
-            while m >= divider {
-                let (d, m0) = m.div_mod_floor(&divider);
-                result.push(m0.to_uint().unwrap() as BigDigit);
-                m = d;
+            if n.is_zero() { return Vec::new(); }
+
+            // powers[k] is base^(2^k); stop at the first one larger than n
+            let mut powers: Vec<BigUint> = vec!(FromPrimitive::from_uint(base).unwrap());
+            while *powers.last().unwrap() <= *n {
+                let next = {
+                    let last = powers.last().unwrap();
+                    *last * *last
+                };
+                powers.push(next);
             }
-            if !m.is_zero() {
-                result.push(m.to_uint().unwrap() as BigDigit);
+
+            let mut result = Vec::new();
+            convert(n, powers.len() - 1, powers.as_slice(), &mut result);
+            while result.last() == Some(&ZERO_BIG_DIGIT) {
+                result.pop();
             }
             return result;
         }
 
+        // Push exactly 2^k digits of n, which is less than base^(2^k).
+        fn convert(n: &BigUint, k: uint, powers: &[BigUint], result: &mut Vec<BigDigit>) {
+            if k == 0 {
+                result.push(n.to_uint().unwrap() as BigDigit);
+                return;
+            }
+            let (hi, lo) = n.div_rem(&powers[k - 1]);
+            convert(&lo, k - 1, powers, result);
+            convert(&hi, k - 1, powers, result);
+        }
+
         fn fill_concat(v: &[BigDigit], radix: uint, l: uint) -> ~str {
             if v.is_empty() { return "0".to_owned() }
             let mut s = StrBuf::with_capacity(v.len() * l);
@@ -713,33 +818,100 @@ impl BigUint {
             None => { return None; }
         };
 
-        let mut end             = buf.len();
-        let mut n: BigUint      = Zero::zero();
-        let mut power: BigUint  = One::one();
+        // Parse the digits in chunks which fit in a machine word, least
+        // significant first.
+        let mut parts = Vec::with_capacity(buf.len() / unit_len + 1);
+        let mut end = buf.len();
         loop {
             let start = cmp::max(end, unit_len) - unit_len;
             match uint::parse_bytes(buf.slice(start, end), radix) {
                 Some(d) => {
                     let d: Option<BigUint> = FromPrimitive::from_uint(d);
                     match d {
-                        Some(d) => {
-                            // FIXME(#5992): assignment operator overloads
-                            // n += d * power;
-                            n = n + d * power;
-                        }
+                        Some(d) => parts.push(d),
                         None => { return None; }
                     }
                 }
                 None => { return None; }
             }
             if end <= unit_len {
-                return Some(n);
+                break;
             }
             end -= unit_len;
-            // FIXME(#5992): assignment operator overloads
-            // power *= base_num;
-            power = power * base_num;
         }
+
+        // Then combine neighbouring parts pairwise, so that most of the work
+        // is done by a few multiplications of large, similarly sized numbers.
+        let mut power: BigUint = base_num;
+        while parts.len() > 1 {
+            let mut combined = Vec::with_capacity((parts.len() + 1) / 2);
+            let mut iter = parts.move_iter();
+            loop {
+                match (iter.next(), iter.next()) {
+                    (Some(lo), Some(hi)) => combined.push(lo + hi * power),
+                    (Some(lo), None) => combined.push(lo),
+                    _ => break
+                }
+            }
+            parts = combined;
+            if parts.len() > 1 {
+                power = power * power;
+            }
+        }
+        parts.pop()
+    }
+
+    /// Calculates `self` raised to the power `exponent`, modulo `modulus`.
+    ///
+    /// # Failure
+    ///
+    /// Fails if `modulus` is zero.
+    pub fn modpow(&self, exponent: &BigUint, modulus: &BigUint) -> BigUint {
+        assert!(!modulus.is_zero(), "BigUint::modpow: modulus is zero");
+
+        // Left-to-right binary exponentiation
+        let mut result: BigUint = One::one();
+        let base = *self % *modulus;
+        for i in range(0, exponent.bits()).rev() {
+            result = (result * result) % *modulus;
+            let digit = exponent.data.as_slice()[i / BigDigit::bits];
+            if (digit >> (i % BigDigit::bits)) & 1 == 1 {
+                result = (result * base) % *modulus;
+            }
+        }
+        result % *modulus
+    }
+
+    /// Calculates the inverse of `self` modulo `modulus`: the number `x` in
+    /// `[0, modulus)` such that `self * x` is 1 modulo `modulus`. Returns
+    /// `None` if there is no such number, which is the case unless `self`
+    /// and `modulus` are coprime.
+    ///
+    /// # Failure
+    ///
+    /// Fails if `modulus` is zero.
+    pub fn modinv(&self, modulus: &BigUint) -> Option<BigUint> {
+        assert!(!modulus.is_zero(), "BigUint::modinv: modulus is zero");
+
+        // Extended Euclid's algorithm, tracking only the coefficient of self
+        let m = BigInt::from_biguint(Plus, modulus.clone());
+        let (mut r0, mut r1) = (m.clone(), BigInt::from_biguint(Plus, *self % *modulus));
+        let (mut t0, mut t1): (BigInt, BigInt) = (Zero::zero(), One::one());
+        while !r1.is_zero() {
+            let q = r0 / r1;
+            let r2 = r0 - q * r1;
+            let t2 = t0 - q * t1;
+            r0 = r1;
+            r1 = r2;
+            t0 = t1;
+            t1 = t2;
+        }
+
+        let one: BigInt = One::one();
+        if r0 != one {
+            return None;
+        }
+        t0.mod_floor(&m).to_biguint()
     }
 
     #[inline]
@@ -796,6 +968,18 @@ impl BigUint {
     }
 }
 
+// Divide `a` by the single digit `b`, returning the quotient and remainder.
+fn div_rem_digit(a: &BigUint, b: BigDigit) -> (BigUint, BigUint) {
+    let mut rem = 0;
+    let mut quot = Vec::from_elem(a.data.len(), ZERO_BIG_DIGIT);
+    for i in range(0, a.data.len()).rev() {
+        let n = BigDigit::to_uint(rem, a.data.as_slice()[i]);
+        *quot.get_mut(i) = (n / (b as uint)) as BigDigit;
+        rem = (n % (b as uint)) as BigDigit;
+    }
+    return (BigUint::new(quot), BigUint::new(vec!(rem)));
+}
+
 #[cfg(target_word_size = "32")]
 #[inline]
 fn get_radix_base(radix: uint) -> (uint, uint) {
@@ -2146,6 +2330,110 @@ mod biguint_tests {
         // Switching u and l should fail:
         let _n: BigUint = rng.gen_biguint_range(&u, &l);
     }
+
+    #[test]
+    fn test_mul_large() {
+        // (B^n - 1)^2 == B^2n - 2 * B^n + 1, for sizes handled by each of
+        // the multiplication algorithms
+        for &n in [10u, 100, 300, 700].iter() {
+            let one: BigUint = One::one();
+            let x = (one << (n * BigDigit::bits)) - one;
+            let expected = (one << (2 * n * BigDigit::bits)) + one
+                - (one << (n * BigDigit::bits + 1));
+            assert_eq!(x * x, expected);
+        }
+
+        // Unbalanced operands against a product built a digit at a time
+        let mut rng = task_rng();
+        for &(a_bits, b_bits) in [(20000u, 9000u), (50000, 30000), (3000, 40000)].iter() {
+            let a = rng.gen_biguint(a_bits);
+            let b = rng.gen_biguint(b_bits);
+            let mut expected: BigUint = Zero::zero();
+            for (i, &digit) in b.data.iter().enumerate() {
+                expected = expected + (a * BigUint::new(vec!(digit))).shl_unit(i);
+            }
+            assert_eq!(a * b, expected);
+            assert_eq!(b * a, expected);
+        }
+    }
+
+    #[test]
+    fn test_modpow() {
+        fn check(b: uint, e: uint, m: uint, r: uint) {
+            let b: BigUint = FromPrimitive::from_uint(b).unwrap();
+            let e: BigUint = FromPrimitive::from_uint(e).unwrap();
+            let m: BigUint = FromPrimitive::from_uint(m).unwrap();
+            let r: BigUint = FromPrimitive::from_uint(r).unwrap();
+            assert_eq!(b.modpow(&e, &m), r);
+        }
+        check(4, 13, 497, 445);
+        check(2, 0, 7, 1);
+        check(0, 5, 7, 0);
+        check(12, 3, 1, 0);
+        check(10, 10, 1000000007, 999999937);
+
+        // Fermat's little theorem, for the Mersenne prime 2^127 - 1
+        let one: BigUint = One::one();
+        let p = (one << 127) - one;
+        let a: BigUint = FromStr::from_str("123456789012345678901234567890").unwrap();
+        assert_eq!(a.modpow(&(p - one), &p), one);
+    }
+
+    #[test]
+    fn test_modinv() {
+        fn check(a: uint, m: uint, r: Option<uint>) {
+            let a: BigUint = FromPrimitive::from_uint(a).unwrap();
+            let m: BigUint = FromPrimitive::from_uint(m).unwrap();
+            let r: Option<BigUint> = r.map(|r| FromPrimitive::from_uint(r).unwrap());
+            assert_eq!(a.modinv(&m), r);
+        }
+        check(3, 11, Some(4));
+        check(10, 17, Some(12));
+        check(27, 17, Some(12));
+        check(6, 9, None);
+        check(0, 5, None);
+        check(1, 1, Some(0));
+
+        let one: BigUint = One::one();
+        let p = (one << 127) - one;
+        let mut rng = task_rng();
+        for _ in range(0, 10) {
+            let a = rng.gen_biguint_below(&p);
+            if a.is_zero() { continue; }
+            let inv = a.modinv(&p).unwrap();
+            assert!(inv < p);
+            assert_eq!((a * inv) % p, one);
+        }
+    }
+
+    #[test]
+    fn test_radix_large() {
+        let one: BigUint = One::one();
+        assert_eq!((one << 200).to_str_radix(10),
+                   "1606938044258990275541962092341162602522202993782792835301376".to_owned());
+        let three: BigUint = FromPrimitive::from_uint(3).unwrap();
+        let mut p = one.clone();
+        for _ in range(0, 100) { p = p * three; }
+        assert_eq!(p.to_str_radix(8),
+                   "55106247451471566412626640767353532243725271716011721".to_owned());
+
+        // Numbers with runs of zero digits in the middle
+        let ten: BigUint = FromPrimitive::from_uint(10).unwrap();
+        let mut q = one.clone();
+        for _ in range(0, 150) { q = q * ten; }
+        let q = q + one;
+        let s = q.to_str_radix(10);
+        assert_eq!(s.len(), 151);
+        assert!(s.starts_with("10000") && s.ends_with("00001"));
+
+        let mut rng = task_rng();
+        for &radix in [2u, 3, 7, 10, 16].iter() {
+            let n = rng.gen_biguint(10000);
+            let s = n.to_str_radix(radix);
+            let back: BigUint = FromStrRadix::from_str_radix(s, radix).unwrap();
+            assert_eq!(back, n);
+        }
+    }
 }
 
 #[cfg(test)]
@@ -2792,6 +3080,26 @@ mod bench {
         });
     }
 
+    #[bench]
+    fn mul_large(b: &mut Bencher) {
+        let x = factorial(2000);
+        b.iter(|| x * x);
+    }
+
+    #[bench]
+    fn to_str_large(b: &mut Bencher) {
+        let x = factorial(2000);
+        b.iter(|| x.to_str());
+    }
+
+    #[bench]
+    fn modpow(b: &mut Bencher) {
+        let one: BigUint = One::one();
+        let p = (one << 521) - one;
+        let x = factorial(100);
+        b.iter(|| x.modpow(&(p - one), &p));
+    }
+
     #[bench]
     fn shr(b: &mut Bencher) {
         let n = { let one : BigUint = One::one(); one << 1000 };
@@ -0,0 +1,539 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Arbitrary precision decimal numbers
+//!
+//! A `BigDecimal` is an arbitrary precision integer together with a scale,
+//! the number of digits after the decimal point: `12.345` is stored as the
+//! integer `12345` with a scale of 3. Addition, subtraction, multiplication
+//! and remainder are exact. Division generally isn't, so the result has to be
+//! rounded to some scale with one of the `RoundingMode`s.
+//!
+//! ```rust
+//! use num::decimal::{BigDecimal, HalfEven};
+//!
+//! let price: BigDecimal = from_str("19.99").unwrap();
+//! let rate: BigDecimal = from_str("0.0825").unwrap();
+//! let tax = (price * rate).with_scale(2, HalfEven);
+//! assert_eq!(tax.to_str(), "1.65".to_owned());
+//! assert_eq!((price + tax).to_str(), "21.64".to_owned());
+//! ```
+
+use Integer;
+
+use std::cmp;
+use std::fmt;
+use std::from_str::FromStr;
+use std::num;
+use std::num::{Zero, One, ToPrimitive, FromPrimitive};
+use std::strbuf::StrBuf;
+use bigint::BigInt;
+
+/// The smallest scale of the result of dividing two `BigDecimal`s with `/`.
+pub static DIV_SCALE: uint = 32;
+
+/// The largest exponent, positive or negative, accepted by `from_str`.
+pub static MAX_EXPONENT: int = 100000;
+
+/// How to round a number which can't be represented exactly at the scale it
+/// is being rounded to.
+#[deriving(Clone, Eq, Show)]
+pub enum RoundingMode {
+    /// Round away from zero.
+    Up,
+    /// Round towards zero.
+    Down,
+    /// Round towards positive infinity.
+    Ceiling,
+    /// Round towards negative infinity.
+    Floor,
+    /// Round to the nearest neighbour, and away from zero if both are
+    /// equally near.
+    HalfUp,
+    /// Round to the nearest neighbour, and towards zero if both are equally
+    /// near.
+    HalfDown,
+    /// Round to the nearest neighbour, and to the even one if both are
+    /// equally near. This is also known as banker's rounding.
+    HalfEven,
+}
+
+/// An arbitrary precision decimal number.
+#[deriving(Clone)]
+pub struct BigDecimal {
+    int_val: BigInt,
+    scale: uint,
+}
+
+fn ten_to_the(n: uint) -> BigInt {
+    num::pow(FromPrimitive::from_uint(10).unwrap(), n)
+}
+
+// Divide n by d, rounding the quotient according to mode.
+fn div_round(n: &BigInt, d: &BigInt, mode: RoundingMode) -> BigInt {
+    let (q, r) = n.div_rem(d);
+    if r.is_zero() {
+        return q;
+    }
+
+    // q has been truncated towards zero; decide whether to move it one
+    // further away.
+    let negative = n.is_negative() != d.is_negative();
+    let away = match mode {
+        Up => true,
+        Down => false,
+        Ceiling => !negative,
+        Floor => negative,
+        HalfUp | HalfDown | HalfEven => {
+            let two: BigInt = FromPrimitive::from_uint(2).unwrap();
+            match (r.abs() * two).cmp(&d.abs()) {
+                Less => false,
+                Greater => true,
+                Equal => match mode {
+                    HalfUp => true,
+                    HalfDown => false,
+                    _ => q.is_odd(),
+                },
+            }
+        }
+    };
+
+    if !away {
+        q
+    } else if negative {
+        q - One::one()
+    } else {
+        q + One::one()
+    }
+}
+
+impl BigDecimal {
+    /// Create the decimal `int_val * 10^-scale`.
+    #[inline]
+    pub fn new(int_val: BigInt, scale: uint) -> BigDecimal {
+        BigDecimal { int_val: int_val, scale: scale }
+    }
+
+    /// Create a decimal representing the integer `n`, with a scale of zero.
+    #[inline]
+    pub fn from_bigint(n: BigInt) -> BigDecimal {
+        BigDecimal::new(n, 0)
+    }
+
+    /// The number of digits after the decimal point.
+    #[inline]
+    pub fn scale(&self) -> uint {
+        self.scale
+    }
+
+    /// The unscaled integer value, which is `self * 10^scale`.
+    #[inline]
+    pub fn unscaled<'a>(&'a self) -> &'a BigInt {
+        &self.int_val
+    }
+
+    /// Return this number with the given scale, rounding with `mode` if
+    /// that means dropping digits.
+    pub fn with_scale(&self, scale: uint, mode: RoundingMode) -> BigDecimal {
+        if scale >= self.scale {
+            let int_val = self.int_val * ten_to_the(scale - self.scale);
+            BigDecimal::new(int_val, scale)
+        } else {
+            let int_val = div_round(&self.int_val, &ten_to_the(self.scale - scale), mode);
+            BigDecimal::new(int_val, scale)
+        }
+    }
+
+    /// Return this number with the smallest scale which represents it
+    /// exactly, by removing trailing zeros after the decimal point.
+    pub fn normalize(&self) -> BigDecimal {
+        let ten: BigInt = FromPrimitive::from_uint(10).unwrap();
+        let mut int_val = self.int_val.clone();
+        let mut scale = self.scale;
+        while scale > 0 {
+            let (q, r) = int_val.div_rem(&ten);
+            if !r.is_zero() {
+                break;
+            }
+            int_val = q;
+            scale -= 1;
+        }
+        BigDecimal::new(int_val, scale)
+    }
+
+    /// Divide `self` by `other`, rounding the quotient to `scale` digits
+    /// after the decimal point with `mode`.
+    ///
+    /// # Failure
+    ///
+    /// Fails if `other` is zero.
+    pub fn div_with_scale(&self, other: &BigDecimal, scale: uint,
+                          mode: RoundingMode) -> BigDecimal {
+        if other.is_zero() {
+            fail!("BigDecimal division by zero");
+        }
+        // (a / 10^sa) / (b / 10^sb) * 10^scale == a * 10^(sb + scale) / (b * 10^sa)
+        let n = self.int_val * ten_to_the(other.scale + scale);
+        let d = other.int_val * ten_to_the(self.scale);
+        BigDecimal::new(div_round(&n, &d, mode), scale)
+    }
+
+    // The unscaled values of self and other at their common scale, along
+    // with that scale.
+    fn align(&self, other: &BigDecimal) -> (BigInt, BigInt, uint) {
+        let scale = cmp::max(self.scale, other.scale);
+        let a = self.int_val * ten_to_the(scale - self.scale);
+        let b = other.int_val * ten_to_the(scale - other.scale);
+        (a, b, scale)
+    }
+}
+
+impl Eq for BigDecimal {
+    #[inline]
+    fn eq(&self, other: &BigDecimal) -> bool {
+        self.cmp(other) == Equal
+    }
+}
+
+impl TotalEq for BigDecimal {}
+
+impl Ord for BigDecimal {
+    #[inline]
+    fn lt(&self, other: &BigDecimal) -> bool {
+        self.cmp(other) == Less
+    }
+}
+
+impl TotalOrd for BigDecimal {
+    /// Compare the values of two decimals, regardless of their scales.
+    fn cmp(&self, other: &BigDecimal) -> Ordering {
+        if self.scale == other.scale {
+            return self.int_val.cmp(&other.int_val);
+        }
+        let (a, b, _) = self.align(other);
+        a.cmp(&b)
+    }
+}
+
+impl Add<BigDecimal, BigDecimal> for BigDecimal {
+    fn add(&self, other: &BigDecimal) -> BigDecimal {
+        let (a, b, scale) = self.align(other);
+        BigDecimal::new(a + b, scale)
+    }
+}
+
+impl Sub<BigDecimal, BigDecimal> for BigDecimal {
+    fn sub(&self, other: &BigDecimal) -> BigDecimal {
+        let (a, b, scale) = self.align(other);
+        BigDecimal::new(a - b, scale)
+    }
+}
+
+impl Mul<BigDecimal, BigDecimal> for BigDecimal {
+    /// Multiply exactly; the scale of the result is the sum of the scales.
+    fn mul(&self, other: &BigDecimal) -> BigDecimal {
+        BigDecimal::new(self.int_val * other.int_val, self.scale + other.scale)
+    }
+}
+
+impl Div<BigDecimal, BigDecimal> for BigDecimal {
+    /// Divide, rounding half-even to the larger of the two scales or
+    /// `DIV_SCALE`, whichever is larger. Use `div_with_scale` to control the
+    /// scale and rounding.
+    fn div(&self, other: &BigDecimal) -> BigDecimal {
+        let scale = cmp::max(cmp::max(self.scale, other.scale), DIV_SCALE);
+        self.div_with_scale(other, scale, HalfEven)
+    }
+}
+
+impl Rem<BigDecimal, BigDecimal> for BigDecimal {
+    /// The exact remainder of truncated division, which has the sign of
+    /// `self`.
+    fn rem(&self, other: &BigDecimal) -> BigDecimal {
+        let (a, b, scale) = self.align(other);
+        BigDecimal::new(a % b, scale)
+    }
+}
+
+impl Neg<BigDecimal> for BigDecimal {
+    #[inline]
+    fn neg(&self) -> BigDecimal {
+        BigDecimal::new(-self.int_val, self.scale)
+    }
+}
+
+impl Zero for BigDecimal {
+    #[inline]
+    fn zero() -> BigDecimal {
+        BigDecimal::new(Zero::zero(), 0)
+    }
+
+    #[inline]
+    fn is_zero(&self) -> bool {
+        self.int_val.is_zero()
+    }
+}
+
+impl One for BigDecimal {
+    #[inline]
+    fn one() -> BigDecimal {
+        BigDecimal::new(One::one(), 0)
+    }
+}
+
+impl Num for BigDecimal {}
+
+impl Signed for BigDecimal {
+    #[inline]
+    fn abs(&self) -> BigDecimal {
+        BigDecimal::new(self.int_val.abs(), self.scale)
+    }
+
+    #[inline]
+    fn abs_sub(&self, other: &BigDecimal) -> BigDecimal {
+        if *self <= *other { Zero::zero() } else { *self - *other }
+    }
+
+    #[inline]
+    fn signum(&self) -> BigDecimal {
+        BigDecimal::new(self.int_val.signum(), 0)
+    }
+
+    #[inline]
+    fn is_positive(&self) -> bool { self.int_val.is_positive() }
+
+    #[inline]
+    fn is_negative(&self) -> bool { self.int_val.is_negative() }
+}
+
+impl ToPrimitive for BigDecimal {
+    /// Convert the integer part, if it fits.
+    #[inline]
+    fn to_i64(&self) -> Option<i64> {
+        self.with_scale(0, Down).int_val.to_i64()
+    }
+
+    /// Convert the integer part, if it fits.
+    #[inline]
+    fn to_u64(&self) -> Option<u64> {
+        self.with_scale(0, Down).int_val.to_u64()
+    }
+
+    #[inline]
+    fn to_f64(&self) -> Option<f64> {
+        from_str(self.to_str())
+    }
+}
+
+impl FromPrimitive for BigDecimal {
+    #[inline]
+    fn from_i64(n: i64) -> Option<BigDecimal> {
+        FromPrimitive::from_i64(n).map(BigDecimal::from_bigint)
+    }
+
+    #[inline]
+    fn from_u64(n: u64) -> Option<BigDecimal> {
+        FromPrimitive::from_u64(n).map(BigDecimal::from_bigint)
+    }
+}
+
+impl fmt::Show for BigDecimal {
+    /// Formats the number in plain notation, with exactly `scale` digits
+    /// after the decimal point.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.scale == 0 {
+            return write!(f.buf, "{}", self.int_val);
+        }
+        let digits = self.int_val.abs().to_str();
+        let mut s = StrBuf::with_capacity(digits.len() + self.scale + 2);
+        if self.int_val.is_negative() {
+            s.push_char('-');
+        }
+        if digits.len() <= self.scale {
+            s.push_str("0.");
+            s.push_str("0".repeat(self.scale - digits.len()).as_slice());
+            s.push_str(digits.as_slice());
+        } else {
+            let point = digits.len() - self.scale;
+            s.push_str(digits.slice_to(point));
+            s.push_char('.');
+            s.push_str(digits.slice_from(point));
+        }
+        write!(f.buf, "{}", s.as_slice())
+    }
+}
+
+impl FromStr for BigDecimal {
+    /// Parses a decimal such as `-12.345`, optionally followed by an
+    /// exponent, as in `1.5e-3`. The scale of the result is the number of
+    /// digits after the decimal point, adjusted by the exponent.
+    ///
+    /// Exponents beyond `MAX_EXPONENT` either way are rejected, since the
+    /// value would take that many digits to represent.
+    fn from_str(s: &str) -> Option<BigDecimal> {
+        let (mantissa, exponent) = match s.find(|c: char| c == 'e' || c == 'E') {
+            Some(i) => match from_str::<int>(s.slice_from(i + 1)) {
+                Some(e) if e >= -MAX_EXPONENT && e <= MAX_EXPONENT => (s.slice_to(i), e),
+                _ => return None,
+            },
+            None => (s, 0),
+        };
+        let (int_part, frac_part) = match mantissa.find('.') {
+            Some(i) => (mantissa.slice_to(i), mantissa.slice_from(i + 1)),
+            None => (mantissa, ""),
+        };
+        if !frac_part.chars().all(|c| c.is_digit()) {
+            return None;
+        }
+        let unsigned = if int_part.starts_with("-") { int_part.slice_from(1) } else { int_part };
+        if unsigned.is_empty() && frac_part.is_empty() {
+            return None;
+        }
+
+        let mut digits = StrBuf::from_str(int_part);
+        if unsigned.is_empty() {
+            digits.push_char('0');
+        }
+        digits.push_str(frac_part);
+        let int_val: BigInt = match from_str(digits.as_slice()) {
+            Some(n) => n,
+            None => return None,
+        };
+
+        let scale = frac_part.len() as int - exponent;
+        if scale >= 0 {
+            Some(BigDecimal::new(int_val, scale as uint))
+        } else {
+            Some(BigDecimal::new(int_val * ten_to_the((-scale) as uint), 0))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BigDecimal, RoundingMode, Up, Down, Ceiling, Floor,
+                HalfUp, HalfDown, HalfEven};
+    use bigint::BigInt;
+    use std::num::{Zero, One, ToPrimitive, FromPrimitive};
+
+    fn dec(s: &str) -> BigDecimal {
+        from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_from_str_show() {
+        fn check(s: &str, expected: &str, scale: uint) {
+            let d = dec(s);
+            assert_eq!(d.to_str(), expected.to_owned());
+            assert_eq!(d.scale(), scale);
+        }
+        check("0", "0", 0);
+        check("123", "123", 0);
+        check("-123.450", "-123.450", 3);
+        check("0.001", "0.001", 3);
+        check("-.5", "-0.5", 1);
+        check("7.", "7", 0);
+        check("1.5e3", "1500", 0);
+        check("1.5e-3", "0.0015", 4);
+        check("-25E-1", "-2.5", 1);
+
+        for s in ["", ".", "-", "1.2.3", "1.-2", "abc", "1e", "1e1.5", "--1"].iter() {
+            assert_eq!(from_str::<BigDecimal>(*s), None);
+        }
+
+        assert!(from_str::<BigDecimal>("1e-100000").is_some());
+        for s in ["1e100001", "1e-100001", "1e999999999", "1e-9223372036854775808"].iter() {
+            assert_eq!(from_str::<BigDecimal>(*s), None);
+        }
+    }
+
+    #[test]
+    fn test_arith() {
+        assert_eq!((dec("1.1") + dec("2.22")).to_str(), "3.32".to_owned());
+        assert_eq!((dec("1.1") - dec("2.22")).to_str(), "-1.12".to_owned());
+        assert_eq!((dec("1.5") * dec("-0.25")).to_str(), "-0.375".to_owned());
+        assert_eq!((dec("7.5") % dec("2")).to_str(), "1.5".to_owned());
+        assert_eq!((dec("-7.5") % dec("2")).to_str(), "-1.5".to_owned());
+        assert_eq!((-dec("3.10")).to_str(), "-3.10".to_owned());
+        assert_eq!(dec("0.1") + dec("0.2"), dec("0.3"));
+
+        let third = dec("1") / dec("3");
+        assert_eq!(third.scale(), super::DIV_SCALE);
+        assert_eq!(third.with_scale(5, Down).to_str(), "0.33333".to_owned());
+        assert_eq!(dec("1").div_with_scale(&dec("8"), 3, HalfEven).to_str(), "0.125".to_owned());
+        assert_eq!(dec("2").div_with_scale(&dec("0.3"), 2, HalfUp).to_str(), "6.67".to_owned());
+        assert_eq!(dec("1.00").div_with_scale(&dec("-16"), 2, HalfEven).to_str(),
+                   "-0.06".to_owned());
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_div_zero() {
+        dec("1") / dec("0.00");
+    }
+
+    #[test]
+    fn test_rounding() {
+        fn check(mode: RoundingMode, expected: [&'static str, ..10]) {
+            let inputs = ["5.5", "2.5", "1.6", "1.1", "1.0",
+                          "-1.0", "-1.1", "-1.6", "-2.5", "-5.5"];
+            for (input, &want) in inputs.iter().zip(expected.iter()) {
+                let got = dec(*input).with_scale(0, mode.clone());
+                assert_eq!((input, got.to_str()), (input, want.to_owned()));
+            }
+        }
+        check(Up,       ["6", "3", "2", "2", "1", "-1", "-2", "-2", "-3", "-6"]);
+        check(Down,     ["5", "2", "1", "1", "1", "-1", "-1", "-1", "-2", "-5"]);
+        check(Ceiling,  ["6", "3", "2", "2", "1", "-1", "-1", "-1", "-2", "-5"]);
+        check(Floor,    ["5", "2", "1", "1", "1", "-1", "-2", "-2", "-3", "-6"]);
+        check(HalfUp,   ["6", "3", "2", "1", "1", "-1", "-1", "-2", "-3", "-6"]);
+        check(HalfDown, ["5", "2", "2", "1", "1", "-1", "-1", "-2", "-2", "-5"]);
+        check(HalfEven, ["6", "2", "2", "1", "1", "-1", "-1", "-2", "-2", "-6"]);
+
+        assert_eq!(dec("1.2345").with_scale(2, HalfUp).to_str(), "1.23".to_owned());
+        assert_eq!(dec("1.235").with_scale(2, HalfEven).to_str(), "1.24".to_owned());
+        assert_eq!(dec("1.2").with_scale(3, Up).to_str(), "1.200".to_owned());
+    }
+
+    #[test]
+    fn test_cmp_normalize() {
+        assert_eq!(dec("1.0"), dec("1.000"));
+        assert!(dec("1.01") > dec("1.001"));
+        assert!(dec("-1.01") < dec("-1.001"));
+        assert_eq!(dec("1.2300").normalize().to_str(), "1.23".to_owned());
+        assert_eq!(dec("100").normalize().to_str(), "100".to_owned());
+        assert_eq!(dec("0.000").normalize().to_str(), "0".to_owned());
+    }
+
+    #[test]
+    fn test_num_traits() {
+        let zero: BigDecimal = Zero::zero();
+        let one: BigDecimal = One::one();
+        assert!(zero.is_zero());
+        assert!(dec("0.00").is_zero());
+        assert_eq!(one * dec("4.5"), dec("4.5"));
+        assert_eq!(dec("-4.5").abs(), dec("4.5"));
+        assert_eq!(dec("-4.5").signum(), -one);
+        assert_eq!(dec("3").abs_sub(&dec("4.5")), zero);
+        assert_eq!(dec("4.5").abs_sub(&dec("3")), dec("1.5"));
+        assert!(dec("0.1").is_positive() && dec("-0.1").is_negative());
+
+        let n: BigDecimal = FromPrimitive::from_int(-42).unwrap();
+        assert_eq!(n, dec("-42"));
+        assert_eq!(dec("-42.9").to_i64(), Some(-42));
+        assert_eq!(dec("-42.9").to_u64(), None);
+        assert_eq!(dec("0.25").to_f64(), Some(0.25));
+
+        let big: BigInt = from_str("123456789012345678901234567890").unwrap();
+        assert_eq!(BigDecimal::new(big.clone(), 30).to_str(),
+                   "0.123456789012345678901234567890".to_owned());
+        assert_eq!(BigDecimal::from_bigint(big.clone()).unscaled(), &big);
+    }
+}
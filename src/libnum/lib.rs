@@ -25,6 +25,9 @@ extern crate rand;
 pub mod bigint;
 pub mod rational;
 pub mod complex;
+pub mod decimal;
+pub mod linalg;
+pub mod stats;
 
 pub trait Integer: Num + Ord
                  + Div<Self, Self>
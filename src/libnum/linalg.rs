@@ -0,0 +1,1067 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Vectors and matrices
+//!
+//! `Vector` and `Matrix` are dynamically sized and generic over any `Num`,
+//! including `Ratio<BigInt>` for exact arithmetic. Square matrices can be
+//! decomposed with `lu` for solving linear systems, inverses and
+//! determinants, and floating point matrices with `qr` for least squares
+//! problems.
+//!
+//! The fixed-size `Vec2`, `Vec3`, `Vec4`, `Mat2`, `Mat3` and `Mat4` are plain
+//! values for small `Copy` number types, as used in geometry.
+//!
+//! ```rust
+//! use num::linalg::{Matrix, Vector};
+//!
+//! let a = Matrix::from_vec(2, 2, vec!(2.0, 1.0,
+//!                                     1.0, 3.0));
+//! let b = Vector::new(vec!(3.0, 5.0));
+//! let x = a.solve(&b).unwrap();
+//! assert_eq!(x, Vector::new(vec!(0.8, 1.4)));
+//! ```
+
+use std::cmp;
+use std::fmt;
+use std::num::{Zero, One};
+
+/// A vector of numbers with a length chosen at runtime.
+#[deriving(Clone, Eq)]
+pub struct Vector<T> {
+    data: Vec<T>,
+}
+
+impl<T> Vector<T> {
+    /// Create a vector holding `data`.
+    #[inline]
+    pub fn new(data: Vec<T>) -> Vector<T> {
+        Vector { data: data }
+    }
+
+    /// Create a vector of length `len`, calling `f` with each index to get
+    /// its element.
+    #[inline]
+    pub fn from_fn(len: uint, f: |uint| -> T) -> Vector<T> {
+        Vector::new(Vec::from_fn(len, f))
+    }
+
+    /// Return a reference to the element at index `i`.
+    ///
+    /// # Failure
+    ///
+    /// Fails if `i` is out of bounds.
+    #[inline]
+    pub fn get<'a>(&'a self, i: uint) -> &'a T {
+        self.data.get(i)
+    }
+
+    /// Return a mutable reference to the element at index `i`.
+    ///
+    /// # Failure
+    ///
+    /// Fails if `i` is out of bounds.
+    #[inline]
+    pub fn get_mut<'a>(&'a mut self, i: uint) -> &'a mut T {
+        self.data.get_mut(i)
+    }
+
+    /// Return the elements as a slice.
+    #[inline]
+    pub fn as_slice<'a>(&'a self) -> &'a [T] {
+        self.data.as_slice()
+    }
+
+    /// Consume the vector, returning its elements.
+    #[inline]
+    pub fn unwrap(self) -> Vec<T> {
+        self.data
+    }
+}
+
+impl<T: Clone + Num> Vector<T> {
+    /// Create a vector of `len` zeros.
+    pub fn zeros(len: uint) -> Vector<T> {
+        Vector::new(Vec::from_elem(len, Zero::zero()))
+    }
+
+    /// Return the dot product of two vectors.
+    ///
+    /// # Failure
+    ///
+    /// Fails if the vectors have different lengths.
+    pub fn dot(&self, other: &Vector<T>) -> T {
+        assert_eq!(self.len(), other.len());
+        self.data.iter().zip(other.data.iter())
+            .fold(Zero::zero(), |sum: T, (a, b)| sum + *a * *b)
+    }
+
+    /// Multiply every element by `k`.
+    pub fn scale(&self, k: &T) -> Vector<T> {
+        Vector::new(self.data.iter().map(|a| *a * *k).collect())
+    }
+
+    fn zip_with(&self, other: &Vector<T>, f: |&T, &T| -> T) -> Vector<T> {
+        assert_eq!(self.len(), other.len());
+        Vector::new(self.data.iter().zip(other.data.iter()).map(|(a, b)| f(a, b)).collect())
+    }
+}
+
+impl<T: Clone + Float> Vector<T> {
+    /// Return the Euclidean norm of the vector.
+    pub fn norm(&self) -> T {
+        self.dot(self).sqrt()
+    }
+}
+
+impl<T> Container for Vector<T> {
+    #[inline]
+    fn len(&self) -> uint {
+        self.data.len()
+    }
+}
+
+impl<T: Clone + Num> Add<Vector<T>, Vector<T>> for Vector<T> {
+    fn add(&self, other: &Vector<T>) -> Vector<T> {
+        self.zip_with(other, |a, b| *a + *b)
+    }
+}
+
+impl<T: Clone + Num> Sub<Vector<T>, Vector<T>> for Vector<T> {
+    fn sub(&self, other: &Vector<T>) -> Vector<T> {
+        self.zip_with(other, |a, b| *a - *b)
+    }
+}
+
+impl<T: Clone + Num> Neg<Vector<T>> for Vector<T> {
+    fn neg(&self) -> Vector<T> {
+        Vector::new(self.data.iter().map(|a| -*a).collect())
+    }
+}
+
+impl<T: fmt::Show> fmt::Show for Vector<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.data.fmt(f)
+    }
+}
+
+/// A matrix of numbers with dimensions chosen at runtime, stored in row-major
+/// order.
+#[deriving(Clone, Eq)]
+pub struct Matrix<T> {
+    rows: uint,
+    cols: uint,
+    data: Vec<T>,
+}
+
+impl<T> Matrix<T> {
+    /// Create a `rows` by `cols` matrix from its elements in row-major order.
+    ///
+    /// # Failure
+    ///
+    /// Fails if `data` doesn't have `rows * cols` elements.
+    pub fn from_vec(rows: uint, cols: uint, data: Vec<T>) -> Matrix<T> {
+        assert!(data.len() == rows * cols,
+                "{} elements can't fill a {}x{} matrix", data.len(), rows, cols);
+        Matrix { rows: rows, cols: cols, data: data }
+    }
+
+    /// Create a `rows` by `cols` matrix, calling `f` with the row and column
+    /// of each element to get its value.
+    pub fn from_fn(rows: uint, cols: uint, f: |uint, uint| -> T) -> Matrix<T> {
+        let data = Vec::from_fn(rows * cols, |k| f(k / cols, k % cols));
+        Matrix::from_vec(rows, cols, data)
+    }
+
+    /// The number of rows.
+    #[inline]
+    pub fn rows(&self) -> uint {
+        self.rows
+    }
+
+    /// The number of columns.
+    #[inline]
+    pub fn cols(&self) -> uint {
+        self.cols
+    }
+
+    /// Return whether the matrix has as many rows as columns.
+    #[inline]
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    /// Return a reference to the element in row `i` and column `j`.
+    ///
+    /// # Failure
+    ///
+    /// Fails if `i` or `j` is out of bounds.
+    #[inline]
+    pub fn get<'a>(&'a self, i: uint, j: uint) -> &'a T {
+        assert!(i < self.rows && j < self.cols);
+        self.data.get(i * self.cols + j)
+    }
+
+    /// Return a mutable reference to the element in row `i` and column `j`.
+    ///
+    /// # Failure
+    ///
+    /// Fails if `i` or `j` is out of bounds.
+    #[inline]
+    pub fn get_mut<'a>(&'a mut self, i: uint, j: uint) -> &'a mut T {
+        assert!(i < self.rows && j < self.cols);
+        self.data.get_mut(i * self.cols + j)
+    }
+
+    /// Return row `i` as a slice.
+    #[inline]
+    pub fn row<'a>(&'a self, i: uint) -> &'a [T] {
+        assert!(i < self.rows);
+        self.data.slice(i * self.cols, (i + 1) * self.cols)
+    }
+
+    /// Return the elements in row-major order.
+    #[inline]
+    pub fn as_slice<'a>(&'a self) -> &'a [T] {
+        self.data.as_slice()
+    }
+
+    fn swap_rows(&mut self, a: uint, b: uint) {
+        if a != b {
+            let cols = self.cols;
+            let data = self.data.as_mut_slice();
+            for j in range(0, cols) {
+                data.swap(a * cols + j, b * cols + j);
+            }
+        }
+    }
+}
+
+impl<T: Clone + Num> Matrix<T> {
+    /// Create a `rows` by `cols` matrix of zeros.
+    pub fn zeros(rows: uint, cols: uint) -> Matrix<T> {
+        Matrix::from_vec(rows, cols, Vec::from_elem(rows * cols, Zero::zero()))
+    }
+
+    /// Create the `n` by `n` identity matrix.
+    pub fn identity(n: uint) -> Matrix<T> {
+        Matrix::from_fn(n, n, |i, j| if i == j { One::one() } else { Zero::zero() })
+    }
+
+    /// Return the transpose of the matrix.
+    pub fn transpose(&self) -> Matrix<T> {
+        Matrix::from_fn(self.cols, self.rows, |i, j| self.get(j, i).clone())
+    }
+
+    /// Multiply every element by `k`.
+    pub fn scale(&self, k: &T) -> Matrix<T> {
+        Matrix::from_vec(self.rows, self.cols, self.data.iter().map(|a| *a * *k).collect())
+    }
+
+    /// Multiply the matrix by a column vector.
+    ///
+    /// # Failure
+    ///
+    /// Fails if the length of `v` isn't the number of columns.
+    pub fn mul_vec(&self, v: &Vector<T>) -> Vector<T> {
+        assert!(v.len() == self.cols,
+                "can't multiply a {}x{} matrix by a vector of length {}",
+                self.rows, self.cols, v.len());
+        Vector::from_fn(self.rows, |i| {
+            self.row(i).iter().zip(v.as_slice().iter())
+                .fold(Zero::zero(), |sum: T, (a, b)| sum + *a * *b)
+        })
+    }
+
+    fn zip_with(&self, other: &Matrix<T>, f: |&T, &T| -> T) -> Matrix<T> {
+        assert!(self.rows == other.rows && self.cols == other.cols,
+                "matrix dimensions differ: {}x{} and {}x{}",
+                self.rows, self.cols, other.rows, other.cols);
+        let data = self.data.iter().zip(other.data.iter()).map(|(a, b)| f(a, b)).collect();
+        Matrix::from_vec(self.rows, self.cols, data)
+    }
+}
+
+impl<T: Clone + Signed + Ord> Matrix<T> {
+    /// Compute the LU decomposition of a square matrix.
+    ///
+    /// The decomposition divides by pivots, so it is only exact for types
+    /// with exact division, such as `Ratio`. Integer matrices should be
+    /// converted to `Ratio` first.
+    ///
+    /// # Failure
+    ///
+    /// Fails if the matrix isn't square.
+    pub fn lu(&self) -> Lu<T> {
+        Lu::new(self)
+    }
+
+    /// Return the determinant of a square matrix.
+    ///
+    /// # Failure
+    ///
+    /// Fails if the matrix isn't square.
+    pub fn det(&self) -> T {
+        self.lu().det()
+    }
+
+    /// Solve `self * x = b` for `x`, returning `None` if the matrix is
+    /// singular.
+    ///
+    /// # Failure
+    ///
+    /// Fails if the matrix isn't square, or `b` has the wrong length.
+    pub fn solve(&self, b: &Vector<T>) -> Option<Vector<T>> {
+        self.lu().solve(b)
+    }
+
+    /// Return the inverse of the matrix, or `None` if it is singular.
+    ///
+    /// # Failure
+    ///
+    /// Fails if the matrix isn't square.
+    pub fn inverse(&self) -> Option<Matrix<T>> {
+        self.lu().inverse()
+    }
+}
+
+impl<T: Clone + Float> Matrix<T> {
+    /// Compute the QR decomposition of the matrix.
+    pub fn qr(&self) -> Qr<T> {
+        Qr::new(self)
+    }
+}
+
+impl<T: Clone + Num> Add<Matrix<T>, Matrix<T>> for Matrix<T> {
+    fn add(&self, other: &Matrix<T>) -> Matrix<T> {
+        self.zip_with(other, |a, b| *a + *b)
+    }
+}
+
+impl<T: Clone + Num> Sub<Matrix<T>, Matrix<T>> for Matrix<T> {
+    fn sub(&self, other: &Matrix<T>) -> Matrix<T> {
+        self.zip_with(other, |a, b| *a - *b)
+    }
+}
+
+impl<T: Clone + Num> Mul<Matrix<T>, Matrix<T>> for Matrix<T> {
+    fn mul(&self, other: &Matrix<T>) -> Matrix<T> {
+        assert!(self.cols == other.rows,
+                "can't multiply a {}x{} matrix by a {}x{} matrix",
+                self.rows, self.cols, other.rows, other.cols);
+        Matrix::from_fn(self.rows, other.cols, |i, j| {
+            let mut sum: T = Zero::zero();
+            for k in range(0, self.cols) {
+                sum = sum + *self.get(i, k) * *other.get(k, j);
+            }
+            sum
+        })
+    }
+}
+
+impl<T: Clone + Num> Neg<Matrix<T>> for Matrix<T> {
+    fn neg(&self) -> Matrix<T> {
+        Matrix::from_vec(self.rows, self.cols, self.data.iter().map(|a| -*a).collect())
+    }
+}
+
+impl<T: fmt::Show> fmt::Show for Matrix<T> {
+    /// Formats the matrix as a list of rows, e.g. `[[1, 2], [3, 4]]`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f.buf, "["));
+        for i in range(0, self.rows) {
+            if i > 0 {
+                try!(write!(f.buf, ", "));
+            }
+            try!(write!(f.buf, "{}", self.row(i)));
+        }
+        write!(f.buf, "]")
+    }
+}
+
+/// The LU decomposition of a square matrix with partial pivoting: `P * A =
+/// L * U`, where `P` is a permutation, `L` is lower triangular with a unit
+/// diagonal and `U` is upper triangular.
+#[deriving(Clone)]
+pub struct Lu<T> {
+    // L below the diagonal and U on and above it.
+    lu: Matrix<T>,
+    // Row i of P * A is row perm[i] of A.
+    perm: Vec<uint>,
+    odd_swaps: bool,
+    singular: bool,
+}
+
+impl<T: Clone + Signed + Ord> Lu<T> {
+    fn new(a: &Matrix<T>) -> Lu<T> {
+        assert!(a.is_square(), "LU decomposition of a non-square matrix");
+        let n = a.rows;
+        let mut lu = a.clone();
+        let mut perm = Vec::from_fn(n, |i| i);
+        let mut odd_swaps = false;
+        let mut singular = false;
+
+        for k in range(0, n) {
+            let mut p = k;
+            for i in range(k + 1, n) {
+                if lu.get(i, k).abs() > lu.get(p, k).abs() {
+                    p = i;
+                }
+            }
+            if lu.get(p, k).is_zero() {
+                singular = true;
+                continue;
+            }
+            if p != k {
+                lu.swap_rows(p, k);
+                perm.as_mut_slice().swap(p, k);
+                odd_swaps = !odd_swaps;
+            }
+
+            let pivot = lu.get(k, k).clone();
+            for i in range(k + 1, n) {
+                let factor = *lu.get(i, k) / pivot;
+                if factor.is_zero() {
+                    continue;
+                }
+                for j in range(k + 1, n) {
+                    let x = *lu.get(i, j) - factor * *lu.get(k, j);
+                    *lu.get_mut(i, j) = x;
+                }
+                *lu.get_mut(i, k) = factor;
+            }
+        }
+
+        Lu { lu: lu, perm: perm, odd_swaps: odd_swaps, singular: singular }
+    }
+
+    /// Return whether the matrix is singular, i.e. has a zero determinant.
+    pub fn is_singular(&self) -> bool {
+        self.singular
+    }
+
+    /// The lower triangular factor `L`.
+    pub fn l(&self) -> Matrix<T> {
+        let n = self.lu.rows;
+        Matrix::from_fn(n, n, |i, j| {
+            if i > j {
+                self.lu.get(i, j).clone()
+            } else if i == j {
+                One::one()
+            } else {
+                Zero::zero()
+            }
+        })
+    }
+
+    /// The upper triangular factor `U`.
+    pub fn u(&self) -> Matrix<T> {
+        let n = self.lu.rows;
+        Matrix::from_fn(n, n, |i, j| {
+            if i <= j { self.lu.get(i, j).clone() } else { Zero::zero() }
+        })
+    }
+
+    /// The permutation `P`, as the row of the original matrix which each
+    /// row of `P * A` comes from.
+    pub fn permutation<'a>(&'a self) -> &'a [uint] {
+        self.perm.as_slice()
+    }
+
+    /// Return the determinant of the decomposed matrix.
+    pub fn det(&self) -> T {
+        if self.singular {
+            return Zero::zero();
+        }
+        let mut det: T = One::one();
+        for i in range(0, self.lu.rows) {
+            det = det * *self.lu.get(i, i);
+        }
+        if self.odd_swaps { -det } else { det }
+    }
+
+    /// Solve `A * x = b` for `x`, returning `None` if `A` is singular.
+    ///
+    /// # Failure
+    ///
+    /// Fails if `b` has the wrong length.
+    pub fn solve(&self, b: &Vector<T>) -> Option<Vector<T>> {
+        let n = self.lu.rows;
+        assert!(b.len() == n, "right hand side has length {}, expected {}", b.len(), n);
+        if self.singular {
+            return None;
+        }
+
+        // Forward substitution with L, then back substitution with U.
+        let mut x = Vector::from_fn(n, |i| b.get(*self.perm.get(i)).clone());
+        for i in range(0, n) {
+            let mut sum = x.get(i).clone();
+            for j in range(0, i) {
+                sum = sum - *self.lu.get(i, j) * *x.get(j);
+            }
+            *x.get_mut(i) = sum;
+        }
+        for i in range(0, n).rev() {
+            let mut sum = x.get(i).clone();
+            for j in range(i + 1, n) {
+                sum = sum - *self.lu.get(i, j) * *x.get(j);
+            }
+            *x.get_mut(i) = sum / *self.lu.get(i, i);
+        }
+        Some(x)
+    }
+
+    /// Return the inverse of the decomposed matrix, or `None` if it is
+    /// singular.
+    pub fn inverse(&self) -> Option<Matrix<T>> {
+        if self.singular {
+            return None;
+        }
+        let n = self.lu.rows;
+        let mut inv = Matrix::zeros(n, n);
+        for j in range(0, n) {
+            let e = Vector::from_fn(n, |i| if i == j { One::one() } else { Zero::zero() });
+            let col = self.solve(&e).unwrap();
+            for i in range(0, n) {
+                *inv.get_mut(i, j) = col.get(i).clone();
+            }
+        }
+        Some(inv)
+    }
+}
+
+/// The QR decomposition of an `m` by `n` matrix: `A = Q * R`, where `Q` is an
+/// `m` by `m` orthogonal matrix and `R` is `m` by `n` and upper triangular.
+///
+/// It is computed with Householder reflections, which are numerically
+/// stable, so it is better suited than `Lu` to ill-conditioned systems, and
+/// can solve overdetermined systems in the least squares sense.
+#[deriving(Clone)]
+pub struct Qr<T> {
+    q: Matrix<T>,
+    r: Matrix<T>,
+    odd_reflections: bool,
+}
+
+impl<T: Clone + Float> Qr<T> {
+    fn new(a: &Matrix<T>) -> Qr<T> {
+        let (m, n) = (a.rows, a.cols);
+        let mut q: Matrix<T> = Matrix::identity(m);
+        let mut r = a.clone();
+        let mut odd_reflections = false;
+        let one: T = One::one();
+        let two = one + one;
+
+        // The last row needs no reflection, and a matrix without rows none at
+        // all.
+        let reflections = if m == 0 { 0 } else { cmp::min(m - 1, n) };
+        for k in range(0, reflections) {
+            // Reflect column k below the diagonal onto the diagonal, with
+            // the sign chosen to avoid cancellation.
+            let mut v = Vec::from_fn(m - k, |i| r.get(k + i, k).clone());
+            let norm = v.iter().fold(Zero::zero(), |s: T, x| s + *x * *x).sqrt();
+            if norm.is_zero() {
+                continue;
+            }
+            let alpha = if *v.get(0) > Zero::zero() { -norm } else { norm };
+            *v.get_mut(0) = *v.get(0) - alpha;
+            let vv = v.iter().fold(Zero::zero(), |s: T, x| s + *x * *x);
+            if vv.is_zero() {
+                continue;
+            }
+            odd_reflections = !odd_reflections;
+
+            // R = H * R and Q = Q * H, where H = I - 2 v v^T / (v^T v).
+            for j in range(0, n) {
+                let mut s: T = Zero::zero();
+                for i in range(0, m - k) {
+                    s = s + *v.get(i) * *r.get(k + i, j);
+                }
+                let f = two * s / vv;
+                for i in range(0, m - k) {
+                    let x = *r.get(k + i, j) - f * *v.get(i);
+                    *r.get_mut(k + i, j) = x;
+                }
+            }
+            for i in range(0, m) {
+                let mut s: T = Zero::zero();
+                for l in range(0, m - k) {
+                    s = s + *q.get(i, k + l) * *v.get(l);
+                }
+                let f = two * s / vv;
+                for l in range(0, m - k) {
+                    let x = *q.get(i, k + l) - f * *v.get(l);
+                    *q.get_mut(i, k + l) = x;
+                }
+            }
+        }
+
+        Qr { q: q, r: r, odd_reflections: odd_reflections }
+    }
+
+    /// The orthogonal factor `Q`.
+    pub fn q<'a>(&'a self) -> &'a Matrix<T> {
+        &self.q
+    }
+
+    /// The upper triangular factor `R`.
+    pub fn r<'a>(&'a self) -> &'a Matrix<T> {
+        &self.r
+    }
+
+    /// Return the determinant of the decomposed matrix.
+    ///
+    /// # Failure
+    ///
+    /// Fails if the matrix isn't square.
+    pub fn det(&self) -> T {
+        assert!(self.r.is_square(), "determinant of a non-square matrix");
+        let mut det: T = One::one();
+        for i in range(0, self.r.rows) {
+            det = det * *self.r.get(i, i);
+        }
+        if self.odd_reflections { -det } else { det }
+    }
+
+    /// Find the `x` minimizing the norm of `A * x - b`, which is the exact
+    /// solution if `A` is square. Returns `None` if `A` has more columns
+    /// than rows, or its columns are linearly dependent.
+    ///
+    /// # Failure
+    ///
+    /// Fails if `b` has the wrong length.
+    pub fn solve(&self, b: &Vector<T>) -> Option<Vector<T>> {
+        let (m, n) = (self.r.rows, self.r.cols);
+        assert!(b.len() == m, "right hand side has length {}, expected {}", b.len(), m);
+        if n > m {
+            return None;
+        }
+
+        // Treat diagonal elements which are negligible next to the largest
+        // one as zero.
+        let mut largest: T = Zero::zero();
+        for i in range(0, n) {
+            largest = largest.max(self.r.get(i, i).abs());
+        }
+        let size: T = NumCast::from(cmp::max(m, n)).unwrap();
+        let tolerance = largest * size * Float::epsilon();
+
+        // R x = Q^T b, using only the first n rows.
+        let qtb = self.q.transpose().mul_vec(b);
+        let mut x = Vector::from_fn(n, |i| qtb.get(i).clone());
+        for i in range(0, n).rev() {
+            let d = self.r.get(i, i).clone();
+            if d.abs() <= tolerance {
+                return None;
+            }
+            let mut sum = x.get(i).clone();
+            for j in range(i + 1, n) {
+                sum = sum - *self.r.get(i, j) * *x.get(j);
+            }
+            *x.get_mut(i) = sum / d;
+        }
+        Some(x)
+    }
+}
+
+macro_rules! fixed_vector(
+    ($Vec:ident, $n:expr, $($field:ident $idx:expr),+) => (
+        /// A vector with a fixed number of elements.
+        #[deriving(Clone, Eq, Show)]
+        pub struct $Vec<T> {
+            $(pub $field: T),+
+        }
+
+        impl<T: Copy + Num> $Vec<T> {
+            /// Create a vector from its elements.
+            #[inline]
+            pub fn new($($field: T),+) -> $Vec<T> {
+                $Vec { $($field: $field),+ }
+            }
+
+            /// Create a vector from an array of its elements.
+            #[inline]
+            pub fn from_array(a: [T, ..$n]) -> $Vec<T> {
+                $Vec { $($field: a[$idx]),+ }
+            }
+
+            /// Return the elements as an array.
+            #[inline]
+            pub fn to_array(&self) -> [T, ..$n] {
+                [$(self.$field),+]
+            }
+
+            /// Return the dot product of two vectors.
+            #[inline]
+            pub fn dot(&self, other: &$Vec<T>) -> T {
+                let mut sum: T = Zero::zero();
+                $(sum = sum + self.$field * other.$field;)+
+                sum
+            }
+
+            /// Multiply every element by `k`.
+            #[inline]
+            pub fn scale(&self, k: T) -> $Vec<T> {
+                $Vec { $($field: self.$field * k),+ }
+            }
+
+            /// Convert to a dynamically sized `Vector`.
+            pub fn to_vector(&self) -> Vector<T> {
+                Vector::new(vec!($(self.$field),+))
+            }
+        }
+
+        impl<T: Copy + Float> $Vec<T> {
+            /// Return the Euclidean norm of the vector.
+            #[inline]
+            pub fn norm(&self) -> T {
+                self.dot(self).sqrt()
+            }
+        }
+
+        impl<T: Copy + Num> Add<$Vec<T>, $Vec<T>> for $Vec<T> {
+            #[inline]
+            fn add(&self, other: &$Vec<T>) -> $Vec<T> {
+                $Vec { $($field: self.$field + other.$field),+ }
+            }
+        }
+
+        impl<T: Copy + Num> Sub<$Vec<T>, $Vec<T>> for $Vec<T> {
+            #[inline]
+            fn sub(&self, other: &$Vec<T>) -> $Vec<T> {
+                $Vec { $($field: self.$field - other.$field),+ }
+            }
+        }
+
+        impl<T: Copy + Num> Neg<$Vec<T>> for $Vec<T> {
+            #[inline]
+            fn neg(&self) -> $Vec<T> {
+                $Vec { $($field: -self.$field),+ }
+            }
+        }
+
+        impl<T: Copy + Num> Zero for $Vec<T> {
+            #[inline]
+            fn zero() -> $Vec<T> {
+                $Vec { $($field: Zero::zero()),+ }
+            }
+
+            #[inline]
+            fn is_zero(&self) -> bool {
+                true $(&& self.$field.is_zero())+
+            }
+        }
+    )
+)
+
+macro_rules! fixed_matrix(
+    ($Mat:ident, $Vec:ident, $n:expr) => (
+        /// A square matrix with a fixed size. `m[i][j]` is the element in
+        /// row `i` and column `j`.
+        pub struct $Mat<T> {
+            pub m: [[T, ..$n], ..$n],
+        }
+
+        impl<T: Copy + Num> $Mat<T> {
+            /// Create a matrix from an array of its rows.
+            #[inline]
+            pub fn new(m: [[T, ..$n], ..$n]) -> $Mat<T> {
+                $Mat { m: m }
+            }
+
+            /// Create the identity matrix.
+            pub fn identity() -> $Mat<T> {
+                let mut m = [[Zero::zero(), ..$n], ..$n];
+                for i in range(0u, $n) {
+                    m[i][i] = One::one();
+                }
+                $Mat { m: m }
+            }
+
+            /// Return the transpose of the matrix.
+            pub fn transpose(&self) -> $Mat<T> {
+                let mut m = self.m;
+                for i in range(0u, $n) {
+                    for j in range(0u, $n) {
+                        m[i][j] = self.m[j][i];
+                    }
+                }
+                $Mat { m: m }
+            }
+
+            /// Multiply the matrix by a column vector.
+            pub fn mul_vec(&self, v: &$Vec<T>) -> $Vec<T> {
+                let v = v.to_array();
+                let mut r = v;
+                for i in range(0u, $n) {
+                    let mut sum: T = Zero::zero();
+                    for j in range(0u, $n) {
+                        sum = sum + self.m[i][j] * v[j];
+                    }
+                    r[i] = sum;
+                }
+                $Vec::from_array(r)
+            }
+
+            /// Convert to a dynamically sized `Matrix`.
+            pub fn to_matrix(&self) -> Matrix<T> {
+                Matrix::from_fn($n, $n, |i, j| self.m[i][j])
+            }
+        }
+
+        impl<T: Copy + Signed + Ord> $Mat<T> {
+            /// Return the determinant of the matrix.
+            pub fn det(&self) -> T {
+                self.to_matrix().det()
+            }
+
+            /// Return the inverse of the matrix, or `None` if it is
+            /// singular.
+            pub fn inverse(&self) -> Option<$Mat<T>> {
+                self.to_matrix().inverse().map(|inv| {
+                    let mut m = self.m;
+                    for i in range(0u, $n) {
+                        for j in range(0u, $n) {
+                            m[i][j] = *inv.get(i, j);
+                        }
+                    }
+                    $Mat { m: m }
+                })
+            }
+        }
+
+        impl<T: Copy> Clone for $Mat<T> {
+            #[inline]
+            fn clone(&self) -> $Mat<T> {
+                $Mat { m: self.m }
+            }
+        }
+
+        impl<T: Copy + Num> Eq for $Mat<T> {
+            fn eq(&self, other: &$Mat<T>) -> bool {
+                range(0u, $n).all(|i| range(0u, $n).all(|j| self.m[i][j] == other.m[i][j]))
+            }
+        }
+
+        impl<T: Copy + Num> Add<$Mat<T>, $Mat<T>> for $Mat<T> {
+            fn add(&self, other: &$Mat<T>) -> $Mat<T> {
+                let mut m = self.m;
+                for i in range(0u, $n) {
+                    for j in range(0u, $n) {
+                        m[i][j] = self.m[i][j] + other.m[i][j];
+                    }
+                }
+                $Mat { m: m }
+            }
+        }
+
+        impl<T: Copy + Num> Sub<$Mat<T>, $Mat<T>> for $Mat<T> {
+            fn sub(&self, other: &$Mat<T>) -> $Mat<T> {
+                let mut m = self.m;
+                for i in range(0u, $n) {
+                    for j in range(0u, $n) {
+                        m[i][j] = self.m[i][j] - other.m[i][j];
+                    }
+                }
+                $Mat { m: m }
+            }
+        }
+
+        impl<T: Copy + Num> Mul<$Mat<T>, $Mat<T>> for $Mat<T> {
+            fn mul(&self, other: &$Mat<T>) -> $Mat<T> {
+                let mut m = self.m;
+                for i in range(0u, $n) {
+                    for j in range(0u, $n) {
+                        let mut sum: T = Zero::zero();
+                        for k in range(0u, $n) {
+                            sum = sum + self.m[i][k] * other.m[k][j];
+                        }
+                        m[i][j] = sum;
+                    }
+                }
+                $Mat { m: m }
+            }
+        }
+
+        impl<T: Copy + Num + fmt::Show> fmt::Show for $Mat<T> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                self.to_matrix().fmt(f)
+            }
+        }
+    )
+)
+
+fixed_vector!(Vec2, 2, x 0, y 1)
+fixed_vector!(Vec3, 3, x 0, y 1, z 2)
+fixed_vector!(Vec4, 4, x 0, y 1, z 2, w 3)
+
+fixed_matrix!(Mat2, Vec2, 2)
+fixed_matrix!(Mat3, Vec3, 3)
+fixed_matrix!(Mat4, Vec4, 4)
+
+impl<T: Copy + Num> Vec3<T> {
+    /// Return the cross product of two vectors.
+    pub fn cross(&self, other: &Vec3<T>) -> Vec3<T> {
+        Vec3::new(self.y * other.z - self.z * other.y,
+                  self.z * other.x - self.x * other.z,
+                  self.x * other.y - self.y * other.x)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Vector, Matrix, Vec2, Vec3, Mat2, Mat3};
+    use bigint::BigInt;
+    use rational::{Ratio, BigRational};
+    use std::num::FromPrimitive;
+
+    fn approx_eq(a: &Matrix<f64>, b: &Matrix<f64>) -> bool {
+        a.rows() == b.rows() && a.cols() == b.cols() &&
+            a.as_slice().iter().zip(b.as_slice().iter()).all(|(x, y)| (*x - *y).abs() < 1e-9)
+    }
+
+    fn approx_eq_vec(a: &Vector<f64>, b: &Vector<f64>) -> bool {
+        a.len() == b.len() &&
+            a.as_slice().iter().zip(b.as_slice().iter()).all(|(x, y)| (*x - *y).abs() < 1e-9)
+    }
+
+    fn ratio(n: int, d: int) -> BigRational {
+        Ratio::new(FromPrimitive::from_int(n).unwrap(), FromPrimitive::from_int(d).unwrap())
+    }
+
+    #[test]
+    fn test_vector() {
+        let a = Vector::new(vec!(1, 2, 3));
+        let b = Vector::from_fn(3, |i| i as int * 2);
+        assert_eq!(a + b, Vector::new(vec!(1, 4, 7)));
+        assert_eq!(a - b, Vector::new(vec!(1, 0, -1)));
+        assert_eq!(-a, Vector::new(vec!(-1, -2, -3)));
+        assert_eq!(a.dot(&b), 16);
+        assert_eq!(a.scale(&3), Vector::new(vec!(3, 6, 9)));
+        assert_eq!(Vector::<int>::zeros(2), Vector::new(vec!(0, 0)));
+        assert_eq!(Vector::new(vec!(3.0, 4.0)).norm(), 5.0);
+        assert_eq!(a.to_str(), "[1, 2, 3]".to_owned());
+    }
+
+    #[test]
+    fn test_matrix_arith() {
+        let a = Matrix::from_vec(2, 3, vec!(1, 2, 3,
+                                            4, 5, 6));
+        let b = Matrix::from_fn(3, 2, |i, j| (i * 2 + j) as int);
+        assert_eq!(a * b, Matrix::from_vec(2, 2, vec!(16, 22,
+                                                      34, 49)));
+        assert_eq!(a.transpose(), Matrix::from_vec(3, 2, vec!(1, 4, 2, 5, 3, 6)));
+        assert_eq!(a + a, a.scale(&2));
+        assert_eq!(a - a, Matrix::zeros(2, 3));
+        assert_eq!(-a, a.scale(&-1));
+        assert_eq!(a.mul_vec(&Vector::new(vec!(1, 0, -1))), Vector::new(vec!(-2, -2)));
+        assert_eq!(Matrix::<int>::identity(3) * b, b);
+        assert_eq!(a.row(1), [4, 5, 6].as_slice());
+        assert_eq!(a.to_str(), "[[1, 2, 3], [4, 5, 6]]".to_owned());
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_mul_mismatch() {
+        let a: Matrix<int> = Matrix::zeros(2, 3);
+        a * a;
+    }
+
+    #[test]
+    fn test_lu() {
+        let a = Matrix::from_vec(3, 3, vec!(2.0, 1.0, 1.0,
+                                            4.0, -6.0, 0.0,
+                                            -2.0, 7.0, 2.0));
+        let lu = a.lu();
+        assert!(!lu.is_singular());
+        let p = Matrix::from_fn(3, 3, |i, j| if lu.permutation()[i] == j { 1.0 } else { 0.0 });
+        assert!(approx_eq(&(p * a), &(lu.l() * lu.u())));
+        assert!((a.det() - -16.0).abs() < 1e-9);
+
+        let x = a.solve(&Vector::new(vec!(5.0, -2.0, 9.0))).unwrap();
+        assert!(approx_eq_vec(&x, &Vector::new(vec!(1.0, 1.0, 2.0))));
+        let inv = a.inverse().unwrap();
+        assert!(approx_eq(&(a * inv), &Matrix::identity(3)));
+
+        let singular = Matrix::from_vec(2, 2, vec!(1.0, 2.0, 2.0, 4.0));
+        assert!(singular.lu().is_singular());
+        assert_eq!(singular.det(), 0.0);
+        assert!(singular.solve(&Vector::new(vec!(1.0, 2.0))).is_none());
+        assert!(singular.inverse().is_none());
+    }
+
+    #[test]
+    fn test_lu_exact() {
+        // The Hilbert matrix is notoriously ill-conditioned, but exact with
+        // rationals.
+        let n = 5;
+        let h = Matrix::from_fn(n, n, |i, j| ratio(1, (i + j + 1) as int));
+        let inv = h.inverse().unwrap();
+        assert_eq!(h * inv, Matrix::identity(n));
+        assert_eq!(*inv.get(0, 0), ratio(25, 1));
+        assert_eq!(*inv.get(4, 4), ratio(44100, 1));
+        let det: BigInt = FromPrimitive::from_int(266716800000).unwrap();
+        assert_eq!(h.det(), Ratio::new(FromPrimitive::from_int(1).unwrap(), det));
+    }
+
+    #[test]
+    fn test_qr() {
+        let a = Matrix::from_vec(3, 3, vec!(12.0, -51.0, 4.0,
+                                            6.0, 167.0, -68.0,
+                                            -4.0, 24.0, -41.0));
+        let qr = a.qr();
+        assert!(approx_eq(&(*qr.q() * *qr.r()), &a));
+        assert!(approx_eq(&(qr.q().transpose() * *qr.q()), &Matrix::identity(3)));
+        for i in range(0u, 3) {
+            for j in range(0, i) {
+                assert!(qr.r().get(i, j).abs() < 1e-9);
+            }
+        }
+        assert!((qr.det() - a.det()).abs() < 1e-6);
+
+        // Fit the line y = c0 + c1 t to four points by least squares.
+        let t = [0.0, 1.0, 2.0, 3.0];
+        let y = [1.5, 2.5, 5.5, 6.5];
+        let design = Matrix::from_fn(4, 2, |i, j| if j == 0 { 1.0 } else { t[i] });
+        let c = design.qr().solve(&Vector::from_fn(4, |i| y[i])).unwrap();
+        assert!(approx_eq_vec(&c, &Vector::new(vec!(1.3, 1.8))));
+
+        let dependent = Matrix::from_vec(3, 2, vec!(1.0, 2.0, 2.0, 4.0, 3.0, 6.0));
+        assert!(dependent.qr().solve(&Vector::new(vec!(1.0, 2.0, 3.0))).is_none());
+
+        let empty: Matrix<f64> = Matrix::from_vec(0, 2, vec!());
+        let qr = empty.qr();
+        assert_eq!((qr.q().rows(), qr.q().cols()), (0, 0));
+        assert_eq!((qr.r().rows(), qr.r().cols()), (0, 2));
+    }
+
+    #[test]
+    fn test_fixed() {
+        let a = Vec3::new(1, 0, 0);
+        let b = Vec3::new(0, 1, 0);
+        assert_eq!(a.cross(&b), Vec3::new(0, 0, 1));
+        assert_eq!(a + b, Vec3::from_array([1, 1, 0]));
+        assert_eq!(Vec3::from_array((a - b).to_array()), Vec3::new(1, -1, 0));
+        assert_eq!(a.dot(&b), 0);
+        assert_eq!(Vec2::new(3.0, 4.0).norm(), 5.0);
+        assert_eq!(Vec2::new(1, 2).scale(3), Vec2::new(3, 6));
+
+        let m = Mat2::new([[1, 2], [3, 4]]);
+        assert_eq!(m * Mat2::identity(), m);
+        assert_eq!(m.transpose(), Mat2::new([[1, 3], [2, 4]]));
+        assert_eq!(m.mul_vec(&Vec2::new(1, 1)), Vec2::new(3, 7));
+        assert_eq!(m.to_str(), "[[1, 2], [3, 4]]".to_owned());
+
+        let r = Mat3::new([[0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]]);
+        assert_eq!(r.det(), 1.0);
+        assert_eq!(r.inverse().unwrap(), r.transpose());
+        assert_eq!(r.mul_vec(&Vec3::new(1.0, 0.0, 0.0)), Vec3::new(0.0, 1.0, 0.0));
+    }
+}
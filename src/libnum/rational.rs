@@ -238,6 +238,37 @@ impl<T: Clone + Integer + Ord>
 impl<T: Clone + Integer + Ord>
     Num for Ratio<T> {}
 
+impl<T: Clone + Integer + Ord>
+    Signed for Ratio<T> {
+    #[inline]
+    fn abs(&self) -> Ratio<T> {
+        if self.is_negative() { -*self } else { self.clone() }
+    }
+
+    #[inline]
+    fn abs_sub(&self, other: &Ratio<T>) -> Ratio<T> {
+        if *self <= *other { Zero::zero() } else { *self - *other }
+    }
+
+    #[inline]
+    fn signum(&self) -> Ratio<T> {
+        if self.is_positive() {
+            One::one()
+        } else if self.is_negative() {
+            let one: Ratio<T> = One::one();
+            -one
+        } else {
+            Zero::zero()
+        }
+    }
+
+    #[inline]
+    fn is_positive(&self) -> bool { *self > Zero::zero() }
+
+    #[inline]
+    fn is_negative(&self) -> bool { *self < Zero::zero() }
+}
+
 /* Utils */
 impl<T: Clone + Integer + Ord>
     Round for Ratio<T> {
@@ -523,6 +554,19 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_signed() {
+        assert_eq!(_neg1_2.abs(), _1_2);
+        assert_eq!(_3_2.abs(), _3_2);
+        assert_eq!(_1_2.abs_sub(&_3_2), _0);
+        assert_eq!(_3_2.abs_sub(&_1_2), _1);
+        assert_eq!(_neg1_2.signum(), -_1);
+        assert_eq!(_3_2.signum(), _1);
+        assert_eq!(_0.signum(), _0);
+        assert!(_1_2.is_positive() && !_1_2.is_negative());
+        assert!(_neg1_2.is_negative() && !_0.is_positive());
+    }
+
     #[test]
     fn test_round() {
         assert_eq!(_1_2.ceil(), _1);
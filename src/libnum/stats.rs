@@ -0,0 +1,788 @@
+// Copyright 2012-2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Descriptive statistics
+//!
+//! The `Stats` trait and `Summary` compute statistics of a complete set of
+//! samples held in a slice. The remaining types summarize a stream of samples
+//! as they arrive, in constant or bounded space:
+//!
+//! * `OnlineStats` keeps the count, mean, variance and extremes.
+//! * `Histogram` counts the samples falling into equally sized bins.
+//! * `QuantileSketch` answers approximate quantile queries.
+
+use std::cmp;
+use std::default::Default;
+use std::mem;
+use std::num;
+use std::f64;
+
+// NB: this can probably be rewritten in terms of num::Num
+// to be less f64-specific.
+
+fn f64_cmp(x: f64, y: f64) -> Ordering {
+    // arbitrarily decide that NaNs are larger than everything.
+    if y.is_nan() {
+        Less
+    } else if x.is_nan() {
+        Greater
+    } else if x < y {
+        Less
+    } else if x == y {
+        Equal
+    } else {
+        Greater
+    }
+}
+
+fn f64_sort(v: &mut [f64]) {
+    v.sort_by(|x: &f64, y: &f64| f64_cmp(*x, *y));
+}
+
+/// Trait that provides simple descriptive statistics on a univariate set of numeric samples.
+pub trait Stats {
+
+    /// Sum of the samples.
+    ///
+    /// Note: this method sacrifices performance at the altar of accuracy
+    /// Depends on IEEE-754 arithmetic guarantees. See proof of correctness at:
+    /// ["Adaptive Precision Floating-Point Arithmetic and Fast Robust Geometric Predicates"]
+    /// (http://www.cs.cmu.edu/~quake-papers/robust-arithmetic.ps)
+    /// *Discrete & Computational Geometry 18*, 3 (Oct 1997), 305-363, Shewchuk J.R.
+    fn sum(self) -> f64;
+
+    /// Minimum value of the samples.
+    fn min(self) -> f64;
+
+    /// Maximum value of the samples.
+    fn max(self) -> f64;
+
+    /// Arithmetic mean (average) of the samples: sum divided by sample-count.
+    ///
+    /// See: https://en.wikipedia.org/wiki/Arithmetic_mean
+    fn mean(self) -> f64;
+
+    /// Median of the samples: value separating the lower half of the samples from the higher half.
+    /// Equal to `self.percentile(50.0)`.
+    ///
+    /// See: https://en.wikipedia.org/wiki/Median
+    fn median(self) -> f64;
+
+    /// Variance of the samples: bias-corrected mean of the squares of the differences of each
+    /// sample from the sample mean. Note that this calculates the _sample variance_ rather than the
+    /// population variance, which is assumed to be unknown. It therefore corrects the `(n-1)/n`
+    /// bias that would appear if we calculated a population variance, by dividing by `(n-1)` rather
+    /// than `n`.
+    ///
+    /// See: https://en.wikipedia.org/wiki/Variance
+    fn var(self) -> f64;
+
+    /// Standard deviation: the square root of the sample variance.
+    ///
+    /// Note: this is not a robust statistic for non-normal distributions. Prefer the
+    /// `median_abs_dev` for unknown distributions.
+    ///
+    /// See: https://en.wikipedia.org/wiki/Standard_deviation
+    fn std_dev(self) -> f64;
+
+    /// Standard deviation as a percent of the mean value. See `std_dev` and `mean`.
+    ///
+    /// Note: this is not a robust statistic for non-normal distributions. Prefer the
+    /// `median_abs_dev_pct` for unknown distributions.
+    fn std_dev_pct(self) -> f64;
+
+    /// Scaled median of the absolute deviations of each sample from the sample median. This is a
+    /// robust (distribution-agnostic) estimator of sample variability. Use this in preference to
+    /// `std_dev` if you cannot assume your sample is normally distributed. Note that this is scaled
+    /// by the constant `1.4826` to allow its use as a consistent estimator for the standard
+    /// deviation.
+    ///
+    /// See: http://en.wikipedia.org/wiki/Median_absolute_deviation
+    fn median_abs_dev(self) -> f64;
+
+    /// Median absolute deviation as a percent of the median. See `median_abs_dev` and `median`.
+    fn median_abs_dev_pct(self) -> f64;
+
+    /// Percentile: the value below which `pct` percent of the values in `self` fall. For example,
+    /// percentile(95.0) will return the value `v` such that 95% of the samples `s` in `self`
+    /// satisfy `s <= v`.
+    ///
+    /// Calculated by linear interpolation between closest ranks.
+    ///
+    /// See: http://en.wikipedia.org/wiki/Percentile
+    fn percentile(self, pct: f64) -> f64;
+
+    /// Quartiles of the sample: three values that divide the sample into four equal groups, each
+    /// with 1/4 of the data. The middle value is the median. See `median` and `percentile`. This
+    /// function may calculate the 3 quartiles more efficiently than 3 calls to `percentile`, but
+    /// is otherwise equivalent.
+    ///
+    /// See also: https://en.wikipedia.org/wiki/Quartile
+    fn quartiles(self) -> (f64,f64,f64);
+
+    /// Inter-quartile range: the difference between the 25th percentile (1st quartile) and the 75th
+    /// percentile (3rd quartile). See `quartiles`.
+    ///
+    /// See also: https://en.wikipedia.org/wiki/Interquartile_range
+    fn iqr(self) -> f64;
+}
+
+/// Extracted collection of all the summary statistics of a sample set.
+#[deriving(Clone, Eq)]
+#[allow(missing_doc)]
+pub struct Summary {
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub median: f64,
+    pub var: f64,
+    pub std_dev: f64,
+    pub std_dev_pct: f64,
+    pub median_abs_dev: f64,
+    pub median_abs_dev_pct: f64,
+    pub quartiles: (f64,f64,f64),
+    pub iqr: f64,
+}
+
+impl Summary {
+
+    /// Construct a new summary of a sample set.
+    pub fn new(samples: &[f64]) -> Summary {
+        Summary {
+            sum: samples.sum(),
+            min: samples.min(),
+            max: samples.max(),
+            mean: samples.mean(),
+            median: samples.median(),
+            var: samples.var(),
+            std_dev: samples.std_dev(),
+            std_dev_pct: samples.std_dev_pct(),
+            median_abs_dev: samples.median_abs_dev(),
+            median_abs_dev_pct: samples.median_abs_dev_pct(),
+            quartiles: samples.quartiles(),
+            iqr: samples.iqr()
+        }
+    }
+}
+
+impl<'a> Stats for &'a [f64] {
+
+    // FIXME #11059 handle NaN, inf and overflow
+    #[allow(deprecated_owned_vector)]
+    fn sum(self) -> f64 {
+        let mut partials = vec![];
+
+        for &mut x in self.iter() {
+            let mut j = 0;
+            // This inner loop applies `hi`/`lo` summation to each
+            // partial so that the list of partial sums remains exact.
+            for i in range(0, partials.len()) {
+                let mut y = *partials.get(i);
+                if num::abs(x) < num::abs(y) {
+                    mem::swap(&mut x, &mut y);
+                }
+                // Rounded `x+y` is stored in `hi` with round-off stored in
+                // `lo`. Together `hi+lo` are exactly equal to `x+y`.
+                let hi = x + y;
+                let lo = y - (hi - x);
+                if lo != 0f64 {
+                    *partials.get_mut(j) = lo;
+                    j += 1;
+                }
+                x = hi;
+            }
+            if j >= partials.len() {
+                partials.push(x);
+            } else {
+                *partials.get_mut(j) = x;
+                partials.truncate(j+1);
+            }
+        }
+        partials.iter().fold(0.0, |p, q| p + *q)
+    }
+
+    fn min(self) -> f64 {
+        assert!(self.len() != 0);
+        self.iter().fold(self[0], |p, q| p.min(*q))
+    }
+
+    fn max(self) -> f64 {
+        assert!(self.len() != 0);
+        self.iter().fold(self[0], |p, q| p.max(*q))
+    }
+
+    fn mean(self) -> f64 {
+        assert!(self.len() != 0);
+        self.sum() / (self.len() as f64)
+    }
+
+    fn median(self) -> f64 {
+        self.percentile(50.0)
+    }
+
+    fn var(self) -> f64 {
+        if self.len() < 2 {
+            0.0
+        } else {
+            let mean = self.mean();
+            let mut v = 0.0;
+            for s in self.iter() {
+                let x = *s - mean;
+                v += x*x;
+            }
+            // NB: this is _supposed to be_ len-1, not len. If you
+            // change it back to len, you will be calculating a
+            // population variance, not a sample variance.
+            v/((self.len()-1) as f64)
+        }
+    }
+
+    fn std_dev(self) -> f64 {
+        self.var().sqrt()
+    }
+
+    fn std_dev_pct(self) -> f64 {
+        (self.std_dev() / self.mean()) * 100.0
+    }
+
+    fn median_abs_dev(self) -> f64 {
+        let med = self.median();
+        let abs_devs: Vec<f64> = self.iter().map(|&v| num::abs(med - v)).collect();
+        // This constant is derived by smarter statistics brains than me, but it is
+        // consistent with how R and other packages treat the MAD.
+        abs_devs.as_slice().median() * 1.4826
+    }
+
+    fn median_abs_dev_pct(self) -> f64 {
+        (self.median_abs_dev() / self.median()) * 100.0
+    }
+
+    fn percentile(self, pct: f64) -> f64 {
+        let mut tmp = Vec::from_slice(self);
+        f64_sort(tmp.as_mut_slice());
+        percentile_of_sorted(tmp.as_slice(), pct)
+    }
+
+    fn quartiles(self) -> (f64,f64,f64) {
+        let mut tmp = Vec::from_slice(self);
+        f64_sort(tmp.as_mut_slice());
+        let a = percentile_of_sorted(tmp.as_slice(), 25.0);
+        let b = percentile_of_sorted(tmp.as_slice(), 50.0);
+        let c = percentile_of_sorted(tmp.as_slice(), 75.0);
+        (a,b,c)
+    }
+
+    fn iqr(self) -> f64 {
+        let (a,_,c) = self.quartiles();
+        c - a
+    }
+}
+
+
+// Helper function: extract a value representing the `pct` percentile of a sorted sample-set, using
+// linear interpolation. If samples are not sorted, return nonsensical value.
+fn percentile_of_sorted(sorted_samples: &[f64],
+                             pct: f64) -> f64 {
+    assert!(sorted_samples.len() != 0);
+    if sorted_samples.len() == 1 {
+        return sorted_samples[0];
+    }
+    assert!(0.0 <= pct);
+    assert!(pct <= 100.0);
+    if pct == 100.0 {
+        return sorted_samples[sorted_samples.len() - 1];
+    }
+    let rank = (pct / 100.0) * ((sorted_samples.len() - 1) as f64);
+    let lrank = rank.floor();
+    let d = rank - lrank;
+    let n = lrank as uint;
+    let lo = sorted_samples[n];
+    let hi = sorted_samples[n+1];
+    lo + (hi - lo) * d
+}
+
+
+/// Winsorize a set of samples, replacing values above the `100-pct` percentile and below the `pct`
+/// percentile with those percentiles themselves. This is a way of minimizing the effect of
+/// outliers, at the cost of biasing the sample. It differs from trimming in that it does not
+/// change the number of samples, just changes the values of those that are outliers.
+///
+/// See: http://en.wikipedia.org/wiki/Winsorising
+pub fn winsorize(samples: &mut [f64], pct: f64) {
+    let mut tmp = Vec::from_slice(samples);
+    f64_sort(tmp.as_mut_slice());
+    let lo = percentile_of_sorted(tmp.as_slice(), pct);
+    let hi = percentile_of_sorted(tmp.as_slice(), 100.0-pct);
+    for samp in samples.mut_iter() {
+        if *samp > hi {
+            *samp = hi
+        } else if *samp < lo {
+            *samp = lo
+        }
+    }
+}
+
+
+/// Statistics of a stream of samples, updated one sample at a time in
+/// constant space.
+///
+/// The mean and variance are computed with Welford's algorithm, which
+/// avoids the loss of precision of summing squares. Two `OnlineStats` built
+/// from different samples can be merged, for example to combine the results
+/// of several tasks.
+#[deriving(Clone, Show)]
+pub struct OnlineStats {
+    count: u64,
+    mean: f64,
+    m2: f64,
+    min: f64,
+    max: f64,
+}
+
+impl OnlineStats {
+    /// Create an empty `OnlineStats`.
+    pub fn new() -> OnlineStats {
+        OnlineStats {
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    /// Add a sample.
+    pub fn push(&mut self, x: f64) {
+        self.count += 1;
+        let delta = x - self.mean;
+        self.mean += delta / (self.count as f64);
+        self.m2 += delta * (x - self.mean);
+        self.min = self.min.min(x);
+        self.max = self.max.max(x);
+    }
+
+    /// Add all the samples seen by `other`, as if they had been pushed to
+    /// `self`.
+    pub fn merge(&mut self, other: &OnlineStats) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            *self = other.clone();
+            return;
+        }
+        let (n1, n2) = (self.count as f64, other.count as f64);
+        let n = n1 + n2;
+        let delta = other.mean - self.mean;
+        self.mean += delta * n2 / n;
+        self.m2 += other.m2 + delta * delta * n1 * n2 / n;
+        self.count += other.count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    /// Number of samples.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Arithmetic mean of the samples, or NaN if there are none.
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 { f64::NAN } else { self.mean }
+    }
+
+    /// Sample variance, as computed by `Stats::var`. Zero if there are fewer
+    /// than two samples.
+    pub fn var(&self) -> f64 {
+        if self.count < 2 { 0.0 } else { self.m2 / ((self.count - 1) as f64) }
+    }
+
+    /// Standard deviation: the square root of the sample variance.
+    pub fn std_dev(&self) -> f64 {
+        self.var().sqrt()
+    }
+
+    /// Smallest sample, or NaN if there are none.
+    pub fn min(&self) -> f64 {
+        if self.count == 0 { f64::NAN } else { self.min }
+    }
+
+    /// Largest sample, or NaN if there are none.
+    pub fn max(&self) -> f64 {
+        if self.count == 0 { f64::NAN } else { self.max }
+    }
+}
+
+impl Default for OnlineStats {
+    fn default() -> OnlineStats { OnlineStats::new() }
+}
+
+impl Extendable<f64> for OnlineStats {
+    fn extend<T: Iterator<f64>>(&mut self, mut iter: T) {
+        for x in iter {
+            self.push(x);
+        }
+    }
+}
+
+impl FromIterator<f64> for OnlineStats {
+    fn from_iter<T: Iterator<f64>>(iter: T) -> OnlineStats {
+        let mut stats = OnlineStats::new();
+        stats.extend(iter);
+        stats
+    }
+}
+
+/// A histogram counting samples in equally sized bins spanning `[lo, hi)`.
+///
+/// Samples below `lo` or at or above `hi` are counted separately as
+/// underflow and overflow. NaN samples are ignored.
+#[deriving(Clone, Eq, Show)]
+pub struct Histogram {
+    lo: f64,
+    hi: f64,
+    bins: Vec<u64>,
+    underflow: u64,
+    overflow: u64,
+}
+
+impl Histogram {
+    /// Create an empty histogram with `bins` bins spanning `[lo, hi)`.
+    ///
+    /// # Failure
+    ///
+    /// Fails if `bins` is zero or `lo` isn't less than `hi`.
+    pub fn new(lo: f64, hi: f64, bins: uint) -> Histogram {
+        assert!(bins > 0, "a histogram needs at least one bin");
+        assert!(lo < hi, "histogram range is empty");
+        Histogram {
+            lo: lo,
+            hi: hi,
+            bins: Vec::from_elem(bins, 0u64),
+            underflow: 0,
+            overflow: 0,
+        }
+    }
+
+    /// Count a sample.
+    pub fn push(&mut self, x: f64) {
+        if x.is_nan() {
+            return;
+        }
+        if x < self.lo {
+            self.underflow += 1;
+        } else if x >= self.hi {
+            self.overflow += 1;
+        } else {
+            let n = self.bins.len();
+            let i = ((x - self.lo) / (self.hi - self.lo) * (n as f64)) as uint;
+            // Rounding may push samples just below `hi` past the last bin.
+            *self.bins.get_mut(cmp::min(i, n - 1)) += 1;
+        }
+    }
+
+    /// Add the counts of `other`, which must have the same range and number
+    /// of bins.
+    ///
+    /// # Failure
+    ///
+    /// Fails if the histograms have different bins.
+    pub fn merge(&mut self, other: &Histogram) {
+        assert!(self.lo == other.lo && self.hi == other.hi &&
+                self.bins.len() == other.bins.len(),
+                "can't merge histograms with different bins");
+        for (a, b) in self.bins.mut_iter().zip(other.bins.iter()) {
+            *a += *b;
+        }
+        self.underflow += other.underflow;
+        self.overflow += other.overflow;
+    }
+
+    /// The number of samples in each bin.
+    pub fn bins<'a>(&'a self) -> &'a [u64] {
+        self.bins.as_slice()
+    }
+
+    /// The range `[lo, hi)` covered by bin `i`.
+    pub fn bin_range(&self, i: uint) -> (f64, f64) {
+        assert!(i < self.bins.len());
+        let width = (self.hi - self.lo) / (self.bins.len() as f64);
+        let lo = self.lo + width * (i as f64);
+        let hi = if i == self.bins.len() - 1 { self.hi } else { lo + width };
+        (lo, hi)
+    }
+
+    /// The number of samples below the range of the histogram.
+    pub fn underflow(&self) -> u64 {
+        self.underflow
+    }
+
+    /// The number of samples at or above the end of the range of the
+    /// histogram.
+    pub fn overflow(&self) -> u64 {
+        self.overflow
+    }
+
+    /// The total number of samples counted, including underflow and
+    /// overflow.
+    pub fn count(&self) -> u64 {
+        self.bins.iter().fold(self.underflow + self.overflow, |n, b| n + *b)
+    }
+}
+
+impl Extendable<f64> for Histogram {
+    fn extend<T: Iterator<f64>>(&mut self, mut iter: T) {
+        for x in iter {
+            self.push(x);
+        }
+    }
+}
+
+#[deriving(Clone, Show)]
+struct SketchEntry {
+    value: f64,
+    // The difference between the smallest possible rank of this entry and
+    // that of the previous one.
+    g: u64,
+    // The difference between the largest and smallest possible rank of this
+    // entry.
+    delta: u64,
+}
+
+/// An approximate quantile summary of a stream of samples.
+///
+/// This is the Greenwald-Khanna sketch: a query for the `phi`-quantile of
+/// `n` samples returns a sample whose rank is within `epsilon * n` of
+/// `phi * n`, while storing only `O(log(epsilon * n) / epsilon)` samples.
+/// The smallest and largest samples are always returned exactly. NaN
+/// samples are ignored.
+///
+/// # Example
+///
+/// ```rust
+/// use num::stats::QuantileSketch;
+///
+/// let mut sketch = QuantileSketch::new(0.01);
+/// for i in range(0, 10000) {
+///     sketch.push(i as f64);
+/// }
+/// let median = sketch.quantile(0.5).unwrap();
+/// assert!(median >= 4900.0 && median <= 5100.0);
+/// ```
+#[deriving(Clone)]
+pub struct QuantileSketch {
+    epsilon: f64,
+    count: u64,
+    compress_period: u64,
+    entries: Vec<SketchEntry>,
+}
+
+impl QuantileSketch {
+    /// Create an empty sketch with the given error bound.
+    ///
+    /// # Failure
+    ///
+    /// Fails unless `0 < epsilon < 1`.
+    pub fn new(epsilon: f64) -> QuantileSketch {
+        assert!(0.0 < epsilon && epsilon < 1.0, "epsilon must be between 0 and 1");
+        let period = (1.0 / (2.0 * epsilon)).floor() as u64;
+        QuantileSketch {
+            epsilon: epsilon,
+            count: 0,
+            compress_period: if period == 0 { 1 } else { period },
+            entries: Vec::new(),
+        }
+    }
+
+    /// The error bound of the sketch, as a fraction of the number of
+    /// samples.
+    pub fn epsilon(&self) -> f64 {
+        self.epsilon
+    }
+
+    /// Number of samples.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Add a sample.
+    pub fn push(&mut self, x: f64) {
+        if x.is_nan() {
+            return;
+        }
+
+        // Find the first entry with a larger value.
+        let (mut lo, mut hi) = (0, self.entries.len());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if self.entries.get(mid).value <= x {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+
+        // A new minimum or maximum is known exactly; anything else may have
+        // any rank allowed by its neighbours.
+        let delta = if lo == 0 || lo == self.entries.len() {
+            0
+        } else {
+            self.band()
+        };
+        self.entries.insert(lo, SketchEntry { value: x, g: 1, delta: delta });
+        self.count += 1;
+
+        if self.count % self.compress_period == 0 {
+            self.compress();
+        }
+    }
+
+    /// Return an approximation of the `phi`-quantile of the samples, or
+    /// `None` if there are none.
+    ///
+    /// # Failure
+    ///
+    /// Fails unless `0 <= phi <= 1`.
+    pub fn quantile(&self, phi: f64) -> Option<f64> {
+        assert!(0.0 <= phi && phi <= 1.0, "quantile must be between 0 and 1");
+        if self.entries.is_empty() {
+            return None;
+        }
+        let rank = (phi * (self.count as f64)).ceil();
+        let bound = rank + self.epsilon * (self.count as f64);
+        let mut min_rank = 0;
+        let mut prev = self.entries.get(0).value;
+        for e in self.entries.iter() {
+            if ((min_rank + e.g + e.delta) as f64) > bound {
+                return Some(prev);
+            }
+            min_rank += e.g;
+            prev = e.value;
+        }
+        Some(prev)
+    }
+
+    fn band(&self) -> u64 {
+        (2.0 * self.epsilon * (self.count as f64)).floor() as u64
+    }
+
+    // Merge entries into their successors wherever that keeps the rank
+    // uncertainty within the error bound. The first and last entries are
+    // never removed, so the extremes stay exact.
+    fn compress(&mut self) {
+        let band = self.band();
+        let mut i = self.entries.len() as int - 2;
+        while i >= 1 {
+            let j = i as uint;
+            let (g, next_g, next_delta) = {
+                let (a, b) = (self.entries.get(j), self.entries.get(j + 1));
+                (a.g, b.g, b.delta)
+            };
+            if g + next_g + next_delta <= band {
+                self.entries.get_mut(j + 1).g += g;
+                self.entries.remove(j);
+            }
+            i -= 1;
+        }
+    }
+}
+
+impl Extendable<f64> for QuantileSketch {
+    fn extend<T: Iterator<f64>>(&mut self, mut iter: T) {
+        for x in iter {
+            self.push(x);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Stats, OnlineStats, Histogram, QuantileSketch};
+
+    macro_rules! assert_approx_eq(
+        ($a:expr, $b:expr) => ({
+            let (a, b) = (&$a, &$b);
+            assert!((*a - *b).abs() < 1.0e-6,
+                    "{} is not approximately equal to {}", *a, *b);
+        })
+    )
+
+    #[test]
+    fn test_online_stats() {
+        let samples = [2.5, -1.0, 7.25, 3.0, 3.0, 10.5, 0.125];
+        let stats: OnlineStats = samples.iter().map(|&x| x).collect();
+        assert_eq!(stats.count(), 7);
+        assert_approx_eq!(stats.mean(), samples.mean());
+        assert_approx_eq!(stats.var(), samples.var());
+        assert_approx_eq!(stats.std_dev(), samples.std_dev());
+        assert_eq!(stats.min(), -1.0);
+        assert_eq!(stats.max(), 10.5);
+
+        let mut a: OnlineStats = samples.slice_to(3).iter().map(|&x| x).collect();
+        let b: OnlineStats = samples.slice_from(3).iter().map(|&x| x).collect();
+        a.merge(&b);
+        a.merge(&OnlineStats::new());
+        assert_eq!(a.count(), 7);
+        assert_approx_eq!(a.mean(), samples.mean());
+        assert_approx_eq!(a.var(), samples.var());
+        assert_eq!(a.min(), -1.0);
+        assert_eq!(a.max(), 10.5);
+
+        let empty = OnlineStats::new();
+        assert!(empty.mean().is_nan() && empty.min().is_nan());
+        assert_eq!(empty.var(), 0.0);
+    }
+
+    #[test]
+    fn test_histogram() {
+        let mut h = Histogram::new(0.0, 10.0, 5);
+        h.extend([-1.0, 0.0, 1.9, 2.0, 5.5, 9.99, 10.0, 42.0, 0.0 / 0.0].iter().map(|&x| x));
+        assert_eq!(h.bins(), [2u64, 1, 1, 0, 1].as_slice());
+        assert_eq!(h.underflow(), 1);
+        assert_eq!(h.overflow(), 2);
+        assert_eq!(h.count(), 8);
+        assert_eq!(h.bin_range(1), (2.0, 4.0));
+        assert_eq!(h.bin_range(4), (8.0, 10.0));
+
+        let mut other = Histogram::new(0.0, 10.0, 5);
+        other.push(3.0);
+        h.merge(&other);
+        assert_eq!(h.bins(), [2u64, 2, 1, 0, 1].as_slice());
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_histogram_merge_mismatch() {
+        let mut h = Histogram::new(0.0, 10.0, 5);
+        h.merge(&Histogram::new(0.0, 10.0, 4));
+    }
+
+    #[test]
+    fn test_quantile_sketch() {
+        let mut sketch = QuantileSketch::new(0.01);
+        assert_eq!(sketch.quantile(0.5), None);
+
+        // A permutation of most of 0..10007.
+        let samples = Vec::from_fn(10000, |i| ((i * 7919) % 10007) as f64);
+        sketch.extend(samples.iter().map(|&x| x));
+        assert_eq!(sketch.count(), 10000);
+        assert!(sketch.entries.len() < 200);
+
+        assert_eq!(sketch.quantile(0.0), Some(0.0));
+        assert_eq!(sketch.quantile(1.0), Some(10006.0));
+        for &phi in [0.01, 0.1, 0.25, 0.5, 0.75, 0.9, 0.99].iter() {
+            let v = sketch.quantile(phi).unwrap();
+            let rank = samples.iter().filter(|&&x| x <= v).len() as f64;
+            assert!((rank - phi * 10000.0).abs() <= 100.0,
+                    "quantile {} is {}, with rank {}", phi, v, rank);
+        }
+    }
+}
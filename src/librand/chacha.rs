@@ -0,0 +1,316 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The ChaCha random number generator.
+
+use {Rng, SeedableRng, OSRng};
+use std::io::IoResult;
+use std::iter::Repeat;
+
+static KEY_WORDS: uint = 8;     // 256 bit key
+static STATE_WORDS: uint = 16;
+static CHACHA_ROUNDS: uint = 20;
+
+/// A random number generator that uses the ChaCha20 algorithm[1].
+///
+/// The ChaCha family of stream ciphers is widely used and analysed, and
+/// this generator is suitable for cryptographic purposes when seeded from a
+/// secure source such as `OSRng`, which is what `ChaChaRng::new` does.
+///
+/// The generator is keyed by its seed, and produces the keystream of that
+/// key as 32-bit words in little-endian order. Each key has 2^64 streams of
+/// 2^64 blocks of 16 words, selected by `set_stream` and `set_counter`.
+/// These make it easy to give parallel tasks independent generators
+/// derived from one seed.
+///
+/// The sequence produced for a given seed will not change between
+/// releases.
+///
+/// [1]: D. J. Bernstein, [*ChaCha, a variant of
+/// Salsa20*](http://cr.yp.to/chacha.html)
+#[deriving(Clone)]
+pub struct ChaChaRng {
+    buffer: [u32, .. STATE_WORDS], // Internal buffer of output
+    state: [u32, .. STATE_WORDS],  // Initial state
+    index: uint,                   // Index into buffer
+}
+
+static EMPTY: ChaChaRng = ChaChaRng {
+    buffer: [0, .. STATE_WORDS],
+    state: [0, .. STATE_WORDS],
+    index: STATE_WORDS
+};
+
+#[inline]
+fn rotl(x: u32, k: uint) -> u32 {
+    (x << k) | (x >> (32 - k))
+}
+
+macro_rules! quarter_round(
+    ($a:expr, $b:expr, $c:expr, $d:expr) => {{
+        $a += $b; $d ^= $a; $d = rotl($d, 16);
+        $c += $d; $b ^= $c; $b = rotl($b, 12);
+        $a += $b; $d ^= $a; $d = rotl($d, 8);
+        $c += $d; $b ^= $c; $b = rotl($b, 7);
+    }}
+)
+
+macro_rules! double_round(
+    ($x:expr) => {{
+        // Column round
+        quarter_round!($x[ 0], $x[ 4], $x[ 8], $x[12]);
+        quarter_round!($x[ 1], $x[ 5], $x[ 9], $x[13]);
+        quarter_round!($x[ 2], $x[ 6], $x[10], $x[14]);
+        quarter_round!($x[ 3], $x[ 7], $x[11], $x[15]);
+        // Diagonal round
+        quarter_round!($x[ 0], $x[ 5], $x[10], $x[15]);
+        quarter_round!($x[ 1], $x[ 6], $x[11], $x[12]);
+        quarter_round!($x[ 2], $x[ 7], $x[ 8], $x[13]);
+        quarter_round!($x[ 3], $x[ 4], $x[ 9], $x[14]);
+    }}
+)
+
+#[inline]
+fn core(output: &mut [u32, .. STATE_WORDS], input: &[u32, .. STATE_WORDS]) {
+    *output = *input;
+
+    for _ in range(0, CHACHA_ROUNDS / 2) {
+        double_round!(output);
+    }
+
+    for i in range(0, STATE_WORDS) {
+        output[i] += input[i];
+    }
+}
+
+impl ChaChaRng {
+    /// Create a ChaCha random number generator with a random key.
+    ///
+    /// This reads randomness from the operating system (via `OSRng`)
+    /// which may fail, any error is propagated via the `IoResult`
+    /// return value.
+    pub fn new() -> IoResult<ChaChaRng> {
+        let mut os_rng = try!(OSRng::new());
+        let mut key = [0u32, .. KEY_WORDS];
+        for k in key.mut_iter() {
+            *k = os_rng.next_u32();
+        }
+        let mut rng = EMPTY;
+        rng.init(&key);
+        Ok(rng)
+    }
+
+    /// Create a ChaCha random number generator using the all-zero key.
+    /// Its output is well known, so it should only be used where
+    /// reproducibility matters more than security.
+    pub fn new_unseeded() -> ChaChaRng {
+        let mut rng = EMPTY;
+        rng.init(&[0, .. KEY_WORDS]);
+        rng
+    }
+
+    /// Create a generator with the same key as this one, positioned at the
+    /// start of stream `stream`.
+    pub fn with_stream(&self, stream: u64) -> ChaChaRng {
+        let mut rng = EMPTY;
+        rng.state = self.state;
+        rng.set_stream(stream);
+        rng
+    }
+
+    /// Switch to stream `stream` and move to its start. A generator starts
+    /// on stream 0.
+    pub fn set_stream(&mut self, stream: u64) {
+        self.state[14] = stream as u32;
+        self.state[15] = (stream >> 32) as u32;
+        self.set_counter(0);
+    }
+
+    /// Move to the start of block `counter` of the current stream, that is
+    /// to the `16 * counter`th word of output. This discards any output
+    /// buffered from the current block.
+    pub fn set_counter(&mut self, counter: u64) {
+        self.state[12] = counter as u32;
+        self.state[13] = (counter >> 32) as u32;
+        self.index = STATE_WORDS;
+    }
+
+    /// Initialise `self` with `key`, on stream 0 and at block 0.
+    fn init(&mut self, key: &[u32, .. KEY_WORDS]) {
+        self.state[0] = 0x61707865;
+        self.state[1] = 0x3320646E;
+        self.state[2] = 0x79622D32;
+        self.state[3] = 0x6B206574;
+
+        for i in range(0, KEY_WORDS) {
+            self.state[4 + i] = key[i];
+        }
+
+        self.state[12] = 0;
+        self.state[13] = 0;
+        self.state[14] = 0;
+        self.state[15] = 0;
+
+        self.index = STATE_WORDS;
+    }
+
+    /// Refill the internal output buffer (`self.buffer`) and move on to the
+    /// next block.
+    fn update(&mut self) {
+        core(&mut self.buffer, &self.state);
+        self.index = 0;
+        // 64-bit block counter
+        self.state[12] += 1;
+        if self.state[12] != 0 { return };
+        self.state[13] += 1;
+    }
+}
+
+impl Rng for ChaChaRng {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        if self.index == STATE_WORDS {
+            self.update();
+        }
+
+        let value = self.buffer[self.index];
+        self.index += 1;
+        value
+    }
+}
+
+impl<'a> SeedableRng<&'a [u32]> for ChaChaRng {
+    fn reseed(&mut self, seed: &'a [u32]) {
+        // make the seed into [seed[0], seed[1], ..., seed[seed.len()
+        // - 1], 0, 0, ...], to fill the key.
+        let mut key = [0u32, .. KEY_WORDS];
+        let seed_iter = seed.iter().map(|&x| x).chain(Repeat::new(0u32));
+        for (k, s) in key.mut_iter().zip(seed_iter) {
+            *k = s;
+        }
+        self.init(&key);
+    }
+
+    /// Create a ChaCha generator from a seed, which is used as the key.
+    /// This can be any length, although the maximum number of elements
+    /// used is 8 and any more will be silently ignored. A generator
+    /// constructed with a given seed will generate the same sequence of
+    /// values as all other generators constructed with that seed.
+    fn from_seed(seed: &'a [u32]) -> ChaChaRng {
+        let mut rng = EMPTY;
+        rng.reseed(seed);
+        rng
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ChaChaRng;
+    use {Rng, SeedableRng, task_rng};
+
+    #[test]
+    fn test_rng_rand_seeded() {
+        let s = task_rng().gen_vec::<u32>(8);
+        let mut ra: ChaChaRng = SeedableRng::from_seed(s.as_slice());
+        let mut rb: ChaChaRng = SeedableRng::from_seed(s.as_slice());
+        assert_eq!(ra.gen_ascii_str(100u), rb.gen_ascii_str(100u));
+    }
+
+    #[test]
+    fn test_rng_seeded() {
+        let seed = &[0, 1, 2, 3, 4, 5, 6, 7];
+        let mut ra: ChaChaRng = SeedableRng::from_seed(seed);
+        let mut rb: ChaChaRng = SeedableRng::from_seed(seed);
+        assert_eq!(ra.gen_ascii_str(100u), rb.gen_ascii_str(100u));
+    }
+
+    #[test]
+    fn test_rng_clone() {
+        let seed = &[0, 1, 2, 3, 4, 5, 6, 7];
+        let mut ra: ChaChaRng = SeedableRng::from_seed(seed);
+        ra.gen_vec::<u32>(5);
+        let mut rb = ra.clone();
+        assert_eq!(ra.gen_vec::<u32>(40), rb.gen_vec::<u32>(40));
+    }
+
+    #[test]
+    fn test_rng_reseed() {
+        let s = task_rng().gen_vec::<u32>(8);
+        let mut r: ChaChaRng = SeedableRng::from_seed(s.as_slice());
+        let string1 = r.gen_ascii_str(100);
+
+        r.reseed(s.as_slice());
+
+        let string2 = r.gen_ascii_str(100);
+        assert_eq!(string1, string2);
+    }
+
+    #[test]
+    fn test_rng_streams() {
+        let seed = &[0, 1, 2, 3, 4, 5, 6, 7];
+        let mut ra: ChaChaRng = SeedableRng::from_seed(seed);
+        let mut rb = ra.with_stream(1);
+        let mut rc = ra.with_stream(0);
+        let v = ra.gen_vec::<u32>(10);
+        assert!(v != rb.gen_vec::<u32>(10));
+        assert_eq!(v, rc.gen_vec::<u32>(10));
+
+        // seeking forward a block skips 16 words
+        let mut rd = ra.with_stream(0);
+        rd.set_counter(1);
+        let mut re = ra.with_stream(0);
+        for _ in range(0, 16) { re.next_u32(); }
+        assert_eq!(rd.gen_vec::<u32>(20), re.gen_vec::<u32>(20));
+    }
+
+    #[test]
+    fn test_rng_true_values() {
+        // Test vectors 1 and 2 from
+        // http://tools.ietf.org/html/draft-nir-cfrg-chacha20-poly1305-04
+        let seed = &[0u32, 0, 0, 0, 0, 0, 0, 0];
+        let mut ra: ChaChaRng = SeedableRng::from_seed(seed);
+
+        let v = Vec::from_fn(16, |_| ra.next_u32());
+        assert_eq!(v,
+                   vec!(0xade0b876, 0x903df1a0, 0xe56a5d40, 0x28bd8653,
+                        0xb819d2bd, 0x1aed8da0, 0xccef36a8, 0xc70d778b,
+                        0x7c5941da, 0x8d485751, 0x3fe02477, 0x374ad8b8,
+                        0xf4b8436a, 0x1ca11815, 0x69b687c3, 0x8665eeb2));
+
+        let v = Vec::from_fn(16, |_| ra.next_u32());
+        assert_eq!(v,
+                   vec!(0xbee7079f, 0x7a385155, 0x7c97ba98, 0x0d082d73,
+                        0xa0290fcb, 0x6965e348, 0x3e53c612, 0xed7aee32,
+                        0x7621b729, 0x434ee69c, 0xb03371d5, 0xd539d874,
+                        0x281fed31, 0x45fb0a51, 0x1f0ae1ac, 0x6f4d794b));
+
+        let seed = &[0u32, 1, 2, 3, 4, 5, 6, 7];
+        let mut rb: ChaChaRng = SeedableRng::from_seed(seed);
+        let v = Vec::from_fn(8, |_| rb.next_u32());
+        assert_eq!(v,
+                   vec!(4062562330, 1058763918, 2822854854, 2671630309,
+                        1688322078, 828310969, 186264792, 3721351595));
+
+        // stream and a counter past 2^32 blocks
+        let mut rc = rb.with_stream(5);
+        rc.set_counter((1 << 32) + 3);
+        let v = Vec::from_fn(4, |_| rc.next_u32());
+        assert_eq!(v, vec!(252375118, 1371292989, 3085924130, 201327234));
+
+        // carrying into the high word of the counter
+        let mut rd = rb.with_stream(0);
+        rd.set_counter(0xffffffff);
+        for _ in range(0, 14) { rd.next_u32(); }
+        let v = Vec::from_fn(6, |_| rd.next_u32());
+        assert_eq!(v,
+                   vec!(3009707711, 3874275941, 398268743,
+                        2309667637, 2918942064, 3064370431));
+    }
+}
@@ -0,0 +1,78 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The Bernoulli distribution.
+
+use Rng;
+use distributions::{Sample, IndependentSample};
+
+/// The Bernoulli distribution, which is `true` with probability `p` and
+/// `false` otherwise.
+///
+/// # Example
+///
+/// ```rust
+/// use rand::distributions::{Bernoulli, IndependentSample};
+///
+/// let coin = Bernoulli::new(0.5);
+/// let v = coin.ind_sample(&mut rand::task_rng());
+/// println!("the coin came up {}", if v { "heads" } else { "tails" });
+/// ```
+pub struct Bernoulli {
+    p: f64
+}
+
+impl Bernoulli {
+    /// Construct a new `Bernoulli` with probability `p` of success. Fails
+    /// if `p` isn't between 0 and 1.
+    pub fn new(p: f64) -> Bernoulli {
+        assert!(p >= 0.0 && p <= 1.0, "Bernoulli::new called with `p` not in [0, 1]");
+        Bernoulli { p: p }
+    }
+}
+
+impl Sample<bool> for Bernoulli {
+    fn sample<R: Rng>(&mut self, rng: &mut R) -> bool { self.ind_sample(rng) }
+}
+impl IndependentSample<bool> for Bernoulli {
+    fn ind_sample<R: Rng>(&self, rng: &mut R) -> bool {
+        // gen::<f64>() is in [0, 1), so p == 1 is always true and p == 0
+        // always false.
+        rng.gen::<f64>() < self.p
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use distributions::{Sample, IndependentSample};
+    use {SeedableRng, XorShiftRng, task_rng};
+    use super::Bernoulli;
+
+    #[test]
+    fn test_bernoulli() {
+        let mut always = Bernoulli::new(1.0);
+        let never = Bernoulli::new(0.0);
+        let mut rng = task_rng();
+        for _ in range(0, 1000) {
+            assert!(always.sample(&mut rng));
+            assert!(!never.ind_sample(&mut rng));
+        }
+
+        let mut rng: XorShiftRng = SeedableRng::from_seed([1, 2, 3, 4]);
+        let b = Bernoulli::new(0.3);
+        let n = range(0, 10000).filter(|_| b.ind_sample(&mut rng)).len();
+        assert!(n > 2800 && n < 3200, "{} successes out of 10000", n);
+    }
+    #[test]
+    #[should_fail]
+    fn test_bernoulli_invalid_p() {
+        Bernoulli::new(1.5);
+    }
+}
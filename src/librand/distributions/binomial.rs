@@ -0,0 +1,250 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The binomial and Poisson distributions.
+
+use std::num::Float;
+use Rng;
+use distributions::{Sample, IndependentSample, Gamma, Beta};
+
+// Below these sizes, sample directly rather than splitting the problem.
+static BINOMIAL_DIRECT_MAX: u64 = 64;
+static POISSON_DIRECT_MAX: f64 = 16.0;
+
+/// The binomial distribution `Binomial(n, p)`: the number of successes in
+/// `n` independent trials which each succeed with probability `p`.
+///
+/// Large `n` are split into smaller problems with the Beta distribution as
+/// described by Knuth[1], so sampling takes `O(log n)` time.
+///
+/// [1]: Donald E. Knuth (1997). *The Art of Computer Programming, Volume 2:
+/// Seminumerical Algorithms*, 3rd edition, section 3.4.1F.
+///
+/// # Example
+///
+/// ```rust
+/// use rand::distributions::{Binomial, IndependentSample};
+///
+/// let bin = Binomial::new(20, 0.3);
+/// let v = bin.ind_sample(&mut rand::task_rng());
+/// println!("{} is from a binomial distribution", v);
+/// ```
+pub struct Binomial {
+    n: u64,
+    p: f64,
+}
+
+impl Binomial {
+    /// Construct a new `Binomial` with `n` trials and probability `p` of
+    /// success. Fails if `p` isn't between 0 and 1.
+    pub fn new(n: u64, p: f64) -> Binomial {
+        assert!(p >= 0.0 && p <= 1.0, "Binomial::new called with `p` not in [0, 1]");
+        Binomial { n: n, p: p }
+    }
+}
+
+impl Sample<u64> for Binomial {
+    fn sample<R: Rng>(&mut self, rng: &mut R) -> u64 { self.ind_sample(rng) }
+}
+impl IndependentSample<u64> for Binomial {
+    fn ind_sample<R: Rng>(&self, rng: &mut R) -> u64 {
+        let (mut n, mut p) = (self.n, self.p);
+        let mut successes = 0;
+        while n > BINOMIAL_DIRECT_MAX {
+            // x is distributed as the a'th smallest of n uniform samples.
+            // The trials whose samples fall on the same side of x as p
+            // are then a smaller binomial problem, and the rest are
+            // decided.
+            let a = 1 + n / 2;
+            let b = n + 1 - a;
+            let x = Beta::new(a as f64, b as f64).ind_sample(rng);
+            if x >= p {
+                n = a - 1;
+                p = p / x;
+            } else {
+                successes += a;
+                n = b - 1;
+                p = (p - x) / (1.0 - x);
+            }
+        }
+        for _ in range(0, n) {
+            if rng.gen::<f64>() < p {
+                successes += 1;
+            }
+        }
+        successes
+    }
+}
+
+/// The Poisson distribution `Poisson(lambda)`: the number of events in a
+/// unit of time, when they occur independently at an average rate of
+/// `lambda`.
+///
+/// Small `lambda` are sampled by multiplying uniform numbers, and large
+/// ones are first reduced using the Gamma and binomial distributions as
+/// described by Ahrens and Dieter[1], so sampling takes `O(log lambda)`
+/// time.
+///
+/// [1]: J. H. Ahrens and U. Dieter (1974). *Computer methods for sampling
+/// from gamma, beta, poisson and binomial distributions*. Computing 12,
+/// 223-246.
+///
+/// # Example
+///
+/// ```rust
+/// use rand::distributions::{Poisson, IndependentSample};
+///
+/// let poi = Poisson::new(2.0);
+/// let v = poi.ind_sample(&mut rand::task_rng());
+/// println!("{} is from a Poisson(2) distribution", v);
+/// ```
+pub struct Poisson {
+    lambda: f64,
+}
+
+impl Poisson {
+    /// Construct a new `Poisson` with the given rate `lambda`. Fails if
+    /// `lambda <= 0`.
+    pub fn new(lambda: f64) -> Poisson {
+        assert!(lambda > 0.0, "Poisson::new called with `lambda` <= 0");
+        Poisson { lambda: lambda }
+    }
+}
+
+impl Sample<u64> for Poisson {
+    fn sample<R: Rng>(&mut self, rng: &mut R) -> u64 { self.ind_sample(rng) }
+}
+impl IndependentSample<u64> for Poisson {
+    fn ind_sample<R: Rng>(&self, rng: &mut R) -> u64 {
+        let mut lambda = self.lambda;
+        let mut events = 0;
+        while lambda > POISSON_DIRECT_MAX {
+            // x is the time of the m'th event of a Poisson process with
+            // rate 1. If it is before lambda, those m events count and
+            // the rest of the time remains; otherwise each of the first
+            // m - 1 events is independently before lambda.
+            let m = (lambda * 0.875).floor() as u64;
+            let x = Gamma::new(m as f64, 1.0).ind_sample(rng);
+            if x < lambda {
+                events += m;
+                lambda -= x;
+            } else {
+                return events + Binomial::new(m - 1, lambda / x).ind_sample(rng);
+            }
+        }
+
+        let limit = (-lambda).exp();
+        let mut product = rng.gen::<f64>();
+        while product > limit {
+            product *= rng.gen::<f64>();
+            events += 1;
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use distributions::{Sample, IndependentSample};
+    use {SeedableRng, XorShiftRng, task_rng};
+    use super::{Binomial, Poisson};
+
+    // Check the sample mean and variance of a distribution over u64 with a
+    // seeded generator, to within `tolerance` relative error.
+    fn check_moments<D: IndependentSample<u64>>(d: D, mean: f64, var: f64, tolerance: f64) {
+        let mut rng: XorShiftRng = SeedableRng::from_seed([0x193a6754, 0xa8a7d469,
+                                                          0x97830e05, 0x113ba7bb]);
+        let n = 20000;
+        let samples = Vec::from_fn(n, |_| d.ind_sample(&mut rng) as f64);
+        let m = samples.iter().fold(0.0, |s, x| s + *x) / (n as f64);
+        let v = samples.iter().fold(0.0, |s, x| s + (*x - m) * (*x - m)) / ((n - 1) as f64);
+        assert!((m - mean).abs() <= mean * tolerance, "mean {}, expected {}", m, mean);
+        assert!((v - var).abs() <= var * tolerance, "variance {}, expected {}", v, var);
+    }
+
+    #[test]
+    fn test_binomial() {
+        let mut bin = Binomial::new(150, 0.3);
+        let mut rng = task_rng();
+        for _ in range(0, 1000) {
+            assert!(bin.sample(&mut rng) <= 150);
+            assert!(bin.ind_sample(&mut rng) <= 150);
+        }
+        assert_eq!(Binomial::new(1000, 0.0).ind_sample(&mut rng), 0);
+        assert_eq!(Binomial::new(1000, 1.0).ind_sample(&mut rng), 1000);
+        assert_eq!(Binomial::new(0, 0.5).ind_sample(&mut rng), 0);
+
+        check_moments(Binomial::new(20, 0.5), 10.0, 5.0, 0.05);
+        check_moments(Binomial::new(1000, 0.3), 300.0, 210.0, 0.05);
+        check_moments(Binomial::new(1 << 40, 0.25), (1u64 << 38) as f64,
+                      (3u64 << 36) as f64, 0.05);
+    }
+    #[test]
+    #[should_fail]
+    fn test_binomial_invalid_p() {
+        Binomial::new(10, -0.5);
+    }
+
+    #[test]
+    fn test_poisson() {
+        let mut poi = Poisson::new(10.0);
+        let mut rng = task_rng();
+        for _ in range(0, 1000) {
+            poi.sample(&mut rng);
+            poi.ind_sample(&mut rng);
+        }
+
+        check_moments(Poisson::new(0.5), 0.5, 0.5, 0.05);
+        check_moments(Poisson::new(12.0), 12.0, 12.0, 0.05);
+        check_moments(Poisson::new(1000.0), 1000.0, 1000.0, 0.05);
+        check_moments(Poisson::new(1e9), 1e9, 1e9, 0.05);
+    }
+    #[test]
+    #[should_fail]
+    fn test_poisson_invalid_lambda() {
+        Poisson::new(0.0);
+    }
+}
+
+#[cfg(test)]
+mod bench {
+    extern crate test;
+    use self::test::Bencher;
+    use std::mem::size_of;
+    use {XorShiftRng, RAND_BENCH_N};
+    use distributions::Sample;
+    use super::{Binomial, Poisson};
+
+    #[bench]
+    fn rand_binomial(b: &mut Bencher) {
+        let mut rng = XorShiftRng::new().unwrap();
+        let mut bin = Binomial::new(10000, 0.3);
+
+        b.iter(|| {
+            for _ in range(0, RAND_BENCH_N) {
+                bin.sample(&mut rng);
+            }
+        });
+        b.bytes = size_of::<u64>() as u64 * RAND_BENCH_N;
+    }
+
+    #[bench]
+    fn rand_poisson(b: &mut Bencher) {
+        let mut rng = XorShiftRng::new().unwrap();
+        let mut poi = Poisson::new(1000.0);
+
+        b.iter(|| {
+            for _ in range(0, RAND_BENCH_N) {
+                poi.sample(&mut rng);
+            }
+        });
+        b.bytes = size_of::<u64>() as u64 * RAND_BENCH_N;
+    }
+}
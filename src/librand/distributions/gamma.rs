@@ -313,11 +313,96 @@ impl IndependentSample<f64> for StudentT {
     }
 }
 
+/// The Beta distribution `Beta(alpha, beta)`, over the interval `[0, 1]`.
+///
+/// Sampled as `X / (X + Y)`, where `X` and `Y` are independent
+/// `Gamma(alpha, 1)` and `Gamma(beta, 1)` variables.
+///
+/// # Example
+///
+/// ```rust
+/// use rand::distributions::{Beta, IndependentSample};
+///
+/// let beta = Beta::new(2.0, 5.0);
+/// let v = beta.ind_sample(&mut rand::task_rng());
+/// println!("{} is from a Beta(2, 5) distribution", v)
+/// ```
+pub struct Beta {
+    gamma_a: Gamma,
+    gamma_b: Gamma,
+}
+
+impl Beta {
+    /// Create a new Beta distribution with shape parameters `alpha` and
+    /// `beta`. Fails if either is not positive.
+    pub fn new(alpha: f64, beta: f64) -> Beta {
+        assert!(alpha > 0.0, "Beta::new called with `alpha <= 0`");
+        assert!(beta > 0.0, "Beta::new called with `beta <= 0`");
+        Beta {
+            gamma_a: Gamma::new(alpha, 1.0),
+            gamma_b: Gamma::new(beta, 1.0)
+        }
+    }
+}
+impl Sample<f64> for Beta {
+    fn sample<R: Rng>(&mut self, rng: &mut R) -> f64 { self.ind_sample(rng) }
+}
+impl IndependentSample<f64> for Beta {
+    fn ind_sample<R: Rng>(&self, rng: &mut R) -> f64 {
+        let x = self.gamma_a.ind_sample(rng);
+        let y = self.gamma_b.ind_sample(rng);
+        x / (x + y)
+    }
+}
+
+/// The Dirichlet distribution `Dir(alpha)`, over vectors of non-negative
+/// numbers summing to one. It generalises the Beta distribution to more
+/// than two components.
+///
+/// # Example
+///
+/// ```rust
+/// use rand::distributions::{Dirichlet, IndependentSample};
+///
+/// let dir = Dirichlet::new(vec!(1.0, 2.0, 3.0));
+/// let v = dir.ind_sample(&mut rand::task_rng());
+/// println!("{} is from a Dirichlet distribution", v)
+/// ```
+pub struct Dirichlet {
+    gammas: Vec<Gamma>,
+}
+
+impl Dirichlet {
+    /// Create a new Dirichlet distribution with concentration parameters
+    /// `alpha`. Fails if there are fewer than two, or any is not
+    /// positive.
+    pub fn new(alpha: Vec<f64>) -> Dirichlet {
+        assert!(alpha.len() >= 2, "Dirichlet::new called with fewer than 2 parameters");
+        assert!(alpha.iter().all(|a| *a > 0.0), "Dirichlet::new called with `alpha <= 0`");
+        Dirichlet {
+            gammas: alpha.iter().map(|a| Gamma::new(*a, 1.0)).collect()
+        }
+    }
+}
+impl Sample<Vec<f64>> for Dirichlet {
+    fn sample<R: Rng>(&mut self, rng: &mut R) -> Vec<f64> { self.ind_sample(rng) }
+}
+impl IndependentSample<Vec<f64>> for Dirichlet {
+    fn ind_sample<R: Rng>(&self, rng: &mut R) -> Vec<f64> {
+        let mut xs: Vec<f64> = self.gammas.iter().map(|g| g.ind_sample(rng)).collect();
+        let sum = xs.iter().fold(0.0, |s, x| s + *x);
+        for x in xs.mut_iter() {
+            *x = *x / sum;
+        }
+        xs
+    }
+}
+
 #[cfg(test)]
 mod test {
     use distributions::{Sample, IndependentSample};
     use {Rng, task_rng};
-    use super::{ChiSquared, StudentT, FisherF};
+    use super::{ChiSquared, StudentT, FisherF, Beta, Dirichlet};
 
     #[test]
     fn test_chi_squared_one() {
@@ -371,6 +456,41 @@ mod test {
             t.ind_sample(&mut rng);
         }
     }
+
+    #[test]
+    fn test_beta() {
+        let mut beta = Beta::new(0.5, 2.5);
+        let mut rng = task_rng();
+        for _ in range(0, 1000) {
+            let x = beta.sample(&mut rng);
+            assert!(x >= 0.0 && x <= 1.0);
+            let x = beta.ind_sample(&mut rng);
+            assert!(x >= 0.0 && x <= 1.0);
+        }
+    }
+    #[test]
+    #[should_fail]
+    fn test_beta_invalid_alpha() {
+        Beta::new(0.0, 1.0);
+    }
+
+    #[test]
+    fn test_dirichlet() {
+        let mut dir = Dirichlet::new(vec!(0.5, 1.0, 20.0));
+        let mut rng = task_rng();
+        for _ in range(0, 1000) {
+            let v = dir.sample(&mut rng);
+            assert_eq!(v.len(), 3);
+            assert!(v.iter().all(|x| *x >= 0.0));
+            let sum = dir.ind_sample(&mut rng).iter().fold(0.0, |s, x| s + *x);
+            assert!((sum - 1.0).abs() < 1e-9);
+        }
+    }
+    #[test]
+    #[should_fail]
+    fn test_dirichlet_one_param() {
+        Dirichlet::new(vec!(1.0));
+    }
 }
 
 #[cfg(test)]
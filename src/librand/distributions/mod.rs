@@ -21,18 +21,26 @@ that do not need to record state.
 */
 
 use std::num;
-use std::num::CheckedAdd;
+use std::num::{CheckedAdd, CheckedMul};
 use {Rng, Rand};
 
 pub use self::range::Range;
-pub use self::gamma::{Gamma, ChiSquared, FisherF, StudentT};
+pub use self::gamma::{Gamma, ChiSquared, FisherF, StudentT, Beta, Dirichlet};
 pub use self::normal::{Normal, LogNormal};
 pub use self::exponential::Exp;
+pub use self::bernoulli::Bernoulli;
+pub use self::binomial::{Binomial, Poisson};
+pub use self::pareto::{Pareto, Zipf};
+pub use self::sphere::UnitSphereSurface;
 
 pub mod range;
 pub mod gamma;
 pub mod normal;
 pub mod exponential;
+pub mod bernoulli;
+pub mod binomial;
+pub mod pareto;
+pub mod sphere;
 
 /// Types that can be used to create a random instance of `Support`.
 pub trait Sample<Support> {
@@ -186,6 +194,113 @@ impl<T: Clone> IndependentSample<T> for WeightedChoice<T> {
     }
 }
 
+/// A distribution that selects from a finite collection of weighted items in
+/// constant time, using Vose's alias method.
+///
+/// Construction takes time linear in the number of items, after which each
+/// sample costs two random numbers and no search, whereas `WeightedChoice`
+/// searches its items on every sample. This makes `AliasTable` the better
+/// choice when many samples are drawn from the same weights. The
+/// probabilities are exact, as the table is built with integer arithmetic.
+///
+/// # Example
+///
+/// ```rust
+/// use rand::distributions::{Weighted, AliasTable, IndependentSample};
+///
+/// let table = AliasTable::new(vec!(Weighted { weight: 2, item: 'a' },
+///                                  Weighted { weight: 4, item: 'b' },
+///                                  Weighted { weight: 1, item: 'c' }));
+/// let mut rng = rand::task_rng();
+/// for _ in range(0, 16) {
+///      // on average prints 'a' 4 times, 'b' 8 and 'c' twice.
+///      println!("{}", table.ind_sample(&mut rng));
+/// }
+/// ```
+pub struct AliasTable<T> {
+    items: Vec<T>,
+    // Column i of the table holds item i below thresholds[i], and item
+    // aliases[i] above it. Every column has a height of the total weight.
+    thresholds: Vec<u64>,
+    aliases: Vec<uint>,
+    index_range: Range<uint>,
+    weight_range: Range<u64>,
+}
+
+impl<T: Clone> AliasTable<T> {
+    /// Create a new `AliasTable`.
+    ///
+    /// Fails if:
+    /// - `items` is empty
+    /// - the total weight is 0
+    /// - the total weight times the number of items is larger than a `u64`
+    ///   can contain.
+    pub fn new(items: Vec<Weighted<T>>) -> AliasTable<T> {
+        assert!(!items.is_empty(), "AliasTable::new called with no items");
+        let n = items.len();
+
+        let mut total = 0u64;
+        for item in items.iter() {
+            total = total.checked_add(&(item.weight as u64))
+                .expect("AliasTable::new called with a total weight larger \
+                        than a u64 can contain");
+        }
+        assert!(total != 0, "AliasTable::new called with a total weight of 0");
+        total.checked_mul(&(n as u64))
+            .expect("AliasTable::new called with a total weight too large \
+                    for the number of items");
+
+        // Scale the weights so that the average is the total, the height of
+        // each column, then repeatedly fill up a short column with part of
+        // a tall one.
+        let mut scaled: Vec<u64> = items.iter().map(|item| item.weight as u64 * n as u64).collect();
+        let mut thresholds = Vec::from_elem(n, total);
+        let mut aliases = Vec::from_fn(n, |i| i);
+        let mut small = Vec::new();
+        let mut large = Vec::new();
+        for (i, &w) in scaled.iter().enumerate() {
+            if w < total { small.push(i) } else { large.push(i) }
+        }
+        loop {
+            let (s, l) = match (small.last(), large.last()) {
+                (Some(&s), Some(&l)) => (s, l),
+                _ => break
+            };
+            small.pop();
+            large.pop();
+            *thresholds.get_mut(s) = *scaled.get(s);
+            *aliases.get_mut(s) = l;
+            *scaled.get_mut(l) -= total - *scaled.get(s);
+            if *scaled.get(l) < total { small.push(l) } else { large.push(l) }
+        }
+        // With integer weights there is no rounding error, so any columns
+        // left over are exactly full and keep their default threshold.
+
+        AliasTable {
+            items: items.move_iter().map(|item| item.item).collect(),
+            thresholds: thresholds,
+            aliases: aliases,
+            index_range: Range::new(0, n),
+            weight_range: Range::new(0, total)
+        }
+    }
+}
+
+impl<T: Clone> Sample<T> for AliasTable<T> {
+    fn sample<R: Rng>(&mut self, rng: &mut R) -> T { self.ind_sample(rng) }
+}
+
+impl<T: Clone> IndependentSample<T> for AliasTable<T> {
+    fn ind_sample<R: Rng>(&self, rng: &mut R) -> T {
+        let i = self.index_range.ind_sample(rng);
+        if self.weight_range.ind_sample(rng) < *self.thresholds.get(i) {
+            self.items.get(i).clone()
+        } else {
+            self.items.get(*self.aliases.get(i)).clone()
+        }
+    }
+}
+
 mod ziggurat_tables;
 
 /// Sample a random number using the Ziggurat method (specifically the
@@ -248,7 +363,8 @@ fn ziggurat<R:Rng>(
 #[cfg(test)]
 mod tests {
     use {task_rng, Rng, Rand};
-    use super::{RandSample, WeightedChoice, Weighted, Sample, IndependentSample};
+    use {SeedableRng, XorShiftRng};
+    use super::{RandSample, WeightedChoice, Weighted, AliasTable, Sample, IndependentSample};
 
     #[deriving(Eq, Show)]
     struct ConstRand(uint);
@@ -347,4 +463,54 @@ mod tests {
                                  Weighted { weight: x, item: 2 },
                                  Weighted { weight: 1, item: 3 }));
     }
+
+    #[test]
+    fn test_alias_table() {
+        fn check(weights: &[uint]) {
+            let items = weights.iter().enumerate()
+                .map(|(i, &w)| Weighted { weight: w, item: i }).collect();
+            let table = AliasTable::new(items);
+            let n = weights.len() as u64;
+            let total = weights.iter().fold(0, |t, w| t + *w) as u64;
+
+            // Add up the area of the table given to each item, which must
+            // be exactly proportional to its weight.
+            let mut area = Vec::from_elem(weights.len(), 0u64);
+            for i in range(0, weights.len()) {
+                let threshold = *table.thresholds.get(i);
+                *area.get_mut(i) += threshold;
+                *area.get_mut(*table.aliases.get(i)) += total - threshold;
+            }
+            for (i, &w) in weights.iter().enumerate() {
+                assert_eq!(*area.get(i), w as u64 * n);
+            }
+
+            let mut rng: XorShiftRng = SeedableRng::from_seed([1, 2, 3, 4]);
+            for _ in range(0, 100) {
+                assert!(weights[table.ind_sample(&mut rng)] > 0);
+            }
+        }
+        check([1]);
+        check([0, 2, 0, 1]);
+        check([4, 3]);
+        check([1, 1, 1, 1, 1, 1, 1]);
+        check([1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        check([1000000, 1, 0, 3, 999]);
+
+        let mut table = AliasTable::new(vec!(Weighted { weight: 3, item: 'a' },
+                                             Weighted { weight: 1, item: 'b' }));
+        let mut rng: XorShiftRng = SeedableRng::from_seed([1, 2, 3, 4]);
+        let a = range(0, 10000).filter(|_| table.sample(&mut rng) == 'a').len();
+        assert!(a > 7250 && a < 7750, "{} a's out of 10000", a);
+    }
+
+    #[test] #[should_fail]
+    fn test_alias_table_no_items() {
+        AliasTable::<int>::new(vec!());
+    }
+    #[test] #[should_fail]
+    fn test_alias_table_zero_weight() {
+        AliasTable::new(vec!(Weighted { weight: 0, item: 0},
+                             Weighted { weight: 0, item: 1}));
+    }
 }
@@ -0,0 +1,235 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The Pareto and Zipf power law distributions.
+
+use std::num::Float;
+use {Rng, Open01};
+use distributions::{Sample, IndependentSample};
+
+/// The Pareto distribution `Pareto(scale, shape)`.
+///
+/// This distribution has density function `f(x) = shape * scale^shape /
+/// x^(shape + 1)` for `x >= scale`. It is sampled by inverting its
+/// distribution function.
+///
+/// # Example
+///
+/// ```rust
+/// use rand::distributions::{Pareto, IndependentSample};
+///
+/// let pareto = Pareto::new(1.0, 2.0);
+/// let v = pareto.ind_sample(&mut rand::task_rng());
+/// println!("{} is from a Pareto(1, 2) distribution", v);
+/// ```
+pub struct Pareto {
+    scale: f64,
+    inv_neg_shape: f64,
+}
+
+impl Pareto {
+    /// Construct a new `Pareto` with the given `scale`, which is the
+    /// smallest possible value, and `shape`. Fails if either is not
+    /// positive.
+    pub fn new(scale: f64, shape: f64) -> Pareto {
+        assert!(scale > 0.0, "Pareto::new called with `scale <= 0`");
+        assert!(shape > 0.0, "Pareto::new called with `shape <= 0`");
+        Pareto { scale: scale, inv_neg_shape: -1.0 / shape }
+    }
+}
+
+impl Sample<f64> for Pareto {
+    fn sample<R: Rng>(&mut self, rng: &mut R) -> f64 { self.ind_sample(rng) }
+}
+impl IndependentSample<f64> for Pareto {
+    fn ind_sample<R: Rng>(&self, rng: &mut R) -> f64 {
+        let Open01(u) = rng.gen::<Open01<f64>>();
+        self.scale * u.powf(&self.inv_neg_shape)
+    }
+}
+
+/// The Zipf distribution `Zipf(n, s)`, over the integers from 1 to `n`,
+/// where `k` has probability proportional to `1 / k^s`.
+///
+/// Implemented by rejection-inversion[1], which takes constant expected
+/// time for any `n` and `s`, without precomputing a table.
+///
+/// [1]: Wolfgang Hörmann and Gerhard Derflinger (1996). *Rejection-inversion
+/// to generate variates from monotone discrete distributions*. ACM
+/// Transactions on Modeling and Computer Simulation 6(3), 169-184.
+///
+/// # Example
+///
+/// ```rust
+/// use rand::distributions::{Zipf, IndependentSample};
+///
+/// let zipf = Zipf::new(1000, 1.1);
+/// let v = zipf.ind_sample(&mut rand::task_rng());
+/// println!("word {} was picked", v);
+/// ```
+pub struct Zipf {
+    n: u64,
+    s: f64,
+    h_integral_x1: f64,
+    h_integral_n: f64,
+    threshold: f64,
+}
+
+impl Zipf {
+    /// Construct a new `Zipf` over the integers from 1 to `n` with
+    /// exponent `s`. Fails if `n` is zero or `s` is negative.
+    pub fn new(n: u64, s: f64) -> Zipf {
+        assert!(n > 0, "Zipf::new called with `n == 0`");
+        assert!(s >= 0.0, "Zipf::new called with `s < 0`");
+        let mut zipf = Zipf {
+            n: n,
+            s: s,
+            h_integral_x1: 0.0,
+            h_integral_n: 0.0,
+            threshold: 0.0,
+        };
+        zipf.h_integral_x1 = zipf.h_integral(1.5) - 1.0;
+        zipf.h_integral_n = zipf.h_integral(n as f64 + 0.5);
+        zipf.threshold = 2.0 - zipf.h_integral_inverse(zipf.h_integral(2.5) - zipf.h(2.0));
+        zipf
+    }
+
+    // The unnormalised density, x^-s, which is the probability of x at
+    // integers.
+    fn h(&self, x: f64) -> f64 {
+        (-self.s * x.ln()).exp()
+    }
+
+    // The integral of h from 1 to x, (x^(1 - s) - 1) / (1 - s), written so
+    // that it stays accurate when s is close to 1.
+    fn h_integral(&self, x: f64) -> f64 {
+        let log_x = x.ln();
+        helper2((1.0 - self.s) * log_x) * log_x
+    }
+
+    fn h_integral_inverse(&self, x: f64) -> f64 {
+        let mut t = x * (1.0 - self.s);
+        if t < -1.0 {
+            // Rounding error may take t out of the domain of ln_1p.
+            t = -1.0;
+        }
+        (helper1(t) * x).exp()
+    }
+}
+
+// ln(1 + x) / x, continued to x == 0.
+fn helper1(x: f64) -> f64 {
+    if x.abs() > 1e-8 {
+        x.ln_1p() / x
+    } else {
+        1.0 - x * (0.5 - x * (1.0 / 3.0 - 0.25 * x))
+    }
+}
+
+// (exp(x) - 1) / x, continued to x == 0.
+fn helper2(x: f64) -> f64 {
+    if x.abs() > 1e-8 {
+        x.exp_m1() / x
+    } else {
+        1.0 + x * 0.5 * (1.0 + x * (1.0 / 3.0) * (1.0 + 0.25 * x))
+    }
+}
+
+impl Sample<u64> for Zipf {
+    fn sample<R: Rng>(&mut self, rng: &mut R) -> u64 { self.ind_sample(rng) }
+}
+impl IndependentSample<u64> for Zipf {
+    fn ind_sample<R: Rng>(&self, rng: &mut R) -> u64 {
+        loop {
+            // Sample from the continuous hat function by inversion, round
+            // to the nearest integer, then accept it if it falls under the
+            // distribution. Most samples are accepted by the cheap test
+            // against the threshold.
+            let u = self.h_integral_n +
+                rng.gen::<f64>() * (self.h_integral_x1 - self.h_integral_n);
+            let x = self.h_integral_inverse(u);
+            let mut k = (x + 0.5) as u64;
+            if k < 1 {
+                k = 1;
+            } else if k > self.n {
+                k = self.n;
+            }
+            if k as f64 - x <= self.threshold ||
+               u >= self.h_integral(k as f64 + 0.5) - self.h(k as f64) {
+                return k;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::Float;
+    use distributions::{Sample, IndependentSample};
+    use {SeedableRng, XorShiftRng, task_rng};
+    use super::{Pareto, Zipf};
+
+    #[test]
+    fn test_pareto() {
+        let mut pareto = Pareto::new(2.0, 1.5);
+        let mut rng = task_rng();
+        for _ in range(0, 1000) {
+            assert!(pareto.sample(&mut rng) >= 2.0);
+            assert!(pareto.ind_sample(&mut rng) >= 2.0);
+        }
+    }
+    #[test]
+    #[should_fail]
+    fn test_pareto_invalid_shape() {
+        Pareto::new(1.0, 0.0);
+    }
+
+    #[test]
+    fn test_zipf() {
+        let mut zipf = Zipf::new(100, 1.2);
+        let mut rng = task_rng();
+        for _ in range(0, 1000) {
+            let k = zipf.sample(&mut rng);
+            assert!(k >= 1 && k <= 100);
+            let k = zipf.ind_sample(&mut rng);
+            assert!(k >= 1 && k <= 100);
+        }
+        assert_eq!(Zipf::new(1, 2.0).ind_sample(&mut rng), 1);
+    }
+
+    #[test]
+    fn test_zipf_frequencies() {
+        fn check(n: u64, s: f64) {
+            let mut rng: XorShiftRng = SeedableRng::from_seed([11, 22, 33, 44]);
+            let zipf = Zipf::new(n, s);
+            let samples = 50000;
+            let mut counts = Vec::from_elem(n as uint + 1, 0u);
+            for _ in range(0, samples) {
+                *counts.get_mut(zipf.ind_sample(&mut rng) as uint) += 1;
+            }
+            let total = range(1, n + 1).fold(0.0, |t, k| t + (k as f64).powf(&-s));
+            for k in range(1, n + 1) {
+                let expected = (k as f64).powf(&-s) / total;
+                let actual = *counts.get(k as uint) as f64 / (samples as f64);
+                assert!((actual - expected).abs() < 0.01,
+                        "Zipf({}, {}): P({}) = {}, expected {}", n, s, k, actual, expected);
+            }
+        }
+        check(10, 1.0);
+        check(5, 2.5);
+        check(4, 0.0);
+        check(30, 0.5);
+    }
+    #[test]
+    #[should_fail]
+    fn test_zipf_invalid_n() {
+        Zipf::new(0, 1.0);
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The uniform distribution on the surface of a sphere.
+
+use std::num::Float;
+use Rng;
+use distributions::{Sample, IndependentSample};
+use distributions::normal::StandardNormal;
+
+/// The uniform distribution on the surface of the unit sphere in `dim`
+/// dimensions, i.e. over unit vectors pointing in a random direction.
+///
+/// Sampled by normalising a vector of independent standard normal
+/// variables, whose distribution is spherically symmetric.
+///
+/// # Example
+///
+/// ```rust
+/// use rand::distributions::{UnitSphereSurface, IndependentSample};
+///
+/// let sphere = UnitSphereSurface::new(3);
+/// let v = sphere.ind_sample(&mut rand::task_rng());
+/// println!("{} is a random direction in space", v);
+/// ```
+pub struct UnitSphereSurface {
+    dim: uint,
+}
+
+impl UnitSphereSurface {
+    /// Construct a new `UnitSphereSurface` for vectors of `dim` elements.
+    /// Fails if `dim` is zero.
+    pub fn new(dim: uint) -> UnitSphereSurface {
+        assert!(dim > 0, "UnitSphereSurface::new called with `dim == 0`");
+        UnitSphereSurface { dim: dim }
+    }
+}
+
+impl Sample<Vec<f64>> for UnitSphereSurface {
+    fn sample<R: Rng>(&mut self, rng: &mut R) -> Vec<f64> { self.ind_sample(rng) }
+}
+impl IndependentSample<Vec<f64>> for UnitSphereSurface {
+    fn ind_sample<R: Rng>(&self, rng: &mut R) -> Vec<f64> {
+        loop {
+            let mut v = Vec::from_fn(self.dim, |_| {
+                let StandardNormal(x) = rng.gen::<StandardNormal>();
+                x
+            });
+            let norm = v.iter().fold(0.0, |s, x| s + *x * *x).sqrt();
+            // Vanishingly unlikely, but there's no direction to keep.
+            if norm == 0.0 {
+                continue;
+            }
+            for x in v.mut_iter() {
+                *x = *x / norm;
+            }
+            return v;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::num::Float;
+    use distributions::{Sample, IndependentSample};
+    use {SeedableRng, XorShiftRng, task_rng};
+    use super::UnitSphereSurface;
+
+    #[test]
+    fn test_unit_sphere_surface() {
+        let mut rng = task_rng();
+        for dim in range(1u, 6) {
+            let mut sphere = UnitSphereSurface::new(dim);
+            for _ in range(0, 100) {
+                let v = sphere.sample(&mut rng);
+                assert_eq!(v.len(), dim);
+                let norm = v.iter().fold(0.0, |s, x| s + *x * *x).sqrt();
+                assert!((norm - 1.0).abs() < 1e-9);
+                assert_eq!(sphere.ind_sample(&mut rng).len(), dim);
+            }
+        }
+
+        // Each coordinate of a uniform point on the 2-sphere is uniform on
+        // [-1, 1], so its mean is 0 and its mean square is 1/3.
+        let mut rng: XorShiftRng = SeedableRng::from_seed([5, 6, 7, 8]);
+        let sphere = UnitSphereSurface::new(3);
+        let n = 20000;
+        let (mut sum, mut sum_sq) = ([0.0, ..3], [0.0, ..3]);
+        for _ in range(0, n) {
+            let v = sphere.ind_sample(&mut rng);
+            for i in range(0u, 3) {
+                sum[i] += *v.get(i);
+                sum_sq[i] += *v.get(i) * *v.get(i);
+            }
+        }
+        for i in range(0u, 3) {
+            assert!((sum[i] / (n as f64)).abs() < 0.02);
+            assert!((sum_sq[i] / (n as f64) - 1.0 / 3.0).abs() < 0.02);
+        }
+    }
+    #[test]
+    #[should_fail]
+    fn test_unit_sphere_surface_zero_dim() {
+        UnitSphereSurface::new(0);
+    }
+}
@@ -33,9 +33,12 @@ after generating 32 KiB of random data.
 An application that requires random numbers for cryptographic purposes
 should prefer `OSRng`, which reads randomness from one of the source
 that the operating system provides (e.g. `/dev/urandom` on
-Unixes). The other random number generators provided by this module
-are either known to be insecure (`XorShiftRng`), or are not verified
-to be secure (`IsaacRng`, `Isaac64Rng` and `StdRng`).
+Unixes). Where a seedable generator is needed, `ChaChaRng` is built on
+the ChaCha20 stream cipher and is secure as long as its seed is. The
+other random number generators provided by this module are either
+known to be insecure (`XorShiftRng`, `Pcg32Rng`, `Xoshiro256Rng` and
+`Xoshiro128Rng`), or are not verified to be secure (`IsaacRng`,
+`Isaac64Rng` and `StdRng`).
 
 *Note*: on Linux, `/dev/random` is more secure than `/dev/urandom`,
 but it is a blocking RNG, and will wait until it has determined that
@@ -45,6 +48,33 @@ opening the file and passing it to `reader::ReaderRng`. Since it
 blocks, `/dev/random` should only be used to retrieve small amounts of
 randomness.
 
+# Reproducibility
+
+The values produced by `IsaacRng`, `Isaac64Rng`, `XorShiftRng`,
+`Pcg32Rng`, `Xoshiro256Rng`, `Xoshiro128Rng` and `ChaChaRng` for a
+given seed are checked against known values by the tests, and will not
+change between releases. This only covers the raw output of `next_u32`,
+`next_u64` and `fill_bytes`; the algorithms behind other methods of
+`Rng` and the `distributions` may still be improved. `StdRng`,
+`task_rng` and `weak_rng` make no such promise, and may switch to a
+different algorithm at any time.
+
+To run a computation in parallel reproducibly, derive one generator
+per task from a single seeded one, with `split` or `jump` on the PCG
+and xoshiro generators or `with_stream` on `ChaChaRng`:
+
+```rust
+use rand::{Rng, SeedableRng, Xoshiro256Rng};
+
+let mut rng: Xoshiro256Rng = SeedableRng::from_seed(12345u64);
+for _ in range(0, 4) {
+    let mut child = rng.split();
+    spawn(proc() {
+        println!("{}", child.gen::<f64>());
+    });
+}
+```
+
 # Examples
 
 ```rust
@@ -82,17 +112,24 @@ use std::kinds::marker;
 use std::local_data;
 use std::strbuf::StrBuf;
 
+pub use chacha::ChaChaRng;
 pub use isaac::{IsaacRng, Isaac64Rng};
 pub use os::OSRng;
+pub use pcg::Pcg32Rng;
+pub use xoshiro::{Xoshiro256Rng, Xoshiro128Rng};
 
 use distributions::{Range, IndependentSample};
 use distributions::range::SampleRange;
 
+pub mod chacha;
 pub mod distributions;
 pub mod isaac;
 pub mod os;
+pub mod pcg;
 pub mod reader;
 pub mod reseeding;
+pub mod reservoir;
+pub mod xoshiro;
 mod rand_impls;
 
 /// A type that can be randomly generated using an `Rng`.
@@ -347,6 +384,76 @@ pub trait Rng {
         }
         reservoir
     }
+
+    /// Choose `amount` distinct indices from `[0, len)` uniformly at
+    /// random, in random order.
+    ///
+    /// Fails if `amount > len`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::{task_rng, Rng};
+    ///
+    /// let mut rng = task_rng();
+    /// println!("{}", rng.sample_indices(1000, 3));
+    /// ```
+    fn sample_indices(&mut self, len: uint, amount: uint) -> Vec<uint> {
+        assert!(amount <= len, "Rng.sample_indices called with amount > len");
+        if amount > len / 2 {
+            // Most indices are wanted, so shuffle just the first `amount`
+            // of them into place.
+            let mut indices = Vec::from_fn(len, |i| i);
+            for i in range(0, amount) {
+                let j = self.gen_range(i, len);
+                indices.as_mut_slice().swap(i, j);
+            }
+            indices.truncate(amount);
+            return indices;
+        }
+
+        // Floyd's algorithm, which only needs space for the chosen
+        // indices. They are also kept sorted to check for duplicates.
+        let mut chosen = Vec::with_capacity(amount);
+        let mut sorted: Vec<uint> = Vec::with_capacity(amount);
+        for j in range(len - amount, len) {
+            let t = self.gen_range(0, j + 1);
+            let (mut lo, mut hi) = (0, sorted.len());
+            while lo < hi {
+                let mid = (lo + hi) / 2;
+                if *sorted.get(mid) < t { lo = mid + 1 } else { hi = mid }
+            }
+            if lo < sorted.len() && *sorted.get(lo) == t {
+                // Every index chosen so far is less than j.
+                sorted.push(j);
+                chosen.push(j);
+            } else {
+                sorted.insert(lo, t);
+                chosen.push(t);
+            }
+        }
+        // Floyd's algorithm chooses a uniform subset, but not in a uniform
+        // order.
+        self.shuffle(chosen.as_mut_slice());
+        chosen
+    }
+
+    /// Randomly choose up to `amount` distinct elements of `values`,
+    /// without replacement and in random order.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rand::{task_rng, Rng};
+    ///
+    /// let mut rng = task_rng();
+    /// let letters = ['a', 'b', 'c', 'd', 'e'];
+    /// println!("{}", rng.choose_multiple(letters, 3));
+    /// ```
+    fn choose_multiple<'a, T>(&mut self, values: &'a [T], amount: uint) -> Vec<&'a T> {
+        let amount = if amount < values.len() { amount } else { values.len() };
+        self.sample_indices(values.len(), amount).iter().map(|&i| &values[i]).collect()
+    }
 }
 
 /// A random number generator that can be explicitly seeded to produce
@@ -684,7 +791,7 @@ pub struct Closed01<F>(pub F);
 
 #[cfg(test)]
 mod test {
-    use super::{Rng, task_rng, random, SeedableRng, StdRng};
+    use super::{Rng, task_rng, random, SeedableRng, StdRng, XorShiftRng};
 
     struct ConstRng { i: u64 }
     impl Rng for ConstRng {
@@ -856,6 +963,46 @@ mod test {
         }));
     }
 
+    #[test]
+    fn test_sample_indices() {
+        let mut r = task_rng();
+        for &(len, amount) in [(0u, 0u), (10, 0), (10, 10), (10, 3), (1000, 7), (1000, 900)].iter() {
+            let mut v = r.sample_indices(len, amount);
+            assert_eq!(v.len(), amount);
+            v.sort();
+            assert!(v.iter().all(|&i| i < len));
+            assert!(v.as_slice().windows(2).all(|w| w[0] < w[1]));
+        }
+
+        // Every index of a small range is equally likely to be chosen
+        // first.
+        let mut counts = [0, ..5];
+        for _ in range(0, 5000) {
+            counts[*r.sample_indices(5, 2).get(0)] += 1;
+        }
+        assert!(counts.iter().all(|&c| c > 800 && c < 1200), "{:?}", counts);
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_sample_indices_too_many() {
+        task_rng().sample_indices(3, 4);
+    }
+
+    #[test]
+    fn test_choose_multiple() {
+        let mut r = task_rng();
+        let values = [1, 2, 3, 4, 5];
+        let mut chosen: Vec<int> = r.choose_multiple(values, 3).iter().map(|&&x| x).collect();
+        assert_eq!(chosen.len(), 3);
+        chosen.sort();
+        chosen.dedup();
+        assert_eq!(chosen.len(), 3);
+        assert_eq!(r.choose_multiple(values, 10).len(), 5);
+        let empty: &[int] = &[];
+        assert!(r.choose_multiple(empty, 2).is_empty());
+    }
+
     #[test]
     fn test_std_rng_seeded() {
         let s = task_rng().gen_vec::<uint>(256);
@@ -875,6 +1022,18 @@ mod test {
         let string2 = r.gen_ascii_str(100);
         assert_eq!(string1, string2);
     }
+
+    #[test]
+    fn test_xorshift_true_values() {
+        let mut r: XorShiftRng = SeedableRng::from_seed([123, 456, 789, 101112]);
+        // skip forward to the 1000th number
+        for _ in range(0, 1000) { r.next_u32(); }
+
+        let v = Vec::from_fn(10, |_| r.next_u32());
+        assert_eq!(v,
+                   vec!(3661250632, 194808412, 1530269006, 805839221, 943202108,
+                        3608787981, 1018071005, 1306266292, 3051699660, 2936407721));
+    }
 }
 
 #[cfg(test)]
@@ -884,7 +1043,8 @@ static RAND_BENCH_N: u64 = 100;
 mod bench {
     extern crate test;
     use self::test::Bencher;
-    use {XorShiftRng, StdRng, IsaacRng, Isaac64Rng, Rng, RAND_BENCH_N};
+    use {XorShiftRng, StdRng, IsaacRng, Isaac64Rng, Pcg32Rng, Xoshiro256Rng,
+         Xoshiro128Rng, ChaChaRng, Rng, RAND_BENCH_N};
     use std::mem::size_of;
 
     #[bench]
@@ -920,6 +1080,50 @@ mod bench {
         b.bytes = size_of::<uint>() as u64 * RAND_BENCH_N;
     }
 
+    #[bench]
+    fn rand_pcg32(b: &mut Bencher) {
+        let mut rng = Pcg32Rng::new().unwrap();
+        b.iter(|| {
+            for _ in range(0, RAND_BENCH_N) {
+                rng.gen::<uint>();
+            }
+        });
+        b.bytes = size_of::<uint>() as u64 * RAND_BENCH_N;
+    }
+
+    #[bench]
+    fn rand_xoshiro256(b: &mut Bencher) {
+        let mut rng = Xoshiro256Rng::new().unwrap();
+        b.iter(|| {
+            for _ in range(0, RAND_BENCH_N) {
+                rng.gen::<uint>();
+            }
+        });
+        b.bytes = size_of::<uint>() as u64 * RAND_BENCH_N;
+    }
+
+    #[bench]
+    fn rand_xoshiro128(b: &mut Bencher) {
+        let mut rng = Xoshiro128Rng::new().unwrap();
+        b.iter(|| {
+            for _ in range(0, RAND_BENCH_N) {
+                rng.gen::<uint>();
+            }
+        });
+        b.bytes = size_of::<uint>() as u64 * RAND_BENCH_N;
+    }
+
+    #[bench]
+    fn rand_chacha(b: &mut Bencher) {
+        let mut rng = ChaChaRng::new().unwrap();
+        b.iter(|| {
+            for _ in range(0, RAND_BENCH_N) {
+                rng.gen::<uint>();
+            }
+        });
+        b.bytes = size_of::<uint>() as u64 * RAND_BENCH_N;
+    }
+
     #[bench]
     fn rand_std(b: &mut Bencher) {
         let mut rng = StdRng::new().unwrap();
@@ -0,0 +1,198 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The PCG random number generator.
+
+use {Rng, SeedableRng, OSRng};
+use std::io::IoResult;
+
+static MULTIPLIER: u64 = 6364136223846793005;
+
+/// A random number generator that uses the PCG-XSH-RR 64/32 algorithm[1],
+/// as `pcg32` in the reference implementation.
+///
+/// The generator has 64 bits of state and a period of 2^64, and each seed
+/// also selects one of 2^63 independent streams. Its output passes the
+/// usual statistical test suites, but it is not suitable for cryptographic
+/// purposes.
+///
+/// The sequence produced for a given seed will not change between
+/// releases.
+///
+/// [1]: Melissa E. O'Neill, [*PCG: A Family of Simple Fast Space-Efficient
+/// Statistically Good Algorithms for Random Number
+/// Generation*](http://www.pcg-random.org/paper.html)
+#[deriving(Clone)]
+pub struct Pcg32Rng {
+    state: u64,
+    inc: u64,
+}
+
+impl Pcg32Rng {
+    /// Create a PCG random number generator with a random seed and stream.
+    ///
+    /// This reads randomness from the operating system (via `OSRng`)
+    /// which may fail, any error is propagated via the `IoResult`
+    /// return value.
+    pub fn new() -> IoResult<Pcg32Rng> {
+        let mut r = try!(OSRng::new());
+        Ok(SeedableRng::from_seed([r.next_u64(), r.next_u64()]))
+    }
+
+    #[inline]
+    fn step(&mut self) {
+        self.state = self.state * MULTIPLIER + self.inc;
+    }
+
+    /// Advance the generator by `delta` steps in O(log delta) time, as if
+    /// `next_u32` had been called `delta` times.
+    ///
+    /// Since the period is 2^64, going back by `n` steps is the same as
+    /// advancing by `-n` modulo 2^64.
+    pub fn advance(&mut self, mut delta: u64) {
+        let mut acc_mult = 1u64;
+        let mut acc_plus = 0u64;
+        let mut cur_mult = MULTIPLIER;
+        let mut cur_plus = self.inc;
+        while delta > 0 {
+            if delta & 1 == 1 {
+                acc_mult *= cur_mult;
+                acc_plus = acc_plus * cur_mult + cur_plus;
+            }
+            cur_plus = (cur_mult + 1) * cur_plus;
+            cur_mult *= cur_mult;
+            delta >>= 1;
+        }
+        self.state = acc_mult * self.state + acc_plus;
+    }
+
+    /// Create a new generator on a different stream, seeded from the output
+    /// of this one.
+    ///
+    /// Splitting is deterministic, so a single seeded generator can hand
+    /// out reproducible generators to any number of tasks.
+    pub fn split(&mut self) -> Pcg32Rng {
+        let state = self.next_u64();
+        let stream = self.next_u64();
+        SeedableRng::from_seed([state, stream])
+    }
+}
+
+impl Rng for Pcg32Rng {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.step();
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rot = (old >> 59) as uint;
+        (xorshifted >> rot) | (xorshifted << ((32 - rot) & 31))
+    }
+}
+
+impl SeedableRng<[u64, .. 2]> for Pcg32Rng {
+    /// Reseed a PCG generator. The seed is `[state, stream]`, and only the
+    /// low 63 bits of `stream` are used.
+    fn reseed(&mut self, seed: [u64, .. 2]) {
+        self.state = 0;
+        self.inc = (seed[1] << 1) | 1;
+        self.step();
+        self.state += seed[0];
+        self.step();
+    }
+
+    /// Create a PCG generator from `[state, stream]`. This is the same as
+    /// seeding `pcg32` in the reference implementation with `initstate` and
+    /// `initseq`, and produces the same sequence of values.
+    fn from_seed(seed: [u64, .. 2]) -> Pcg32Rng {
+        let mut rng = Pcg32Rng { state: 0, inc: 0 };
+        rng.reseed(seed);
+        rng
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::u64;
+    use super::Pcg32Rng;
+    use {Rng, SeedableRng, task_rng};
+
+    #[test]
+    fn test_rng_rand_seeded() {
+        let mut r = task_rng();
+        let s = [r.gen(), r.gen()];
+        let mut ra: Pcg32Rng = SeedableRng::from_seed(s);
+        let mut rb: Pcg32Rng = SeedableRng::from_seed(s);
+        assert_eq!(ra.gen_ascii_str(100u), rb.gen_ascii_str(100u));
+    }
+
+    #[test]
+    fn test_rng_reseed() {
+        let mut r = task_rng();
+        let s = [r.gen(), r.gen()];
+        let mut r: Pcg32Rng = SeedableRng::from_seed(s);
+        let string1 = r.gen_ascii_str(100);
+
+        r.reseed(s);
+
+        let string2 = r.gen_ascii_str(100);
+        assert_eq!(string1, string2);
+    }
+
+    #[test]
+    fn test_rng_streams() {
+        let mut ra: Pcg32Rng = SeedableRng::from_seed([42, 54]);
+        let mut rb: Pcg32Rng = SeedableRng::from_seed([42, 55]);
+        assert!(ra.gen_vec::<u32>(10) != rb.gen_vec::<u32>(10));
+    }
+
+    #[test]
+    fn test_rng_advance() {
+        let mut ra: Pcg32Rng = SeedableRng::from_seed([42, 54]);
+        let mut rb = ra.clone();
+        for _ in range(0, 1000) { ra.next_u32(); }
+        rb.advance(1000);
+        assert_eq!(ra.gen_vec::<u32>(10), rb.gen_vec::<u32>(10));
+
+        // going all the way round the period
+        let mut rc = ra.clone();
+        rc.advance(u64::MAX);
+        rc.next_u32();
+        assert_eq!(ra.gen_vec::<u32>(10), rc.gen_vec::<u32>(10));
+    }
+
+    #[test]
+    fn test_rng_split() {
+        let mut ra: Pcg32Rng = SeedableRng::from_seed([42, 54]);
+        let mut rb: Pcg32Rng = SeedableRng::from_seed([42, 54]);
+        let mut ca = ra.split();
+        let mut cb = rb.split();
+        assert_eq!(ca.gen_vec::<u32>(10), cb.gen_vec::<u32>(10));
+        assert!(ca.gen_vec::<u32>(10) != ra.gen_vec::<u32>(10));
+    }
+
+    #[test]
+    fn test_rng_true_values() {
+        // The demo program of the reference implementation
+        let mut ra: Pcg32Rng = SeedableRng::from_seed([42, 54]);
+        let v = Vec::from_fn(6, |_| ra.next_u32());
+        assert_eq!(v,
+                   vec!(0xa15c02b7, 0x7b47f409, 0xba1d3330,
+                        0x83d2f293, 0xbfa4784b, 0xcbed606e));
+
+        let mut rb: Pcg32Rng = SeedableRng::from_seed([12345, 67890]);
+        // skip forward to the 10000th number
+        for _ in range(0, 10000) { rb.next_u32(); }
+
+        let v = Vec::from_fn(10, |_| rb.next_u32());
+        assert_eq!(v,
+                   vec!(3667024205, 878235649, 2322333943, 728911362, 2280053355,
+                        2876126353, 4240745166, 706803635, 2288327183, 1038341290));
+    }
+}
@@ -0,0 +1,248 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Random samples of streams whose length isn't known in advance.
+//!
+//! `Rng::sample` takes a uniform sample of an iterator in one go. The
+//! reservoirs here do the same incrementally, so that items can be added as
+//! they arrive and the sample inspected at any point.
+
+use std::num::Float;
+use {Rng, Open01};
+
+/// A uniform random sample of up to `capacity` of the items pushed into it.
+///
+/// At any time, every item pushed so far is equally likely to be in the
+/// sample.
+///
+/// # Example
+///
+/// ```rust
+/// use rand::reservoir::Reservoir;
+///
+/// let mut rng = rand::task_rng();
+/// let mut reservoir = Reservoir::new(3);
+/// for line in ["a", "b", "c", "d", "e"].iter() {
+///     reservoir.push(&mut rng, *line);
+/// }
+/// println!("3 of 5 lines: {}", reservoir.as_slice());
+/// ```
+pub struct Reservoir<T> {
+    capacity: uint,
+    seen: u64,
+    items: Vec<T>,
+}
+
+impl<T> Reservoir<T> {
+    /// Create an empty reservoir which holds at most `capacity` items.
+    pub fn new(capacity: uint) -> Reservoir<T> {
+        Reservoir { capacity: capacity, seen: 0, items: Vec::with_capacity(capacity) }
+    }
+
+    /// Offer an item to the sample, which keeps it with probability
+    /// `capacity / n` if it is the `n`th item, evicting a random item if
+    /// the reservoir is full.
+    pub fn push<R: Rng>(&mut self, rng: &mut R, item: T) {
+        self.seen += 1;
+        if self.items.len() < self.capacity {
+            self.items.push(item);
+        } else {
+            let k = rng.gen_range(0, self.seen);
+            if k < self.capacity as u64 {
+                *self.items.get_mut(k as uint) = item;
+            }
+        }
+    }
+
+    /// The number of items pushed so far.
+    pub fn seen(&self) -> u64 {
+        self.seen
+    }
+
+    /// The items in the sample, in no particular order.
+    pub fn as_slice<'a>(&'a self) -> &'a [T] {
+        self.items.as_slice()
+    }
+
+    /// Consume the reservoir, returning the items in the sample.
+    pub fn unwrap(self) -> Vec<T> {
+        self.items
+    }
+}
+
+impl<T> Container for Reservoir<T> {
+    fn len(&self) -> uint {
+        self.items.len()
+    }
+}
+
+/// A weighted random sample, without replacement, of up to `capacity` of
+/// the items pushed into it.
+///
+/// This is the A-Res algorithm of Efraimidis and Spirakis[1]: each item is
+/// given a random key `u^(1/weight)` for a uniform `u`, and the items with
+/// the largest keys are kept. An item's chance of being in the sample
+/// therefore grows with its weight, and items with zero weight are never
+/// chosen.
+///
+/// [1]: Pavlos S. Efraimidis and Paul G. Spirakis (2006). *Weighted random
+/// sampling with a reservoir*. Information Processing Letters 97(5),
+/// 181-185.
+///
+/// # Example
+///
+/// ```rust
+/// use rand::reservoir::WeightedReservoir;
+///
+/// let mut rng = rand::task_rng();
+/// let mut reservoir = WeightedReservoir::new(2);
+/// reservoir.push(&mut rng, "rarely", 1.0);
+/// reservoir.push(&mut rng, "often", 10.0);
+/// reservoir.push(&mut rng, "usually", 20.0);
+/// println!("{}", reservoir.unwrap());
+/// ```
+pub struct WeightedReservoir<T> {
+    capacity: uint,
+    // The items with their keys, of which the logarithm is stored to avoid
+    // underflow with large weights.
+    entries: Vec<(f64, T)>,
+    // The index of the entry with the smallest key, once full.
+    min: uint,
+}
+
+impl<T> WeightedReservoir<T> {
+    /// Create an empty reservoir which holds at most `capacity` items.
+    pub fn new(capacity: uint) -> WeightedReservoir<T> {
+        WeightedReservoir { capacity: capacity, entries: Vec::with_capacity(capacity), min: 0 }
+    }
+
+    /// Offer an item with the given weight to the sample. Fails if `weight`
+    /// is negative or NaN.
+    pub fn push<R: Rng>(&mut self, rng: &mut R, item: T, weight: f64) {
+        assert!(weight >= 0.0, "WeightedReservoir::push called with `weight < 0`");
+        if weight == 0.0 || self.capacity == 0 {
+            return;
+        }
+        let Open01(u) = rng.gen::<Open01<f64>>();
+        let key = u.ln() / weight;
+
+        if self.entries.len() < self.capacity {
+            self.entries.push((key, item));
+            if self.entries.len() == self.capacity {
+                self.find_min();
+            }
+        } else if key > self.key(self.min) {
+            *self.entries.get_mut(self.min) = (key, item);
+            self.find_min();
+        }
+    }
+
+    fn key(&self, i: uint) -> f64 {
+        let &(key, _) = self.entries.get(i);
+        key
+    }
+
+    fn find_min(&mut self) {
+        let mut min = 0;
+        for i in range(1, self.entries.len()) {
+            if self.key(i) < self.key(min) {
+                min = i;
+            }
+        }
+        self.min = min;
+    }
+
+    /// Consume the reservoir, returning the items in the sample.
+    pub fn unwrap(self) -> Vec<T> {
+        self.entries.move_iter().map(|(_, item)| item).collect()
+    }
+}
+
+impl<T> Container for WeightedReservoir<T> {
+    fn len(&self) -> uint {
+        self.entries.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {SeedableRng, XorShiftRng, task_rng};
+    use super::{Reservoir, WeightedReservoir};
+
+    #[test]
+    fn test_reservoir() {
+        let mut rng = task_rng();
+        let mut r = Reservoir::new(5);
+        for i in range(0, 3) {
+            r.push(&mut rng, i);
+        }
+        assert_eq!(r.as_slice(), [0, 1, 2].as_slice());
+        for i in range(3, 1000) {
+            r.push(&mut rng, i);
+        }
+        assert_eq!(r.len(), 5);
+        assert_eq!(r.seen(), 1000);
+        let mut v = r.unwrap();
+        v.sort();
+        v.dedup();
+        assert_eq!(v.len(), 5);
+
+        let mut empty = Reservoir::new(0);
+        empty.push(&mut rng, 1);
+        assert!(empty.is_empty());
+
+        // Each of 10 items should be kept about a fifth of the time.
+        let mut rng: XorShiftRng = SeedableRng::from_seed([9, 8, 7, 6]);
+        let mut counts = [0, ..10];
+        for _ in range(0, 5000) {
+            let mut r = Reservoir::new(2);
+            for i in range(0u, 10) {
+                r.push(&mut rng, i);
+            }
+            for &i in r.as_slice().iter() {
+                counts[i] += 1;
+            }
+        }
+        assert!(counts.iter().all(|&c| c > 850 && c < 1150), "{:?}", counts);
+    }
+
+    #[test]
+    fn test_weighted_reservoir() {
+        let mut rng = task_rng();
+        let mut r = WeightedReservoir::new(3);
+        r.push(&mut rng, 'a', 1.0);
+        r.push(&mut rng, 'b', 0.0);
+        r.push(&mut rng, 'c', 2.0);
+        assert_eq!(r.len(), 2);
+        let mut v = r.unwrap();
+        v.sort();
+        assert_eq!(v, vec!('a', 'c'));
+
+        // With one slot, each item is chosen in proportion to its weight.
+        let mut rng: XorShiftRng = SeedableRng::from_seed([9, 8, 7, 6]);
+        let mut counts = [0, ..3];
+        for _ in range(0, 6000) {
+            let mut r = WeightedReservoir::new(1);
+            for (i, &w) in [1.0, 2.0, 3.0].iter().enumerate() {
+                r.push(&mut rng, i, w);
+            }
+            counts[*r.unwrap().get(0)] += 1;
+        }
+        assert!(counts[0] > 850 && counts[0] < 1150, "{:?}", counts);
+        assert!(counts[1] > 1850 && counts[1] < 2150, "{:?}", counts);
+        assert!(counts[2] > 2850 && counts[2] < 3150, "{:?}", counts);
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_weighted_reservoir_negative_weight() {
+        WeightedReservoir::new(1).push(&mut task_rng(), (), -1.0);
+    }
+}
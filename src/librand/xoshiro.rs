@@ -0,0 +1,377 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The xoshiro family of random number generators.
+
+use {Rng, SeedableRng, OSRng};
+use std::io::IoResult;
+
+#[inline]
+fn rotl64(x: u64, k: uint) -> u64 {
+    (x << k) | (x >> (64 - k))
+}
+
+#[inline]
+fn rotl32(x: u32, k: uint) -> u32 {
+    (x << k) | (x >> (32 - k))
+}
+
+/// A random number generator that uses the xoshiro256** algorithm[1].
+///
+/// The generator has 256 bits of state and a period of 2^256 - 1. It is
+/// very fast and its output passes the usual statistical test suites, but
+/// it is not suitable for cryptographic purposes.
+///
+/// `jump` and `long_jump` move the generator forward by 2^128 and 2^192
+/// steps respectively, which splits its period into non-overlapping
+/// streams for parallel computations.
+///
+/// The sequence produced for a given seed will not change between
+/// releases.
+///
+/// [1]: David Blackman and Sebastiano Vigna, [*Scrambled Linear Pseudorandom
+/// Number Generators*](http://xoshiro.di.unimi.it/)
+#[deriving(Clone)]
+pub struct Xoshiro256Rng {
+    s: [u64, .. 4],
+}
+
+static JUMP_256: [u64, .. 4] = [0x180ec6d33cfd0aba, 0xd5a61266f0c9392c,
+                                0xa9582618e03fc9aa, 0x39abdc4529b1661c];
+static LONG_JUMP_256: [u64, .. 4] = [0x76e15d3efefdcbbf, 0xc5004e441c522fb3,
+                                     0x77710069854ee241, 0x39109bb02acbe635];
+
+impl Xoshiro256Rng {
+    /// Create a xoshiro256** random number generator with a random seed.
+    ///
+    /// This reads randomness from the operating system (via `OSRng`)
+    /// which may fail, any error is propagated via the `IoResult`
+    /// return value.
+    pub fn new() -> IoResult<Xoshiro256Rng> {
+        let mut r = try!(OSRng::new());
+        loop {
+            let s = [r.next_u64(), r.next_u64(), r.next_u64(), r.next_u64()];
+            if !s.iter().all(|&x| x == 0) {
+                return Ok(SeedableRng::from_seed(s));
+            }
+        }
+    }
+
+    fn jump_with(&mut self, table: &[u64, .. 4]) {
+        let mut s = [0u64, .. 4];
+        for &word in table.iter() {
+            for b in range(0u, 64) {
+                if word & (1 << b) != 0 {
+                    for (x, &y) in s.mut_iter().zip(self.s.iter()) {
+                        *x ^= y;
+                    }
+                }
+                self.next_u64();
+            }
+        }
+        self.s = s;
+    }
+
+    /// Advance the generator by 2^128 steps. Calling this `n` times on
+    /// copies of one generator gives `n` streams which won't overlap for
+    /// 2^128 values each.
+    pub fn jump(&mut self) {
+        self.jump_with(&JUMP_256);
+    }
+
+    /// Advance the generator by 2^192 steps, giving 2^64 starting points
+    /// from each of which `jump` can be used 2^64 times.
+    pub fn long_jump(&mut self) {
+        self.jump_with(&LONG_JUMP_256);
+    }
+
+    /// Return a copy of this generator and then `jump` this one, so that
+    /// the two produce non-overlapping sequences of 2^128 values.
+    pub fn split(&mut self) -> Xoshiro256Rng {
+        let rng = self.clone();
+        self.jump();
+        rng
+    }
+}
+
+impl Rng for Xoshiro256Rng {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        let result = rotl64(self.s[1] * 5, 7) * 9;
+        let t = self.s[1] << 17;
+
+        self.s[2] ^= self.s[0];
+        self.s[3] ^= self.s[1];
+        self.s[1] ^= self.s[2];
+        self.s[0] ^= self.s[3];
+
+        self.s[2] ^= t;
+        self.s[3] = rotl64(self.s[3], 45);
+
+        result
+    }
+}
+
+impl SeedableRng<[u64, .. 4]> for Xoshiro256Rng {
+    /// Reseed a Xoshiro256Rng. This will fail if `seed` is entirely 0.
+    fn reseed(&mut self, seed: [u64, .. 4]) {
+        assert!(!seed.iter().all(|&x| x == 0),
+                "Xoshiro256Rng.reseed called with an all zero seed.");
+        self.s = seed;
+    }
+
+    /// Create a new Xoshiro256Rng. This will fail if `seed` is entirely 0.
+    fn from_seed(seed: [u64, .. 4]) -> Xoshiro256Rng {
+        assert!(!seed.iter().all(|&x| x == 0),
+                "Xoshiro256Rng::from_seed called with an all zero seed.");
+        Xoshiro256Rng { s: seed }
+    }
+}
+
+impl SeedableRng<u64> for Xoshiro256Rng {
+    /// Reseed a Xoshiro256Rng from a single `u64`, which is expanded to the
+    /// full state with SplitMix64 as recommended by the authors. Any value,
+    /// including 0, is a valid seed.
+    fn reseed(&mut self, seed: u64) {
+        let mut x = seed;
+        for s in self.s.mut_iter() {
+            x += 0x9e3779b97f4a7c15;
+            let mut z = x;
+            z = (z ^ (z >> 30)) * 0xbf58476d1ce4e5b9;
+            z = (z ^ (z >> 27)) * 0x94d049bb133111eb;
+            *s = z ^ (z >> 31);
+        }
+    }
+
+    /// Create a new Xoshiro256Rng from a single `u64`.
+    fn from_seed(seed: u64) -> Xoshiro256Rng {
+        let mut rng = Xoshiro256Rng { s: [0, .. 4] };
+        rng.reseed(seed);
+        rng
+    }
+}
+
+/// A random number generator that uses the xoshiro128** algorithm[1].
+///
+/// This is the 32-bit sibling of `Xoshiro256Rng`, with 128 bits of state
+/// and a period of 2^128 - 1. `jump` moves it forward by 2^64 steps.
+///
+/// The sequence produced for a given seed will not change between
+/// releases.
+///
+/// [1]: David Blackman and Sebastiano Vigna, [*Scrambled Linear Pseudorandom
+/// Number Generators*](http://xoshiro.di.unimi.it/)
+#[deriving(Clone)]
+pub struct Xoshiro128Rng {
+    s: [u32, .. 4],
+}
+
+static JUMP_128: [u32, .. 4] = [0x8764000b, 0xf542d2d3, 0x6fa035c3, 0x77f2db5b];
+
+impl Xoshiro128Rng {
+    /// Create a xoshiro128** random number generator with a random seed.
+    ///
+    /// This reads randomness from the operating system (via `OSRng`)
+    /// which may fail, any error is propagated via the `IoResult`
+    /// return value.
+    pub fn new() -> IoResult<Xoshiro128Rng> {
+        let mut r = try!(OSRng::new());
+        loop {
+            let s = [r.next_u32(), r.next_u32(), r.next_u32(), r.next_u32()];
+            if !s.iter().all(|&x| x == 0) {
+                return Ok(SeedableRng::from_seed(s));
+            }
+        }
+    }
+
+    /// Advance the generator by 2^64 steps. Calling this `n` times on
+    /// copies of one generator gives `n` streams which won't overlap for
+    /// 2^64 values each.
+    pub fn jump(&mut self) {
+        let mut s = [0u32, .. 4];
+        for &word in JUMP_128.iter() {
+            for b in range(0u, 32) {
+                if word & (1 << b) != 0 {
+                    for (x, &y) in s.mut_iter().zip(self.s.iter()) {
+                        *x ^= y;
+                    }
+                }
+                self.next_u32();
+            }
+        }
+        self.s = s;
+    }
+
+    /// Return a copy of this generator and then `jump` this one, so that
+    /// the two produce non-overlapping sequences of 2^64 values.
+    pub fn split(&mut self) -> Xoshiro128Rng {
+        let rng = self.clone();
+        self.jump();
+        rng
+    }
+}
+
+impl Rng for Xoshiro128Rng {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        let result = rotl32(self.s[1] * 5, 7) * 9;
+        let t = self.s[1] << 9;
+
+        self.s[2] ^= self.s[0];
+        self.s[3] ^= self.s[1];
+        self.s[1] ^= self.s[2];
+        self.s[0] ^= self.s[3];
+
+        self.s[2] ^= t;
+        self.s[3] = rotl32(self.s[3], 11);
+
+        result
+    }
+}
+
+impl SeedableRng<[u32, .. 4]> for Xoshiro128Rng {
+    /// Reseed a Xoshiro128Rng. This will fail if `seed` is entirely 0.
+    fn reseed(&mut self, seed: [u32, .. 4]) {
+        assert!(!seed.iter().all(|&x| x == 0),
+                "Xoshiro128Rng.reseed called with an all zero seed.");
+        self.s = seed;
+    }
+
+    /// Create a new Xoshiro128Rng. This will fail if `seed` is entirely 0.
+    fn from_seed(seed: [u32, .. 4]) -> Xoshiro128Rng {
+        assert!(!seed.iter().all(|&x| x == 0),
+                "Xoshiro128Rng::from_seed called with an all zero seed.");
+        Xoshiro128Rng { s: seed }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Xoshiro256Rng, Xoshiro128Rng};
+    use {Rng, SeedableRng, task_rng};
+
+    #[test]
+    fn test_rng_256_rand_seeded() {
+        let s: u64 = task_rng().gen();
+        let mut ra: Xoshiro256Rng = SeedableRng::from_seed(s);
+        let mut rb: Xoshiro256Rng = SeedableRng::from_seed(s);
+        assert_eq!(ra.gen_ascii_str(100u), rb.gen_ascii_str(100u));
+    }
+
+    #[test]
+    fn test_rng_128_rand_seeded() {
+        let mut r = task_rng();
+        let s = [r.gen(), r.gen(), r.gen(), 1u32];
+        let mut ra: Xoshiro128Rng = SeedableRng::from_seed(s);
+        let mut rb: Xoshiro128Rng = SeedableRng::from_seed(s);
+        assert_eq!(ra.gen_ascii_str(100u), rb.gen_ascii_str(100u));
+    }
+
+    #[test]
+    fn test_rng_256_reseed() {
+        let mut r: Xoshiro256Rng = SeedableRng::from_seed([1u64, 2, 3, 4]);
+        let string1 = r.gen_ascii_str(100);
+
+        r.reseed([1u64, 2, 3, 4]);
+
+        let string2 = r.gen_ascii_str(100);
+        assert_eq!(string1, string2);
+    }
+
+    #[test]
+    fn test_rng_128_reseed() {
+        let mut r: Xoshiro128Rng = SeedableRng::from_seed([1u32, 2, 3, 4]);
+        let string1 = r.gen_ascii_str(100);
+
+        r.reseed([1u32, 2, 3, 4]);
+
+        let string2 = r.gen_ascii_str(100);
+        assert_eq!(string1, string2);
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_rng_256_zero_seed() {
+        let _: Xoshiro256Rng = SeedableRng::from_seed([0u64, 0, 0, 0]);
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_rng_128_zero_seed() {
+        let _: Xoshiro128Rng = SeedableRng::from_seed([0u32, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_rng_256_split() {
+        let mut ra: Xoshiro256Rng = SeedableRng::from_seed(12345u64);
+        let mut rb = ra.clone();
+        let mut child = ra.split();
+        assert_eq!(child.gen_vec::<u64>(10), rb.gen_vec::<u64>(10));
+
+        let mut rc: Xoshiro256Rng = SeedableRng::from_seed(12345u64);
+        rc.jump();
+        assert_eq!(ra.gen_vec::<u64>(10), rc.gen_vec::<u64>(10));
+    }
+
+    #[test]
+    fn test_rng_128_split() {
+        let mut ra: Xoshiro128Rng = SeedableRng::from_seed([1u32, 2, 3, 4]);
+        let mut rb = ra.clone();
+        let mut child = ra.split();
+        assert_eq!(child.gen_vec::<u32>(10), rb.gen_vec::<u32>(10));
+        assert!(child.gen_vec::<u32>(10) != ra.gen_vec::<u32>(10));
+    }
+
+    #[test]
+    fn test_rng_256_true_values() {
+        let mut ra: Xoshiro256Rng = SeedableRng::from_seed([1u64, 2, 3, 4]);
+        let v = Vec::from_fn(10, |_| ra.next_u64());
+        assert_eq!(v,
+                   vec!(11520, 0, 1509978240, 1215971899390074240, 1216172134540287360,
+                        607988272756665600, 16172922978634559625, 8476171486693032832,
+                        10595114339597558777, 2904607092377533576));
+
+        let mut rb: Xoshiro256Rng = SeedableRng::from_seed([1u64, 2, 3, 4]);
+        rb.jump();
+        let v = Vec::from_fn(3, |_| rb.next_u64());
+        assert_eq!(v,
+                   vec!(13534147089533256664, 7126240192422241655, 3805973808039778091));
+
+        let mut rc: Xoshiro256Rng = SeedableRng::from_seed([1u64, 2, 3, 4]);
+        rc.long_jump();
+        let v = Vec::from_fn(3, |_| rc.next_u64());
+        assert_eq!(v,
+                   vec!(5942309088398569549, 15625447729937358436, 6925613901769781251));
+
+        let mut rd: Xoshiro256Rng = SeedableRng::from_seed(12345u64);
+        let v = Vec::from_fn(3, |_| rd.next_u64());
+        assert_eq!(v,
+                   vec!(13720838825685603483, 2398916695208396998, 17770384849984869256));
+    }
+
+    #[test]
+    fn test_rng_128_true_values() {
+        let mut ra: Xoshiro128Rng = SeedableRng::from_seed([1u32, 2, 3, 4]);
+        let v = Vec::from_fn(10, |_| ra.next_u32());
+        assert_eq!(v,
+                   vec!(11520, 0, 5927040, 70819200, 2031721883,
+                        1637235492, 1287239034, 3734860849, 3729100597, 4258142804));
+
+        let mut rb: Xoshiro128Rng = SeedableRng::from_seed([1u32, 2, 3, 4]);
+        rb.jump();
+        let v = Vec::from_fn(3, |_| rb.next_u32());
+        assert_eq!(v, vec!(1194304935, 745561276, 25819468));
+    }
+}
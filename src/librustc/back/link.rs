@@ -24,7 +24,8 @@ use middle::trans::common::gensym_name;
 use middle::ty;
 use util::common::time;
 use util::ppaux;
-use util::sha2::{Digest, Sha256};
+use crypto::Digest;
+use crypto::sha2::Sha256;
 
 use std::c_str::{ToCStr, CString};
 use std::char;
@@ -38,6 +38,7 @@ extern crate serialize;
 extern crate sync;
 extern crate getopts;
 extern crate collections;
+extern crate crypto;
 extern crate time;
 extern crate libc;
 
@@ -125,7 +126,6 @@ pub mod driver;
 pub mod util {
     pub mod common;
     pub mod ppaux;
-    pub mod sha2;
     pub mod nodemap;
     pub mod fs;
 }
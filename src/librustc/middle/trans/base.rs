@@ -67,7 +67,7 @@ use middle::ty;
 use middle::typeck;
 use util::common::indenter;
 use util::ppaux::{Repr, ty_to_str};
-use util::sha2::Sha256;
+use crypto::sha2::Sha256;
 use util::nodemap::NodeMap;
 
 use arena::TypedArena;
@@ -24,7 +24,7 @@ use middle::trans::common::{mono_id,ExternMap,tydesc_info,BuilderRef_res,Stats};
 use middle::trans::debuginfo;
 use middle::trans::type_::Type;
 use middle::ty;
-use util::sha2::Sha256;
+use crypto::sha2::Sha256;
 use util::nodemap::{NodeMap, NodeSet, DefIdMap};
 
 use std::cell::{Cell, RefCell};
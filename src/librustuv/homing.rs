@@ -168,6 +168,7 @@ mod test {
         let mut pool = SchedPool::new(PoolConfig {
             threads: 1,
             event_loop_factory: ::event_loop,
+            seed: None,
         });
 
         pool.spawn(TaskOpts::new(), proc() {
@@ -189,6 +190,7 @@ mod test {
         let mut pool = SchedPool::new(PoolConfig {
             threads: 1,
             event_loop_factory: ::event_loop,
+            seed: None,
         });
 
         pool.spawn(TaskOpts::new(), proc() {
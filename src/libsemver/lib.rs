@@ -27,6 +27,9 @@
 //!
 //! An example version number with all five components is
 //! `0.8.1-rc.3.0+20130922.linux`.
+//!
+//! A `VersionReq` is a set of constraints such as `>=1.2, <2.0` which a
+//! version may satisfy; see the `req` module for their syntax.
 
 #![crate_id = "semver#0.11-pre"]
 #![crate_type = "rlib"]
@@ -41,8 +44,13 @@ use std::char;
 use std::cmp;
 use std::fmt;
 use std::fmt::Show;
+use std::from_str::FromStr;
 use std::option::{Option, Some, None};
-use std::strbuf::StrBuf;
+
+pub use req::{VersionReq, ReqParseError, ErrorEmptyPredicate,
+              ErrorInvalidOperator, ErrorInvalidWildcard, ErrorVersion};
+
+pub mod req;
 
 /// An identifier in the pre-release or build metadata. If the identifier can
 /// be parsed as a decimal value, it will be represented with `Numeric`.
@@ -157,124 +165,128 @@ impl cmp::Ord for Version {
     }
 }
 
-fn take_nonempty_prefix<T:Iterator<char>>(rdr: &mut T, pred: |char| -> bool)
-                        -> (~str, Option<char>) {
-    let mut buf = StrBuf::new();
-    let mut ch = rdr.next();
-    loop {
-        match ch {
-            None => break,
-            Some(c) if !pred(c) => break,
-            Some(c) => {
-                buf.push_char(c);
-                ch = rdr.next();
-            }
+/// An error in parsing a version.
+#[deriving(Clone, Eq)]
+pub enum ParseError {
+    /// The version contains characters which aren't ASCII.
+    ErrorNonAscii,
+    /// The version doesn't have exactly three numeric components. Contains
+    /// the number of components found.
+    ErrorWrongNumberOfParts(uint),
+    /// A numeric component is empty, isn't a decimal number or is too large.
+    ErrorInvalidNumber(~str),
+    /// A numeric component or identifier has a leading zero, which
+    /// SemVer 2.0 forbids.
+    ErrorLeadingZero(~str),
+    /// A pre-release or build metadata identifier is empty.
+    ErrorEmptyIdentifier,
+    /// An identifier contains a character other than an ASCII letter, digit
+    /// or hyphen.
+    ErrorInvalidCharacter(char),
+}
+
+impl fmt::Show for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ErrorNonAscii =>
+                write!(f.buf, "Invalid version; contains non-ASCII characters"),
+            ErrorWrongNumberOfParts(found) =>
+                write!(f.buf, "Invalid version; expected major.minor.patch, \
+                               found {} component(s)", found),
+            ErrorInvalidNumber(ref s) =>
+                write!(f.buf, "Invalid version; `{}` is not a valid number", *s),
+            ErrorLeadingZero(ref s) =>
+                write!(f.buf, "Invalid version; `{}` has a leading zero", *s),
+            ErrorEmptyIdentifier =>
+                write!(f.buf, "Invalid version; empty pre-release or build \
+                               identifier"),
+            ErrorInvalidCharacter(c) =>
+                write!(f.buf, "Invalid version; unexpected character `{}` in \
+                               identifier", c),
         }
     }
-    (buf.into_owned(), ch)
 }
 
-fn take_num<T: Iterator<char>>(rdr: &mut T) -> Option<(uint, Option<char>)> {
-    let (s, ch) = take_nonempty_prefix(rdr, char::is_digit);
+fn parse_number(s: &str) -> Result<uint, ParseError> {
+    if s.is_empty() || !s.chars().all(char::is_digit) {
+        return Err(ErrorInvalidNumber(s.to_owned()));
+    }
+    if s.len() > 1 && s.starts_with("0") {
+        return Err(ErrorLeadingZero(s.to_owned()));
+    }
     match from_str::<uint>(s) {
-        None => None,
-        Some(i) => Some((i, ch))
+        Some(n) => Ok(n),
+        None => Err(ErrorInvalidNumber(s.to_owned()))
     }
 }
 
-fn take_ident<T: Iterator<char>>(rdr: &mut T) -> Option<(Identifier, Option<char>)> {
-    let (s,ch) = take_nonempty_prefix(rdr, char::is_alphanumeric);
-    if s.chars().all(char::is_digit) {
-        match from_str::<uint>(s) {
-            None => None,
-            Some(i) => Some((Numeric(i), ch))
+// Build metadata may have numeric identifiers with leading zeros, which are
+// kept as alphanumeric ones so that the version prints as it was written.
+fn parse_identifiers(s: &str, leading_zeros: bool) -> Result<Vec<Identifier>, ParseError> {
+    let mut ids = Vec::new();
+    for id in s.split('.') {
+        if id.is_empty() {
+            return Err(ErrorEmptyIdentifier);
+        }
+        match id.chars().find(|&c| !(char::is_alphanumeric(c) || c == '-')) {
+            Some(c) => return Err(ErrorInvalidCharacter(c)),
+            None => {}
+        }
+        if !id.chars().all(char::is_digit) ||
+           (leading_zeros && id.len() > 1 && id.starts_with("0")) {
+            ids.push(AlphaNumeric(id.to_owned()));
+        } else {
+            ids.push(Numeric(try!(parse_number(id))));
         }
-    } else {
-        Some((AlphaNumeric(s), ch))
     }
+    Ok(ids)
 }
 
-fn expect(ch: Option<char>, c: char) -> Option<()> {
-    if ch != Some(c) {
-        None
-    } else {
-        Some(())
-    }
-}
+impl Version {
+    /// Parse a string into a version, following the rules of SemVer 2.0.
+    /// Leading and trailing whitespace is ignored.
+    pub fn parse(s: &str) -> Result<Version, ParseError> {
+        if !s.is_ascii() {
+            return Err(ErrorNonAscii);
+        }
+        let s = s.trim();
+
+        let (s, build) = match s.find('+') {
+            Some(i) => (s.slice_to(i), try!(parse_identifiers(s.slice_from(i + 1), true))),
+            None => (s, vec!())
+        };
+        let (s, pre) = match s.find('-') {
+            Some(i) => (s.slice_to(i), try!(parse_identifiers(s.slice_from(i + 1), false))),
+            None => (s, vec!())
+        };
+
+        let parts: Vec<&str> = s.split('.').collect();
+        if parts.len() != 3 {
+            return Err(ErrorWrongNumberOfParts(parts.len()));
+        }
 
-fn parse_iter<T: Iterator<char>>(rdr: &mut T) -> Option<Version> {
-    let maybe_vers = take_num(rdr).and_then(|(major, ch)| {
-        expect(ch, '.').and_then(|_| Some(major))
-    }).and_then(|major| {
-        take_num(rdr).and_then(|(minor, ch)| {
-            expect(ch, '.').and_then(|_| Some((major, minor)))
+        Ok(Version {
+            major: try!(parse_number(*parts.get(0))),
+            minor: try!(parse_number(*parts.get(1))),
+            patch: try!(parse_number(*parts.get(2))),
+            pre: pre,
+            build: build,
         })
-    }).and_then(|(major, minor)| {
-        take_num(rdr).and_then(|(patch, ch)| {
-           Some((major, minor, patch, ch))
-        })
-    });
-
-    let (major, minor, patch, ch) = match maybe_vers {
-        Some((a, b, c, d)) => (a, b, c, d),
-        None => return None
-    };
-
-    let mut pre = vec!();
-    let mut build = vec!();
-
-    let mut ch = ch;
-    if ch == Some('-') {
-        loop {
-            let (id, c) = match take_ident(rdr) {
-                Some((id, c)) => (id, c),
-                None => return None
-            };
-            pre.push(id);
-            ch = c;
-            if ch != Some('.') { break; }
-        }
     }
+}
 
-    if ch == Some('+') {
-        loop {
-            let (id, c) = match take_ident(rdr) {
-                Some((id, c)) => (id, c),
-                None => return None
-            };
-            build.push(id);
-            ch = c;
-            if ch != Some('.') { break; }
-        }
+impl FromStr for Version {
+    fn from_str(s: &str) -> Option<Version> {
+        Version::parse(s).ok()
     }
-
-    Some(Version {
-        major: major,
-        minor: minor,
-        patch: patch,
-        pre: pre,
-        build: build,
-    })
 }
 
-
 /// Parse a string into a semver object.
+///
+/// Returns `None` if the string isn't a valid version; use `Version::parse`
+/// to find out why.
 pub fn parse(s: &str) -> Option<Version> {
-    if !s.is_ascii() {
-        return None;
-    }
-    let s = s.trim();
-    let v = parse_iter(&mut s.chars());
-    match v {
-        Some(v) => {
-            if v.to_str().equiv(&s) {
-                Some(v)
-            } else {
-                None
-            }
-        }
-        None => None
-    }
+    Version::parse(s).ok()
 }
 
 #[test]
@@ -358,6 +370,34 @@ fn test_parse() {
 
 }
 
+#[test]
+fn test_parse_errors() {
+    assert_eq!(Version::parse("1.2.3-alpha.1+build.007"), Ok(Version {
+        major: 1u,
+        minor: 2u,
+        patch: 3u,
+        pre: vec!(AlphaNumeric("alpha".to_owned()), Numeric(1)),
+        build: vec!(AlphaNumeric("build".to_owned()), AlphaNumeric("007".to_owned()))
+    }));
+    assert_eq!(Version::parse("1.2.3-x-y").unwrap().to_str(), "1.2.3-x-y".to_owned());
+    assert_eq!(from_str::<Version>("1.2.3"), parse("1.2.3"));
+
+    assert_eq!(Version::parse("1.2.3\u00e9"), Err(ErrorNonAscii));
+    assert_eq!(Version::parse(""), Err(ErrorWrongNumberOfParts(1)));
+    assert_eq!(Version::parse("1.2"), Err(ErrorWrongNumberOfParts(2)));
+    assert_eq!(Version::parse("1.2.3.4"), Err(ErrorWrongNumberOfParts(4)));
+    assert_eq!(Version::parse("1.2.3 abc"), Err(ErrorInvalidNumber("3 abc".to_owned())));
+    assert_eq!(Version::parse("1..3"), Err(ErrorInvalidNumber("".to_owned())));
+    assert_eq!(Version::parse("99999999999999999999.0.0"),
+               Err(ErrorInvalidNumber("99999999999999999999".to_owned())));
+    assert_eq!(Version::parse("01.2.3"), Err(ErrorLeadingZero("01".to_owned())));
+    assert_eq!(Version::parse("1.2.3-01"), Err(ErrorLeadingZero("01".to_owned())));
+    assert_eq!(Version::parse("1.2.3-"), Err(ErrorEmptyIdentifier));
+    assert_eq!(Version::parse("1.2.3-a..b"), Err(ErrorEmptyIdentifier));
+    assert_eq!(Version::parse("1.2.3+"), Err(ErrorEmptyIdentifier));
+    assert_eq!(Version::parse("1.2.3-a_b"), Err(ErrorInvalidCharacter('_')));
+}
+
 #[test]
 fn test_eq() {
     assert_eq!(parse("1.2.3"), parse("1.2.3"));
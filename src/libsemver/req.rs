@@ -0,0 +1,511 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Version requirements.
+//!
+//! A requirement is a comma-separated list of predicates, all of which a
+//! version must satisfy to match it. Each predicate is an operator followed
+//! by a version whose minor and patch numbers may be left out:
+//!
+//!  * `=1.2.3` matches exactly 1.2.3, `=1.2` any 1.2.x and `=1` any 1.x.y.
+//!  * `>1.2.3`, `>=1.2.3`, `<1.2.3` and `<=1.2.3` compare with the version.
+//!    Missing parts act as wildcards, so `>1.2` means `>=1.3.0` and `<=1.2`
+//!    means `<1.3.0`.
+//!  * `~1.2.3` allows patch-level changes: `>=1.2.3, <1.3.0`. `~1` is
+//!    `>=1.0.0, <2.0.0`.
+//!  * `^1.2.3` allows changes which don't modify the left-most non-zero
+//!    number: `>=1.2.3, <2.0.0`, and `^0.2.3` is `>=0.2.3, <0.3.0`.
+//!  * A version without an operator is the same as `^`.
+//!  * `1.*` and `1.2.*` match any version with the given numbers, and `*`
+//!    matches any version at all.
+//!
+//! A pre-release version only matches a requirement if one of its
+//! predicates names a pre-release of the same major, minor and patch
+//! numbers. So `>=1.2.3-beta` matches `1.2.3-rc.1`, but not `1.3.0-beta`.
+//!
+//! ```rust
+//! use semver::{Version, VersionReq};
+//!
+//! let req = VersionReq::parse(">=1.2, <2.0").unwrap();
+//! assert!(req.matches(&Version::parse("1.4.0").unwrap()));
+//! assert!(!req.matches(&Version::parse("2.0.0").unwrap()));
+//! ```
+
+use std::fmt;
+use std::from_str::FromStr;
+
+use {Version, Identifier, ParseError, ErrorWrongNumberOfParts};
+use {parse_number, parse_identifiers};
+
+/// An error in parsing a version requirement.
+#[deriving(Clone, Eq)]
+pub enum ReqParseError {
+    /// The requirement or one of its predicates is empty.
+    ErrorEmptyPredicate,
+    /// An operator isn't one of `=`, `>`, `>=`, `<`, `<=`, `~` and `^`.
+    ErrorInvalidOperator(~str),
+    /// A wildcard is used with an operator, or isn't the last part of a
+    /// version.
+    ErrorInvalidWildcard,
+    /// The version of a predicate is invalid.
+    ErrorVersion(ParseError),
+}
+
+impl fmt::Show for ReqParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ErrorEmptyPredicate =>
+                write!(f.buf, "Invalid requirement; empty predicate"),
+            ErrorInvalidOperator(ref op) =>
+                write!(f.buf, "Invalid requirement; unknown operator `{}`", *op),
+            ErrorInvalidWildcard =>
+                write!(f.buf, "Invalid requirement; wildcards must be the last \
+                               part of a version without an operator"),
+            ErrorVersion(ref e) => e.fmt(f),
+        }
+    }
+}
+
+#[deriving(Clone, Eq)]
+enum Op {
+    Ex,         // =
+    Gt,         // >
+    GtEq,       // >=
+    Lt,         // <
+    LtEq,       // <=
+    Tilde,      // ~
+    Compatible, // ^
+    Wildcard,   // 1.* or 1.2.*
+}
+
+#[deriving(Clone, Eq)]
+struct Predicate {
+    op: Op,
+    major: uint,
+    minor: Option<uint>,
+    patch: Option<uint>,
+    pre: Vec<Identifier>,
+}
+
+/// A bound of the range of versions allowed by a predicate.
+enum Bound {
+    Unbounded,
+    Inclusive(Version),
+    Exclusive(Version),
+}
+
+fn version(major: uint, minor: uint, patch: uint) -> Version {
+    Version { major: major, minor: minor, patch: patch, pre: vec!(), build: vec!() }
+}
+
+impl Predicate {
+    /// The version named by the predicate, with missing parts set to 0.
+    fn version(&self) -> Version {
+        Version {
+            major: self.major,
+            minor: self.minor.unwrap_or(0),
+            patch: self.patch.unwrap_or(0),
+            pre: self.pre.clone(),
+            build: vec!(),
+        }
+    }
+
+    /// The first version after those matching the given parts of the
+    /// predicate, as in `1.3.0` for `1.2`.
+    fn next_unmatched(&self) -> Version {
+        match self.minor {
+            Some(minor) => version(self.major, minor + 1, 0),
+            None => version(self.major + 1, 0, 0),
+        }
+    }
+
+    fn lower(&self) -> Bound {
+        match self.op {
+            Gt if self.patch.is_some() => Exclusive(self.version()),
+            Gt => Inclusive(self.next_unmatched()),
+            Lt | LtEq => Unbounded,
+            Ex | GtEq | Tilde | Compatible | Wildcard => Inclusive(self.version()),
+        }
+    }
+
+    fn upper(&self) -> Bound {
+        match self.op {
+            Gt | GtEq => Unbounded,
+            Lt => Exclusive(self.version()),
+            Ex | LtEq if self.patch.is_some() => Inclusive(self.version()),
+            Ex | LtEq | Tilde | Wildcard => Exclusive(self.next_unmatched()),
+            Compatible => match (self.major, self.minor, self.patch) {
+                (0, Some(0), Some(patch)) => Exclusive(version(0, 0, patch + 1)),
+                (0, Some(minor), _) => Exclusive(version(0, minor + 1, 0)),
+                (major, _, _) => Exclusive(version(major + 1, 0, 0)),
+            },
+        }
+    }
+
+    fn matches(&self, v: &Version) -> bool {
+        let above = match self.lower() {
+            Unbounded => true,
+            Inclusive(ref lo) => v >= lo,
+            Exclusive(ref lo) => v > lo,
+        };
+        let below = match self.upper() {
+            Unbounded => true,
+            Inclusive(ref hi) => v <= hi,
+            Exclusive(ref hi) => v < hi,
+        };
+        above && below
+    }
+
+    /// Whether the predicate allows pre-releases of `v`'s major, minor and
+    /// patch numbers.
+    fn allows_pre(&self, v: &Version) -> bool {
+        !self.pre.is_empty() &&
+            self.major == v.major &&
+            self.minor == Some(v.minor) &&
+            self.patch == Some(v.patch)
+    }
+}
+
+impl fmt::Show for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let op = match self.op {
+            Ex => "=", Gt => ">", GtEq => ">=", Lt => "<", LtEq => "<=",
+            Tilde => "~", Compatible => "^", Wildcard => "",
+        };
+        try!(write!(f.buf, "{}{}", op, self.major));
+        match self.minor {
+            Some(minor) => try!(write!(f.buf, ".{}", minor)),
+            None => {}
+        }
+        match self.patch {
+            Some(patch) => try!(write!(f.buf, ".{}", patch)),
+            None => {}
+        }
+        if self.op == Wildcard {
+            try!(write!(f.buf, ".*"));
+        }
+        for (i, x) in self.pre.iter().enumerate() {
+            try!(write!(f.buf, "{}", if i == 0 { "-" } else { "." }));
+            try!(x.fmt(f));
+        }
+        Ok(())
+    }
+}
+
+fn parse_predicate(s: &str) -> Result<Predicate, ReqParseError> {
+    let n = s.find(|c: char| !"=<>~^".contains_char(c)).unwrap_or(s.len());
+    let op = match s.slice_to(n) {
+        "" => None,
+        "=" => Some(Ex),
+        ">" => Some(Gt),
+        ">=" => Some(GtEq),
+        "<" => Some(Lt),
+        "<=" => Some(LtEq),
+        "~" => Some(Tilde),
+        "^" => Some(Compatible),
+        op => return Err(ErrorInvalidOperator(op.to_owned()))
+    };
+    let s = s.slice_from(n).trim();
+
+    let (s, pre) = match s.find('-') {
+        Some(i) => {
+            match parse_identifiers(s.slice_from(i + 1), false) {
+                Ok(pre) => (s.slice_to(i), pre),
+                Err(e) => return Err(ErrorVersion(e))
+            }
+        }
+        None => (s, vec!())
+    };
+
+    let parts: Vec<&str> = s.split('.').collect();
+    if parts.len() > 3 || (!pre.is_empty() && parts.len() != 3) {
+        return Err(ErrorVersion(ErrorWrongNumberOfParts(parts.len())));
+    }
+
+    let mut nums = Vec::new();
+    let mut wildcard = false;
+    for (i, &part) in parts.iter().enumerate() {
+        if part == "*" || part == "x" || part == "X" {
+            if i == 0 || i != parts.len() - 1 || op.is_some() || !pre.is_empty() {
+                return Err(ErrorInvalidWildcard);
+            }
+            wildcard = true;
+        } else {
+            match parse_number(part) {
+                Ok(n) => nums.push(n),
+                Err(e) => return Err(ErrorVersion(e))
+            }
+        }
+    }
+
+    Ok(Predicate {
+        op: if wildcard { Wildcard } else { op.unwrap_or(Compatible) },
+        major: *nums.get(0),
+        minor: nums.as_slice().get(1).map(|&n| n),
+        patch: nums.as_slice().get(2).map(|&n| n),
+        pre: pre,
+    })
+}
+
+/// A requirement which versions may or may not match, such as
+/// `>=1.2, <2.0`.
+#[deriving(Clone, Eq)]
+pub struct VersionReq {
+    predicates: Vec<Predicate>,
+}
+
+impl VersionReq {
+    /// Returns the requirement which matches any version, written `*`.
+    pub fn any() -> VersionReq {
+        VersionReq { predicates: vec!() }
+    }
+
+    /// Returns the requirement which only matches `v`.
+    pub fn exact(v: &Version) -> VersionReq {
+        VersionReq {
+            predicates: vec!(Predicate {
+                op: Ex,
+                major: v.major,
+                minor: Some(v.minor),
+                patch: Some(v.patch),
+                pre: v.pre.clone(),
+            })
+        }
+    }
+
+    /// Parse a comma-separated list of predicates into a requirement.
+    /// Whitespace around predicates and after operators is ignored.
+    pub fn parse(s: &str) -> Result<VersionReq, ReqParseError> {
+        let mut predicates = vec!();
+        for p in s.split(',').map(|p| p.trim()) {
+            if p.is_empty() {
+                return Err(ErrorEmptyPredicate);
+            }
+            if p != "*" {
+                predicates.push(try!(parse_predicate(p)));
+            }
+        }
+        Ok(VersionReq { predicates: predicates })
+    }
+
+    /// Returns whether `v` satisfies the requirement.
+    pub fn matches(&self, v: &Version) -> bool {
+        self.predicates.iter().all(|p| p.matches(v)) &&
+            (v.pre.is_empty() || self.predicates.iter().any(|p| p.allows_pre(v)))
+    }
+
+    /// Returns the requirement which is satisfied by the versions which
+    /// satisfy both `self` and `other`, or `None` if the ranges of versions
+    /// they allow don't overlap.
+    pub fn intersection(&self, other: &VersionReq) -> Option<VersionReq> {
+        let mut predicates = self.predicates.clone();
+        predicates.push_all(other.predicates.as_slice());
+        let req = VersionReq { predicates: predicates };
+        if req.is_empty() { None } else { Some(req) }
+    }
+
+    /// Returns the greatest of `versions` which satisfies the requirement,
+    /// if any do.
+    pub fn best_match<'a>(&self, versions: &'a [Version]) -> Option<&'a Version> {
+        let mut best: Option<&'a Version> = None;
+        for v in versions.iter() {
+            if !self.matches(v) {
+                continue;
+            }
+            best = match best {
+                Some(b) if *b >= *v => Some(b),
+                _ => Some(v),
+            };
+        }
+        best
+    }
+
+    /// Whether the bounds of the predicates exclude every version.
+    fn is_empty(&self) -> bool {
+        let mut lower = Unbounded;
+        let mut upper = Unbounded;
+        for p in self.predicates.iter() {
+            lower = match (lower, p.lower()) {
+                (Unbounded, b) | (b, Unbounded) => b,
+                (Inclusive(a), Inclusive(b)) => Inclusive(if a > b { a } else { b }),
+                (Exclusive(a), Exclusive(b)) => Exclusive(if a > b { a } else { b }),
+                (Inclusive(a), Exclusive(b)) | (Exclusive(b), Inclusive(a)) =>
+                    if a > b { Inclusive(a) } else { Exclusive(b) },
+            };
+            upper = match (upper, p.upper()) {
+                (Unbounded, b) | (b, Unbounded) => b,
+                (Inclusive(a), Inclusive(b)) => Inclusive(if a < b { a } else { b }),
+                (Exclusive(a), Exclusive(b)) => Exclusive(if a < b { a } else { b }),
+                (Inclusive(a), Exclusive(b)) | (Exclusive(b), Inclusive(a)) =>
+                    if a < b { Inclusive(a) } else { Exclusive(b) },
+            };
+        }
+        match (lower, upper) {
+            (Inclusive(ref lo), Inclusive(ref hi)) => lo > hi,
+            (Inclusive(ref lo), Exclusive(ref hi)) |
+            (Exclusive(ref lo), Inclusive(ref hi)) |
+            (Exclusive(ref lo), Exclusive(ref hi)) => lo >= hi,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Show for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.predicates.is_empty() {
+            return write!(f.buf, "*");
+        }
+        for (i, p) in self.predicates.iter().enumerate() {
+            if i != 0 { try!(write!(f.buf, ", ")) };
+            try!(p.fmt(f));
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for VersionReq {
+    fn from_str(s: &str) -> Option<VersionReq> {
+        VersionReq::parse(s).ok()
+    }
+}
+
+#[cfg(test)]
+fn req(s: &str) -> VersionReq {
+    VersionReq::parse(s).unwrap()
+}
+
+#[cfg(test)]
+fn v(s: &str) -> Version {
+    Version::parse(s).unwrap()
+}
+
+#[test]
+fn test_parse_req() {
+    use {ErrorInvalidNumber, ErrorLeadingZero};
+
+    assert_eq!(req(">=1.2, <2.0").to_str(), ">=1.2, <2.0".to_owned());
+    assert_eq!(req("  ~1.3  ").to_str(), "~1.3".to_owned());
+    assert_eq!(req("^0.4").to_str(), "^0.4".to_owned());
+    assert_eq!(req("1.2.3").to_str(), "^1.2.3".to_owned());
+    assert_eq!(req(">= 1.0.0-beta.2").to_str(), ">=1.0.0-beta.2".to_owned());
+    assert_eq!(req("1.x").to_str(), "1.*".to_owned());
+    assert_eq!(req("1.2.*").to_str(), "1.2.*".to_owned());
+    assert_eq!(req("*").to_str(), "*".to_owned());
+
+    assert_eq!(VersionReq::parse(""), Err(ErrorEmptyPredicate));
+    assert_eq!(VersionReq::parse(">=1.0,"), Err(ErrorEmptyPredicate));
+    assert_eq!(VersionReq::parse("=>1.0"), Err(ErrorInvalidOperator("=>".to_owned())));
+    assert_eq!(VersionReq::parse(">=1.*"), Err(ErrorInvalidWildcard));
+    assert_eq!(VersionReq::parse("1.*.2"), Err(ErrorInvalidWildcard));
+    assert_eq!(VersionReq::parse("*.1"), Err(ErrorInvalidWildcard));
+    assert_eq!(VersionReq::parse("1.2.3.4"),
+               Err(ErrorVersion(ErrorWrongNumberOfParts(4))));
+    assert_eq!(VersionReq::parse("1.2-beta"),
+               Err(ErrorVersion(ErrorWrongNumberOfParts(2))));
+    assert_eq!(VersionReq::parse(">=a"),
+               Err(ErrorVersion(ErrorInvalidNumber("a".to_owned()))));
+    assert_eq!(VersionReq::parse("~01.2"),
+               Err(ErrorVersion(ErrorLeadingZero("01".to_owned()))));
+
+    assert_eq!(from_str::<VersionReq>(">1"), Some(req(">1")));
+    assert_eq!(from_str::<VersionReq>(">1,,"), None);
+}
+
+#[test]
+fn test_matches() {
+    let r = req("=1.2.3");
+    assert!(r.matches(&v("1.2.3")));
+    assert!(r.matches(&v("1.2.3+build")));
+    assert!(!r.matches(&v("1.2.4")));
+    assert!(req("=1.2").matches(&v("1.2.9")));
+    assert!(!req("=1").matches(&v("2.0.0")));
+
+    let r = req(">=1.2, <2.0");
+    assert!(!r.matches(&v("1.1.9")));
+    assert!(r.matches(&v("1.2.0")));
+    assert!(r.matches(&v("1.9.9")));
+    assert!(!r.matches(&v("2.0.0")));
+
+    assert!(req(">1.2").matches(&v("1.3.0")));
+    assert!(!req(">1.2").matches(&v("1.2.9")));
+    assert!(!req(">1.2.3").matches(&v("1.2.3")));
+    assert!(req("<=1.2").matches(&v("1.2.9")));
+    assert!(!req("<=1.2").matches(&v("1.3.0")));
+    assert!(!req("<1").matches(&v("1.0.0")));
+
+    let r = req("~1.3");
+    assert!(r.matches(&v("1.3.0")));
+    assert!(r.matches(&v("1.3.7")));
+    assert!(!r.matches(&v("1.4.0")));
+    assert!(req("~1.2.3").matches(&v("1.2.5")));
+    assert!(!req("~1.2.3").matches(&v("1.2.2")));
+    assert!(req("~1").matches(&v("1.9.0")));
+
+    let r = req("^0.4");
+    assert!(r.matches(&v("0.4.0")));
+    assert!(r.matches(&v("0.4.12")));
+    assert!(!r.matches(&v("0.5.0")));
+    assert!(req("^1.2.3").matches(&v("1.9.0")));
+    assert!(!req("^1.2.3").matches(&v("2.0.0")));
+    assert!(!req("^0.2.3").matches(&v("0.3.0")));
+    assert!(req("^0.0.3").matches(&v("0.0.3")));
+    assert!(!req("^0.0.3").matches(&v("0.0.4")));
+    assert!(req("^0.0").matches(&v("0.0.9")));
+    assert!(!req("^0.0").matches(&v("0.1.0")));
+    assert!(req("^0").matches(&v("0.9.0")));
+    assert!(req("1.2").matches(&v("1.5.0")));
+
+    assert!(req("1.*").matches(&v("1.5.0")));
+    assert!(!req("1.*").matches(&v("2.0.0")));
+    assert!(req("1.2.*").matches(&v("1.2.5")));
+    assert!(!req("1.2.*").matches(&v("1.3.0")));
+    assert!(req("*").matches(&v("42.0.0")));
+}
+
+#[test]
+fn test_matches_pre() {
+    let r = req(">=1.2.3-beta");
+    assert!(r.matches(&v("1.2.3-beta")));
+    assert!(r.matches(&v("1.2.3-rc.1")));
+    assert!(r.matches(&v("1.2.3")));
+    assert!(!r.matches(&v("1.2.3-alpha")));
+    assert!(!r.matches(&v("1.3.0-beta")));
+
+    assert!(!req("<2.0").matches(&v("2.0.0-alpha")));
+    assert!(!req("*").matches(&v("1.0.0-alpha")));
+    assert!(VersionReq::exact(&v("1.0.0-alpha")).matches(&v("1.0.0-alpha")));
+}
+
+#[test]
+fn test_intersection() {
+    let r = req(">=1.2").intersection(&req("<2.0")).unwrap();
+    assert_eq!(r, req(">=1.2, <2.0"));
+    assert!(r.matches(&v("1.5.0")));
+
+    assert!(req("^1.2").intersection(&req("~1.4")).is_some());
+    assert!(req("^1.2").intersection(&req("^2.0")).is_none());
+    assert!(req("<1.0").intersection(&req(">=1.0")).is_none());
+    assert!(req("=1.2.3").intersection(&req("=1.2.3")).is_some());
+    assert!(req("=1.2.3").intersection(&req(">1.2.3")).is_none());
+    assert!(req("<=1.2.3").intersection(&req(">=1.2.3")).is_some());
+    assert!(req("*").intersection(&req("*")).is_some());
+}
+
+#[test]
+fn test_best_match() {
+    let versions = vec!(v("0.9.0"), v("1.2.0"), v("1.4.1"), v("1.10.0"),
+                        v("2.0.0-beta"), v("2.0.0"));
+    let best = |s: &str| req(s).best_match(versions.as_slice()).map(|v| v.to_str());
+    assert_eq!(best("^1.2"), Some("1.10.0".to_owned()));
+    assert_eq!(best("~1.4"), Some("1.4.1".to_owned()));
+    assert_eq!(best("*"), Some("2.0.0".to_owned()));
+    assert_eq!(best(">=2.0.0-alpha, <2.0.0"), Some("2.0.0-beta".to_owned()));
+    assert_eq!(best(">=3.0"), None);
+}
@@ -16,7 +16,7 @@ use std::hash::{Hash, Hasher};
 
 use {Decodable, Encodable, Decoder, Encoder};
 use collections::{DList, RingBuf, TreeMap, TreeSet, Deque, HashMap, HashSet,
-                  TrieMap, TrieSet};
+                  TrieMap, TrieSet, RoaringBitmap};
 use collections::enum_set::{EnumSet, CLike};
 
 impl<
@@ -311,3 +311,26 @@ impl<E, D: Decoder<E>> Decodable<D, E> for TrieSet {
         })
     }
 }
+
+impl<E, S: Encoder<E>> Encodable<S, E> for RoaringBitmap {
+    fn encode(&self, s: &mut S) -> Result<(), E> {
+        s.emit_seq(self.len(), |s| {
+            for (i, e) in self.iter().enumerate() {
+                try!(s.emit_seq_elt(i, |s| e.encode(s)));
+            }
+            Ok(())
+        })
+    }
+}
+
+impl<E, D: Decoder<E>> Decodable<D, E> for RoaringBitmap {
+    fn decode(d: &mut D) -> Result<RoaringBitmap, E> {
+        d.read_seq(|d, len| {
+            let mut set = RoaringBitmap::new();
+            for i in range(0u, len) {
+                set.insert(try!(d.read_seq_elt(i, |d| Decodable::decode(d))));
+            }
+            Ok(set)
+        })
+    }
+}
@@ -0,0 +1,251 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * Implementation of the 64-bit FNV-1a hash
+ *
+ * See: http://www.isthe.com/chongo/tech/comp/fnv/
+ *
+ * FNV-1a processes its input a byte at a time with a single xor and
+ * multiply, which makes it considerably cheaper than SipHash for the short
+ * keys (integers, small strings) that dominate many hashtables.
+ *
+ * FNV is *not* resistant to collision attacks, even when seeded through
+ * `SeedableHasher`. Only use it for tables whose keys are not chosen by an
+ * adversary.
+ */
+
+use clone::Clone;
+use default::Default;
+use io::{IoResult, Writer};
+use iter::Iterator;
+use result::Ok;
+use slice::ImmutableVector;
+
+use super::{Hash, Hasher, SeedableHasher};
+
+static OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+static PRIME: u64 = 0x100000001b3;
+
+/// `FnvState` computes a 64-bit FNV-1a hash over a stream of bytes.
+pub struct FnvState {
+    basis: u64,
+    hash: u64,
+}
+
+impl FnvState {
+    /// Create a `FnvState` starting from the standard offset basis.
+    #[inline]
+    pub fn new() -> FnvState {
+        FnvState::new_with_basis(OFFSET_BASIS)
+    }
+
+    /// Create a `FnvState` starting from a custom offset basis.
+    #[inline]
+    pub fn new_with_basis(basis: u64) -> FnvState {
+        FnvState { basis: basis, hash: basis }
+    }
+
+    /// Reset the state back to it's initial state.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.hash = self.basis;
+    }
+
+    /// Return the computed hash.
+    #[inline]
+    pub fn result(&self) -> u64 {
+        self.hash
+    }
+}
+
+impl Writer for FnvState {
+    #[inline]
+    fn write(&mut self, msg: &[u8]) -> IoResult<()> {
+        let mut hash = self.hash;
+        for byte in msg.iter() {
+            hash ^= *byte as u64;
+            hash *= PRIME;
+        }
+        self.hash = hash;
+        Ok(())
+    }
+}
+
+impl Clone for FnvState {
+    #[inline]
+    fn clone(&self) -> FnvState {
+        *self
+    }
+}
+
+impl Default for FnvState {
+    #[inline]
+    fn default() -> FnvState {
+        FnvState::new()
+    }
+}
+
+/// `FnvHasher` computes the FNV-1a algorithm from a stream of bytes.
+#[deriving(Clone)]
+pub struct FnvHasher {
+    basis: u64,
+}
+
+impl FnvHasher {
+    /// Create a `FnvHasher` using the standard offset basis.
+    #[inline]
+    pub fn new() -> FnvHasher {
+        FnvHasher { basis: OFFSET_BASIS }
+    }
+
+    /// Create a `FnvHasher` whose offset basis is derived from the provided
+    /// keys.
+    #[inline]
+    pub fn new_with_keys(key0: u64, key1: u64) -> FnvHasher {
+        let mut state = FnvState::new();
+        let _ = state.write_le_u64(key0);
+        let _ = state.write_le_u64(key1);
+        FnvHasher { basis: state.result() }
+    }
+}
+
+impl Hasher<FnvState> for FnvHasher {
+    #[inline]
+    fn hash<T: Hash<FnvState>>(&self, value: &T) -> u64 {
+        let mut state = FnvState::new_with_basis(self.basis);
+        value.hash(&mut state);
+        state.result()
+    }
+}
+
+impl SeedableHasher for FnvHasher {
+    #[inline]
+    fn from_keys(key0: u64, key1: u64) -> FnvHasher {
+        FnvHasher::new_with_keys(key0, key1)
+    }
+}
+
+impl Default for FnvHasher {
+    #[inline]
+    fn default() -> FnvHasher {
+        FnvHasher::new()
+    }
+}
+
+/// Hash a value using the FNV-1a algorithm.
+#[inline]
+pub fn hash<T: Hash<FnvState>>(value: &T) -> u64 {
+    let mut state = FnvState::new();
+    value.hash(&mut state);
+    state.result()
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test;
+    use prelude::*;
+    use self::test::Bencher;
+
+    use super::super::{Hash, Hasher};
+    use super::{FnvState, FnvHasher, hash};
+
+    // Hash just the bytes of the slice, without length prefix
+    struct Bytes<'a>(&'a [u8]);
+
+    impl<'a, S: Writer> Hash<S> for Bytes<'a> {
+        #[allow(unused_must_use)]
+        fn hash(&self, state: &mut S) {
+            let Bytes(v) = *self;
+            state.write(v);
+        }
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(hash(&Bytes(&[])), 0xcbf29ce484222325);
+        assert_eq!(hash(&Bytes("a".as_bytes())), 0xaf63dc4c8601ec8c);
+        assert_eq!(hash(&Bytes("abc".as_bytes())), 0xe71fa2190541574b);
+        assert_eq!(hash(&Bytes("foobar".as_bytes())), 0x85944171f73967e8);
+    }
+
+    #[test]
+    #[allow(unused_must_use)]
+    fn test_incremental() {
+        let mut state = FnvState::new();
+        state.write("foo".as_bytes());
+        state.write("bar".as_bytes());
+        assert_eq!(state.result(), 0x85944171f73967e8);
+
+        state.reset();
+        assert_eq!(state.result(), 0xcbf29ce484222325);
+    }
+
+    #[test]
+    fn test_keys() {
+        let plain = FnvHasher::new();
+        let k1 = FnvHasher::new_with_keys(1, 2);
+        let k2 = FnvHasher::new_with_keys(2, 1);
+
+        assert_eq!(plain.hash(&5u64), hash(&5u64));
+        assert!(k1.hash(&5u64) != plain.hash(&5u64));
+        assert!(k1.hash(&5u64) != k2.hash(&5u64));
+        assert_eq!(k1.hash(&5u64), FnvHasher::new_with_keys(1, 2).hash(&5u64));
+    }
+
+    #[test]
+    fn test_hash_no_concat_alias() {
+        let s = ("aa", "bb");
+        let t = ("aabb", "");
+        let u = ("a", "abb");
+
+        assert!(hash(&s) != hash(&t) && hash(&s) != hash(&u));
+    }
+
+    #[bench]
+    fn bench_str_under_8_bytes(b: &mut Bencher) {
+        let s = "foo";
+        b.iter(|| {
+            hash(&s)
+        })
+    }
+
+    #[bench]
+    fn bench_str_of_8_bytes(b: &mut Bencher) {
+        let s = "foobar78";
+        b.iter(|| {
+            hash(&s)
+        })
+    }
+
+    #[bench]
+    fn bench_str_over_8_bytes(b: &mut Bencher) {
+        let s = "foobarbaz0";
+        b.iter(|| {
+            hash(&s)
+        })
+    }
+
+    #[bench]
+    fn bench_u64(b: &mut Bencher) {
+        let u = 16262950014981195938u64;
+        b.iter(|| {
+            hash(&u)
+        })
+    }
+
+    #[bench]
+    fn bench_u32(b: &mut Bencher) {
+        let u = 162629500u32;
+        b.iter(|| {
+            hash(&u)
+        })
+    }
+}
@@ -76,6 +76,8 @@ use vec::Vec;
 pub use hash = self::sip::hash;
 
 pub mod sip;
+pub mod fnv;
+pub mod xxhash;
 
 /// A trait that represents a hashable type. The `S` type parameter is an
 /// abstract hash state that is used by the `Hash` to compute the hash.
@@ -92,6 +94,14 @@ pub trait Hasher<S> {
     fn hash<T: Hash<S>>(&self, value: &T) -> u64;
 }
 
+/// A `Hasher` which can be keyed from a pair of 64-bit values. Containers use
+/// this to build a fresh, unpredictable hasher without knowing which hashing
+/// algorithm they are parameterized over.
+pub trait SeedableHasher {
+    /// Create a hasher keyed off the provided keys.
+    fn from_keys(key0: u64, key1: u64) -> Self;
+}
+
 //////////////////////////////////////////////////////////////////////////////
 
 macro_rules! impl_hash(
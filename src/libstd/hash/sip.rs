@@ -34,7 +34,7 @@ use result::Ok;
 use slice::ImmutableVector;
 use uint;
 
-use super::{Hash, Hasher};
+use super::{Hash, Hasher, SeedableHasher};
 
 /// `SipState` computes a SipHash 2-4 hash over a stream of bytes.
 pub struct SipState {
@@ -331,6 +331,13 @@ impl Hasher<SipState> for SipHasher {
     }
 }
 
+impl SeedableHasher for SipHasher {
+    #[inline]
+    fn from_keys(key0: u64, key1: u64) -> SipHasher {
+        SipHasher::new_with_keys(key0, key1)
+    }
+}
+
 impl Default for SipHasher {
     #[inline]
     fn default() -> SipHasher {
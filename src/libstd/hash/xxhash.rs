@@ -0,0 +1,415 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+ * Implementation of xxHash64
+ *
+ * See: https://code.google.com/p/xxhash/
+ *
+ * xxHash consumes its input in 32-byte stripes spread over four independent
+ * accumulators, and finishes short inputs with a cheap avalanche. It is much
+ * faster than SipHash on both short keys and long byte strings, while still
+ * mixing well enough for hashtables.
+ *
+ * Like FNV, xxHash is not designed to resist collision attacks. Seeding it
+ * (for example through `SeedableHasher`) makes collisions harder to
+ * precompute, but it should not be relied upon when keys come from an
+ * adversary.
+ */
+
+use clone::Clone;
+use container::Container;
+use default::Default;
+use io::{IoResult, Writer};
+use result::Ok;
+use slice::{ImmutableVector, MutableVector};
+use slice::bytes::copy_memory;
+
+use super::{Hash, Hasher, SeedableHasher};
+
+static PRIME1: u64 = 11400714785074694791;
+static PRIME2: u64 = 14029467366897019727;
+static PRIME3: u64 = 1609587929392839161;
+static PRIME4: u64 = 9650029242287828579;
+static PRIME5: u64 = 2870177450012600261;
+
+static STRIPE: uint = 32;
+
+#[inline]
+fn rotl(x: u64, b: uint) -> u64 {
+    (x << b) | (x >> (64 - b))
+}
+
+#[inline]
+fn read_le_u64(buf: &[u8], i: uint) -> u64 {
+    buf[i] as u64 |
+    buf[i + 1] as u64 << 8 |
+    buf[i + 2] as u64 << 16 |
+    buf[i + 3] as u64 << 24 |
+    buf[i + 4] as u64 << 32 |
+    buf[i + 5] as u64 << 40 |
+    buf[i + 6] as u64 << 48 |
+    buf[i + 7] as u64 << 56
+}
+
+#[inline]
+fn read_le_u32(buf: &[u8], i: uint) -> u64 {
+    buf[i] as u64 |
+    buf[i + 1] as u64 << 8 |
+    buf[i + 2] as u64 << 16 |
+    buf[i + 3] as u64 << 24
+}
+
+#[inline]
+fn round(acc: u64, input: u64) -> u64 {
+    rotl(acc + input * PRIME2, 31) * PRIME1
+}
+
+#[inline]
+fn merge_round(acc: u64, val: u64) -> u64 {
+    (acc ^ round(0, val)) * PRIME1 + PRIME4
+}
+
+/// `XxState` computes an xxHash64 hash over a stream of bytes.
+pub struct XxState {
+    seed: u64,
+    v1: u64,
+    v2: u64,
+    v3: u64,
+    v4: u64,
+    length: u64,      // how many bytes we've processed
+    buf: [u8, ..32],  // unprocessed bytes
+    nbuf: uint,       // how many bytes in buf are valid
+}
+
+impl XxState {
+    /// Create a `XxState` with a seed of zero.
+    #[inline]
+    pub fn new() -> XxState {
+        XxState::new_with_seed(0)
+    }
+
+    /// Create a `XxState` that is seeded with the provided value.
+    #[inline]
+    pub fn new_with_seed(seed: u64) -> XxState {
+        let mut state = XxState {
+            seed: seed,
+            v1: 0,
+            v2: 0,
+            v3: 0,
+            v4: 0,
+            length: 0,
+            buf: [0, ..32],
+            nbuf: 0,
+        };
+        state.reset();
+        state
+    }
+
+    /// Reset the state back to it's initial state.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.v1 = self.seed + PRIME1 + PRIME2;
+        self.v2 = self.seed + PRIME2;
+        self.v3 = self.seed;
+        self.v4 = self.seed - PRIME1;
+        self.length = 0;
+        self.nbuf = 0;
+    }
+
+    /// Return the computed hash.
+    pub fn result(&self) -> u64 {
+        let mut h = if self.length >= STRIPE as u64 {
+            let mut h = rotl(self.v1, 1) + rotl(self.v2, 7) +
+                        rotl(self.v3, 12) + rotl(self.v4, 18);
+            h = merge_round(h, self.v1);
+            h = merge_round(h, self.v2);
+            h = merge_round(h, self.v3);
+            merge_round(h, self.v4)
+        } else {
+            self.seed + PRIME5
+        };
+
+        h += self.length;
+
+        let tail = self.buf.slice_to(self.nbuf);
+        let mut i = 0;
+        while i + 8 <= tail.len() {
+            h ^= round(0, read_le_u64(tail, i));
+            h = rotl(h, 27) * PRIME1 + PRIME4;
+            i += 8;
+        }
+        if i + 4 <= tail.len() {
+            h ^= read_le_u32(tail, i) * PRIME1;
+            h = rotl(h, 23) * PRIME2 + PRIME3;
+            i += 4;
+        }
+        while i < tail.len() {
+            h ^= tail[i] as u64 * PRIME5;
+            h = rotl(h, 11) * PRIME1;
+            i += 1;
+        }
+
+        h ^= h >> 33;
+        h *= PRIME2;
+        h ^= h >> 29;
+        h *= PRIME3;
+        h ^ (h >> 32)
+    }
+
+    #[inline]
+    fn process_stripe(&mut self, stripe: &[u8]) {
+        self.v1 = round(self.v1, read_le_u64(stripe, 0));
+        self.v2 = round(self.v2, read_le_u64(stripe, 8));
+        self.v3 = round(self.v3, read_le_u64(stripe, 16));
+        self.v4 = round(self.v4, read_le_u64(stripe, 24));
+    }
+}
+
+impl Writer for XxState {
+    #[inline]
+    fn write(&mut self, msg: &[u8]) -> IoResult<()> {
+        let length = msg.len();
+        self.length += length as u64;
+
+        // Not enough for a whole stripe yet, just buffer it.
+        if self.nbuf + length < STRIPE {
+            copy_memory(self.buf.mut_slice_from(self.nbuf), msg);
+            self.nbuf += length;
+            return Ok(());
+        }
+
+        let mut i = 0;
+
+        // Top up and flush the buffered stripe first.
+        if self.nbuf != 0 {
+            i = STRIPE - self.nbuf;
+            copy_memory(self.buf.mut_slice_from(self.nbuf), msg.slice_to(i));
+            let stripe = self.buf;
+            self.process_stripe(stripe.as_slice());
+            self.nbuf = 0;
+        }
+
+        while i + STRIPE <= length {
+            self.process_stripe(msg.slice(i, i + STRIPE));
+            i += STRIPE;
+        }
+
+        copy_memory(self.buf.as_mut_slice(), msg.slice_from(i));
+        self.nbuf = length - i;
+
+        Ok(())
+    }
+}
+
+impl Clone for XxState {
+    #[inline]
+    fn clone(&self) -> XxState {
+        *self
+    }
+}
+
+impl Default for XxState {
+    #[inline]
+    fn default() -> XxState {
+        XxState::new()
+    }
+}
+
+/// `XxHasher` computes the xxHash64 algorithm from a stream of bytes.
+#[deriving(Clone)]
+pub struct XxHasher {
+    seed: u64,
+}
+
+impl XxHasher {
+    /// Create a `XxHasher` with a seed of zero.
+    #[inline]
+    pub fn new() -> XxHasher {
+        XxHasher::new_with_seed(0)
+    }
+
+    /// Create a `XxHasher` that is seeded with the provided value.
+    #[inline]
+    pub fn new_with_seed(seed: u64) -> XxHasher {
+        XxHasher { seed: seed }
+    }
+}
+
+impl Hasher<XxState> for XxHasher {
+    #[inline]
+    fn hash<T: Hash<XxState>>(&self, value: &T) -> u64 {
+        let mut state = XxState::new_with_seed(self.seed);
+        value.hash(&mut state);
+        state.result()
+    }
+}
+
+impl SeedableHasher for XxHasher {
+    /// xxHash only takes a 64-bit seed, so the two keys are folded together.
+    #[inline]
+    fn from_keys(key0: u64, key1: u64) -> XxHasher {
+        XxHasher::new_with_seed(key0 ^ rotl(key1, 32))
+    }
+}
+
+impl Default for XxHasher {
+    #[inline]
+    fn default() -> XxHasher {
+        XxHasher::new()
+    }
+}
+
+/// Hash a value using the xxHash64 algorithm.
+#[inline]
+pub fn hash<T: Hash<XxState>>(value: &T) -> u64 {
+    let mut state = XxState::new();
+    value.hash(&mut state);
+    state.result()
+}
+
+/// Hash a value using the xxHash64 algorithm with the provided seed.
+#[inline]
+pub fn hash_with_seed<T: Hash<XxState>>(seed: u64, value: &T) -> u64 {
+    let mut state = XxState::new_with_seed(seed);
+    value.hash(&mut state);
+    state.result()
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test;
+    use prelude::*;
+    use self::test::Bencher;
+
+    use super::super::{Hash, Hasher};
+    use super::{XxState, XxHasher, hash, hash_with_seed};
+
+    // Hash just the bytes of the slice, without length prefix
+    struct Bytes<'a>(&'a [u8]);
+
+    impl<'a, S: Writer> Hash<S> for Bytes<'a> {
+        #[allow(unused_must_use)]
+        fn hash(&self, state: &mut S) {
+            let Bytes(v) = *self;
+            state.write(v);
+        }
+    }
+
+    #[test]
+    fn test_xxhash64() {
+        assert_eq!(hash(&Bytes(&[])), 0xef46db3751d8e999);
+        assert_eq!(hash(&Bytes("a".as_bytes())), 0xd24ec4f1a98c6e5b);
+        assert_eq!(hash(&Bytes("abc".as_bytes())), 0x44bc2cf5ad770999);
+        assert_eq!(hash(&Bytes("Nobody inspects the spammish repetition".as_bytes())),
+                   0xfbcea83c8a378bf1);
+        assert_eq!(hash_with_seed(1, &Bytes("abc".as_bytes())), 0xbea9ca8199328908);
+
+        let bytes = Vec::from_fn(100, |i| i as u8);
+        assert_eq!(hash(&Bytes(bytes.as_slice())), 0x6ac1e58032166597);
+    }
+
+    #[test]
+    #[allow(unused_must_use)]
+    fn test_incremental() {
+        let bytes = Vec::from_fn(100, |i| i as u8);
+        let full = hash(&Bytes(bytes.as_slice()));
+
+        // Every split point must agree with the one-shot hash, covering
+        // partial stripes on both sides of the buffer boundary.
+        for split in range(0u, 101) {
+            let mut state = XxState::new();
+            state.write(bytes.slice_to(split));
+            state.write(bytes.slice_from(split));
+            assert_eq!(state.result(), full);
+        }
+
+        let mut state = XxState::new();
+        for b in bytes.iter() {
+            state.write_u8(*b);
+        }
+        assert_eq!(state.result(), full);
+
+        state.reset();
+        assert_eq!(state.result(), 0xef46db3751d8e999);
+    }
+
+    #[test]
+    fn test_seeds() {
+        let plain = XxHasher::new();
+        let seeded = XxHasher::new_with_seed(1);
+
+        assert_eq!(plain.hash(&5u64), hash(&5u64));
+        assert_eq!(seeded.hash(&5u64), hash_with_seed(1, &5u64));
+        assert!(plain.hash(&5u64) != seeded.hash(&5u64));
+    }
+
+    #[test]
+    fn test_hash_no_concat_alias() {
+        let s = ("aa", "bb");
+        let t = ("aabb", "");
+        let u = ("a", "abb");
+
+        assert!(hash(&s) != hash(&t) && hash(&s) != hash(&u));
+    }
+
+    #[bench]
+    fn bench_str_under_8_bytes(b: &mut Bencher) {
+        let s = "foo";
+        b.iter(|| {
+            hash(&s)
+        })
+    }
+
+    #[bench]
+    fn bench_str_of_8_bytes(b: &mut Bencher) {
+        let s = "foobar78";
+        b.iter(|| {
+            hash(&s)
+        })
+    }
+
+    #[bench]
+    fn bench_str_over_8_bytes(b: &mut Bencher) {
+        let s = "foobarbaz0";
+        b.iter(|| {
+            hash(&s)
+        })
+    }
+
+    #[bench]
+    fn bench_long_str(b: &mut Bencher) {
+        let s = "Lorem ipsum dolor sit amet, consectetur adipisicing elit, sed do eiusmod tempor \
+incididunt ut labore et dolore magna aliqua. Ut enim ad minim veniam, quis nostrud \
+exercitation ullamco laboris nisi ut aliquip ex ea commodo consequat. Duis aute \
+irure dolor in reprehenderit in voluptate velit esse cillum dolore eu fugiat nulla \
+pariatur. Excepteur sint occaecat cupidatat non proident, sunt in culpa qui \
+officia deserunt mollit anim id est laborum.";
+        b.iter(|| {
+            hash(&s)
+        })
+    }
+
+    #[bench]
+    fn bench_u64(b: &mut Bencher) {
+        let u = 16262950014981195938u64;
+        b.iter(|| {
+            hash(&u)
+        })
+    }
+
+    #[bench]
+    fn bench_u32(b: &mut Bencher) {
+        let u = 162629500u32;
+        b.iter(|| {
+            hash(&u)
+        })
+    }
+}
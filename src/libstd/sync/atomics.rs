@@ -15,7 +15,8 @@
 //! types.
 //!
 //! This module defines atomic versions of a select number of primitive
-//! types, including `AtomicBool`, `AtomicInt`, `AtomicUint`, and `AtomicOption`.
+//! types, including `AtomicBool`, `AtomicInt`, `AtomicUint`, `AtomicI64`,
+//! `AtomicU64` and `AtomicOption`.
 //! Atomic types present operations that, when used correctly, synchronize
 //! updates between threads.
 //!
@@ -109,9 +110,12 @@
 
 use intrinsics;
 use cast;
+use cmp::Eq;
 use std::kinds::marker;
+use kinds::Copy;
 use option::{Option,Some,None};
 use ops::Drop;
+use result::{Result, Ok, Err};
 use ty::Unsafe;
 
 /// An atomic boolean type.
@@ -132,6 +136,22 @@ pub struct AtomicUint {
     nocopy: marker::NoCopy
 }
 
+/// A signed 64-bit atomic integer type, available on all targets
+///
+/// On targets without a 64-bit word the operations are emulated with locks.
+pub struct AtomicI64 {
+    v: Unsafe<i64>,
+    nocopy: marker::NoCopy
+}
+
+/// An unsigned 64-bit atomic integer type, available on all targets
+///
+/// On targets without a 64-bit word the operations are emulated with locks.
+pub struct AtomicU64 {
+    v: Unsafe<u64>,
+    nocopy: marker::NoCopy
+}
+
 /// An unsafe atomic pointer. Only supports basic atomic operations
 pub struct AtomicPtr<T> {
     p: Unsafe<uint>,
@@ -189,6 +209,14 @@ pub static INIT_ATOMIC_INT  : AtomicInt  = AtomicInt  { v: Unsafe{value: 0,
 pub static INIT_ATOMIC_UINT : AtomicUint = AtomicUint { v: Unsafe{value: 0,
                                                                   marker1: marker::InvariantType},
                                                         nocopy: marker::NoCopy };
+/// An `AtomicI64` initialized to `0`
+pub static INIT_ATOMIC_I64  : AtomicI64  = AtomicI64  { v: Unsafe{value: 0,
+                                                                  marker1: marker::InvariantType},
+                                                        nocopy: marker::NoCopy };
+/// An `AtomicU64` initialized to `0`
+pub static INIT_ATOMIC_U64  : AtomicU64  = AtomicU64  { v: Unsafe{value: 0,
+                                                                  marker1: marker::InvariantType},
+                                                        nocopy: marker::NoCopy };
 
 // NB: Needs to be -1 (0b11111111...) to make fetch_nand work correctly
 static UINT_TRUE: uint = -1;
@@ -395,6 +423,36 @@ impl AtomicBool {
 
         unsafe { atomic_xor(self.v.get(), val, order) > 0 }
     }
+
+    /// Store `new` if the current value is `old`
+    ///
+    /// Returns `Ok` of the previous value if the exchange happened and `Err`
+    /// of the current value otherwise. `success` is the ordering of the
+    /// read-modify-write performed on success and `failure` that of the load
+    /// performed on failure; `failure` may not be `Release` or `AcqRel`.
+    #[inline]
+    pub fn compare_exchange(&self, old: bool, new: bool, success: Ordering,
+                            failure: Ordering) -> Result<bool, bool> {
+        let old = if old { UINT_TRUE } else { 0 };
+        let new = if new { UINT_TRUE } else { 0 };
+
+        match unsafe { atomic_compare_exchange(self.v.get(), old, new,
+                                               success, failure) } {
+            Ok(v) => Ok(v > 0),
+            Err(v) => Err(v > 0),
+        }
+    }
+
+    /// Store `new` if the current value is `old`, possibly failing spuriously
+    ///
+    /// Like `compare_exchange`, but may fail even if the current value is
+    /// `old`, which allows for more efficient code on some platforms when
+    /// called in a loop.
+    #[inline]
+    pub fn compare_exchange_weak(&self, old: bool, new: bool, success: Ordering,
+                                 failure: Ordering) -> Result<bool, bool> {
+        self.compare_exchange(old, new, success, failure)
+    }
 }
 
 impl AtomicInt {
@@ -507,6 +565,71 @@ impl AtomicInt {
     pub fn fetch_xor(&self, val: int, order: Ordering) -> int {
         unsafe { atomic_xor(self.v.get(), val, order) }
     }
+
+    /// Store `new` if the current value is `old`
+    ///
+    /// Returns `Ok` of the previous value if the exchange happened and `Err`
+    /// of the current value otherwise. `success` is the ordering of the
+    /// read-modify-write performed on success and `failure` that of the load
+    /// performed on failure; `failure` may not be `Release` or `AcqRel`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomics::{AtomicInt, AcqRel, Acquire};
+    ///
+    /// let foo = AtomicInt::new(5);
+    /// assert_eq!(foo.compare_exchange(5, 10, AcqRel, Acquire), Ok(5));
+    /// assert_eq!(foo.compare_exchange(5, 15, AcqRel, Acquire), Err(10));
+    /// ```
+    #[inline]
+    pub fn compare_exchange(&self, old: int, new: int, success: Ordering,
+                            failure: Ordering) -> Result<int, int> {
+        unsafe { atomic_compare_exchange(self.v.get(), old, new, success, failure) }
+    }
+
+    /// Store `new` if the current value is `old`, possibly failing spuriously
+    ///
+    /// Like `compare_exchange`, but may fail even if the current value is
+    /// `old`, which allows for more efficient code on some platforms when
+    /// called in a loop.
+    #[inline]
+    pub fn compare_exchange_weak(&self, old: int, new: int, success: Ordering,
+                                 failure: Ordering) -> Result<int, int> {
+        self.compare_exchange(old, new, success, failure)
+    }
+
+    /// Store the maximum of the current value and `val`, returning the previous
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomics::{AtomicInt, SeqCst};
+    ///
+    /// let foo = AtomicInt::new(-2);
+    /// assert_eq!(-2, foo.fetch_max(-7, SeqCst));
+    /// assert_eq!(-2, foo.load(SeqCst));
+    /// ```
+    #[inline]
+    pub fn fetch_max(&self, val: int, order: Ordering) -> int {
+        unsafe { atomic_max(self.v.get(), val, order) }
+    }
+
+    /// Store the minimum of the current value and `val`, returning the previous
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomics::{AtomicInt, SeqCst};
+    ///
+    /// let foo = AtomicInt::new(-2);
+    /// assert_eq!(-2, foo.fetch_min(-7, SeqCst));
+    /// assert_eq!(-7, foo.load(SeqCst));
+    /// ```
+    #[inline]
+    pub fn fetch_min(&self, val: int, order: Ordering) -> int {
+        unsafe { atomic_min(self.v.get(), val, order) }
+    }
 }
 
 impl AtomicUint {
@@ -619,6 +742,295 @@ impl AtomicUint {
     pub fn fetch_xor(&self, val: uint, order: Ordering) -> uint {
         unsafe { atomic_xor(self.v.get(), val, order) }
     }
+
+    /// Store `new` if the current value is `old`
+    ///
+    /// Returns `Ok` of the previous value if the exchange happened and `Err`
+    /// of the current value otherwise. `success` is the ordering of the
+    /// read-modify-write performed on success and `failure` that of the load
+    /// performed on failure; `failure` may not be `Release` or `AcqRel`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomics::{AtomicUint, AcqRel, Acquire};
+    ///
+    /// let foo = AtomicUint::new(5);
+    /// assert_eq!(foo.compare_exchange(5, 10, AcqRel, Acquire), Ok(5));
+    /// assert_eq!(foo.compare_exchange(5, 15, AcqRel, Acquire), Err(10));
+    /// ```
+    #[inline]
+    pub fn compare_exchange(&self, old: uint, new: uint, success: Ordering,
+                            failure: Ordering) -> Result<uint, uint> {
+        unsafe { atomic_compare_exchange(self.v.get(), old, new, success, failure) }
+    }
+
+    /// Store `new` if the current value is `old`, possibly failing spuriously
+    ///
+    /// Like `compare_exchange`, but may fail even if the current value is
+    /// `old`, which allows for more efficient code on some platforms when
+    /// called in a loop.
+    #[inline]
+    pub fn compare_exchange_weak(&self, old: uint, new: uint, success: Ordering,
+                                 failure: Ordering) -> Result<uint, uint> {
+        self.compare_exchange(old, new, success, failure)
+    }
+
+    /// Store the maximum of the current value and `val`, returning the previous
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomics::{AtomicUint, SeqCst};
+    ///
+    /// let foo = AtomicUint::new(10);
+    /// assert_eq!(10, foo.fetch_max(3, SeqCst));
+    /// assert_eq!(10, foo.load(SeqCst));
+    /// ```
+    #[inline]
+    pub fn fetch_max(&self, val: uint, order: Ordering) -> uint {
+        unsafe { atomic_umax(self.v.get(), val, order) }
+    }
+
+    /// Store the minimum of the current value and `val`, returning the previous
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomics::{AtomicUint, SeqCst};
+    ///
+    /// let foo = AtomicUint::new(10);
+    /// assert_eq!(10, foo.fetch_min(3, SeqCst));
+    /// assert_eq!(3, foo.load(SeqCst));
+    /// ```
+    #[inline]
+    pub fn fetch_min(&self, val: uint, order: Ordering) -> uint {
+        unsafe { atomic_umin(self.v.get(), val, order) }
+    }
+}
+
+impl AtomicI64 {
+    /// Create a new `AtomicI64`
+    pub fn new(v: i64) -> AtomicI64 {
+        AtomicI64 { v: Unsafe::new(v), nocopy: marker::NoCopy }
+    }
+
+    /// Load the value
+    #[inline]
+    pub fn load(&self, order: Ordering) -> i64 {
+        unsafe { wide::load(self.v.get(), order) }
+    }
+
+    /// Store the value
+    #[inline]
+    pub fn store(&self, val: i64, order: Ordering) {
+        unsafe { wide::store(self.v.get(), val, order); }
+    }
+
+    /// Store a value, returning the old value
+    #[inline]
+    pub fn swap(&self, val: i64, order: Ordering) -> i64 {
+        unsafe { wide::swap(self.v.get(), val, order) }
+    }
+
+    /// If the current value is the same as expected, store a new value
+    ///
+    /// Compare the current value with `old`; if they are the same then
+    /// replace the current value with `new`. Return the previous value.
+    /// If the return value is equal to `old` then the value was updated.
+    #[inline]
+    pub fn compare_and_swap(&self, old: i64, new: i64, order: Ordering) -> i64 {
+        unsafe { wide::compare_and_swap(self.v.get(), old, new, order) }
+    }
+
+    /// Store `new` if the current value is `old`
+    ///
+    /// Returns `Ok` of the previous value if the exchange happened and `Err`
+    /// of the current value otherwise. `success` is the ordering of the
+    /// read-modify-write performed on success and `failure` that of the load
+    /// performed on failure; `failure` may not be `Release` or `AcqRel`.
+    #[inline]
+    pub fn compare_exchange(&self, old: i64, new: i64, success: Ordering,
+                            failure: Ordering) -> Result<i64, i64> {
+        let order = cmpxchg_order(success, failure);
+        let prev = unsafe { wide::compare_and_swap(self.v.get(), old, new, order) };
+        if prev == old { Ok(prev) } else { Err(prev) }
+    }
+
+    /// Store `new` if the current value is `old`, possibly failing spuriously
+    ///
+    /// Like `compare_exchange`, but may fail even if the current value is
+    /// `old`, which allows for more efficient code on some platforms when
+    /// called in a loop.
+    #[inline]
+    pub fn compare_exchange_weak(&self, old: i64, new: i64, success: Ordering,
+                                 failure: Ordering) -> Result<i64, i64> {
+        self.compare_exchange(old, new, success, failure)
+    }
+
+    /// Add to the current value, returning the previous
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomics::{AtomicI64, SeqCst};
+    ///
+    /// let foo = AtomicI64::new(1 << 40);
+    /// assert_eq!(1 << 40, foo.fetch_add(1, SeqCst));
+    /// assert_eq!((1 << 40) + 1, foo.load(SeqCst));
+    /// ```
+    #[inline]
+    pub fn fetch_add(&self, val: i64, order: Ordering) -> i64 {
+        unsafe { wide::add(self.v.get(), val, order) }
+    }
+
+    /// Subtract from the current value, returning the previous
+    #[inline]
+    pub fn fetch_sub(&self, val: i64, order: Ordering) -> i64 {
+        unsafe { wide::sub(self.v.get(), val, order) }
+    }
+
+    /// Bitwise and with the current value, returning the previous
+    #[inline]
+    pub fn fetch_and(&self, val: i64, order: Ordering) -> i64 {
+        unsafe { wide::and(self.v.get(), val, order) }
+    }
+
+    /// Bitwise or with the current value, returning the previous
+    #[inline]
+    pub fn fetch_or(&self, val: i64, order: Ordering) -> i64 {
+        unsafe { wide::or(self.v.get(), val, order) }
+    }
+
+    /// Bitwise xor with the current value, returning the previous
+    #[inline]
+    pub fn fetch_xor(&self, val: i64, order: Ordering) -> i64 {
+        unsafe { wide::xor(self.v.get(), val, order) }
+    }
+
+    /// Store the maximum of the current value and `val`, returning the previous
+    #[inline]
+    pub fn fetch_max(&self, val: i64, order: Ordering) -> i64 {
+        unsafe { wide::max(self.v.get(), val, order) }
+    }
+
+    /// Store the minimum of the current value and `val`, returning the previous
+    #[inline]
+    pub fn fetch_min(&self, val: i64, order: Ordering) -> i64 {
+        unsafe { wide::min(self.v.get(), val, order) }
+    }
+}
+
+impl AtomicU64 {
+    /// Create a new `AtomicU64`
+    pub fn new(v: u64) -> AtomicU64 {
+        AtomicU64 { v: Unsafe::new(v), nocopy: marker::NoCopy }
+    }
+
+    /// Load the value
+    #[inline]
+    pub fn load(&self, order: Ordering) -> u64 {
+        unsafe { wide::load(self.v.get(), order) }
+    }
+
+    /// Store the value
+    #[inline]
+    pub fn store(&self, val: u64, order: Ordering) {
+        unsafe { wide::store(self.v.get(), val, order); }
+    }
+
+    /// Store a value, returning the old value
+    #[inline]
+    pub fn swap(&self, val: u64, order: Ordering) -> u64 {
+        unsafe { wide::swap(self.v.get(), val, order) }
+    }
+
+    /// If the current value is the same as expected, store a new value
+    ///
+    /// Compare the current value with `old`; if they are the same then
+    /// replace the current value with `new`. Return the previous value.
+    /// If the return value is equal to `old` then the value was updated.
+    #[inline]
+    pub fn compare_and_swap(&self, old: u64, new: u64, order: Ordering) -> u64 {
+        unsafe { wide::compare_and_swap(self.v.get(), old, new, order) }
+    }
+
+    /// Store `new` if the current value is `old`
+    ///
+    /// Returns `Ok` of the previous value if the exchange happened and `Err`
+    /// of the current value otherwise. `success` is the ordering of the
+    /// read-modify-write performed on success and `failure` that of the load
+    /// performed on failure; `failure` may not be `Release` or `AcqRel`.
+    #[inline]
+    pub fn compare_exchange(&self, old: u64, new: u64, success: Ordering,
+                            failure: Ordering) -> Result<u64, u64> {
+        let order = cmpxchg_order(success, failure);
+        let prev = unsafe { wide::compare_and_swap(self.v.get(), old, new, order) };
+        if prev == old { Ok(prev) } else { Err(prev) }
+    }
+
+    /// Store `new` if the current value is `old`, possibly failing spuriously
+    ///
+    /// Like `compare_exchange`, but may fail even if the current value is
+    /// `old`, which allows for more efficient code on some platforms when
+    /// called in a loop.
+    #[inline]
+    pub fn compare_exchange_weak(&self, old: u64, new: u64, success: Ordering,
+                                 failure: Ordering) -> Result<u64, u64> {
+        self.compare_exchange(old, new, success, failure)
+    }
+
+    /// Add to the current value, returning the previous
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::atomics::{AtomicU64, SeqCst};
+    ///
+    /// let foo = AtomicU64::new(1 << 40);
+    /// assert_eq!(1 << 40, foo.fetch_add(1, SeqCst));
+    /// assert_eq!((1 << 40) + 1, foo.load(SeqCst));
+    /// ```
+    #[inline]
+    pub fn fetch_add(&self, val: u64, order: Ordering) -> u64 {
+        unsafe { wide::add(self.v.get(), val, order) }
+    }
+
+    /// Subtract from the current value, returning the previous
+    #[inline]
+    pub fn fetch_sub(&self, val: u64, order: Ordering) -> u64 {
+        unsafe { wide::sub(self.v.get(), val, order) }
+    }
+
+    /// Bitwise and with the current value, returning the previous
+    #[inline]
+    pub fn fetch_and(&self, val: u64, order: Ordering) -> u64 {
+        unsafe { wide::and(self.v.get(), val, order) }
+    }
+
+    /// Bitwise or with the current value, returning the previous
+    #[inline]
+    pub fn fetch_or(&self, val: u64, order: Ordering) -> u64 {
+        unsafe { wide::or(self.v.get(), val, order) }
+    }
+
+    /// Bitwise xor with the current value, returning the previous
+    #[inline]
+    pub fn fetch_xor(&self, val: u64, order: Ordering) -> u64 {
+        unsafe { wide::xor(self.v.get(), val, order) }
+    }
+
+    /// Store the maximum of the current value and `val`, returning the previous
+    #[inline]
+    pub fn fetch_max(&self, val: u64, order: Ordering) -> u64 {
+        unsafe { wide::umax(self.v.get(), val, order) }
+    }
+
+    /// Store the minimum of the current value and `val`, returning the previous
+    #[inline]
+    pub fn fetch_min(&self, val: u64, order: Ordering) -> u64 {
+        unsafe { wide::umin(self.v.get(), val, order) }
+    }
 }
 
 impl<T> AtomicPtr<T> {
@@ -659,6 +1071,35 @@ impl<T> AtomicPtr<T> {
                                     new as uint, order) as *mut T
         }
     }
+
+    /// Store `new` if the current value is `old`
+    ///
+    /// Returns `Ok` of the previous value if the exchange happened and `Err`
+    /// of the current value otherwise. `success` is the ordering of the
+    /// read-modify-write performed on success and `failure` that of the load
+    /// performed on failure; `failure` may not be `Release` or `AcqRel`.
+    #[inline]
+    pub fn compare_exchange(&self, old: *mut T, new: *mut T, success: Ordering,
+                            failure: Ordering) -> Result<*mut T, *mut T> {
+        unsafe {
+            match atomic_compare_exchange(self.p.get(), old as uint, new as uint,
+                                          success, failure) {
+                Ok(p) => Ok(p as *mut T),
+                Err(p) => Err(p as *mut T),
+            }
+        }
+    }
+
+    /// Store `new` if the current value is `old`, possibly failing spuriously
+    ///
+    /// Like `compare_exchange`, but may fail even if the current value is
+    /// `old`, which allows for more efficient code on some platforms when
+    /// called in a loop.
+    #[inline]
+    pub fn compare_exchange_weak(&self, old: *mut T, new: *mut T, success: Ordering,
+                                 failure: Ordering) -> Result<*mut T, *mut T> {
+        self.compare_exchange(old, new, success, failure)
+    }
 }
 
 impl<T> AtomicOption<T> {
@@ -837,6 +1278,214 @@ unsafe fn atomic_xor<T>(dst: *mut T, val: T, order: Ordering) -> T {
     }
 }
 
+#[inline]
+unsafe fn atomic_max<T>(dst: *mut T, val: T, order: Ordering) -> T {
+    match order {
+        Acquire => intrinsics::atomic_max_acq(dst, val),
+        Release => intrinsics::atomic_max_rel(dst, val),
+        AcqRel  => intrinsics::atomic_max_acqrel(dst, val),
+        Relaxed => intrinsics::atomic_max_relaxed(dst, val),
+        _       => intrinsics::atomic_max(dst, val)
+    }
+}
+
+#[inline]
+unsafe fn atomic_min<T>(dst: *mut T, val: T, order: Ordering) -> T {
+    match order {
+        Acquire => intrinsics::atomic_min_acq(dst, val),
+        Release => intrinsics::atomic_min_rel(dst, val),
+        AcqRel  => intrinsics::atomic_min_acqrel(dst, val),
+        Relaxed => intrinsics::atomic_min_relaxed(dst, val),
+        _       => intrinsics::atomic_min(dst, val)
+    }
+}
+
+#[inline]
+unsafe fn atomic_umax<T>(dst: *mut T, val: T, order: Ordering) -> T {
+    match order {
+        Acquire => intrinsics::atomic_umax_acq(dst, val),
+        Release => intrinsics::atomic_umax_rel(dst, val),
+        AcqRel  => intrinsics::atomic_umax_acqrel(dst, val),
+        Relaxed => intrinsics::atomic_umax_relaxed(dst, val),
+        _       => intrinsics::atomic_umax(dst, val)
+    }
+}
+
+#[inline]
+unsafe fn atomic_umin<T>(dst: *mut T, val: T, order: Ordering) -> T {
+    match order {
+        Acquire => intrinsics::atomic_umin_acq(dst, val),
+        Release => intrinsics::atomic_umin_rel(dst, val),
+        AcqRel  => intrinsics::atomic_umin_acqrel(dst, val),
+        Relaxed => intrinsics::atomic_umin_relaxed(dst, val),
+        _       => intrinsics::atomic_umin(dst, val)
+    }
+}
+
+/// Collapses the success and failure orderings of a compare-exchange into the
+/// single ordering understood by the `cmpxchg` intrinsics, which must be at
+/// least as strong as both of them.
+///
+/// A failed compare-exchange performs no store, so `Release` and `AcqRel`
+/// are meaningless as failure orderings and are rejected.
+#[inline]
+fn cmpxchg_order(success: Ordering, failure: Ordering) -> Ordering {
+    match (success, failure) {
+        (_, Release) => fail!("there is no such thing as a release failure ordering"),
+        (_, AcqRel) => fail!("there is no such thing as an acquire/release \
+                              failure ordering"),
+        (SeqCst, _) | (_, SeqCst) => SeqCst,
+        (Release, Acquire) => AcqRel,
+        (Relaxed, Acquire) => Acquire,
+        (success, _) => success,
+    }
+}
+
+/// Returns `Ok` of the previous value if it was `old` and has been replaced,
+/// or `Err` of the current value otherwise.
+///
+/// There is no weak compare-exchange intrinsic yet, so the weak variants of
+/// the public API are implemented by this strong version for now.
+#[inline]
+unsafe fn atomic_compare_exchange<T: Eq + Copy>(dst: *mut T, old: T, new: T,
+                                                success: Ordering,
+                                                failure: Ordering) -> Result<T, T> {
+    let prev = atomic_compare_and_swap(dst, old, new, cmpxchg_order(success, failure));
+    if prev == old { Ok(prev) } else { Err(prev) }
+}
+
+// Operations on 64-bit values. Targets with a 64-bit word use the atomic
+// intrinsics directly; elsewhere 64-bit atomics are not available everywhere
+// (nor are 64-bit fields guaranteed to be 8-byte aligned), so each operation
+// is instead serialized through a small table of spinlocks keyed by address.
+#[cfg(target_word_size = "64")]
+mod wide {
+    use super::{Ordering, atomic_load, atomic_store, atomic_swap,
+                atomic_compare_and_swap, atomic_add, atomic_sub, atomic_and,
+                atomic_or, atomic_xor, atomic_max, atomic_min, atomic_umax,
+                atomic_umin};
+
+    #[inline]
+    pub unsafe fn load<T>(dst: *mut T, order: Ordering) -> T {
+        atomic_load(dst as *T, order)
+    }
+    #[inline]
+    pub unsafe fn store<T>(dst: *mut T, val: T, order: Ordering) {
+        atomic_store(dst, val, order)
+    }
+    #[inline]
+    pub unsafe fn swap<T>(dst: *mut T, val: T, order: Ordering) -> T {
+        atomic_swap(dst, val, order)
+    }
+    #[inline]
+    pub unsafe fn compare_and_swap<T>(dst: *mut T, old: T, new: T,
+                                      order: Ordering) -> T {
+        atomic_compare_and_swap(dst, old, new, order)
+    }
+    #[inline]
+    pub unsafe fn add<T>(dst: *mut T, val: T, order: Ordering) -> T {
+        atomic_add(dst, val, order)
+    }
+    #[inline]
+    pub unsafe fn sub<T>(dst: *mut T, val: T, order: Ordering) -> T {
+        atomic_sub(dst, val, order)
+    }
+    #[inline]
+    pub unsafe fn and<T>(dst: *mut T, val: T, order: Ordering) -> T {
+        atomic_and(dst, val, order)
+    }
+    #[inline]
+    pub unsafe fn or<T>(dst: *mut T, val: T, order: Ordering) -> T {
+        atomic_or(dst, val, order)
+    }
+    #[inline]
+    pub unsafe fn xor<T>(dst: *mut T, val: T, order: Ordering) -> T {
+        atomic_xor(dst, val, order)
+    }
+    #[inline]
+    pub unsafe fn max<T>(dst: *mut T, val: T, order: Ordering) -> T {
+        atomic_max(dst, val, order)
+    }
+    #[inline]
+    pub unsafe fn min<T>(dst: *mut T, val: T, order: Ordering) -> T {
+        atomic_min(dst, val, order)
+    }
+    #[inline]
+    pub unsafe fn umax<T>(dst: *mut T, val: T, order: Ordering) -> T {
+        atomic_umax(dst, val, order)
+    }
+    #[inline]
+    pub unsafe fn umin<T>(dst: *mut T, val: T, order: Ordering) -> T {
+        atomic_umin(dst, val, order)
+    }
+}
+
+#[cfg(not(target_word_size = "64"))]
+mod wide {
+    use cmp::{Eq, Ord};
+    use kinds::Copy;
+    use ops::{Add, Sub, BitAnd, BitOr, BitXor};
+    use super::{Ordering, SeqCst, atomic_compare_and_swap, atomic_store};
+
+    static NLOCKS: uint = 64;
+    static mut LOCKS: [uint, ..NLOCKS] = [0, ..NLOCKS];
+
+    /// Replaces `*dst` with `f(*dst)` under the spinlock guarding `dst`,
+    /// returning the previous value. The critical sections are only a few
+    /// instructions long, so spinning is cheaper than blocking.
+    unsafe fn locked<T: Copy>(dst: *mut T, f: |T| -> T) -> T {
+        let lock = &mut LOCKS[(dst as uint >> 3) % NLOCKS] as *mut uint;
+        while atomic_compare_and_swap(lock, 0, 1, SeqCst) != 0 {}
+        let old = *dst;
+        *dst = f(old);
+        atomic_store(lock, 0, SeqCst);
+        old
+    }
+
+    pub unsafe fn load<T: Copy>(dst: *mut T, _: Ordering) -> T {
+        locked(dst, |v| v)
+    }
+    pub unsafe fn store<T: Copy>(dst: *mut T, val: T, _: Ordering) {
+        locked(dst, |_| val);
+    }
+    pub unsafe fn swap<T: Copy>(dst: *mut T, val: T, _: Ordering) -> T {
+        locked(dst, |_| val)
+    }
+    pub unsafe fn compare_and_swap<T: Copy + Eq>(dst: *mut T, old: T, new: T,
+                                                 _: Ordering) -> T {
+        locked(dst, |v| if v == old { new } else { v })
+    }
+    pub unsafe fn add<T: Copy + Add<T, T>>(dst: *mut T, val: T, _: Ordering) -> T {
+        locked(dst, |v| v + val)
+    }
+    pub unsafe fn sub<T: Copy + Sub<T, T>>(dst: *mut T, val: T, _: Ordering) -> T {
+        locked(dst, |v| v - val)
+    }
+    pub unsafe fn and<T: Copy + BitAnd<T, T>>(dst: *mut T, val: T, _: Ordering) -> T {
+        locked(dst, |v| v & val)
+    }
+    pub unsafe fn or<T: Copy + BitOr<T, T>>(dst: *mut T, val: T, _: Ordering) -> T {
+        locked(dst, |v| v | val)
+    }
+    pub unsafe fn xor<T: Copy + BitXor<T, T>>(dst: *mut T, val: T, _: Ordering) -> T {
+        locked(dst, |v| v ^ val)
+    }
+    pub unsafe fn max<T: Copy + Ord>(dst: *mut T, val: T, _: Ordering) -> T {
+        locked(dst, |v| if val > v { val } else { v })
+    }
+    pub unsafe fn min<T: Copy + Ord>(dst: *mut T, val: T, _: Ordering) -> T {
+        locked(dst, |v| if val < v { val } else { v })
+    }
+    // The signedness of the comparison comes from `T` itself here.
+    pub unsafe fn umax<T: Copy + Ord>(dst: *mut T, val: T, order: Ordering) -> T {
+        max(dst, val, order)
+    }
+    pub unsafe fn umin<T: Copy + Ord>(dst: *mut T, val: T, order: Ordering) -> T {
+        min(dst, val, order)
+    }
+}
+
+
 
 /// An atomic fence.
 ///
@@ -874,6 +1523,7 @@ pub fn fence(order: Ordering) {
 #[cfg(test)]
 mod test {
     use option::*;
+    use result::{Ok, Err};
     use super::*;
 
     #[test]
@@ -1004,4 +1654,108 @@ mod test {
             super::atomic_store(&mut slot, 2, SeqCst);
         }
     }
+
+    #[test]
+    fn int_max_min() {
+        let x = AtomicInt::new(-5);
+        assert_eq!(x.fetch_max(3, SeqCst), -5);
+        assert_eq!(x.fetch_max(-10, SeqCst), 3);
+        assert_eq!(x.load(SeqCst), 3);
+        assert_eq!(x.fetch_min(-7, SeqCst), 3);
+        assert_eq!(x.load(SeqCst), -7);
+    }
+
+    #[test]
+    fn uint_max_min() {
+        let x = AtomicUint::new(10);
+        assert_eq!(x.fetch_max(-1, SeqCst), 10);
+        assert_eq!(x.load(SeqCst), -1);
+        assert_eq!(x.fetch_min(3, SeqCst), -1);
+        assert_eq!(x.load(SeqCst), 3);
+    }
+
+    #[test]
+    fn compare_exchange() {
+        let x = AtomicUint::new(1);
+        assert_eq!(x.compare_exchange(1, 2, AcqRel, Acquire), Ok(1));
+        assert_eq!(x.compare_exchange(1, 3, AcqRel, Acquire), Err(2));
+        assert_eq!(x.load(SeqCst), 2);
+
+        let b = AtomicBool::new(false);
+        assert_eq!(b.compare_exchange(false, true, SeqCst, Relaxed), Ok(false));
+        assert_eq!(b.compare_exchange(false, true, SeqCst, Relaxed), Err(true));
+
+        let i = AtomicInt::new(0);
+        let mut cur = i.load(Relaxed);
+        loop {
+            match i.compare_exchange_weak(cur, cur - 4, Release, Relaxed) {
+                Ok(..) => break,
+                Err(v) => cur = v,
+            }
+        }
+        assert_eq!(i.load(SeqCst), -4);
+    }
+
+    #[test]
+    fn compare_exchange_ptr() {
+        let mut a = 1;
+        let mut b = 2;
+        let pa = &mut a as *mut int;
+        let pb = &mut b as *mut int;
+        let p = AtomicPtr::new(pa);
+        assert_eq!(p.compare_exchange(pb, pb, SeqCst, SeqCst), Err(pa));
+        assert_eq!(p.compare_exchange(pa, pb, SeqCst, Acquire), Ok(pa));
+        assert_eq!(p.load(SeqCst), pb);
+    }
+
+    #[test] #[should_fail]
+    fn compare_exchange_release_failure() {
+        let x = AtomicUint::new(0);
+        let _ = x.compare_exchange(0, 1, SeqCst, Release);
+    }
+
+    #[test]
+    fn u64_() {
+        let x = AtomicU64::new(1 << 40);
+        assert_eq!(x.fetch_add(1 << 40, SeqCst), 1 << 40);
+        assert_eq!(x.load(SeqCst), 1 << 41);
+        assert_eq!(x.swap(0xffff_ffff_ffff_ffff, SeqCst), 1 << 41);
+        assert_eq!(x.fetch_and(0xf0f0_0000_0000_0000, SeqCst), 0xffff_ffff_ffff_ffff);
+        assert_eq!(x.fetch_xor(0xff00_0000_0000_0001, SeqCst), 0xf0f0_0000_0000_0000);
+        assert_eq!(x.load(SeqCst), 0x0ff0_0000_0000_0001);
+        assert_eq!(x.fetch_min(7, SeqCst), 0x0ff0_0000_0000_0001);
+        assert_eq!(x.fetch_max(1 << 63, SeqCst), 7);
+        assert_eq!(x.compare_exchange(1 << 63, 5, SeqCst, SeqCst), Ok(1 << 63));
+        assert_eq!(x.compare_and_swap(1, 6, SeqCst), 5);
+        assert_eq!(x.fetch_sub(5, SeqCst), 5);
+        assert_eq!(x.fetch_or(1 << 50, SeqCst), 0);
+        assert_eq!(x.load(SeqCst), 1 << 50);
+    }
+
+    #[test]
+    fn i64_() {
+        let x = AtomicI64::new(-(1 << 40));
+        assert_eq!(x.fetch_sub(1 << 40, SeqCst), -(1 << 40));
+        assert_eq!(x.load(SeqCst), -(1 << 41));
+        assert_eq!(x.fetch_max(-1, SeqCst), -(1 << 41));
+        assert_eq!(x.fetch_min(-(1 << 50), SeqCst), -1);
+        assert_eq!(x.fetch_add(1 << 50, SeqCst), -(1 << 50));
+        assert_eq!(x.compare_exchange_weak(1, 2, SeqCst, SeqCst), Err(0));
+        x.store(0x7fff_ffff_ffff_ffff, SeqCst);
+        assert_eq!(x.fetch_and(0xff, SeqCst), 0x7fff_ffff_ffff_ffff);
+        assert_eq!(x.fetch_or(0x100, SeqCst), 0xff);
+        assert_eq!(x.fetch_xor(0x1ff, SeqCst), 0x1ff);
+        assert_eq!(x.load(SeqCst), 0);
+    }
+
+    static mut S_I64 : AtomicI64 = INIT_ATOMIC_I64;
+    static mut S_U64 : AtomicU64 = INIT_ATOMIC_U64;
+
+    #[test]
+    fn static_init_64() {
+        unsafe {
+            assert!(S_I64.load(SeqCst) == 0);
+            assert!(S_U64.load(SeqCst) == 0);
+        }
+    }
 }
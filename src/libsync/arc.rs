@@ -17,6 +17,7 @@ use std::cast;
 use std::ptr;
 use std::rt::global_heap;
 use std::sync::atomics;
+use std::task;
 
 /// An atomically reference counted wrapper for shared state.
 ///
@@ -245,10 +246,150 @@ impl<T: Share + Send> Drop for Weak<T> {
     }
 }
 
+/// A slot holding an `Arc` which may be atomically replaced while other tasks
+/// read from it.
+///
+/// Loading from the slot never blocks and is cheap enough to be done on every
+/// access, which makes this suitable for read-mostly shared state such as
+/// configuration that is occasionally swapped out wholesale.
+///
+/// # Example
+///
+/// ```rust
+/// use sync::{Arc, AtomicArc};
+///
+/// let config = Arc::new(AtomicArc::new(Arc::new(~"v1")));
+///
+/// let reader = config.clone();
+/// spawn(proc() {
+///     let current = reader.load();
+///     assert!(current.as_slice() == "v1" || current.as_slice() == "v2");
+/// });
+///
+/// let old = config.swap(Arc::new(~"v2"));
+/// assert_eq!(old.as_slice(), "v1");
+/// ```
+pub struct AtomicArc<T> {
+    ptr: atomics::AtomicPtr<ArcInner<T>>,
+    // Readers announce themselves in the counter selected by the low bit of
+    // `epoch` for the short window between reading `ptr` and bumping the
+    // reference count. A writer publishes the new pointer, flips the epoch
+    // and then waits for the old epoch's readers to drain before its
+    // reference to the previous value can be released.
+    epoch: atomics::AtomicUint,
+    readers: [atomics::AtomicUint, ..2],
+    writer: atomics::AtomicBool,
+}
+
+impl<T: Share + Send> AtomicArc<T> {
+    /// Creates a new slot initially holding `value`.
+    pub fn new(value: Arc<T>) -> AtomicArc<T> {
+        let x = value.x;
+        unsafe { cast::forget(value); }
+        AtomicArc {
+            ptr: atomics::AtomicPtr::new(x),
+            epoch: atomics::AtomicUint::new(0),
+            readers: [atomics::AtomicUint::new(0), atomics::AtomicUint::new(0)],
+            writer: atomics::AtomicBool::new(false),
+        }
+    }
+
+    /// Returns a new strong reference to the value currently in the slot.
+    pub fn load(&self) -> Arc<T> {
+        let mut epoch = self.epoch.load(atomics::SeqCst);
+        loop {
+            self.readers[epoch & 1].fetch_add(1, atomics::SeqCst);
+            // A writer may have flipped the epoch between reading it and
+            // announcing ourselves, in which case the next writer won't wait
+            // for this counter. Back off and announce in the new epoch.
+            let now = self.epoch.load(atomics::SeqCst);
+            if now == epoch {
+                break;
+            }
+            self.readers[epoch & 1].fetch_sub(1, atomics::Release);
+            epoch = now;
+        }
+        let readers = &self.readers[epoch & 1];
+        let x = self.ptr.load(atomics::SeqCst);
+        // The writer which replaced `x` (if any) is waiting on `readers`, so
+        // the slot's reference keeps `x` alive until this is released.
+        unsafe { (*x).strong.fetch_add(1, atomics::Relaxed); }
+        readers.fetch_sub(1, atomics::Release);
+        Arc { x: x }
+    }
+
+    /// Replaces the value in the slot, dropping the slot's reference to the
+    /// previous value.
+    pub fn store(&self, value: Arc<T>) {
+        drop(self.swap(value));
+    }
+
+    /// Replaces the value in the slot, returning the previous value.
+    pub fn swap(&self, value: Arc<T>) -> Arc<T> {
+        self.lock_writer();
+        let old = self.publish(value);
+        self.unlock_writer();
+        old
+    }
+
+    /// Replaces the value in the slot with `new` if it currently holds the
+    /// same allocation as `current`.
+    ///
+    /// On success the previous value is returned as `Ok`, otherwise `new` is
+    /// handed back as `Err`.
+    pub fn compare_and_swap(&self, current: &Arc<T>,
+                            new: Arc<T>) -> Result<Arc<T>, Arc<T>> {
+        self.lock_writer();
+        let ret = if self.ptr.load(atomics::SeqCst) == current.x {
+            Ok(self.publish(new))
+        } else {
+            Err(new)
+        };
+        self.unlock_writer();
+        ret
+    }
+
+    // Must be called with the writer lock held.
+    fn publish(&self, value: Arc<T>) -> Arc<T> {
+        let x = value.x;
+        unsafe { cast::forget(value); }
+        let old = self.ptr.swap(x, atomics::SeqCst);
+
+        // Readers arriving from now on will see `x`, so once the readers of
+        // the current epoch have finished no one can still be about to take
+        // a reference to `old`.
+        let epoch = self.epoch.fetch_add(1, atomics::SeqCst) & 1;
+        while self.readers[epoch].load(atomics::Acquire) != 0 {
+            task::deschedule();
+        }
+        Arc { x: old }
+    }
+
+    fn lock_writer(&self) {
+        while self.writer.compare_and_swap(false, true, atomics::Acquire) {
+            task::deschedule();
+        }
+    }
+
+    fn unlock_writer(&self) {
+        self.writer.store(false, atomics::Release);
+    }
+}
+
+#[unsafe_destructor]
+impl<T: Share + Send> Drop for AtomicArc<T> {
+    fn drop(&mut self) {
+        let x = self.ptr.swap(ptr::mut_null(), atomics::SeqCst);
+        if !x.is_null() {
+            drop(Arc { x: x });
+        }
+    }
+}
+
 #[cfg(test)]
 #[allow(experimental)]
 mod tests {
-    use super::{Arc, Weak};
+    use super::{Arc, Weak, AtomicArc};
     use std::sync::atomics;
     use std::task;
     use Mutex;
@@ -381,4 +522,102 @@ mod tests {
         assert!(canary.load(atomics::Acquire) == 1);
         drop(arc_weak);
     }
+
+    #[test]
+    fn atomic_arc_swap() {
+        let slot = AtomicArc::new(Arc::new(1));
+        assert_eq!(*slot.load(), 1);
+        let old = slot.swap(Arc::new(2));
+        assert_eq!(*old, 1);
+        assert_eq!(*slot.load(), 2);
+        slot.store(Arc::new(3));
+        assert_eq!(*slot.load(), 3);
+    }
+
+    #[test]
+    fn atomic_arc_compare_and_swap() {
+        let first = Arc::new(1);
+        let slot = AtomicArc::new(first.clone());
+
+        let other = Arc::new(1);
+        match slot.compare_and_swap(&other, Arc::new(2)) {
+            Ok(..) => fail!("swapped in a value for a different allocation"),
+            Err(new) => assert_eq!(*new, 2),
+        }
+
+        match slot.compare_and_swap(&first, Arc::new(3)) {
+            Ok(prev) => assert!(prev.x == first.x),
+            Err(..) => fail!("failed to swap out the current value"),
+        }
+        assert_eq!(*slot.load(), 3);
+    }
+
+    #[test]
+    fn atomic_arc_drops_values() {
+        let mut canary = atomics::AtomicUint::new(0);
+        let p = &mut canary as *mut atomics::AtomicUint;
+        let slot = AtomicArc::new(Arc::new(Canary(p)));
+        let held = slot.load();
+        slot.store(Arc::new(Canary(p)));
+        assert!(canary.load(atomics::Acquire) == 0);
+        drop(held);
+        assert!(canary.load(atomics::Acquire) == 1);
+        drop(slot);
+        assert!(canary.load(atomics::Acquire) == 2);
+    }
+
+    #[test]
+    fn atomic_arc_concurrent() {
+        let slot = Arc::new(AtomicArc::new(Arc::new(0u)));
+        let (tx, rx) = channel();
+
+        for _ in range(0, 4) {
+            let slot = slot.clone();
+            let tx = tx.clone();
+            task::spawn(proc() {
+                let mut last = 0;
+                for _ in range(0, 1000) {
+                    // Values are only ever replaced by larger ones
+                    let cur = *slot.load();
+                    assert!(cur >= last);
+                    last = cur;
+                }
+                tx.send(());
+            });
+        }
+        for i in range(1u, 1001) {
+            slot.store(Arc::new(i));
+        }
+        drop(tx);
+        for _ in range(0, 4) { rx.recv(); }
+        assert_eq!(*slot.load(), 1000);
+    }
+
+    #[test]
+    fn atomic_arc_stress() {
+        // Several writers race each other and the readers, so that readers
+        // are caught between consecutive writers. Every value loaded must
+        // still be intact.
+        let slot = Arc::new(AtomicArc::new(Arc::new(Vec::from_elem(16, 0u))));
+        let (tx, rx) = channel();
+
+        for i in range(0u, 8) {
+            let slot = slot.clone();
+            let tx = tx.clone();
+            task::spawn(proc() {
+                for j in range(0u, 2000) {
+                    if i % 2 == 0 {
+                        slot.store(Arc::new(Vec::from_elem(16, j)));
+                    } else {
+                        let v = slot.load();
+                        let first = *v.get(0);
+                        assert!(v.iter().all(|&x| x == first));
+                    }
+                }
+                tx.send(());
+            });
+        }
+        drop(tx);
+        for _ in range(0, 8) { rx.recv(); }
+    }
 }
@@ -22,10 +22,28 @@
  * make_a_sandwich();
  * println!("fib(5000) = {}", delayed_fib.get())
  * ```
+ *
+ * Futures whose values are produced by other tasks are backed by a channel,
+ * so several of them can be waited on at once without polling:
+ *
+ * ```rust
+ * use sync::future::{Future, select_first};
+ *
+ * let (promise, slow) = Future::promise();
+ * let fast = Future::spawn(proc() 1).map(proc(n) n + 1);
+ *
+ * let (index, value, rest) = select_first(vec!(slow, fast));
+ * assert_eq!((index, value), (1, 2));
+ *
+ * promise.fulfill(3);
+ * assert_eq!(rest.move_iter().next().unwrap().unwrap(), 3);
+ * ```
  */
 
 #![allow(missing_doc)]
 
+use std::comm::Select;
+use std::io::timer::Timer;
 use std::mem::replace;
 
 /// A type encapsulating the result of a computation which may not be complete
@@ -35,10 +53,29 @@ pub struct Future<A> {
 
 enum FutureState<A> {
     Pending(proc():Send -> A),
+    Waiting(Receiver<A>),
     Evaluating,
     Forced(A)
 }
 
+/// The producing half of a future created with `Future::promise`
+///
+/// A promise may be sent to another task, which then completes the future by
+/// calling `fulfill`. If a promise is dropped without being fulfilled then
+/// forcing its future will fail.
+pub struct Promise<A> {
+    tx: Sender<A>,
+}
+
+impl<A:Send> Promise<A> {
+    /// Completes the future associated with this promise.
+    ///
+    /// If the future has already been dropped the value is discarded.
+    pub fn fulfill(self, value: A) {
+        let _ = self.tx.send_opt(value);
+    }
+}
+
 /// Methods on the `future` type
 impl<A:Clone> Future<A> {
     pub fn get(&mut self) -> A {
@@ -67,18 +104,74 @@ impl<A> Future<A> {
         match self.state {
             Forced(ref v) => return v,
             Evaluating => fail!("Recursive forcing of future!"),
-            Pending(_) => {
+            Pending(_) | Waiting(_) => {
                 match replace(&mut self.state, Evaluating) {
                     Forced(_) | Evaluating => fail!("Logic error."),
                     Pending(f) => {
                         self.state = Forced(f());
                         self.get_ref()
                     }
+                    Waiting(rx) => {
+                        self.state = Forced(rx.recv());
+                        self.get_ref()
+                    }
                 }
             }
         }
     }
 
+    pub fn is_ready(&mut self) -> bool {
+        /*!
+         * Returns whether the value of this future is available without
+         * blocking.
+         *
+         * Futures computed by a local function are not considered ready
+         * until they have been forced.
+         */
+
+        let value = match self.state {
+            Forced(_) => return true,
+            Pending(_) | Evaluating => return false,
+            Waiting(ref rx) => match rx.try_recv() {
+                Ok(value) => value,
+                Err(..) => return false,
+            },
+        };
+        self.state = Forced(value);
+        true
+    }
+
+    pub fn wait_timeout(&mut self, msecs: u64) -> bool {
+        /*!
+         * Blocks for at most `msecs` milliseconds waiting for the value of
+         * this future, returning whether it is now available.
+         *
+         * Futures computed by a local function are forced immediately, as
+         * there is nothing to wait for.
+         */
+
+        let value = match self.state {
+            Forced(_) => return true,
+            Evaluating => fail!("Recursive forcing of future!"),
+            Pending(_) => { self.get_ref(); return true }
+            Waiting(ref rx) => {
+                let mut timer = new_timer();
+                let timeout = timer.oneshot(msecs);
+                let sel = Select::new();
+                let mut rx = sel.handle(rx);
+                let mut timeout = sel.handle(&timeout);
+                unsafe {
+                    rx.add();
+                    timeout.add();
+                }
+                if sel.wait() != rx.id() { return false }
+                rx.recv()
+            }
+        };
+        self.state = Forced(value);
+        true
+    }
+
     pub fn from_value(val: A) -> Future<A> {
         /*!
          * Create a future from a value.
@@ -112,9 +205,49 @@ impl<A:Send> Future<A> {
          * waiting for the result to be received on the port.
          */
 
-        Future::from_fn(proc() {
-            rx.recv()
-        })
+        Future {state: Waiting(rx)}
+    }
+
+    pub fn promise() -> (Promise<A>, Future<A>) {
+        /*!
+         * Create a future along with the promise which completes it.
+         *
+         * The promise may be handed to another task; forcing the future
+         * blocks until that task fulfills it.
+         */
+
+        let (tx, rx) = channel();
+        (Promise { tx: tx }, Future::from_receiver(rx))
+    }
+
+    pub fn map<B:Send>(self, f: proc(A):Send -> B) -> Future<B> {
+        /*!
+         * Create a future whose value is `f` applied to the value of this
+         * one.
+         *
+         * If this future is waiting on another task then `f` runs in a new
+         * task as soon as the value arrives, so that the resulting future may
+         * itself be waited on alongside others. Otherwise `f` runs when the
+         * resulting future is forced.
+         */
+
+        let Future { state } = self;
+        match state {
+            Waiting(rx) => Future::spawn(proc() f(rx.recv())),
+            state => Future::from_fn(proc() f(Future { state: state }.unwrap())),
+        }
+    }
+
+    pub fn and_then<B:Send>(self, f: proc(A):Send -> Future<B>) -> Future<B> {
+        /*!
+         * Create a future which is completed by the future returned from
+         * applying `f` to the value of this one.
+         *
+         * Like `map`, this stays waitable if this future is waiting on
+         * another task.
+         */
+
+        self.map(f).map(proc(future: Future<B>) future.unwrap())
     }
 
     pub fn spawn(blk: proc():Send -> A) -> Future<A> {
@@ -135,9 +268,120 @@ impl<A:Send> Future<A> {
     }
 }
 
+/// Create a future whose value is the values of all of `futures`, in order.
+///
+/// If any of the futures is waiting on another task then they are collected
+/// by a new task, so that the resulting future may itself be waited on.
+pub fn join_all<A:Send>(futures: Vec<Future<A>>) -> Future<Vec<A>> {
+    let waiting = futures.iter().any(|f| match f.state {
+        Waiting(_) => true, _ => false
+    });
+    let collect = proc() {
+        futures.move_iter().map(|f| f.unwrap()).collect::<Vec<A>>()
+    };
+    if waiting {
+        Future::spawn(collect)
+    } else {
+        Future::from_fn(collect)
+    }
+}
+
+/// Waits for the first of `futures` to complete.
+///
+/// Returns the index of the completed future, its value, and the remaining
+/// futures in their original order. Futures which are already forced, or
+/// which are computed by a local function, are taken in preference to those
+/// waiting on other tasks.
+///
+/// # Failure
+///
+/// Fails if `futures` is empty. Like forcing it would, this also fails if the
+/// first future to complete is one whose promise was dropped without being
+/// fulfilled, or whose task failed.
+pub fn select_first<A:Send>(futures: Vec<Future<A>>) -> (uint, A, Vec<Future<A>>) {
+    match select_first_with(futures, None) {
+        Ok(ret) => ret,
+        Err(..) => unreachable!(),
+    }
+}
+
+/// Waits at most `msecs` milliseconds for the first of `futures` to complete.
+///
+/// This behaves like `select_first`, but if none of the futures completes in
+/// time they are all handed back as `Err`.
+pub fn select_first_timeout<A:Send>(futures: Vec<Future<A>>, msecs: u64)
+    -> Result<(uint, A, Vec<Future<A>>), Vec<Future<A>>>
+{
+    select_first_with(futures, Some(msecs))
+}
+
+fn select_first_with<A:Send>(mut futures: Vec<Future<A>>, timeout: Option<u64>)
+    -> Result<(uint, A, Vec<Future<A>>), Vec<Future<A>>>
+{
+    assert!(!futures.is_empty(), "select_first requires at least one future");
+
+    let local = futures.iter().position(|f| match f.state {
+        Forced(_) => true, _ => false
+    }).or_else(|| futures.iter().position(|f| match f.state {
+        Pending(_) => true, _ => false
+    }));
+    match local {
+        Some(i) => {
+            let value = futures.remove(i).unwrap().unwrap();
+            return Ok((i, value, futures))
+        }
+        None => {}
+    }
+
+    let selected = {
+        let sel = Select::new();
+        let mut handles = Vec::with_capacity(futures.len());
+        for f in futures.iter() {
+            match f.state {
+                Waiting(ref rx) => handles.push(sel.handle(rx)),
+                _ => fail!("Logic error."),
+            }
+        }
+        // Keep the timer alive while waiting so the oneshot isn't cancelled
+        let mut timer = timeout.map(|_| new_timer());
+        let timeout = match (timer.as_mut(), timeout) {
+            (Some(timer), Some(msecs)) => Some(timer.oneshot(msecs)),
+            _ => None,
+        };
+        let mut timeout_handle = timeout.as_ref().map(|rx| sel.handle(rx));
+
+        // The handles must not move once they've been added to the set
+        unsafe {
+            for handle in handles.mut_iter() { handle.add(); }
+            match timeout_handle {
+                Some(ref mut handle) => handle.add(),
+                None => {}
+            }
+        }
+        let id = sel.wait();
+        handles.mut_iter().enumerate().find(|&(_, ref h)| h.id() == id)
+               .map(|(i, handle)| (i, handle.recv()))
+    };
+
+    match selected {
+        Some((i, value)) => {
+            futures.remove(i);
+            Ok((i, value, futures))
+        }
+        None => Err(futures),
+    }
+}
+
+fn new_timer() -> Timer {
+    match Timer::new() {
+        Ok(timer) => timer,
+        Err(e) => fail!("waiting on a future with a timeout requires a timer: {}", e),
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use future::Future;
+    use future::{Future, Promise, join_all, select_first, select_first_timeout};
 
     use std::task;
 
@@ -202,4 +446,121 @@ mod test {
             assert_eq!(actual, expected);
         });
     }
+
+    #[test]
+    fn test_promise() {
+        let (promise, mut f) = Future::promise();
+        assert!(!f.is_ready());
+        task::spawn(proc() promise.fulfill("kept".to_owned()));
+        assert_eq!(f.get(), "kept".to_owned());
+        assert!(f.is_ready());
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_broken_promise() {
+        let (promise, f): (Promise<int>, Future<int>) = Future::promise();
+        drop(promise);
+        f.unwrap();
+    }
+
+    #[test]
+    fn test_map() {
+        let f = Future::from_value(2).map(proc(n) n * 10);
+        assert_eq!(f.unwrap(), 20);
+
+        let f = Future::spawn(proc() 2).map(proc(n) n * 10);
+        assert_eq!(f.unwrap(), 20);
+    }
+
+    #[test]
+    fn test_and_then() {
+        let f = Future::spawn(proc() 3).and_then(proc(n) {
+            Future::spawn(proc() n + 1)
+        });
+        assert_eq!(f.unwrap(), 4);
+
+        let f = Future::from_value(3).and_then(proc(n) Future::from_value(n * 2));
+        assert_eq!(f.unwrap(), 6);
+    }
+
+    #[test]
+    fn test_join_all() {
+        let (promise, waiting) = Future::promise();
+        let futures = vec!(Future::from_value(1), waiting,
+                           Future::from_fn(proc() 3));
+        let joined = join_all(futures);
+        promise.fulfill(2);
+        assert_eq!(joined.unwrap(), vec!(1, 2, 3));
+
+        let empty: Vec<Future<int>> = Vec::new();
+        assert_eq!(join_all(empty).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn test_select_first() {
+        let (p1, f1) = Future::promise();
+        let (p2, f2) = Future::promise();
+        let (p3, f3) = Future::promise();
+        p2.fulfill(2);
+
+        let (i, value, rest) = select_first(vec!(f1, f2, f3));
+        assert_eq!((i, value), (1, 2));
+        assert_eq!(rest.len(), 2);
+
+        p3.fulfill(3);
+        let (i, value, rest) = select_first(rest);
+        assert_eq!((i, value), (1, 3));
+
+        p1.fulfill(1);
+        let (i, value, rest) = select_first(rest);
+        assert_eq!((i, value), (0, 1));
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_select_first_broken_promise() {
+        let (_p1, f1): (Promise<int>, Future<int>) = Future::promise();
+        let (p2, f2) = Future::promise();
+        drop(p2);
+        select_first(vec!(f1, f2));
+    }
+
+    #[test]
+    fn test_select_first_prefers_local() {
+        let (_promise, waiting) = Future::promise();
+        let futures = vec!(waiting, Future::from_fn(proc() 5), Future::from_value(6));
+        let (i, value, _) = select_first(futures);
+        assert_eq!((i, value), (2, 6));
+    }
+
+    #[test]
+    fn test_select_first_timeout() {
+        let (promise, waiting) = Future::promise();
+        let rest = match select_first_timeout(vec!(waiting), 10) {
+            Ok(..) => fail!("nothing should have completed"),
+            Err(rest) => rest,
+        };
+        assert_eq!(rest.len(), 1);
+
+        promise.fulfill(7);
+        match select_first_timeout(rest, 1000) {
+            Ok((i, value, _)) => assert_eq!((i, value), (0, 7)),
+            Err(..) => fail!("the future should have completed"),
+        }
+    }
+
+    #[test]
+    fn test_wait_timeout() {
+        let (promise, mut f) = Future::promise();
+        assert!(!f.wait_timeout(10));
+        promise.fulfill(8);
+        assert!(f.wait_timeout(1000));
+        assert_eq!(f.get(), 8);
+
+        let mut f = Future::from_fn(proc() 9);
+        assert!(f.wait_timeout(0));
+        assert_eq!(f.get(), 9);
+    }
 }
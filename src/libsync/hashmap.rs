@@ -0,0 +1,383 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A hash map which can be shared and mutated by many tasks at once.
+//!
+//! The map is split into a number of shards, each of which is a `HashMap`
+//! protected by its own reader-writer lock. A key always lives in the same
+//! shard, chosen by its hash, so operations on keys in different shards never
+//! contend with each other, and lookups in the same shard only contend with
+//! writers of that shard.
+//!
+//! The locks used are the ones from `sync::raw`, which deschedule green tasks
+//! and block native tasks, so the map can be used from either kind of task.
+//! Since entries are only ever freed while holding the lock of their shard,
+//! there is no need for any deferred memory reclamation scheme.
+
+use std::hash::{Hash, Hasher};
+use std::hash::sip::SipHasher;
+use std::sync::atomics;
+use std::ty::Unsafe;
+
+use collections::HashMap;
+use rand;
+use rand::Rng;
+
+use raw;
+
+static DEFAULT_SHARDS: uint = 16;
+
+/// A concurrent hash map, usually shared among tasks through an `Arc`.
+///
+/// All operations take `&self`. Since no reference into the map can outlive
+/// the lock of its shard, values are accessed either by copying them out or
+/// through closures which run with the shard locked. These closures must not
+/// access the same map again, as that may deadlock.
+///
+/// # Failure
+///
+/// A task which fails inside one of the closures passed to the map will
+/// unlock the shard as it unwinds. The map itself stays consistent, but a
+/// value which was being modified may have been left half-updated.
+///
+/// # Example
+///
+/// ```
+/// use sync::{Arc, ConcurrentHashMap};
+///
+/// let map = Arc::new(ConcurrentHashMap::new());
+/// for i in range(0, 4) {
+///     let map = map.clone();
+///     spawn(proc() {
+///         // Count the odd and even task ids
+///         map.insert_or_update_with(i % 2, 1, |_, count| *count += 1);
+///     });
+/// }
+/// ```
+pub struct ConcurrentHashMap<K, V, H = SipHasher> {
+    hasher: H,
+    shards: Vec<Shard<K, V, H>>,
+    size: atomics::AtomicUint,
+}
+
+struct Shard<K, V, H> {
+    lock: raw::RWLock,
+    map: Unsafe<HashMap<K, V, H>>,
+}
+
+impl<K: TotalEq + Hash<S>, V, S, H: Hasher<S>> Shard<K, V, H> {
+    fn read<R>(&self, f: |&HashMap<K, V, H>| -> R) -> R {
+        let _guard = self.lock.read();
+        f(unsafe { &*self.map.get() })
+    }
+
+    fn write<R>(&self, f: |&mut HashMap<K, V, H>| -> R) -> R {
+        let _guard = self.lock.write();
+        f(unsafe { &mut *self.map.get() })
+    }
+}
+
+impl<K: TotalEq + Hash, V> ConcurrentHashMap<K, V, SipHasher> {
+    /// Creates an empty map with a default number of shards.
+    pub fn new() -> ConcurrentHashMap<K, V, SipHasher> {
+        ConcurrentHashMap::with_shards(DEFAULT_SHARDS)
+    }
+
+    /// Creates an empty map split into `shards` shards. The number of shards
+    /// bounds the number of tasks which can write to the map at once.
+    pub fn with_shards(shards: uint) -> ConcurrentHashMap<K, V, SipHasher> {
+        let mut r = rand::task_rng();
+        let hasher = SipHasher::new_with_keys(r.gen(), r.gen());
+        ConcurrentHashMap::with_shards_and_hasher(shards, hasher)
+    }
+}
+
+impl<K: TotalEq + Hash<S>, V, S, H: Hasher<S> + Clone> ConcurrentHashMap<K, V, H> {
+    /// Creates an empty map split into `shards` shards, which uses `hasher`
+    /// to hash keys.
+    pub fn with_shards_and_hasher(shards: uint,
+                                  hasher: H) -> ConcurrentHashMap<K, V, H> {
+        assert!(shards > 0, "a concurrent map needs at least one shard");
+        ConcurrentHashMap {
+            shards: Vec::from_fn(shards, |_| Shard {
+                lock: raw::RWLock::new(),
+                map: Unsafe::new(HashMap::with_hasher(hasher.clone())),
+            }),
+            hasher: hasher,
+            size: atomics::AtomicUint::new(0),
+        }
+    }
+
+    fn shard<'a>(&'a self, k: &K) -> &'a Shard<K, V, H> {
+        // The low bits of the hash pick the bucket inside of the shard, so use
+        // the high bits to pick the shard.
+        let hash = self.hasher.hash(k);
+        self.shards.get(((hash >> 32) as uint) % self.shards.len())
+    }
+
+    /// Returns the number of elements in the map. Other tasks may be
+    /// modifying the map concurrently, so this is only a hint.
+    pub fn len(&self) -> uint { self.size.load(atomics::SeqCst) }
+
+    /// Returns true if the map contains no elements, with the same caveat as
+    /// `len`.
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// Returns true if the map contains a value for the specified key.
+    pub fn contains_key(&self, k: &K) -> bool {
+        self.shard(k).read(|map| map.contains_key(k))
+    }
+
+    /// Calls `f` with the value corresponding to the key while its shard is
+    /// locked for reading, returning its result, or `None` if the key isn't
+    /// present.
+    pub fn find_with<R>(&self, k: &K, f: |&V| -> R) -> Option<R> {
+        self.shard(k).read(|map| map.find(k).map(|v| f(v)))
+    }
+
+    /// Calls `f` with the value corresponding to the key while its shard is
+    /// locked for writing, returning its result, or `None` if the key isn't
+    /// present.
+    pub fn find_mut_with<R>(&self, k: &K, f: |&mut V| -> R) -> Option<R> {
+        self.shard(k).write(|map| map.find_mut(k).map(|v| f(v)))
+    }
+
+    /// Inserts a key-value pair into the map. Returns true if the key did not
+    /// already exist in the map.
+    pub fn insert(&self, k: K, v: V) -> bool {
+        self.swap(k, v).is_none()
+    }
+
+    /// Inserts a key-value pair into the map, returning the previous value of
+    /// the key if there was one.
+    pub fn swap(&self, k: K, v: V) -> Option<V> {
+        self.shard(&k).write(|map| {
+            let old = map.swap(k, v);
+            if old.is_none() {
+                self.size.fetch_add(1, atomics::SeqCst);
+            }
+            old
+        })
+    }
+
+    /// Removes a key-value pair from the map. Returns true if the key was
+    /// present in the map.
+    pub fn remove(&self, k: &K) -> bool {
+        self.pop(k).is_some()
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key
+    /// was previously in the map.
+    pub fn pop(&self, k: &K) -> Option<V> {
+        self.shard(k).write(|map| {
+            let old = map.pop(k);
+            if old.is_some() {
+                self.size.fetch_sub(1, atomics::SeqCst);
+            }
+            old
+        })
+    }
+
+    /// Inserts `v` for the key if it isn't present, and otherwise updates
+    /// the existing value with `f`. Both happen atomically with respect to
+    /// the other operations on the map.
+    pub fn insert_or_update_with(&self, k: K, v: V, f: |&K, &mut V|) {
+        self.shard(&k).write(|map| {
+            let mut inserted = true;
+            map.insert_or_update_with(k, v, |k, v| { inserted = false; f(k, v) });
+            if inserted {
+                self.size.fetch_add(1, atomics::SeqCst);
+            }
+        })
+    }
+
+    /// Removes all key-value pairs from the map. Pairs inserted concurrently
+    /// with this call may or may not survive it.
+    pub fn clear(&self) {
+        for shard in self.shards.iter() {
+            shard.write(|map| {
+                self.size.fetch_sub(map.len(), atomics::SeqCst);
+                map.clear();
+            });
+        }
+    }
+
+    /// Calls `f` on every key-value pair in the map, one shard at a time,
+    /// stopping early if `f` returns false. Returns false if the iteration was
+    /// stopped early.
+    ///
+    /// Each shard is locked for reading while it is visited, but changes to
+    /// shards which have not been visited yet, or have already been visited,
+    /// can happen during the iteration. Use `snapshot` for a consistent view.
+    pub fn each(&self, f: |&K, &V| -> bool) -> bool {
+        self.shards.iter().all(|shard| {
+            shard.read(|map| map.iter().all(|(k, v)| f(k, v)))
+        })
+    }
+}
+
+impl<K: TotalEq + Hash<S>, V: Clone, S, H: Hasher<S> + Clone>
+        ConcurrentHashMap<K, V, H> {
+    /// Returns a copy of the value corresponding to the key.
+    pub fn find_copy(&self, k: &K) -> Option<V> {
+        self.find_with(k, |v| v.clone())
+    }
+
+    /// Returns a copy of the value corresponding to the key, first inserting
+    /// the value produced by `f` if the key isn't present.
+    ///
+    /// `f` is called at most once, and only if no other task inserted the key
+    /// first, so it may be used for expensive or effectful initialization.
+    pub fn find_or_insert_with(&self, k: K, f: |&K| -> V) -> V {
+        match self.find_copy(&k) {
+            Some(v) => return v,
+            None => {}
+        }
+        self.shard(&k).write(|map| {
+            if !map.contains_key(&k) {
+                self.size.fetch_add(1, atomics::SeqCst);
+            }
+            map.find_or_insert_with(k, |k| f(k)).clone()
+        })
+    }
+}
+
+impl<K: TotalEq + Hash<S> + Clone, V: Clone, S, H: Hasher<S> + Clone>
+        ConcurrentHashMap<K, V, H> {
+    /// Copies the contents of the map into a regular `HashMap`.
+    ///
+    /// All shards are locked for reading at the same time while copying, so
+    /// the result is a consistent point-in-time view of the map: either all
+    /// or none of the effects of any single operation are visible in it.
+    pub fn snapshot(&self) -> HashMap<K, V, H> {
+        let mut snapshot = HashMap::with_capacity_and_hasher(self.len(),
+                                                             self.hasher.clone());
+        // Shards are always locked in the same order, and writers only ever
+        // lock one shard, so this can't deadlock.
+        let guards: Vec<raw::RWLockReadGuard> =
+            self.shards.iter().map(|shard| shard.lock.read()).collect();
+        for shard in self.shards.iter() {
+            let map = unsafe { &*shard.map.get() };
+            for (k, v) in map.iter() {
+                snapshot.insert(k.clone(), v.clone());
+            }
+        }
+        drop(guards);
+        snapshot
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::hash::sip::SipHasher;
+    use std::task;
+
+    use arc::Arc;
+    use super::ConcurrentHashMap;
+
+    #[test]
+    fn test_insert_find_remove() {
+        let m = ConcurrentHashMap::new();
+        assert!(m.is_empty());
+        assert!(m.insert(1, 2));
+        assert!(m.insert(2, 4));
+        assert!(!m.insert(1, 3));
+        assert_eq!(m.len(), 2);
+        assert_eq!(m.find_copy(&1), Some(3));
+        assert_eq!(m.find_copy(&3), None);
+        assert!(m.contains_key(&2));
+        assert_eq!(m.swap(2, 5), Some(4));
+        assert!(m.remove(&2));
+        assert!(!m.remove(&2));
+        assert_eq!(m.pop(&1), Some(3));
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn test_closures() {
+        let m = ConcurrentHashMap::new();
+        m.insert(~"a", 1);
+        assert_eq!(m.find_with(&~"a", |v| *v + 1), Some(2));
+        assert_eq!(m.find_mut_with(&~"a", |v| { *v += 10; *v }), Some(11));
+        assert_eq!(m.find_mut_with(&~"b", |v| *v), None);
+        m.insert_or_update_with(~"a", 0, |_, v| *v += 1);
+        m.insert_or_update_with(~"b", 0, |_, v| *v += 1);
+        assert_eq!(m.find_copy(&~"a"), Some(12));
+        assert_eq!(m.find_copy(&~"b"), Some(0));
+        assert_eq!(m.len(), 2);
+    }
+
+    #[test]
+    fn test_find_or_insert_with() {
+        let m = ConcurrentHashMap::new();
+        let mut calls = 0;
+        assert_eq!(m.find_or_insert_with(1, |_| { calls += 1; 10 }), 10);
+        assert_eq!(m.find_or_insert_with(1, |_| { calls += 1; 20 }), 10);
+        assert_eq!(calls, 1);
+        assert_eq!(m.len(), 1);
+    }
+
+    #[test]
+    fn test_single_shard() {
+        let m = ConcurrentHashMap::with_shards_and_hasher(1, SipHasher::new());
+        for i in range(0, 100) {
+            m.insert(i, i);
+        }
+        assert_eq!(m.len(), 100);
+        m.clear();
+        assert!(m.is_empty());
+        assert_eq!(m.find_copy(&1), None);
+    }
+
+    #[test]
+    fn test_each_and_snapshot() {
+        let m = ConcurrentHashMap::new();
+        for i in range(0, 50) {
+            m.insert(i, i * 2);
+        }
+        let mut sum = 0;
+        assert!(m.each(|k, v| { assert_eq!(*k * 2, *v); sum += *k; true }));
+        assert_eq!(sum, 49 * 50 / 2);
+
+        let mut seen = 0;
+        assert!(!m.each(|_, _| { seen += 1; seen < 10 }));
+        assert_eq!(seen, 10);
+
+        let snapshot = m.snapshot();
+        m.insert(100, 200);
+        assert_eq!(snapshot.len(), 50);
+        for i in range(0, 50) {
+            assert_eq!(snapshot.find(&i), Some(&(i * 2)));
+        }
+    }
+
+    #[test]
+    fn test_concurrent() {
+        let m = Arc::new(ConcurrentHashMap::new());
+        let (tx, rx) = channel();
+        for t in range(0u, 8) {
+            let (m, tx) = (m.clone(), tx.clone());
+            task::spawn(proc() {
+                for i in range(0u, 100) {
+                    m.insert(t * 100 + i, t);
+                    m.insert_or_update_with(1000, 1, |_, v| *v += 1);
+                }
+                tx.send(());
+            });
+        }
+        for _ in range(0, 8) { rx.recv(); }
+        assert_eq!(m.len(), 801);
+        assert_eq!(m.find_copy(&1000), Some(800));
+        let snapshot = m.snapshot();
+        for i in range(0u, 800) {
+            assert_eq!(snapshot.find(&i), Some(&(i / 100)));
+        }
+    }
+}
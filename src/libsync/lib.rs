@@ -19,31 +19,40 @@
 #![doc(html_logo_url = "http://www.rust-lang.org/logos/rust-logo-128x128-blk-v2.png",
        html_favicon_url = "http://www.rust-lang.org/favicon.ico",
        html_root_url = "http://static.rust-lang.org/doc/master")]
-#![feature(phase)]
+#![feature(phase, default_type_params)]
 #![deny(deprecated_owned_vector)]
 
 #![deny(missing_doc)]
 
+extern crate collections;
+extern crate rand;
+
 #[cfg(test)]
 #[phase(syntax, link)] extern crate log;
 
 pub use comm::{DuplexStream, duplex};
 pub use task_pool::TaskPool;
-pub use future::Future;
-pub use arc::{Arc, Weak};
+pub use future::{Future, Promise};
+pub use arc::{Arc, Weak, AtomicArc};
+pub use hashmap::ConcurrentHashMap;
+pub use lru_cache::ShardedLruCache;
 pub use lock::{Mutex, MutexGuard, Condvar, Barrier,
-               RWLock, RWLockReadGuard, RWLockWriteGuard};
+               RWLock, RWLockReadGuard, RWLockWriteGuard,
+               RWLockUpgradableGuard};
 
 // The mutex/rwlock in this module are not meant for reexport
 pub use raw::{Semaphore, SemaphoreGuard};
+pub use raw::{RWLockPolicy, ArrivalOrder, PreferWriters};
 
 mod arc;
 mod comm;
-mod future;
+mod hashmap;
 mod lock;
+mod lru_cache;
 mod mpsc_intrusive;
 mod task_pool;
 
+pub mod future;
 pub mod raw;
 pub mod mutex;
 pub mod one;
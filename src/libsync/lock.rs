@@ -212,8 +212,33 @@ impl<T: Send> Mutex<T> {
     /// blocked on the mutex) will also fail immediately.
     #[inline]
     pub fn lock<'a>(&'a self) -> MutexGuard<'a, T> {
-        let guard = self.lock.lock();
+        self.guard(self.lock.lock())
+    }
+
+    /// Attempts to lock the mutex without blocking, returning `None` if it is
+    /// currently locked by another task.
+    ///
+    /// # Failure
+    ///
+    /// Fails if the mutex is poisoned, as `lock` does.
+    #[inline]
+    pub fn try_lock<'a>(&'a self) -> Option<MutexGuard<'a, T>> {
+        self.lock.try_lock().map(|guard| self.guard(guard))
+    }
+
+    /// As `lock`, but returns `None` if the mutex could not be locked within
+    /// `msecs` milliseconds.
+    ///
+    /// # Failure
+    ///
+    /// Fails if the mutex is poisoned, as `lock` does, or if the local runtime
+    /// can't provide a timer.
+    #[inline]
+    pub fn lock_timeout<'a>(&'a self, msecs: u64) -> Option<MutexGuard<'a, T>> {
+        self.lock.lock_timeout(msecs).map(|guard| self.guard(guard))
+    }
 
+    fn guard<'a>(&'a self, guard: raw::MutexGuard<'a>) -> MutexGuard<'a, T> {
         // These two accesses are safe because we're guranteed at this point
         // that we have exclusive access to this mutex. We are indeed able to
         // promote ourselves from &Mutex to `&mut T`
@@ -285,6 +310,14 @@ pub struct RWLockReadGuard<'a, T> {
     guard: raw::RWLockReadGuard<'a>,
 }
 
+/// A guard which is created by locking an rwlock in upgradable read mode.
+/// Through this guard the underlying data can be accessed, and it can be
+/// upgraded to a write guard.
+pub struct RWLockUpgradableGuard<'a, T> {
+    lock: &'a RWLock<T>,
+    guard: raw::RWLockUpgradableGuard<'a>,
+}
+
 impl<T: Send + Share> RWLock<T> {
     /// Create a reader/writer lock with the supplied data.
     pub fn new(user_data: T) -> RWLock<T> {
@@ -294,8 +327,16 @@ impl<T: Send + Share> RWLock<T> {
     /// Create a reader/writer lock with the supplied data and a specified number
     /// of condvars (as sync::RWLock::new_with_condvars).
     pub fn new_with_condvars(user_data: T, num_condvars: uint) -> RWLock<T> {
+        RWLock::new_with_policy(user_data, num_condvars, raw::ArrivalOrder)
+    }
+
+    /// Create a reader/writer lock with the supplied data, a specified number
+    /// of condvars and the given policy for ordering readers and writers (as
+    /// sync::raw::RWLock::new_with_policy).
+    pub fn new_with_policy(user_data: T, num_condvars: uint,
+                           policy: raw::RWLockPolicy) -> RWLock<T> {
         RWLock {
-            lock: raw::RWLock::new_with_condvars(num_condvars),
+            lock: raw::RWLock::new_with_policy(num_condvars, policy),
             failed: Unsafe::new(false),
             data: Unsafe::new(user_data),
         }
@@ -311,8 +352,26 @@ impl<T: Send + Share> RWLock<T> {
     /// the lock, so subsequent readers and writers will both also fail.
     #[inline]
     pub fn write<'a>(&'a self) -> RWLockWriteGuard<'a, T> {
-        let guard = self.lock.write();
+        self.write_guard(self.lock.write())
+    }
 
+    /// Attempts to lock the rwlock in write mode without blocking, returning
+    /// `None` if any other task holds the lock.
+    #[inline]
+    pub fn try_write<'a>(&'a self) -> Option<RWLockWriteGuard<'a, T>> {
+        self.lock.try_write().map(|guard| self.write_guard(guard))
+    }
+
+    /// As `write`, but returns `None` if the lock could not be acquired within
+    /// `msecs` milliseconds.
+    #[inline]
+    pub fn write_timeout<'a>(&'a self,
+                             msecs: u64) -> Option<RWLockWriteGuard<'a, T>> {
+        self.lock.write_timeout(msecs).map(|guard| self.write_guard(guard))
+    }
+
+    fn write_guard<'a>(&'a self,
+                       guard: raw::RWLockWriteGuard<'a>) -> RWLockWriteGuard<'a, T> {
         // These two accesses are safe because we're guranteed at this point
         // that we have exclusive access to this rwlock. We are indeed able to
         // promote ourselves from &RWLock to `&mut T`
@@ -337,13 +396,75 @@ impl<T: Send + Share> RWLock<T> {
     /// Failing will unlock the lock while unwinding. However, unlike all other
     /// access modes, this will not poison the lock.
     pub fn read<'a>(&'a self) -> RWLockReadGuard<'a, T> {
-        let guard = self.lock.read();
+        self.read_guard(self.lock.read())
+    }
+
+    /// Attempts to lock the rwlock in read mode without blocking, returning
+    /// `None` if a writer holds the lock.
+    #[inline]
+    pub fn try_read<'a>(&'a self) -> Option<RWLockReadGuard<'a, T>> {
+        self.lock.try_read().map(|guard| self.read_guard(guard))
+    }
+
+    /// As `read`, but returns `None` if the lock could not be acquired within
+    /// `msecs` milliseconds.
+    #[inline]
+    pub fn read_timeout<'a>(&'a self,
+                            msecs: u64) -> Option<RWLockReadGuard<'a, T>> {
+        self.lock.read_timeout(msecs).map(|guard| self.read_guard(guard))
+    }
+
+    /// Access the underlying data immutably, with the option of upgrading to
+    /// mutable access later on without letting any other writer in between.
+    /// May run concurrently with other reading tasks, but not with writers or
+    /// other upgradable readers.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sync::RWLock;
+    ///
+    /// let lock = RWLock::new(vec!(1, 2, 3));
+    /// let val = lock.upgradable_read();
+    /// if !(*val).contains(&4) {
+    ///     let mut val = val.upgrade();
+    ///     (*val).push(4);
+    /// }
+    /// ```
+    pub fn upgradable_read<'a>(&'a self) -> RWLockUpgradableGuard<'a, T> {
+        self.upgradable_guard(self.lock.upgradable_read())
+    }
+
+    /// Attempts to lock the rwlock in upgradable read mode without blocking.
+    #[inline]
+    pub fn try_upgradable_read<'a>(&'a self)
+                                   -> Option<RWLockUpgradableGuard<'a, T>> {
+        self.lock.try_upgradable_read().map(|guard| self.upgradable_guard(guard))
+    }
+
+    fn read_guard<'a>(&'a self,
+                      guard: raw::RWLockReadGuard<'a>) -> RWLockReadGuard<'a, T> {
         PoisonOnFail::check(unsafe { *self.failed.get() }, "RWLock");
         RWLockReadGuard {
             guard: guard,
             data: unsafe { &*self.data.get() },
         }
     }
+
+    fn upgradable_guard<'a>(&'a self, guard: raw::RWLockUpgradableGuard<'a>)
+                            -> RWLockUpgradableGuard<'a, T> {
+        PoisonOnFail::check(unsafe { *self.failed.get() }, "RWLock");
+        RWLockUpgradableGuard { lock: self, guard: guard }
+    }
+}
+
+impl<'a, T: Send + Share> RWLockUpgradableGuard<'a, T> {
+    /// Consumes this upgradable read lock token, returning a write lock token
+    /// once all other readers have left.
+    pub fn upgrade(self) -> RWLockWriteGuard<'a, T> {
+        let RWLockUpgradableGuard { lock, guard } = self;
+        lock.write_guard(guard.upgrade())
+    }
 }
 
 impl<'a, T: Send + Share> RWLockWriteGuard<'a, T> {
@@ -365,6 +486,9 @@ impl<'a, T: Send + Share> RWLockWriteGuard<'a, T> {
 impl<'a, T: Send + Share> Deref<T> for RWLockReadGuard<'a, T> {
     fn deref<'a>(&'a self) -> &'a T { self.data }
 }
+impl<'a, T: Send + Share> Deref<T> for RWLockUpgradableGuard<'a, T> {
+    fn deref<'a>(&'a self) -> &'a T { unsafe { &*self.lock.data.get() } }
+}
 impl<'a, T: Send + Share> Deref<T> for RWLockWriteGuard<'a, T> {
     fn deref<'a>(&'a self) -> &'a T { &*self.data }
 }
@@ -810,5 +934,81 @@ mod tests {
             rx.recv();
         }
     }
-}
 
+    #[test]
+    fn test_mutex_arc_try_lock() {
+        let arc = Arc::new(Mutex::new(1));
+        {
+            let _lock = arc.lock();
+            assert!(arc.try_lock().is_none());
+            assert!(arc.lock_timeout(10).is_none());
+        }
+        let mut lock = arc.try_lock().unwrap();
+        *lock += 1;
+        drop(lock);
+        assert_eq!(*arc.lock_timeout(10).unwrap(), 2);
+    }
+
+    #[test] #[should_fail]
+    fn test_mutex_arc_try_lock_poison() {
+        let arc = Arc::new(Mutex::new(1));
+        let arc2 = arc.clone();
+        let _ = task::try(proc() {
+            let _lock = arc2.lock();
+            fail!();
+        });
+        arc.try_lock();
+    }
+
+    #[test]
+    fn test_rw_arc_try() {
+        let arc = Arc::new(RWLock::new(1));
+        {
+            let _read = arc.read();
+            assert!(arc.try_read().is_some());
+            assert!(arc.try_write().is_none());
+            assert!(arc.write_timeout(10).is_none());
+        }
+        {
+            let _write = arc.write();
+            assert!(arc.try_read().is_none());
+            assert!(arc.read_timeout(10).is_none());
+        }
+        *arc.try_write().unwrap() = 2;
+        assert_eq!(*arc.read_timeout(10).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_rw_arc_upgrade() {
+        let arc = Arc::new(RWLock::new(1));
+        let arc2 = arc.clone();
+        let (tx, rx) = channel();
+        let read = arc.upgradable_read();
+        assert_eq!(*read, 1);
+        task::spawn(proc() {
+            tx.send(());
+            let mut write = arc2.write();
+            *write *= 10;
+        });
+        rx.recv();
+        let mut write = read.upgrade();
+        // The other writer can't have gotten in between.
+        assert_eq!(*write, 1);
+        *write += 1;
+        drop(write);
+        while *arc.read() != 20 { task::deschedule(); }
+    }
+
+    #[test] #[should_fail]
+    fn test_rw_arc_poison_upgrade() {
+        let arc = Arc::new(RWLock::new(1));
+        let arc2 = arc.clone();
+        let _ = task::try(proc() {
+            let lock = arc2.upgradable_read();
+            let _lock = lock.upgrade();
+            fail!();
+        });
+        let lock = arc.read();
+        assert_eq!(*lock, 1);
+    }
+}
@@ -0,0 +1,255 @@
+// Copyright 2014 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! An LRU cache which can be shared by many tasks at once.
+//!
+//! The cache is split into a number of shards, each of which is a
+//! `collections::LruCache` protected by its own mutex. A key always lives in
+//! the same shard, chosen by its hash, so tasks using keys in different
+//! shards never contend with each other. Since even a lookup reorders its
+//! shard, there is no separate read lock.
+//!
+//! Recency is tracked per shard, so the entry evicted when a shard is full is
+//! the least recently used one of that shard, not necessarily of the whole
+//! cache. With keys spread evenly across shards this makes little difference.
+
+use std::default::Default;
+use std::hash::{Hash, Hasher};
+use std::hash::sip::SipHasher;
+use std::ty::Unsafe;
+
+use collections::lru_cache::{LruCache, CacheStats};
+use rand;
+use rand::Rng;
+
+use raw;
+
+static DEFAULT_SHARDS: uint = 16;
+
+/// A sharded LRU cache, usually shared among tasks through an `Arc`.
+///
+/// All operations take `&self`. Since no reference into the cache can outlive
+/// the lock of its shard, values are accessed either by copying them out or
+/// through closures which run with the shard locked. These closures must not
+/// access the same cache again, as that may deadlock.
+///
+/// # Example
+///
+/// ```
+/// use sync::{Arc, ShardedLruCache};
+///
+/// let cache = Arc::new(ShardedLruCache::new(1000));
+/// for i in range(0, 4) {
+///     let cache = cache.clone();
+///     spawn(proc() {
+///         cache.put(i, i * 10);
+///         assert_eq!(cache.get_copy(&i), Some(i * 10));
+///     });
+/// }
+/// ```
+pub struct ShardedLruCache<K, V> {
+    hasher: SipHasher,
+    shards: Vec<Shard<K, V>>,
+}
+
+struct Shard<K, V> {
+    lock: raw::Mutex,
+    cache: Unsafe<LruCache<K, V>>,
+}
+
+impl<K: TotalEq + Hash, V> Shard<K, V> {
+    fn with<R>(&self, f: |&mut LruCache<K, V>| -> R) -> R {
+        let _guard = self.lock.lock();
+        f(unsafe { &mut *self.cache.get() })
+    }
+}
+
+impl<K: TotalEq + Hash, V> ShardedLruCache<K, V> {
+    /// Creates a cache holding at most about `capacity` entries, split into
+    /// a default number of shards.
+    pub fn new(capacity: uint) -> ShardedLruCache<K, V> {
+        ShardedLruCache::with_shards(capacity, DEFAULT_SHARDS)
+    }
+
+    /// Creates a cache holding at most about `capacity` entries, split into
+    /// `shards` shards. Each shard gets an equal share of the capacity.
+    pub fn with_shards(capacity: uint, shards: uint) -> ShardedLruCache<K, V> {
+        let per_shard = (capacity + shards - 1) / shards;
+        ShardedLruCache::from_fn(shards, |_| LruCache::new(per_shard))
+    }
+
+    /// Creates a cache with `shards` shards, using `f` to create the cache
+    /// for each one. This is how shards are given a `Weigher`, `Clock` or
+    /// `EvictionListener`, and `f` is responsible for choosing each shard's
+    /// share of the total capacity.
+    pub fn from_fn(shards: uint, f: |uint| -> LruCache<K, V>) -> ShardedLruCache<K, V> {
+        assert!(shards > 0, "a sharded cache needs at least one shard");
+        let mut r = rand::task_rng();
+        ShardedLruCache {
+            hasher: SipHasher::new_with_keys(r.gen(), r.gen()),
+            shards: Vec::from_fn(shards, |i| Shard {
+                lock: raw::Mutex::new(),
+                cache: Unsafe::new(f(i)),
+            }),
+        }
+    }
+
+    fn shard<'a>(&'a self, k: &K) -> &'a Shard<K, V> {
+        // Use the high bits of the hash, as the low ones pick the bucket in
+        // the shard's map.
+        let hash = self.hasher.hash(k);
+        self.shards.get(((hash >> 32) as uint) % self.shards.len())
+    }
+
+    /// Puts a key-value pair into the cache.
+    pub fn put(&self, k: K, v: V) {
+        let shard = self.shard(&k);
+        let _guard = shard.lock.lock();
+        unsafe { (*shard.cache.get()).put(k, v) }
+    }
+
+    /// Puts a key-value pair into the cache, which expires once `ttl` has
+    /// passed on the clock of its shard.
+    ///
+    /// # Failure
+    ///
+    /// Fails if the shard has no clock.
+    pub fn put_with_ttl(&self, k: K, v: V, ttl: u64) {
+        let shard = self.shard(&k);
+        let _guard = shard.lock.lock();
+        unsafe { (*shard.cache.get()).put_with_ttl(k, v, ttl) }
+    }
+
+    /// Calls `f` with the value corresponding to the key while its shard is
+    /// locked, marking it as used, and returns its result, or `None` if the
+    /// key isn't present.
+    pub fn get_with<R>(&self, k: &K, f: |&V| -> R) -> Option<R> {
+        self.shard(k).with(|cache| cache.get(k).map(|v| f(v)))
+    }
+
+    /// Like `get_with`, but doesn't mark the value as used or count the
+    /// lookup in the statistics.
+    pub fn peek_with<R>(&self, k: &K, f: |&V| -> R) -> Option<R> {
+        self.shard(k).with(|cache| cache.peek(k).map(|v| f(v)))
+    }
+
+    /// Removes a key from the cache, returning the value at the key if the
+    /// key was previously in the cache.
+    pub fn pop(&self, k: &K) -> Option<V> {
+        self.shard(k).with(|cache| cache.pop(k))
+    }
+
+    /// Returns the number of entries in the cache. Other tasks may be
+    /// modifying the cache concurrently, so this is only a hint.
+    pub fn len(&self) -> uint {
+        self.shards.iter().fold(0, |n, shard| n + shard.with(|cache| cache.len()))
+    }
+
+    /// Returns the total weight of the entries in the cache, with the same
+    /// caveat as `len`.
+    pub fn weight(&self) -> uint {
+        self.shards.iter().fold(0, |n, shard| n + shard.with(|cache| cache.weight()))
+    }
+
+    /// Removes all entries from the cache, one shard at a time.
+    pub fn clear(&self) {
+        for shard in self.shards.iter() {
+            shard.with(|cache| cache.clear());
+        }
+    }
+
+    /// Removes all expired entries from the cache, one shard at a time.
+    pub fn purge_expired(&self) {
+        for shard in self.shards.iter() {
+            shard.with(|cache| cache.purge_expired());
+        }
+    }
+
+    /// Returns the sum of the statistics of all the shards.
+    pub fn stats(&self) -> CacheStats {
+        self.shards.iter().fold(Default::default(), |total: CacheStats, shard| {
+            total + shard.with(|cache| cache.stats())
+        })
+    }
+}
+
+impl<K: TotalEq + Hash, V: Clone> ShardedLruCache<K, V> {
+    /// Returns a copy of the value corresponding to the key, marking it as
+    /// used.
+    pub fn get_copy(&self, k: &K) -> Option<V> {
+        self.get_with(k, |v| v.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::task;
+
+    use arc::Arc;
+    use collections::LruCache;
+    use super::ShardedLruCache;
+
+    #[test]
+    fn test_put_get_pop() {
+        let c = ShardedLruCache::new(100);
+        c.put(1, 10);
+        c.put(2, 20);
+        assert_eq!(c.get_copy(&1), Some(10));
+        assert_eq!(c.get_with(&2, |v| *v + 1), Some(21));
+        assert_eq!(c.peek_with(&3, |v| *v), None);
+        assert_eq!(c.len(), 2);
+        assert_eq!(c.pop(&1), Some(10));
+        assert_eq!(c.get_copy(&1), None);
+
+        let stats = c.stats();
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.misses, 1);
+
+        c.clear();
+        assert_eq!(c.len(), 0);
+    }
+
+    #[test]
+    fn test_capacity() {
+        let c = ShardedLruCache::with_shards(40, 4);
+        for i in range(0, 1000) {
+            c.put(i, i);
+        }
+        assert_eq!(c.len(), 40);
+        assert_eq!(c.stats().evictions, 960);
+
+        let single = ShardedLruCache::from_fn(1, |_| LruCache::new(3));
+        for i in range(0, 5) {
+            single.put(i, i);
+        }
+        assert_eq!(single.get_copy(&1), None);
+        assert_eq!(single.get_copy(&2), Some(2));
+        assert_eq!(single.weight(), 3);
+    }
+
+    #[test]
+    fn test_concurrent() {
+        let c = Arc::new(ShardedLruCache::new(10000));
+        let (tx, rx) = channel();
+        for t in range(0u, 8) {
+            let (c, tx) = (c.clone(), tx.clone());
+            task::spawn(proc() {
+                for i in range(0u, 100) {
+                    c.put(t * 100 + i, t);
+                    assert_eq!(c.get_copy(&(t * 100 + i)), Some(t));
+                }
+                tx.send(());
+            });
+        }
+        for _ in range(0, 8) { rx.recv(); }
+        assert_eq!(c.len(), 800);
+        assert_eq!(c.stats().hits, 800);
+    }
+}
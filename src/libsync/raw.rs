@@ -16,7 +16,10 @@
 //! containing data.
 
 use std::cast;
+use std::comm::Select;
+use std::io::timer::Timer;
 use std::kinds::marker;
+use std::local_data;
 use std::mem::replace;
 use std::sync::atomics;
 use std::unstable::finally::Finally;
@@ -80,6 +83,73 @@ impl WaitQueue {
     }
 }
 
+// A point in time after which a timed acquisition gives up. A single deadline
+// is shared among all the blocking steps of acquiring a lock. The timer is
+// only started once one of them actually has to block, so an uncontended
+// acquisition never needs one.
+struct Deadline {
+    msecs: u64,
+    // Kept alive so that the oneshot below isn't cancelled.
+    timer: Option<Timer>,
+    timeout: Option<Receiver<()>>,
+    expired: bool,
+}
+
+// Timers are kept around between timed acquisitions by the same task rather
+// than creating one for each of them.
+local_data_key!(deadline_timer: Timer)
+
+impl Deadline {
+    fn new(msecs: u64) -> Deadline {
+        Deadline { msecs: msecs, timer: None, timeout: None, expired: false }
+    }
+
+    // Blocks until either `rx` is ready to be received from, in which case
+    // this returns true, or the deadline passes.
+    fn wait(&mut self, rx: &WaitEnd) -> bool {
+        if self.expired { return false }
+        if self.timeout.is_none() {
+            let mut timer = match local_data::pop(deadline_timer) {
+                Some(timer) => timer,
+                None => match Timer::new() {
+                    Ok(timer) => timer,
+                    Err(e) => fail!("timed lock acquisition requires a timer: {}", e),
+                },
+            };
+            self.timeout = Some(timer.oneshot(self.msecs));
+            self.timer = Some(timer);
+        }
+        let woken = {
+            let sel = Select::new();
+            let mut rx = sel.handle(rx);
+            let mut timeout = sel.handle(self.timeout.get_ref());
+            unsafe {
+                rx.add();
+                timeout.add();
+            }
+            sel.wait() == rx.id()
+        };
+        if !woken { self.expired = true; }
+        woken
+    }
+}
+
+impl Drop for Deadline {
+    fn drop(&mut self) {
+        match self.timer.take() {
+            Some(timer) => local_data::set(deadline_timer, timer),
+            None => {}
+        }
+    }
+}
+
+// How long an acquisition is willing to block for.
+enum Wait<'a> {
+    Blocking,
+    NonBlocking,
+    Until(&'a mut Deadline),
+}
+
 // The building-block used to make semaphores, mutexes, and rwlocks.
 struct Sem<Q> {
     lock: mutex::Mutex,
@@ -147,6 +217,60 @@ impl<Q: Send> Sem<Q> {
         }
     }
 
+    pub fn try_acquire(&self) -> bool {
+        let mut acquired = false;
+        unsafe {
+            self.with(|state| {
+                if state.count > 0 {
+                    state.count -= 1;
+                    acquired = true;
+                }
+            });
+        }
+        acquired
+    }
+
+    fn acquire_before(&self, deadline: &mut Deadline) -> bool {
+        let mut waiter_nobe = None;
+        unsafe {
+            self.with(|state| {
+                state.count -= 1;
+                if state.count < 0 {
+                    waiter_nobe = Some(state.waiters.wait_end());
+                }
+            });
+        }
+        if waiter_nobe.is_none() || deadline.wait(waiter_nobe.get_ref()) {
+            waiter_nobe.map(|w| { let _ = w.recv(); });
+            return true
+        }
+
+        // We timed out, but a signal may have raced with the timeout. Whether
+        // we got the resource is decided with the lock held, and if we didn't
+        // then our wait end is closed before the lock is released so that
+        // signal() skips over us from now on.
+        let mut acquired = false;
+        unsafe {
+            self.with(|state| {
+                let wait_end = waiter_nobe.take_unwrap();
+                if wait_end.try_recv().is_ok() {
+                    acquired = true;
+                } else {
+                    state.count += 1;
+                }
+            });
+        }
+        acquired
+    }
+
+    fn acquire_with(&self, wait: &mut Wait) -> bool {
+        match *wait {
+            Blocking => { self.acquire(); true }
+            NonBlocking => self.try_acquire(),
+            Until(ref mut deadline) => self.acquire_before(&mut **deadline),
+        }
+    }
+
     pub fn release(&self) {
         unsafe {
             self.with(|state| {
@@ -162,6 +286,14 @@ impl<Q: Send> Sem<Q> {
         self.acquire();
         SemGuard { sem: self }
     }
+
+    fn access_with<'a>(&'a self, wait: &mut Wait) -> Option<SemGuard<'a, Q>> {
+        if self.acquire_with(wait) {
+            Some(SemGuard { sem: self })
+        } else {
+            None
+        }
+    }
 }
 
 #[unsafe_destructor]
@@ -189,10 +321,17 @@ impl Sem<Vec<WaitQueue>> {
     // The only other places that condvars get built are rwlock.write_cond()
     // and rwlock_write_mode.
     pub fn access_cond<'a>(&'a self) -> SemCondGuard<'a> {
-        SemCondGuard {
-            guard: self.access(),
-            cvar: Condvar { sem: self, order: Nothing, nocopy: marker::NoCopy },
-        }
+        self.access_cond_with(&mut Blocking).unwrap()
+    }
+
+    fn access_cond_with<'a>(&'a self,
+                            wait: &mut Wait) -> Option<SemCondGuard<'a>> {
+        self.access_with(wait).map(|guard| {
+            SemCondGuard {
+                guard: guard,
+                cvar: Condvar { sem: self, order: Nothing, nocopy: marker::NoCopy },
+            }
+        })
     }
 }
 
@@ -202,6 +341,7 @@ impl Sem<Vec<WaitQueue>> {
 enum ReacquireOrderLock<'a> {
     Nothing, // c.c
     Just(&'a Semaphore),
+    Both(&'a Semaphore, &'a Semaphore),
 }
 
 /// A mechanism for atomic-unlock-and-deschedule blocking and signalling.
@@ -274,6 +414,11 @@ impl<'a> Condvar<'a> {
                         let _g = lock.access();
                         self.sem.acquire();
                     }
+                    Both(first, second) => {
+                        let _g1 = first.access();
+                        let _g2 = second.access();
+                        self.sem.acquire();
+                    }
                     Nothing => self.sem.acquire(),
                 }
             })
@@ -390,6 +535,38 @@ impl Semaphore {
     pub fn access<'a>(&'a self) -> SemaphoreGuard<'a> {
         SemaphoreGuard { guard: self.sem.access() }
     }
+
+    /// Attempts to acquire a resource without blocking. Returns whether a
+    /// resource was acquired.
+    pub fn try_acquire(&self) -> bool { self.sem.try_acquire() }
+
+    /// As acquire(), but gives up after blocking for `msecs` milliseconds.
+    /// Returns whether a resource was acquired.
+    ///
+    /// # Failure
+    ///
+    /// This function fails if the local runtime can't provide a timer.
+    pub fn acquire_timeout(&self, msecs: u64) -> bool {
+        self.sem.acquire_before(&mut Deadline::new(msecs))
+    }
+
+    /// As access(), but returns `None` instead of blocking if no resource is
+    /// available.
+    pub fn try_access<'a>(&'a self) -> Option<SemaphoreGuard<'a>> {
+        self.access_with(&mut NonBlocking)
+    }
+
+    /// As access(), but returns `None` if no resource could be acquired within
+    /// `msecs` milliseconds.
+    pub fn access_timeout<'a>(&'a self,
+                              msecs: u64) -> Option<SemaphoreGuard<'a>> {
+        self.access_with(&mut Until(&mut Deadline::new(msecs)))
+    }
+
+    fn access_with<'a>(&'a self,
+                       wait: &mut Wait) -> Option<SemaphoreGuard<'a>> {
+        self.sem.access_with(wait).map(|guard| SemaphoreGuard { guard: guard })
+    }
 }
 
 /****************************************************************************
@@ -433,8 +610,30 @@ impl Mutex {
     /// unlock the mutex when dropped. The associated condition variable can
     /// also be accessed through the returned guard.
     pub fn lock<'a>(&'a self) -> MutexGuard<'a> {
-        let SemCondGuard { guard, cvar } = self.sem.access_cond();
-        MutexGuard { guard: guard, cond: cvar }
+        self.lock_with(&mut Blocking).unwrap()
+    }
+
+    /// Attempts to acquire this mutex without blocking, returning `None` if
+    /// it is currently locked.
+    pub fn try_lock<'a>(&'a self) -> Option<MutexGuard<'a>> {
+        self.lock_with(&mut NonBlocking)
+    }
+
+    /// As lock(), but returns `None` if the mutex could not be acquired within
+    /// `msecs` milliseconds.
+    ///
+    /// # Failure
+    ///
+    /// This function fails if the local runtime can't provide a timer.
+    pub fn lock_timeout<'a>(&'a self, msecs: u64) -> Option<MutexGuard<'a>> {
+        self.lock_with(&mut Until(&mut Deadline::new(msecs)))
+    }
+
+    fn lock_with<'a>(&'a self, wait: &mut Wait) -> Option<MutexGuard<'a>> {
+        self.sem.access_cond_with(wait).map(|guard| {
+            let SemCondGuard { guard, cvar } = guard;
+            MutexGuard { guard: guard, cond: cvar }
+        })
     }
 }
 
@@ -444,8 +643,29 @@ impl Mutex {
 
 // NB: Wikipedia - Readers-writers_problem#The_third_readers-writers_problem
 
+/// The order in which an `RWLock` admits contending readers and writers.
+#[deriving(Eq, Clone, Show)]
+pub enum RWLockPolicy {
+    /// Readers and writers are admitted roughly in the order in which they
+    /// arrive. A writer waits for the readers which arrived before it, but
+    /// readers arriving after a waiting writer queue up behind it. This is
+    /// the default.
+    ArrivalOrder,
+    /// As long as any writer is waiting for or holding the lock, no new
+    /// readers are admitted. Writers can't be starved by a steady stream of
+    /// readers, at the expense of readers possibly being starved by writers.
+    /// Note that this means that recursively acquiring a read lock can
+    /// deadlock if a writer arrives in between.
+    PreferWriters,
+}
+
 /// A blocking, no-starvation, reader-writer lock with an associated condvar.
 ///
+/// Besides reading and writing, the lock can be acquired in "upgradable read"
+/// mode. An upgradable reader coexists with other readers but excludes writers
+/// and other upgradable readers, and it can later atomically be turned into a
+/// writer: no other writer can sneak in between the read and the write.
+///
 /// # Failure
 ///
 /// A task which fails while holding an rwlock will unlock the rwlock as it
@@ -454,6 +674,19 @@ pub struct RWLock {
     order_lock:  Semaphore,
     access_lock: Sem<Vec<WaitQueue>>,
 
+    // Held by upgradable readers for as long as they hold the lock, and by
+    // writers while they acquire it. This is acquired before the order lock
+    // and the access lock, so that no writer can get in between an upgrader
+    // releasing its read lock and acquiring the write lock.
+    upgrade_lock: Semaphore,
+
+    // Only used with `PreferWriters`. The first writer to arrive closes this
+    // gate on behalf of all writers, the last writer to leave opens it again,
+    // and readers have to pass through it before anything else.
+    reader_gate: Semaphore,
+    writers: atomics::AtomicUint,
+    policy: RWLockPolicy,
+
     // The only way the count flag is ever accessed is with xadd. Since it is
     // a read-modify-write operation, multiple xadds on different cores will
     // always be consistent with respect to each other, so a monotonic/relaxed
@@ -471,6 +704,15 @@ pub struct RWLockReadGuard<'a> {
     lock: &'a RWLock,
 }
 
+/// An RAII helper which is created by acquiring an upgradable read lock on an
+/// RWLock. When dropped, this will unlock the RWLock.
+///
+/// A value of this type can be consumed to upgrade to a write lock.
+#[must_use]
+pub struct RWLockUpgradableGuard<'a> {
+    lock: &'a RWLock,
+}
+
 /// An RAII helper which is created by acquiring a write lock on an RWLock. When
 /// dropped, this will unlock the RWLock.
 ///
@@ -478,6 +720,8 @@ pub struct RWLockReadGuard<'a> {
 #[must_use]
 pub struct RWLockWriteGuard<'a> {
     lock: &'a RWLock,
+    // Whether this writer has to leave through the reader gate.
+    gated: bool,
     /// Inner condition variable that is connected to the write-mode of the
     /// outer rwlock.
     pub cond: Condvar<'a>,
@@ -490,23 +734,75 @@ impl RWLock {
     /// Create a new rwlock, with a specified number of associated condvars.
     /// Similar to mutex_with_condvars.
     pub fn new_with_condvars(num_condvars: uint) -> RWLock {
+        RWLock::new_with_policy(num_condvars, ArrivalOrder)
+    }
+
+    /// Create a new rwlock, with a specified number of associated condvars and
+    /// the given policy for ordering readers and writers.
+    pub fn new_with_policy(num_condvars: uint, policy: RWLockPolicy) -> RWLock {
         RWLock {
             order_lock: Semaphore::new(1),
             access_lock: Sem::new_and_signal(1, num_condvars),
+            upgrade_lock: Semaphore::new(1),
+            reader_gate: Semaphore::new(1),
+            writers: atomics::AtomicUint::new(0),
+            policy: policy,
             read_count: atomics::AtomicUint::new(0),
         }
     }
 
+    /// Returns the policy this lock was created with.
+    pub fn policy(&self) -> RWLockPolicy { self.policy }
+
     /// Acquires a read-lock, returning an RAII guard that will unlock the lock
     /// when dropped. Calls to 'read' from other tasks may run concurrently with
     /// this one.
     pub fn read<'a>(&'a self) -> RWLockReadGuard<'a> {
-        let _guard = self.order_lock.access();
-        let old_count = self.read_count.fetch_add(1, atomics::Acquire);
-        if old_count == 0 {
-            self.access_lock.acquire();
-        }
-        RWLockReadGuard { lock: self }
+        self.read_with(&mut Blocking).unwrap()
+    }
+
+    /// Attempts to acquire a read-lock without blocking, returning `None` if
+    /// the lock is currently held by a writer.
+    pub fn try_read<'a>(&'a self) -> Option<RWLockReadGuard<'a>> {
+        self.read_with(&mut NonBlocking)
+    }
+
+    /// As read(), but returns `None` if the lock could not be acquired within
+    /// `msecs` milliseconds.
+    ///
+    /// # Failure
+    ///
+    /// This function fails if the local runtime can't provide a timer.
+    pub fn read_timeout<'a>(&'a self,
+                            msecs: u64) -> Option<RWLockReadGuard<'a>> {
+        self.read_with(&mut Until(&mut Deadline::new(msecs)))
+    }
+
+    /// Acquires an upgradable read-lock, returning an RAII guard that will
+    /// unlock the lock when dropped. Calls to 'read' from other tasks may run
+    /// concurrently with this one, but calls to 'write' and 'upgradable_read'
+    /// will block.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use sync::raw::RWLock;
+    ///
+    /// let lock = RWLock::new();
+    /// let read = lock.upgradable_read();
+    /// // ... shared access, decide that a write is needed ...
+    /// let write = read.upgrade();
+    /// // ... exclusive access ...
+    /// drop(write);
+    /// ```
+    pub fn upgradable_read<'a>(&'a self) -> RWLockUpgradableGuard<'a> {
+        self.upgradable_read_with(&mut Blocking).unwrap()
+    }
+
+    /// Attempts to acquire an upgradable read-lock without blocking.
+    pub fn try_upgradable_read<'a>(&'a self)
+                                   -> Option<RWLockUpgradableGuard<'a>> {
+        self.upgradable_read_with(&mut NonBlocking)
     }
 
     /// Acquire a write-lock, returning an RAII guard that will unlock the lock
@@ -530,9 +826,132 @@ impl RWLock {
     /// drop(read);
     /// ```
     pub fn write<'a>(&'a self) -> RWLockWriteGuard<'a> {
-        let _g = self.order_lock.access();
-        self.access_lock.acquire();
+        self.write_with(&mut Blocking).unwrap()
+    }
+
+    /// Attempts to acquire a write-lock without blocking, returning `None` if
+    /// the lock is currently held by anyone else.
+    pub fn try_write<'a>(&'a self) -> Option<RWLockWriteGuard<'a>> {
+        self.write_with(&mut NonBlocking)
+    }
+
+    /// As write(), but returns `None` if the lock could not be acquired within
+    /// `msecs` milliseconds.
+    ///
+    /// # Failure
+    ///
+    /// This function fails if the local runtime can't provide a timer.
+    pub fn write_timeout<'a>(&'a self,
+                             msecs: u64) -> Option<RWLockWriteGuard<'a>> {
+        self.write_with(&mut Until(&mut Deadline::new(msecs)))
+    }
+
+    fn read_with<'a>(&'a self, wait: &mut Wait) -> Option<RWLockReadGuard<'a>> {
+        if !self.pass_reader_gate(wait) { return None }
+        if self.read_shared(wait) {
+            Some(RWLockReadGuard { lock: self })
+        } else {
+            None
+        }
+    }
+
+    fn upgradable_read_with<'a>(&'a self, wait: &mut Wait)
+                                -> Option<RWLockUpgradableGuard<'a>> {
+        // The gate has to be passed before taking the upgrade lock, because a
+        // writer holds the gate while waiting for the upgrade lock.
+        if !self.pass_reader_gate(wait) { return None }
+        if !self.upgrade_lock.sem.acquire_with(wait) { return None }
+        if self.read_shared(wait) {
+            Some(RWLockUpgradableGuard { lock: self })
+        } else {
+            self.upgrade_lock.release();
+            None
+        }
+    }
+
+    fn write_with<'a>(&'a self, wait: &mut Wait) -> Option<RWLockWriteGuard<'a>> {
+        // A writer that won't wait for the lock has no use for keeping readers
+        // out in the meantime.
+        let gated = match *wait {
+            NonBlocking => false,
+            _ => self.close_reader_gate(),
+        };
+        let acquired = self.upgrade_lock.sem.acquire_with(wait) && {
+            let acquired = self.write_exclusive(wait);
+            self.upgrade_lock.release();
+            acquired
+        };
+        if acquired {
+            Some(self.write_guard(gated))
+        } else {
+            if gated { self.open_reader_gate() }
+            None
+        }
+    }
+
+    fn pass_reader_gate(&self, wait: &mut Wait) -> bool {
+        if self.policy != PreferWriters { return true }
+        if !self.reader_gate.sem.acquire_with(wait) { return false }
+        self.reader_gate.release();
+        true
+    }
+
+    // Returns whether the caller is now responsible for opening the gate
+    // again. Closing the gate only ever waits on readers passing through it or
+    // on the previous generation of writers leaving, so it always blocks.
+    fn close_reader_gate(&self) -> bool {
+        if self.policy != PreferWriters { return false }
+        if self.writers.fetch_add(1, atomics::SeqCst) == 0 {
+            self.reader_gate.acquire();
+        }
+        true
+    }
+
+    fn open_reader_gate(&self) {
+        if self.writers.fetch_sub(1, atomics::SeqCst) == 1 {
+            self.reader_gate.release();
+        }
+    }
 
+    fn read_shared(&self, wait: &mut Wait) -> bool {
+        if !self.order_lock.sem.acquire_with(wait) { return false }
+        let old_count = self.read_count.fetch_add(1, atomics::Acquire);
+        let mut acquired = old_count != 0 || self.access_lock.acquire_with(wait);
+        if !acquired {
+            // No other reader can have touched the count, we're still holding
+            // the order lock. A writer downgrading can have, though, in which
+            // case it is handing the access lock over to us (nobody else can
+            // be waiting for it without the order lock) and we take it after
+            // all rather than leave it with a reader count of zero.
+            if self.read_count.compare_and_swap(1, 0, atomics::Release) != 1 {
+                self.access_lock.acquire();
+                acquired = true;
+            }
+        }
+        self.order_lock.release();
+        acquired
+    }
+
+    fn release_shared(&self) {
+        let old_count = self.read_count.fetch_sub(1, atomics::Release);
+        assert!(old_count > 0);
+        if old_count == 1 {
+            // Note: this release used to be outside of a locked access
+            // to exclusive-protected state. If this code is ever
+            // converted back to such (instead of using atomic ops),
+            // this access MUST NOT go inside the exclusive access.
+            self.access_lock.release();
+        }
+    }
+
+    fn write_exclusive(&self, wait: &mut Wait) -> bool {
+        if !self.order_lock.sem.acquire_with(wait) { return false }
+        let acquired = self.access_lock.acquire_with(wait);
+        self.order_lock.release();
+        acquired
+    }
+
+    fn write_guard<'a>(&'a self, gated: bool) -> RWLockWriteGuard<'a> {
         // It's important to thread our order lock into the condvar, so that
         // when a cond.wait() wakes up, it uses it while reacquiring the
         // access lock. If we permitted a waking-up writer to "cut in line",
@@ -559,11 +978,15 @@ impl RWLock {
         // which can't happen until T2 finishes the downgrade-read entirely.
         // The astute reader will also note that making waking writers use the
         // order_lock is better for not starving readers.
+        //
+        // Waking writers also go through the upgrade lock first, for the same
+        // reason as any other writer.
         RWLockWriteGuard {
             lock: self,
+            gated: gated,
             cond: Condvar {
                 sem: &self.access_lock,
-                order: Just(&self.order_lock),
+                order: Both(&self.upgrade_lock, &self.order_lock),
                 nocopy: marker::NoCopy,
             }
         }
@@ -574,6 +997,7 @@ impl<'a> RWLockWriteGuard<'a> {
     /// Consumes this write lock and converts it into a read lock.
     pub fn downgrade(self) -> RWLockReadGuard<'a> {
         let lock = self.lock;
+        let gated = self.gated;
         // Don't run the destructor of the write guard, we're in charge of
         // things from now on
         unsafe { cast::forget(self) }
@@ -589,29 +1013,50 @@ impl<'a> RWLockWriteGuard<'a> {
             // the comment in write_cond for more justification.
             lock.access_lock.release();
         }
+        if gated { lock.open_reader_gate() }
         RWLockReadGuard { lock: lock }
     }
 }
 
+impl<'a> RWLockUpgradableGuard<'a> {
+    /// Consumes this upgradable read lock and converts it into a write lock,
+    /// blocking until all other readers have left.
+    ///
+    /// No other writer can acquire the lock in between, so anything observed
+    /// through this read lock still holds once the write lock is acquired.
+    pub fn upgrade(self) -> RWLockWriteGuard<'a> {
+        let lock = self.lock;
+        unsafe { cast::forget(self) }
+
+        lock.release_shared();
+        // The gate isn't closed on our behalf: a writer closing it may be
+        // waiting on the upgrade lock that we hold.
+        lock.write_exclusive(&mut Blocking);
+        lock.upgrade_lock.release();
+        lock.write_guard(false)
+    }
+}
+
 #[unsafe_destructor]
 impl<'a> Drop for RWLockWriteGuard<'a> {
     fn drop(&mut self) {
         self.lock.access_lock.release();
+        if self.gated { self.lock.open_reader_gate() }
     }
 }
 
 #[unsafe_destructor]
 impl<'a> Drop for RWLockReadGuard<'a> {
     fn drop(&mut self) {
-        let old_count = self.lock.read_count.fetch_sub(1, atomics::Release);
-        assert!(old_count > 0);
-        if old_count == 1 {
-            // Note: this release used to be outside of a locked access
-            // to exclusive-protected state. If this code is ever
-            // converted back to such (instead of using atomic ops),
-            // this access MUST NOT go inside the exclusive access.
-            self.lock.access_lock.release();
-        }
+        self.lock.release_shared();
+    }
+}
+
+#[unsafe_destructor]
+impl<'a> Drop for RWLockUpgradableGuard<'a> {
+    fn drop(&mut self) {
+        self.lock.release_shared();
+        self.lock.upgrade_lock.release();
     }
 }
 
@@ -622,10 +1067,11 @@ impl<'a> Drop for RWLockReadGuard<'a> {
 #[cfg(test)]
 mod tests {
     use arc::Arc;
-    use super::{Semaphore, Mutex, RWLock, Condvar};
+    use super::{Semaphore, Mutex, RWLock, Condvar, PreferWriters};
 
     use std::cast;
     use std::result;
+    use std::sync::atomics;
     use std::task;
 
     /************************************************************************
@@ -716,6 +1162,54 @@ mod tests {
         }
         rx.recv(); // wait for child to be done
     }
+    #[test]
+    fn test_sem_try_acquire() {
+        let s = Semaphore::new(1);
+        assert!(s.try_acquire());
+        assert!(!s.try_acquire());
+        assert!(s.try_access().is_none());
+        s.release();
+        let g = s.try_access();
+        assert!(g.is_some());
+        assert!(!s.try_acquire());
+        drop(g);
+        assert!(s.try_acquire());
+    }
+    #[test]
+    fn test_sem_acquire_timeout() {
+        let s = Semaphore::new(0);
+        assert!(!s.acquire_timeout(10));
+        assert!(s.access_timeout(10).is_none());
+        // Timing out must not have leaked any resources.
+        s.release();
+        assert!(s.acquire_timeout(10));
+        assert!(!s.try_acquire());
+    }
+    #[test]
+    fn test_sem_timeout_then_signal() {
+        // A waiter which gave up must not swallow the wakeup of the next one.
+        let s = Arc::new(Semaphore::new(0));
+        let s2 = s.clone();
+        let (tx, rx) = channel();
+        assert!(!s.acquire_timeout(1));
+        task::spawn(proc() {
+            s2.acquire();
+            tx.send(());
+        });
+        for _ in range(0, 5) { task::deschedule(); }
+        s.release();
+        rx.recv();
+    }
+    #[test]
+    fn test_sem_acquire_timeout_success() {
+        let s = Arc::new(Semaphore::new(0));
+        let s2 = s.clone();
+        task::spawn(proc() {
+            for _ in range(0, 5) { task::deschedule(); }
+            s2.release();
+        });
+        assert!(s.acquire_timeout(60 * 1000));
+    }
     /************************************************************************
      * Mutex tests
      ************************************************************************/
@@ -856,6 +1350,30 @@ mod tests {
         lock.cond.wait();
     }
     #[test]
+    fn test_mutex_try_lock() {
+        let m = Mutex::new();
+        {
+            let _g = m.lock();
+            assert!(m.try_lock().is_none());
+            assert!(m.lock_timeout(10).is_none());
+        }
+        let g = m.try_lock();
+        assert!(g.is_some());
+        drop(g);
+        assert!(m.lock_timeout(10).is_some());
+    }
+    #[test]
+    fn test_mutex_timeout_cond() {
+        let m = Arc::new(Mutex::new());
+        let m2 = m.clone();
+        let lock = m.lock_timeout(10).unwrap();
+        task::spawn(proc() {
+            let lock = m2.lock();
+            assert!(lock.cond.signal());
+        });
+        lock.cond.wait();
+    }
+    #[test]
     fn test_mutex_no_condvars() {
         let result = task::try(proc() {
             let m = Mutex::new_with_condvars(0);
@@ -1115,4 +1633,131 @@ mod tests {
         rwlock_kill_helper(Downgrade, DowngradeRead);
         rwlock_kill_helper(Downgrade, DowngradeRead);
     }
+    #[test]
+    fn test_rwlock_try() {
+        let x = RWLock::new();
+        {
+            let _r = x.read();
+            assert!(x.try_read().is_some());
+            assert!(x.try_write().is_none());
+            assert!(x.write_timeout(10).is_none());
+        }
+        {
+            let _w = x.write();
+            assert!(x.try_read().is_none());
+            assert!(x.try_write().is_none());
+            assert!(x.read_timeout(10).is_none());
+            assert!(x.try_upgradable_read().is_none());
+        }
+        assert!(x.try_write().is_some());
+        assert!(x.read_timeout(10).is_some());
+        assert!(x.write_timeout(10).is_some());
+    }
+    #[test]
+    fn test_rwlock_upgradable() {
+        let x = RWLock::new();
+        let u = x.upgradable_read();
+        assert!(x.try_read().is_some());
+        assert!(x.try_upgradable_read().is_none());
+        assert!(x.try_write().is_none());
+        let w = u.upgrade();
+        assert!(x.try_read().is_none());
+        let r = w.downgrade();
+        assert!(x.try_upgradable_read().is_some());
+        drop(r);
+        assert!(x.try_write().is_some());
+    }
+    #[test]
+    fn test_rwlock_upgrade_waits_for_readers() {
+        let x = Arc::new(RWLock::new());
+        let x2 = x.clone();
+        let (tx, rx) = channel();
+        let (tx2, rx2) = channel();
+        task::spawn(proc() {
+            let _r = x2.read();
+            tx.send(());
+            rx2.recv();
+        });
+        rx.recv();
+        let u = x.upgradable_read();
+        tx2.send(());
+        // Blocks until the reader above is gone.
+        let _w = u.upgrade();
+        assert!(x.try_read().is_none());
+    }
+    #[test]
+    fn test_rwlock_upgrade_excludes_writers() {
+        let x = Arc::new(RWLock::new());
+        let data: Arc<atomics::AtomicUint> = Arc::new(atomics::AtomicUint::new(0));
+        let (x2, data2) = (x.clone(), data.clone());
+        let (tx, rx) = channel();
+        let u = x.upgradable_read();
+        task::spawn(proc() {
+            tx.send(());
+            let _w = x2.write();
+            data2.store(2, atomics::SeqCst);
+        });
+        rx.recv();
+        for _ in range(0, 5) { task::deschedule(); }
+        let observed = data.load(atomics::SeqCst);
+        let _w = u.upgrade();
+        assert_eq!(data.load(atomics::SeqCst), observed);
+    }
+    #[test]
+    fn test_rwlock_prefer_writers() {
+        let x = Arc::new(RWLock::new_with_policy(1, PreferWriters));
+        let x2 = x.clone();
+        let (tx, rx) = channel();
+        let r = x.read();
+        task::spawn(proc() {
+            tx.send(());
+            drop(x2.write());
+            tx.send(());
+        });
+        rx.recv();
+        // Wait for the writer to be queued up, after which new readers are
+        // turned away.
+        while x.try_read().is_some() { task::deschedule(); }
+        drop(r);
+        rx.recv();
+        assert!(x.try_read().is_some());
+        assert!(x.try_write().is_some());
+    }
+    #[test]
+    fn test_rwlock_prefer_writers_cond() {
+        let x = Arc::new(RWLock::new_with_policy(1, PreferWriters));
+        let x2 = x.clone();
+        let lock = x.write();
+        task::spawn(proc() {
+            let lock = x2.write();
+            assert!(lock.cond.signal());
+        });
+        lock.cond.wait();
+        drop(lock);
+        drop(x.read());
+    }
+    #[test]
+    fn test_rwlock_timeout_downgrade_race() {
+        // A reader giving up just as a writer downgrades must not leave the
+        // access lock free while the downgraded reader still holds it.
+        for _ in range(0, 100) {
+            let x = Arc::new(RWLock::new());
+            let x2 = x.clone();
+            let (tx, rx) = channel();
+            let (done_tx, done_rx) = channel();
+            let w = x.write();
+            task::spawn(proc() {
+                let r = x2.read_timeout(1);
+                tx.send(());
+                done_rx.recv();
+                drop(r);
+            });
+            task::deschedule();
+            let r = w.downgrade();
+            rx.recv();
+            assert!(x.try_write().is_none());
+            done_tx.send(());
+            drop(r);
+        }
+    }
 }
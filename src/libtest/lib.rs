@@ -37,6 +37,7 @@
 
 extern crate collections;
 extern crate getopts;
+extern crate num;
 extern crate serialize;
 extern crate term;
 extern crate time;
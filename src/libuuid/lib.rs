@@ -37,6 +37,30 @@ fn main() {
 }
  ```
 
+To create a name-based (V5) UUID, which is always the same for the same
+namespace and name:
+
+```rust
+use uuid::{Uuid, NAMESPACE_DNS};
+
+let uuid = Uuid::new_v5(&NAMESPACE_DNS, "www.example.com".as_bytes());
+assert_eq!(uuid.to_hyphenated_str(), "2ed6657d-e927-568b-95e1-2665a8aea6a2".to_owned());
+```
+
+# Versions
+
+* V1 UUIDs combine a timestamp, a clock sequence and a node ID, normally
+  a MAC address. Use a `UuidContext` to control the node ID and clock
+  sequence.
+* V3 and V5 UUIDs are derived from a namespace UUID and a name by hashing
+  them with MD5 and SHA-1 respectively.
+* V4 UUIDs are random.
+* V7 UUIDs start with a millisecond Unix timestamp followed by random
+  bits, so that sorting them by their bytes or strings sorts them by
+  creation time. This makes them good keys for database indexes.
+
+Version 2 (DCE security) UUIDs are not supported.
+
 # Strings
 
 Examples of string representations:
@@ -68,8 +92,10 @@ Examples of string representations:
 #[cfg(test)]
 extern crate test;
 
+extern crate crypto;
 extern crate rand;
 extern crate serialize;
+extern crate time;
 
 use std::cast::{transmute,transmute_copy};
 use std::char::Char;
@@ -81,10 +107,16 @@ use std::num::FromStrRadix;
 use std::str;
 use std::slice;
 
+use crypto::Digest;
+use crypto::md5::Md5;
+use crypto::sha1::Sha1;
+
 use rand::Rng;
 
 use serialize::{Encoder, Encodable, Decoder, Decodable};
 
+use time::Timespec;
+
 /// A 128-bit (16 byte) buffer containing the ID
 pub type UuidBytes = [u8, ..16];
 
@@ -101,6 +133,8 @@ pub enum UuidVersion {
     Version4Random = 4,
     /// Version 5: SHA-1 hash
     Version5Sha1   = 5,
+    /// Version 7: Unix timestamp, sortable
+    Version7UnixTime = 7,
 }
 
 /// The reserved variants of UUIDs
@@ -122,6 +156,27 @@ pub struct Uuid {
     bytes: UuidBytes
 }
 
+/// The namespace for fully-qualified domain names (RFC4122 Appendix C)
+pub static NAMESPACE_DNS: Uuid = Uuid { bytes: [
+    0x6b, 0xa7, 0xb8, 0x10, 0x9d, 0xad, 0x11, 0xd1,
+    0x80, 0xb4, 0x00, 0xc0, 0x4f, 0xd4, 0x30, 0xc8] };
+/// The namespace for URLs (RFC4122 Appendix C)
+pub static NAMESPACE_URL: Uuid = Uuid { bytes: [
+    0x6b, 0xa7, 0xb8, 0x11, 0x9d, 0xad, 0x11, 0xd1,
+    0x80, 0xb4, 0x00, 0xc0, 0x4f, 0xd4, 0x30, 0xc8] };
+/// The namespace for ISO object identifiers (RFC4122 Appendix C)
+pub static NAMESPACE_OID: Uuid = Uuid { bytes: [
+    0x6b, 0xa7, 0xb8, 0x12, 0x9d, 0xad, 0x11, 0xd1,
+    0x80, 0xb4, 0x00, 0xc0, 0x4f, 0xd4, 0x30, 0xc8] };
+/// The namespace for X.500 distinguished names (RFC4122 Appendix C)
+pub static NAMESPACE_X500: Uuid = Uuid { bytes: [
+    0x6b, 0xa7, 0xb8, 0x14, 0x9d, 0xad, 0x11, 0xd1,
+    0x80, 0xb4, 0x00, 0xc0, 0x4f, 0xd4, 0x30, 0xc8] };
+
+// Number of 100ns intervals between the start of the Gregorian calendar,
+// 1582-10-15, and the Unix epoch
+static GREGORIAN_OFFSET: u64 = 0x01B21DD213814000;
+
 impl<S: Writer> Hash<S> for Uuid {
     fn hash(&self, state: &mut S) {
         self.bytes.hash(state)
@@ -181,13 +236,60 @@ impl Uuid {
     }
 
     /// Create a new UUID of the specified version
+    ///
+    /// Returns `None` for the versions which can't be generated without
+    /// further information: V2, and the name-based V3 and V5.
     pub fn new(v: UuidVersion) -> Option<Uuid> {
         match v {
+            Version1Mac => Some(Uuid::new_v1()),
             Version4Random => Some(Uuid::new_v4()),
+            Version7UnixTime => Some(Uuid::new_v7()),
             _ => None
         }
     }
 
+    /// Creates a new time-based UUID from the current time
+    ///
+    /// A random node ID and clock sequence are used, so this doesn't reveal
+    /// the MAC address of the host. Use a `UuidContext` to choose the node
+    /// ID, or to generate many V1 UUIDs which are guaranteed to differ.
+    pub fn new_v1() -> Uuid {
+        UuidContext::with_random_node().new_v1()
+    }
+
+    /// Creates a name-based UUID by hashing a namespace UUID and a name
+    /// with MD5
+    ///
+    /// Prefer `new_v5` unless compatibility requires V3.
+    pub fn new_v3(namespace: &Uuid, name: &[u8]) -> Uuid {
+        let mut md5 = Md5::new();
+        md5.input(namespace.bytes);
+        md5.input(name);
+        Uuid::from_hash(md5.result_bytes().as_slice(), Version3Md5)
+    }
+
+    /// Creates a name-based UUID by hashing a namespace UUID and a name
+    /// with SHA-1
+    ///
+    /// The same namespace and name always give the same UUID, which makes
+    /// these suitable for deterministic IDs. The namespace is usually one
+    /// of `NAMESPACE_DNS`, `NAMESPACE_URL`, `NAMESPACE_OID` or
+    /// `NAMESPACE_X500`, but can be any UUID.
+    pub fn new_v5(namespace: &Uuid, name: &[u8]) -> Uuid {
+        let mut sha1 = Sha1::new();
+        sha1.input(namespace.bytes);
+        sha1.input(name);
+        Uuid::from_hash(sha1.result_bytes().as_slice(), Version5Sha1)
+    }
+
+    fn from_hash(hash: &[u8], v: UuidVersion) -> Uuid {
+        let mut uuid = Uuid{ bytes: [0, .. 16] };
+        slice::bytes::copy_memory(uuid.bytes, hash.slice_to(16));
+        uuid.set_variant(VariantRFC4122);
+        uuid.set_version(v);
+        uuid
+    }
+
     /// Creates a new random UUID
     ///
     /// Uses the `rand` module's default RNG task as the source
@@ -202,6 +304,33 @@ impl Uuid {
         uuid
     }
 
+    /// Creates a new time-ordered UUID from the current time
+    ///
+    /// V7 UUIDs created in different milliseconds sort in the order they
+    /// were created. Within a millisecond the order is random; use
+    /// `UuidContext::new_v7` for UUIDs which always increase.
+    pub fn new_v7() -> Uuid {
+        Uuid::v7_from_parts(unix_millis(), rand::task_rng().gen())
+    }
+
+    /// Assembles a V7 UUID from a millisecond timestamp, whose low 48 bits
+    /// are used, and the low 12 and 62 bits of the two parts of `rand`
+    fn v7_from_parts(millis: u64, rand: (u16, u64)) -> Uuid {
+        let (rand_a, rand_b) = rand;
+        let mut uuid = Uuid{ bytes: [0, .. 16] };
+        for i in range(0u, 6) {
+            uuid.bytes[i] = (millis >> (40 - 8 * i)) as u8;
+        }
+        uuid.bytes[6] = (rand_a >> 8) as u8;
+        uuid.bytes[7] = rand_a as u8;
+        for i in range(0u, 8) {
+            uuid.bytes[8 + i] = (rand_b >> (56 - 8 * i)) as u8;
+        }
+        uuid.set_variant(VariantRFC4122);
+        uuid.set_version(Version7UnixTime);
+        uuid
+    }
+
     /// Creates a UUID using the supplied field values
     ///
     /// # Arguments
@@ -288,9 +417,10 @@ impl Uuid {
     ///
     /// This represents the algorithm used to generate the contents.
     ///
-    /// Currently only the Random (V4) algorithm is supported by this
+    /// All versions except DCE Security (V2) are supported by this
     /// module.  There are security and privacy implications for using
-    /// older versions - see [Wikipedia: Universally Unique Identifier](
+    /// V1, which can reveal the MAC address and clock of the host, and
+    /// for the weak hash of V3 - see [Wikipedia: Universally Unique Identifier](
     /// http://en.wikipedia.org/wiki/Universally_unique_identifier) for
     /// details.
     ///
@@ -310,6 +440,40 @@ impl Uuid {
             3 => Some(Version3Md5),
             4 => Some(Version4Random),
             5 => Some(Version5Sha1),
+            7 => Some(Version7UnixTime),
+            _ => None
+        }
+    }
+
+    /// Returns the time at which a V1 or V7 UUID was created
+    ///
+    /// V1 timestamps have a precision of 100 nanoseconds, and V7 ones of a
+    /// millisecond. Returns `None` for UUIDs of any other version, which
+    /// don't contain a timestamp.
+    pub fn get_timestamp(&self) -> Option<Timespec> {
+        match self.get_version() {
+            Some(Version1Mac) => {
+                let b = self.bytes;
+                let ticks = (b[6] & 0x0f) as u64 << 56 | b[7] as u64 << 48 |
+                            b[4] as u64 << 40 | b[5] as u64 << 32 |
+                            b[0] as u64 << 24 | b[1] as u64 << 16 |
+                            b[2] as u64 << 8 | b[3] as u64;
+                // Timestamps before 1970 are negative
+                let unix = (ticks - GREGORIAN_OFFSET) as i64;
+                let mut sec = unix / 10_000_000;
+                let mut rem = unix % 10_000_000;
+                if rem < 0 {
+                    sec -= 1;
+                    rem += 10_000_000;
+                }
+                Some(Timespec::new(sec, (rem * 100) as i32))
+            }
+            Some(Version7UnixTime) => {
+                let millis = self.bytes.slice_to(6).iter()
+                                 .fold(0u64, |n, &b| n << 8 | b as u64);
+                Some(Timespec::new((millis / 1000) as i64,
+                                   (millis % 1000) as i32 * 1_000_000))
+            }
             _ => None
         }
     }
@@ -445,6 +609,146 @@ impl Uuid {
     }
 }
 
+/// The current time in milliseconds since the Unix epoch
+fn unix_millis() -> u64 {
+    let now = time::get_time();
+    now.sec as u64 * 1000 + now.nsec as u64 / 1_000_000
+}
+
+/// The current time in 100ns intervals since the start of the Gregorian
+/// calendar, as used by V1 UUIDs
+fn gregorian_ticks() -> u64 {
+    let now = time::get_time();
+    now.sec as u64 * 10_000_000 + now.nsec as u64 / 100 + GREGORIAN_OFFSET
+}
+
+/// A generator of V1 and V7 UUIDs
+///
+/// A context holds the node ID and clock sequence of the V1 UUIDs it
+/// generates, and remembers the last timestamps it used. This guarantees
+/// that it never generates the same V1 UUID twice, even when the clock
+/// doesn't advance between calls, and that each V7 UUID it generates is
+/// greater than the previous one.
+///
+/// # Example
+///
+/// ```rust
+/// use uuid::UuidContext;
+///
+/// let mut ctx = UuidContext::new([0x00, 0x1b, 0x63, 0x84, 0x45, 0xe6]);
+/// let a = ctx.new_v1();
+/// let b = ctx.new_v1();
+/// assert!(a != b);
+/// ```
+pub struct UuidContext {
+    node: [u8, ..6],
+    clock_seq: u16,
+    last_ticks: u64,
+    last_millis: u64,
+    counter: u16,
+}
+
+// V7 UUIDs from a context use the 12 bits after the version as a counter
+// within a millisecond, which starts randomly in its lower half.
+static V7_COUNTER_MAX: u16 = 0xfff;
+
+impl UuidContext {
+    /// Creates a context for V1 UUIDs with the given node ID, normally the
+    /// MAC address of one of the host's network interfaces, and a random
+    /// clock sequence
+    pub fn new(node: [u8, ..6]) -> UuidContext {
+        UuidContext {
+            node: node,
+            clock_seq: rand::task_rng().gen::<u16>() & 0x3fff,
+            last_ticks: 0,
+            last_millis: 0,
+            counter: 0,
+        }
+    }
+
+    /// Creates a context with a random node ID
+    ///
+    /// As RFC4122 requires, the multicast bit of the node ID is set so that
+    /// it can't be mistaken for a real MAC address.
+    pub fn with_random_node() -> UuidContext {
+        let mut node: [u8, ..6] = [0, ..6];
+        rand::task_rng().fill_bytes(node);
+        node[0] |= 0x01;
+        UuidContext::new(node)
+    }
+
+    /// Returns the node ID of the context
+    pub fn get_node<'a>(&'a self) -> &'a [u8] {
+        self.node.as_slice()
+    }
+
+    /// Returns the clock sequence of the context
+    pub fn get_clock_seq(&self) -> u16 {
+        self.clock_seq
+    }
+
+    /// Sets the clock sequence, of which only the low 14 bits are used
+    ///
+    /// An application which stores the clock sequence between runs should
+    /// restore it with this, and increment it if the clock may have been
+    /// set back or the node ID has changed.
+    pub fn set_clock_seq(&mut self, clock_seq: u16) {
+        self.clock_seq = clock_seq & 0x3fff;
+    }
+
+    /// Creates a new V1 UUID from the current time
+    pub fn new_v1(&mut self) -> Uuid {
+        self.new_v1_at(gregorian_ticks())
+    }
+
+    /// Creates a new V1 UUID with the given timestamp, in 100ns intervals
+    /// since the start of the Gregorian calendar on 1582-10-15
+    ///
+    /// If the timestamp isn't later than the one last used by this context,
+    /// the one after that is used instead.
+    pub fn new_v1_at(&mut self, ticks: u64) -> Uuid {
+        let ticks = if ticks > self.last_ticks { ticks } else { self.last_ticks + 1 };
+        self.last_ticks = ticks;
+
+        let mut uuid = Uuid{ bytes: [0, .. 16] };
+        uuid.bytes[0] = (ticks >> 24) as u8;
+        uuid.bytes[1] = (ticks >> 16) as u8;
+        uuid.bytes[2] = (ticks >> 8) as u8;
+        uuid.bytes[3] = ticks as u8;
+        uuid.bytes[4] = (ticks >> 40) as u8;
+        uuid.bytes[5] = (ticks >> 32) as u8;
+        uuid.bytes[6] = (ticks >> 56) as u8;
+        uuid.bytes[7] = (ticks >> 48) as u8;
+        uuid.bytes[8] = (self.clock_seq >> 8) as u8;
+        uuid.bytes[9] = self.clock_seq as u8;
+        slice::bytes::copy_memory(uuid.bytes.mut_slice_from(10), self.node);
+        uuid.set_variant(VariantRFC4122);
+        uuid.set_version(Version1Mac);
+        uuid
+    }
+
+    /// Creates a new V7 UUID from the current time, which is greater than
+    /// any V7 UUID previously created by this context
+    pub fn new_v7(&mut self) -> Uuid {
+        self.new_v7_at(unix_millis())
+    }
+
+    fn new_v7_at(&mut self, millis: u64) -> Uuid {
+        let mut rng = rand::task_rng();
+        if millis > self.last_millis {
+            self.last_millis = millis;
+            self.counter = rng.gen::<u16>() & (V7_COUNTER_MAX >> 1);
+        } else if self.counter < V7_COUNTER_MAX {
+            self.counter += 1;
+        } else {
+            // Out of counter values, so borrow from the next millisecond
+            self.last_millis += 1;
+            self.counter = 0;
+        }
+        Uuid::v7_from_parts(self.last_millis, (self.counter, rng.gen()))
+    }
+}
+
 impl Default for Uuid {
     /// Returns the nil UUID, which is all zeroes
     fn default() -> Uuid {
@@ -522,9 +826,11 @@ mod test {
     extern crate collections;
     extern crate rand;
 
-    use super::{Uuid, VariantMicrosoft, VariantNCS, VariantRFC4122,
+    use super::{Uuid, UuidContext, VariantMicrosoft, VariantNCS, VariantRFC4122,
                 Version1Mac, Version2Dce, Version3Md5, Version4Random,
-                Version5Sha1};
+                Version5Sha1, Version7UnixTime, NAMESPACE_DNS, NAMESPACE_URL,
+                NAMESPACE_OID, NAMESPACE_X500};
+    use time;
     use std::str;
     use std::io::MemWriter;
 
@@ -546,13 +852,116 @@ mod test {
         assert!(s.len() == 32);
         assert!(uuid1.get_version().unwrap() == Version4Random);
 
+        let uuid2 = Uuid::new(Version1Mac).unwrap();
+        assert!(uuid2.get_version().unwrap() == Version1Mac);
+        let uuid3 = Uuid::new(Version7UnixTime).unwrap();
+        assert!(uuid3.get_version().unwrap() == Version7UnixTime);
+
         // Test unsupported versions
-        assert!(Uuid::new(Version1Mac) == None);
         assert!(Uuid::new(Version2Dce) == None);
         assert!(Uuid::new(Version3Md5) == None);
         assert!(Uuid::new(Version5Sha1) == None);
     }
 
+    #[test]
+    fn test_new_v3_v5() {
+        let uuid1 = Uuid::new_v3(&NAMESPACE_DNS, "www.example.com".as_bytes());
+        assert!(uuid1.get_version().unwrap() == Version3Md5);
+        assert!(uuid1.get_variant().unwrap() == VariantRFC4122);
+        assert_eq!(uuid1.to_hyphenated_str(),
+                   "5df41881-3aed-3515-88a7-2f4a814cf09e".to_owned());
+
+        let uuid2 = Uuid::new_v5(&NAMESPACE_DNS, "www.example.com".as_bytes());
+        assert!(uuid2.get_version().unwrap() == Version5Sha1);
+        assert!(uuid2.get_variant().unwrap() == VariantRFC4122);
+        assert_eq!(uuid2.to_hyphenated_str(),
+                   "2ed6657d-e927-568b-95e1-2665a8aea6a2".to_owned());
+
+        let url = "http://www.rust-lang.org/".as_bytes();
+        assert_eq!(Uuid::new_v3(&NAMESPACE_URL, url).to_hyphenated_str(),
+                   "b49ab6e9-67b8-3017-af61-46a287c4d449".to_owned());
+        assert_eq!(Uuid::new_v5(&NAMESPACE_URL, url).to_hyphenated_str(),
+                   "9ecb1d89-d169-5cec-8e41-21218da583d5".to_owned());
+
+        assert!(Uuid::new_v5(&NAMESPACE_DNS, url) == Uuid::new_v5(&NAMESPACE_DNS, url));
+        assert!(Uuid::new_v5(&NAMESPACE_DNS, url) != Uuid::new_v5(&NAMESPACE_URL, url));
+        assert_eq!(NAMESPACE_OID.to_hyphenated_str(),
+                   "6ba7b812-9dad-11d1-80b4-00c04fd430c8".to_owned());
+        assert_eq!(NAMESPACE_X500.to_hyphenated_str(),
+                   "6ba7b814-9dad-11d1-80b4-00c04fd430c8".to_owned());
+    }
+
+    #[test]
+    fn test_new_v1() {
+        let mut ctx = UuidContext::new([1, 2, 3, 4, 5, 6]);
+        ctx.set_clock_seq(0x1234);
+        assert_eq!(ctx.get_clock_seq(), 0x1234);
+
+        let uuid1 = ctx.new_v1_at(0x1e4a3b5c6d7e8f9);
+        assert!(uuid1.get_version().unwrap() == Version1Mac);
+        assert!(uuid1.get_variant().unwrap() == VariantRFC4122);
+        assert_eq!(uuid1.to_hyphenated_str(),
+                   "c6d7e8f9-a3b5-11e4-9234-010203040506".to_owned());
+        let ts = uuid1.get_timestamp().unwrap();
+        assert_eq!((ts.sec, ts.nsec), (1422096184, 840421700));
+
+        // The clock not advancing doesn't give duplicates
+        let uuid2 = ctx.new_v1_at(0x1e4a3b5c6d7e8f9);
+        assert!(uuid1 != uuid2);
+        assert_eq!(uuid2.to_hyphenated_str(),
+                   "c6d7e8fa-a3b5-11e4-9234-010203040506".to_owned());
+
+        // The start of the Gregorian calendar
+        let uuid3 = UuidContext::new([0, ..6]).new_v1_at(1);
+        let ts = uuid3.get_timestamp().unwrap();
+        assert_eq!((ts.sec, ts.nsec), (-12219292800, 100));
+
+        let before = time::get_time();
+        let uuid4 = Uuid::new_v1();
+        let after = time::get_time();
+        let ts = uuid4.get_timestamp().unwrap();
+        assert!(before.sec <= ts.sec && ts.sec <= after.sec);
+        assert!(uuid4.as_bytes()[10] & 0x01 == 0x01);
+        assert!(Uuid::new_v4().get_timestamp().is_none());
+    }
+
+    #[test]
+    fn test_new_v7() {
+        let before = time::get_time();
+        let uuid1 = Uuid::new_v7();
+        let after = time::get_time();
+        assert!(uuid1.get_version().unwrap() == Version7UnixTime);
+        assert!(uuid1.get_variant().unwrap() == VariantRFC4122);
+        let ts = uuid1.get_timestamp().unwrap();
+        assert!(before.sec <= ts.sec && ts.sec <= after.sec);
+
+        let uuid2 = Uuid::v7_from_parts(1422096184840, (0, 0));
+        assert_eq!(uuid2.to_hyphenated_str(),
+                   "014b1b89-b608-7000-8000-000000000000".to_owned());
+        let ts = uuid2.get_timestamp().unwrap();
+        assert_eq!((ts.sec, ts.nsec), (1422096184, 840000000));
+    }
+
+    #[test]
+    fn test_context_v7_increasing() {
+        let mut ctx = UuidContext::with_random_node();
+        let mut last = ctx.new_v7_at(1000).to_hyphenated_str();
+        // Enough UUIDs in the same millisecond to run out of counter values
+        for _ in range(0, 5000) {
+            let next = ctx.new_v7_at(1000).to_hyphenated_str();
+            assert!(next > last);
+            last = next;
+        }
+        let uuid = Uuid::parse_string(last).unwrap();
+        assert!(uuid.get_timestamp().unwrap().nsec > 0);
+
+        for _ in range(0, 100) {
+            let next = ctx.new_v7().to_hyphenated_str();
+            assert!(next > last);
+            last = next;
+        }
+    }
+
     #[test]
     fn test_new_v4() {
         let uuid1 = Uuid::new_v4();
@@ -819,7 +1228,7 @@ mod test {
 mod bench {
     extern crate test;
     use self::test::Bencher;
-    use super::Uuid;
+    use super::{Uuid, NAMESPACE_URL};
 
     #[bench]
     pub fn create_uuids(b: &mut Bencher) {
@@ -828,6 +1237,14 @@ mod bench {
         })
     }
 
+    #[bench]
+    pub fn create_uuids_v5(b: &mut Bencher) {
+        let name = "http://www.rust-lang.org/".as_bytes();
+        b.iter(|| {
+            Uuid::new_v5(&NAMESPACE_URL, name);
+        })
+    }
+
     #[bench]
     pub fn uuid_to_str(b: &mut Bencher) {
         let u = Uuid::new_v4();
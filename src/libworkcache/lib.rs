@@ -22,16 +22,18 @@
 #[phase(syntax, link)] extern crate log;
 extern crate serialize;
 extern crate collections;
+extern crate crypto;
 extern crate sync;
 
+use crypto::Digest;
+use crypto::sha2::Sha256;
 use serialize::json;
-use serialize::json::ToJson;
 use serialize::{Encoder, Encodable, Decoder, Decodable};
 use sync::{Arc, RWLock};
 use collections::TreeMap;
 use std::str;
 use std::io;
-use std::io::{File, MemWriter};
+use std::io::{fs, File, MemWriter, TempDir};
 
 /**
 *
@@ -54,7 +56,7 @@ use std::io::{File, MemWriter};
 *    kind   name    value
 *   ------------------------
 *    cfg    os      linux
-*    file   foo.c   <sha1>
+*    file   foo.c   <sha256>
 *    url    foo.com <etag>
 *
 * Works are conceptually single units, but we store them most of the time
@@ -92,6 +94,11 @@ use std::io::{File, MemWriter};
 * (fn_name,{declared_input}) => ({discovered_input},
 *                                {discovered_output},result)
 *
+* The key is stored as a SHA-256 hash. Files are usually recorded by the
+* hash of their contents (see `Prep::declare_file`), so the key changes
+* whenever a declared input file does, and going back to an earlier version
+* of a file finds the results computed from it again.
+*
 * (Note: fbuild, which workcache is based on, has the concept of a declared
 * output as separate from a discovered output. This distinction exists only
 * as an artifact of how fbuild works: via annotations on function types
@@ -140,10 +147,32 @@ impl WorkMap {
     }
 }
 
+// One record in the database log.
+#[deriving(Clone, Encodable, Decodable)]
+struct Entry {
+    key: ~str,
+    fn_name: ~str,
+    declared_inputs: WorkMap,
+    discovered_inputs: WorkMap,
+    discovered_outputs: WorkMap,
+    result: ~str,
+}
+
+/// The persistent store of a workcache.
+///
+/// Entries are appended to a log file as one line of JSON each, and are
+/// never modified in place, so several processes can share a database: each
+/// entry is written with a single append, and every lookup first reads
+/// whatever other processes have appended since. When a key appears more
+/// than once the last entry wins. A line left incomplete by a writer that
+/// crashed is skipped.
 pub struct Database {
     db_filename: Path,
-    db_cache: TreeMap<~str, ~str>,
-    pub db_dirty: bool,
+    db_cache: TreeMap<~str, Entry>,
+    // How much of the log has been read into db_cache, and the inode it was
+    // read from, so that a log replaced by garbage collection is reread.
+    db_offset: u64,
+    db_inode: u64,
 }
 
 impl Database {
@@ -152,22 +181,23 @@ impl Database {
         let mut rslt = Database {
             db_filename: p,
             db_cache: TreeMap::new(),
-            db_dirty: false
+            db_offset: 0,
+            db_inode: 0,
         };
-        if rslt.db_filename.exists() {
-            rslt.load();
-        }
+        rslt.load();
         rslt
     }
 
-    pub fn prepare(&self,
+    pub fn prepare(&mut self,
                    fn_name: &str,
                    declared_inputs: &WorkMap)
                    -> Option<(WorkMap, WorkMap, ~str)> {
-        let k = json_encode(&(fn_name, declared_inputs));
-        match self.db_cache.find(&k) {
+        self.load();
+        match self.db_cache.find(&entry_key(fn_name, declared_inputs)) {
             None => None,
-            Some(v) => Some(json_decode(*v))
+            Some(e) => Some((e.discovered_inputs.clone(),
+                             e.discovered_outputs.clone(),
+                             e.result.clone()))
         }
     }
 
@@ -177,48 +207,112 @@ impl Database {
                  discovered_inputs: &WorkMap,
                  discovered_outputs: &WorkMap,
                  result: &str) {
-        let k = json_encode(&(fn_name, declared_inputs));
-        let v = json_encode(&(discovered_inputs,
-                              discovered_outputs,
-                              result));
-        self.db_cache.insert(k,v);
-        self.db_dirty = true
-    }
-
-    // FIXME #4330: This should have &mut self and should set self.db_dirty to false.
-    fn save(&self) -> io::IoResult<()> {
-        let mut f = File::create(&self.db_filename);
-        self.db_cache.to_json().to_pretty_writer(&mut f)
+        let entry = Entry {
+            key: entry_key(fn_name, declared_inputs),
+            fn_name: fn_name.to_owned(),
+            declared_inputs: declared_inputs.clone(),
+            discovered_inputs: discovered_inputs.clone(),
+            discovered_outputs: discovered_outputs.clone(),
+            result: result.to_owned(),
+        };
+        // The leading newline ends any line left incomplete by a writer that
+        // crashed, so that this entry can still be read.
+        let line = format!("\n{}\n", json_encode(&entry));
+        let written = match File::open_mode(&self.db_filename, io::Append, io::Write) {
+            Ok(mut f) => f.write(line.as_bytes()),
+            Err(e) => Err(e)
+        };
+        match written {
+            Err(e) => fail!("Couldn't write workcache database {}: {}",
+                            self.db_filename.display(), e),
+            Ok(()) => {}
+        }
+        self.db_cache.insert(entry.key.clone(), entry);
     }
 
     fn load(&mut self) {
-        assert!(!self.db_dirty);
-        assert!(self.db_filename.exists());
-        match File::open(&self.db_filename) {
+        match self.read_log() {
             Err(e) => fail!("Couldn't load workcache database {}: {}",
-                            self.db_filename.display(),
-                            e),
-            Ok(mut stream) => {
-                match json::from_reader(&mut stream) {
-                    Err(e) => fail!("Couldn't parse workcache database (from file {}): {}",
-                                    self.db_filename.display(), e.to_str()),
-                    Ok(r) => {
-                        let mut decoder = json::Decoder::new(r);
-                        self.db_cache = Decodable::decode(&mut decoder).unwrap();
-                    }
-                }
+                            self.db_filename.display(), e),
+            Ok(()) => {}
+        }
+    }
+
+    // Read the entries appended to the log since it was last read.
+    fn read_log(&mut self) -> io::IoResult<()> {
+        if !self.db_filename.exists() {
+            return Ok(());
+        }
+        let stat = try!(fs::stat(&self.db_filename));
+        if stat.unstable.inode != self.db_inode || stat.size < self.db_offset {
+            self.db_cache = TreeMap::new();
+            self.db_offset = 0;
+            self.db_inode = stat.unstable.inode;
+        }
+
+        let mut f = try!(File::open(&self.db_filename));
+        try!(f.seek(self.db_offset as i64, io::SeekSet));
+        let data = try!(f.read_to_end());
+
+        // Only read up to the last complete line; the rest may still be
+        // being written, and is read next time.
+        let end = match data.iter().rposition(|&b| b == '\n' as u8) {
+            Some(i) => i + 1,
+            None => return Ok(())
+        };
+        for line in data.slice_to(end).split(|&b| b == '\n' as u8) {
+            if line.is_empty() {
+                continue;
+            }
+            match decode_entry(line) {
+                Some(entry) => { self.db_cache.insert(entry.key.clone(), entry); }
+                None => debug!("skipping malformed line in {}", self.db_filename.display())
             }
         }
+        self.db_offset += end as u64;
+        Ok(())
     }
-}
 
-#[unsafe_destructor]
-impl Drop for Database {
-    fn drop(&mut self) {
-        if self.db_dirty {
-            // FIXME: is failing the right thing to do here
-            self.save().unwrap();
+    // Rewrite the log with only the newest entry for each key that `keep`
+    // accepts, returning how many entries were dropped. Entries other
+    // processes append while this runs may be lost, which only costs them a
+    // rebuild.
+    fn gc(&mut self, keep: |&Entry| -> bool) -> io::IoResult<uint> {
+        try!(self.read_log());
+
+        let mut stale = Vec::new();
+        for (k, e) in self.db_cache.iter() {
+            if !keep(e) {
+                stale.push(k.clone());
+            }
+        }
+        for k in stale.iter() {
+            self.db_cache.remove(k);
+        }
+
+        // Write the new log next to the old one, so that it can be renamed
+        // over it.
+        let tmp = match TempDir::new_in(&self.db_filename.dir_path(), "workcache") {
+            Some(tmp) => tmp,
+            None => return Err(io::IoError {
+                kind: io::OtherIoError,
+                desc: "couldn't create a temporary directory",
+                detail: None
+            })
+        };
+        let log = tmp.path().join("log");
+        {
+            let mut f = try!(File::create(&log));
+            for (_, e) in self.db_cache.iter() {
+                try!(f.write_line(json_encode(e)));
+            }
         }
+        try!(fs::rename(&log, &self.db_filename));
+
+        let stat = try!(fs::stat(&self.db_filename));
+        self.db_offset = stat.size;
+        self.db_inode = stat.unstable.inode;
+        Ok(stale.len())
     }
 }
 
@@ -248,7 +342,9 @@ pub struct Exec {
     discovered_outputs: WorkMap
 }
 
-enum Work<'a, T> {
+/// The result of `Prep::exec_work`: either a value found in the cache, or a
+/// task computing one. `unwrap` waits for the task and caches its result.
+pub enum Work<'a, T> {
     WorkValue(T),
     WorkFromTask(&'a Prep<'a>, Receiver<(Exec, T)>),
 }
@@ -268,6 +364,49 @@ fn json_decode<T:Decodable<json::Decoder, json::Error>>(s: &str) -> T {
     Decodable::decode(&mut decoder).unwrap()
 }
 
+fn decode_entry(line: &[u8]) -> Option<Entry> {
+    let s = match str::from_utf8(line) {
+        Some(s) => s,
+        None => return None
+    };
+    let j = match json::from_str(s) {
+        Ok(j) => j,
+        Err(_) => return None
+    };
+    let mut decoder = json::Decoder::new(j);
+    Decodable::decode(&mut decoder).ok()
+}
+
+fn entry_key(fn_name: &str, declared_inputs: &WorkMap) -> ~str {
+    let mut hasher = Sha256::new();
+    hasher.input_str(json_encode(&(fn_name, declared_inputs)));
+    hasher.result_str()
+}
+
+/// Returns the SHA-256 hash of the contents of the file at `p`, in
+/// hexadecimal. This is the value that `file` works are recorded with.
+pub fn hash_file(p: &Path) -> io::IoResult<~str> {
+    let mut f = try!(File::open(p));
+    let mut hasher = Sha256::new();
+    try!(io::util::copy(&mut f, &mut hasher));
+    Ok(hasher.result_str())
+}
+
+fn hash_work_file(p: &Path) -> ~str {
+    match hash_file(p) {
+        Ok(hash) => hash,
+        Err(e) => fail!("Couldn't hash workcache file {}: {}", p.display(), e)
+    }
+}
+
+// The freshness function for `file` works, unless the context has its own.
+fn file_is_fresh(name: &str, val: &str) -> bool {
+    match hash_file(&Path::new(name)) {
+        Ok(hash) => hash.as_slice() == val,
+        Err(_) => false
+    }
+}
+
 impl Context {
 
     pub fn new(db: Arc<RWLock<Database>>,
@@ -299,6 +438,41 @@ impl Context {
         blk(&mut p)
     }
 
+    /// Removes the database entries that can no longer be used, because one
+    /// of their works is not fresh any more, and compacts the database.
+    /// Works of a kind with no freshness function are assumed to be fresh.
+    /// Returns the number of entries removed.
+    pub fn gc(&self) -> io::IoResult<uint> {
+        let mut db = self.db.deref().write();
+        db.deref_mut().gc(|e| {
+            self.all_fresh(&e.declared_inputs) &&
+            self.all_fresh(&e.discovered_inputs) &&
+            self.all_fresh(&e.discovered_outputs)
+        })
+    }
+
+    fn all_fresh(&self, map: &WorkMap) -> bool {
+        let WorkMap(ref map) = *map;
+        for (k_name, kindmap) in map.iter() {
+            let KindMap(ref kindmap_) = *kindmap;
+            for (k_kind, v) in kindmap_.iter() {
+                if self.freshness_of(*k_kind, *k_name, *v) == Some(false) {
+                    return false;
+                }
+            }
+        }
+        return true;
+    }
+
+    // Whether a work is fresh, or None if there's no freshness function for
+    // its kind.
+    fn freshness_of(&self, kind: &str, name: &str, val: &str) -> Option<bool> {
+        match self.freshness.deref().find(&kind.to_owned()) {
+            Some(f) => Some((*f)(name, val)),
+            None if kind == "file" => Some(file_is_fresh(name, val)),
+            None => None
+        }
+    }
 }
 
 impl Exec {
@@ -319,6 +493,21 @@ impl Exec {
                                  dependency_val.to_owned());
     }
 
+    /// Records the file at `path` as an input, by the hash of its contents.
+    pub fn discover_file_input(&mut self, path: &Path) {
+        let hash = hash_work_file(path);
+        // FIXME (#9639): This needs to handle non-utf8 paths
+        self.discover_input("file", path.as_str().unwrap(), hash);
+    }
+
+    /// Records the file at `path` as an output, by the hash of its contents.
+    /// The file must already have been written.
+    pub fn discover_file_output(&mut self, path: &Path) {
+        let hash = hash_work_file(path);
+        // FIXME (#9639): This needs to handle non-utf8 paths
+        self.discover_output("file", path.as_str().unwrap(), hash);
+    }
+
     // returns pairs of (kind, name)
     pub fn lookup_discovered_inputs(&self) -> Vec<(~str, ~str)> {
         let mut rs = vec![];
@@ -362,14 +551,21 @@ impl<'a> Prep<'a> {
                                  val.to_owned());
     }
 
+    /// Declares the file at `path` as an input, by the hash of its contents.
+    /// Unless the context has its own freshness function for `file` works,
+    /// it stays fresh for as long as the contents are unchanged.
+    pub fn declare_file(&mut self, path: &Path) {
+        let hash = hash_work_file(path);
+        // FIXME (#9639): This needs to handle non-utf8 paths
+        self.declare_input("file", path.as_str().unwrap(), hash);
+    }
+
     fn is_fresh(&self, cat: &str, kind: &str,
                 name: &str, val: &str) -> bool {
-        let k = kind.to_owned();
-        let f = self.ctxt.freshness.deref().find(&k);
         debug!("freshness for: {}/{}/{}/{}", cat, kind, name, val)
-        let fresh = match f {
+        let fresh = match self.ctxt.freshness_of(kind, name, val) {
             None => fail!("missing freshness-function for '{}'", kind),
-            Some(f) => (*f)(name, val)
+            Some(fresh) => fresh
         };
         if fresh {
             info!("{} {}:{} is fresh", cat, kind, name);
@@ -399,7 +595,10 @@ impl<'a> Prep<'a> {
         self.exec_work(blk).unwrap()
     }
 
-    fn exec_work<'a, T:Send +
+    /// Like `exec`, but doesn't wait for `blk` when it has to be run. The
+    /// work of independent preps proceeds in parallel if they are all
+    /// started before any of their results are unwrapped.
+    pub fn exec_work<'a, T:Send +
         Encodable<json::Encoder<'a>, io::IoError> +
         Decodable<json::Decoder, json::Error>>( // FIXME(#5121)
             &'a self, blk: proc(&mut Exec):Send -> T) -> Work<'a, T> {
@@ -408,8 +607,8 @@ impl<'a> Prep<'a> {
         debug!("exec_work: looking up {} and {:?}", self.fn_name,
                self.declared_inputs);
         let cached = {
-            let db = self.ctxt.db.deref().read();
-            db.deref().prepare(self.fn_name, &self.declared_inputs)
+            let mut db = self.ctxt.db.deref().write();
+            db.deref_mut().prepare(self.fn_name, &self.declared_inputs)
         };
 
         match cached {
@@ -479,8 +678,7 @@ impl<'a, T:Send +
 #[cfg(not(target_os="android"))] // FIXME(#10455)
 fn test() {
     use std::os;
-    use std::io::{fs, Process};
-    use std::str::from_utf8;
+    use std::io::Process;
 
     // Create a path to a new file 'filename' in the directory in which
     // this test is running.
@@ -505,18 +703,15 @@ fn test() {
         let subcx = cx.clone();
         let pth = pth.clone();
 
-        let contents = File::open(&pth).read_to_end().unwrap();
-        let file_content = from_utf8(contents.as_slice()).unwrap().to_owned();
-
-        // FIXME (#9639): This needs to handle non-utf8 paths
-        prep.declare_input("file", pth.as_str().unwrap(), file_content);
-        prep.exec(proc(_exe) {
+        prep.declare_file(&pth);
+        prep.exec(proc(exe) {
             let out = make_path("foo.o".to_owned());
             let compiler = if cfg!(windows) {"gcc"} else {"cc"};
             // FIXME (#9639): This needs to handle non-utf8 paths
             Process::status(compiler, [pth.as_str().unwrap().to_owned(),
                                     "-o".to_owned(),
                                     out.as_str().unwrap().to_owned()]).unwrap();
+            exe.discover_file_output(&out);
 
             let _proof_of_concept = subcx.prep("subfn");
             // Could run sub-rules inside here.
@@ -528,3 +723,108 @@ fn test() {
 
     println!("{}", s);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Context, Database};
+    use collections::TreeMap;
+    use sync::{Arc, RWLock};
+    use std::io::{File, TempDir};
+
+    fn context(db: &Path) -> Context {
+        Context::new(Arc::new(RWLock::new(Database::new(db.clone()))),
+                     Arc::new(TreeMap::new()))
+    }
+
+    // Returns the cached result of building `src`, or `result` if it isn't
+    // cached.
+    fn build(cx: &Context, src: &Path, result: &str) -> ~str {
+        let mut prep = cx.prep("build");
+        prep.declare_file(src);
+        let result = result.to_owned();
+        prep.exec(proc(_exe) result)
+    }
+
+    #[test]
+    fn test_file_contents() {
+        let dir = TempDir::new("workcache").unwrap();
+        let src = dir.path().join("a.c");
+        let cx = context(&dir.path().join("db"));
+
+        File::create(&src).write(bytes!("one")).unwrap();
+        assert_eq!(build(&cx, &src, "first"), "first".to_owned());
+        assert_eq!(build(&cx, &src, "second"), "first".to_owned());
+
+        File::create(&src).write(bytes!("two")).unwrap();
+        assert_eq!(build(&cx, &src, "third"), "third".to_owned());
+
+        // Rewriting the file as it was finds the earlier result again
+        File::create(&src).write(bytes!("one")).unwrap();
+        assert_eq!(build(&cx, &src, "fourth"), "first".to_owned());
+    }
+
+    #[test]
+    fn test_shared_database() {
+        let dir = TempDir::new("workcache").unwrap();
+        let src = dir.path().join("a.c");
+        let db = dir.path().join("db");
+        File::create(&src).write(bytes!("one")).unwrap();
+
+        // Two databases on the same file, as if in different processes
+        let cx1 = context(&db);
+        let cx2 = context(&db);
+        assert_eq!(build(&cx1, &src, "first"), "first".to_owned());
+        assert_eq!(build(&cx2, &src, "second"), "first".to_owned());
+    }
+
+    #[test]
+    fn test_incomplete_line() {
+        let dir = TempDir::new("workcache").unwrap();
+        let src = dir.path().join("a.c");
+        let db = dir.path().join("db");
+        File::create(&src).write(bytes!("one")).unwrap();
+        File::create(&db).write(bytes!("{\"key\": \"012")).unwrap();
+
+        assert_eq!(build(&context(&db), &src, "first"), "first".to_owned());
+        assert_eq!(build(&context(&db), &src, "second"), "first".to_owned());
+    }
+
+    #[test]
+    fn test_gc() {
+        let dir = TempDir::new("workcache").unwrap();
+        let src = dir.path().join("a.c");
+        let db = dir.path().join("db");
+        let cx = context(&db);
+
+        File::create(&src).write(bytes!("one")).unwrap();
+        build(&cx, &src, "first");
+        File::create(&src).write(bytes!("two")).unwrap();
+        build(&cx, &src, "second");
+        build(&cx, &src, "second");
+
+        assert_eq!(cx.gc().unwrap(), 1);
+        assert_eq!(cx.gc().unwrap(), 0);
+        assert_eq!(build(&cx, &src, "third"), "second".to_owned());
+        assert_eq!(build(&context(&db), &src, "third"), "second".to_owned());
+
+        File::create(&src).write(bytes!("one")).unwrap();
+        assert_eq!(build(&cx, &src, "fourth"), "fourth".to_owned());
+    }
+
+    #[test]
+    fn test_parallel_preps() {
+        let dir = TempDir::new("workcache").unwrap();
+        let cx = context(&dir.path().join("db"));
+
+        // `a` can only finish once `b` has started
+        let (tx, rx) = channel();
+        let a = cx.prep("a");
+        let b = cx.prep("b");
+        let wa = a.exec_work(proc(_exe) { rx.recv(); "a".to_owned() });
+        let wb = b.exec_work(proc(_exe) { tx.send(()); "b".to_owned() });
+        assert_eq!(wa.unwrap(), "a".to_owned());
+        assert_eq!(wb.unwrap(), "b".to_owned());
+
+        assert_eq!(cx.prep("a").exec(proc(_exe) "c".to_owned()), "a".to_owned());
+    }
+}
@@ -188,6 +188,12 @@ typedef enum
 // This is a universal API, i.e. it can be used as a building block to build any desired higher level decompression API. In the limit case, it can be called once per every byte input or output.
 tinfl_status tinfl_decompress(tinfl_decompressor *r, const mz_uint8 *pIn_buf_next, size_t *pIn_buf_size, mz_uint8 *pOut_buf_start, mz_uint8 *pOut_buf_next, size_t *pOut_buf_size, const mz_uint32 decomp_flags);
 
+// Allocate and initialize a decompressor on the heap, for callers which can't know sizeof(tinfl_decompressor). Returns NULL if out of memory.
+#ifndef MINIZ_NO_MALLOC
+tinfl_decompressor *tinfl_decompressor_alloc(void);
+void tinfl_decompressor_free(tinfl_decompressor *pDecomp);
+#endif
+
 // Internal/private bits follow.
 enum
 {
@@ -347,6 +353,12 @@ tdefl_status tdefl_compress_buffer(tdefl_compressor *d, const void *pIn_buf, siz
 tdefl_status tdefl_get_prev_return_status(tdefl_compressor *d);
 mz_uint32 tdefl_get_adler32(tdefl_compressor *d);
 
+// Allocate a compressor on the heap, for callers which can't know sizeof(tdefl_compressor). It must still be initialized with tdefl_init(). Returns NULL if out of memory.
+#ifndef MINIZ_NO_MALLOC
+tdefl_compressor *tdefl_compressor_alloc(void);
+void tdefl_compressor_free(tdefl_compressor *pComp);
+#endif
+
 #ifdef __cplusplus
 }
 #endif
@@ -754,6 +766,17 @@ tinfl_status tinfl_decompress(tinfl_decompressor *r, const mz_uint8 *pIn_buf_nex
   TINFL_CR_FINISH
 
 common_exit:
+  // Put back any whole bytes the bit buffer read past the end of the stream, so that callers can find data following it (such as a gzip trailer).
+  if (status != TINFL_STATUS_NEEDS_MORE_INPUT)
+  {
+    while ((pIn_buf_cur > pIn_buf_next) && (num_bits >= 8))
+    {
+      --pIn_buf_cur;
+      num_bits -= 8;
+    }
+    if (num_bits < TINFL_BITBUF_SIZE)
+      bit_buf &= (tinfl_bit_buf_t)((((mz_uint64)1) << num_bits) - (mz_uint64)1);
+  }
   r->m_num_bits = num_bits; r->m_bit_buf = bit_buf; r->m_dist = dist; r->m_counter = counter; r->m_num_extra = num_extra; r->m_dist_from_out_buf_start = dist_from_out_buf_start;
   *pIn_buf_size = pIn_buf_cur - pIn_buf_next; *pOut_buf_size = pOut_buf_cur - pOut_buf_next;
   if ((decomp_flags & (TINFL_FLAG_PARSE_ZLIB_HEADER | TINFL_FLAG_COMPUTE_ADLER32)) && (status >= 0))
@@ -775,6 +798,21 @@ common_exit:
   return status;
 }
 
+#ifndef MINIZ_NO_MALLOC
+tinfl_decompressor *tinfl_decompressor_alloc(void)
+{
+  tinfl_decompressor *pDecomp = (tinfl_decompressor*)MZ_MALLOC(sizeof(tinfl_decompressor));
+  if (pDecomp)
+    tinfl_init(pDecomp);
+  return pDecomp;
+}
+
+void tinfl_decompressor_free(tinfl_decompressor *pDecomp)
+{
+  MZ_FREE(pDecomp);
+}
+#endif
+
 // Higher level helper functions.
 void *tinfl_decompress_mem_to_heap(const void *pSrc_buf, size_t src_buf_len, size_t *pOut_len, int flags)
 {
@@ -1801,6 +1839,18 @@ mz_uint32 tdefl_get_adler32(tdefl_compressor *d)
   return d->m_adler32;
 }
 
+#ifndef MINIZ_NO_MALLOC
+tdefl_compressor *tdefl_compressor_alloc(void)
+{
+  return (tdefl_compressor*)MZ_MALLOC(sizeof(tdefl_compressor));
+}
+
+void tdefl_compressor_free(tdefl_compressor *pComp)
+{
+  MZ_FREE(pComp);
+}
+#endif
+
 mz_bool tdefl_compress_mem_to_output(const void *pBuf, size_t buf_len, tdefl_put_buf_func_ptr pPut_buf_func, void *pPut_buf_user, int flags)
 {
   tdefl_compressor *pComp; mz_bool succeeded; if (((buf_len) && (!pBuf)) || (!pPut_buf_func)) return MZ_FALSE;